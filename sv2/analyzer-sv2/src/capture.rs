@@ -0,0 +1,97 @@
+use std::{fmt, time::Duration};
+
+use crate::error::Error;
+
+/// Direction of a stream within a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Bytes sent by the endpoint that opened the connection, i.e. the Noise initiator.
+    ClientToServer,
+    /// Bytes sent by the endpoint that accepted the connection, i.e. the Noise responder.
+    ServerToClient,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::ClientToServer => write!(f, "client->server"),
+            Direction::ServerToClient => write!(f, "server->client"),
+        }
+    }
+}
+
+/// Bytes of a stream received at once, e.g. the payload of a TCP segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    /// Capture time, since the Unix epoch. `None` for hex dumps and raw inputs.
+    pub timestamp: Option<Duration>,
+    pub data: Vec<u8>,
+}
+
+/// One direction of a connection, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stream {
+    /// Identifies the stream in the timeline, e.g. `10.0.0.2:51234 -> 10.0.0.1:3333`.
+    pub label: String,
+    pub direction: Direction,
+    pub segments: Vec<Segment>,
+}
+
+impl Stream {
+    /// Builds a stream from a hex dump or a raw input, with no timestamp.
+    pub fn from_bytes(label: String, direction: Direction, data: Vec<u8>) -> Self {
+        Self {
+            label,
+            direction,
+            segments: vec![Segment {
+                timestamp: None,
+                data,
+            }],
+        }
+    }
+}
+
+/// Decodes a hex dump.
+///
+/// Whitespace, `:` and `,` separators and `0x` prefixes are ignored, as well as anything following
+/// a `#` on a line.
+pub fn parse_hex(text: &str) -> Result<Vec<u8>, Error> {
+    let mut digits = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        for word in line.split(|c: char| c.is_whitespace() || c == ':' || c == ',') {
+            let word = word
+                .strip_prefix("0x")
+                .or_else(|| word.strip_prefix("0X"))
+                .unwrap_or(word);
+            for c in word.chars() {
+                digits.push(c.to_digit(16).ok_or(Error::InvalidHex(i + 1))? as u8);
+            }
+        }
+    }
+    if digits.len() % 2 != 0 {
+        return Err(Error::OddHexLength);
+    }
+    Ok(digits
+        .chunks(2)
+        .map(|pair| (pair[0] << 4) | pair[1])
+        .collect())
+}
+
+/// Returns `true` if the input looks like a hex dump rather than raw bytes.
+pub fn is_hex_dump(input: &[u8]) -> bool {
+    std::str::from_utf8(input).is_ok_and(|text| parse_hex(text).is_ok())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hex_dump_separators_and_comments() {
+        let text = "# SetupConnection\n00 00 00 06:00:00 # header\n0x0102,0XfF\n";
+        assert_eq!(parse_hex(text).unwrap(), vec![0, 0, 0, 6, 0, 0, 1, 2, 0xff]);
+        assert!(matches!(parse_hex("00 0g"), Err(Error::InvalidHex(1))));
+        assert!(matches!(parse_hex("000"), Err(Error::OddHexLength)));
+    }
+}
@@ -0,0 +1,38 @@
+use std::fmt;
+
+/// Errors preventing a capture from being analyzed.
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    /// The hex dump has an invalid character, at the given line.
+    InvalidHex(usize),
+    /// The hex dump has an odd number of digits.
+    OddHexLength,
+    /// The pcap or pcapng file is truncated or malformed.
+    InvalidCapture(&'static str),
+    /// The capture uses a link-layer type this tool can't decode.
+    UnsupportedLinkType(u32),
+    /// A command-line argument is missing or invalid.
+    InvalidArgument(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{e}"),
+            Error::InvalidHex(line) => write!(f, "invalid hex dump at line {line}"),
+            Error::OddHexLength => write!(f, "hex dump has an odd number of digits"),
+            Error::InvalidCapture(reason) => write!(f, "invalid capture: {reason}"),
+            Error::UnsupportedLinkType(link_type) => {
+                write!(f, "unsupported link-layer type {link_type}")
+            }
+            Error::InvalidArgument(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
@@ -0,0 +1,238 @@
+use std::{fmt, time::Duration};
+
+use framing_sv2::{header::Header, ENCRYPTED_SV2_FRAME_HEADER_SIZE, SV2_FRAME_CHUNK_SIZE};
+use noise_sv2::NoiseCodec;
+
+use crate::capture::Stream;
+
+/// A Sv2 frame read from a stream, decrypted if the stream is encrypted.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// Capture time of the last byte of the frame, if known.
+    pub timestamp: Option<Duration>,
+    /// Position of the frame in the stream.
+    pub offset: usize,
+    pub header: Header,
+    pub payload: Vec<u8>,
+}
+
+/// Errors ending the reading of a stream, as frame boundaries are lost from there on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    /// The stream ends in the middle of the frame starting at `offset`.
+    Truncated { offset: usize, missing: usize },
+    /// The frame starting at `offset` could not be decrypted, e.g. because of a wrong key or
+    /// missing bytes earlier in the stream.
+    Decryption { offset: usize },
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::Truncated { offset, missing } => write!(
+                f,
+                "stream ends in the frame at offset {offset}, {missing} bytes missing"
+            ),
+            FrameError::Decryption { offset } => {
+                write!(f, "failed to decrypt the frame at offset {offset}")
+            }
+        }
+    }
+}
+
+/// Reads the frames of a stream in order, decrypting them with a [`NoiseCodec`] if given.
+pub struct FrameReader {
+    data: Vec<u8>,
+    // End offset and timestamp of each segment of the stream.
+    segment_ends: Vec<(usize, Option<Duration>)>,
+    position: usize,
+    noise_codec: Option<NoiseCodec>,
+    failed: bool,
+}
+
+impl FrameReader {
+    /// Reads the frames of `stream` after its first `skip` bytes, e.g. the Noise handshake.
+    pub fn new(stream: &Stream, noise_codec: Option<NoiseCodec>, skip: usize) -> Self {
+        let mut data = Vec::new();
+        let mut segment_ends = Vec::new();
+        for segment in &stream.segments {
+            data.extend_from_slice(&segment.data);
+            segment_ends.push((data.len(), segment.timestamp));
+        }
+        Self {
+            position: skip.min(data.len()),
+            data,
+            segment_ends,
+            noise_codec,
+            failed: false,
+        }
+    }
+
+    // Takes the next `len` bytes of the frame starting at `offset`.
+    fn take(&mut self, offset: usize, len: usize) -> Result<Vec<u8>, FrameError> {
+        let available = self.data.len() - self.position;
+        if available < len {
+            return Err(FrameError::Truncated {
+                offset,
+                missing: len - available,
+            });
+        }
+        self.position += len;
+        Ok(self.data[self.position - len..self.position].to_vec())
+    }
+
+    fn timestamp(&self, end: usize) -> Option<Duration> {
+        let i = self
+            .segment_ends
+            .partition_point(|(segment_end, _)| *segment_end < end);
+        self.segment_ends
+            .get(i)
+            .and_then(|(_, timestamp)| *timestamp)
+    }
+
+    fn read_frame(&mut self) -> Result<(Header, Vec<u8>), FrameError> {
+        let offset = self.position;
+        match self.noise_codec.take() {
+            None => {
+                let header = Header::from_bytes(&self.take(offset, Header::SIZE)?)
+                    .expect("the header has the right size");
+                let payload = self.take(offset, header.len())?;
+                Ok((header, payload))
+            }
+            Some(mut noise_codec) => {
+                let result = self.read_encrypted_frame(offset, &mut noise_codec);
+                self.noise_codec = Some(noise_codec);
+                result
+            }
+        }
+    }
+
+    // Decrypts the header, then the payload chunk by chunk, each chunk having its own MAC.
+    fn read_encrypted_frame(
+        &mut self,
+        offset: usize,
+        noise_codec: &mut NoiseCodec,
+    ) -> Result<(Header, Vec<u8>), FrameError> {
+        let mut header = self.take(offset, ENCRYPTED_SV2_FRAME_HEADER_SIZE)?;
+        noise_codec
+            .decrypt(&mut header)
+            .map_err(|_| FrameError::Decryption { offset })?;
+        let header = Header::from_bytes(&header).expect("the header has the right size");
+
+        let encrypted_payload = self.take(offset, header.encrypted_len())?;
+        let mut payload = Vec::with_capacity(header.len());
+        for chunk in encrypted_payload.chunks(SV2_FRAME_CHUNK_SIZE) {
+            let mut chunk = chunk.to_vec();
+            noise_codec
+                .decrypt(&mut chunk)
+                .map_err(|_| FrameError::Decryption { offset })?;
+            payload.extend_from_slice(&chunk);
+        }
+        Ok((header, payload))
+    }
+}
+
+impl Iterator for FrameReader {
+    type Item = Result<Frame, FrameError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.position == self.data.len() {
+            return None;
+        }
+        let offset = self.position;
+        let frame = self.read_frame().map(|(header, payload)| Frame {
+            timestamp: self.timestamp(self.position),
+            offset,
+            header,
+            payload,
+        });
+        self.failed = frame.is_err();
+        Some(frame)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::capture::{Direction, Segment};
+
+    fn stream(segments: Vec<Vec<u8>>) -> Stream {
+        Stream {
+            label: "test".to_string(),
+            direction: Direction::ClientToServer,
+            segments: segments
+                .into_iter()
+                .enumerate()
+                .map(|(i, data)| Segment {
+                    timestamp: Some(Duration::from_secs(i as u64)),
+                    data,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn frames_across_segments() {
+        let stream = stream(vec![
+            vec![0, 0, 0x18, 2, 0, 0, 1],
+            vec![2, 0x00, 0x80, 0x1c],
+            vec![0, 0, 0, 0, 0],
+        ]);
+        let frames: Vec<_> = FrameReader::new(&stream, None, 0).collect();
+        assert_eq!(frames.len(), 3);
+
+        let frame = frames[0].as_ref().unwrap();
+        assert_eq!(frame.header.msg_type(), 0x18);
+        assert_eq!(frame.payload, vec![1, 2]);
+        assert_eq!(frame.timestamp, Some(Duration::from_secs(1)));
+
+        let frame = frames[1].as_ref().unwrap();
+        assert_eq!(frame.offset, 8);
+        assert!(frame.header.channel_msg());
+        assert_eq!(frame.timestamp, Some(Duration::from_secs(2)));
+
+        assert_eq!(
+            frames[2].as_ref().unwrap_err(),
+            &FrameError::Truncated {
+                offset: 14,
+                missing: 4
+            }
+        );
+    }
+
+    #[test]
+    fn encrypted_frames() {
+        let (client_to_server, server_to_client) = ([1; 32], [2; 32]);
+        let mut client = NoiseCodec::from_transport_keys(client_to_server, server_to_client);
+
+        let mut data = Vec::new();
+        for payload in [vec![], vec![7; 70_000]] {
+            let len = (payload.len() as u32).to_le_bytes();
+            let mut header = vec![0, 0, 0x1b, len[0], len[1], len[2]];
+            client.encrypt(&mut header).unwrap();
+            data.extend_from_slice(&header);
+            for chunk in payload.chunks(SV2_FRAME_CHUNK_SIZE - noise_sv2::AEAD_MAC_LEN) {
+                let mut chunk = chunk.to_vec();
+                client.encrypt(&mut chunk).unwrap();
+                data.extend_from_slice(&chunk);
+            }
+        }
+        let stream = stream(vec![vec![0; 64], data]);
+
+        let server = NoiseCodec::from_transport_keys(server_to_client, client_to_server);
+        let frames: Vec<_> = FrameReader::new(&stream, Some(server), 64)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(frames.len(), 2);
+        assert!(frames[0].payload.is_empty());
+        assert_eq!(frames[1].payload, vec![7; 70_000]);
+
+        let wrong_key = NoiseCodec::from_transport_keys([0; 32], [0; 32]);
+        let frames: Vec<_> = FrameReader::new(&stream, Some(wrong_key), 64).collect();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(
+            frames[0].as_ref().unwrap_err(),
+            &FrameError::Decryption { offset: 64 }
+        );
+    }
+}
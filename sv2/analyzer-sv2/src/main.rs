@@ -0,0 +1,231 @@
+//! # Sv2 Analyzer
+//!
+//! `sv2-analyzer` decodes the Sv2 frames of a captured connection offline, and prints them as a
+//! timeline of named messages.
+//!
+//! The capture can be a hex dump or the raw bytes of one direction of a connection, or a pcap or
+//! pcapng file, of which every TCP stream is decoded. Frames are reassembled from the stream,
+//! decrypted with the Noise session keys if given, and parsed with `parsers_sv2`, including the
+//! TLV fields of the negotiated extensions.
+//!
+//! ```text
+//! sv2-analyzer capture.pcap --port 3333 --channel 1 --extensions 0x0002
+//! sv2-analyzer capture.pcap --handshake --client-key <HEX> --server-key <HEX>
+//! sv2-analyzer frames.hex --direction server
+//! ```
+mod capture;
+mod error;
+mod frames;
+mod pcap;
+mod timeline;
+
+use std::{io::Read, process::ExitCode};
+
+use capture::{Direction, Stream};
+use error::Error;
+use noise_sv2::{NoiseCodec, ELLSWIFT_ENCODING_SIZE, INITIATOR_EXPECTED_HANDSHAKE_MESSAGE_SIZE};
+
+const USAGE: &str = "\
+Usage: sv2-analyzer [OPTIONS] <FILE>
+
+Decodes the Sv2 frames of a captured connection and prints them as a timeline.
+FILE is a hex dump, raw bytes, or a pcap or pcapng capture; `-` reads from stdin.
+
+Options:
+  --format <hex|raw|pcap>      Input format, detected from the content by default
+  --direction <client|server>  Direction of a hex or raw input [default: client]
+  --port <PORT>                Only decode the TCP connections from or to this port
+  --channel <ID>               Only print the messages of this channel; can be repeated
+  --extensions <TYPES>         Comma-separated negotiated extension types, for TLV fields
+  --client-key <HEX>           Noise key of the client-to-server transport frames
+  --server-key <HEX>           Noise key of the server-to-client transport frames
+  --handshake                  Skip the Noise handshake at the start of each stream
+  -h, --help                   Print this help
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Hex,
+    Raw,
+    Pcap,
+}
+
+#[derive(Debug, Default)]
+struct Args {
+    file: Option<String>,
+    format: Option<Format>,
+    direction: Option<Direction>,
+    port: Option<u16>,
+    channels: Vec<u32>,
+    extensions: Vec<u16>,
+    client_key: Option<[u8; 32]>,
+    server_key: Option<[u8; 32]>,
+    handshake: bool,
+}
+
+// Parses a decimal or `0x`-prefixed hexadecimal number.
+fn parse_number<T: TryFrom<u64>>(option: &str, value: &str) -> Result<T, Error> {
+    let number = match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    number
+        .ok()
+        .and_then(|number| T::try_from(number).ok())
+        .ok_or_else(|| Error::InvalidArgument(format!("invalid value for {option}: {value}")))
+}
+
+fn parse_key(option: &str, value: &str) -> Result<[u8; 32], Error> {
+    capture::parse_hex(value)
+        .ok()
+        .and_then(|key| key.try_into().ok())
+        .ok_or_else(|| Error::InvalidArgument(format!("{option} must be 32 bytes in hex")))
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, Error> {
+        let mut parsed = Args::default();
+        while let Some(arg) = args.next() {
+            let mut value = |option: &str| {
+                args.next()
+                    .ok_or_else(|| Error::InvalidArgument(format!("missing value for {option}")))
+            };
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--format" => {
+                    parsed.format = Some(match value(&arg)?.as_str() {
+                        "hex" => Format::Hex,
+                        "raw" => Format::Raw,
+                        "pcap" => Format::Pcap,
+                        format => {
+                            return Err(Error::InvalidArgument(format!("unknown format {format}")))
+                        }
+                    })
+                }
+                "--direction" => {
+                    parsed.direction = Some(match value(&arg)?.as_str() {
+                        "client" => Direction::ClientToServer,
+                        "server" => Direction::ServerToClient,
+                        direction => {
+                            return Err(Error::InvalidArgument(format!(
+                                "unknown direction {direction}"
+                            )))
+                        }
+                    })
+                }
+                "--port" => parsed.port = Some(parse_number(&arg, &value(&arg)?)?),
+                "--channel" => parsed.channels.push(parse_number(&arg, &value(&arg)?)?),
+                "--extensions" => {
+                    for extension in value(&arg)?.split(',') {
+                        parsed
+                            .extensions
+                            .push(parse_number(&arg, extension.trim())?);
+                    }
+                }
+                "--client-key" => parsed.client_key = Some(parse_key(&arg, &value(&arg)?)?),
+                "--server-key" => parsed.server_key = Some(parse_key(&arg, &value(&arg)?)?),
+                "--handshake" => parsed.handshake = true,
+                _ if arg.starts_with("--") => {
+                    return Err(Error::InvalidArgument(format!("unknown option {arg}")))
+                }
+                _ if parsed.file.is_none() => parsed.file = Some(arg),
+                _ => return Err(Error::InvalidArgument(format!("unexpected argument {arg}"))),
+            }
+        }
+        Ok(Some(parsed))
+    }
+}
+
+fn read_input(file: &str) -> Result<Vec<u8>, Error> {
+    match file {
+        "-" => {
+            let mut input = Vec::new();
+            std::io::stdin().read_to_end(&mut input)?;
+            Ok(input)
+        }
+        file => Ok(std::fs::read(file)?),
+    }
+}
+
+fn read_streams(args: &Args) -> Result<Vec<Stream>, Error> {
+    let file = args
+        .file
+        .as_deref()
+        .ok_or_else(|| Error::InvalidArgument("missing input file".to_string()))?;
+    let input = read_input(file)?;
+    let format = args.format.unwrap_or(if pcap::is_capture(&input) {
+        Format::Pcap
+    } else if capture::is_hex_dump(&input) {
+        Format::Hex
+    } else {
+        Format::Raw
+    });
+
+    let direction = args.direction.unwrap_or(Direction::ClientToServer);
+    match format {
+        Format::Pcap => pcap::read_capture(&input, args.port),
+        Format::Hex => {
+            let text = String::from_utf8(input).map_err(|_| Error::InvalidHex(1))?;
+            let data = capture::parse_hex(&text)?;
+            Ok(vec![Stream::from_bytes(
+                direction.to_string(),
+                direction,
+                data,
+            )])
+        }
+        Format::Raw => Ok(vec![Stream::from_bytes(
+            direction.to_string(),
+            direction,
+            input,
+        )]),
+    }
+}
+
+fn run(args: Args) -> Result<(), Error> {
+    let streams = read_streams(&args)?;
+    let encrypted = args.client_key.is_some() || args.server_key.is_some();
+
+    let mut timeline = timeline::Timeline::new(args.extensions.clone(), args.channels.clone());
+    for stream in &streams {
+        let (key, handshake_len) = match stream.direction {
+            Direction::ClientToServer => (args.client_key, ELLSWIFT_ENCODING_SIZE),
+            Direction::ServerToClient => {
+                (args.server_key, INITIATOR_EXPECTED_HANDSHAKE_MESSAGE_SIZE)
+            }
+        };
+        if encrypted && key.is_none() {
+            eprintln!(
+                "{}: skipped, no key for the {}",
+                stream.label, stream.direction
+            );
+            continue;
+        }
+        // Only decrypting, the encryption key is unused.
+        let noise_codec = key.map(|key| NoiseCodec::from_transport_keys(key, key));
+        let skip = if args.handshake { handshake_len } else { 0 };
+        timeline.add_stream(stream, frames::FrameReader::new(stream, noise_codec, skip));
+    }
+    timeline.print();
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
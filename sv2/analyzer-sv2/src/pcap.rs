@@ -0,0 +1,519 @@
+// Extraction of the TCP streams of pcap and pcapng captures.
+//
+// Packets are decoded down to TCP (Ethernet, Linux cooked captures, loopback or raw IP, then IPv4
+// or IPv6), and the payloads of each direction of each connection are reassembled in sequence
+// number order, dropping retransmitted bytes. The endpoint sending the first SYN is the client of
+// a connection, or the sender of the first captured packet if the handshake was not captured.
+//
+// IP fragments and IPv6 extension headers other than hop-by-hop, routing and destination options
+// are not supported, and packets using them are skipped.
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+use crate::{
+    capture::{Direction, Segment, Stream},
+    error::Error,
+};
+
+const PCAP_MAGIC_MICROS: u32 = 0xa1b2_c3d4;
+const PCAP_MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+const PCAPNG_SECTION_HEADER_BLOCK: u32 = 0x0a0d_0d0a;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b_3c4d;
+const PCAPNG_INTERFACE_DESCRIPTION_BLOCK: u32 = 1;
+const PCAPNG_SIMPLE_PACKET_BLOCK: u32 = 3;
+const PCAPNG_ENHANCED_PACKET_BLOCK: u32 = 6;
+const PCAPNG_OPTION_IF_TSRESOL: u16 = 9;
+
+const LINKTYPE_NULL: u32 = 0;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW_BSD: u32 = 12;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LOOP: u32 = 108;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_IPV4: u32 = 228;
+const LINKTYPE_IPV6: u32 = 229;
+const LINKTYPE_LINUX_SLL2: u32 = 276;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+const ETHERTYPE_QINQ: u16 = 0x88a8;
+
+const IP_PROTOCOL_TCP: u8 = 6;
+const TCP_FLAG_SYN: u8 = 0x02;
+const TCP_FLAG_ACK: u8 = 0x10;
+
+/// Returns `true` if the input starts with the magic number of a pcap or pcapng file.
+pub fn is_capture(input: &[u8]) -> bool {
+    let Some(magic) = input.get(..4) else {
+        return false;
+    };
+    let magic = [magic[0], magic[1], magic[2], magic[3]];
+    [
+        PCAP_MAGIC_MICROS,
+        PCAP_MAGIC_NANOS,
+        PCAPNG_SECTION_HEADER_BLOCK,
+    ]
+    .iter()
+    .any(|m| u32::from_le_bytes(magic) == *m || u32::from_be_bytes(magic) == *m)
+}
+
+/// Reads the TCP streams of a pcap or pcapng capture, both directions of a connection being
+/// returned next to each other.
+///
+/// If `port` is given, only the connections from or to that port are read.
+pub fn read_capture(input: &[u8], port: Option<u16>) -> Result<Vec<Stream>, Error> {
+    let packets = if input.get(..4) == Some(&PCAPNG_SECTION_HEADER_BLOCK.to_le_bytes()) {
+        read_pcapng(input)?
+    } else {
+        read_pcap(input)?
+    };
+
+    let mut connections = Connections::default();
+    for packet in packets {
+        let Some(segment) = decode_packet(packet.link_type, packet.data)? else {
+            continue;
+        };
+        let ports = [segment.source.port(), segment.destination.port()];
+        if port.is_some_and(|port| !ports.contains(&port)) {
+            continue;
+        }
+        connections.push(packet.timestamp, segment);
+    }
+    Ok(connections.into_streams())
+}
+
+struct Packet<'a> {
+    timestamp: Duration,
+    link_type: u32,
+    data: &'a [u8],
+}
+
+// Reads integers with the byte order of the capture.
+#[derive(Clone, Copy)]
+struct Reader {
+    big_endian: bool,
+}
+
+impl Reader {
+    fn u16(self, input: &[u8], at: usize) -> Result<u16, Error> {
+        let bytes = input
+            .get(at..at + 2)
+            .ok_or(Error::InvalidCapture("truncated"))?;
+        let bytes = [bytes[0], bytes[1]];
+        Ok(match self.big_endian {
+            true => u16::from_be_bytes(bytes),
+            false => u16::from_le_bytes(bytes),
+        })
+    }
+
+    fn u32(self, input: &[u8], at: usize) -> Result<u32, Error> {
+        let bytes = input
+            .get(at..at + 4)
+            .ok_or(Error::InvalidCapture("truncated"))?;
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        Ok(match self.big_endian {
+            true => u32::from_be_bytes(bytes),
+            false => u32::from_le_bytes(bytes),
+        })
+    }
+}
+
+fn read_pcap(input: &[u8]) -> Result<Vec<Packet<'_>>, Error> {
+    let little_endian = Reader { big_endian: false };
+    let (reader, nanos) = match little_endian.u32(input, 0)? {
+        PCAP_MAGIC_MICROS => (Reader { big_endian: false }, false),
+        PCAP_MAGIC_NANOS => (Reader { big_endian: false }, true),
+        magic if magic.swap_bytes() == PCAP_MAGIC_MICROS => (Reader { big_endian: true }, false),
+        magic if magic.swap_bytes() == PCAP_MAGIC_NANOS => (Reader { big_endian: true }, true),
+        _ => return Err(Error::InvalidCapture("unknown magic number")),
+    };
+    let link_type = reader.u32(input, 20)? & 0x0fff_ffff;
+
+    let mut packets = Vec::new();
+    let mut at = 24;
+    while at < input.len() {
+        let seconds = reader.u32(input, at)?;
+        let fraction = reader.u32(input, at + 4)?;
+        let captured_len = reader.u32(input, at + 8)? as usize;
+        let data = input
+            .get(at + 16..at + 16 + captured_len)
+            .ok_or(Error::InvalidCapture("truncated packet"))?;
+        let timestamp = match nanos {
+            true => Duration::new(seconds.into(), fraction),
+            false => Duration::new(seconds.into(), 0) + Duration::from_micros(fraction.into()),
+        };
+        packets.push(Packet {
+            timestamp,
+            link_type,
+            data,
+        });
+        at += 16 + captured_len;
+    }
+    Ok(packets)
+}
+
+struct Interface {
+    link_type: u32,
+    // Number of timestamp units per second.
+    units_per_second: u64,
+}
+
+fn read_pcapng(input: &[u8]) -> Result<Vec<Packet<'_>>, Error> {
+    let little_endian = Reader { big_endian: false };
+    let mut reader = little_endian;
+    let mut interfaces = Vec::new();
+    let mut packets = Vec::new();
+    let mut at = 0;
+    while at < input.len() {
+        let block_type = reader.u32(input, at)?;
+        if block_type == PCAPNG_SECTION_HEADER_BLOCK {
+            // Each section has its own byte order and interfaces.
+            reader.big_endian = match little_endian.u32(input, at + 8)? {
+                PCAPNG_BYTE_ORDER_MAGIC => false,
+                magic if magic.swap_bytes() == PCAPNG_BYTE_ORDER_MAGIC => true,
+                _ => return Err(Error::InvalidCapture("unknown byte-order magic")),
+            };
+            interfaces.clear();
+        }
+        let block_len = reader.u32(input, at + 4)? as usize;
+        if block_len < 12 || block_len % 4 != 0 {
+            return Err(Error::InvalidCapture("invalid block length"));
+        }
+        let block = input
+            .get(at + 8..at + block_len - 4)
+            .ok_or(Error::InvalidCapture("truncated block"))?;
+
+        match block_type {
+            PCAPNG_INTERFACE_DESCRIPTION_BLOCK => interfaces.push(Interface {
+                link_type: reader.u16(block, 0)?.into(),
+                units_per_second: read_tsresol(reader, block.get(8..).unwrap_or_default())?,
+            }),
+            PCAPNG_ENHANCED_PACKET_BLOCK => {
+                let interface = interfaces
+                    .get(reader.u32(block, 0)? as usize)
+                    .ok_or(Error::InvalidCapture("unknown interface"))?;
+                let timestamp =
+                    u64::from(reader.u32(block, 4)?) << 32 | u64::from(reader.u32(block, 8)?);
+                let captured_len = reader.u32(block, 12)? as usize;
+                let units = interface.units_per_second;
+                let nanos = u128::from(timestamp % units) * 1_000_000_000 / u128::from(units);
+                packets.push(Packet {
+                    timestamp: Duration::new(timestamp / units, nanos as u32),
+                    link_type: interface.link_type,
+                    data: block
+                        .get(20..20 + captured_len)
+                        .ok_or(Error::InvalidCapture("truncated packet"))?,
+                });
+            }
+            PCAPNG_SIMPLE_PACKET_BLOCK => {
+                let interface = interfaces
+                    .first()
+                    .ok_or(Error::InvalidCapture("unknown interface"))?;
+                let original_len = reader.u32(block, 0)? as usize;
+                let data = &block[4..];
+                packets.push(Packet {
+                    // Simple packet blocks have no timestamp.
+                    timestamp: Duration::ZERO,
+                    link_type: interface.link_type,
+                    data: &data[..original_len.min(data.len())],
+                });
+            }
+            _ => {}
+        }
+        at += block_len;
+    }
+    Ok(packets)
+}
+
+// Reads the `if_tsresol` option of an interface description block, microseconds by default.
+fn read_tsresol(reader: Reader, mut options: &[u8]) -> Result<u64, Error> {
+    while options.len() >= 4 {
+        let code = reader.u16(options, 0)?;
+        let len = reader.u16(options, 2)? as usize;
+        if code == PCAPNG_OPTION_IF_TSRESOL && len == 1 {
+            let resolution = options[4];
+            let exponent = u32::from(resolution & 0x7f);
+            return match resolution & 0x80 {
+                0 => 10u64.checked_pow(exponent),
+                _ => 2u64.checked_pow(exponent),
+            }
+            .ok_or(Error::InvalidCapture("invalid timestamp resolution"));
+        }
+        if code == 0 {
+            break;
+        }
+        options = options
+            .get(4 + len.next_multiple_of(4)..)
+            .unwrap_or_default();
+    }
+    Ok(1_000_000)
+}
+
+struct TcpSegment<'a> {
+    source: SocketAddr,
+    destination: SocketAddr,
+    sequence_number: u32,
+    flags: u8,
+    payload: &'a [u8],
+}
+
+// Decodes a packet down to TCP. Returns `None` for packets that are not TCP.
+fn decode_packet(link_type: u32, data: &[u8]) -> Result<Option<TcpSegment<'_>>, Error> {
+    let ip_packet = match link_type {
+        LINKTYPE_NULL | LINKTYPE_LOOP => data.get(4..),
+        LINKTYPE_ETHERNET => {
+            let mut at = 12;
+            loop {
+                match data
+                    .get(at..at + 2)
+                    .map(|t| u16::from_be_bytes([t[0], t[1]]))
+                {
+                    Some(ETHERTYPE_VLAN | ETHERTYPE_QINQ) => at += 4,
+                    Some(ETHERTYPE_IPV4 | ETHERTYPE_IPV6) => break data.get(at + 2..),
+                    _ => break None,
+                }
+            }
+        }
+        LINKTYPE_LINUX_SLL => data.get(16..),
+        LINKTYPE_LINUX_SLL2 => data.get(20..),
+        LINKTYPE_RAW | LINKTYPE_RAW_BSD | LINKTYPE_IPV4 | LINKTYPE_IPV6 => Some(data),
+        link_type => return Err(Error::UnsupportedLinkType(link_type)),
+    };
+    Ok(ip_packet.and_then(decode_ip))
+}
+
+fn decode_ip(packet: &[u8]) -> Option<TcpSegment<'_>> {
+    let (source, destination, tcp) = match packet.first()? >> 4 {
+        4 => {
+            let header_len = usize::from(packet[0] & 0x0f) * 4;
+            let total_len = usize::from(u16::from_be_bytes([*packet.get(2)?, *packet.get(3)?]));
+            let fragment = u16::from_be_bytes([*packet.get(6)?, *packet.get(7)?]);
+            // Skip fragments, i.e. packets with the "more fragments" flag or an offset.
+            if *packet.get(9)? != IP_PROTOCOL_TCP || fragment & 0x3fff != 0 {
+                return None;
+            }
+            let source: [u8; 4] = packet.get(12..16)?.try_into().ok()?;
+            let destination: [u8; 4] = packet.get(16..20)?.try_into().ok()?;
+            (
+                IpAddr::V4(Ipv4Addr::from(source)),
+                IpAddr::V4(Ipv4Addr::from(destination)),
+                // The captured packet can be padded, e.g. by Ethernet.
+                packet.get(header_len..total_len.min(packet.len()))?,
+            )
+        }
+        6 => {
+            let payload_len = usize::from(u16::from_be_bytes([*packet.get(4)?, *packet.get(5)?]));
+            let source: [u8; 16] = packet.get(8..24)?.try_into().ok()?;
+            let destination: [u8; 16] = packet.get(24..40)?.try_into().ok()?;
+            let mut next_header = *packet.get(6)?;
+            let mut payload = packet.get(40..(40 + payload_len).min(packet.len()))?;
+            // Hop-by-hop, routing and destination options headers.
+            while matches!(next_header, 0 | 43 | 60) {
+                let len = (usize::from(*payload.get(1)?) + 1) * 8;
+                next_header = *payload.first()?;
+                payload = payload.get(len..)?;
+            }
+            if next_header != IP_PROTOCOL_TCP {
+                return None;
+            }
+            (
+                IpAddr::V6(Ipv6Addr::from(source)),
+                IpAddr::V6(Ipv6Addr::from(destination)),
+                payload,
+            )
+        }
+        _ => return None,
+    };
+
+    let header_len = usize::from(tcp.get(12)? >> 4) * 4;
+    Some(TcpSegment {
+        source: SocketAddr::new(source, u16::from_be_bytes([tcp[0], tcp[1]])),
+        destination: SocketAddr::new(destination, u16::from_be_bytes([tcp[2], tcp[3]])),
+        sequence_number: u32::from_be_bytes(tcp.get(4..8)?.try_into().ok()?),
+        flags: *tcp.get(13)?,
+        payload: tcp.get(header_len..)?,
+    })
+}
+
+// One direction of a TCP connection being reassembled.
+#[derive(Default)]
+struct TcpStream {
+    // Sequence number of the first payload byte, known from the SYN or the first segment.
+    initial_sequence_number: Option<u32>,
+    // Number of payload bytes reassembled so far.
+    reassembled_len: u32,
+    // Out-of-order segments, by offset from the initial sequence number.
+    pending: BTreeMap<u32, Vec<u8>>,
+    segments: Vec<Segment>,
+}
+
+impl TcpStream {
+    fn push(&mut self, timestamp: Duration, segment: &TcpSegment<'_>) {
+        if segment.flags & TCP_FLAG_SYN != 0 {
+            self.initial_sequence_number = Some(segment.sequence_number.wrapping_add(1));
+            return;
+        }
+        if segment.payload.is_empty() {
+            return;
+        }
+        let initial_sequence_number = *self
+            .initial_sequence_number
+            .get_or_insert(segment.sequence_number);
+        let offset = segment
+            .sequence_number
+            .wrapping_sub(initial_sequence_number);
+        // Segments sent before the SYN, or more than 2 GiB after the initial sequence number.
+        if offset > i32::MAX as u32 {
+            return;
+        }
+        let pending = self.pending.entry(offset).or_default();
+        if segment.payload.len() > pending.len() {
+            *pending = segment.payload.to_vec();
+        }
+
+        // Bytes are timestamped when they become contiguous, i.e. readable by the receiver.
+        while let Some(entry) = self.pending.first_entry() {
+            let offset = *entry.key();
+            if offset > self.reassembled_len {
+                break;
+            }
+            let data = entry.remove();
+            let end = offset + data.len() as u32;
+            // Drop the bytes already reassembled, e.g. of retransmitted segments.
+            if end > self.reassembled_len {
+                let new_data = data[(self.reassembled_len - offset) as usize..].to_vec();
+                self.segments.push(Segment {
+                    timestamp: Some(timestamp),
+                    data: new_data,
+                });
+                self.reassembled_len = end;
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Connections {
+    // Both directions of each connection, indexed by (client, server).
+    connections: HashMap<(SocketAddr, SocketAddr), [TcpStream; 2]>,
+    // Connections in the order of their first packet.
+    order: Vec<(SocketAddr, SocketAddr)>,
+}
+
+impl Connections {
+    fn push(&mut self, timestamp: Duration, segment: TcpSegment<'_>) {
+        let (source, destination) = (segment.source, segment.destination);
+        let (key, direction) = if self.connections.contains_key(&(source, destination)) {
+            ((source, destination), 0)
+        } else if self.connections.contains_key(&(destination, source))
+            // The SYN of the client was not captured.
+            || segment.flags & (TCP_FLAG_SYN | TCP_FLAG_ACK) == TCP_FLAG_SYN | TCP_FLAG_ACK
+        {
+            ((destination, source), 1)
+        } else {
+            ((source, destination), 0)
+        };
+        if !self.connections.contains_key(&key) {
+            self.order.push(key);
+        }
+        self.connections.entry(key).or_default()[direction].push(timestamp, &segment);
+    }
+
+    fn into_streams(mut self) -> Vec<Stream> {
+        let mut streams = Vec::new();
+        for (client, server) in self.order {
+            let [client_to_server, server_to_client] = self
+                .connections
+                .remove(&(client, server))
+                .expect("every connection is ordered");
+            streams.push(Stream {
+                label: format!("{client} -> {server}"),
+                direction: Direction::ClientToServer,
+                segments: client_to_server.segments,
+            });
+            streams.push(Stream {
+                label: format!("{server} -> {client}"),
+                direction: Direction::ServerToClient,
+                segments: server_to_client.segments,
+            });
+        }
+        streams
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Builds an Ethernet + IPv4 + TCP packet between 10.0.0.1:50000 and 10.0.0.2:3333.
+    fn tcp_packet(from_client: bool, seq: u32, flags: u8, payload: &[u8]) -> Vec<u8> {
+        let mut endpoints = [([10, 0, 0, 1], 50000u16), ([10, 0, 0, 2], 3333u16)];
+        if !from_client {
+            endpoints.reverse();
+        }
+        let mut packet = vec![0; 12];
+        packet.extend_from_slice(&ETHERTYPE_IPV4.to_be_bytes());
+        let total_len = (20 + 20 + payload.len()) as u16;
+        packet.extend_from_slice(&[0x45, 0]);
+        packet.extend_from_slice(&total_len.to_be_bytes());
+        packet.extend_from_slice(&[0, 0, 0x40, 0, 64, IP_PROTOCOL_TCP, 0, 0]);
+        packet.extend_from_slice(&endpoints[0].0);
+        packet.extend_from_slice(&endpoints[1].0);
+        packet.extend_from_slice(&endpoints[0].1.to_be_bytes());
+        packet.extend_from_slice(&endpoints[1].1.to_be_bytes());
+        packet.extend_from_slice(&seq.to_be_bytes());
+        packet.extend_from_slice(&[0, 0, 0, 0, 0x50, flags, 0xff, 0xff, 0, 0, 0, 0]);
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    fn pcap(packets: &[Vec<u8>]) -> Vec<u8> {
+        let mut pcap = Vec::new();
+        pcap.extend_from_slice(&PCAP_MAGIC_MICROS.to_le_bytes());
+        pcap.extend_from_slice(&[2, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 0, 0]);
+        pcap.extend_from_slice(&LINKTYPE_ETHERNET.to_le_bytes());
+        for (i, packet) in packets.iter().enumerate() {
+            pcap.extend_from_slice(&(i as u32).to_le_bytes());
+            pcap.extend_from_slice(&0u32.to_le_bytes());
+            pcap.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+            pcap.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+            pcap.extend_from_slice(packet);
+        }
+        pcap
+    }
+
+    #[test]
+    fn reassembles_out_of_order_and_retransmitted_segments() {
+        let capture = pcap(&[
+            tcp_packet(true, 99, TCP_FLAG_SYN, &[]),
+            tcp_packet(false, 499, TCP_FLAG_SYN | TCP_FLAG_ACK, &[]),
+            tcp_packet(true, 103, TCP_FLAG_ACK, &[4, 5]),
+            tcp_packet(true, 100, TCP_FLAG_ACK, &[1, 2, 3]),
+            tcp_packet(true, 100, TCP_FLAG_ACK, &[1, 2, 3, 4, 5, 6]),
+            tcp_packet(false, 500, TCP_FLAG_ACK, &[9]),
+        ]);
+        assert!(is_capture(&capture));
+
+        let streams = read_capture(&capture, Some(3333)).unwrap();
+        assert_eq!(streams.len(), 2);
+        assert_eq!(streams[0].label, "10.0.0.1:50000 -> 10.0.0.2:3333");
+        assert_eq!(streams[0].direction, Direction::ClientToServer);
+        let data: Vec<u8> = streams[0]
+            .segments
+            .iter()
+            .flat_map(|s| s.data.clone())
+            .collect();
+        assert_eq!(data, vec![1, 2, 3, 4, 5, 6]);
+        let timestamps: Vec<_> = streams[0].segments.iter().map(|s| s.timestamp).collect();
+        let seconds = |s| Some(Duration::from_secs(s));
+        assert_eq!(timestamps, vec![seconds(3), seconds(3), seconds(4)]);
+        assert_eq!(streams[1].direction, Direction::ServerToClient);
+        assert_eq!(streams[1].segments[0].data, vec![9]);
+
+        assert!(read_capture(&capture, Some(3334)).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use parsers_sv2::{
+    message_type_to_name, parse_message_frame_with_tlvs, AnyMessage, CommonMessages, Extensions,
+    ExtensionsNegotiation, Mining, Tlv,
+};
+
+use crate::{
+    capture::Stream,
+    frames::{Frame, FrameError},
+};
+
+enum Entry {
+    Frame(Frame),
+    Error(FrameError),
+}
+
+struct Event {
+    timestamp: Option<Duration>,
+    stream: String,
+    entry: Entry,
+}
+
+/// Frames of all the streams of a capture, printed in capture order.
+pub struct Timeline {
+    negotiated_extensions: Vec<u16>,
+    channels: Vec<u32>,
+    events: Vec<Event>,
+}
+
+impl Timeline {
+    /// `negotiated_extensions` are the extensions whose TLV fields are decoded, and `channels`
+    /// the channels whose messages are printed, all of them if empty.
+    pub fn new(negotiated_extensions: Vec<u16>, channels: Vec<u32>) -> Self {
+        Self {
+            negotiated_extensions,
+            channels,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn add_stream(
+        &mut self,
+        stream: &Stream,
+        frames: impl Iterator<Item = Result<Frame, FrameError>>,
+    ) {
+        for frame in frames {
+            let (timestamp, entry) = match frame {
+                Ok(frame) => (frame.timestamp, Entry::Frame(frame)),
+                // Errors end the stream, so they come after its last frame.
+                Err(error) => (
+                    stream.segments.last().and_then(|segment| segment.timestamp),
+                    Entry::Error(error),
+                ),
+            };
+            self.events.push(Event {
+                timestamp,
+                stream: stream.label.clone(),
+                entry,
+            });
+        }
+    }
+
+    /// Prints the frames sorted by capture time, or in stream order if the capture has no
+    /// timestamps, e.g. for hex dumps.
+    pub fn print(mut self) {
+        // Stable, so the order of frames with the same timestamp is kept.
+        self.events.sort_by_key(|event| event.timestamp);
+        let start = self.events.iter().find_map(|event| event.timestamp);
+
+        for event in &self.events {
+            let time = match (event.timestamp, start) {
+                (Some(timestamp), Some(start)) => {
+                    format!("{:>12.6} ", (timestamp - start).as_secs_f64())
+                }
+                _ => String::new(),
+            };
+            match &event.entry {
+                Entry::Frame(frame) => {
+                    if let Some(lines) = self.describe_frame(frame) {
+                        println!("{time}{} @{} {}", event.stream, frame.offset, lines[0]);
+                        for line in &lines[1..] {
+                            println!("    {line}");
+                        }
+                    }
+                }
+                Entry::Error(error) => println!("{time}{} error: {error}", event.stream),
+            }
+        }
+    }
+
+    // Returns the lines printed for a frame, or `None` if it is filtered out.
+    fn describe_frame(&self, frame: &Frame) -> Option<Vec<String>> {
+        let header = frame.header;
+        let mut payload = frame.payload.clone();
+        let parsed =
+            parse_message_frame_with_tlvs(header, &mut payload, &self.negotiated_extensions);
+        let channels = match &parsed {
+            Ok((message, _)) => channel_ids(message),
+            Err(_) => Vec::new(),
+        };
+        if !self.channels.is_empty() && !channels.iter().any(|c| self.channels.contains(c)) {
+            return None;
+        }
+
+        let name = match &parsed {
+            Ok((message, _)) => message_name(message),
+            Err(_) if header.ext_type_without_channel_msg() == 0 => {
+                message_type_to_name(header.msg_type())
+            }
+            Err(_) => "Unknown Message",
+        };
+        let mut lines = vec![format!(
+            "{name} (extension_type=0x{:04x}, msg_type=0x{:02x}, channel_msg={}, length={})",
+            header.ext_type_without_channel_msg(),
+            header.msg_type(),
+            header.channel_msg(),
+            header.len(),
+        )];
+        match parsed {
+            Ok((message, tlv_fields)) => {
+                lines.push(message.to_string());
+                lines.extend(tlv_fields.iter().flatten().map(describe_tlv));
+            }
+            Err(e) => lines.push(format!(
+                "failed to parse: {e:?}, payload={}",
+                hex(&frame.payload)
+            )),
+        }
+        Some(lines)
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn describe_tlv(tlv: &Tlv) -> String {
+    let value = match std::str::from_utf8(&tlv.value) {
+        Ok(text) if text.chars().all(|c| !c.is_control()) => format!(" \"{text}\""),
+        _ => String::new(),
+    };
+    format!(
+        "TLV(extension_type=0x{:04x}, field_type=0x{:02x}, value={}{value})",
+        tlv.r#type.extension_type,
+        tlv.r#type.field_type,
+        hex(&tlv.value),
+    )
+}
+
+// Names extension messages from their variant, as their message types overlap with the ones of
+// `message_type_to_name`.
+fn message_name(message: &AnyMessage<'_>) -> &'static str {
+    match message {
+        AnyMessage::Extensions(Extensions::ExtensionsNegotiation(m)) => match m {
+            ExtensionsNegotiation::RequestExtensions(_) => "RequestExtensions",
+            ExtensionsNegotiation::RequestExtensionsSuccess(_) => "RequestExtensionsSuccess",
+            ExtensionsNegotiation::RequestExtensionsError(_) => "RequestExtensionsError",
+        },
+        AnyMessage::Extensions(Extensions::Custom(_)) => "Custom Extension Message",
+        AnyMessage::Common(CommonMessages::Reconnect(_)) => "Reconnect",
+        m => message_type_to_name(parsers_sv2::IsSv2Message::message_type(m)),
+    }
+}
+
+// Returns the channels a message relates to, including the channels of `SetGroupChannel`.
+fn channel_ids(message: &AnyMessage<'_>) -> Vec<u32> {
+    let channel_id = match message {
+        AnyMessage::Common(CommonMessages::ChannelEndpointChanged(m)) => m.channel_id,
+        AnyMessage::Mining(m) => match m {
+            Mining::CloseChannel(m) => m.channel_id,
+            Mining::NewExtendedMiningJob(m) => m.channel_id,
+            Mining::NewMiningJob(m) => m.channel_id,
+            Mining::OpenExtendedMiningChannelSuccess(m) => m.channel_id,
+            Mining::OpenStandardMiningChannelSuccess(m) => m.channel_id,
+            Mining::SetCustomMiningJob(m) => m.channel_id,
+            Mining::SetCustomMiningJobError(m) => m.channel_id,
+            Mining::SetCustomMiningJobSuccess(m) => m.channel_id,
+            Mining::SetExtranoncePrefix(m) => m.channel_id,
+            Mining::SetGroupChannel(m) => {
+                let mut channel_ids = m.channel_ids.clone().into_inner();
+                channel_ids.push(m.group_channel_id);
+                return channel_ids;
+            }
+            Mining::SetNewPrevHash(m) => m.channel_id,
+            Mining::SetTarget(m) => m.channel_id,
+            Mining::SubmitSharesError(m) => m.channel_id,
+            Mining::SubmitSharesExtended(m) => m.channel_id,
+            Mining::SubmitSharesStandard(m) => m.channel_id,
+            Mining::SubmitSharesSuccess(m) => m.channel_id,
+            Mining::UpdateChannel(m) => m.channel_id,
+            Mining::UpdateChannelError(m) => m.channel_id,
+            Mining::OpenExtendedMiningChannel(_)
+            | Mining::OpenMiningChannelError(_)
+            | Mining::OpenStandardMiningChannel(_) => return Vec::new(),
+        },
+        _ => return Vec::new(),
+    };
+    vec![channel_id]
+}
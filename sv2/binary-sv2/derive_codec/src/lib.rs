@@ -27,6 +27,15 @@
 //!   - **Generated Methods**: `get_structure` (defines field structure) and `from_decoded_fields`
 //!     (builds the struct from decoded fields).
 //!
+//! - **`ValidateFields`**: Implements `ValidateFields`, checking each field of a struct against the
+//!   limits of its Sv2 data type before encoding. Opt-in, the fields having to implement
+//!   `ValidateFields` themselves.
+//!
+//! - **`TlvField`**: Implements `parsers_sv2::TlvField` for a struct that also derives `Encodable`
+//!   and `Decodable`, its encoding being the value of the TLV field.
+//!   - **Attributes**: `#[tlv(extension_type = .., field_type = .., max_length = ..)]`, the maximum
+//!     length of the value being optional.
+//!
 //! ## Internal Structure
 //!
 //! ### `is_already_sized`
@@ -53,7 +62,7 @@ use alloc::{
     vec::Vec,
 };
 use core::iter::FromIterator;
-use proc_macro::{Group, TokenStream, TokenTree};
+use proc_macro::{Delimiter, Group, TokenStream, TokenTree};
 
 // Reserved field names to avoid conflicts
 const RESERVED_FIELDS: [&str; 2] = ["__decodable_internal_data", "__decodable_internal_offset"];
@@ -876,3 +885,344 @@ pub fn encodable(item: TokenStream) -> TokenStream {
     // Never executed at runtime it ok to panic
     result.parse().unwrap()
 }
+
+/// Derives the `ValidateFields` trait, checking each field of a struct against the limits of its
+/// Sv2 data type.
+///
+/// Every field has to implement `ValidateFields`, which `binary_sv2` does for its data types.
+/// Violations of a field are reported with the name of the field prepended to their path.
+///
+/// # Example
+///
+/// Given a struct:
+///
+/// ```ignore
+/// struct Test<'decoder> {
+///     a: u32,
+///     b: Str0255<'decoder>,
+/// }
+/// ```
+///
+/// Using `#[derive(ValidateFields)]` on `Test` generates the following implementation:
+///
+/// ```ignore
+/// mod impl_validate_fields_test {
+///     use super::binary_sv2::{FieldViolation, ValidateFields};
+///     use super::Test;
+///     extern crate alloc;
+///     use alloc::vec::Vec;
+///
+///     impl<'decoder> ValidateFields for Test<'decoder> {
+///         fn collect_violations(&self, violations: &mut Vec<FieldViolation>) {
+///             self.a.collect_field_violations("a", violations);
+///             self.b.collect_field_violations("b", violations);
+///         }
+///     }
+/// }
+/// ```
+#[proc_macro_derive(ValidateFields)]
+pub fn validate_fields(item: TokenStream) -> TokenStream {
+    let parsed_struct = get_struct_properties(item);
+
+    let mut field_violations = String::new();
+    for f in parsed_struct.fields.iter() {
+        field_violations.push_str(&format!(
+            "
+            self.{}.collect_field_violations(\"{}\", violations);
+            ",
+            f.name, f.name
+        ));
+    }
+
+    // Named `_violations` for structs without fields, to avoid an unused variable warning.
+    let violations_ident = if field_violations.is_empty() {
+        "_violations"
+    } else {
+        "violations"
+    };
+
+    let result = format!(
+        "mod impl_validate_fields_{} {{
+
+    use super::binary_sv2::{{FieldViolation, ValidateFields}};
+    use super::{};
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    impl{} ValidateFields for {}{} {{
+        fn collect_violations(&self, {}: &mut Vec<FieldViolation>) {{
+            {}
+        }}
+    }}
+
+    }}",
+        parsed_struct.name.to_lowercase(),
+        parsed_struct.name,
+        parsed_struct.generics,
+        parsed_struct.name,
+        parsed_struct.generics,
+        violations_ident,
+        field_violations,
+    );
+
+    // Never executed at runtime it ok to panic
+    result.parse().unwrap()
+}
+
+// Arguments of the `#[tlv(...)]` attribute of a struct deriving `TlvField`, as source code.
+#[derive(Debug, Default)]
+struct TlvAttributes {
+    // Expression of the `extension_type` argument.
+    extension_type: Option<String>,
+    // Expression of the `field_type` argument.
+    field_type: Option<String>,
+    // Expression of the `max_length` argument, if any.
+    max_length: Option<String>,
+}
+
+// Parses the `#[tlv(extension_type = .., field_type = .., max_length = ..)]` attribute.
+//
+// Arguments are arbitrary expressions, e.g. literals or constants, and are kept as source code to
+// be pasted in the generated implementation.
+//
+// # Example
+//
+// ```ignore
+// #[tlv(extension_type = 0x4000, field_type = 0x01, max_length = MAX_DEVICE_NAME_LENGTH)]
+// ```
+//
+// gives `extension_type: Some("0x4000")`, `field_type: Some("0x01")` and
+// `max_length: Some("MAX_DEVICE_NAME_LENGTH")`.
+fn get_tlv_attributes(item: TokenStream) -> Result<TlvAttributes, String> {
+    let mut attributes = TlvAttributes::default();
+    let mut is_attribute = false;
+
+    for next in item.into_iter() {
+        match next {
+            TokenTree::Punct(p) if p.as_char() == '#' => {
+                is_attribute = true;
+                continue;
+            }
+            TokenTree::Group(g) if is_attribute && g.delimiter() == Delimiter::Bracket => {
+                let mut attribute = g.stream().into_iter();
+                match (attribute.next(), attribute.next()) {
+                    (Some(TokenTree::Ident(i)), Some(TokenTree::Group(args)))
+                        if i.to_string() == "tlv" =>
+                    {
+                        parse_tlv_arguments(args.stream(), &mut attributes)?
+                    }
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+        is_attribute = false;
+    }
+    Ok(attributes)
+}
+
+// Parses the `name = expression` arguments of the `#[tlv(...)]` attribute.
+fn parse_tlv_arguments(args: TokenStream, attributes: &mut TlvAttributes) -> Result<(), String> {
+    let mut args = args.into_iter().peekable();
+    while let Some(name) = args.next() {
+        match args.next() {
+            Some(TokenTree::Punct(p)) if p.as_char() == '=' => (),
+            _ => return Err(format!("Expected `{name} = <value>` in #[tlv(...)]")),
+        }
+        let mut value = String::new();
+        while let Some(token) =
+            args.next_if(|t| !matches!(t, TokenTree::Punct(p) if p.as_char() == ','))
+        {
+            value.push_str(&token.to_string());
+        }
+        // Skip the separator
+        args.next();
+        if value.is_empty() {
+            return Err(format!("Missing value of `{name}` in #[tlv(...)]"));
+        }
+        let argument = match name.to_string().as_str() {
+            "extension_type" => &mut attributes.extension_type,
+            "field_type" => &mut attributes.field_type,
+            "max_length" => &mut attributes.max_length,
+            _ => return Err(format!("Unknown argument `{name}` in #[tlv(...)]")),
+        };
+        *argument = Some(value);
+    }
+    Ok(())
+}
+
+/// Derives `parsers_sv2::TlvField`, encoding the struct as the value of a TLV field.
+///
+/// The value is the struct encoded with the `binary_sv2` format, so the struct must also derive
+/// `Serialize`, `Deserialize` and `Clone`. The extension and field types are given by the
+/// `#[tlv(...)]` attribute, with an optional maximum length of the value, in bytes. Values longer
+/// than the maximum, or than the 65535 bytes allowed by the TLV length field, are rejected when
+/// encoding and decoding, with `TlvError::ValueTooLong`. TLV fields of another type are rejected
+/// with `TlvError::UnexpectedType`.
+///
+/// Structs with a lifetime implement `TlvField` for their `'static` version. As `Serialize` and
+/// `Deserialize` also have `to_bytes` and `from_bytes` methods, the ones of `TlvField` are called
+/// as `TlvField::to_bytes(&field)` and `<Field as TlvField>::from_bytes(bytes)`.
+///
+/// As with `Serialize` and `Deserialize`, `binary_sv2` must be in scope, and `parsers_sv2` must be
+/// a dependency.
+///
+/// # Example
+///
+/// Given a struct:
+///
+/// ```ignore
+/// use binary_sv2::{self, Deserialize, Serialize, Str0255};
+/// use parsers_sv2::TlvField;
+///
+/// #[derive(Clone, Deserialize, Serialize, TlvField)]
+/// #[tlv(extension_type = 0x4000, field_type = 0x01, max_length = 64)]
+/// struct DeviceName<'decoder> {
+///     name: Str0255<'decoder>,
+/// }
+/// ```
+///
+/// Using `#[derive(TlvField)]` on `DeviceName` generates the following implementation:
+///
+/// ```ignore
+/// mod impl_tlv_field_devicename {
+///     use super::*;
+///     extern crate alloc;
+///     use alloc::vec::Vec;
+///     use parsers_sv2::{ParserError, Tlv, TlvError};
+///
+///     const MAX_LENGTH: usize = 64;
+///
+///     impl parsers_sv2::TlvField for DeviceName<'static> {
+///         const EXTENSION_TYPE: u16 = 0x4000;
+///         const FIELD_TYPE: u8 = 0x01;
+///
+///         fn from_bytes(bytes: &[u8]) -> Result<Tlv, ParserError> {
+///             Tlv::decode(bytes).map_err(Into::into)
+///         }
+///
+///         fn to_bytes(&self) -> Result<Vec<u8>, ParserError> {
+///             self.to_tlv()?.encode().map_err(Into::into)
+///         }
+///
+///         fn from_tlv(tlv: &Tlv) -> Result<Self, ParserError> {
+///             if tlv.r#type.extension_type != Self::EXTENSION_TYPE
+///                 || tlv.r#type.field_type != Self::FIELD_TYPE
+///             {
+///                 return Err(TlvError::UnexpectedType(
+///                     tlv.r#type.extension_type,
+///                     tlv.r#type.field_type,
+///                 )
+///                 .into());
+///             }
+///             if tlv.value.len() > MAX_LENGTH {
+///                 return Err(TlvError::ValueTooLong(tlv.value.len(), MAX_LENGTH).into());
+///             }
+///             let mut value = tlv.value.clone();
+///             let field: DeviceName = binary_sv2::from_bytes(&mut value)
+///                 .map_err(TlvError::DecodingError)?;
+///             Ok(field.into_static())
+///         }
+///
+///         fn to_tlv(&self) -> Result<Tlv, ParserError> {
+///             let value = binary_sv2::to_bytes(self.clone()).map_err(TlvError::EncodingError)?;
+///             if value.len() > MAX_LENGTH {
+///                 return Err(TlvError::ValueTooLong(value.len(), MAX_LENGTH).into());
+///             }
+///             Ok(Tlv::new(Self::EXTENSION_TYPE, Self::FIELD_TYPE, value))
+///         }
+///     }
+/// }
+/// ```
+#[proc_macro_derive(TlvField, attributes(tlv))]
+pub fn tlv_field(item: TokenStream) -> TokenStream {
+    let attributes = match get_tlv_attributes(item.clone()) {
+        Ok(attributes) => attributes,
+        Err(e) => return format!("compile_error!(\"{e}\");").parse().unwrap(),
+    };
+    let parsed_struct = get_struct_properties(item);
+    let (Some(extension_type), Some(field_type)) =
+        (attributes.extension_type, attributes.field_type)
+    else {
+        return format!(
+            "compile_error!(\"Struct '{}' deriving TlvField needs a #[tlv(extension_type = .., field_type = ..)] attribute\");",
+            parsed_struct.name
+        )
+        .parse()
+        .unwrap();
+    };
+    // The TLV length field is a U16, whatever the maximum length
+    let max_length = match attributes.max_length {
+        Some(max_length) => format!(
+            "if {max_length} < u16::MAX as usize {{ {max_length} }} else {{ u16::MAX as usize }}"
+        ),
+        None => "u16::MAX as usize".to_string(),
+    };
+    let (static_generics, into_static) = if parsed_struct.generics.is_empty() {
+        ("", "")
+    } else {
+        ("<'static>", ".into_static()")
+    };
+
+    let result = format!(
+        "mod impl_tlv_field_{} {{
+
+    use super::*;
+    extern crate alloc;
+    use alloc::vec::Vec;
+    use parsers_sv2::{{ParserError, Tlv, TlvError}};
+
+    const MAX_LENGTH: usize = {};
+
+    impl parsers_sv2::TlvField for {}{} {{
+        const EXTENSION_TYPE: u16 = {};
+        const FIELD_TYPE: u8 = {};
+
+        fn from_bytes(bytes: &[u8]) -> Result<Tlv, ParserError> {{
+            Tlv::decode(bytes).map_err(Into::into)
+        }}
+
+        fn to_bytes(&self) -> Result<Vec<u8>, ParserError> {{
+            self.to_tlv()?.encode().map_err(Into::into)
+        }}
+
+        fn from_tlv(tlv: &Tlv) -> Result<Self, ParserError> {{
+            if tlv.r#type.extension_type != Self::EXTENSION_TYPE
+                || tlv.r#type.field_type != Self::FIELD_TYPE
+            {{
+                return Err(TlvError::UnexpectedType(
+                    tlv.r#type.extension_type,
+                    tlv.r#type.field_type,
+                )
+                .into());
+            }}
+            if tlv.value.len() > MAX_LENGTH {{
+                return Err(TlvError::ValueTooLong(tlv.value.len(), MAX_LENGTH).into());
+            }}
+            let mut value = tlv.value.clone();
+            let field: {} = binary_sv2::from_bytes(&mut value).map_err(TlvError::DecodingError)?;
+            Ok(field{})
+        }}
+
+        fn to_tlv(&self) -> Result<Tlv, ParserError> {{
+            let value = binary_sv2::to_bytes(self.clone()).map_err(TlvError::EncodingError)?;
+            if value.len() > MAX_LENGTH {{
+                return Err(TlvError::ValueTooLong(value.len(), MAX_LENGTH).into());
+            }}
+            Ok(Tlv::new(Self::EXTENSION_TYPE, Self::FIELD_TYPE, value))
+        }}
+    }}
+    }}",
+        parsed_struct.name.to_lowercase(),
+        max_length,
+        parsed_struct.name,
+        static_generics,
+        extension_type,
+        field_type,
+        parsed_struct.name,
+        into_static,
+    );
+
+    // Never executed at runtime it ok to panic
+    result.parse().unwrap()
+}
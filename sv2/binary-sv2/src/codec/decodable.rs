@@ -31,6 +31,14 @@ pub trait Decodable<'a>: Sized {
     /// the raw data, decoding each field, and then using `from_decoded_fields` to reassemble
     /// the fields into the original type.
     fn from_bytes(data: &'a mut [u8]) -> Result<Self, Error> {
+        Self::from_bytes_with_tail(data).map(|(decoded, _)| decoded)
+    }
+
+    /// Decodes the type from the start of raw bytes, and returns the bytes following it.
+    ///
+    /// Same as [`Decodable::from_bytes`], except that the trailing bytes, e.g. the TLV fields
+    /// following a message, are given back instead of being borrowed by the decoded type.
+    fn from_bytes_with_tail(data: &'a mut [u8]) -> Result<(Self, &'a mut [u8]), Error> {
         let structure = Self::get_structure(data)?;
         let mut fields = Vec::new();
         let mut tail = data;
@@ -44,7 +52,7 @@ pub trait Decodable<'a>: Sized {
             tail = t;
             fields.push(field.decode(head)?);
         }
-        Self::from_decoded_fields(fields)
+        Ok((Self::from_decoded_fields(fields)?, tail))
     }
 
     /// Converts a readable input to self representation.
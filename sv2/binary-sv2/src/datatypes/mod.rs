@@ -35,12 +35,16 @@ use crate::{
 mod non_copy_data_types;
 
 mod copy_data_types;
+#[cfg(feature = "serde")]
+mod serde_impls;
 use crate::codec::decodable::FieldMarker;
 pub use copy_data_types::U24;
 pub use non_copy_data_types::{
     Inner, PubKey, Seq0255, Seq064K, Signature, Str0255, Sv2Option, U32AsRef, B016M, B0255, B032,
     B064K, U256,
 };
+#[cfg(feature = "serde")]
+pub use serde_impls::serde_str;
 
 use alloc::vec::Vec;
 use core::convert::TryInto;
@@ -0,0 +1,139 @@
+// Serde support for the Sv2 data types, enabled by the `serde` feature.
+//
+// The representation is meant to be stable and readable by non-Rust tooling:
+// - byte arrays (`U256`, `PubKey`, `Signature`, `B032`, `B0255`, `B064K`, `B016M`, ...) are
+//   lowercase hex strings, without `0x` prefix;
+// - `Seq0255` and `Seq064K` are arrays;
+// - `Sv2Option` is its value, or `null`;
+// - `U24` is a number.
+//
+// `Str0255` is the same type as `B0255`, so it is a hex string too unless the field opts into a
+// UTF-8 string with `#[serde(with = "binary_sv2::serde_str")]`.
+//
+// Deserialization enforces the size limits of each type.
+use super::{Inner, Seq0255, Seq064K, Sv2Option};
+use crate::U24;
+use alloc::{string::String, vec::Vec};
+use core::convert::TryFrom;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+        })
+        .collect()
+}
+
+impl<const ISFIXED: bool, const SIZE: usize, const HEADERSIZE: usize, const MAXSIZE: usize>
+    Serialize for Inner<'_, ISFIXED, SIZE, HEADERSIZE, MAXSIZE>
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let bytes: &[u8] = match self {
+            Inner::Ref(bytes) => bytes,
+            Inner::Owned(bytes) => bytes,
+        };
+        serializer.serialize_str(&to_hex(bytes))
+    }
+}
+
+impl<
+        'de,
+        const ISFIXED: bool,
+        const SIZE: usize,
+        const HEADERSIZE: usize,
+        const MAXSIZE: usize,
+    > Deserialize<'de> for Inner<'_, ISFIXED, SIZE, HEADERSIZE, MAXSIZE>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        let bytes = from_hex(&hex).ok_or_else(|| de::Error::custom("invalid hex string"))?;
+        Self::try_from(bytes)
+            .map_err(|_| de::Error::custom("invalid length for the Sv2 byte array type"))
+    }
+}
+
+impl<T: Serialize> Serialize for Seq0255<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Seq0255<'_, T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::new(Vec::deserialize(deserializer)?)
+            .map_err(|_| de::Error::custom("Seq0255 has more than 255 elements"))
+    }
+}
+
+impl<T: Serialize> Serialize for Seq064K<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Seq064K<'_, T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::new(Vec::deserialize(deserializer)?)
+            .map_err(|_| de::Error::custom("Seq064K has more than 65535 elements"))
+    }
+}
+
+impl<T: Serialize> Serialize for Sv2Option<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.first().serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Sv2Option<'_, T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::new(Option::deserialize(deserializer)?))
+    }
+}
+
+impl Serialize for U24 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for U24 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::try_from(u32::deserialize(deserializer)?)
+            .map_err(|_| de::Error::custom("U24 is greater than 16777215"))
+    }
+}
+
+/// Serializes a [`Str0255`](crate::Str0255) field as a UTF-8 string rather than hex.
+///
+/// To be used with `#[serde(with = "binary_sv2::serde_str")]`. Serialization fails if the value
+/// is not valid UTF-8.
+pub mod serde_str {
+    use crate::Str0255;
+    use alloc::string::String;
+    use core::convert::TryFrom;
+    use serde::{de, ser, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Str0255<'_>, serializer: S) -> Result<S::Ok, S::Error> {
+        let string = core::str::from_utf8(value.inner_as_ref())
+            .map_err(|_| ser::Error::custom("Str0255 is not valid UTF-8"))?;
+        serializer.serialize_str(string)
+    }
+
+    pub fn deserialize<'de, 'a, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Str0255<'a>, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        Str0255::try_from(string.into_bytes())
+            .map_err(|_| de::Error::custom("Str0255 is longer than 255 bytes"))
+    }
+}
@@ -40,6 +40,9 @@
 //! - **to_bytes**: Encodes an SV2 data type into a byte vector.
 //! - **to_writer**: Encodes an SV2 data type into a byte slice.
 //! - **from_bytes**: Decodes an SV2-encoded byte slice into the specified data type.
+//! - **ValidateFields**: Checks every field of a message against the limits of its SV2 data type,
+//!   e.g. a `Str0255` of at most 255 bytes or a `Seq0255` of at most 255 items, before encoding.
+//!   Derived with `#[derive(ValidateFields)]`.
 //!
 //! # Error Handling
 //!
@@ -65,11 +68,14 @@
 use std::io::{Error as E, ErrorKind};
 
 pub use decodable::Decodable as Deserialize;
-pub use derive_codec_sv2::{Decodable as Deserialize, Encodable as Serialize};
+pub use derive_codec_sv2::{Decodable as Deserialize, Encodable as Serialize, ValidateFields};
 pub use encodable::Encodable as Serialize;
 
 mod codec;
 mod datatypes;
+mod validate;
+#[cfg(feature = "serde")]
+pub use datatypes::serde_str;
 pub use datatypes::{
     PubKey, Seq0255, Seq064K, Signature, Str0255, Sv2DataType, Sv2Option, U32AsRef, B016M, B0255,
     B032, B064K, U24, U256,
@@ -80,6 +86,7 @@ pub use crate::codec::{
     encodable::{Encodable, EncodableField},
     Fixed, GetSize, SizeHint,
 };
+pub use validate::{FieldViolation, ValidateFields};
 
 use alloc::vec::Vec;
 
@@ -103,6 +110,14 @@ pub fn from_bytes<'a, T: Decodable<'a>>(data: &'a mut [u8]) -> Result<T, Error>
     T::from_bytes(data)
 }
 
+/// Decodes an SV2-encoded data type from the start of a byte slice, and returns the bytes
+/// following it.
+pub fn from_bytes_with_tail<'a, T: Decodable<'a>>(
+    data: &'a mut [u8],
+) -> Result<(T, &'a mut [u8]), Error> {
+    T::from_bytes_with_tail(data)
+}
+
 /// Provides an interface and implementation details for decoding complex data structures
 /// from raw bytes or I/O streams. Handles deserialization of nested and primitive data
 /// structures through traits, enums, and helper functions for managing the decoding process.
@@ -0,0 +1,205 @@
+// Checks Sv2 values against the limits of their data types before encoding.
+//
+// The variable-length types (`Str0255`, `B064K`, `Seq0255`, ...) can hold more data than their
+// encoding allows, e.g. when built from `Inner::Owned` or by pushing into a sequence, in which case
+// encoding fails with no hint of the offending field. `ValidateFields` reports every such field
+// with its name, and is implemented with `#[derive(ValidateFields)]` for every message.
+
+use crate::{
+    datatypes::{Inner, Seq0255, Seq064K, Sv2Option},
+    U24,
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+/// A field exceeding the limits of its Sv2 data type.
+///
+/// `field` is the path of the field in the validated value, e.g. `user_identity` or
+/// `merkle_path[3]`, and is empty if the value itself is invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldViolation {
+    /// A string or byte array longer than the maximum length of its type, in bytes.
+    TooLong {
+        field: String,
+        length: usize,
+        max_length: usize,
+    },
+    /// A fixed-size byte array, e.g. `U256`, of the wrong length.
+    WrongLength {
+        field: String,
+        length: usize,
+        expected_length: usize,
+    },
+    /// A sequence with more items than the maximum of its type.
+    TooManyItems {
+        field: String,
+        items: usize,
+        max_items: usize,
+    },
+}
+
+impl FieldViolation {
+    /// Returns the path of the offending field.
+    pub fn get_field(&self) -> &str {
+        match self {
+            FieldViolation::TooLong { field, .. }
+            | FieldViolation::WrongLength { field, .. }
+            | FieldViolation::TooManyItems { field, .. } => field,
+        }
+    }
+
+    // Prepends the name of the enclosing field, or the index of the enclosing sequence item.
+    fn prefix_field(&mut self, prefix: &str) {
+        let field = match self {
+            FieldViolation::TooLong { field, .. }
+            | FieldViolation::WrongLength { field, .. }
+            | FieldViolation::TooManyItems { field, .. } => field,
+        };
+        *field = if field.is_empty() {
+            prefix.to_string()
+        } else if field.starts_with('[') {
+            format!("{prefix}{field}")
+        } else {
+            format!("{prefix}.{field}")
+        };
+    }
+}
+
+impl fmt::Display for FieldViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldViolation::TooLong {
+                field,
+                length,
+                max_length,
+            } => write!(
+                f,
+                "{field} is {length} bytes long, at most {max_length} allowed"
+            ),
+            FieldViolation::WrongLength {
+                field,
+                length,
+                expected_length,
+            } => write!(
+                f,
+                "{field} is {length} bytes long, {expected_length} expected"
+            ),
+            FieldViolation::TooManyItems {
+                field,
+                items,
+                max_items,
+            } => write!(f, "{field} has {items} items, at most {max_items} allowed"),
+        }
+    }
+}
+
+/// Checks the fields of a value against the limits of their Sv2 data types.
+///
+/// Implemented for the Sv2 data types and, with `#[derive(ValidateFields)]`, for the messages of
+/// the subprotocol crates.
+pub trait ValidateFields {
+    /// Appends the violations of `self` to `violations`, with field paths relative to `self`.
+    fn collect_violations(&self, violations: &mut Vec<FieldViolation>);
+
+    /// Appends the violations of `self` to `violations`, as the field `name` of an enclosing
+    /// struct.
+    fn collect_field_violations(&self, name: &str, violations: &mut Vec<FieldViolation>) {
+        let start = violations.len();
+        self.collect_violations(violations);
+        for violation in &mut violations[start..] {
+            violation.prefix_field(name);
+        }
+    }
+
+    /// Returns every field of `self` exceeding the limits of its Sv2 data type.
+    fn validate_fields(&self) -> Result<(), Vec<FieldViolation>> {
+        let mut violations = Vec::new();
+        self.collect_violations(&mut violations);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+macro_rules! impl_validate_fields_without_limits {
+    ($($t:ty),*) => {
+        $(
+            impl ValidateFields for $t {
+                fn collect_violations(&self, _violations: &mut Vec<FieldViolation>) {}
+            }
+        )*
+    };
+}
+
+// `Vec<u8>` is encoded as `BYTES`, which have no length limit of their own.
+impl_validate_fields_without_limits!(bool, u8, u16, U24, u32, u64, f32, Vec<u8>);
+
+impl<const ISFIXED: bool, const SIZE: usize, const HEADERSIZE: usize, const MAXSIZE: usize>
+    ValidateFields for Inner<'_, ISFIXED, SIZE, HEADERSIZE, MAXSIZE>
+{
+    fn collect_violations(&self, violations: &mut Vec<FieldViolation>) {
+        let length = self.as_ref().len();
+        if ISFIXED && length != SIZE {
+            violations.push(FieldViolation::WrongLength {
+                field: String::new(),
+                length,
+                expected_length: SIZE,
+            });
+        } else if !ISFIXED && length > MAXSIZE {
+            violations.push(FieldViolation::TooLong {
+                field: String::new(),
+                length,
+                max_length: MAXSIZE,
+            });
+        }
+    }
+}
+
+// Checks the number of items of a sequence, then each of its items.
+fn collect_seq_violations<T: ValidateFields>(
+    items: &[T],
+    max_items: usize,
+    violations: &mut Vec<FieldViolation>,
+) {
+    if items.len() > max_items {
+        violations.push(FieldViolation::TooManyItems {
+            field: String::new(),
+            items: items.len(),
+            max_items,
+        });
+    }
+    for (i, item) in items.iter().enumerate() {
+        let start = violations.len();
+        item.collect_violations(violations);
+        if violations.len() > start {
+            let index = format!("[{i}]");
+            for violation in &mut violations[start..] {
+                violation.prefix_field(&index);
+            }
+        }
+    }
+}
+
+impl<T: ValidateFields> ValidateFields for Seq0255<'_, T> {
+    fn collect_violations(&self, violations: &mut Vec<FieldViolation>) {
+        collect_seq_violations(&self.0, u8::MAX as usize, violations);
+    }
+}
+
+impl<T: ValidateFields> ValidateFields for Seq064K<'_, T> {
+    fn collect_violations(&self, violations: &mut Vec<FieldViolation>) {
+        collect_seq_violations(&self.0, u16::MAX as usize, violations);
+    }
+}
+
+impl<T: ValidateFields> ValidateFields for Sv2Option<'_, T> {
+    fn collect_violations(&self, violations: &mut Vec<FieldViolation>) {
+        collect_seq_violations(&self.0, 1, violations);
+    }
+}
@@ -25,6 +25,23 @@ mod test_struct {
 
         assert_eq!(deserialized, expected);
     }
+
+    #[test]
+    fn test_struct_with_tail() {
+        let expected = Test {
+            a: 456,
+            b: 9,
+            c: 67_u32.try_into().unwrap(),
+        };
+
+        let mut bytes = to_bytes(expected.clone()).unwrap();
+        bytes.extend_from_slice(&[1, 2, 3]);
+
+        let (deserialized, tail): (Test, _) = from_bytes_with_tail(&mut bytes[..]).unwrap();
+
+        assert_eq!(deserialized, expected);
+        assert_eq!(tail, &[1, 2, 3]);
+    }
 }
 
 mod test_f32 {
@@ -633,3 +650,70 @@ mod test_sv2_option_none {
         assert_eq!(bytes, bytes_2);
     }
 }
+mod test_validate_fields {
+    use super::*;
+    use core::convert::TryInto;
+
+    #[derive(Deserialize, Serialize, ValidateFields, PartialEq, Debug, Clone)]
+    struct Inner<'decoder> {
+        name: Str0255<'decoder>,
+    }
+
+    #[derive(Deserialize, Serialize, ValidateFields, PartialEq, Debug, Clone)]
+    struct Test<'decoder> {
+        a: u32,
+        user_identity: Str0255<'decoder>,
+        merkle_path: Seq0255<'decoder, U256<'decoder>>,
+        inner: Inner<'decoder>,
+    }
+
+    #[test]
+    fn test_validate_fields() {
+        let mut valid = Test {
+            a: 1,
+            user_identity: "user".to_string().try_into().unwrap(),
+            merkle_path: Seq0255::new(vec![[1; 32].into()]).unwrap(),
+            inner: Inner {
+                name: "name".to_string().try_into().unwrap(),
+            },
+        };
+        assert!(valid.validate_fields().is_ok());
+
+        valid.user_identity = Str0255::Owned(vec![b'x'; 300]);
+        valid.merkle_path.0.push(U256::Owned(vec![1; 31]));
+        valid.inner.name = Str0255::Owned(vec![b'x'; 256]);
+        let violations = valid.validate_fields().unwrap_err();
+        assert_eq!(
+            violations,
+            vec![
+                FieldViolation::TooLong {
+                    field: "user_identity".to_string(),
+                    length: 300,
+                    max_length: 255,
+                },
+                FieldViolation::WrongLength {
+                    field: "merkle_path[1]".to_string(),
+                    length: 31,
+                    expected_length: 32,
+                },
+                FieldViolation::TooLong {
+                    field: "inner.name".to_string(),
+                    length: 256,
+                    max_length: 255,
+                },
+            ]
+        );
+        assert_eq!(
+            violations[0].to_string(),
+            "user_identity is 300 bytes long, at most 255 allowed"
+        );
+
+        valid.merkle_path.0 = vec![[1; 32].into(); 256];
+        let violations = valid.validate_fields().unwrap_err();
+        assert!(violations.contains(&FieldViolation::TooManyItems {
+            field: "merkle_path".to_string(),
+            items: 256,
+            max_items: 255,
+        }));
+    }
+}
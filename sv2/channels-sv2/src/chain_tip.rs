@@ -1,5 +1,6 @@
 //! # Chain Tip
 use binary_sv2::U256;
+use bitcoin::{CompactTarget, Network, Target};
 use mining_sv2::SetNewPrevHash as SetNewPrevHashMp;
 use template_distribution_sv2::SetNewPrevHash as SetNewPrevHashTdp;
 
@@ -8,11 +9,16 @@ use template_distribution_sv2::SetNewPrevHash as SetNewPrevHashTdp;
 /// Used for:
 /// - creating non-future jobs
 /// - validating shares.
+///
+/// The timestamp of the parent block (the block at `prev_hash`) is not carried by
+/// `SetNewPrevHash` messages. It is optional, and only needed to apply the minimum difficulty
+/// rule of test networks (see [`ChainTip::network_target`]).
 #[derive(Debug, Clone)]
 pub struct ChainTip {
     prev_hash: U256<'static>,
     nbits: u32,
     min_ntime: u32,
+    parent_timestamp: Option<u32>,
 }
 
 impl ChainTip {
@@ -22,6 +28,7 @@ impl ChainTip {
             prev_hash,
             nbits,
             min_ntime,
+            parent_timestamp: None,
         }
     }
 
@@ -39,6 +46,46 @@ impl ChainTip {
     pub fn min_ntime(&self) -> u32 {
         self.min_ntime
     }
+
+    /// Retrieves the timestamp of the parent block, if known
+    pub fn parent_timestamp(&self) -> Option<u32> {
+        self.parent_timestamp
+    }
+
+    /// Sets the timestamp of the parent block (the block at `prev_hash`)
+    pub fn set_parent_timestamp(&mut self, parent_timestamp: u32) {
+        self.parent_timestamp = Some(parent_timestamp);
+    }
+
+    /// Returns the target a block built on this chain tip with `ntime` must meet on `network`.
+    ///
+    /// This is the target encoded by `nbits`, except for:
+    /// - networks without difficulty retargeting (regtest), where any block meeting the
+    ///   network's maximum attainable target is valid.
+    /// - networks allowing minimum difficulty blocks (testnet3, testnet4), where a block whose
+    ///   `ntime` is more than 20 minutes (twice the target block spacing) after the parent block
+    ///   timestamp may be mined at the minimum difficulty. This rule is only applied if the
+    ///   parent timestamp is known.
+    ///
+    /// It is only used to decide whether a share is a block: share headers are always built with
+    /// `nbits`, which is what the miner hashed.
+    pub fn network_target(&self, network: Network, ntime: u32) -> Target {
+        let params = network.params();
+
+        if params.no_pow_retargeting {
+            return params.max_attainable_target;
+        }
+
+        if params.allow_min_difficulty_blocks {
+            if let Some(parent_timestamp) = self.parent_timestamp {
+                if ntime as u64 > parent_timestamp as u64 + 2 * params.pow_target_spacing {
+                    return params.max_attainable_target;
+                }
+            }
+        }
+
+        Target::from_compact(CompactTarget::from_consensus(self.nbits))
+    }
 }
 
 impl From<SetNewPrevHashTdp<'_>> for ChainTip {
@@ -60,3 +107,57 @@ impl From<SetNewPrevHashMp<'_>> for ChainTip {
         Self::new(prev_hash, nbits, min_ntime)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_target() {
+        // target: 000000000000d7c0000000000000000000000000000000000000000000000000
+        let nbits = 453040064;
+        let parent_timestamp = 1745611105;
+        let mut chain_tip = ChainTip::new([0; 32].into(), nbits, parent_timestamp + 1);
+        let tip_target = Target::from_compact(CompactTarget::from_consensus(nbits));
+        let late_ntime = parent_timestamp + 20 * 60 + 1;
+
+        // the parent timestamp is unknown, so min difficulty blocks can't be detected
+        assert_eq!(
+            chain_tip.network_target(Network::Testnet4, late_ntime),
+            tip_target
+        );
+
+        chain_tip.set_parent_timestamp(parent_timestamp);
+        assert_eq!(chain_tip.parent_timestamp(), Some(parent_timestamp));
+
+        // exactly 20 minutes after the parent block is not enough
+        assert_eq!(
+            chain_tip.network_target(Network::Testnet4, late_ntime - 1),
+            tip_target
+        );
+        assert_eq!(
+            chain_tip.network_target(Network::Testnet4, late_ntime),
+            Network::Testnet4.params().max_attainable_target
+        );
+        assert_eq!(
+            chain_tip.network_target(Network::Testnet, late_ntime),
+            Network::Testnet.params().max_attainable_target
+        );
+
+        // mainnet and signet never allow min difficulty blocks
+        assert_eq!(
+            chain_tip.network_target(Network::Bitcoin, late_ntime),
+            tip_target
+        );
+        assert_eq!(
+            chain_tip.network_target(Network::Signet, late_ntime),
+            tip_target
+        );
+
+        // regtest always accepts its trivial target
+        assert_eq!(
+            chain_tip.network_target(Network::Regtest, parent_timestamp),
+            Network::Regtest.params().max_attainable_target
+        );
+    }
+}
@@ -24,8 +24,9 @@ use bitcoin::{
     consensus::{serialize, Decodable},
     hashes::sha256d::Hash,
     transaction::Version,
-    CompactTarget, OutPoint, Sequence, Target, Transaction, TxIn, TxOut, Witness,
+    CompactTarget, Network, OutPoint, Sequence, Target, Transaction, TxIn, TxOut, Witness,
 };
+use extensions_sv2::ShareRejectDetail;
 use mining_sv2::{
     NewExtendedMiningJob, SetCustomMiningJob, SetCustomMiningJobSuccess,
     SetNewPrevHash as SetNewPrevHashMp, SubmitSharesExtended,
@@ -59,6 +60,7 @@ pub type ExtendedJob<'a> = (NewExtendedMiningJob<'a>, Vec<u8>, Target);
 ///   `job_id`).
 /// - Share accounting for the channel (as tracked by the client).
 /// - The channel's current chain tip.
+/// - The [`Network`] the channel is mining on, used to detect found blocks.
 #[derive(Clone, Debug)]
 pub struct ExtendedChannel<'a> {
     channel_id: u32,
@@ -77,6 +79,7 @@ pub struct ExtendedChannel<'a> {
     stale_jobs: HashMap<u32, ExtendedJob<'a>>,
     share_accounting: ShareAccounting,
     chain_tip: Option<ChainTip>,
+    network: Network,
 }
 
 impl<'a> ExtendedChannel<'a> {
@@ -104,6 +107,7 @@ impl<'a> ExtendedChannel<'a> {
             stale_jobs: HashMap::new(),
             share_accounting: ShareAccounting::new(),
             chain_tip: None,
+            network: Network::Bitcoin,
         }
     }
 
@@ -132,6 +136,19 @@ impl<'a> ExtendedChannel<'a> {
         self.chain_tip.as_ref()
     }
 
+    /// Returns the [`Network`] this channel is mining on.
+    pub fn get_network(&self) -> Network {
+        self.network
+    }
+
+    /// Sets the [`Network`] this channel is mining on.
+    ///
+    /// The network defines which target a share must meet to be considered a block (see
+    /// [`ChainTip::network_target`]). Defaults to [`Network::Bitcoin`].
+    pub fn set_network(&mut self, network: Network) {
+        self.network = network;
+    }
+
     /// Sets the [`ChainTip`].
     pub fn set_chain_tip(&mut self, chain_tip: ChainTip) {
         self.chain_tip = Some(chain_tip);
@@ -223,6 +240,13 @@ impl<'a> ExtendedChannel<'a> {
             .on_share_acknowledgement(new_submits_accepted_count, new_shares_sum);
     }
 
+    /// Updates share accounting based on a `SubmitShares.Error` message from the upstream server,
+    /// with its Share Reject Detail if any. Delegates to [`ShareAccounting::on_share_rejection`].
+    pub fn on_share_rejection(&mut self, share_reject_detail: Option<ShareRejectDetail>) {
+        self.share_accounting
+            .on_share_rejection(share_reject_detail);
+    }
+
     /// Handles a [`NewExtendedMiningJob`] message received from upstream.
     ///
     /// The message could be either directed at this channel, or at a group channel it belongs to.
@@ -522,7 +546,9 @@ impl<'a> ExtendedChannel<'a> {
             .ok_or(ShareValidationError::NoChainTip)?;
 
         let prev_hash = chain_tip.prev_hash();
-        let nbits: CompactTarget = CompactTarget::from_consensus(chain_tip.nbits());
+        // the header carries the nbits the miner was given in SetNewPrevHash, whatever the
+        // target of a block found on this network (see `ChainTip::network_target`)
+        let nbits = CompactTarget::from_consensus(chain_tip.nbits());
 
         // validate when version rolling is not allowed
         if !job.0.version_rolling_allowed {
@@ -550,7 +576,7 @@ impl<'a> ExtendedChannel<'a> {
         let share_hash_target = Target::from_le_bytes(raw_share_hash);
         let share_hash_as_diff = share_hash_target.difficulty_float();
 
-        let network_target = Target::from_compact(nbits);
+        let network_target = chain_tip.network_target(self.network, share.ntime);
         let job_target = job.2;
 
         // print hash_as_target and self.target as human readable hex
@@ -601,6 +627,7 @@ mod tests {
     };
     use binary_sv2::Sv2Option;
     use bitcoin::Target;
+    use extensions_sv2::{JobState, RejectReason, ShareRejectDetail};
     use mining_sv2::{
         NewExtendedMiningJob, SetNewPrevHash as SetNewPrevHashMp, SubmitSharesExtended,
     };
@@ -956,6 +983,43 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_share_rejection_with_detail() {
+        // channel target: 0000ffff00000000000000000000000000000000000000000000000000000000
+        let target = Target::from_be_bytes([
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0xff, 0xff, 0x00, 0x00,
+        ]);
+        let mut channel = ExtendedChannel::new(
+            1,
+            "user_identity".to_string(),
+            vec![0; 24],
+            target,
+            1.0,
+            true,
+            8,
+        );
+
+        // the upstream server rejects a share, explaining why
+        let detail = ShareRejectDetail::new(RejectReason::DoesNotMeetTarget)
+            .with_job_state(JobState::Active)
+            .with_expected_target(target.to_le_bytes());
+        channel.on_share_rejection(Some(detail.clone()));
+        let share_accounting = channel.get_share_accounting();
+        assert_eq!(share_accounting.get_shares_rejected(), 1);
+        assert_eq!(
+            share_accounting.get_last_share_reject_detail(),
+            Some(&detail)
+        );
+
+        // without the extension, the rejection is counted with no detail
+        channel.on_share_rejection(None);
+        let share_accounting = channel.get_share_accounting();
+        assert_eq!(share_accounting.get_shares_rejected(), 2);
+        assert_eq!(share_accounting.get_last_share_reject_detail(), None);
+    }
+
     #[test]
     fn test_share_validation_valid_share() {
         let channel_id = 1;
@@ -6,6 +6,7 @@
 
 use super::HashSet;
 use bitcoin::hashes::sha256d::Hash;
+use extensions_sv2::ShareRejectDetail;
 
 /// The outcome of share validation, as seen by a Mining Client.
 ///
@@ -52,16 +53,21 @@ pub enum ShareValidationError {
 /// **Acceptance phase** (updated by the application layer via [`on_share_acknowledgement`]):
 /// - total accepted shares (confirmed by upstream [`SubmitSharesSuccess`])
 /// - cumulative work from accepted shares
+/// - total rejected shares (reported by upstream `SubmitShares.Error`, see
+///   [`on_share_rejection`]), with the Share Reject Detail of the last one
 ///
 /// [`validate_share`]: super::extended::ExtendedChannel::validate_share
 /// [`track_validated_share`]: ShareAccounting::track_validated_share
 /// [`on_share_acknowledgement`]: ShareAccounting::on_share_acknowledgement
+/// [`on_share_rejection`]: ShareAccounting::on_share_rejection
 /// [`SubmitSharesSuccess`]: mining_sv2::SubmitSharesSuccess
 #[derive(Clone, Debug)]
 pub struct ShareAccounting {
     last_share_sequence_number: u32,
     shares_accepted: u32,
     share_work_sum: f64,
+    shares_rejected: u32,
+    last_share_reject_detail: Option<ShareRejectDetail>,
     seen_shares: HashSet<Hash>,
     best_diff: f64,
 }
@@ -79,6 +85,8 @@ impl ShareAccounting {
             last_share_sequence_number: 0,
             shares_accepted: 0,
             share_work_sum: 0.0,
+            shares_rejected: 0,
+            last_share_reject_detail: None,
             seen_shares: HashSet::new(),
             best_diff: 0.0,
         }
@@ -100,6 +108,17 @@ impl ShareAccounting {
         self.share_work_sum += new_shares_sum;
     }
 
+    /// Updates rejection accounting based on a `SubmitShares.Error` message from the upstream
+    /// server.
+    ///
+    /// `share_reject_detail` is the detail decoded from the TLV fields of the message when the
+    /// Share Reject Detail extension is negotiated, e.g. with
+    /// `parsers_sv2::share_reject_detail_from_tlvs`.
+    pub fn on_share_rejection(&mut self, share_reject_detail: Option<ShareRejectDetail>) {
+        self.shares_rejected += 1;
+        self.last_share_reject_detail = share_reject_detail;
+    }
+
     /// Records a share that passed local validation.
     ///
     /// Adds the hash to the seen set for duplicate detection and updates the last sequence
@@ -134,6 +153,16 @@ impl ShareAccounting {
         self.share_work_sum
     }
 
+    /// Returns the total number of shares rejected by the upstream server.
+    pub fn get_shares_rejected(&self) -> u32 {
+        self.shares_rejected
+    }
+
+    /// Returns the Share Reject Detail of the last rejected share, if the server sent one.
+    pub fn get_last_share_reject_detail(&self) -> Option<&ShareRejectDetail> {
+        self.last_share_reject_detail.as_ref()
+    }
+
     /// Checks if the given share hash has already been seen (duplicate detection).
     pub fn is_share_seen(&self, share_hash: Hash) -> bool {
         self.seen_shares.contains(&share_hash)
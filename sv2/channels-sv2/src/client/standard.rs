@@ -21,8 +21,9 @@ use binary_sv2::{self, Sv2Option};
 use bitcoin::{
     blockdata::block::{Header, Version},
     hashes::sha256d::Hash,
-    CompactTarget, Target,
+    CompactTarget, Network, Target,
 };
+use extensions_sv2::ShareRejectDetail;
 use mining_sv2::{
     NewExtendedMiningJob, NewMiningJob, SetNewPrevHash as SetNewPrevHashMp, SubmitSharesStandard,
 };
@@ -45,6 +46,7 @@ pub type StandardJob<'a> = (NewMiningJob<'a>, Target);
 /// - stale jobs (jobs from previous chain tip, indexed by job_id)
 /// - share accounting state
 /// - chain tip state
+/// - the [`Network`] the channel is mining on
 #[derive(Debug, Clone)]
 pub struct StandardChannel<'a> {
     channel_id: u32,
@@ -58,6 +60,7 @@ pub struct StandardChannel<'a> {
     stale_jobs: HashMap<u32, StandardJob<'a>>,
     share_accounting: ShareAccounting,
     chain_tip: Option<ChainTip>,
+    network: Network,
 }
 
 impl<'a> StandardChannel<'a> {
@@ -81,6 +84,7 @@ impl<'a> StandardChannel<'a> {
             stale_jobs: HashMap::new(),
             share_accounting: ShareAccounting::new(),
             chain_tip: None,
+            network: Network::Bitcoin,
         }
     }
 
@@ -99,6 +103,19 @@ impl<'a> StandardChannel<'a> {
         self.chain_tip.as_ref()
     }
 
+    /// Returns the [`Network`] this channel is mining on.
+    pub fn get_network(&self) -> Network {
+        self.network
+    }
+
+    /// Sets the [`Network`] this channel is mining on.
+    ///
+    /// The network defines which target a share must meet to be considered a block (see
+    /// [`ChainTip::network_target`]). Defaults to [`Network::Bitcoin`].
+    pub fn set_network(&mut self, network: Network) {
+        self.network = network;
+    }
+
     /// Sets the [`ChainTip`]
     pub fn set_chain_tip(&mut self, chain_tip: ChainTip) {
         self.chain_tip = Some(chain_tip);
@@ -179,6 +196,13 @@ impl<'a> StandardChannel<'a> {
             .on_share_acknowledgement(new_submits_accepted_count, new_shares_sum);
     }
 
+    /// Updates share accounting based on a `SubmitShares.Error` message from the upstream server,
+    /// with its Share Reject Detail if any. Delegates to [`ShareAccounting::on_share_rejection`].
+    pub fn on_share_rejection(&mut self, share_reject_detail: Option<ShareRejectDetail>) {
+        self.share_accounting
+            .on_share_rejection(share_reject_detail);
+    }
+
     /// Handles a new group channel job by converting it into a standard job
     /// and activating it in this channel's context.
     ///
@@ -313,6 +337,8 @@ impl<'a> StandardChannel<'a> {
             .ok_or(ShareValidationError::NoChainTip)?;
 
         let prev_hash = chain_tip.prev_hash();
+        // the header carries the nbits the miner was given in SetNewPrevHash, whatever the
+        // target of a block found on this network (see `ChainTip::network_target`)
         let nbits = CompactTarget::from_consensus(chain_tip.nbits());
 
         // create the header for validation
@@ -330,7 +356,7 @@ impl<'a> StandardChannel<'a> {
         let raw_share_hash: [u8; 32] = *share_hash.to_raw_hash().as_ref();
         let share_hash_target = Target::from_le_bytes(raw_share_hash);
         let share_hash_as_diff = share_hash_target.difficulty_float();
-        let network_target = Target::from_compact(nbits);
+        let network_target = chain_tip.network_target(self.network, share.ntime);
 
         let job_target = job.1;
 
@@ -11,7 +11,12 @@
 //! - Channel management for mining servers and clients
 //! - Standard, extended, and group channel support
 //! - Share accounting
+//! - Per-worker share statistics of extended channels, from the `UserIdentity` TLV of the
+//!   Worker-Specific Hashrate Tracking extension
+//! - Share rejection diagnostics of the Share Reject Detail extension
 //! - Job store abstractions
+//! - Difficulty unit conversions (targets, compact `nbits`, pdiff, bdiff and expected work)
+//! - Signet block signing for jobs with a fixed coinbase ([`signet`] module)
 //! - [`client`] module is `no_std` compatible. To enable it build the crate with `no_std` feature.
 #![cfg_attr(feature = "no_std", no_std)]
 
@@ -24,6 +29,9 @@ pub mod server;
 #[cfg(not(feature = "no_std"))]
 pub mod outputs;
 
+#[cfg(not(feature = "no_std"))]
+pub mod signet;
+
 pub mod bip141;
 pub mod chain_tip;
 pub mod client;
@@ -1,6 +1,13 @@
 //! # Channel Error Types
 
-use crate::server::jobs::error::JobFactoryError;
+use crate::server::{
+    jobs::error::{JobFactoryError, JobStoreError},
+    share_accounting::ShareValidationError,
+};
+use mining_sv2::{
+    OpenMiningChannelErrorCode, SetCustomMiningJobErrorCode, SubmitSharesErrorCode,
+    UpdateChannelErrorCode,
+};
 
 #[derive(Debug)]
 pub enum ExtendedChannelError {
@@ -14,6 +21,7 @@ pub enum ExtendedChannelError {
     ExtranoncePrefixTooLarge,
     ScriptSigSizeTooLarge,
     InvalidJobOrigin,
+    JobStoreError(JobStoreError),
 }
 
 #[derive(Debug)]
@@ -35,4 +43,173 @@ pub enum StandardChannelError {
     ChainTipNotSet,
     FailedToConvertToStandardJob,
     ScriptSigSizeTooLarge,
+    JobStoreError(JobStoreError),
+}
+
+// Codes of the channel errors, sent as `Unknown` codes by the error messages whose spec doesn't
+// define a code for the error.
+fn extended_channel_error_code(error: &ExtendedChannelError) -> &'static str {
+    match error {
+        ExtendedChannelError::InvalidNominalHashrate => "invalid-nominal-hashrate",
+        ExtendedChannelError::RequestedMaxTargetOutOfRange => "max-target-out-of-range",
+        ExtendedChannelError::RequestedMinExtranonceSizeTooLarge => {
+            "unsupported-min-extranonce-size"
+        }
+        ExtendedChannelError::TemplateIdNotFound => "template-id-not-found",
+        ExtendedChannelError::JobIdNotFound => "invalid-job-id",
+        ExtendedChannelError::ExtranoncePrefixTooLarge => "extranonce-prefix-too-large",
+        ExtendedChannelError::ScriptSigSizeTooLarge => "script-sig-size-too-large",
+        ExtendedChannelError::InvalidJobOrigin => "invalid-job-origin",
+        ExtendedChannelError::JobFactoryError(_)
+        | ExtendedChannelError::ChainTipNotSet
+        | ExtendedChannelError::JobStoreError(_) => "internal-error",
+    }
+}
+
+fn standard_channel_error_code(error: &StandardChannelError) -> &'static str {
+    match error {
+        StandardChannelError::InvalidNominalHashrate => "invalid-nominal-hashrate",
+        StandardChannelError::RequestedMaxTargetOutOfRange => "max-target-out-of-range",
+        StandardChannelError::TemplateIdNotFound => "template-id-not-found",
+        StandardChannelError::ExtranoncePrefixTooLarge => "extranonce-prefix-too-large",
+        StandardChannelError::ScriptSigSizeTooLarge => "script-sig-size-too-large",
+        StandardChannelError::JobFactoryError(_)
+        | StandardChannelError::ChainTipNotSet
+        | StandardChannelError::FailedToConvertToStandardJob
+        | StandardChannelError::JobStoreError(_) => "internal-error",
+    }
+}
+
+/// Stale shares and shares of evicted jobs are `stale-share`. Errors without a spec-defined code
+/// are [`SubmitSharesErrorCode::Unknown`], e.g. `duplicate-share`.
+impl From<&ShareValidationError> for SubmitSharesErrorCode {
+    fn from(error: &ShareValidationError) -> Self {
+        let unknown = |error_code: &str| SubmitSharesErrorCode::Unknown(error_code.to_string());
+        match error {
+            ShareValidationError::Stale | ShareValidationError::EvictedJob => {
+                SubmitSharesErrorCode::StaleShare
+            }
+            ShareValidationError::InvalidJobId => SubmitSharesErrorCode::InvalidJobId,
+            ShareValidationError::DoesNotMeetTarget => SubmitSharesErrorCode::DifficultyTooLow,
+            ShareValidationError::Invalid => unknown("invalid-share"),
+            ShareValidationError::VersionRollingNotAllowed => {
+                unknown("version-rolling-not-allowed")
+            }
+            ShareValidationError::DuplicateShare => unknown("duplicate-share"),
+            ShareValidationError::InvalidCoinbase => unknown("invalid-coinbase"),
+            ShareValidationError::NoChainTip => unknown("no-chain-tip"),
+            ShareValidationError::BadExtranonceSize => unknown("bad-extranonce-size"),
+            ShareValidationError::JobStoreError(_) => unknown("internal-error"),
+        }
+    }
+}
+
+/// Errors without a spec-defined code are [`OpenMiningChannelErrorCode::Unknown`].
+impl From<&ExtendedChannelError> for OpenMiningChannelErrorCode {
+    fn from(error: &ExtendedChannelError) -> Self {
+        match error {
+            ExtendedChannelError::RequestedMaxTargetOutOfRange => {
+                OpenMiningChannelErrorCode::MaxTargetOutOfRange
+            }
+            ExtendedChannelError::RequestedMinExtranonceSizeTooLarge => {
+                OpenMiningChannelErrorCode::UnsupportedMinExtranonceSize
+            }
+            error => {
+                OpenMiningChannelErrorCode::Unknown(extended_channel_error_code(error).to_string())
+            }
+        }
+    }
+}
+
+/// Errors without a spec-defined code are [`UpdateChannelErrorCode::Unknown`].
+impl From<&ExtendedChannelError> for UpdateChannelErrorCode {
+    fn from(error: &ExtendedChannelError) -> Self {
+        match error {
+            ExtendedChannelError::RequestedMaxTargetOutOfRange => {
+                UpdateChannelErrorCode::MaxTargetOutOfRange
+            }
+            error => {
+                UpdateChannelErrorCode::Unknown(extended_channel_error_code(error).to_string())
+            }
+        }
+    }
+}
+
+/// The spec defines no code for the errors of extended channels, which are all
+/// [`SetCustomMiningJobErrorCode::Unknown`].
+impl From<&ExtendedChannelError> for SetCustomMiningJobErrorCode {
+    fn from(error: &ExtendedChannelError) -> Self {
+        SetCustomMiningJobErrorCode::Unknown(extended_channel_error_code(error).to_string())
+    }
+}
+
+/// Errors without a spec-defined code are [`OpenMiningChannelErrorCode::Unknown`].
+impl From<&StandardChannelError> for OpenMiningChannelErrorCode {
+    fn from(error: &StandardChannelError) -> Self {
+        match error {
+            StandardChannelError::RequestedMaxTargetOutOfRange => {
+                OpenMiningChannelErrorCode::MaxTargetOutOfRange
+            }
+            error => {
+                OpenMiningChannelErrorCode::Unknown(standard_channel_error_code(error).to_string())
+            }
+        }
+    }
+}
+
+/// Errors without a spec-defined code are [`UpdateChannelErrorCode::Unknown`].
+impl From<&StandardChannelError> for UpdateChannelErrorCode {
+    fn from(error: &StandardChannelError) -> Self {
+        match error {
+            StandardChannelError::RequestedMaxTargetOutOfRange => {
+                UpdateChannelErrorCode::MaxTargetOutOfRange
+            }
+            error => {
+                UpdateChannelErrorCode::Unknown(standard_channel_error_code(error).to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes() {
+        assert_eq!(
+            SubmitSharesErrorCode::from(&ShareValidationError::DoesNotMeetTarget),
+            SubmitSharesErrorCode::DifficultyTooLow
+        );
+        assert_eq!(
+            SubmitSharesErrorCode::from(&ShareValidationError::EvictedJob),
+            SubmitSharesErrorCode::StaleShare
+        );
+        assert_eq!(
+            SubmitSharesErrorCode::from(&ShareValidationError::DuplicateShare),
+            SubmitSharesErrorCode::Unknown("duplicate-share".to_string())
+        );
+        assert_eq!(
+            OpenMiningChannelErrorCode::from(&ExtendedChannelError::RequestedMaxTargetOutOfRange),
+            OpenMiningChannelErrorCode::MaxTargetOutOfRange
+        );
+        assert_eq!(
+            OpenMiningChannelErrorCode::from(
+                &ExtendedChannelError::RequestedMinExtranonceSizeTooLarge
+            ),
+            OpenMiningChannelErrorCode::UnsupportedMinExtranonceSize
+        );
+        assert_eq!(
+            UpdateChannelErrorCode::from(&StandardChannelError::RequestedMaxTargetOutOfRange),
+            UpdateChannelErrorCode::MaxTargetOutOfRange
+        );
+        assert_eq!(
+            UpdateChannelErrorCode::from(&ExtendedChannelError::RequestedMinExtranonceSizeTooLarge),
+            UpdateChannelErrorCode::Unknown("unsupported-min-extranonce-size".to_string())
+        );
+        assert_eq!(
+            SetCustomMiningJobErrorCode::from(&ExtendedChannelError::InvalidJobOrigin),
+            SetCustomMiningJobErrorCode::Unknown("invalid-job-origin".to_string())
+        );
+    }
 }
@@ -20,10 +20,16 @@
 //! - **Share Validation and Accounting**: Validates shares submitted by the miner, updating
 //!   internal accounting and detecting duplicates or stale submissions. Determines if a share meets
 //!   the channel or network target and responds accordingly.
+//! - **Worker Accounting**: Keeps per-worker share statistics from the `UserIdentity` TLV of the
+//!   Worker-Specific Hashrate Tracking extension.
 //! - **Chain Tip Management**: Tracks the latest known chain tip (previous hash, timestamp, and
 //!   target) for constructing headers and validating shares.
+//! - **Network Rules**: Applies the block target rules of the configured [`Network`] (e.g.
+//!   testnet minimum difficulty blocks, regtest trivial target) when detecting found blocks.
 //! - **Version Rolling**: Honors server configuration on whether version rolling is permitted,
 //!   validating submitted BIP320 header versions accordingly.
+//! - **Signet Signing**: Optionally signs jobs for a signet challenge when the channel leaves no
+//!   extranonce to roll, so found blocks carry a valid signet solution.
 //!
 //! ## Usage
 //!
@@ -44,9 +50,18 @@ use crate::{
     merkle_root::merkle_root_from_path,
     server::{
         error::ExtendedChannelError,
-        jobs::{extended::ExtendedJob, factory::JobFactory, job_store::JobStore, JobOrigin},
+        jobs::{
+            error::JobStoreError,
+            extended::ExtendedJob,
+            factory::JobFactory,
+            job_store::{JobStore, JobStoreAsync, JobStoreSize},
+            JobOrigin,
+        },
         share_accounting::{ShareAccounting, ShareValidationError, ShareValidationResult},
+        share_reject_detail::ShareDetail,
+        worker_accounting::WorkerAccounting,
     },
+    signet::{has_solution, SignetSigner},
     target::{bytes_to_hex, hash_rate_to_target, u256_to_block_hash},
     MAX_EXTRANONCE_PREFIX_LEN,
 };
@@ -54,10 +69,11 @@ use bitcoin::{
     blockdata::block::{Header, Version},
     hashes::sha256d::Hash,
     transaction::TxOut,
-    CompactTarget, Target,
+    CompactTarget, Network, Target,
 };
+use extensions_sv2::{JobState, ShareRejectDetail, UserIdentity};
 use mining_sv2::{SetCustomMiningJob, SubmitSharesExtended};
-use std::{collections::HashMap, convert::TryInto, marker::PhantomData};
+use std::{convert::TryInto, marker::PhantomData, time::Instant};
 use template_distribution_sv2::{NewTemplate, SetNewPrevHash as SetNewPrevHashTdp};
 use tracing::debug;
 
@@ -70,39 +86,35 @@ use tracing::debug;
 /// - the channel's rollable extranonce size
 /// - the channel's requested max target (limit established by the client)
 /// - the channel's current target
-/// - the channel's mapping between `job_id` and target
 /// - the channel's nominal hashrate
-/// - the channel's [`JobStore`]
+/// - the channel's [`JobStore`] (or [`JobStoreAsync`])
 /// - the channel's [`JobFactory`]
 /// - the channel's [`ShareAccounting`]
+/// - the channel's [`WorkerAccounting`]
 /// - the channel's expected share per minute
 /// - the channel's [`JobFactory`]
 /// - the channel's [`ChainTip`]
+/// - the channel's [`Network`]
 #[derive(Debug)]
-pub struct ExtendedChannel<'a, J>
-where
-    J: JobStore<ExtendedJob<'a>>,
-{
+pub struct ExtendedChannel<'a, J> {
     channel_id: u32,
     user_identity: String,
     extranonce_prefix: Vec<u8>,
     rollable_extranonce_size: u16,
     requested_max_target: Target,
     target: Target,
-    job_id_to_target: HashMap<u32, Target>,
     nominal_hashrate: f32,
     job_store: J,
     job_factory: JobFactory,
     share_accounting: ShareAccounting,
+    worker_accounting: WorkerAccounting,
     expected_share_per_minute: f32,
     chain_tip: Option<ChainTip>,
+    network: Network,
     phantom: PhantomData<&'a ()>,
 }
 
-impl<'a, J> ExtendedChannel<'a, J>
-where
-    J: JobStore<ExtendedJob<'a>>,
-{
+impl<'a, J> ExtendedChannel<'a, J> {
     /// Constructor of `ExtendedChannel` for a Sv2 Pool Server.
     /// Not meant for usage on a Sv2 Job Declaration Client.
     ///
@@ -126,7 +138,10 @@ where
         expected_share_per_minute: f32,
         job_store: J,
         pool_tag_string: String,
-    ) -> Result<Self, ExtendedChannelError> {
+    ) -> Result<Self, ExtendedChannelError>
+    where
+        J: JobStore<ExtendedJob<'a>>,
+    {
         Self::new(
             channel_id,
             user_identity,
@@ -143,6 +158,40 @@ where
         )
     }
 
+    /// Variant of [`ExtendedChannel::new_for_pool`] for channels backed by a [`JobStoreAsync`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_for_pool_async(
+        channel_id: u32,
+        user_identity: String,
+        extranonce_prefix: Vec<u8>,
+        max_target: Target,
+        nominal_hashrate: f32,
+        version_rolling_allowed: bool,
+        rollable_extranonce_size: u16,
+        share_batch_size: usize,
+        expected_share_per_minute: f32,
+        job_store: J,
+        pool_tag_string: String,
+    ) -> Result<Self, ExtendedChannelError>
+    where
+        J: JobStoreAsync<ExtendedJob<'a>>,
+    {
+        Self::new_async(
+            channel_id,
+            user_identity,
+            extranonce_prefix,
+            max_target,
+            nominal_hashrate,
+            version_rolling_allowed,
+            rollable_extranonce_size,
+            share_batch_size,
+            expected_share_per_minute,
+            job_store,
+            Some(pool_tag_string),
+            None,
+        )
+    }
+
     /// Constructor of `ExtendedChannel` for a Sv2 Job Declaration Client.
     /// Not meant for usage on a Sv2 Pool Server.
     ///
@@ -167,7 +216,10 @@ where
         job_store: J,
         pool_tag_string: Option<String>,
         miner_tag_string: String,
-    ) -> Result<Self, ExtendedChannelError> {
+    ) -> Result<Self, ExtendedChannelError>
+    where
+        J: JobStore<ExtendedJob<'a>>,
+    {
         Self::new(
             channel_id,
             user_identity,
@@ -184,7 +236,43 @@ where
         )
     }
 
-    // private constructor
+    /// Variant of [`ExtendedChannel::new_for_job_declaration_client`] for channels backed by a
+    /// [`JobStoreAsync`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_for_job_declaration_client_async(
+        channel_id: u32,
+        user_identity: String,
+        extranonce_prefix: Vec<u8>,
+        max_target: Target,
+        nominal_hashrate: f32,
+        version_rolling_allowed: bool,
+        rollable_extranonce_size: u16,
+        share_batch_size: usize,
+        expected_share_per_minute: f32,
+        job_store: J,
+        pool_tag_string: Option<String>,
+        miner_tag_string: String,
+    ) -> Result<Self, ExtendedChannelError>
+    where
+        J: JobStoreAsync<ExtendedJob<'a>>,
+    {
+        Self::new_async(
+            channel_id,
+            user_identity,
+            extranonce_prefix,
+            max_target,
+            nominal_hashrate,
+            version_rolling_allowed,
+            rollable_extranonce_size,
+            share_batch_size,
+            expected_share_per_minute,
+            job_store,
+            pool_tag_string,
+            Some(miner_tag_string),
+        )
+    }
+
+    // private constructor, for channels backed by a `JobStore`
     #[allow(clippy::too_many_arguments)]
     fn new(
         channel_id: u32,
@@ -199,6 +287,75 @@ where
         job_store: J,
         pool_tag: Option<String>,
         miner_tag: Option<String>,
+    ) -> Result<Self, ExtendedChannelError>
+    where
+        J: JobStore<ExtendedJob<'a>>,
+    {
+        Self::new_with_job_store(
+            channel_id,
+            user_identity,
+            extranonce_prefix,
+            max_target,
+            nominal_hashrate,
+            version_rolling_allowed,
+            rollable_extranonce_size,
+            share_batch_size,
+            expected_share_per_minute,
+            job_store,
+            pool_tag,
+            miner_tag,
+        )
+    }
+
+    // private constructor, for channels backed by a `JobStoreAsync`
+    #[allow(clippy::too_many_arguments)]
+    fn new_async(
+        channel_id: u32,
+        user_identity: String,
+        extranonce_prefix: Vec<u8>,
+        max_target: Target,
+        nominal_hashrate: f32,
+        version_rolling_allowed: bool,
+        rollable_extranonce_size: u16,
+        share_batch_size: usize,
+        expected_share_per_minute: f32,
+        job_store: J,
+        pool_tag: Option<String>,
+        miner_tag: Option<String>,
+    ) -> Result<Self, ExtendedChannelError>
+    where
+        J: JobStoreAsync<ExtendedJob<'a>>,
+    {
+        Self::new_with_job_store(
+            channel_id,
+            user_identity,
+            extranonce_prefix,
+            max_target,
+            nominal_hashrate,
+            version_rolling_allowed,
+            rollable_extranonce_size,
+            share_batch_size,
+            expected_share_per_minute,
+            job_store,
+            pool_tag,
+            miner_tag,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn new_with_job_store(
+        channel_id: u32,
+        user_identity: String,
+        extranonce_prefix: Vec<u8>,
+        max_target: Target,
+        nominal_hashrate: f32,
+        version_rolling_allowed: bool,
+        rollable_extranonce_size: u16,
+        share_batch_size: usize,
+        expected_share_per_minute: f32,
+        job_store: J,
+        pool_tag: Option<String>,
+        miner_tag: Option<String>,
     ) -> Result<Self, ExtendedChannelError> {
         let target =
             match hash_rate_to_target(nominal_hashrate.into(), expected_share_per_minute.into()) {
@@ -238,13 +395,14 @@ where
             rollable_extranonce_size,
             requested_max_target: max_target,
             target,
-            job_id_to_target: HashMap::new(),
             nominal_hashrate,
             job_store,
             job_factory: JobFactory::new(version_rolling_allowed, pool_tag, miner_tag),
             share_accounting: ShareAccounting::new(share_batch_size),
+            worker_accounting: WorkerAccounting::default(),
             expected_share_per_minute,
             chain_tip: None,
+            network: Network::Bitcoin,
             phantom: PhantomData,
         })
     }
@@ -269,6 +427,55 @@ where
         self.chain_tip.as_ref()
     }
 
+    /// Returns the [`Network`] this channel is mining on.
+    pub fn get_network(&self) -> Network {
+        self.network
+    }
+
+    /// Sets the [`Network`] this channel is mining on.
+    ///
+    /// The network defines which target a share must meet to be considered a block (see
+    /// [`ChainTip::network_target`]). Defaults to [`Network::Bitcoin`].
+    pub fn set_network(&mut self, network: Network) {
+        self.network = network;
+    }
+
+    /// Sets the signer used to sign the jobs of this channel on a signet.
+    ///
+    /// The signet solution commits to the whole coinbase, so jobs are only signed if the channel
+    /// leaves no extranonce to roll (a `rollable_extranonce_size` of 0). Non-future jobs are signed
+    /// when created. Future jobs are signed on activation, where the activated job is replaced by a
+    /// signed one with a new `job_id`, so [`ExtendedChannel::get_active_job`] must be sent
+    /// downstream after [`ExtendedChannel::on_set_new_prev_hash`]. The activated job is kept as a
+    /// past job, so shares already in flight for it are still accepted, but they can't be reported
+    /// as blocks.
+    ///
+    /// Since the signet solution commits to the block `version` and `ntime`, only shares that keep
+    /// the job `version` and `min_ntime` can be reported as blocks. Custom jobs can be reported as
+    /// blocks if the Job Declaration Client signed them.
+    ///
+    /// Jobs received via [`ExtendedChannel::on_group_channel_job`] are not signed.
+    pub fn set_signet_signer(&mut self, signet_signer: SignetSigner) {
+        self.job_factory.set_signet_signer(signet_signer);
+    }
+
+    /// Sets the timestamp of the block at the current chain tip.
+    ///
+    /// `SetNewPrevHash` messages don't carry it, so it must be provided after every chain tip
+    /// update for the minimum difficulty rule of test networks to be applied to share validation.
+    pub fn set_chain_tip_parent_timestamp(
+        &mut self,
+        parent_timestamp: u32,
+    ) -> Result<(), ExtendedChannelError> {
+        match self.chain_tip.as_mut() {
+            Some(chain_tip) => {
+                chain_tip.set_parent_timestamp(parent_timestamp);
+                Ok(())
+            }
+            None => Err(ExtendedChannelError::ChainTipNotSet),
+        }
+    }
+
     /// Returns the expected number of shares per minute configured for this channel.
     pub fn get_shares_per_minute(&self) -> f32 {
         self.expected_share_per_minute
@@ -329,12 +536,6 @@ where
         self.target = target;
     }
 
-    /// Returns the job ID for a future job from a template ID, if any.
-    pub fn get_future_job_id_from_template_id(&self, template_id: u64) -> Option<u32> {
-        self.job_store
-            .get_future_job_id_from_template_id(template_id)
-    }
-
     /// Returns the nominal hashrate for this channel.
     pub fn get_nominal_hashrate(&self) -> f32 {
         self.nominal_hashrate
@@ -409,100 +610,90 @@ where
         Ok(())
     }
 
-    /// Returns an owned copy of the currently active job, if any.
-    pub fn get_active_job(&self) -> Option<ExtendedJob<'a>> {
-        // cloning happens inside the job store
-        self.job_store.get_active_job()
+    /// Returns a reference to the share accounting state for this channel.
+    pub fn get_share_accounting(&self) -> &ShareAccounting {
+        &self.share_accounting
     }
 
-    /// Returns an owned copy of a future job from its job ID, if any.
-    pub fn get_future_job(&self, job_id: u32) -> Option<ExtendedJob<'a>> {
-        // cloning happens inside the job store
-        self.job_store.get_future_job(job_id)
+    /// Returns a reference to the per-worker share statistics of this channel.
+    pub fn get_worker_accounting(&self) -> &WorkerAccounting {
+        &self.worker_accounting
     }
 
-    /// Returns an owned copy of a past job from its job ID, if any.
-    pub fn get_past_job(&self, job_id: u32) -> Option<ExtendedJob<'a>> {
-        // cloning happens inside the job store
-        self.job_store.get_past_job(job_id)
+    /// Drops the workers of this channel that submitted no share for the idle timeout of its
+    /// [`WorkerAccounting`], returning how many were dropped.
+    ///
+    /// Idle workers are also dropped while validating shares from workers, so this is only needed
+    /// to drop them from channels that stopped receiving shares, e.g. from a timer.
+    pub fn expire_idle_workers(&mut self) -> usize {
+        self.worker_accounting.expire_idle_workers(Instant::now())
     }
-    /// Returns a reference to the share accounting state for this channel.
-    pub fn get_share_accounting(&self) -> &ShareAccounting {
-        &self.share_accounting
+
+    /// Replaces the per-worker share statistics of this channel, e.g. to change the maximum
+    /// number of tracked workers, their idle timeout or the window of their hashrate estimate.
+    pub fn set_worker_accounting(&mut self, worker_accounting: WorkerAccounting) {
+        self.worker_accounting = worker_accounting;
     }
 
-    /// Updates the channel state with a new template.
-    ///
-    /// If the template is a future template, the chain tip is not used.
-    /// If the template is not a future template, the chain tip must be set.
-    ///
-    /// Only meant for usage on a Sv2 Pool Server or a Sv2 Job Declaration Client,
-    /// but not on mining clients such as Mining Devices or Proxies.
-    ///
-    /// Only meant to be used if REQUIRES_CUSTOM_WORK is NOT set on the connection this channel exists on.
-    /// If this flag is set, on_set_custom_mining_job should be used instead.
-    pub fn on_new_template(
+    // Records the result of a share validation for the worker that submitted it, with the work
+    // added to the channel share accounting by the validation.
+    fn record_worker_share(
+        &mut self,
+        user_identity: Option<&UserIdentity>,
+        result: &Result<ShareValidationResult, ShareValidationError>,
+        share_work_sum_before: f64,
+    ) {
+        let share_work = self.share_accounting.get_share_work_sum() - share_work_sum_before;
+        self.worker_accounting
+            .record_share(user_identity, result, share_work, Instant::now());
+    }
+
+    // Makes sure the job factory doesn't hand out the job IDs of the job store, which may outlive
+    // this channel.
+    fn resume_job_ids(&mut self, last_job_id: Option<u32>) {
+        if let Some(last_job_id) = last_job_id {
+            self.job_factory.resume_job_ids_after(last_job_id);
+        }
+    }
+
+    // Creates a job from a template.
+    fn new_job_from_template(
         &mut self,
         template: NewTemplate<'a>,
         coinbase_reward_outputs: Vec<TxOut>,
-    ) -> Result<(), ExtendedChannelError> {
-        match template.future_template {
-            true => {
-                let new_job = self
-                    .job_factory
-                    .new_extended_job(
-                        self.channel_id,
-                        None,
-                        self.extranonce_prefix.clone(),
-                        template.clone(),
-                        coinbase_reward_outputs,
-                        self.get_full_extranonce_size(),
-                    )
-                    .map_err(ExtendedChannelError::JobFactoryError)?;
-                self.job_store.add_future_job(template.template_id, new_job);
-            }
-            false => {
-                match self.chain_tip.clone() {
-                    // we can only create non-future jobs if we have a chain tip
-                    None => return Err(ExtendedChannelError::ChainTipNotSet),
-                    Some(chain_tip) => {
-                        let new_job = self
-                            .job_factory
-                            .new_extended_job(
-                                self.channel_id,
-                                Some(chain_tip),
-                                self.extranonce_prefix.clone(),
-                                template.clone(),
-                                coinbase_reward_outputs,
-                                self.get_full_extranonce_size(),
-                            )
-                            .map_err(ExtendedChannelError::JobFactoryError)?;
-
-                        // associate the new active job with the current target
-                        self.job_id_to_target
-                            .insert(new_job.get_job_id(), self.target);
-
-                        // add the new active job to the job store
-                        self.job_store.add_active_job(new_job);
-                    }
-                }
-            }
-        }
+    ) -> Result<ExtendedJob<'a>, ExtendedChannelError> {
+        let chain_tip = match template.future_template {
+            true => None,
+            // we can only create non-future jobs if we have a chain tip
+            false => Some(
+                self.chain_tip
+                    .clone()
+                    .ok_or(ExtendedChannelError::ChainTipNotSet)?,
+            ),
+        };
 
-        Ok(())
+        let new_job = self
+            .job_factory
+            .new_extended_job(
+                self.channel_id,
+                chain_tip,
+                self.extranonce_prefix.clone(),
+                template,
+                coinbase_reward_outputs,
+                self.get_full_extranonce_size(),
+            )
+            .map_err(ExtendedChannelError::JobFactoryError)?;
+
+        Ok(new_job)
     }
 
-    /// Used as an alternative to `on_new_template` when an extended job is meant to be broadcast to the group channel,
-    /// instead of multiple extended jobs to different extended channels.
-    ///
-    /// We use this method to update the channel state, so it can validate shares from the job that was broadcast to the group channel.
-    ///
-    /// Only meant to be used if REQUIRES_CUSTOM_WORK is NOT set on the connection this channel exists on.
-    /// If this flag is set, on_set_custom_mining_job should be used instead.
-    pub fn on_group_channel_job(
+    // Binds a job broadcast to the group channel to this channel.
+    //
+    // Returns the template id of the job.
+    fn bind_group_channel_job(
         &mut self,
-        mut extended_job: ExtendedJob<'a>,
-    ) -> Result<(), ExtendedChannelError> {
+        extended_job: &mut ExtendedJob<'a>,
+    ) -> Result<u64, ExtendedChannelError> {
         // make sure the extranonce prefix is associated to the channel's extranonce prefix
         extended_job.set_extranonce_prefix(self.extranonce_prefix.clone());
 
@@ -513,100 +704,63 @@ where
             }
         };
 
-        match extended_job.is_future() {
-            true => {
-                self.job_store.add_future_job(template_id, extended_job);
-            }
-            false => {
-                self.job_id_to_target
-                    .insert(extended_job.get_job_id(), self.target);
-                self.job_store.add_active_job(extended_job);
-            }
-        }
+        Ok(template_id)
+    }
 
-        Ok(())
+    // Returns true if the job was activated before it could be signed, and can be replaced by a
+    // signed job, i.e. it's a job of this channel (not of its group channel) leaving no extranonce
+    // to roll.
+    fn needs_signet_signature(&self, job: &ExtendedJob<'a>) -> bool {
+        self.job_factory.get_signet_signer().is_some()
+            && self.rollable_extranonce_size == 0
+            && job.get_job_message().channel_id == self.channel_id
+            && matches!(job.get_origin(), JobOrigin::NewTemplate(_))
     }
 
-    /// Updates the channel state with a new `SetNewPrevHash` message (Template Distribution
-    /// Protocol variant).
-    ///
-    /// If there are future jobs in the Job Store, it activates the future job matching the
-    /// `template_id` and sets it as the active job.
-    ///
-    /// If there are future jobs in the Job Store, but the template id is not found, returns an
-    /// error.
-    ///
-    /// All past jobs are cleared.
-    pub fn on_set_new_prev_hash(
+    // Recreates the active job with a signet solution for the current chain tip.
+    fn new_signet_signed_job(
         &mut self,
-        set_new_prev_hash: SetNewPrevHashTdp<'a>,
-    ) -> Result<(), ExtendedChannelError> {
-        // clear the job id to target mapping
-        self.job_id_to_target.clear();
-
-        // extended channels dedicated to custom work don't need to keep track of future jobs
-        match self.job_store.has_future_jobs() {
-            false => {
-                // explicitly mark past jobs as stale, because we're not going to
-                // do it implicitly via activate_future_job in case this extended channel is doing custom work
-                self.job_store.mark_past_jobs_as_stale();
+        active_job: ExtendedJob<'a>,
+    ) -> Result<ExtendedJob<'a>, ExtendedChannelError> {
+        let mut template = match active_job.get_origin() {
+            JobOrigin::NewTemplate(template) => template.clone(),
+            JobOrigin::SetCustomMiningJob(_) => {
+                return Err(ExtendedChannelError::InvalidJobOrigin);
             }
-            true => {
-                // try to activate the future job, and also mark past jobs as stale
-                if !self.job_store.activate_future_job(
-                    set_new_prev_hash.template_id,
-                    set_new_prev_hash.header_timestamp,
-                ) {
-                    return Err(ExtendedChannelError::TemplateIdNotFound);
-                }
+        };
+        template.future_template = false;
+        let additional_coinbase_outputs_count =
+            active_job.get_coinbase_outputs().len() - template.coinbase_tx_outputs_count as usize;
+        let additional_coinbase_outputs =
+            active_job.get_coinbase_outputs()[..additional_coinbase_outputs_count].to_vec();
 
-                // associate the new active job with the current target
-                let job_id = self
-                    .job_store
-                    .get_active_job()
-                    .expect("active job must exist")
-                    .get_job_id();
-                self.job_id_to_target.insert(job_id, self.target);
-            }
-        }
+        let signed_job = self
+            .job_factory
+            .new_extended_job(
+                self.channel_id,
+                self.chain_tip.clone(),
+                active_job.get_extranonce_prefix().clone(),
+                template,
+                additional_coinbase_outputs,
+                self.get_full_extranonce_size(),
+            )
+            .map_err(ExtendedChannelError::JobFactoryError)?;
+
+        Ok(signed_job)
+    }
 
+    // Updates the chain tip from a `SetNewPrevHash` message, once the job store has been updated.
+    fn update_chain_tip(&mut self, set_new_prev_hash: SetNewPrevHashTdp<'a>) {
         // clear seen shares, as shares for past chain tip will be rejected as stale
         self.share_accounting.flush_seen_shares();
 
         // update the chain tip
         self.chain_tip = Some(set_new_prev_hash.into());
-
-        Ok(())
     }
 
-    /// Updates the channel state with a new custom mining job.
-    ///
-    /// If there is an active job, it is moved to the past jobs.
-    /// The new custom mining job is then set as the active job.
-    ///
-    /// Assumes SetCustomMiningJob.{prev_hash, nbits, min_ntime} have already been validated.
-    /// Updates the channel's `ChainTip``.
-    ///
-    /// Returns the job id of the new custom mining job.
-    ///
-    /// To be used by a Sv2 Pool Server upon receiving a `SetCustomMiningJob` message.
-    pub fn on_set_custom_mining_job(
-        &mut self,
-        set_custom_mining_job: SetCustomMiningJob<'a>,
-    ) -> Result<u32, ExtendedChannelError> {
-        let new_job = self
-            .job_factory
-            .new_extended_job_from_custom_job(
-                set_custom_mining_job.clone(),
-                self.extranonce_prefix.clone(),
-                self.get_full_extranonce_size(),
-            )
-            .map_err(ExtendedChannelError::JobFactoryError)?;
-
-        let job_id = new_job.get_job_id();
-
-        self.job_store.add_active_job(new_job);
-
+    // Updates the chain tip from a `SetCustomMiningJob` message, once the job created from it has
+    // been added to the job store.
+    fn on_custom_job_added(&mut self, set_custom_mining_job: SetCustomMiningJob<'a>) {
         // update the chain tip
         let set_custom_mining_job_static = set_custom_mining_job.into_static();
         let prev_hash = set_custom_mining_job_static.prev_hash;
@@ -614,61 +768,21 @@ where
         let min_ntime = set_custom_mining_job_static.min_ntime;
         let new_chain_tip = ChainTip::new(prev_hash, nbits, min_ntime);
         self.chain_tip = Some(new_chain_tip);
-
-        // associate the new active job with the current target
-        self.job_id_to_target.insert(job_id, self.target);
-
-        Ok(job_id)
     }
 
-    /// Validates a share.
-    ///
-    /// Updates the channel state with the result of the share validation.
-    pub fn validate_share(
+    // Validates a share against the job it was submitted for, and the target the job store
+    // associates with it.
+    fn validate_share_for_job(
         &mut self,
         share: SubmitSharesExtended,
+        job: ExtendedJob<'a>,
+        job_target: Option<Target>,
+        detail: &mut ShareDetail,
     ) -> Result<ShareValidationResult, ShareValidationError> {
-        let job_id = share.job_id;
-
-        // check if job_id is active job
-        let is_active_job = self
-            .job_store
-            .get_active_job()
-            .is_some_and(|job| job.get_job_id() == job_id);
-
-        // check if job_id is past job
-        let is_past_job = self.job_store.get_past_job(job_id).is_some();
-
-        // check if job_id is stale job
-        let is_stale_job = self.job_store.get_stale_job(job_id).is_some();
-
-        if is_stale_job {
-            return Err(ShareValidationError::Stale);
-        }
-
-        // if job_id is not active, past or stale, return error
-        if !is_active_job && !is_past_job && !is_stale_job {
-            return Err(ShareValidationError::InvalidJobId);
-        };
-
-        let job = if is_active_job {
-            self.job_store
-                .get_active_job()
-                .expect("active job must exist")
-        } else if is_past_job {
-            self.job_store
-                .get_past_job(job_id)
-                .expect("past job must exist")
-        } else {
-            self.job_store
-                .get_stale_job(job_id)
-                .expect("stale job must exist")
-        };
-
-        let job_target = self
-            .job_id_to_target
-            .get(&job_id)
-            .expect("job target must exist");
+        let job_target = job_target.ok_or(ShareValidationError::JobStoreError(
+            JobStoreError::MissingJobTarget(share.job_id),
+        ))?;
+        detail.set_job_target(job_target);
 
         let extranonce_size = share.extranonce.inner_as_ref().len();
         if extranonce_size != self.rollable_extranonce_size as usize {
@@ -701,6 +815,8 @@ where
             .ok_or(ShareValidationError::NoChainTip)?;
 
         let prev_hash = chain_tip.prev_hash();
+        // the header carries the nbits the miner was given in SetNewPrevHash, whatever the
+        // target of a block found on this network (see `ChainTip::network_target`)
         let nbits = CompactTarget::from_consensus(chain_tip.nbits());
 
         // validate when version rolling is not allowed
@@ -725,11 +841,12 @@ where
 
         // convert the header hash to a target type for easy comparison
         let share_hash = header.block_hash();
+        detail.set_share_hash(share_hash.to_raw_hash());
         let raw_share_hash: [u8; 32] = *share_hash.to_raw_hash().as_ref();
         let share_hash_target = Target::from_le_bytes(raw_share_hash);
         let share_hash_as_diff = share_hash_target.difficulty_float();
 
-        let network_target = Target::from_compact(nbits);
+        let network_target = chain_tip.network_target(self.network, share.ntime);
 
         // print hash_as_target and self.target as human readable hex
         let share_hash_target_bytes = share_hash_target.to_be_bytes();
@@ -742,8 +859,17 @@ where
             format!("{:x}", network_target)
         );
 
+        // a signet solution commits to the whole coinbase, the job version and min_ntime, so
+        // shares rolling any of them, or shares for jobs carrying no solution, can't produce a
+        // valid block
+        let is_signet_solution_valid = self.job_factory.get_signet_signer().is_none()
+            || (self.rollable_extranonce_size == 0
+                && has_solution(job.get_coinbase_outputs())
+                && share.version == job.get_version()
+                && Some(share.ntime) == job.get_min_ntime().into_inner());
+
         // check if a block was found
-        if network_target.is_met_by(share_hash) {
+        if is_signet_solution_valid && network_target.is_met_by(share_hash) {
             self.share_accounting.update_share_accounting(
                 job_target.difficulty_float(),
                 share.sequence_number,
@@ -775,7 +901,7 @@ where
         }
 
         // check if the share hash meets the job target
-        if share_hash_target <= *job_target {
+        if share_hash_target <= job_target {
             if self
                 .share_accounting
                 .is_share_seen(share_hash.to_raw_hash())
@@ -799,191 +925,1482 @@ where
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::{
-        chain_tip::ChainTip,
-        server::{
-            error::ExtendedChannelError,
-            extended::ExtendedChannel,
-            jobs::{
-                extended::ExtendedJob,
-                job_store::{DefaultJobStore, JobStore},
-            },
-            share_accounting::{ShareValidationError, ShareValidationResult},
-        },
-    };
-    use binary_sv2::{Sv2Option, U256};
-    use bitcoin::{transaction::TxOut, Amount, ScriptBuf, Target};
-    use mining_sv2::{NewExtendedMiningJob, SetCustomMiningJob, SubmitSharesExtended};
-    use std::convert::TryInto;
-    use template_distribution_sv2::{NewTemplate, SetNewPrevHash};
+impl<'a, J> ExtendedChannel<'a, J>
+where
+    J: JobStore<ExtendedJob<'a>>,
+{
+    /// Returns the job ID for a future job from a template ID, if any.
+    pub fn get_future_job_id_from_template_id(&self, template_id: u64) -> Option<u32> {
+        self.job_store
+            .get_future_job_id_from_template_id(template_id)
+    }
 
-    const SATS_AVAILABLE_IN_TEMPLATE: u64 = 5000000000;
+    /// Returns an owned copy of the currently active job, if any.
+    pub fn get_active_job(&self) -> Option<ExtendedJob<'a>> {
+        // cloning happens inside the job store
+        self.job_store.get_active_job()
+    }
 
-    #[test]
-    fn test_future_job_activation_flow() {
-        // note:
-        // the messages on this test were collected from a sane message flow
-        // we use them as test vectors to assert correct behavior of job creation
-        let channel_id = 1;
-        let user_identity = "user_identity".to_string();
-        let extranonce_prefix = [
-            83, 116, 114, 97, 116, 117, 109, 32, 86, 50, 32, 83, 82, 73, 32, 80, 111, 111, 108, 0,
-            0, 0, 0, 0, 0, 0, 1,
-        ]
-        .to_vec();
-        let max_target = Target::from_le_bytes([0xff; 32]);
-        let expected_share_per_minute = 1.0;
-        let nominal_hashrate = 1.0;
-        let version_rolling_allowed = true;
-        let rollable_extranonce_size = 4u16;
-        let share_batch_size = 100;
-        let job_store = DefaultJobStore::new();
+    /// Returns an owned copy of a future job from its job ID, if any.
+    pub fn get_future_job(&self, job_id: u32) -> Option<ExtendedJob<'a>> {
+        // cloning happens inside the job store
+        self.job_store.get_future_job(job_id)
+    }
 
-        let mut channel = ExtendedChannel::new(
-            channel_id,
-            user_identity,
-            extranonce_prefix,
-            max_target,
-            nominal_hashrate,
-            version_rolling_allowed,
-            rollable_extranonce_size,
-            share_batch_size,
-            expected_share_per_minute,
-            job_store,
-            None,
-            None,
-        )
-        .unwrap();
+    /// Returns an owned copy of a past job from its job ID, if any.
+    pub fn get_past_job(&self, job_id: u32) -> Option<ExtendedJob<'a>> {
+        // cloning happens inside the job store
+        self.job_store.get_past_job(job_id)
+    }
 
-        let template = NewTemplate {
-            template_id: 1,
-            future_template: true,
-            version: 536870912,
-            coinbase_tx_version: 2,
-            coinbase_prefix: vec![82, 0].try_into().unwrap(),
-            coinbase_tx_input_sequence: 4294967295,
-            coinbase_tx_value_remaining: SATS_AVAILABLE_IN_TEMPLATE,
-            coinbase_tx_outputs_count: 1,
-            coinbase_tx_outputs: vec![
-                0, 0, 0, 0, 0, 0, 0, 0, 38, 106, 36, 170, 33, 169, 237, 226, 246, 28, 63, 113, 209,
-                222, 253, 63, 169, 153, 223, 163, 105, 83, 117, 92, 105, 6, 137, 121, 153, 98, 180,
-                139, 235, 216, 54, 151, 78, 140, 249,
-            ]
-            .try_into()
-            .unwrap(),
-            coinbase_tx_locktime: 0,
-            merkle_path: vec![].try_into().unwrap(),
+    /// Returns the number of jobs held by the job store in each state, and an estimate of their
+    /// memory footprint.
+    pub fn get_job_store_size(&self) -> JobStoreSize {
+        self.job_store.get_size()
+    }
+
+    /// Updates the channel state with a new template.
+    ///
+    /// If the template is a future template, the chain tip is not used.
+    /// If the template is not a future template, the chain tip must be set.
+    ///
+    /// Only meant for usage on a Sv2 Pool Server or a Sv2 Job Declaration Client,
+    /// but not on mining clients such as Mining Devices or Proxies.
+    ///
+    /// Only meant to be used if REQUIRES_CUSTOM_WORK is NOT set on the connection this channel exists on.
+    /// If this flag is set, on_set_custom_mining_job should be used instead.
+    pub fn on_new_template(
+        &mut self,
+        template: NewTemplate<'a>,
+        coinbase_reward_outputs: Vec<TxOut>,
+    ) -> Result<(), ExtendedChannelError> {
+        let template_id = template.template_id;
+        self.resume_job_ids(self.job_store.get_last_job_id());
+        let new_job = self.new_job_from_template(template, coinbase_reward_outputs)?;
+        self.add_job(template_id, new_job);
+
+        Ok(())
+    }
+
+    /// Used as an alternative to `on_new_template` when an extended job is meant to be broadcast to the group channel,
+    /// instead of multiple extended jobs to different extended channels.
+    ///
+    /// We use this method to update the channel state, so it can validate shares from the job that was broadcast to the group channel.
+    ///
+    /// Only meant to be used if REQUIRES_CUSTOM_WORK is NOT set on the connection this channel exists on.
+    /// If this flag is set, on_set_custom_mining_job should be used instead.
+    pub fn on_group_channel_job(
+        &mut self,
+        mut extended_job: ExtendedJob<'a>,
+    ) -> Result<(), ExtendedChannelError> {
+        let template_id = self.bind_group_channel_job(&mut extended_job)?;
+        self.add_job(template_id, extended_job);
+
+        Ok(())
+    }
+
+    /// Updates the channel state with a new `SetNewPrevHash` message (Template Distribution
+    /// Protocol variant).
+    ///
+    /// If there are future jobs in the Job Store, it activates the future job matching the
+    /// `template_id` and sets it as the active job.
+    ///
+    /// If there are future jobs in the Job Store, but the template id is not found, returns an
+    /// error.
+    ///
+    /// All past jobs are cleared.
+    pub fn on_set_new_prev_hash(
+        &mut self,
+        set_new_prev_hash: SetNewPrevHashTdp<'a>,
+    ) -> Result<(), ExtendedChannelError> {
+        let template_id = set_new_prev_hash.template_id;
+
+        // extended channels dedicated to custom work don't need to keep track of future jobs
+        let activated_job = match self.job_store.has_future_jobs() {
+            false => {
+                // explicitly mark past jobs as stale, because we're not going to
+                // do it implicitly via activate_future_job in case this extended channel is doing custom work
+                self.job_store.mark_past_jobs_as_stale();
+                None
+            }
+            true => {
+                let job_id = self
+                    .job_store
+                    .get_future_job_id_from_template_id(template_id)
+                    .ok_or(ExtendedChannelError::TemplateIdNotFound)?;
+
+                // associate the job with the current target before it becomes the active job
+                self.job_store.set_job_target(job_id, self.target);
+
+                // try to activate the future job, and also mark past jobs as stale
+                if !self
+                    .job_store
+                    .activate_future_job(template_id, set_new_prev_hash.header_timestamp)
+                {
+                    return Err(ExtendedChannelError::TemplateIdNotFound);
+                }
+                self.job_store.get_active_job()
+            }
         };
 
-        // match the original script format used to generate the coinbase_reward_outputs for the
-        // expected job
-        let pubkey_hash = [
-            235, 225, 183, 220, 194, 147, 204, 170, 14, 231, 67, 168, 111, 137, 223, 130, 88, 194,
-            8, 252,
-        ];
-        let mut script_bytes = vec![0]; // SegWit version 0
-        script_bytes.push(20); // Push 20 bytes (length of pubkey hash)
-        script_bytes.extend_from_slice(&pubkey_hash);
-        let script = ScriptBuf::from(script_bytes);
-        let coinbase_reward_outputs = vec![TxOut {
-            value: Amount::from_sat(SATS_AVAILABLE_IN_TEMPLATE),
-            script_pubkey: script,
-        }];
+        self.update_chain_tip(set_new_prev_hash);
+
+        // the activated job was created before the chain tip was known, so it can't carry a
+        // signet solution yet
+        if let Some(activated_job) = activated_job {
+            if self.needs_signet_signature(&activated_job) {
+                // the unsigned job becomes a past job, so shares already in flight for it are
+                // still accepted
+                self.resume_job_ids(self.job_store.get_last_job_id());
+                let signed_job = self.new_signet_signed_job(activated_job)?;
+                self.add_job(template_id, signed_job);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Updates the channel state with a new custom mining job.
+    ///
+    /// If there is an active job, it is moved to the past jobs.
+    /// The new custom mining job is then set as the active job.
+    ///
+    /// Assumes SetCustomMiningJob.{prev_hash, nbits, min_ntime} have already been validated.
+    /// Updates the channel's `ChainTip``.
+    ///
+    /// Returns the job id of the new custom mining job.
+    ///
+    /// To be used by a Sv2 Pool Server upon receiving a `SetCustomMiningJob` message.
+    pub fn on_set_custom_mining_job(
+        &mut self,
+        set_custom_mining_job: SetCustomMiningJob<'a>,
+    ) -> Result<u32, ExtendedChannelError> {
+        self.resume_job_ids(self.job_store.get_last_job_id());
+        let new_job = self
+            .job_factory
+            .new_extended_job_from_custom_job(
+                set_custom_mining_job.clone(),
+                self.extranonce_prefix.clone(),
+                self.get_full_extranonce_size(),
+            )
+            .map_err(ExtendedChannelError::JobFactoryError)?;
+
+        let job_id = new_job.get_job_id();
+
+        // associate the new active job with the current target
+        self.job_store.set_job_target(job_id, self.target);
+        self.job_store.add_active_job(new_job);
+        self.on_custom_job_added(set_custom_mining_job);
+
+        Ok(job_id)
+    }
+
+    /// Validates a share.
+    ///
+    /// Updates the channel state with the result of the share validation.
+    pub fn validate_share(
+        &mut self,
+        share: SubmitSharesExtended,
+    ) -> Result<ShareValidationResult, ShareValidationError> {
+        self.validate_share_with_detail(share, &mut ShareDetail::default())
+    }
+
+    /// Validates a share like [`ExtendedChannel::validate_share`], returning the Share Reject Detail of
+    /// the share together with the error if it is rejected, to be appended to
+    /// `SubmitShares.Error` when the Share Reject Detail extension is negotiated.
+    pub fn validate_share_with_reject_detail(
+        &mut self,
+        share: SubmitSharesExtended,
+    ) -> Result<ShareValidationResult, (ShareValidationError, ShareRejectDetail)> {
+        let mut detail = ShareDetail::default();
+        self.validate_share_with_detail(share, &mut detail)
+            .map_err(|error| {
+                let reject_detail = detail.to_share_reject_detail(&error, self.target);
+                (error, reject_detail)
+            })
+    }
+
+    // Validates a share, recording in `detail` what the validation found out about it.
+    fn validate_share_with_detail(
+        &mut self,
+        share: SubmitSharesExtended,
+        detail: &mut ShareDetail,
+    ) -> Result<ShareValidationResult, ShareValidationError> {
+        let job_id = share.job_id;
+
+        if self.job_store.get_stale_job(job_id).is_some() {
+            detail.set_job_state(JobState::Stale);
+            return Err(ShareValidationError::Stale);
+        }
+
+        if self.job_store.is_evicted_job(job_id) {
+            detail.set_job_state(JobState::Past);
+            return Err(ShareValidationError::EvictedJob);
+        }
+
+        let job = match self.job_store.get_active_job() {
+            Some(job) if job.get_job_id() == job_id => {
+                detail.set_job_state(JobState::Active);
+                job
+            }
+            _ => {
+                let job = self
+                    .job_store
+                    .get_past_job(job_id)
+                    .ok_or(ShareValidationError::InvalidJobId)?;
+                detail.set_job_state(JobState::Past);
+                job
+            }
+        };
+        let job_target = self.job_store.get_job_target(job_id);
+
+        self.validate_share_for_job(share, job, job_target, detail)
+    }
+
+    /// Validates a share submitted with the `UserIdentity` TLV of the Worker-Specific Hashrate
+    /// Tracking extension, if any, and records its result in the per-worker share statistics.
+    pub fn validate_share_from_worker(
+        &mut self,
+        share: SubmitSharesExtended,
+        user_identity: Option<&UserIdentity>,
+    ) -> Result<ShareValidationResult, ShareValidationError> {
+        self.validate_share_from_worker_with_reject_detail(share, user_identity)
+            .map_err(|(error, _)| error)
+    }
+
+    /// Validates a share like [`ExtendedChannel::validate_share_from_worker`], returning the Share
+    /// Reject Detail of the share together with the error if it is rejected, like
+    /// [`ExtendedChannel::validate_share_with_reject_detail`].
+    pub fn validate_share_from_worker_with_reject_detail(
+        &mut self,
+        share: SubmitSharesExtended,
+        user_identity: Option<&UserIdentity>,
+    ) -> Result<ShareValidationResult, (ShareValidationError, ShareRejectDetail)> {
+        let share_work_sum = self.share_accounting.get_share_work_sum();
+        let mut detail = ShareDetail::default();
+        let result = self.validate_share_with_detail(share, &mut detail);
+        self.record_worker_share(user_identity, &result, share_work_sum);
+        result.map_err(|error| {
+            let reject_detail = detail.to_share_reject_detail(&error, self.target);
+            (error, reject_detail)
+        })
+    }
+
+    // Adds a job to the job store, associating non-future jobs with the current target.
+    fn add_job(&mut self, template_id: u64, job: ExtendedJob<'a>) {
+        match job.is_future() {
+            true => {
+                self.job_store.add_future_job(template_id, job);
+            }
+            false => {
+                self.job_store.set_job_target(job.get_job_id(), self.target);
+                self.job_store.add_active_job(job);
+            }
+        }
+    }
+}
+
+/// Variants of the methods that use the job store, for channels backed by a [`JobStoreAsync`].
+///
+/// Failures of the job store are reported as [`ExtendedChannelError::JobStoreError`] and
+/// [`ShareValidationError::JobStoreError`].
+impl<'a, J> ExtendedChannel<'a, J>
+where
+    J: JobStoreAsync<ExtendedJob<'a>>,
+{
+    /// Async variant of [`ExtendedChannel::get_future_job_id_from_template_id`].
+    pub async fn get_future_job_id_from_template_id_async(
+        &self,
+        template_id: u64,
+    ) -> Result<Option<u32>, ExtendedChannelError> {
+        self.job_store
+            .get_future_job_id_from_template_id(template_id)
+            .await
+            .map_err(ExtendedChannelError::JobStoreError)
+    }
+
+    /// Async variant of [`ExtendedChannel::get_active_job`].
+    pub async fn get_active_job_async(
+        &self,
+    ) -> Result<Option<ExtendedJob<'a>>, ExtendedChannelError> {
+        self.job_store
+            .get_active_job()
+            .await
+            .map_err(ExtendedChannelError::JobStoreError)
+    }
+
+    /// Async variant of [`ExtendedChannel::get_future_job`].
+    pub async fn get_future_job_async(
+        &self,
+        job_id: u32,
+    ) -> Result<Option<ExtendedJob<'a>>, ExtendedChannelError> {
+        self.job_store
+            .get_future_job(job_id)
+            .await
+            .map_err(ExtendedChannelError::JobStoreError)
+    }
+
+    /// Async variant of [`ExtendedChannel::get_past_job`].
+    pub async fn get_past_job_async(
+        &self,
+        job_id: u32,
+    ) -> Result<Option<ExtendedJob<'a>>, ExtendedChannelError> {
+        self.job_store
+            .get_past_job(job_id)
+            .await
+            .map_err(ExtendedChannelError::JobStoreError)
+    }
+
+    /// Async variant of [`ExtendedChannel::get_job_store_size`].
+    pub async fn get_job_store_size_async(&self) -> Result<JobStoreSize, ExtendedChannelError> {
+        self.job_store
+            .get_size()
+            .await
+            .map_err(ExtendedChannelError::JobStoreError)
+    }
+
+    /// Async variant of [`ExtendedChannel::on_new_template`].
+    pub async fn on_new_template_async(
+        &mut self,
+        template: NewTemplate<'a>,
+        coinbase_reward_outputs: Vec<TxOut>,
+    ) -> Result<(), ExtendedChannelError> {
+        let template_id = template.template_id;
+        self.resume_job_ids_async().await?;
+        let new_job = self.new_job_from_template(template, coinbase_reward_outputs)?;
+        self.add_job_async(template_id, new_job).await
+    }
+
+    /// Async variant of [`ExtendedChannel::on_group_channel_job`].
+    pub async fn on_group_channel_job_async(
+        &mut self,
+        mut extended_job: ExtendedJob<'a>,
+    ) -> Result<(), ExtendedChannelError> {
+        let template_id = self.bind_group_channel_job(&mut extended_job)?;
+        self.add_job_async(template_id, extended_job).await
+    }
+
+    /// Async variant of [`ExtendedChannel::on_set_new_prev_hash`].
+    pub async fn on_set_new_prev_hash_async(
+        &mut self,
+        set_new_prev_hash: SetNewPrevHashTdp<'a>,
+    ) -> Result<(), ExtendedChannelError> {
+        let template_id = set_new_prev_hash.template_id;
+
+        // extended channels dedicated to custom work don't need to keep track of future jobs
+        let activated_job = match self
+            .job_store
+            .has_future_jobs()
+            .await
+            .map_err(ExtendedChannelError::JobStoreError)?
+        {
+            false => {
+                self.job_store
+                    .mark_past_jobs_as_stale()
+                    .await
+                    .map_err(ExtendedChannelError::JobStoreError)?;
+                None
+            }
+            true => {
+                let job_id = self
+                    .job_store
+                    .get_future_job_id_from_template_id(template_id)
+                    .await
+                    .map_err(ExtendedChannelError::JobStoreError)?
+                    .ok_or(ExtendedChannelError::TemplateIdNotFound)?;
+
+                // associate the job with the current target before it becomes the active job
+                self.job_store
+                    .set_job_target(job_id, self.target)
+                    .await
+                    .map_err(ExtendedChannelError::JobStoreError)?;
+
+                // try to activate the future job, and also mark past jobs as stale
+                if !self
+                    .job_store
+                    .activate_future_job(template_id, set_new_prev_hash.header_timestamp)
+                    .await
+                    .map_err(ExtendedChannelError::JobStoreError)?
+                {
+                    return Err(ExtendedChannelError::TemplateIdNotFound);
+                }
+                self.job_store
+                    .get_active_job()
+                    .await
+                    .map_err(ExtendedChannelError::JobStoreError)?
+            }
+        };
+
+        self.update_chain_tip(set_new_prev_hash);
+
+        // the activated job was created before the chain tip was known, so it can't carry a
+        // signet solution yet
+        if let Some(activated_job) = activated_job {
+            if self.needs_signet_signature(&activated_job) {
+                self.resume_job_ids_async().await?;
+                let signed_job = self.new_signet_signed_job(activated_job)?;
+                self.add_job_async(template_id, signed_job).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Async variant of [`ExtendedChannel::on_set_custom_mining_job`].
+    pub async fn on_set_custom_mining_job_async(
+        &mut self,
+        set_custom_mining_job: SetCustomMiningJob<'a>,
+    ) -> Result<u32, ExtendedChannelError> {
+        self.resume_job_ids_async().await?;
+        let new_job = self
+            .job_factory
+            .new_extended_job_from_custom_job(
+                set_custom_mining_job.clone(),
+                self.extranonce_prefix.clone(),
+                self.get_full_extranonce_size(),
+            )
+            .map_err(ExtendedChannelError::JobFactoryError)?;
+
+        let job_id = new_job.get_job_id();
+
+        self.add_active_job_async(new_job).await?;
+        self.on_custom_job_added(set_custom_mining_job);
+
+        Ok(job_id)
+    }
+
+    /// Async variant of [`ExtendedChannel::validate_share`].
+    pub async fn validate_share_async(
+        &mut self,
+        share: SubmitSharesExtended<'_>,
+    ) -> Result<ShareValidationResult, ShareValidationError> {
+        self.validate_share_with_detail_async(share, &mut ShareDetail::default())
+            .await
+    }
+
+    /// Async variant of [`ExtendedChannel::validate_share_with_reject_detail`].
+    pub async fn validate_share_with_reject_detail_async(
+        &mut self,
+        share: SubmitSharesExtended<'_>,
+    ) -> Result<ShareValidationResult, (ShareValidationError, ShareRejectDetail)> {
+        let mut detail = ShareDetail::default();
+        self.validate_share_with_detail_async(share, &mut detail)
+            .await
+            .map_err(|error| {
+                let reject_detail = detail.to_share_reject_detail(&error, self.target);
+                (error, reject_detail)
+            })
+    }
+
+    async fn validate_share_with_detail_async(
+        &mut self,
+        share: SubmitSharesExtended<'_>,
+        detail: &mut ShareDetail,
+    ) -> Result<ShareValidationResult, ShareValidationError> {
+        let job_id = share.job_id;
+
+        if self
+            .job_store
+            .get_stale_job(job_id)
+            .await
+            .map_err(ShareValidationError::JobStoreError)?
+            .is_some()
+        {
+            detail.set_job_state(JobState::Stale);
+            return Err(ShareValidationError::Stale);
+        }
+
+        if self
+            .job_store
+            .is_evicted_job(job_id)
+            .await
+            .map_err(ShareValidationError::JobStoreError)?
+        {
+            detail.set_job_state(JobState::Past);
+            return Err(ShareValidationError::EvictedJob);
+        }
+
+        let job = match self
+            .job_store
+            .get_active_job()
+            .await
+            .map_err(ShareValidationError::JobStoreError)?
+        {
+            Some(job) if job.get_job_id() == job_id => {
+                detail.set_job_state(JobState::Active);
+                job
+            }
+            _ => {
+                let job = self
+                    .job_store
+                    .get_past_job(job_id)
+                    .await
+                    .map_err(ShareValidationError::JobStoreError)?
+                    .ok_or(ShareValidationError::InvalidJobId)?;
+                detail.set_job_state(JobState::Past);
+                job
+            }
+        };
+        let job_target = self
+            .job_store
+            .get_job_target(job_id)
+            .await
+            .map_err(ShareValidationError::JobStoreError)?;
+
+        self.validate_share_for_job(share, job, job_target, detail)
+    }
+
+    /// Async variant of [`ExtendedChannel::validate_share_from_worker`].
+    pub async fn validate_share_from_worker_async(
+        &mut self,
+        share: SubmitSharesExtended<'_>,
+        user_identity: Option<&UserIdentity>,
+    ) -> Result<ShareValidationResult, ShareValidationError> {
+        self.validate_share_from_worker_with_reject_detail_async(share, user_identity)
+            .await
+            .map_err(|(error, _)| error)
+    }
+
+    /// Async variant of [`ExtendedChannel::validate_share_from_worker_with_reject_detail`].
+    pub async fn validate_share_from_worker_with_reject_detail_async(
+        &mut self,
+        share: SubmitSharesExtended<'_>,
+        user_identity: Option<&UserIdentity>,
+    ) -> Result<ShareValidationResult, (ShareValidationError, ShareRejectDetail)> {
+        let share_work_sum = self.share_accounting.get_share_work_sum();
+        let mut detail = ShareDetail::default();
+        let result = self
+            .validate_share_with_detail_async(share, &mut detail)
+            .await;
+        self.record_worker_share(user_identity, &result, share_work_sum);
+        result.map_err(|error| {
+            let reject_detail = detail.to_share_reject_detail(&error, self.target);
+            (error, reject_detail)
+        })
+    }
+
+    async fn add_job_async(
+        &mut self,
+        template_id: u64,
+        job: ExtendedJob<'a>,
+    ) -> Result<(), ExtendedChannelError> {
+        match job.is_future() {
+            true => {
+                self.job_store
+                    .add_future_job(template_id, job)
+                    .await
+                    .map_err(ExtendedChannelError::JobStoreError)?;
+            }
+            false => self.add_active_job_async(job).await?,
+        }
+        Ok(())
+    }
+
+    // Adds an active job to the job store, associating it with the current target.
+    async fn add_active_job_async(
+        &mut self,
+        job: ExtendedJob<'a>,
+    ) -> Result<(), ExtendedChannelError> {
+        self.job_store
+            .set_job_target(job.get_job_id(), self.target)
+            .await
+            .map_err(ExtendedChannelError::JobStoreError)?;
+        self.job_store
+            .add_active_job(job)
+            .await
+            .map_err(ExtendedChannelError::JobStoreError)
+    }
+
+    async fn resume_job_ids_async(&mut self) -> Result<(), ExtendedChannelError> {
+        let last_job_id = self
+            .job_store
+            .get_last_job_id()
+            .await
+            .map_err(ExtendedChannelError::JobStoreError)?;
+        self.resume_job_ids(last_job_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        chain_tip::ChainTip,
+        server::{
+            error::ExtendedChannelError,
+            extended::ExtendedChannel,
+            jobs::{
+                extended::ExtendedJob,
+                file_job_store::FileJobStore,
+                job_store::{block_on, DefaultJobStore, JobStore, MemoryJobStore},
+            },
+            share_accounting::{ShareValidationError, ShareValidationResult},
+        },
+        signet::{has_solution, SignetSigner},
+        target::bytes_to_hex,
+    };
+    use binary_sv2::{Sv2Option, U256};
+    use bitcoin::{
+        consensus::deserialize,
+        secp256k1::{Secp256k1, SecretKey},
+        transaction::TxOut,
+        Amount, CompressedPublicKey, Network, ScriptBuf, Target, Transaction,
+    };
+    use extensions_sv2::{JobState, RejectReason, UserIdentity};
+    use mining_sv2::{NewExtendedMiningJob, SetCustomMiningJob, SubmitSharesExtended};
+    use std::convert::TryInto;
+    use template_distribution_sv2::{NewTemplate, SetNewPrevHash};
+
+    const SATS_AVAILABLE_IN_TEMPLATE: u64 = 5000000000;
+
+    #[test]
+    fn test_future_job_activation_flow() {
+        // note:
+        // the messages on this test were collected from a sane message flow
+        // we use them as test vectors to assert correct behavior of job creation
+        let channel_id = 1;
+        let user_identity = "user_identity".to_string();
+        let extranonce_prefix = [
+            83, 116, 114, 97, 116, 117, 109, 32, 86, 50, 32, 83, 82, 73, 32, 80, 111, 111, 108, 0,
+            0, 0, 0, 0, 0, 0, 1,
+        ]
+        .to_vec();
+        let max_target = Target::from_le_bytes([0xff; 32]);
+        let expected_share_per_minute = 1.0;
+        let nominal_hashrate = 1.0;
+        let version_rolling_allowed = true;
+        let rollable_extranonce_size = 4u16;
+        let share_batch_size = 100;
+        let job_store = DefaultJobStore::new();
+
+        let mut channel = ExtendedChannel::new(
+            channel_id,
+            user_identity,
+            extranonce_prefix,
+            max_target,
+            nominal_hashrate,
+            version_rolling_allowed,
+            rollable_extranonce_size,
+            share_batch_size,
+            expected_share_per_minute,
+            job_store,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let template = NewTemplate {
+            template_id: 1,
+            future_template: true,
+            version: 536870912,
+            coinbase_tx_version: 2,
+            coinbase_prefix: vec![82, 0].try_into().unwrap(),
+            coinbase_tx_input_sequence: 4294967295,
+            coinbase_tx_value_remaining: SATS_AVAILABLE_IN_TEMPLATE,
+            coinbase_tx_outputs_count: 1,
+            coinbase_tx_outputs: vec![
+                0, 0, 0, 0, 0, 0, 0, 0, 38, 106, 36, 170, 33, 169, 237, 226, 246, 28, 63, 113, 209,
+                222, 253, 63, 169, 153, 223, 163, 105, 83, 117, 92, 105, 6, 137, 121, 153, 98, 180,
+                139, 235, 216, 54, 151, 78, 140, 249,
+            ]
+            .try_into()
+            .unwrap(),
+            coinbase_tx_locktime: 0,
+            merkle_path: vec![].try_into().unwrap(),
+        };
+
+        // match the original script format used to generate the coinbase_reward_outputs for the
+        // expected job
+        let pubkey_hash = [
+            235, 225, 183, 220, 194, 147, 204, 170, 14, 231, 67, 168, 111, 137, 223, 130, 88, 194,
+            8, 252,
+        ];
+        let mut script_bytes = vec![0]; // SegWit version 0
+        script_bytes.push(20); // Push 20 bytes (length of pubkey hash)
+        script_bytes.extend_from_slice(&pubkey_hash);
+        let script = ScriptBuf::from(script_bytes);
+        let coinbase_reward_outputs = vec![TxOut {
+            value: Amount::from_sat(SATS_AVAILABLE_IN_TEMPLATE),
+            script_pubkey: script,
+        }];
+
+        assert!(!channel.job_store.has_future_jobs());
+        channel
+            .on_new_template(template.clone(), coinbase_reward_outputs)
+            .unwrap();
+        assert!(channel.get_active_job().is_none());
+
+        let future_job_id = channel
+            .get_future_job_id_from_template_id(template.template_id)
+            .unwrap();
+
+        let future_job = channel.get_future_job(future_job_id).unwrap();
+
+        // we know that the provided template + coinbase_reward_outputs should generate this future
+        // job
+        let expected_job = NewExtendedMiningJob {
+            channel_id: 1,
+            job_id: 1,
+            min_ntime: Sv2Option::new(None),
+            version: 536870912,
+            version_rolling_allowed: true,
+            coinbase_tx_prefix: vec![
+                2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                0, 0, 0, 0, 0, 0, 0, 0, 0, 255, 255, 255, 255, 38, 82, 0, 3, 47, 47, 47, 31,
+            ]
+            .try_into()
+            .unwrap(),
+            coinbase_tx_suffix: vec![
+                255, 255, 255, 255, 2, 0, 242, 5, 42, 1, 0, 0, 0, 22, 0, 20, 235, 225, 183, 220,
+                194, 147, 204, 170, 14, 231, 67, 168, 111, 137, 223, 130, 88, 194, 8, 252, 0, 0, 0,
+                0, 0, 0, 0, 0, 38, 106, 36, 170, 33, 169, 237, 226, 246, 28, 63, 113, 209, 222,
+                253, 63, 169, 153, 223, 163, 105, 83, 117, 92, 105, 6, 137, 121, 153, 98, 180, 139,
+                235, 216, 54, 151, 78, 140, 249, 0, 0, 0, 0,
+            ]
+            .try_into()
+            .unwrap(),
+            merkle_path: vec![].try_into().unwrap(),
+        };
+
+        assert_eq!(future_job.get_job_message(), &expected_job);
+
+        let ntime = 1746839905;
+        let set_new_prev_hash = SetNewPrevHash {
+            template_id: 1,
+            prev_hash: [
+                200, 53, 253, 129, 214, 31, 43, 84, 179, 58, 58, 76, 128, 213, 24, 53, 38, 144,
+                205, 88, 172, 20, 251, 22, 217, 141, 21, 221, 21, 0, 0, 0,
+            ]
+            .into(),
+            header_timestamp: ntime,
+            n_bits: 503543726,
+            target: [
+                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                174, 119, 3, 0, 0,
+            ]
+            .into(),
+        };
+
+        channel.on_set_new_prev_hash(set_new_prev_hash).unwrap();
+
+        // we just activated the only future job
+        assert!(!channel.job_store.has_future_jobs());
+
+        let mut previously_future_job = future_job.clone();
+        previously_future_job.activate(ntime);
+
+        let activated_job = channel.get_active_job().unwrap();
+
+        // assert that the activated job is the same as the previously future job
+        assert_eq!(
+            activated_job.get_job_message(),
+            previously_future_job.get_job_message()
+        );
+    }
+
+    #[test]
+    fn test_non_future_job_creation_flow() {
+        // note:
+        // the messages on this test were collected from a sane message flow
+        // we use them as test vectors to assert correct behavior of job creation
+
+        let channel_id = 1;
+        let user_identity = "user_identity".to_string();
+        let extranonce_prefix = [
+            83, 116, 114, 97, 116, 117, 109, 32, 86, 50, 32, 83, 82, 73, 32, 80, 111, 111, 108, 0,
+            0, 0, 0, 0, 0, 0, 1,
+        ]
+        .to_vec();
+        let max_target = Target::from_le_bytes([0xff; 32]);
+        let expected_share_per_minute = 1.0;
+        let nominal_hashrate = 1.0;
+        let version_rolling_allowed = true;
+        let rollable_extranonce_size = 4u16;
+        let share_batch_size = 100;
+        let job_store = DefaultJobStore::new();
+
+        let mut channel = ExtendedChannel::new(
+            channel_id,
+            user_identity,
+            extranonce_prefix,
+            max_target,
+            nominal_hashrate,
+            version_rolling_allowed,
+            rollable_extranonce_size,
+            share_batch_size,
+            expected_share_per_minute,
+            job_store,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let ntime = 1746839905;
+        let prev_hash = [
+            200, 53, 253, 129, 214, 31, 43, 84, 179, 58, 58, 76, 128, 213, 24, 53, 38, 144, 205,
+            88, 172, 20, 251, 22, 217, 141, 21, 221, 21, 0, 0, 0,
+        ]
+        .into();
+        let n_bits = 503543726;
+
+        let chain_tip = ChainTip::new(prev_hash, n_bits, ntime);
+        channel.set_chain_tip(chain_tip);
+
+        let template = NewTemplate {
+            template_id: 1,
+            future_template: false,
+            version: 536870912,
+            coinbase_tx_version: 2,
+            coinbase_prefix: vec![82, 0].try_into().unwrap(),
+            coinbase_tx_input_sequence: 4294967295,
+            coinbase_tx_value_remaining: SATS_AVAILABLE_IN_TEMPLATE,
+            coinbase_tx_outputs_count: 1,
+            coinbase_tx_outputs: vec![
+                0, 0, 0, 0, 0, 0, 0, 0, 38, 106, 36, 170, 33, 169, 237, 226, 246, 28, 63, 113, 209,
+                222, 253, 63, 169, 153, 223, 163, 105, 83, 117, 92, 105, 6, 137, 121, 153, 98, 180,
+                139, 235, 216, 54, 151, 78, 140, 249,
+            ]
+            .try_into()
+            .unwrap(),
+            coinbase_tx_locktime: 0,
+            merkle_path: vec![].try_into().unwrap(),
+        };
+
+        // match the original script format used to generate the coinbase_reward_outputs for the
+        // expected job
+        let pubkey_hash = [
+            235, 225, 183, 220, 194, 147, 204, 170, 14, 231, 67, 168, 111, 137, 223, 130, 88, 194,
+            8, 252,
+        ];
+        let mut script_bytes = vec![0]; // SegWit version 0
+        script_bytes.push(20); // Push 20 bytes (length of pubkey hash)
+        script_bytes.extend_from_slice(&pubkey_hash);
+        let script = ScriptBuf::from(script_bytes);
+        let coinbase_reward_outputs = vec![TxOut {
+            value: Amount::from_sat(SATS_AVAILABLE_IN_TEMPLATE),
+            script_pubkey: script,
+        }];
+
+        channel
+            .on_new_template(template.clone(), coinbase_reward_outputs)
+            .unwrap();
+
+        assert!(!channel.job_store.has_future_jobs());
+
+        let active_job = channel.get_active_job().unwrap().clone();
+
+        // we know that the provided template + coinbase_reward_outputs should generate this
+        // non-future job
+        let expected_job = NewExtendedMiningJob {
+            channel_id: 1,
+            job_id: 1,
+            min_ntime: Sv2Option::new(Some(ntime)),
+            version: 536870912,
+            version_rolling_allowed: true,
+            coinbase_tx_prefix: vec![
+                2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                0, 0, 0, 0, 0, 0, 0, 0, 0, 255, 255, 255, 255, 38, 82, 0, 3, 47, 47, 47, 31,
+            ]
+            .try_into()
+            .unwrap(),
+            coinbase_tx_suffix: vec![
+                255, 255, 255, 255, 2, 0, 242, 5, 42, 1, 0, 0, 0, 22, 0, 20, 235, 225, 183, 220,
+                194, 147, 204, 170, 14, 231, 67, 168, 111, 137, 223, 130, 88, 194, 8, 252, 0, 0, 0,
+                0, 0, 0, 0, 0, 38, 106, 36, 170, 33, 169, 237, 226, 246, 28, 63, 113, 209, 222,
+                253, 63, 169, 153, 223, 163, 105, 83, 117, 92, 105, 6, 137, 121, 153, 98, 180, 139,
+                235, 216, 54, 151, 78, 140, 249, 0, 0, 0, 0,
+            ]
+            .try_into()
+            .unwrap(),
+            merkle_path: vec![].try_into().unwrap(),
+        };
+
+        assert_eq!(active_job.get_job_message(), &expected_job);
+    }
+
+    #[test]
+    fn test_coinbase_reward_outputs_sum_above_template_value() {
+        // note:
+        // the messages on this test were collected from a sane message flow
+        // we use them as test vectors to assert correct behavior of job creation
+
+        let channel_id = 1;
+        let user_identity = "user_identity".to_string();
+        let extranonce_prefix = [
+            83, 116, 114, 97, 116, 117, 109, 32, 86, 50, 32, 83, 82, 73, 32, 80, 111, 111, 108, 0,
+            0, 0, 0, 0, 0, 0, 1,
+        ]
+        .to_vec();
+        let max_target = Target::from_le_bytes([0xff; 32]);
+        let expected_share_per_minute = 1.0;
+        let nominal_hashrate = 1.0;
+        let version_rolling_allowed = true;
+        let rollable_extranonce_size = 4u16;
+        let share_batch_size = 100;
+        let job_store = DefaultJobStore::new();
+
+        let mut channel = ExtendedChannel::new(
+            channel_id,
+            user_identity,
+            extranonce_prefix,
+            max_target,
+            nominal_hashrate,
+            version_rolling_allowed,
+            rollable_extranonce_size,
+            share_batch_size,
+            expected_share_per_minute,
+            job_store,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let template = NewTemplate {
+            template_id: 1,
+            future_template: true,
+            version: 536870912,
+            coinbase_tx_version: 2,
+            coinbase_prefix: vec![82, 0].try_into().unwrap(),
+            coinbase_tx_input_sequence: 4294967295,
+            coinbase_tx_value_remaining: SATS_AVAILABLE_IN_TEMPLATE,
+            coinbase_tx_outputs_count: 1,
+            coinbase_tx_outputs: vec![
+                0, 0, 0, 0, 0, 0, 0, 0, 38, 106, 36, 170, 33, 169, 237, 226, 246, 28, 63, 113, 209,
+                222, 253, 63, 169, 153, 223, 163, 105, 83, 117, 92, 105, 6, 137, 121, 153, 98, 180,
+                139, 235, 216, 54, 151, 78, 140, 249,
+            ]
+            .try_into()
+            .unwrap(),
+            coinbase_tx_locktime: 0,
+            merkle_path: vec![].try_into().unwrap(),
+        };
+
+        let pubkey_hash = [
+            235, 225, 183, 220, 194, 147, 204, 170, 14, 231, 67, 168, 111, 137, 223, 130, 88, 194,
+            8, 252,
+        ];
+        let mut script_bytes = vec![0]; // SegWit version 0
+        script_bytes.push(20); // Push 20 bytes (length of pubkey hash)
+        script_bytes.extend_from_slice(&pubkey_hash);
+        let script = ScriptBuf::from(script_bytes);
+
+        let invalid_coinbase_reward_outputs = vec![TxOut {
+            value: Amount::from_sat(SATS_AVAILABLE_IN_TEMPLATE + 1), /* oops: one too many extra
+                                                                      * sats */
+            script_pubkey: script,
+        }];
+
+        let res = channel.on_new_template(template.clone(), invalid_coinbase_reward_outputs);
+
+        assert!(res.is_err());
+        assert!(!channel.job_store.has_future_jobs());
+    }
+
+    #[test]
+    fn test_share_validation_block_found() {
+        // note:
+        // the messages on this test were collected from a sane message flow
+        // we use them as test vectors to assert correct behavior of job creation and share
+        // validation
+
+        let channel_id = 1;
+        let user_identity = "user_identity".to_string();
+        let extranonce_prefix = [
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+        ]
+        .to_vec();
+        let max_target = Target::from_le_bytes([0xff; 32]);
+        let expected_share_per_minute = 1.0;
+        let nominal_hashrate = 1.0;
+        let version_rolling_allowed = true;
+        let rollable_extranonce_size = 8u16;
+        let share_batch_size = 100;
+        let job_store = DefaultJobStore::new();
+
+        let mut channel = ExtendedChannel::new(
+            channel_id,
+            user_identity,
+            extranonce_prefix,
+            max_target,
+            nominal_hashrate,
+            version_rolling_allowed,
+            rollable_extranonce_size,
+            share_batch_size,
+            expected_share_per_minute,
+            job_store,
+            None,
+            None,
+        )
+        .unwrap();
+
+        // channel target: 04325c53ef368eb04325c53ef368eb04325c53ef368eb04325c53ef368eb0431
+
+        let template_id = 1;
+        let template = NewTemplate {
+            template_id,
+            future_template: false,
+            version: 536870912,
+            coinbase_tx_version: 2,
+            coinbase_prefix: vec![82, 0].try_into().unwrap(),
+            coinbase_tx_input_sequence: 4294967295,
+            coinbase_tx_value_remaining: SATS_AVAILABLE_IN_TEMPLATE,
+            coinbase_tx_outputs_count: 1,
+            coinbase_tx_outputs: vec![
+                0, 0, 0, 0, 0, 0, 0, 0, 38, 106, 36, 170, 33, 169, 237, 226, 246, 28, 63, 113, 209,
+                222, 253, 63, 169, 153, 223, 163, 105, 83, 117, 92, 105, 6, 137, 121, 153, 98, 180,
+                139, 235, 216, 54, 151, 78, 140, 249,
+            ]
+            .try_into()
+            .unwrap(),
+            coinbase_tx_locktime: 0,
+            merkle_path: vec![].try_into().unwrap(),
+        };
+
+        // match the original script format used to generate the coinbase_reward_outputs for the
+        // expected job
+        let pubkey_hash = [
+            235, 225, 183, 220, 194, 147, 204, 170, 14, 231, 67, 168, 111, 137, 223, 130, 88, 194,
+            8, 252,
+        ];
+        let mut script_bytes = vec![0]; // SegWit version 0
+        script_bytes.push(20); // Push 20 bytes (length of pubkey hash)
+        script_bytes.extend_from_slice(&pubkey_hash);
+        let script = ScriptBuf::from(script_bytes);
+        let coinbase_reward_outputs = vec![TxOut {
+            value: Amount::from_sat(SATS_AVAILABLE_IN_TEMPLATE),
+            script_pubkey: script,
+        }];
+
+        // network target: 7fffff0000000000000000000000000000000000000000000000000000000000
+        let ntime = 1745596910;
+        let prev_hash = [
+            251, 175, 106, 40, 35, 87, 122, 90, 58, 51, 78, 32, 202, 236, 228, 36, 154, 174, 206,
+            144, 147, 195, 21, 224, 195, 103, 214, 189, 51, 190, 24, 98,
+        ]
+        .into();
+        let n_bits = 545259519;
+        let chain_tip = ChainTip::new(prev_hash, n_bits, ntime);
+        channel.set_chain_tip(chain_tip);
+
+        // prepare channel with non-future job
+        channel
+            .on_new_template(template.clone(), coinbase_reward_outputs)
+            .unwrap();
+
+        // this share has hash 4c68f79a585c8b609e9b43113f73311eada20ec88a70a999406267db3499f1d9
+        // which satisfies network target
+        // 7fffff0000000000000000000000000000000000000000000000000000000000
+        let share_valid_block = SubmitSharesExtended {
+            channel_id,
+            sequence_number: 0,
+            job_id: 1,
+            nonce: 8,
+            ntime: 1745596971,
+            version: 536870912,
+            extranonce: vec![1, 0, 0, 0, 0, 0, 0, 0].try_into().unwrap(),
+        };
+
+        let res = channel.validate_share(share_valid_block);
+
+        assert!(matches!(
+            res,
+            Ok(ShareValidationResult::BlockFound(_, _, _))
+        ));
+    }
+
+    #[test]
+    fn test_share_validation_does_not_meet_target() {
+        // note:
+        // the messages on this test were collected from a sane message flow
+        // we use them as test vectors to assert correct behavior of job creation and share
+        // validation
+
+        let channel_id = 1;
+        let user_identity = "user_identity".to_string();
+        let extranonce_prefix = [
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+        ]
+        .to_vec();
+        let max_target = Target::from_le_bytes([0xff; 32]);
+        let expected_share_per_minute = 1.0;
+        let nominal_hashrate = 100.0; // bigger hashrate to get higher difficulty
+        let version_rolling_allowed = true;
+        let rollable_extranonce_size = 8u16;
+        let share_batch_size = 100;
+        let job_store = DefaultJobStore::new();
+
+        let mut channel = ExtendedChannel::new(
+            channel_id,
+            user_identity,
+            extranonce_prefix,
+            max_target,
+            nominal_hashrate,
+            version_rolling_allowed,
+            rollable_extranonce_size,
+            share_batch_size,
+            expected_share_per_minute,
+            job_store,
+            None,
+            None,
+        )
+        .unwrap();
+
+        // channel target: 000aebbc990fff5144366f000aebbc990fff5144366f000aebbc990fff514435
+
+        let template_id = 1;
+        let template = NewTemplate {
+            template_id,
+            future_template: false,
+            version: 536870912,
+            coinbase_tx_version: 2,
+            coinbase_prefix: vec![82, 0].try_into().unwrap(),
+            coinbase_tx_input_sequence: 4294967295,
+            coinbase_tx_value_remaining: SATS_AVAILABLE_IN_TEMPLATE,
+            coinbase_tx_outputs_count: 1,
+            coinbase_tx_outputs: vec![
+                0, 0, 0, 0, 0, 0, 0, 0, 38, 106, 36, 170, 33, 169, 237, 226, 246, 28, 63, 113, 209,
+                222, 253, 63, 169, 153, 223, 163, 105, 83, 117, 92, 105, 6, 137, 121, 153, 98, 180,
+                139, 235, 216, 54, 151, 78, 140, 249,
+            ]
+            .try_into()
+            .unwrap(),
+            coinbase_tx_locktime: 0,
+            merkle_path: vec![].try_into().unwrap(),
+        };
+
+        // match the original script format used to generate the coinbase_reward_outputs for the
+        // expected job
+        let pubkey_hash = [
+            235, 225, 183, 220, 194, 147, 204, 170, 14, 231, 67, 168, 111, 137, 223, 130, 88, 194,
+            8, 252,
+        ];
+        let mut script_bytes = vec![0]; // SegWit version 0
+        script_bytes.push(20); // Push 20 bytes (length of pubkey hash)
+        script_bytes.extend_from_slice(&pubkey_hash);
+        let script = ScriptBuf::from(script_bytes);
+        let coinbase_reward_outputs = vec![TxOut {
+            value: Amount::from_sat(SATS_AVAILABLE_IN_TEMPLATE),
+            script_pubkey: script,
+        }];
+
+        // network target: 000000000000d7c0000000000000000000000000000000000000000000000000
+        let ntime = 1745596910;
+        let prev_hash = [
+            154, 124, 239, 231, 221, 122, 160, 173, 164, 175, 87, 33, 74, 214, 191, 107, 73, 34, 0,
+            162, 227, 16, 44, 40, 33, 73, 0, 0, 0, 0, 0, 0,
+        ]
+        .into();
+        let n_bits = 453040064;
+        let chain_tip = ChainTip::new(prev_hash, n_bits, ntime);
+        channel.set_chain_tip(chain_tip);
+
+        // prepare channel with non-future job
+        channel
+            .on_new_template(template.clone(), coinbase_reward_outputs)
+            .unwrap();
+
+        // this share has hash d5767872f3a26e7f9f21cd968f27cfdb8b4061bb9ce0959852594ee8620f4efb
+        // which does not meet the channel target
+        // 000aebbc990fff5144366f000aebbc990fff5144366f000aebbc990fff514435
+        let share_low_diff = SubmitSharesExtended {
+            channel_id,
+            sequence_number: 0,
+            job_id: 1,
+            nonce: 0,
+            ntime: 1745596971,
+            version: 536870912,
+            extranonce: vec![1, 0, 0, 0, 0, 0, 0, 0].try_into().unwrap(),
+        };
+
+        let res = channel.validate_share(share_low_diff);
+
+        assert!(matches!(
+            res.unwrap_err(),
+            ShareValidationError::DoesNotMeetTarget
+        ));
+    }
+
+    #[test]
+    fn test_share_validation_with_reject_detail() {
+        // note:
+        // the messages on this test were collected from a sane message flow
+        // we use them as test vectors to assert correct behavior of job creation and share
+        // validation
+
+        let channel_id = 1;
+        let user_identity = "user_identity".to_string();
+        let extranonce_prefix = [
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+        ]
+        .to_vec();
+        let max_target = Target::from_le_bytes([0xff; 32]);
+        let expected_share_per_minute = 1.0;
+        let nominal_hashrate = 100.0; // bigger hashrate to get higher difficulty
+        let version_rolling_allowed = true;
+        let rollable_extranonce_size = 8u16;
+        let share_batch_size = 100;
+        let job_store = DefaultJobStore::new();
+
+        let mut channel = ExtendedChannel::new(
+            channel_id,
+            user_identity,
+            extranonce_prefix,
+            max_target,
+            nominal_hashrate,
+            version_rolling_allowed,
+            rollable_extranonce_size,
+            share_batch_size,
+            expected_share_per_minute,
+            job_store,
+            None,
+            None,
+        )
+        .unwrap();
+
+        // channel target: 000aebbc990fff5144366f000aebbc990fff5144366f000aebbc990fff514435
+
+        let template_id = 1;
+        let template = NewTemplate {
+            template_id,
+            future_template: false,
+            version: 536870912,
+            coinbase_tx_version: 2,
+            coinbase_prefix: vec![82, 0].try_into().unwrap(),
+            coinbase_tx_input_sequence: 4294967295,
+            coinbase_tx_value_remaining: SATS_AVAILABLE_IN_TEMPLATE,
+            coinbase_tx_outputs_count: 1,
+            coinbase_tx_outputs: vec![
+                0, 0, 0, 0, 0, 0, 0, 0, 38, 106, 36, 170, 33, 169, 237, 226, 246, 28, 63, 113, 209,
+                222, 253, 63, 169, 153, 223, 163, 105, 83, 117, 92, 105, 6, 137, 121, 153, 98, 180,
+                139, 235, 216, 54, 151, 78, 140, 249,
+            ]
+            .try_into()
+            .unwrap(),
+            coinbase_tx_locktime: 0,
+            merkle_path: vec![].try_into().unwrap(),
+        };
+
+        // match the original script format used to generate the coinbase_reward_outputs for the
+        // expected job
+        let pubkey_hash = [
+            235, 225, 183, 220, 194, 147, 204, 170, 14, 231, 67, 168, 111, 137, 223, 130, 88, 194,
+            8, 252,
+        ];
+        let mut script_bytes = vec![0]; // SegWit version 0
+        script_bytes.push(20); // Push 20 bytes (length of pubkey hash)
+        script_bytes.extend_from_slice(&pubkey_hash);
+        let script = ScriptBuf::from(script_bytes);
+        let coinbase_reward_outputs = vec![TxOut {
+            value: Amount::from_sat(SATS_AVAILABLE_IN_TEMPLATE),
+            script_pubkey: script,
+        }];
+
+        // network target: 000000000000d7c0000000000000000000000000000000000000000000000000
+        let ntime = 1745596910;
+        let prev_hash = [
+            154, 124, 239, 231, 221, 122, 160, 173, 164, 175, 87, 33, 74, 214, 191, 107, 73, 34, 0,
+            162, 227, 16, 44, 40, 33, 73, 0, 0, 0, 0, 0, 0,
+        ]
+        .into();
+        let n_bits = 453040064;
+        let chain_tip = ChainTip::new(prev_hash, n_bits, ntime);
+        channel.set_chain_tip(chain_tip);
+
+        // prepare channel with non-future job
+        channel
+            .on_new_template(template.clone(), coinbase_reward_outputs)
+            .unwrap();
+
+        // this share has hash d5767872f3a26e7f9f21cd968f27cfdb8b4061bb9ce0959852594ee8620f4efb
+        // which does not meet the channel target
+        // 000aebbc990fff5144366f000aebbc990fff5144366f000aebbc990fff514435
+        let share_low_diff = SubmitSharesExtended {
+            channel_id,
+            sequence_number: 0,
+            job_id: 1,
+            nonce: 0,
+            ntime: 1745596971,
+            version: 536870912,
+            extranonce: vec![1, 0, 0, 0, 0, 0, 0, 0].try_into().unwrap(),
+        };
+
+        let res = channel.validate_share_with_reject_detail(share_low_diff.clone());
+
+        let (error, detail) = res.unwrap_err();
+        assert!(matches!(error, ShareValidationError::DoesNotMeetTarget));
+        assert_eq!(detail.reason, RejectReason::DoesNotMeetTarget);
+        assert_eq!(detail.job_state, Some(JobState::Active));
+        assert_eq!(
+            detail.expected_target,
+            Some(channel.get_target().to_le_bytes())
+        );
+        let mut share_hash = detail.share_hash.unwrap();
+        share_hash.reverse();
+        assert_eq!(
+            bytes_to_hex(&share_hash),
+            "d5767872f3a26e7f9f21cd968f27cfdb8b4061bb9ce0959852594ee8620f4efb"
+        );
+
+        // shares of unknown jobs are checked against the channel target
+        let share_unknown_job = SubmitSharesExtended {
+            job_id: 42,
+            ..share_low_diff
+        };
+        let (error, detail) = channel
+            .validate_share_with_reject_detail(share_unknown_job)
+            .unwrap_err();
+        assert!(matches!(error, ShareValidationError::InvalidJobId));
+        assert_eq!(detail.reason, RejectReason::InvalidJobId);
+        assert_eq!(detail.job_state, Some(JobState::Unknown));
+        assert_eq!(detail.share_hash, None);
+    }
+
+    #[test]
+    fn test_share_validation_valid_share() {
+        // note:
+        // the messages on this test were collected from a sane message flow
+        // we use them as test vectors to assert correct behavior of job creation and share
+        // validation
+
+        let channel_id = 1;
+        let user_identity = "user_identity".to_string();
+        let extranonce_prefix = [
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+        ]
+        .to_vec();
+        let max_target = Target::from_le_bytes([0xff; 32]);
+        let expected_share_per_minute = 1.0;
+        let nominal_hashrate = 1_000.0; // bigger hashrate to get higher difficulty
+        let version_rolling_allowed = true;
+        let rollable_extranonce_size = 8u16;
+        let share_batch_size = 100;
+        let job_store = DefaultJobStore::new();
+
+        let mut channel = ExtendedChannel::new(
+            channel_id,
+            user_identity,
+            extranonce_prefix,
+            max_target,
+            nominal_hashrate,
+            version_rolling_allowed,
+            rollable_extranonce_size,
+            share_batch_size,
+            expected_share_per_minute,
+            job_store,
+            None,
+            None,
+        )
+        .unwrap();
+
+        // channel target is:
+        // 0001179d9861a761ffdadd11c307c4fc04eea3a418f7d687584e4434af158205
+
+        let template_id = 1;
+        let template = NewTemplate {
+            template_id,
+            future_template: false,
+            version: 536870912,
+            coinbase_tx_version: 2,
+            coinbase_prefix: vec![82, 0].try_into().unwrap(),
+            coinbase_tx_input_sequence: 4294967295,
+            coinbase_tx_value_remaining: SATS_AVAILABLE_IN_TEMPLATE,
+            coinbase_tx_outputs_count: 1,
+            coinbase_tx_outputs: vec![
+                0, 0, 0, 0, 0, 0, 0, 0, 38, 106, 36, 170, 33, 169, 237, 226, 246, 28, 63, 113, 209,
+                222, 253, 63, 169, 153, 223, 163, 105, 83, 117, 92, 105, 6, 137, 121, 153, 98, 180,
+                139, 235, 216, 54, 151, 78, 140, 249,
+            ]
+            .try_into()
+            .unwrap(),
+            coinbase_tx_locktime: 0,
+            merkle_path: vec![].try_into().unwrap(),
+        };
+
+        // match the original script format used to generate the coinbase_reward_outputs for the
+        // expected job
+        let pubkey_hash = [
+            235, 225, 183, 220, 194, 147, 204, 170, 14, 231, 67, 168, 111, 137, 223, 130, 88, 194,
+            8, 252,
+        ];
+        let mut script_bytes = vec![0]; // SegWit version 0
+        script_bytes.push(20); // Push 20 bytes (length of pubkey hash)
+        script_bytes.extend_from_slice(&pubkey_hash);
+        let script = ScriptBuf::from(script_bytes);
+        let coinbase_reward_outputs = vec![TxOut {
+            value: Amount::from_sat(SATS_AVAILABLE_IN_TEMPLATE),
+            script_pubkey: script,
+        }];
+
+        // network tarkget is: 000000000000d7c0000000000000000000000000000000000000000000000000
+        let n_bits = 453040064;
+        let ntime = 1745611105;
+        let prev_hash = [
+            23, 205, 72, 134, 153, 86, 220, 153, 224, 28, 216, 146, 228, 120, 227, 157, 213, 99,
+            160, 163, 128, 59, 139, 190, 158, 62, 0, 0, 0, 0, 0, 0,
+        ]
+        .into();
+        let chain_tip = ChainTip::new(prev_hash, n_bits, ntime);
+        channel.set_chain_tip(chain_tip);
 
-        assert!(!channel.job_store.has_future_jobs());
+        // prepare channel with non-future job
         channel
             .on_new_template(template.clone(), coinbase_reward_outputs)
             .unwrap();
-        assert!(channel.get_active_job().is_none());
-
-        let future_job_id = channel
-            .get_future_job_id_from_template_id(template.template_id)
-            .unwrap();
 
-        let future_job = channel.get_future_job(future_job_id).unwrap();
-
-        // we know that the provided template + coinbase_reward_outputs should generate this future
-        // job
-        let expected_job = NewExtendedMiningJob {
-            channel_id: 1,
+        // this share has hash 000004f9d35777e4d56eedc20b1d05d251a7c0ed0b4e3013b5a809852844e218
+        // which does meet the channel target
+        // 0001179d9861a761ffdadd11c307c4fc04eea3a418f7d687584e4434af158205
+        // but does not meet network target
+        // 000000000000d7c0000000000000000000000000000000000000000000000000
+        let valid_share = SubmitSharesExtended {
+            channel_id,
+            sequence_number: 1,
             job_id: 1,
-            min_ntime: Sv2Option::new(None),
+            nonce: 51208,
+            ntime: 1745611105,
             version: 536870912,
-            version_rolling_allowed: true,
-            coinbase_tx_prefix: vec![
-                2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                0, 0, 0, 0, 0, 0, 0, 0, 0, 255, 255, 255, 255, 38, 82, 0, 3, 47, 47, 47, 31,
-            ]
-            .try_into()
-            .unwrap(),
-            coinbase_tx_suffix: vec![
-                255, 255, 255, 255, 2, 0, 242, 5, 42, 1, 0, 0, 0, 22, 0, 20, 235, 225, 183, 220,
-                194, 147, 204, 170, 14, 231, 67, 168, 111, 137, 223, 130, 88, 194, 8, 252, 0, 0, 0,
-                0, 0, 0, 0, 0, 38, 106, 36, 170, 33, 169, 237, 226, 246, 28, 63, 113, 209, 222,
-                253, 63, 169, 153, 223, 163, 105, 83, 117, 92, 105, 6, 137, 121, 153, 98, 180, 139,
-                235, 216, 54, 151, 78, 140, 249, 0, 0, 0, 0,
-            ]
-            .try_into()
-            .unwrap(),
-            merkle_path: vec![].try_into().unwrap(),
+            extranonce: vec![1, 0, 0, 0, 0, 0, 0, 0].try_into().unwrap(),
         };
 
-        assert_eq!(future_job.get_job_message(), &expected_job);
+        let res = channel.validate_share(valid_share);
+        assert!(matches!(res, Ok(ShareValidationResult::Valid(_))));
 
-        let ntime = 1746839905;
-        let set_new_prev_hash = SetNewPrevHash {
-            template_id: 1,
-            prev_hash: [
-                200, 53, 253, 129, 214, 31, 43, 84, 179, 58, 58, 76, 128, 213, 24, 53, 38, 144,
-                205, 88, 172, 20, 251, 22, 217, 141, 21, 221, 21, 0, 0, 0,
-            ]
-            .into(),
-            header_timestamp: ntime,
-            n_bits: 503543726,
-            target: [
-                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                174, 119, 3, 0, 0,
-            ]
-            .into(),
+        // try to cheat by re-submitting the same share
+        // with a different sequence number
+        let repeated_share = SubmitSharesExtended {
+            channel_id,
+            sequence_number: 2,
+            job_id: 1,
+            nonce: 51208,
+            ntime: 1745611105,
+            version: 536870912,
+            extranonce: vec![1, 0, 0, 0, 0, 0, 0, 0].try_into().unwrap(),
         };
 
-        channel.on_set_new_prev_hash(set_new_prev_hash).unwrap();
-
-        // we just activated the only future job
-        assert!(!channel.job_store.has_future_jobs());
-
-        let mut previously_future_job = future_job.clone();
-        previously_future_job.activate(ntime);
-
-        let activated_job = channel.get_active_job().unwrap();
+        let res = channel.validate_share(repeated_share);
 
-        // assert that the activated job is the same as the previously future job
-        assert_eq!(
-            activated_job.get_job_message(),
-            previously_future_job.get_job_message()
-        );
+        // assert duplicate share is rejected
+        assert!(matches!(res, Err(ShareValidationError::DuplicateShare)));
     }
 
     #[test]
-    fn test_non_future_job_creation_flow() {
+    fn test_share_validation_late_share_on_testnet() {
         // note:
         // the messages on this test were collected from a sane message flow
-        // we use them as test vectors to assert correct behavior of job creation
+        // we use them as test vectors to assert correct behavior of job creation and share
+        // validation
 
         let channel_id = 1;
         let user_identity = "user_identity".to_string();
         let extranonce_prefix = [
-            83, 116, 114, 97, 116, 117, 109, 32, 86, 50, 32, 83, 82, 73, 32, 80, 111, 111, 108, 0,
-            0, 0, 0, 0, 0, 0, 1,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
         ]
         .to_vec();
         let max_target = Target::from_le_bytes([0xff; 32]);
         let expected_share_per_minute = 1.0;
-        let nominal_hashrate = 1.0;
+        let nominal_hashrate = 1_000.0; // bigger hashrate to get higher difficulty
         let version_rolling_allowed = true;
-        let rollable_extranonce_size = 4u16;
+        let rollable_extranonce_size = 8u16;
         let share_batch_size = 100;
         let job_store = DefaultJobStore::new();
 
@@ -1003,19 +2420,12 @@ mod tests {
         )
         .unwrap();
 
-        let ntime = 1746839905;
-        let prev_hash = [
-            200, 53, 253, 129, 214, 31, 43, 84, 179, 58, 58, 76, 128, 213, 24, 53, 38, 144, 205,
-            88, 172, 20, 251, 22, 217, 141, 21, 221, 21, 0, 0, 0,
-        ]
-        .into();
-        let n_bits = 503543726;
-
-        let chain_tip = ChainTip::new(prev_hash, n_bits, ntime);
-        channel.set_chain_tip(chain_tip);
+        // channel target is:
+        // 0001179d9861a761ffdadd11c307c4fc04eea3a418f7d687584e4434af158205
 
+        let template_id = 1;
         let template = NewTemplate {
-            template_id: 1,
+            template_id,
             future_template: false,
             version: 536870912,
             coinbase_tx_version: 2,
@@ -1049,61 +2459,75 @@ mod tests {
             script_pubkey: script,
         }];
 
+        // network tarkget is: 000000000000d7c0000000000000000000000000000000000000000000000000
+        let n_bits = 453040064;
+        let ntime = 1745611105;
+        let prev_hash = [
+            23, 205, 72, 134, 153, 86, 220, 153, 224, 28, 216, 146, 228, 120, 227, 157, 213, 99,
+            160, 163, 128, 59, 139, 190, 158, 62, 0, 0, 0, 0, 0, 0,
+        ]
+        .into();
+        let chain_tip = ChainTip::new(prev_hash, n_bits, ntime);
+        channel.set_network(Network::Testnet4);
+        channel.set_chain_tip(chain_tip);
+        // the share comes more than 20 minutes after the parent block, so a block may be mined
+        // at the testnet minimum difficulty
+        channel
+            .set_chain_tip_parent_timestamp(ntime - 20 * 60 - 1)
+            .unwrap();
+
+        // prepare channel with non-future job
         channel
             .on_new_template(template.clone(), coinbase_reward_outputs)
             .unwrap();
 
-        assert!(!channel.job_store.has_future_jobs());
-
-        let active_job = channel.get_active_job().unwrap().clone();
-
-        // we know that the provided template + coinbase_reward_outputs should generate this
-        // non-future job
-        let expected_job = NewExtendedMiningJob {
-            channel_id: 1,
+        // this share has hash 000004f9d35777e4d56eedc20b1d05d251a7c0ed0b4e3013b5a809852844e218
+        // when hashed by the miner with the nbits of SetNewPrevHash, which does meet the channel
+        // target but does not meet the testnet minimum difficulty target
+        // 00000000ffff0000000000000000000000000000000000000000000000000000
+        let valid_share = SubmitSharesExtended {
+            channel_id,
+            sequence_number: 1,
             job_id: 1,
-            min_ntime: Sv2Option::new(Some(ntime)),
+            nonce: 51208,
+            ntime: 1745611105,
             version: 536870912,
-            version_rolling_allowed: true,
-            coinbase_tx_prefix: vec![
-                2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                0, 0, 0, 0, 0, 0, 0, 0, 0, 255, 255, 255, 255, 38, 82, 0, 3, 47, 47, 47, 31,
-            ]
-            .try_into()
-            .unwrap(),
-            coinbase_tx_suffix: vec![
-                255, 255, 255, 255, 2, 0, 242, 5, 42, 1, 0, 0, 0, 22, 0, 20, 235, 225, 183, 220,
-                194, 147, 204, 170, 14, 231, 67, 168, 111, 137, 223, 130, 88, 194, 8, 252, 0, 0, 0,
-                0, 0, 0, 0, 0, 38, 106, 36, 170, 33, 169, 237, 226, 246, 28, 63, 113, 209, 222,
-                253, 63, 169, 153, 223, 163, 105, 83, 117, 92, 105, 6, 137, 121, 153, 98, 180, 139,
-                235, 216, 54, 151, 78, 140, 249, 0, 0, 0, 0,
-            ]
-            .try_into()
-            .unwrap(),
-            merkle_path: vec![].try_into().unwrap(),
+            extranonce: vec![1, 0, 0, 0, 0, 0, 0, 0].try_into().unwrap(),
         };
 
-        assert_eq!(active_job.get_job_message(), &expected_job);
+        let res = channel.validate_share(valid_share);
+
+        // the share is validated with the hash computed by the miner
+        let share_hash = match res {
+            Ok(ShareValidationResult::Valid(share_hash)) => share_hash,
+            res => panic!("unexpected share validation result {res:?}"),
+        };
+        let mut share_hash: [u8; 32] = *share_hash.as_ref();
+        share_hash.reverse();
+        assert_eq!(
+            bytes_to_hex(&share_hash),
+            "000004f9d35777e4d56eedc20b1d05d251a7c0ed0b4e3013b5a809852844e218"
+        );
     }
 
     #[test]
-    fn test_coinbase_reward_outputs_sum_above_template_value() {
+    fn test_share_validation_from_worker_accounts_shares() {
         // note:
         // the messages on this test were collected from a sane message flow
-        // we use them as test vectors to assert correct behavior of job creation
+        // we use them as test vectors to assert correct behavior of job creation and share
+        // validation
 
         let channel_id = 1;
         let user_identity = "user_identity".to_string();
         let extranonce_prefix = [
-            83, 116, 114, 97, 116, 117, 109, 32, 86, 50, 32, 83, 82, 73, 32, 80, 111, 111, 108, 0,
-            0, 0, 0, 0, 0, 0, 1,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
         ]
         .to_vec();
         let max_target = Target::from_le_bytes([0xff; 32]);
         let expected_share_per_minute = 1.0;
-        let nominal_hashrate = 1.0;
+        let nominal_hashrate = 1_000.0; // bigger hashrate to get higher difficulty
         let version_rolling_allowed = true;
-        let rollable_extranonce_size = 4u16;
+        let rollable_extranonce_size = 8u16;
         let share_batch_size = 100;
         let job_store = DefaultJobStore::new();
 
@@ -1123,9 +2547,13 @@ mod tests {
         )
         .unwrap();
 
+        // channel target is:
+        // 0001179d9861a761ffdadd11c307c4fc04eea3a418f7d687584e4434af158205
+
+        let template_id = 1;
         let template = NewTemplate {
-            template_id: 1,
-            future_template: true,
+            template_id,
+            future_template: false,
             version: 536870912,
             coinbase_tx_version: 2,
             coinbase_prefix: vec![82, 0].try_into().unwrap(),
@@ -1143,6 +2571,8 @@ mod tests {
             merkle_path: vec![].try_into().unwrap(),
         };
 
+        // match the original script format used to generate the coinbase_reward_outputs for the
+        // expected job
         let pubkey_hash = [
             235, 225, 183, 220, 194, 147, 204, 170, 14, 231, 67, 168, 111, 137, 223, 130, 88, 194,
             8, 252,
@@ -1151,21 +2581,82 @@ mod tests {
         script_bytes.push(20); // Push 20 bytes (length of pubkey hash)
         script_bytes.extend_from_slice(&pubkey_hash);
         let script = ScriptBuf::from(script_bytes);
-
-        let invalid_coinbase_reward_outputs = vec![TxOut {
-            value: Amount::from_sat(SATS_AVAILABLE_IN_TEMPLATE + 1), /* oops: one too many extra
-                                                                      * sats */
+        let coinbase_reward_outputs = vec![TxOut {
+            value: Amount::from_sat(SATS_AVAILABLE_IN_TEMPLATE),
             script_pubkey: script,
         }];
 
-        let res = channel.on_new_template(template.clone(), invalid_coinbase_reward_outputs);
+        // network tarkget is: 000000000000d7c0000000000000000000000000000000000000000000000000
+        let n_bits = 453040064;
+        let ntime = 1745611105;
+        let prev_hash = [
+            23, 205, 72, 134, 153, 86, 220, 153, 224, 28, 216, 146, 228, 120, 227, 157, 213, 99,
+            160, 163, 128, 59, 139, 190, 158, 62, 0, 0, 0, 0, 0, 0,
+        ]
+        .into();
+        let chain_tip = ChainTip::new(prev_hash, n_bits, ntime);
+        channel.set_chain_tip(chain_tip);
 
-        assert!(res.is_err());
-        assert!(!channel.job_store.has_future_jobs());
+        // prepare channel with non-future job
+        channel
+            .on_new_template(template.clone(), coinbase_reward_outputs)
+            .unwrap();
+
+        // this share has hash 000004f9d35777e4d56eedc20b1d05d251a7c0ed0b4e3013b5a809852844e218
+        // which does meet the channel target
+        // 0001179d9861a761ffdadd11c307c4fc04eea3a418f7d687584e4434af158205
+        // but does not meet network target
+        // 000000000000d7c0000000000000000000000000000000000000000000000000
+        let valid_share = SubmitSharesExtended {
+            channel_id,
+            sequence_number: 1,
+            job_id: 1,
+            nonce: 51208,
+            ntime: 1745611105,
+            version: 536870912,
+            extranonce: vec![1, 0, 0, 0, 0, 0, 0, 0].try_into().unwrap(),
+        };
+
+        let worker = UserIdentity::new("Worker_001").unwrap();
+        let res = channel.validate_share_from_worker(valid_share, Some(&worker));
+        assert!(matches!(res, Ok(ShareValidationResult::Valid(_))));
+
+        // try to cheat by re-submitting the same share
+        // with a different sequence number
+        let repeated_share = SubmitSharesExtended {
+            channel_id,
+            sequence_number: 2,
+            job_id: 1,
+            nonce: 51208,
+            ntime: 1745611105,
+            version: 536870912,
+            extranonce: vec![1, 0, 0, 0, 0, 0, 0, 0].try_into().unwrap(),
+        };
+
+        let res =
+            channel.validate_share_from_worker_with_reject_detail(repeated_share, Some(&worker));
+
+        // assert duplicate share is rejected, with its detail
+        let (error, reject_detail) = res.unwrap_err();
+        assert!(matches!(error, ShareValidationError::DuplicateShare));
+        assert_eq!(reject_detail.job_state, Some(JobState::Active));
+
+        // assert both shares are accounted to the worker
+        let worker_stats = channel
+            .get_worker_accounting()
+            .get_worker_stats(&worker)
+            .unwrap();
+        assert_eq!(worker_stats.get_shares_accepted(), 1);
+        assert_eq!(worker_stats.get_shares_rejected(), 1);
+        assert_eq!(
+            worker_stats.get_share_work_sum(),
+            channel.get_share_accounting().get_share_work_sum()
+        );
+        assert!(worker_stats.get_best_diff() > 0.0);
     }
 
     #[test]
-    fn test_share_validation_block_found() {
+    fn test_share_validation_valid_share_with_async_job_store() {
         // note:
         // the messages on this test were collected from a sane message flow
         // we use them as test vectors to assert correct behavior of job creation and share
@@ -1179,29 +2670,33 @@ mod tests {
         .to_vec();
         let max_target = Target::from_le_bytes([0xff; 32]);
         let expected_share_per_minute = 1.0;
-        let nominal_hashrate = 1.0;
+        let nominal_hashrate = 1_000.0; // bigger hashrate to get higher difficulty
         let version_rolling_allowed = true;
         let rollable_extranonce_size = 8u16;
         let share_batch_size = 100;
-        let job_store = DefaultJobStore::new();
+        let job_store = MemoryJobStore::new();
+        let new_channel = |job_store| {
+            ExtendedChannel::new_async(
+                channel_id,
+                user_identity.clone(),
+                extranonce_prefix.clone(),
+                max_target,
+                nominal_hashrate,
+                version_rolling_allowed,
+                rollable_extranonce_size,
+                share_batch_size,
+                expected_share_per_minute,
+                job_store,
+                None,
+                None,
+            )
+            .unwrap()
+        };
 
-        let mut channel = ExtendedChannel::new(
-            channel_id,
-            user_identity,
-            extranonce_prefix,
-            max_target,
-            nominal_hashrate,
-            version_rolling_allowed,
-            rollable_extranonce_size,
-            share_batch_size,
-            expected_share_per_minute,
-            job_store,
-            None,
-            None,
-        )
-        .unwrap();
+        let mut channel = new_channel(job_store.clone());
 
-        // channel target: 04325c53ef368eb04325c53ef368eb04325c53ef368eb04325c53ef368eb0431
+        // channel target is:
+        // 0001179d9861a761ffdadd11c307c4fc04eea3a418f7d687584e4434af158205
 
         let template_id = 1;
         let template = NewTemplate {
@@ -1239,85 +2734,114 @@ mod tests {
             script_pubkey: script,
         }];
 
-        // network target: 7fffff0000000000000000000000000000000000000000000000000000000000
-        let ntime = 1745596910;
+        // network tarkget is: 000000000000d7c0000000000000000000000000000000000000000000000000
+        let n_bits = 453040064;
+        let ntime = 1745611105;
         let prev_hash = [
-            251, 175, 106, 40, 35, 87, 122, 90, 58, 51, 78, 32, 202, 236, 228, 36, 154, 174, 206,
-            144, 147, 195, 21, 224, 195, 103, 214, 189, 51, 190, 24, 98,
+            23, 205, 72, 134, 153, 86, 220, 153, 224, 28, 216, 146, 228, 120, 227, 157, 213, 99,
+            160, 163, 128, 59, 139, 190, 158, 62, 0, 0, 0, 0, 0, 0,
         ]
         .into();
-        let n_bits = 545259519;
         let chain_tip = ChainTip::new(prev_hash, n_bits, ntime);
-        channel.set_chain_tip(chain_tip);
+        channel.set_chain_tip(chain_tip.clone());
 
         // prepare channel with non-future job
-        channel
-            .on_new_template(template.clone(), coinbase_reward_outputs)
+        block_on(channel.on_new_template_async(template.clone(), coinbase_reward_outputs.clone()))
             .unwrap();
+        assert!(block_on(channel.get_active_job_async()).unwrap().is_some());
 
-        // this share has hash 4c68f79a585c8b609e9b43113f73311eada20ec88a70a999406267db3499f1d9
-        // which satisfies network target
-        // 7fffff0000000000000000000000000000000000000000000000000000000000
-        let share_valid_block = SubmitSharesExtended {
+        // this share has hash 000004f9d35777e4d56eedc20b1d05d251a7c0ed0b4e3013b5a809852844e218
+        // which does meet the channel target
+        // 0001179d9861a761ffdadd11c307c4fc04eea3a418f7d687584e4434af158205
+        // but does not meet network target
+        // 000000000000d7c0000000000000000000000000000000000000000000000000
+        let valid_share = SubmitSharesExtended {
             channel_id,
-            sequence_number: 0,
+            sequence_number: 1,
             job_id: 1,
-            nonce: 8,
-            ntime: 1745596971,
+            nonce: 51208,
+            ntime: 1745611105,
             version: 536870912,
             extranonce: vec![1, 0, 0, 0, 0, 0, 0, 0].try_into().unwrap(),
         };
 
-        let res = channel.validate_share(share_valid_block);
+        let res = block_on(channel.validate_share_async(valid_share));
+        assert!(matches!(res, Ok(ShareValidationResult::Valid(_))));
 
-        assert!(matches!(
-            res,
-            Ok(ShareValidationResult::BlockFound(_, _, _))
-        ));
+        // try to cheat by re-submitting the same share
+        // with a different sequence number
+        let repeated_share = SubmitSharesExtended {
+            channel_id,
+            sequence_number: 2,
+            job_id: 1,
+            nonce: 51208,
+            ntime: 1745611105,
+            version: 536870912,
+            extranonce: vec![1, 0, 0, 0, 0, 0, 0, 0].try_into().unwrap(),
+        };
+
+        let res = block_on(channel.validate_share_async(repeated_share));
+
+        // assert duplicate share is rejected
+        assert!(matches!(res, Err(ShareValidationError::DuplicateShare)));
+
+        let unknown_job_share = SubmitSharesExtended {
+            channel_id,
+            sequence_number: 3,
+            job_id: 2,
+            nonce: 51208,
+            ntime: 1745611105,
+            version: 536870912,
+            extranonce: vec![1, 0, 0, 0, 0, 0, 0, 0].try_into().unwrap(),
+        };
+
+        let res = block_on(channel.validate_share_async(unknown_job_share.clone()));
+        assert!(matches!(res, Err(ShareValidationError::InvalidJobId)));
+
+        // a channel sharing the job store doesn't reuse its job ids
+        let mut other_channel = new_channel(job_store);
+        other_channel.set_chain_tip(chain_tip);
+        block_on(other_channel.on_new_template_async(template, coinbase_reward_outputs)).unwrap();
+        let active_job = block_on(channel.get_active_job_async()).unwrap().unwrap();
+        assert_eq!(active_job.get_job_id(), 2);
+        let res = block_on(channel.validate_share_async(unknown_job_share));
+        assert!(!matches!(res, Err(ShareValidationError::InvalidJobId)));
     }
 
     #[test]
-    fn test_share_validation_does_not_meet_target() {
-        // note:
-        // the messages on this test were collected from a sane message flow
-        // we use them as test vectors to assert correct behavior of job creation and share
-        // validation
+    fn test_share_validation_job_from_reopened_job_store() {
+        let path = std::env::temp_dir().join(format!(
+            "channels_sv2_reopened_job_store_{}.log",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
 
         let channel_id = 1;
-        let user_identity = "user_identity".to_string();
         let extranonce_prefix = [
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
         ]
         .to_vec();
         let max_target = Target::from_le_bytes([0xff; 32]);
-        let expected_share_per_minute = 1.0;
-        let nominal_hashrate = 100.0; // bigger hashrate to get higher difficulty
-        let version_rolling_allowed = true;
-        let rollable_extranonce_size = 8u16;
-        let share_batch_size = 100;
-        let job_store = DefaultJobStore::new();
-
-        let mut channel = ExtendedChannel::new(
-            channel_id,
-            user_identity,
-            extranonce_prefix,
-            max_target,
-            nominal_hashrate,
-            version_rolling_allowed,
-            rollable_extranonce_size,
-            share_batch_size,
-            expected_share_per_minute,
-            job_store,
-            None,
-            None,
-        )
-        .unwrap();
-
-        // channel target: 000aebbc990fff5144366f000aebbc990fff5144366f000aebbc990fff514435
+        let new_channel = |job_store| {
+            ExtendedChannel::new(
+                channel_id,
+                "user_identity".to_string(),
+                extranonce_prefix.clone(),
+                max_target,
+                1_000.0,
+                true,
+                8,
+                100,
+                1.0,
+                job_store,
+                None,
+                None,
+            )
+            .unwrap()
+        };
 
-        let template_id = 1;
         let template = NewTemplate {
-            template_id,
+            template_id: 1,
             future_template: false,
             version: 536870912,
             coinbase_tx_version: 2,
@@ -1335,9 +2859,6 @@ mod tests {
             coinbase_tx_locktime: 0,
             merkle_path: vec![].try_into().unwrap(),
         };
-
-        // match the original script format used to generate the coinbase_reward_outputs for the
-        // expected job
         let pubkey_hash = [
             235, 225, 183, 220, 194, 147, 204, 170, 14, 231, 67, 168, 111, 137, 223, 130, 88, 194,
             8, 252,
@@ -1345,51 +2866,60 @@ mod tests {
         let mut script_bytes = vec![0]; // SegWit version 0
         script_bytes.push(20); // Push 20 bytes (length of pubkey hash)
         script_bytes.extend_from_slice(&pubkey_hash);
-        let script = ScriptBuf::from(script_bytes);
         let coinbase_reward_outputs = vec![TxOut {
             value: Amount::from_sat(SATS_AVAILABLE_IN_TEMPLATE),
-            script_pubkey: script,
+            script_pubkey: ScriptBuf::from(script_bytes),
         }];
-
-        // network target: 000000000000d7c0000000000000000000000000000000000000000000000000
-        let ntime = 1745596910;
         let prev_hash = [
-            154, 124, 239, 231, 221, 122, 160, 173, 164, 175, 87, 33, 74, 214, 191, 107, 73, 34, 0,
-            162, 227, 16, 44, 40, 33, 73, 0, 0, 0, 0, 0, 0,
+            23, 205, 72, 134, 153, 86, 220, 153, 224, 28, 216, 146, 228, 120, 227, 157, 213, 99,
+            160, 163, 128, 59, 139, 190, 158, 62, 0, 0, 0, 0, 0, 0,
         ]
         .into();
-        let n_bits = 453040064;
-        let chain_tip = ChainTip::new(prev_hash, n_bits, ntime);
-        channel.set_chain_tip(chain_tip);
+        let chain_tip = ChainTip::new(prev_hash, 453040064, 1745611105);
 
-        // prepare channel with non-future job
+        let mut channel = new_channel(FileJobStore::open(&path).unwrap());
+        channel.set_chain_tip(chain_tip.clone());
         channel
-            .on_new_template(template.clone(), coinbase_reward_outputs)
+            .on_new_template(template.clone(), coinbase_reward_outputs.clone())
             .unwrap();
+        drop(channel);
 
-        // this share has hash d5767872f3a26e7f9f21cd968f27cfdb8b4061bb9ce0959852594ee8620f4efb
-        // which does not meet the channel target
-        // 000aebbc990fff5144366f000aebbc990fff5144366f000aebbc990fff514435
-        let share_low_diff = SubmitSharesExtended {
+        // after a restart, the job and its target are replayed from the log
+        let mut channel = new_channel(FileJobStore::open(&path).unwrap());
+        channel.set_chain_tip(chain_tip.clone());
+        assert_eq!(channel.get_active_job().unwrap().get_job_id(), 1);
+
+        // a channel following the log, e.g. in another process, validates shares for its jobs
+        let mut validator = new_channel(FileJobStore::open_read_only(&path).unwrap());
+        validator.set_chain_tip(chain_tip);
+
+        // this share meets the channel target, see test_share_validation_valid_share
+        let share = SubmitSharesExtended {
             channel_id,
-            sequence_number: 0,
+            sequence_number: 1,
             job_id: 1,
-            nonce: 0,
-            ntime: 1745596971,
+            nonce: 51208,
+            ntime: 1745611105,
             version: 536870912,
             extranonce: vec![1, 0, 0, 0, 0, 0, 0, 0].try_into().unwrap(),
         };
+        let res = validator.validate_share(share.clone());
+        assert!(matches!(res, Ok(ShareValidationResult::Valid(_))));
+        let res = channel.validate_share(share);
+        assert!(matches!(res, Ok(ShareValidationResult::Valid(_))));
 
-        let res = channel.validate_share(share_low_diff);
+        // the job factory of the reopened channel doesn't reuse the replayed job id
+        channel
+            .on_new_template(template, coinbase_reward_outputs)
+            .unwrap();
+        assert_eq!(channel.get_active_job().unwrap().get_job_id(), 2);
+        assert_eq!(validator.get_active_job().unwrap().get_job_id(), 2);
 
-        assert!(matches!(
-            res.unwrap_err(),
-            ShareValidationError::DoesNotMeetTarget
-        ));
+        std::fs::remove_file(&path).unwrap();
     }
 
     #[test]
-    fn test_share_validation_valid_share() {
+    fn test_share_validation_block_found_on_regtest() {
         // note:
         // the messages on this test were collected from a sane message flow
         // we use them as test vectors to assert correct behavior of job creation and share
@@ -1464,8 +2994,9 @@ mod tests {
             script_pubkey: script,
         }];
 
-        // network tarkget is: 000000000000d7c0000000000000000000000000000000000000000000000000
-        let n_bits = 453040064;
+        // regtest network target is:
+        // 7fffff0000000000000000000000000000000000000000000000000000000000
+        let n_bits = 545259519;
         let ntime = 1745611105;
         let prev_hash = [
             23, 205, 72, 134, 153, 86, 220, 153, 224, 28, 216, 146, 228, 120, 227, 157, 213, 99,
@@ -1480,12 +3011,10 @@ mod tests {
             .on_new_template(template.clone(), coinbase_reward_outputs)
             .unwrap();
 
-        // this share has hash 000004f9d35777e4d56eedc20b1d05d251a7c0ed0b4e3013b5a809852844e218
-        // which does meet the channel target
-        // 0001179d9861a761ffdadd11c307c4fc04eea3a418f7d687584e4434af158205
-        // but does not meet network target
-        // 000000000000d7c0000000000000000000000000000000000000000000000000
-        let valid_share = SubmitSharesExtended {
+        // this share has hash 42a681698d781f10b2902035be562978393929aa21df32a71cf25d04589dde65
+        // which does not meet the channel target, but does meet the regtest trivial target
+        // 7fffff0000000000000000000000000000000000000000000000000000000000
+        let share = SubmitSharesExtended {
             channel_id,
             sequence_number: 1,
             job_id: 1,
@@ -1495,25 +3024,167 @@ mod tests {
             extranonce: vec![1, 0, 0, 0, 0, 0, 0, 0].try_into().unwrap(),
         };
 
-        let res = channel.validate_share(valid_share);
-        assert!(matches!(res, Ok(ShareValidationResult::Valid(_))));
+        channel.set_network(Network::Regtest);
+        let res = channel.validate_share(share);
+        assert!(matches!(
+            res,
+            Ok(ShareValidationResult::BlockFound(_, Some(1), _))
+        ));
+    }
 
-        // try to cheat by re-submitting the same share
-        // with a different sequence number
-        let repeated_share = SubmitSharesExtended {
+    #[test]
+    fn test_signet_future_job_activation_flow() {
+        let channel_id = 1;
+        let extranonce_prefix = [
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+        ]
+        .to_vec();
+        // no extranonce is left to roll, so the whole coinbase is known to the channel
+        let rollable_extranonce_size = 0u16;
+
+        let mut channel = ExtendedChannel::new(
             channel_id,
-            sequence_number: 2,
-            job_id: 1,
-            nonce: 51208,
-            ntime: 1745611105,
+            "user_identity".to_string(),
+            extranonce_prefix,
+            Target::from_le_bytes([0xff; 32]),
+            1.0,
+            true,
+            rollable_extranonce_size,
+            100,
+            1.0,
+            DefaultJobStore::new(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let secret_key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let public_key = secret_key.public_key(&Secp256k1::signing_only());
+        let challenge = ScriptBuf::new_p2wpkh(&CompressedPublicKey(public_key).wpubkey_hash());
+        channel.set_signet_signer(SignetSigner::new(challenge, secret_key).unwrap());
+        // every share meets the channel target, and half of them meet the regtest target
+        channel.set_target(Target::from_le_bytes([0xff; 32]));
+        channel.set_network(Network::Regtest);
+
+        let template = NewTemplate {
+            template_id: 1,
+            future_template: true,
             version: 536870912,
-            extranonce: vec![1, 0, 0, 0, 0, 0, 0, 0].try_into().unwrap(),
+            coinbase_tx_version: 2,
+            coinbase_prefix: vec![82, 0].try_into().unwrap(),
+            coinbase_tx_input_sequence: 4294967295,
+            coinbase_tx_value_remaining: SATS_AVAILABLE_IN_TEMPLATE,
+            coinbase_tx_outputs_count: 1,
+            coinbase_tx_outputs: vec![
+                0, 0, 0, 0, 0, 0, 0, 0, 38, 106, 36, 170, 33, 169, 237, 226, 246, 28, 63, 113, 209,
+                222, 253, 63, 169, 153, 223, 163, 105, 83, 117, 92, 105, 6, 137, 121, 153, 98, 180,
+                139, 235, 216, 54, 151, 78, 140, 249,
+            ]
+            .try_into()
+            .unwrap(),
+            coinbase_tx_locktime: 0,
+            merkle_path: vec![].try_into().unwrap(),
         };
+        let coinbase_reward_outputs = vec![TxOut {
+            value: Amount::from_sat(SATS_AVAILABLE_IN_TEMPLATE),
+            script_pubkey: ScriptBuf::new_p2wpkh(&CompressedPublicKey(public_key).wpubkey_hash()),
+        }];
 
-        let res = channel.validate_share(repeated_share);
+        channel
+            .on_new_template(template, coinbase_reward_outputs)
+            .unwrap();
+        assert!(!has_solution(
+            channel.get_future_job(1).unwrap().get_coinbase_outputs()
+        ));
 
-        // assert duplicate share is rejected
-        assert!(matches!(res, Err(ShareValidationError::DuplicateShare)));
+        let ntime = 1745611105;
+        let set_new_prev_hash = SetNewPrevHash {
+            template_id: 1,
+            prev_hash: [
+                23, 205, 72, 134, 153, 86, 220, 153, 224, 28, 216, 146, 228, 120, 227, 157, 213,
+                99, 160, 163, 128, 59, 139, 190, 158, 62, 0, 0, 0, 0, 0, 0,
+            ]
+            .into(),
+            header_timestamp: ntime,
+            n_bits: 545259519,
+            target: [0xff; 32].into(),
+        };
+        channel.on_set_new_prev_hash(set_new_prev_hash).unwrap();
+
+        // the activated job is replaced by a signed one, and kept as a past job
+        let active_job = channel.get_active_job().unwrap();
+        assert_eq!(active_job.get_job_id(), 2);
+        assert_eq!(active_job.get_min_ntime(), Sv2Option::new(Some(ntime)));
+        assert!(has_solution(active_job.get_coinbase_outputs()));
+        assert!(channel.get_past_job(1).is_some());
+
+        let share = |job_id, nonce, ntime| SubmitSharesExtended {
+            channel_id,
+            sequence_number: nonce,
+            job_id,
+            nonce,
+            ntime,
+            version: 536870912,
+            extranonce: vec![].try_into().unwrap(),
+        };
+
+        // shares already in flight for the activated job are still accepted, but never as blocks,
+        // and neither are shares rolling the ntime of the signed job
+        for nonce in 0..16 {
+            assert!(matches!(
+                channel.validate_share(share(1, nonce, ntime)),
+                Ok(ShareValidationResult::Valid(_))
+            ));
+            assert!(matches!(
+                channel.validate_share(share(2, nonce, ntime + 1)),
+                Ok(ShareValidationResult::Valid(_))
+            ));
+        }
+
+        let blocks: Vec<_> = (0..16)
+            .filter_map(
+                |nonce| match channel.validate_share(share(2, nonce, ntime)) {
+                    Ok(ShareValidationResult::BlockFound(_, Some(1), coinbase)) => Some(coinbase),
+                    _ => None,
+                },
+            )
+            .collect();
+        assert!(!blocks.is_empty());
+        let coinbase: Transaction = deserialize(&blocks[0]).unwrap();
+        assert!(has_solution(&coinbase.output));
+    }
+
+    #[test]
+    fn test_set_chain_tip_parent_timestamp() {
+        let job_store = DefaultJobStore::new();
+        let mut channel = ExtendedChannel::new(
+            1,
+            "user_identity".to_string(),
+            vec![0, 0, 0, 1],
+            Target::from_le_bytes([0xff; 32]),
+            1.0,
+            true,
+            4,
+            100,
+            1.0,
+            job_store,
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(channel.get_network(), Network::Bitcoin);
+        assert!(matches!(
+            channel.set_chain_tip_parent_timestamp(1745611105),
+            Err(ExtendedChannelError::ChainTipNotSet)
+        ));
+
+        channel.set_chain_tip(ChainTip::new([0; 32].into(), 453040064, 1745611106));
+        channel.set_chain_tip_parent_timestamp(1745611105).unwrap();
+        assert_eq!(
+            channel.get_chain_tip().unwrap().parent_timestamp(),
+            Some(1745611105)
+        );
     }
 
     #[test]
@@ -242,6 +242,11 @@ where
         template: NewTemplate<'a>,
         coinbase_reward_outputs: Vec<TxOut>,
     ) -> Result<(), GroupChannelError> {
+        // don't hand out the job IDs of a job store that outlives this group channel
+        if let Some(last_job_id) = self.job_store.get_last_job_id() {
+            self.job_factory.resume_job_ids_after(last_job_id);
+        }
+
         match template.future_template {
             true => {
                 let new_job = self
@@ -1,5 +1,7 @@
 //! # Job Error Types
 
+use crate::signet::SignetError;
+
 #[derive(Debug)]
 pub enum ExtendedJobError {
     FailedToDeserializeCoinbase,
@@ -29,4 +31,29 @@ pub enum JobFactoryError {
     CoinbaseOutputsSumOverflow,
     InvalidCoinbaseOutputsSum,
     ChainTipRequired,
+    SignetError(SignetError),
+}
+
+#[derive(Debug)]
+pub enum JobStoreError {
+    /// Reading or writing the job store backend failed.
+    Io(std::io::Error),
+    /// A job could not be serialized for persistence.
+    FailedToSerializeJob,
+    /// A persisted job or log record could not be deserialized.
+    FailedToDeserializeJob,
+    /// The lock protecting the job store was poisoned by a panicking thread.
+    PoisonedLock,
+    /// The record of the job log starting at the given offset could not be decoded.
+    InvalidLogRecord(u64),
+    /// The job store is a read-only view of a job set written by another handle.
+    ReadOnly,
+    /// The job with the given ID has no target to validate its shares against.
+    MissingJobTarget(u32),
+}
+
+impl From<std::io::Error> for JobStoreError {
+    fn from(e: std::io::Error) -> Self {
+        JobStoreError::Io(e)
+    }
 }
@@ -2,10 +2,19 @@ use super::Job;
 use crate::{
     merkle_root::merkle_root_from_path,
     outputs::deserialize_template_outputs,
-    server::jobs::{error::ExtendedJobError, standard::StandardJob, JobOrigin},
+    server::jobs::{
+        coinbase_outputs_footprint,
+        error::{ExtendedJobError, JobStoreError},
+        file_job_store::{encode_sv2, write_field, FieldReader, PersistentJob},
+        standard::StandardJob,
+        JobOrigin,
+    },
+};
+use binary_sv2::{GetSize, Seq0255, Sv2Option, U256};
+use bitcoin::{
+    consensus::{deserialize, serialize},
+    transaction::TxOut,
 };
-use binary_sv2::{Seq0255, Sv2Option, U256};
-use bitcoin::transaction::TxOut;
 use mining_sv2::{NewExtendedMiningJob, NewMiningJob, SetCustomMiningJob};
 use std::convert::TryInto;
 use template_distribution_sv2::NewTemplate;
@@ -42,6 +51,21 @@ impl Job for ExtendedJob<'_> {
     fn activate(&mut self, min_ntime: u32) {
         self.activate(min_ntime);
     }
+
+    fn get_memory_footprint(&self) -> usize {
+        let origin_size = match &self.origin {
+            JobOrigin::NewTemplate(template) => template.get_size(),
+            JobOrigin::SetCustomMiningJob(custom_job) => custom_job.get_size(),
+        };
+
+        std::mem::size_of::<Self>()
+            + origin_size
+            + self.extranonce_prefix.len()
+            + coinbase_outputs_footprint(&self.coinbase_outputs)
+            + self.coinbase_tx_prefix_with_bip141.len()
+            + self.coinbase_tx_suffix_with_bip141.len()
+            + self.job_message.get_size()
+    }
 }
 
 impl<'a> ExtendedJob<'a> {
@@ -217,3 +241,69 @@ impl<'a> ExtendedJob<'a> {
         self.job_message.min_ntime = Sv2Option::new(Some(min_ntime));
     }
 }
+
+// origin type markers of persisted jobs
+const ORIGIN_NEW_TEMPLATE: u8 = 0x00;
+const ORIGIN_SET_CUSTOM_MINING_JOB: u8 = 0x01;
+
+impl PersistentJob for ExtendedJob<'static> {
+    fn to_bytes(&self) -> Result<Vec<u8>, JobStoreError> {
+        let mut bytes = vec![];
+        match &self.origin {
+            JobOrigin::NewTemplate(template) => {
+                bytes.push(ORIGIN_NEW_TEMPLATE);
+                write_field(&mut bytes, &encode_sv2(template.clone())?)?;
+            }
+            JobOrigin::SetCustomMiningJob(set_custom_mining_job) => {
+                bytes.push(ORIGIN_SET_CUSTOM_MINING_JOB);
+                write_field(&mut bytes, &encode_sv2(set_custom_mining_job.clone())?)?;
+            }
+        }
+        write_field(&mut bytes, &self.extranonce_prefix)?;
+        write_field(&mut bytes, &serialize(&self.coinbase_outputs))?;
+        write_field(&mut bytes, &self.coinbase_tx_prefix_with_bip141)?;
+        write_field(&mut bytes, &self.coinbase_tx_suffix_with_bip141)?;
+        write_field(&mut bytes, &encode_sv2(self.job_message.clone())?)?;
+        Ok(bytes)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, JobStoreError> {
+        let (origin_type, fields) = bytes
+            .split_first()
+            .ok_or(JobStoreError::FailedToDeserializeJob)?;
+        let mut reader = FieldReader::new(fields);
+
+        let mut origin = reader.read_field()?.to_vec();
+        let origin = match *origin_type {
+            ORIGIN_NEW_TEMPLATE => JobOrigin::NewTemplate(
+                binary_sv2::from_bytes::<NewTemplate>(&mut origin)
+                    .map_err(|_| JobStoreError::FailedToDeserializeJob)?
+                    .into_static(),
+            ),
+            ORIGIN_SET_CUSTOM_MINING_JOB => JobOrigin::SetCustomMiningJob(
+                binary_sv2::from_bytes::<SetCustomMiningJob>(&mut origin)
+                    .map_err(|_| JobStoreError::FailedToDeserializeJob)?
+                    .into_static(),
+            ),
+            _ => return Err(JobStoreError::FailedToDeserializeJob),
+        };
+        let extranonce_prefix = reader.read_field()?.to_vec();
+        let coinbase_outputs =
+            deserialize(reader.read_field()?).map_err(|_| JobStoreError::FailedToDeserializeJob)?;
+        let coinbase_tx_prefix_with_bip141 = reader.read_field()?.to_vec();
+        let coinbase_tx_suffix_with_bip141 = reader.read_field()?.to_vec();
+        let mut job_message = reader.read_field()?.to_vec();
+        let job_message = binary_sv2::from_bytes::<NewExtendedMiningJob>(&mut job_message)
+            .map_err(|_| JobStoreError::FailedToDeserializeJob)?
+            .into_static();
+
+        Ok(Self {
+            origin,
+            extranonce_prefix,
+            coinbase_outputs,
+            coinbase_tx_prefix_with_bip141,
+            coinbase_tx_suffix_with_bip141,
+            job_message,
+        })
+    }
+}
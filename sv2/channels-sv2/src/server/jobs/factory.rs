@@ -14,6 +14,9 @@
 //! - **Coinbase Output Validation**: Verifies that coinbase outputs match SV2 template constraints
 //!   and protocol rules.
 //! - **Version Rolling**: Tracks version rolling allowance for created jobs.
+//! - **Signet Signing**: Optionally signs non-future jobs for a signet challenge, whenever the whole
+//!   coinbase is known at creation: standard jobs, extended jobs leaving no extranonce to roll, and
+//!   custom jobs created for a fixed extranonce.
 //!
 //! ## Usage
 //!
@@ -26,14 +29,18 @@ use crate::{
     merkle_root::merkle_root_from_path,
     outputs::deserialize_template_outputs,
     server::jobs::{error::*, extended::ExtendedJob, standard::StandardJob},
+    signet::{insert_solution, reserve_solution, SignetSigner},
+    target::u256_to_block_hash,
 };
-use binary_sv2::{Sv2Option, B0255};
+use binary_sv2::{Sv2Option, B0255, B064K};
 use bitcoin::{
     absolute::LockTime,
+    block::Version as BlockVersion,
     blockdata::witness::Witness,
     consensus::{serialize, Decodable},
+    hashes::Hash,
     transaction::{OutPoint, Transaction, TxIn, TxOut, Version},
-    Amount, Sequence,
+    Amount, Sequence, TxMerkleNode,
 };
 use mining_sv2::{NewExtendedMiningJob, NewMiningJob, SetCustomMiningJob};
 use std::convert::TryInto;
@@ -55,6 +62,11 @@ impl JobIdFactory {
         self.state += 1;
         self.state
     }
+
+    /// Moves the internal state forward to `job_id`, if it's behind.
+    fn resume_after(&mut self, job_id: u32) {
+        self.state = self.state.max(job_id);
+    }
 }
 
 /// A Factory for creating Extended or Standard Jobs.
@@ -64,12 +76,16 @@ impl JobIdFactory {
 /// Enables creation of new Extended Jobs from NewTemplate and SetCustomMiningJob messages.
 ///
 /// Enables creation of new Standard Jobs from NewTemplate messages.
+///
+/// Optionally holds a [`SignetSigner`], which is used to sign non-future jobs whose coinbase is
+/// fully known at creation.
 #[derive(Debug, Clone)]
 pub struct JobFactory {
     job_id_factory: JobIdFactory,
     version_rolling_allowed: bool,
     pool_tag_string: Option<String>,
     miner_tag_string: Option<String>,
+    signet_signer: Option<SignetSigner>,
 }
 
 impl JobFactory {
@@ -90,9 +106,33 @@ impl JobFactory {
             version_rolling_allowed,
             pool_tag_string,
             miner_tag_string,
+            signet_signer: None,
         }
     }
 
+    /// Sets the signer used to sign non-future jobs on a signet.
+    ///
+    /// A signet solution commits to the block version, `ntime` and coinbase, so a signed job only
+    /// yields a valid block for shares that keep the job `version` and `min_ntime` untouched.
+    /// Future jobs are never signed, as their chain tip is not known at creation time. Neither are
+    /// extended jobs leaving part of the extranonce to the miner, as their coinbase isn't either.
+    pub fn set_signet_signer(&mut self, signet_signer: SignetSigner) {
+        self.signet_signer = Some(signet_signer);
+    }
+
+    /// Returns the signer used to sign jobs, if any.
+    pub fn get_signet_signer(&self) -> Option<&SignetSigner> {
+        self.signet_signer.as_ref()
+    }
+
+    /// Makes sure the next job IDs are above `job_id`.
+    ///
+    /// Used to resume after the last job of a job store that outlives the factory, such as one
+    /// replayed from disk or shared with other processes.
+    pub fn resume_job_ids_after(&mut self, job_id: u32) {
+        self.job_id_factory.resume_after(job_id);
+    }
+
     /// Returns a byte vector with the OP_PUSHBYTES opcode and the pool+miner tag.
     ///
     /// The character `/` is used as a delimiter.
@@ -157,6 +197,17 @@ impl JobFactory {
             return Err(JobFactoryError::InvalidCoinbaseOutputsSum);
         }
 
+        let template = match (&self.signet_signer, &chain_tip, template.future_template) {
+            (Some(signet_signer), Some(chain_tip), false) => self.signet_signed_template(
+                signet_signer,
+                chain_tip,
+                template,
+                additional_coinbase_outputs.clone(),
+                &extranonce_prefix,
+            )?,
+            _ => template,
+        };
+
         let job_id = self.job_id_factory.next();
 
         let version = template.version;
@@ -228,6 +279,10 @@ impl JobFactory {
     ///
     /// It's up to the caller to ensure that the sum of `additional_coinbase_outputs` is equal to
     /// available template revenue. Returns an error otherwise.
+    ///
+    /// With a signet signer set, non-future jobs are signed only if `full_extranonce_size` is the
+    /// length of `extranonce_prefix`. Otherwise the coinbase depends on the extranonce rolled by the
+    /// miner, so blocks found with the job carry no signet solution.
     pub fn new_extended_job<'a>(
         &mut self,
         channel_id: u32,
@@ -245,6 +300,21 @@ impl JobFactory {
             return Err(JobFactoryError::InvalidCoinbaseOutputsSum);
         }
 
+        let template = match (&self.signet_signer, &chain_tip, template.future_template) {
+            (Some(signet_signer), Some(chain_tip), false)
+                if full_extranonce_size == extranonce_prefix.len() =>
+            {
+                self.signet_signed_template(
+                    signet_signer,
+                    chain_tip,
+                    template,
+                    additional_coinbase_outputs.clone(),
+                    &extranonce_prefix,
+                )?
+            }
+            _ => template,
+        };
+
         let job_id = self.job_id_factory.next();
 
         let version = template.version;
@@ -354,6 +424,9 @@ impl JobFactory {
     ///
     /// It's up to the caller to ensure that the sum of the additional coinbase outputs is equal to
     /// available template revenue.
+    ///
+    /// The job is never signed, as the coinbase depends on the extranonce rolled by the miner. See
+    /// [`JobFactory::new_custom_job_for_extranonce`] for signed custom jobs.
     #[allow(clippy::too_many_arguments)]
     pub fn new_custom_job<'a>(
         &self,
@@ -413,10 +486,56 @@ impl JobFactory {
         Ok(set_custom_mining_job)
     }
 
+    /// Creates a new `SetCustomMiningJob` message from a template, for shares mined with the given
+    /// `full_extranonce` only.
+    ///
+    /// With a signet signer set, the coinbase carries a signet solution for `full_extranonce`, so
+    /// blocks found with the job are valid as long as they keep the job `version` and `min_ntime`.
+    /// Otherwise, this is the same as [`JobFactory::new_custom_job`].
+    ///
+    /// To be used by a Sv2 Job Declaration Client mining on a channel that leaves no extranonce to
+    /// roll.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_custom_job_for_extranonce<'a>(
+        &self,
+        channel_id: u32,
+        request_id: u32,
+        token: B0255<'a>,
+        chain_tip: ChainTip,
+        template: NewTemplate<'a>,
+        additional_coinbase_outputs: Vec<TxOut>,
+        full_extranonce: &[u8],
+    ) -> Result<SetCustomMiningJob<'a>, JobFactoryError> {
+        let template = match &self.signet_signer {
+            Some(signet_signer) => self.signet_signed_template(
+                signet_signer,
+                &chain_tip,
+                template,
+                additional_coinbase_outputs.clone(),
+                full_extranonce,
+            )?,
+            None => template,
+        };
+
+        self.new_custom_job(
+            channel_id,
+            request_id,
+            token,
+            chain_tip,
+            template,
+            additional_coinbase_outputs,
+            full_extranonce.len(),
+        )
+    }
+
     /// Creates a new Extended Job from a SetCustomMiningJob message.
     ///
     /// Assumes that the SetCustomMiningJob message has already been validated.
     ///
+    /// The coinbase is taken as is from the message, so the job is never signed, even with a
+    /// signet signer set. It carries a signet solution only if the Job Declaration Client signed
+    /// it (see [`JobFactory::new_custom_job_for_extranonce`]).
+    ///
     /// To be used by Extended Channels on a Sv2 Pool Server.
     pub fn new_extended_job_from_custom_job<'a>(
         &mut self,
@@ -559,6 +678,61 @@ impl JobFactory {
         Ok(coinbase_tx_suffix)
     }
 
+    // Returns a copy of the template where the witness commitment output carries the signet
+    // solution for a block built on `chain_tip`, with the template version and the chain tip
+    // `min_ntime`.
+    fn signet_signed_template<'a>(
+        &self,
+        signet_signer: &SignetSigner,
+        chain_tip: &ChainTip,
+        mut template: NewTemplate<'a>,
+        additional_coinbase_outputs: Vec<TxOut>,
+        extranonce_prefix: &[u8],
+    ) -> Result<NewTemplate<'a>, JobFactoryError> {
+        let mut template_outputs = deserialize_template_outputs(
+            template.coinbase_tx_outputs.to_vec(),
+            template.coinbase_tx_outputs_count,
+        )
+        .map_err(|_| JobFactoryError::DeserializeCoinbaseOutputsError)?;
+
+        reserve_solution(&mut template_outputs).map_err(JobFactoryError::SignetError)?;
+        template.coinbase_tx_outputs = serialize_template_outputs(&template_outputs)?;
+
+        let coinbase_tx_prefix = self.coinbase_tx_prefix(
+            template.clone(),
+            additional_coinbase_outputs.clone(),
+            extranonce_prefix.len(),
+        )?;
+        let coinbase_tx_suffix = self.coinbase_tx_suffix(
+            template.clone(),
+            additional_coinbase_outputs,
+            extranonce_prefix.len(),
+        )?;
+        let signet_merkle_root: [u8; 32] = merkle_root_from_path(
+            &coinbase_tx_prefix,
+            &coinbase_tx_suffix,
+            extranonce_prefix,
+            &template.merkle_path.inner_as_ref(),
+        )
+        .expect("merkle root must be valid")
+        .try_into()
+        .expect("merkle root must be 32 bytes");
+
+        let solution = signet_signer
+            .solution(
+                BlockVersion::from_consensus(template.version as i32),
+                u256_to_block_hash(chain_tip.prev_hash()),
+                TxMerkleNode::from_byte_array(signet_merkle_root),
+                chain_tip.min_ntime(),
+            )
+            .map_err(JobFactoryError::SignetError)?;
+
+        insert_solution(&mut template_outputs, &solution).map_err(JobFactoryError::SignetError)?;
+        template.coinbase_tx_outputs = serialize_template_outputs(&template_outputs)?;
+
+        Ok(template)
+    }
+
     // build a coinbase transaction from some template in the JobFactory
     fn coinbase(
         &self,
@@ -687,10 +861,25 @@ impl JobFactory {
     }
 }
 
+fn serialize_template_outputs(outputs: &[TxOut]) -> Result<B064K<'static>, JobFactoryError> {
+    outputs
+        .iter()
+        .flat_map(serialize)
+        .collect::<Vec<u8>>()
+        .try_into()
+        .map_err(|_| JobFactoryError::FailedToSerializeCoinbaseOutputs)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use bitcoin::ScriptBuf;
+    use crate::signet::has_solution;
+    use bitcoin::{
+        block::Header,
+        consensus::deserialize,
+        secp256k1::{Secp256k1, SecretKey},
+        Block, CompactTarget, CompressedPublicKey, ScriptBuf,
+    };
     use template_distribution_sv2::NewTemplate;
 
     #[test]
@@ -891,4 +1080,89 @@ mod tests {
 
         assert_eq!(custom_job.get_job_message(), &expected_job);
     }
+
+    #[test]
+    fn test_new_signet_signed_custom_job() {
+        let secret_key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let public_key = secret_key.public_key(&Secp256k1::signing_only());
+        let challenge = ScriptBuf::new_p2wpkh(&CompressedPublicKey(public_key).wpubkey_hash());
+        let signet_signer = SignetSigner::new(challenge, secret_key).unwrap();
+
+        let mut jdc_job_factory = JobFactory::new(true, None, None);
+        jdc_job_factory.set_signet_signer(signet_signer.clone());
+
+        let full_extranonce = vec![1; 8];
+        let template = NewTemplate {
+            template_id: 1,
+            future_template: false,
+            version: 536870912,
+            coinbase_tx_version: 2,
+            coinbase_prefix: vec![82, 0].try_into().unwrap(),
+            coinbase_tx_input_sequence: 4294967295,
+            coinbase_tx_value_remaining: 5000000000,
+            coinbase_tx_outputs_count: 1,
+            coinbase_tx_outputs: vec![
+                0, 0, 0, 0, 0, 0, 0, 0, 38, 106, 36, 170, 33, 169, 237, 226, 246, 28, 63, 113, 209,
+                222, 253, 63, 169, 153, 223, 163, 105, 83, 117, 92, 105, 6, 137, 121, 153, 98, 180,
+                139, 235, 216, 54, 151, 78, 140, 249,
+            ]
+            .try_into()
+            .unwrap(),
+            coinbase_tx_locktime: 0,
+            merkle_path: vec![].try_into().unwrap(),
+        };
+        let coinbase_reward_outputs = vec![TxOut {
+            value: Amount::from_sat(5000000000),
+            script_pubkey: ScriptBuf::new_p2wpkh(&CompressedPublicKey(public_key).wpubkey_hash()),
+        }];
+        let chain_tip = ChainTip::new(
+            [
+                200, 53, 253, 129, 214, 31, 43, 84, 179, 58, 58, 76, 128, 213, 24, 53, 38, 144,
+                205, 88, 172, 20, 251, 22, 217, 141, 21, 221, 21, 0, 0, 0,
+            ]
+            .into(),
+            503543726,
+            1746839905,
+        );
+
+        let set_custom_mining_job = jdc_job_factory
+            .new_custom_job_for_extranonce(
+                1,
+                1,
+                vec![0].try_into().unwrap(),
+                chain_tip.clone(),
+                template,
+                coinbase_reward_outputs,
+                &full_extranonce,
+            )
+            .unwrap();
+
+        // the pool takes the signed coinbase as is
+        let custom_job = JobFactory::new(true, None, None)
+            .new_extended_job_from_custom_job(set_custom_mining_job, vec![1; 4], 8)
+            .unwrap();
+        assert!(has_solution(custom_job.get_coinbase_outputs()));
+
+        // signing is deterministic, so signing the block mined with the job again must leave it
+        // unchanged if it already carries the solution for its coinbase
+        let mut coinbase = custom_job.get_coinbase_tx_prefix_with_bip141();
+        coinbase.extend(&full_extranonce);
+        coinbase.extend(custom_job.get_coinbase_tx_suffix_with_bip141());
+        let mut block = Block {
+            header: Header {
+                version: BlockVersion::from_consensus(custom_job.get_version() as i32),
+                prev_blockhash: u256_to_block_hash(chain_tip.prev_hash()),
+                merkle_root: TxMerkleNode::all_zeros(),
+                time: chain_tip.min_ntime(),
+                bits: CompactTarget::from_consensus(chain_tip.nbits()),
+                nonce: 0,
+            },
+            txdata: vec![deserialize(&coinbase).unwrap()],
+        };
+        block.header.merkle_root = block.compute_merkle_root().unwrap();
+
+        let mut resigned = block.clone();
+        signet_signer.sign_block(&mut resigned).unwrap();
+        assert_eq!(resigned, block);
+    }
 }
@@ -0,0 +1,1164 @@
+//! File-backed implementation of [`JobStore`] and [`JobStoreAsync`].
+//!
+//! [`FileJobStore`] keeps the jobs of a channel in memory, and appends every mutation of the job set
+//! to a log file as a record. When the store is opened again, the job set is rebuilt by replaying
+//! the log, so it survives process restarts. Job targets and the ID of the last job added are part
+//! of the job set, so a channel reopening the store validates shares for replayed jobs against
+//! their own target, and never hands out their job IDs again.
+//!
+//! ## Sharing the job set
+//!
+//! A log is written by a single handle, opened with [`FileJobStore::open`]. Any number of
+//! read-only handles, opened with [`FileJobStore::open_read_only`] by the same or other processes
+//! (e.g. share validators behind a load balancer), follow it: before serving a read, they replay
+//! the records the writer appended since their last read. Mutating a read-only handle fails with
+//! [`JobStoreError::ReadOnly`].
+//!
+//! Having a single writer is up to the user, as the log file is not locked. Followers rely on
+//! each record being appended with a single write, which holds on local filesystems.
+//!
+//! I/O is blocking, including on the [`JobStoreAsync`] methods.
+//!
+//! ## Compaction
+//!
+//! Once the log is at least [`MIN_COMPACTION_LEN`] bytes, and twice as large as when it was last
+//! compacted, the writer rewrites it as a snapshot of the job set: the snapshot is written next to
+//! the log and renamed over it, then a record is appended to the old log telling followers to
+//! reopen the log. Compaction can also be run with [`FileJobStore::compact`].
+//!
+//! ## Retention
+//!
+//! The retention limits of [`DefaultJobStore`] are set with [`FileJobStore::set_max_past_jobs`]
+//! and [`FileJobStore::set_max_past_job_age`], and are logged so that followers evict the same
+//! jobs as the writer. Evicted jobs are left out of the snapshot, so they are dropped from disk at
+//! the next compaction, which bounds the log to about twice the size of the retained job set (or
+//! [`MIN_COMPACTION_LEN`]). The age of a past job restarts when the log is replayed.
+//!
+//! ## Errors
+//!
+//! Since [`JobStore`] methods can't fail, a record that can't be written is rolled back from the
+//! log, and the error is kept until it is taken with [`FileJobStore::take_error`]. Mutations keep
+//! being applied to the job set in memory, but are no longer logged, as the log would replay to a
+//! different job set. Opening the store again resumes logging from the last record written, and
+//! compacting it resumes logging from the job set in memory. A read-only handle that fails to
+//! follow the log keeps serving the job set it had, and keeps the error the same way.
+//!
+//! [`JobStoreAsync`] methods report these errors instead, and a mutation whose record can't be
+//! written is not applied.
+//!
+//! A failed compaction leaves the log as it was, and its error is kept for
+//! [`FileJobStore::take_error`].
+//!
+//! [`FileJobStore::open`] fails on a record that can't be decoded, with the offset of the record.
+//! [`FileJobStore::recover`] opens such a log anyway, discarding that record and every record
+//! after it.
+//!
+//! ## Log format
+//!
+//! Each record is framed as a little-endian `u32` payload length followed by the payload. A
+//! payload starts with a one byte record type, followed by the record fields:
+//! - `0x01` add future job: `template_id` (`u64`), serialized job
+//! - `0x02` add active job: serialized job
+//! - `0x03` activate future job: `template_id` (`u64`), `prev_hash_header_timestamp` (`u32`)
+//! - `0x04` mark past jobs as stale
+//! - `0x05` set job target: `job_id` (`u32`), target (32 bytes, little-endian)
+//! - `0x06` add past job: serialized job
+//! - `0x07` add stale job: serialized job
+//! - `0x08` add evicted job: `job_id` (`u32`)
+//! - `0x09` set last job ID: `job_id` (`u32`)
+//! - `0x0a` log compacted: the log was replaced by a compacted one
+//! - `0x0b` set retention: `max_past_jobs` and `max_past_job_age` in milliseconds, each as a one
+//!   byte presence flag followed by a `u64`
+//!
+//! Records `0x06` to `0x09` are only written by compaction, to restore the job set as it was.
+//!
+//! Jobs are serialized with [`PersistentJob`].
+
+use std::{
+    convert::TryInto,
+    ffi::OsString,
+    fmt::Debug,
+    fs::{self, File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use bitcoin::Target;
+
+use super::{
+    error::JobStoreError,
+    job_store::{DefaultJobStore, JobStore, JobStoreAsync, JobStoreSize},
+    Job,
+};
+
+/// Minimum length of the log, in bytes, before it is compacted automatically.
+pub const MIN_COMPACTION_LEN: u64 = 1 << 20;
+
+const ADD_FUTURE_JOB: u8 = 0x01;
+const ADD_ACTIVE_JOB: u8 = 0x02;
+const ACTIVATE_FUTURE_JOB: u8 = 0x03;
+const MARK_PAST_JOBS_AS_STALE: u8 = 0x04;
+const SET_JOB_TARGET: u8 = 0x05;
+const ADD_PAST_JOB: u8 = 0x06;
+const ADD_STALE_JOB: u8 = 0x07;
+const ADD_EVICTED_JOB: u8 = 0x08;
+const SET_LAST_JOB_ID: u8 = 0x09;
+const LOG_COMPACTED: u8 = 0x0a;
+const SET_RETENTION: u8 = 0x0b;
+
+/// Trait for jobs that can be persisted by [`FileJobStore`].
+pub trait PersistentJob: Job + Clone + Debug + Sized {
+    /// Serializes the job into bytes.
+    fn to_bytes(&self) -> Result<Vec<u8>, JobStoreError>;
+
+    /// Deserializes a job from bytes produced by [`PersistentJob::to_bytes`].
+    fn from_bytes(bytes: &[u8]) -> Result<Self, JobStoreError>;
+}
+
+/// Append-log implementation of [`JobStore`] and [`JobStoreAsync`].
+///
+/// See the [module documentation](self) for sharing the log, the log format and error handling.
+#[derive(Debug)]
+pub struct FileJobStore<T: PersistentJob> {
+    path: PathBuf,
+    read_only: bool,
+    // behind a lock, as read-only handles replay the log on reads
+    log: Mutex<Log<T>>,
+}
+
+#[derive(Debug)]
+struct Log<T: PersistentJob> {
+    file: File,
+    // length of the log up to the end of the last record written or replayed
+    len: u64,
+    // length of the log when it was last compacted (or opened)
+    compacted_len: u64,
+    store: DefaultJobStore<T>,
+    // set when a record couldn't be written, after which nothing is logged
+    logging_stopped: bool,
+    error: Option<JobStoreError>,
+}
+
+#[derive(Debug)]
+enum Record<T> {
+    AddFutureJob(u64, T),
+    AddActiveJob(T),
+    ActivateFutureJob(u64, u32),
+    MarkPastJobsAsStale,
+    SetJobTarget(u32, Target),
+    AddPastJob(T),
+    AddStaleJob(T),
+    AddEvictedJob(u32),
+    SetLastJobId(u32),
+    LogCompacted,
+    SetRetention(Option<usize>, Option<Duration>),
+}
+
+impl<T: PersistentJob> FileJobStore<T> {
+    /// Opens the job log at `path` for writing, creating it if it doesn't exist.
+    ///
+    /// The job set is rebuilt from the records already in the log. A truncated record at the end
+    /// of the log (left by a crash in the middle of an append) is discarded.
+    ///
+    /// Returns [`JobStoreError::InvalidLogRecord`] if a record can't be decoded.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, JobStoreError> {
+        Self::open_for_writing(path.as_ref(), false)
+    }
+
+    /// Opens the job log at `path` like [`FileJobStore::open`], discarding the first record that
+    /// can't be decoded and every record after it.
+    ///
+    /// The job set is the one the log had before the invalid record.
+    pub fn recover<P: AsRef<Path>>(path: P) -> Result<Self, JobStoreError> {
+        Self::open_for_writing(path.as_ref(), true)
+    }
+
+    /// Opens the job log at `path`, written by another handle, for reading.
+    ///
+    /// The job set is rebuilt from the records already in the log, and follows the records the
+    /// writer appends next.
+    ///
+    /// Returns [`JobStoreError::InvalidLogRecord`] if a record can't be decoded.
+    pub fn open_read_only<P: AsRef<Path>>(path: P) -> Result<Self, JobStoreError> {
+        let path = path.as_ref();
+        let mut log = Log::new(File::open(path)?);
+        log.follow(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            read_only: true,
+            log: Mutex::new(log),
+        })
+    }
+
+    /// Returns true if the store follows a log written by another handle.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Takes the last error of the store, if any.
+    ///
+    /// If a record couldn't be written, the job set in memory is still up to date, but the log
+    /// misses every mutation since the error.
+    pub fn take_error(&mut self) -> Option<JobStoreError> {
+        self.log_mut().error.take()
+    }
+
+    /// Rewrites the log as a snapshot of the job set.
+    ///
+    /// Returns [`JobStoreError::ReadOnly`] on a read-only handle.
+    pub fn compact(&mut self) -> Result<(), JobStoreError> {
+        if self.read_only {
+            return Err(JobStoreError::ReadOnly);
+        }
+        let path = self.path.clone();
+        self.log_mut().compact(&path)
+    }
+
+    /// Sets the maximum number of past jobs kept until the next chain tip, like
+    /// [`DefaultJobStore::set_max_past_jobs`].
+    ///
+    /// The limit is logged, and errors are kept for [`FileJobStore::take_error`].
+    pub fn set_max_past_jobs(&mut self, max_past_jobs: Option<usize>) {
+        let max_past_job_age = self.get_max_past_job_age();
+        self.mutate(Record::SetRetention(max_past_jobs, max_past_job_age));
+    }
+
+    /// Returns the maximum number of past jobs kept until the next chain tip.
+    pub fn get_max_past_jobs(&self) -> Option<usize> {
+        self.read(|store| store.get_max_past_jobs())
+    }
+
+    /// Sets how long past jobs are kept, like [`DefaultJobStore::set_max_past_job_age`].
+    ///
+    /// The limit is logged with millisecond precision, and errors are kept for
+    /// [`FileJobStore::take_error`].
+    pub fn set_max_past_job_age(&mut self, max_past_job_age: Option<Duration>) {
+        let max_past_jobs = self.get_max_past_jobs();
+        self.mutate(Record::SetRetention(max_past_jobs, max_past_job_age));
+    }
+
+    /// Returns how long past jobs are kept.
+    pub fn get_max_past_job_age(&self) -> Option<Duration> {
+        self.read(|store| store.get_max_past_job_age())
+    }
+
+    fn open_for_writing(path: &Path, recover: bool) -> Result<Self, JobStoreError> {
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+        let mut log = Log::new(file);
+        match log.read_records() {
+            Ok(false) => {}
+            // logs are only marked as compacted once they've been replaced, so the writer
+            // never finds the mark
+            Ok(true) if recover => {}
+            Ok(true) => return Err(JobStoreError::InvalidLogRecord(log.len)),
+            Err(JobStoreError::InvalidLogRecord(_)) if recover => {}
+            Err(e) => return Err(e),
+        }
+
+        if log.file.metadata()?.len() > log.len {
+            log.file.set_len(log.len)?;
+        }
+        log.compacted_len = log.len;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            read_only: false,
+            log: Mutex::new(log),
+        })
+    }
+
+    fn log_mut(&mut self) -> &mut Log<T> {
+        self.log.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // Reads the job set, once the records appended by the writer have been replayed. Errors are
+    // kept for `take_error`, and the job set is read as it was.
+    fn read<R>(&self, f: impl FnOnce(&DefaultJobStore<T>) -> R) -> R {
+        let mut log = self.log.lock().unwrap_or_else(PoisonError::into_inner);
+        if self.read_only {
+            if let Err(e) = log.follow(&self.path) {
+                log.error = Some(e);
+            }
+        }
+        f(&log.store)
+    }
+
+    // Like `read`, reporting errors.
+    fn try_read<R>(&self, f: impl FnOnce(&DefaultJobStore<T>) -> R) -> Result<R, JobStoreError> {
+        let mut log = self.log.lock().map_err(|_| JobStoreError::PoisonedLock)?;
+        if self.read_only {
+            log.follow(&self.path)?;
+        }
+        Ok(f(&log.store))
+    }
+
+    // Logs a mutation of the job set and applies it. Errors are kept for `take_error`.
+    fn mutate(&mut self, record: Record<T>) {
+        if self.read_only {
+            self.log_mut().error = Some(JobStoreError::ReadOnly);
+            return;
+        }
+        let log = self.log.get_mut().unwrap_or_else(PoisonError::into_inner);
+        if !log.logging_stopped {
+            if let Err(e) = log.append(&record) {
+                log.logging_stopped = true;
+                log.error = Some(e);
+            }
+        }
+        record.apply(&mut log.store);
+        log.compact_if_needed(&self.path);
+    }
+
+    // Like `mutate`, reporting errors. The mutation is only applied once it is logged.
+    fn try_mutate(&mut self, record: Record<T>) -> Result<(), JobStoreError> {
+        if self.read_only {
+            return Err(JobStoreError::ReadOnly);
+        }
+        let log = self
+            .log
+            .get_mut()
+            .map_err(|_| JobStoreError::PoisonedLock)?;
+        if !log.logging_stopped {
+            log.append(&record)?;
+        }
+        record.apply(&mut log.store);
+        log.compact_if_needed(&self.path);
+        Ok(())
+    }
+}
+
+impl<T: PersistentJob> Log<T> {
+    fn new(file: File) -> Self {
+        Self {
+            file,
+            len: 0,
+            compacted_len: 0,
+            store: DefaultJobStore::new(),
+            logging_stopped: false,
+            error: None,
+        }
+    }
+
+    // Replays the complete records appended after `len`.
+    //
+    // Returns true if the replay stopped at the mark of a compacted log.
+    fn read_records(&mut self) -> Result<bool, JobStoreError> {
+        self.file.seek(SeekFrom::Start(self.len))?;
+        let mut bytes = vec![];
+        self.file.read_to_end(&mut bytes)?;
+
+        let mut cursor = 0;
+        while bytes.len() - cursor >= 4 {
+            let payload_len = u32::from_le_bytes(
+                bytes[cursor..cursor + 4]
+                    .try_into()
+                    .expect("slice is 4 bytes"),
+            ) as usize;
+            let payload_start = cursor + 4;
+            if bytes.len() - payload_start < payload_len {
+                // the last append was interrupted, or is still in progress
+                break;
+            }
+
+            let record = Record::from_bytes(&bytes[payload_start..payload_start + payload_len])
+                .map_err(|_| JobStoreError::InvalidLogRecord(self.len))?;
+            if let Record::LogCompacted = record {
+                return Ok(true);
+            }
+            record.apply(&mut self.store);
+
+            cursor = payload_start + payload_len;
+            self.len += (4 + payload_len) as u64;
+        }
+
+        Ok(false)
+    }
+
+    // Replays the records appended by the writer, reopening the log each time it was compacted.
+    fn follow(&mut self, path: &Path) -> Result<(), JobStoreError> {
+        while self.read_records()? {
+            // the snapshot restores the retention limits too
+            self.file = File::open(path)?;
+            self.len = 0;
+            self.store = DefaultJobStore::new();
+        }
+        Ok(())
+    }
+
+    fn append(&mut self, record: &Record<T>) -> Result<(), JobStoreError> {
+        let frame = record.to_frame()?;
+        if let Err(e) = self.file.write_all(&frame) {
+            // don't leave a partial record behind, it would be replayed as garbage
+            let _ = self.file.set_len(self.len);
+            return Err(e.into());
+        }
+        self.len += frame.len() as u64;
+        Ok(())
+    }
+
+    fn compact_if_needed(&mut self, path: &Path) {
+        if self.logging_stopped || self.len < MIN_COMPACTION_LEN.max(2 * self.compacted_len) {
+            return;
+        }
+        if let Err(e) = self.compact(path) {
+            self.error = Some(e);
+        }
+    }
+
+    fn compact(&mut self, path: &Path) -> Result<(), JobStoreError> {
+        let mut snapshot = vec![];
+        for record in Record::snapshot(&self.store) {
+            snapshot.extend(record.to_frame()?);
+        }
+
+        let mut compacted_path = OsString::from(path);
+        compacted_path.push(".compacted");
+        let _ = fs::remove_file(&compacted_path);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create_new(true)
+            .open(&compacted_path)?;
+        file.write_all(&snapshot)?;
+        file.sync_all()?;
+        fs::rename(&compacted_path, path)?;
+
+        // the snapshot is the log now, followers still reading the old one must reopen it
+        let mut old_file = std::mem::replace(&mut self.file, file);
+        self.len = snapshot.len() as u64;
+        self.compacted_len = self.len;
+        self.logging_stopped = false;
+        old_file.write_all(&Record::<T>::LogCompacted.to_frame()?)?;
+        Ok(())
+    }
+}
+
+impl<T: PersistentJob> Record<T> {
+    // Records rebuilding the job set of `store`.
+    fn snapshot(store: &DefaultJobStore<T>) -> Vec<Self> {
+        // first, as restoring past jobs applies the retention limits
+        let mut records = vec![Record::SetRetention(
+            store.get_max_past_jobs(),
+            store.get_max_past_job_age(),
+        )];
+        records.extend(store.stale_jobs().cloned().map(Record::AddStaleJob));
+        records.extend(store.past_jobs().cloned().map(Record::AddPastJob));
+        records.extend(store.evicted_job_ids().map(Record::AddEvictedJob));
+        records.extend(store.get_active_job().map(Record::AddActiveJob));
+        records.extend(
+            store
+                .future_jobs()
+                .map(|(template_id, job)| Record::AddFutureJob(template_id, job.clone())),
+        );
+        records.extend(
+            store
+                .job_targets()
+                .map(|(job_id, target)| Record::SetJobTarget(job_id, target)),
+        );
+        // last, as adding jobs sets the last job ID
+        records.extend(store.get_last_job_id().map(Record::SetLastJobId));
+        records
+    }
+
+    fn apply(self, store: &mut DefaultJobStore<T>) {
+        match self {
+            Record::AddFutureJob(template_id, job) => {
+                store.add_future_job(template_id, job);
+            }
+            Record::AddActiveJob(job) => store.add_active_job(job),
+            Record::ActivateFutureJob(template_id, prev_hash_header_timestamp) => {
+                store.activate_future_job(template_id, prev_hash_header_timestamp);
+            }
+            Record::MarkPastJobsAsStale => store.mark_past_jobs_as_stale(),
+            Record::SetJobTarget(job_id, target) => store.set_job_target(job_id, target),
+            Record::AddPastJob(job) => store.restore_past_job(job),
+            Record::AddStaleJob(job) => store.restore_stale_job(job),
+            Record::AddEvictedJob(job_id) => store.restore_evicted_job_id(job_id),
+            Record::SetLastJobId(job_id) => store.restore_last_job_id(job_id),
+            // handled by the replay
+            Record::LogCompacted => {}
+            Record::SetRetention(max_past_jobs, max_past_job_age) => {
+                store.set_max_past_jobs(max_past_jobs);
+                store.set_max_past_job_age(max_past_job_age);
+            }
+        }
+    }
+
+    fn to_frame(&self) -> Result<Vec<u8>, JobStoreError> {
+        let payload = self.to_bytes()?;
+        let payload_len: u32 = payload
+            .len()
+            .try_into()
+            .map_err(|_| JobStoreError::FailedToSerializeJob)?;
+        let mut frame = Vec::with_capacity(4 + payload.len());
+        frame.extend_from_slice(&payload_len.to_le_bytes());
+        frame.extend_from_slice(&payload);
+        Ok(frame)
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, JobStoreError> {
+        let mut bytes = vec![];
+        match self {
+            Record::AddFutureJob(template_id, job) => {
+                bytes.push(ADD_FUTURE_JOB);
+                bytes.extend_from_slice(&template_id.to_le_bytes());
+                bytes.extend(job.to_bytes()?);
+            }
+            Record::AddActiveJob(job) => {
+                bytes.push(ADD_ACTIVE_JOB);
+                bytes.extend(job.to_bytes()?);
+            }
+            Record::ActivateFutureJob(template_id, prev_hash_header_timestamp) => {
+                bytes.push(ACTIVATE_FUTURE_JOB);
+                bytes.extend_from_slice(&template_id.to_le_bytes());
+                bytes.extend_from_slice(&prev_hash_header_timestamp.to_le_bytes());
+            }
+            Record::MarkPastJobsAsStale => bytes.push(MARK_PAST_JOBS_AS_STALE),
+            Record::SetJobTarget(job_id, target) => {
+                bytes.push(SET_JOB_TARGET);
+                bytes.extend_from_slice(&job_id.to_le_bytes());
+                bytes.extend_from_slice(&target.to_le_bytes());
+            }
+            Record::AddPastJob(job) => {
+                bytes.push(ADD_PAST_JOB);
+                bytes.extend(job.to_bytes()?);
+            }
+            Record::AddStaleJob(job) => {
+                bytes.push(ADD_STALE_JOB);
+                bytes.extend(job.to_bytes()?);
+            }
+            Record::AddEvictedJob(job_id) => {
+                bytes.push(ADD_EVICTED_JOB);
+                bytes.extend_from_slice(&job_id.to_le_bytes());
+            }
+            Record::SetLastJobId(job_id) => {
+                bytes.push(SET_LAST_JOB_ID);
+                bytes.extend_from_slice(&job_id.to_le_bytes());
+            }
+            Record::LogCompacted => bytes.push(LOG_COMPACTED),
+            Record::SetRetention(max_past_jobs, max_past_job_age) => {
+                bytes.push(SET_RETENTION);
+                write_optional_u64(&mut bytes, max_past_jobs.map(|max| max as u64));
+                write_optional_u64(
+                    &mut bytes,
+                    max_past_job_age.map(|age| age.as_millis().try_into().unwrap_or(u64::MAX)),
+                );
+            }
+        }
+        Ok(bytes)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, JobStoreError> {
+        let (record_type, fields) = bytes
+            .split_first()
+            .ok_or(JobStoreError::FailedToDeserializeJob)?;
+        let mut reader = FieldReader::new(fields);
+
+        let record = match *record_type {
+            ADD_FUTURE_JOB => {
+                let template_id = reader.read_u64()?;
+                Record::AddFutureJob(template_id, T::from_bytes(reader.remaining())?)
+            }
+            ADD_ACTIVE_JOB => Record::AddActiveJob(T::from_bytes(fields)?),
+            ACTIVATE_FUTURE_JOB => {
+                let template_id = reader.read_u64()?;
+                let prev_hash_header_timestamp = reader.read_u32()?;
+                Record::ActivateFutureJob(template_id, prev_hash_header_timestamp)
+            }
+            MARK_PAST_JOBS_AS_STALE => Record::MarkPastJobsAsStale,
+            SET_JOB_TARGET => {
+                let job_id = reader.read_u32()?;
+                let target = reader.take(32)?.try_into().expect("slice is 32 bytes");
+                Record::SetJobTarget(job_id, Target::from_le_bytes(target))
+            }
+            ADD_PAST_JOB => Record::AddPastJob(T::from_bytes(fields)?),
+            ADD_STALE_JOB => Record::AddStaleJob(T::from_bytes(fields)?),
+            ADD_EVICTED_JOB => Record::AddEvictedJob(reader.read_u32()?),
+            SET_LAST_JOB_ID => Record::SetLastJobId(reader.read_u32()?),
+            LOG_COMPACTED => Record::LogCompacted,
+            SET_RETENTION => {
+                let max_past_jobs =
+                    read_optional_u64(&mut reader)?.map(|max| max.try_into().unwrap_or(usize::MAX));
+                let max_past_job_age = read_optional_u64(&mut reader)?.map(Duration::from_millis);
+                Record::SetRetention(max_past_jobs, max_past_job_age)
+            }
+            _ => return Err(JobStoreError::FailedToDeserializeJob),
+        };
+        Ok(record)
+    }
+}
+
+impl<T: PersistentJob> JobStore<T> for FileJobStore<T> {
+    fn add_future_job(&mut self, template_id: u64, job: T) -> u32 {
+        let job_id = job.get_job_id();
+        self.mutate(Record::AddFutureJob(template_id, job));
+        job_id
+    }
+
+    fn add_active_job(&mut self, job: T) {
+        self.mutate(Record::AddActiveJob(job));
+    }
+
+    fn activate_future_job(&mut self, template_id: u64, prev_hash_header_timestamp: u32) -> bool {
+        // don't log activations that can't succeed
+        if self.read_only
+            || self
+                .read(|store| store.get_future_job_id_from_template_id(template_id))
+                .is_none()
+        {
+            return false;
+        }
+        self.mutate(Record::ActivateFutureJob(
+            template_id,
+            prev_hash_header_timestamp,
+        ));
+        true
+    }
+
+    fn mark_past_jobs_as_stale(&mut self) {
+        self.mutate(Record::MarkPastJobsAsStale);
+    }
+
+    fn get_future_job_id_from_template_id(&self, template_id: u64) -> Option<u32> {
+        self.read(|store| store.get_future_job_id_from_template_id(template_id))
+    }
+
+    fn get_active_job(&self) -> Option<T> {
+        self.read(|store| store.get_active_job())
+    }
+
+    fn has_future_jobs(&self) -> bool {
+        self.read(|store| store.has_future_jobs())
+    }
+
+    fn get_future_job(&self, job_id: u32) -> Option<T> {
+        self.read(|store| store.get_future_job(job_id))
+    }
+
+    fn has_past_jobs(&self) -> bool {
+        self.read(|store| store.has_past_jobs())
+    }
+
+    fn get_past_job(&self, job_id: u32) -> Option<T> {
+        self.read(|store| store.get_past_job(job_id))
+    }
+
+    fn has_stale_jobs(&self) -> bool {
+        self.read(|store| store.has_stale_jobs())
+    }
+
+    fn get_stale_job(&self, job_id: u32) -> Option<T> {
+        self.read(|store| store.get_stale_job(job_id))
+    }
+
+    fn set_job_target(&mut self, job_id: u32, target: Target) {
+        self.mutate(Record::SetJobTarget(job_id, target));
+    }
+
+    fn get_job_target(&self, job_id: u32) -> Option<Target> {
+        self.read(|store| store.get_job_target(job_id))
+    }
+
+    fn get_last_job_id(&self) -> Option<u32> {
+        self.read(|store| store.get_last_job_id())
+    }
+
+    fn is_evicted_job(&self, job_id: u32) -> bool {
+        self.read(|store| store.is_evicted_job(job_id))
+    }
+
+    fn get_size(&self) -> JobStoreSize {
+        self.read(|store| store.get_size())
+    }
+}
+
+impl<T: PersistentJob> JobStoreAsync<T> for FileJobStore<T> {
+    async fn add_future_job(&mut self, template_id: u64, job: T) -> Result<u32, JobStoreError> {
+        let job_id = job.get_job_id();
+        self.try_mutate(Record::AddFutureJob(template_id, job))?;
+        Ok(job_id)
+    }
+
+    async fn add_active_job(&mut self, job: T) -> Result<(), JobStoreError> {
+        self.try_mutate(Record::AddActiveJob(job))
+    }
+
+    async fn activate_future_job(
+        &mut self,
+        template_id: u64,
+        prev_hash_header_timestamp: u32,
+    ) -> Result<bool, JobStoreError> {
+        if self.read_only {
+            return Err(JobStoreError::ReadOnly);
+        }
+        // don't log activations that can't succeed
+        if self
+            .try_read(|store| store.get_future_job_id_from_template_id(template_id))?
+            .is_none()
+        {
+            return Ok(false);
+        }
+        self.try_mutate(Record::ActivateFutureJob(
+            template_id,
+            prev_hash_header_timestamp,
+        ))?;
+        Ok(true)
+    }
+
+    async fn mark_past_jobs_as_stale(&mut self) -> Result<(), JobStoreError> {
+        self.try_mutate(Record::MarkPastJobsAsStale)
+    }
+
+    async fn get_future_job_id_from_template_id(
+        &self,
+        template_id: u64,
+    ) -> Result<Option<u32>, JobStoreError> {
+        self.try_read(|store| store.get_future_job_id_from_template_id(template_id))
+    }
+
+    async fn get_active_job(&self) -> Result<Option<T>, JobStoreError> {
+        self.try_read(|store| store.get_active_job())
+    }
+
+    async fn has_future_jobs(&self) -> Result<bool, JobStoreError> {
+        self.try_read(|store| store.has_future_jobs())
+    }
+
+    async fn get_future_job(&self, job_id: u32) -> Result<Option<T>, JobStoreError> {
+        self.try_read(|store| store.get_future_job(job_id))
+    }
+
+    async fn has_past_jobs(&self) -> Result<bool, JobStoreError> {
+        self.try_read(|store| store.has_past_jobs())
+    }
+
+    async fn get_past_job(&self, job_id: u32) -> Result<Option<T>, JobStoreError> {
+        self.try_read(|store| store.get_past_job(job_id))
+    }
+
+    async fn has_stale_jobs(&self) -> Result<bool, JobStoreError> {
+        self.try_read(|store| store.has_stale_jobs())
+    }
+
+    async fn get_stale_job(&self, job_id: u32) -> Result<Option<T>, JobStoreError> {
+        self.try_read(|store| store.get_stale_job(job_id))
+    }
+
+    async fn set_job_target(&mut self, job_id: u32, target: Target) -> Result<(), JobStoreError> {
+        self.try_mutate(Record::SetJobTarget(job_id, target))
+    }
+
+    async fn get_job_target(&self, job_id: u32) -> Result<Option<Target>, JobStoreError> {
+        self.try_read(|store| store.get_job_target(job_id))
+    }
+
+    async fn get_last_job_id(&self) -> Result<Option<u32>, JobStoreError> {
+        self.try_read(|store| store.get_last_job_id())
+    }
+
+    async fn is_evicted_job(&self, job_id: u32) -> Result<bool, JobStoreError> {
+        self.try_read(|store| store.is_evicted_job(job_id))
+    }
+
+    async fn get_size(&self) -> Result<JobStoreSize, JobStoreError> {
+        self.try_read(|store| store.get_size())
+    }
+}
+
+fn write_optional_u64(bytes: &mut Vec<u8>, value: Option<u64>) {
+    bytes.push(value.is_some() as u8);
+    bytes.extend_from_slice(&value.unwrap_or(0).to_le_bytes());
+}
+
+fn read_optional_u64(reader: &mut FieldReader) -> Result<Option<u64>, JobStoreError> {
+    let is_some = reader.take(1)?[0] != 0;
+    let value = reader.read_u64()?;
+    Ok(is_some.then_some(value))
+}
+
+/// Appends `field` to `bytes`, prefixed by its length as a little-endian `u32`.
+///
+/// Helper for [`PersistentJob`] implementations, to be read back with [`FieldReader`].
+pub fn write_field(bytes: &mut Vec<u8>, field: &[u8]) -> Result<(), JobStoreError> {
+    let len: u32 = field
+        .len()
+        .try_into()
+        .map_err(|_| JobStoreError::FailedToSerializeJob)?;
+    bytes.extend_from_slice(&len.to_le_bytes());
+    bytes.extend_from_slice(field);
+    Ok(())
+}
+
+// Encodes a Sv2 message, to be stored with `write_field`.
+pub(crate) fn encode_sv2<M: binary_sv2::Encodable + binary_sv2::GetSize>(
+    message: M,
+) -> Result<Vec<u8>, JobStoreError> {
+    binary_sv2::to_bytes(message).map_err(|_| JobStoreError::FailedToSerializeJob)
+}
+
+/// Reads fields from bytes produced with [`write_field`].
+#[derive(Debug)]
+pub struct FieldReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> FieldReader<'a> {
+    /// Creates a reader over `bytes`.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Reads the next length-prefixed field.
+    pub fn read_field(&mut self) -> Result<&'a [u8], JobStoreError> {
+        let len = self.read_u32()? as usize;
+        self.take(len)
+    }
+
+    /// Reads a little-endian `u64`.
+    pub fn read_u64(&mut self) -> Result<u64, JobStoreError> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(
+            bytes.try_into().expect("slice is 8 bytes"),
+        ))
+    }
+
+    /// Reads a little-endian `u32`.
+    pub fn read_u32(&mut self) -> Result<u32, JobStoreError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes(
+            bytes.try_into().expect("slice is 4 bytes"),
+        ))
+    }
+
+    /// Returns the bytes that were not read yet.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], JobStoreError> {
+        if self.bytes.len() < len {
+            return Err(JobStoreError::FailedToDeserializeJob);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FileJobStore, JobStore, JobStoreError, ADD_ACTIVE_JOB, MARK_PAST_JOBS_AS_STALE};
+    use crate::server::jobs::{extended::ExtendedJob, factory::JobFactory, job_store::block_on};
+    use bitcoin::{transaction::TxOut, Amount, ScriptBuf, Target};
+    use std::{
+        fs::{File, OpenOptions},
+        io::Write,
+        path::PathBuf,
+    };
+    use template_distribution_sv2::NewTemplate;
+
+    fn temp_log_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("channels_sv2_{}_{}.log", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn new_job(job_factory: &mut JobFactory, template_id: u64) -> ExtendedJob<'static> {
+        let template = NewTemplate {
+            template_id,
+            future_template: true,
+            version: 536870912,
+            coinbase_tx_version: 2,
+            coinbase_prefix: vec![82, 0].try_into().unwrap(),
+            coinbase_tx_input_sequence: 4294967295,
+            coinbase_tx_value_remaining: 5000000000,
+            coinbase_tx_outputs_count: 1,
+            coinbase_tx_outputs: vec![
+                0, 0, 0, 0, 0, 0, 0, 0, 38, 106, 36, 170, 33, 169, 237, 226, 246, 28, 63, 113, 209,
+                222, 253, 63, 169, 153, 223, 163, 105, 83, 117, 92, 105, 6, 137, 121, 153, 98, 180,
+                139, 235, 216, 54, 151, 78, 140, 249,
+            ]
+            .try_into()
+            .unwrap(),
+            coinbase_tx_locktime: 0,
+            merkle_path: vec![].try_into().unwrap(),
+        };
+        let coinbase_reward_outputs = vec![TxOut {
+            value: Amount::from_sat(5000000000),
+            script_pubkey: ScriptBuf::new_op_return([0; 20]),
+        }];
+
+        job_factory
+            .new_extended_job(1, None, vec![0; 8], template, coinbase_reward_outputs, 16)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_job_set_is_replayed_after_reopen() {
+        let path = temp_log_path("replay");
+        let mut job_factory = JobFactory::new(true, None, None);
+        let job = new_job(&mut job_factory, 1);
+
+        let mut store = FileJobStore::open(&path).unwrap();
+        let job_id = store.add_future_job(1, job.clone());
+        assert!(store.activate_future_job(1, 1745611105));
+        // failed activations are not logged
+        assert!(!store.activate_future_job(2, 1745611105));
+        let second_job = new_job(&mut job_factory, 2);
+        let second_job_id = store.add_future_job(2, second_job);
+        store.mark_past_jobs_as_stale();
+        drop(store);
+
+        let store = FileJobStore::<ExtendedJob<'static>>::open(&path).unwrap();
+        let active_job = store.get_active_job().unwrap();
+        assert_eq!(active_job.get_job_id(), job_id);
+        assert_eq!(
+            active_job.get_job_message().min_ntime.clone().into_inner(),
+            Some(1745611105)
+        );
+        assert_eq!(
+            active_job.get_coinbase_tx_prefix_with_bip141(),
+            job.get_coinbase_tx_prefix_with_bip141()
+        );
+        assert_eq!(
+            store.get_future_job_id_from_template_id(2),
+            Some(second_job_id)
+        );
+        assert_eq!(store.get_size().future_jobs, 1);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_truncated_record_is_discarded() {
+        let path = temp_log_path("truncated");
+        let mut job_factory = JobFactory::new(true, None, None);
+
+        let mut store = FileJobStore::open(&path).unwrap();
+        store.add_future_job(1, new_job(&mut job_factory, 1));
+        drop(store);
+
+        // simulate a crash in the middle of an append
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[100, 0, 0, 0, ADD_ACTIVE_JOB]).unwrap();
+        drop(file);
+        let len_with_partial_record = std::fs::metadata(&path).unwrap().len();
+
+        let store = FileJobStore::<ExtendedJob<'static>>::open(&path).unwrap();
+        assert!(store.has_future_jobs());
+        assert!(store.get_active_job().is_none());
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().len(),
+            len_with_partial_record - 5
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_record_is_recovered() {
+        let path = temp_log_path("invalid");
+        let mut job_factory = JobFactory::new(true, None, None);
+
+        let mut store = FileJobStore::open(&path).unwrap();
+        store.add_future_job(1, new_job(&mut job_factory, 1));
+        drop(store);
+        let valid_len = std::fs::metadata(&path).unwrap().len();
+
+        // a record of unknown type, followed by a valid one
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[1, 0, 0, 0, 0xff]).unwrap();
+        file.write_all(&[1, 0, 0, 0, MARK_PAST_JOBS_AS_STALE])
+            .unwrap();
+        drop(file);
+
+        match FileJobStore::<ExtendedJob<'static>>::open(&path) {
+            Err(JobStoreError::InvalidLogRecord(offset)) => assert_eq!(offset, valid_len),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let mut store = FileJobStore::<ExtendedJob<'static>>::recover(&path).unwrap();
+        assert!(store.has_future_jobs());
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), valid_len);
+
+        // the recovered log can be appended to
+        assert!(store.activate_future_job(1, 1745611105));
+        drop(store);
+        let store = FileJobStore::<ExtendedJob<'static>>::open(&path).unwrap();
+        assert!(store.get_active_job().is_some());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_failed_write_stops_logging() {
+        let path = temp_log_path("failed_write");
+        let mut job_factory = JobFactory::new(true, None, None);
+
+        let mut store = FileJobStore::open(&path).unwrap();
+        store.add_future_job(1, new_job(&mut job_factory, 1));
+        // a read only handle makes every write fail
+        store.log_mut().file = File::open(&path).unwrap();
+        assert!(store.activate_future_job(1, 1745611105));
+        assert!(store.get_active_job().is_some());
+        assert!(matches!(store.take_error(), Some(JobStoreError::Io(_))));
+        drop(store);
+
+        let store = FileJobStore::<ExtendedJob<'static>>::open(&path).unwrap();
+        assert!(store.has_future_jobs());
+        assert!(store.get_active_job().is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_compacted_log_keeps_job_set() {
+        let path = temp_log_path("compacted");
+        let mut job_factory = JobFactory::new(true, None, None);
+        let target = Target::from_le_bytes([0x0f; 32]);
+
+        // job 1 is dropped, job 2 becomes stale, job 3 a past job, job 4 the active job and job 5
+        // a future job
+        let mut store = FileJobStore::open(&path).unwrap();
+        store.add_active_job(new_job(&mut job_factory, 1));
+        store.add_active_job(new_job(&mut job_factory, 2));
+        store.mark_past_jobs_as_stale();
+        store.add_active_job(new_job(&mut job_factory, 3));
+        store.mark_past_jobs_as_stale();
+        store.add_active_job(new_job(&mut job_factory, 4));
+        store.set_job_target(4, target);
+        store.add_future_job(5, new_job(&mut job_factory, 5));
+
+        let len = std::fs::metadata(&path).unwrap().len();
+        store.compact().unwrap();
+        assert!(std::fs::metadata(&path).unwrap().len() < len);
+        drop(store);
+
+        let store = FileJobStore::<ExtendedJob<'static>>::open(&path).unwrap();
+        assert!(store.get_stale_job(1).is_none());
+        assert!(store.get_stale_job(2).is_some());
+        assert!(store.get_past_job(3).is_some());
+        assert_eq!(store.get_active_job().unwrap().get_job_id(), 4);
+        assert_eq!(store.get_future_job_id_from_template_id(5), Some(5));
+        assert_eq!(store.get_job_target(4), Some(target));
+        assert_eq!(store.get_last_job_id(), Some(5));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_evicted_jobs_are_dropped_from_the_log() {
+        let path = temp_log_path("retention");
+        let mut job_factory = JobFactory::new(true, None, None);
+
+        let mut writer = FileJobStore::open(&path).unwrap();
+        let reader = FileJobStore::<ExtendedJob<'static>>::open_read_only(&path).unwrap();
+        writer.set_max_past_jobs(Some(1));
+        for template_id in 1..=4 {
+            writer.add_active_job(new_job(&mut job_factory, template_id));
+        }
+
+        // followers evict the same jobs as the writer
+        assert_eq!(reader.get_max_past_jobs(), Some(1));
+        assert!(reader.is_evicted_job(2));
+        assert!(reader.get_past_job(2).is_none());
+        assert!(reader.get_past_job(3).is_some());
+
+        let len = std::fs::metadata(&path).unwrap().len();
+        writer.compact().unwrap();
+        assert!(std::fs::metadata(&path).unwrap().len() < len);
+        drop(writer);
+
+        let store = FileJobStore::<ExtendedJob<'static>>::open(&path).unwrap();
+        assert_eq!(store.get_max_past_jobs(), Some(1));
+        assert!(store.is_evicted_job(1));
+        assert!(store.is_evicted_job(2));
+        assert!(store.get_past_job(3).is_some());
+        assert_eq!(store.get_active_job().unwrap().get_job_id(), 4);
+        assert_eq!(store.get_size().past_jobs, 1);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_read_only_store_follows_writer() {
+        let path = temp_log_path("read_only");
+        let mut job_factory = JobFactory::new(true, None, None);
+        let target = Target::from_le_bytes([0x0f; 32]);
+
+        let mut writer = FileJobStore::open(&path).unwrap();
+        let mut reader = FileJobStore::<ExtendedJob<'static>>::open_read_only(&path).unwrap();
+        assert!(reader.is_read_only());
+
+        let job_id = writer.add_future_job(1, new_job(&mut job_factory, 1));
+        assert_eq!(reader.get_future_job_id_from_template_id(1), Some(job_id));
+
+        // mutations of a read-only store are not applied
+        reader.add_future_job(2, new_job(&mut job_factory, 2));
+        assert!(matches!(reader.take_error(), Some(JobStoreError::ReadOnly)));
+        assert!(reader.get_future_job_id_from_template_id(2).is_none());
+
+        // the reader reopens the log once it's compacted
+        writer.compact().unwrap();
+        writer.set_job_target(job_id, target);
+        assert!(writer.activate_future_job(1, 1745611105));
+        assert_eq!(reader.get_active_job().unwrap().get_job_id(), job_id);
+        assert_eq!(reader.get_job_target(job_id), Some(target));
+        assert!(reader.take_error().is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_async_mutations_are_logged() {
+        // the store implements both traits
+        use super::JobStoreAsync;
+
+        let path = temp_log_path("async");
+        let mut job_factory = JobFactory::new(true, None, None);
+
+        let mut store = FileJobStore::open(&path).unwrap();
+        let job = new_job(&mut job_factory, 1);
+        let job_id = block_on(JobStoreAsync::add_future_job(&mut store, 1, job)).unwrap();
+        assert!(!block_on(JobStoreAsync::activate_future_job(
+            &mut store, 2, 1745611105
+        ))
+        .unwrap());
+
+        // a mutation that can't be logged is not applied
+        let active_job = new_job(&mut job_factory, 2);
+        store.log_mut().file = File::open(&path).unwrap();
+        let res = block_on(JobStoreAsync::add_active_job(&mut store, active_job));
+        assert!(matches!(res, Err(JobStoreError::Io(_))));
+        assert!(block_on(JobStoreAsync::get_active_job(&store))
+            .unwrap()
+            .is_none());
+        drop(store);
+
+        let mut reader = FileJobStore::<ExtendedJob<'static>>::open_read_only(&path).unwrap();
+        assert_eq!(
+            block_on(JobStoreAsync::get_future_job_id_from_template_id(
+                &reader, 1
+            ))
+            .unwrap(),
+            Some(job_id)
+        );
+        assert_eq!(
+            block_on(JobStoreAsync::get_last_job_id(&reader)).unwrap(),
+            Some(job_id)
+        );
+        assert!(matches!(
+            block_on(JobStoreAsync::mark_past_jobs_as_stale(&mut reader)),
+            Err(JobStoreError::ReadOnly)
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -3,22 +3,45 @@
 //! This module provides the [`JobStore`] trait and a default implementation for
 //! tracking mining job states (future, active, past, stale) for SV2 Extended and Standard channels.
 //!
+//! It also provides the [`JobStoreAsync`] trait, for backends that need to await I/O, together
+//! with the [`MemoryJobStore`] backend. A file-backed store, implementing both traits, is available
+//! on [`super::file_job_store`].
+//!
 //! ## Responsibilities
 //!
 //! - **Job Storage**: Manages collections of jobs indexed by job ID and template ID.
 //! - **Job Activation**: Handles transitions between future, active, past, and stale jobs.
 //! - **Template Mapping**: Tracks mappings from template IDs to job IDs for future jobs.
+//! - **Job Targets**: Keeps the target each job was handed out with, so that its shares are
+//!   validated against it wherever the job set is looked at.
+//! - **Job IDs**: Reports the ID of the last job added, so that job IDs are not reused when the
+//!   store outlives the job factory.
 //! - **Lifecycle Management**: Ensures correct state transitions when activating jobs or updating
 //!   chain tips.
+//! - **Retention**: Optionally bounds how many past jobs are kept, and for how long, evicting the
+//!   rest before the next chain tip.
+//! - **Size Reporting**: Reports how many jobs are held in each state, and an estimate of the memory
+//!   they take.
 //!
 //! ## Usage
 //!
 //! Use the [`JobStore`] trait for custom job store implementations, or the [`DefaultJobStore`]
 //! for standard job lifecycle management in mining channel abstractions.
+//!
+//! Use the [`JobStoreAsync`] trait for backends shared across tasks, and the `_async` methods of
+//! the mining channel abstractions to drive them.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Debug,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
-use std::{collections::HashMap, fmt::Debug};
+use bitcoin::Target;
 
-use super::Job;
+use super::{error::JobStoreError, Job};
 
 /// Trait for job lifecycle management in mining channels.
 ///
@@ -66,22 +89,154 @@ pub trait JobStore<T: Job>: Send + Sync + Debug {
 
     /// Returns an owned copy of a stale job from its job ID, if any.
     fn get_stale_job(&self, job_id: u32) -> Option<T>;
+
+    /// Associates a job with the target its shares are validated against.
+    ///
+    /// Targets are set before the job becomes active, and are dropped once the job is stale or
+    /// evicted.
+    fn set_job_target(&mut self, job_id: u32, target: Target);
+
+    /// Returns the target associated with a job, if any.
+    fn get_job_target(&self, job_id: u32) -> Option<Target>;
+
+    /// Returns the ID of the last job added to the store, if any.
+    ///
+    /// Job factories resume after it, so that a store that outlives them (e.g. one replayed from
+    /// disk) never sees a job ID reused.
+    fn get_last_job_id(&self) -> Option<u32>;
+
+    /// Returns true if the job was a past job evicted by the retention limits of the store, so
+    /// that shares can be rejected with the appropriate error code.
+    ///
+    /// Stores without retention limits never evict jobs.
+    fn is_evicted_job(&self, _job_id: u32) -> bool {
+        false
+    }
+
+    /// Returns the number of jobs held in each state, and an estimate of their memory footprint.
+    ///
+    /// Stores that don't track their size report an empty [`JobStoreSize`].
+    fn get_size(&self) -> JobStoreSize {
+        JobStoreSize::default()
+    }
+}
+
+/// Async variant of [`JobStore`].
+///
+/// Semantics of every method are the same as on [`JobStore`], but backends can await I/O and
+/// report failures via [`JobStoreError`].
+#[trait_variant::make(Send)]
+pub trait JobStoreAsync<T: Job>: Sync + Debug {
+    /// Adds a future job associated with a template ID.
+    /// Returns the new job's ID.
+    async fn add_future_job(&mut self, template_id: u64, job: T) -> Result<u32, JobStoreError>;
+
+    /// Adds an active job, moving the previous active job (if any) to past jobs.
+    async fn add_active_job(&mut self, job: T) -> Result<(), JobStoreError>;
+
+    /// Activates a future job given by template ID and header timestamp.
+    /// Returns `true` if successful, `false` if not found.
+    async fn activate_future_job(
+        &mut self,
+        template_id: u64,
+        prev_hash_header_timestamp: u32,
+    ) -> Result<bool, JobStoreError>;
+
+    /// Marks all past jobs as stale, so that shares can be rejected with the appropriate error
+    /// code
+    async fn mark_past_jobs_as_stale(&mut self) -> Result<(), JobStoreError>;
+
+    /// Returns the job ID for a future job from a template ID, if any.
+    async fn get_future_job_id_from_template_id(
+        &self,
+        template_id: u64,
+    ) -> Result<Option<u32>, JobStoreError>;
+
+    /// Returns an owned copy of the currently active job, if any.
+    async fn get_active_job(&self) -> Result<Option<T>, JobStoreError>;
+
+    /// Returns true if there are any future jobs, false otherwise.
+    async fn has_future_jobs(&self) -> Result<bool, JobStoreError>;
+
+    /// Returns an owned copy of a future job from its job ID, if any.
+    async fn get_future_job(&self, job_id: u32) -> Result<Option<T>, JobStoreError>;
+
+    /// Returns true if there are any past jobs, false otherwise.
+    async fn has_past_jobs(&self) -> Result<bool, JobStoreError>;
+
+    /// Returns an owned copy of a past job from its job ID, if any.
+    async fn get_past_job(&self, job_id: u32) -> Result<Option<T>, JobStoreError>;
+
+    /// Returns true if there are any stale jobs, false otherwise.
+    async fn has_stale_jobs(&self) -> Result<bool, JobStoreError>;
+
+    /// Returns an owned copy of a stale job from its job ID, if any.
+    async fn get_stale_job(&self, job_id: u32) -> Result<Option<T>, JobStoreError>;
+
+    /// Associates a job with the target its shares are validated against.
+    async fn set_job_target(&mut self, job_id: u32, target: Target) -> Result<(), JobStoreError>;
+
+    /// Returns the target associated with a job, if any.
+    async fn get_job_target(&self, job_id: u32) -> Result<Option<Target>, JobStoreError>;
+
+    /// Returns the ID of the last job added to the store, if any.
+    async fn get_last_job_id(&self) -> Result<Option<u32>, JobStoreError>;
+
+    /// Returns true if the job was a past job evicted by the retention limits of the store, so
+    /// that shares can be rejected with the appropriate error code.
+    async fn is_evicted_job(&self, job_id: u32) -> Result<bool, JobStoreError>;
+
+    /// Returns the number of jobs held in each state, and an estimate of their memory footprint.
+    ///
+    /// Stores that don't track their size report an empty [`JobStoreSize`].
+    // spelled out as a future, since the Send variant generated by trait_variant can't carry the
+    // default body of an async fn
+    fn get_size(&self) -> impl Future<Output = Result<JobStoreSize, JobStoreError>> + Send {
+        async { Ok(JobStoreSize::default()) }
+    }
+}
+
+/// Number of jobs held by a job store in each state, as reported by [`JobStore::get_size`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct JobStoreSize {
+    pub future_jobs: usize,
+    pub active_jobs: usize,
+    pub past_jobs: usize,
+    pub stale_jobs: usize,
+    /// Past jobs evicted since the last chain tip, of which only the job ID is kept.
+    pub evicted_jobs: usize,
+    /// Estimate of the memory taken by the jobs, in bytes (see [`Job::get_memory_footprint`]).
+    pub bytes: usize,
 }
 
 /// Default implementation of [`JobStore`] for tracking mining job states in SV2 channels.
 ///
 /// Maintains collections for future, active, past, and stale jobs, and tracks template-to-job ID
 /// mappings for future job activation.
+///
+/// By default, past jobs are kept until the next chain tip. Retention limits can be set with
+/// [`DefaultJobStore::set_max_past_jobs`] and [`DefaultJobStore::set_max_past_job_age`], so that
+/// frequent template updates don't pile up jobs. Evicted jobs are reported by
+/// [`JobStore::is_evicted_job`] until the next chain tip.
 #[derive(Debug)]
 pub struct DefaultJobStore<T: Job + Clone> {
     future_template_to_job_id: HashMap<u64, u32>,
     // Future jobs are indexed with job_id (u32)
     future_jobs: HashMap<u32, T>,
     active_job: Option<T>,
-    // Past jobs are indexed with job_id (u32)
-    past_jobs: HashMap<u32, T>,
+    // Past jobs are indexed with job_id (u32), along with the time they stopped being active
+    past_jobs: HashMap<u32, (T, Instant)>,
+    // Past job ids, from the oldest to the most recent
+    past_job_ids: VecDeque<u32>,
+    // Ids of the past jobs evicted since the last chain tip
+    evicted_job_ids: HashSet<u32>,
     // Stale jobs are indexed with job_id (u32)
     stale_jobs: HashMap<u32, T>,
+    // Targets of the jobs that are not stale or evicted yet
+    job_targets: HashMap<u32, Target>,
+    last_job_id: Option<u32>,
+    max_past_jobs: Option<usize>,
+    max_past_job_age: Option<Duration>,
 }
 
 impl<T: Job + Clone> DefaultJobStore<T> {
@@ -92,9 +247,129 @@ impl<T: Job + Clone> DefaultJobStore<T> {
             future_jobs: HashMap::new(),
             active_job: None,
             past_jobs: HashMap::new(),
+            past_job_ids: VecDeque::new(),
+            evicted_job_ids: HashSet::new(),
             stale_jobs: HashMap::new(),
+            job_targets: HashMap::new(),
+            last_job_id: None,
+            max_past_jobs: None,
+            max_past_job_age: None,
+        }
+    }
+
+    /// Sets the maximum number of past jobs to keep.
+    ///
+    /// When exceeded, the oldest past jobs are evicted. `None` (the default) keeps every past job
+    /// until the next chain tip.
+    pub fn set_max_past_jobs(&mut self, max_past_jobs: Option<usize>) {
+        self.max_past_jobs = max_past_jobs;
+        self.evict_past_jobs();
+    }
+
+    /// Returns the maximum number of past jobs to keep, if any.
+    pub fn get_max_past_jobs(&self) -> Option<usize> {
+        self.max_past_jobs
+    }
+
+    /// Sets for how long a job is kept after it stops being the active job.
+    ///
+    /// Older past jobs are evicted. `None` (the default) keeps every past job until the next
+    /// chain tip.
+    pub fn set_max_past_job_age(&mut self, max_past_job_age: Option<Duration>) {
+        self.max_past_job_age = max_past_job_age;
+        self.evict_past_jobs();
+    }
+
+    /// Returns for how long a job is kept after it stops being the active job, if limited.
+    pub fn get_max_past_job_age(&self) -> Option<Duration> {
+        self.max_past_job_age
+    }
+
+    // Moves the currently active job (if any) to past jobs, so it can be marked as stale.
+    fn move_active_job_to_past_jobs(&mut self) {
+        if let Some(active_job) = self.active_job.take() {
+            let job_id = active_job.get_job_id();
+            self.past_jobs.insert(job_id, (active_job, Instant::now()));
+            self.past_job_ids.push_back(job_id);
+        }
+        self.evict_past_jobs();
+    }
+
+    // Evicts the past jobs above the retention limits, oldest first.
+    fn evict_past_jobs(&mut self) {
+        while let Some(&job_id) = self.past_job_ids.front() {
+            let above_max_count = self
+                .max_past_jobs
+                .is_some_and(|max_past_jobs| self.past_job_ids.len() > max_past_jobs);
+            if !above_max_count && !self.is_past_job_expired(job_id) {
+                break;
+            }
+
+            self.past_job_ids.pop_front();
+            self.past_jobs.remove(&job_id);
+            self.job_targets.remove(&job_id);
+            self.evicted_job_ids.insert(job_id);
         }
     }
+
+    // Past jobs are evicted when the store is mutated, so expired jobs can still be around.
+    fn is_past_job_expired(&self, job_id: u32) -> bool {
+        match (self.past_jobs.get(&job_id), self.max_past_job_age) {
+            (Some((_, since)), Some(max_past_job_age)) => since.elapsed() >= max_past_job_age,
+            _ => false,
+        }
+    }
+
+    // Accessors and setters of the raw job set, for job stores persisting it.
+
+    pub(super) fn future_jobs(&self) -> impl Iterator<Item = (u64, &T)> + '_ {
+        self.future_template_to_job_id
+            .iter()
+            .filter_map(|(template_id, job_id)| {
+                self.future_jobs.get(job_id).map(|job| (*template_id, job))
+            })
+    }
+
+    // past jobs, from the oldest to the most recent
+    pub(super) fn past_jobs(&self) -> impl Iterator<Item = &T> + '_ {
+        self.past_job_ids
+            .iter()
+            .filter_map(|job_id| self.past_jobs.get(job_id).map(|(job, _)| job))
+    }
+
+    pub(super) fn stale_jobs(&self) -> impl Iterator<Item = &T> + '_ {
+        self.stale_jobs.values()
+    }
+
+    pub(super) fn evicted_job_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.evicted_job_ids.iter().copied()
+    }
+
+    pub(super) fn job_targets(&self) -> impl Iterator<Item = (u32, Target)> + '_ {
+        self.job_targets
+            .iter()
+            .map(|(job_id, target)| (*job_id, *target))
+    }
+
+    // past jobs are restored as if they just stopped being active
+    pub(super) fn restore_past_job(&mut self, job: T) {
+        let job_id = job.get_job_id();
+        self.past_jobs.insert(job_id, (job, Instant::now()));
+        self.past_job_ids.push_back(job_id);
+        self.evict_past_jobs();
+    }
+
+    pub(super) fn restore_stale_job(&mut self, job: T) {
+        self.stale_jobs.insert(job.get_job_id(), job);
+    }
+
+    pub(super) fn restore_evicted_job_id(&mut self, job_id: u32) {
+        self.evicted_job_ids.insert(job_id);
+    }
+
+    pub(super) fn restore_last_job_id(&mut self, job_id: u32) {
+        self.last_job_id = Some(job_id);
+    }
 }
 
 impl<T: Job + Clone> Default for DefaultJobStore<T> {
@@ -106,18 +381,19 @@ impl<T: Job + Clone> Default for DefaultJobStore<T> {
 impl<T: Job + Clone + Debug> JobStore<T> for DefaultJobStore<T> {
     fn add_future_job(&mut self, template_id: u64, new_job: T) -> u32 {
         let new_job_id = new_job.get_job_id();
+        self.last_job_id = Some(new_job_id);
         self.future_jobs.insert(new_job_id, new_job);
         self.future_template_to_job_id
             .insert(template_id, new_job_id);
+        self.evict_past_jobs();
         new_job_id
     }
 
     fn add_active_job(&mut self, job: T) {
         // Move currently active job to past jobs (so it can be marked as stale)
-        if let Some(active_job) = self.active_job.take() {
-            self.past_jobs.insert(active_job.get_job_id(), active_job);
-        }
+        self.move_active_job_to_past_jobs();
         // Set the new active job
+        self.last_job_id = Some(job.get_job_id());
         self.active_job = Some(job);
     }
 
@@ -134,9 +410,7 @@ impl<T: Job + Clone + Debug> JobStore<T> for DefaultJobStore<T> {
             };
 
         // Move currently active job to past jobs (so it can be marked as stale)
-        if let Some(active_job) = self.active_job.take() {
-            self.past_jobs.insert(active_job.get_job_id(), active_job);
-        }
+        self.move_active_job_to_past_jobs();
 
         // Activate the future job
         future_job.activate(prev_hash_header_timestamp);
@@ -151,7 +425,17 @@ impl<T: Job + Clone + Debug> JobStore<T> for DefaultJobStore<T> {
 
     fn mark_past_jobs_as_stale(&mut self) {
         // Transfer past jobs to stale jobs collection and reset past jobs to empty
-        self.stale_jobs = std::mem::take(&mut self.past_jobs);
+        self.stale_jobs = std::mem::take(&mut self.past_jobs)
+            .into_iter()
+            .map(|(job_id, (job, _))| (job_id, job))
+            .collect();
+        for job_id in self.stale_jobs.keys().chain(self.evicted_job_ids.iter()) {
+            self.job_targets.remove(job_id);
+        }
+        self.past_job_ids.clear();
+        // shares for evicted jobs are now stale, but evicted jobs can't be told apart from
+        // unknown ones anymore
+        self.evicted_job_ids.clear();
     }
 
     fn get_future_job_id_from_template_id(&self, template_id: u64) -> Option<u32> {
@@ -171,11 +455,16 @@ impl<T: Job + Clone + Debug> JobStore<T> for DefaultJobStore<T> {
     }
 
     fn has_past_jobs(&self) -> bool {
-        !self.past_jobs.is_empty()
+        self.past_jobs
+            .keys()
+            .any(|job_id| !self.is_past_job_expired(*job_id))
     }
 
     fn get_past_job(&self, job_id: u32) -> Option<T> {
-        self.past_jobs.get(&job_id).cloned()
+        if self.is_past_job_expired(job_id) {
+            return None;
+        }
+        self.past_jobs.get(&job_id).map(|(job, _)| job.clone())
     }
 
     fn has_stale_jobs(&self) -> bool {
@@ -185,4 +474,312 @@ impl<T: Job + Clone + Debug> JobStore<T> for DefaultJobStore<T> {
     fn get_stale_job(&self, job_id: u32) -> Option<T> {
         self.stale_jobs.get(&job_id).cloned()
     }
+
+    fn set_job_target(&mut self, job_id: u32, target: Target) {
+        self.job_targets.insert(job_id, target);
+    }
+
+    fn get_job_target(&self, job_id: u32) -> Option<Target> {
+        self.job_targets.get(&job_id).copied()
+    }
+
+    fn get_last_job_id(&self) -> Option<u32> {
+        self.last_job_id
+    }
+
+    fn is_evicted_job(&self, job_id: u32) -> bool {
+        self.evicted_job_ids.contains(&job_id) || self.is_past_job_expired(job_id)
+    }
+
+    fn get_size(&self) -> JobStoreSize {
+        let bytes = self
+            .future_jobs
+            .values()
+            .chain(self.active_job.iter())
+            .chain(self.past_jobs.values().map(|(job, _)| job))
+            .chain(self.stale_jobs.values())
+            .map(|job| job.get_memory_footprint())
+            .sum();
+
+        JobStoreSize {
+            future_jobs: self.future_jobs.len(),
+            active_jobs: self.active_job.iter().count(),
+            past_jobs: self.past_jobs.len(),
+            stale_jobs: self.stale_jobs.len(),
+            evicted_jobs: self.evicted_job_ids.len(),
+            bytes,
+        }
+    }
+}
+
+/// In-memory implementation of [`JobStoreAsync`].
+///
+/// Wraps a [`DefaultJobStore`] behind a lock. Clones share the same jobs, so a channel and other
+/// tasks of the same process can look at a single job set.
+#[derive(Debug)]
+pub struct MemoryJobStore<T: Job + Clone> {
+    inner: Arc<Mutex<DefaultJobStore<T>>>,
+}
+
+impl<T: Job + Clone> MemoryJobStore<T> {
+    /// Creates a new empty job store.
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(DefaultJobStore::new())),
+        }
+    }
+
+    fn with_store<R>(
+        &self,
+        f: impl FnOnce(&mut DefaultJobStore<T>) -> R,
+    ) -> Result<R, JobStoreError> {
+        let mut store = self.inner.lock().map_err(|_| JobStoreError::PoisonedLock)?;
+        Ok(f(&mut store))
+    }
+}
+
+impl<T: Job + Clone> Default for MemoryJobStore<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Job + Clone> From<DefaultJobStore<T>> for MemoryJobStore<T> {
+    /// Wraps a [`DefaultJobStore`], keeping its jobs and retention limits.
+    fn from(store: DefaultJobStore<T>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(store)),
+        }
+    }
+}
+
+impl<T: Job + Clone> Clone for MemoryJobStore<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: Job + Clone + Debug> JobStoreAsync<T> for MemoryJobStore<T> {
+    async fn add_future_job(&mut self, template_id: u64, job: T) -> Result<u32, JobStoreError> {
+        self.with_store(|store| store.add_future_job(template_id, job))
+    }
+
+    async fn add_active_job(&mut self, job: T) -> Result<(), JobStoreError> {
+        self.with_store(|store| store.add_active_job(job))
+    }
+
+    async fn activate_future_job(
+        &mut self,
+        template_id: u64,
+        prev_hash_header_timestamp: u32,
+    ) -> Result<bool, JobStoreError> {
+        self.with_store(|store| store.activate_future_job(template_id, prev_hash_header_timestamp))
+    }
+
+    async fn mark_past_jobs_as_stale(&mut self) -> Result<(), JobStoreError> {
+        self.with_store(|store| store.mark_past_jobs_as_stale())
+    }
+
+    async fn get_future_job_id_from_template_id(
+        &self,
+        template_id: u64,
+    ) -> Result<Option<u32>, JobStoreError> {
+        self.with_store(|store| store.get_future_job_id_from_template_id(template_id))
+    }
+
+    async fn get_active_job(&self) -> Result<Option<T>, JobStoreError> {
+        self.with_store(|store| store.get_active_job())
+    }
+
+    async fn has_future_jobs(&self) -> Result<bool, JobStoreError> {
+        self.with_store(|store| store.has_future_jobs())
+    }
+
+    async fn get_future_job(&self, job_id: u32) -> Result<Option<T>, JobStoreError> {
+        self.with_store(|store| store.get_future_job(job_id))
+    }
+
+    async fn has_past_jobs(&self) -> Result<bool, JobStoreError> {
+        self.with_store(|store| store.has_past_jobs())
+    }
+
+    async fn get_past_job(&self, job_id: u32) -> Result<Option<T>, JobStoreError> {
+        self.with_store(|store| store.get_past_job(job_id))
+    }
+
+    async fn has_stale_jobs(&self) -> Result<bool, JobStoreError> {
+        self.with_store(|store| store.has_stale_jobs())
+    }
+
+    async fn get_stale_job(&self, job_id: u32) -> Result<Option<T>, JobStoreError> {
+        self.with_store(|store| store.get_stale_job(job_id))
+    }
+
+    async fn set_job_target(&mut self, job_id: u32, target: Target) -> Result<(), JobStoreError> {
+        self.with_store(|store| store.set_job_target(job_id, target))
+    }
+
+    async fn get_job_target(&self, job_id: u32) -> Result<Option<Target>, JobStoreError> {
+        self.with_store(|store| store.get_job_target(job_id))
+    }
+
+    async fn get_last_job_id(&self) -> Result<Option<u32>, JobStoreError> {
+        self.with_store(|store| store.get_last_job_id())
+    }
+
+    async fn is_evicted_job(&self, job_id: u32) -> Result<bool, JobStoreError> {
+        self.with_store(|store| store.is_evicted_job(job_id))
+    }
+
+    async fn get_size(&self) -> Result<JobStoreSize, JobStoreError> {
+        self.with_store(|store| store.get_size())
+    }
+}
+
+/// Drives a future to completion on the current thread.
+///
+/// Only meant for tests of [`JobStoreAsync`] consumers, whose futures never wait on external
+/// events.
+#[cfg(test)]
+pub(crate) fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::task::{Context, Poll, Wake, Waker};
+
+    struct NoopWaker;
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut context = Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug)]
+    struct TestJob {
+        job_id: u32,
+    }
+
+    impl Job for TestJob {
+        fn get_job_id(&self) -> u32 {
+            self.job_id
+        }
+
+        fn activate(&mut self, _prev_hash_header_timestamp: u32) {}
+    }
+
+    #[test]
+    fn test_past_jobs_evicted_by_count() {
+        let mut job_store = DefaultJobStore::new();
+        job_store.set_max_past_jobs(Some(2));
+
+        for job_id in 1..=5 {
+            job_store.add_active_job(TestJob { job_id });
+        }
+
+        // job 5 is active, jobs 3 and 4 are kept as past jobs
+        assert!(job_store.is_evicted_job(1));
+        assert!(job_store.is_evicted_job(2));
+        assert!(job_store.get_past_job(2).is_none());
+        assert!(job_store.get_past_job(3).is_some());
+        assert!(job_store.get_past_job(4).is_some());
+        assert!(!job_store.is_evicted_job(5));
+
+        // on a new chain tip, evicted jobs are forgotten and kept past jobs become stale
+        job_store.mark_past_jobs_as_stale();
+        assert!(!job_store.is_evicted_job(1));
+        assert!(job_store.get_stale_job(3).is_some());
+        assert!(job_store.get_stale_job(1).is_none());
+    }
+
+    #[test]
+    fn test_past_jobs_evicted_by_age() {
+        let mut job_store = DefaultJobStore::new();
+        job_store.set_max_past_job_age(Some(Duration::from_secs(3600)));
+
+        job_store.add_active_job(TestJob { job_id: 1 });
+        job_store.add_active_job(TestJob { job_id: 2 });
+        assert!(job_store.get_past_job(1).is_some());
+        assert!(!job_store.is_evicted_job(1));
+
+        // expired past jobs are reported as evicted before the store is mutated again
+        job_store.max_past_job_age = Some(Duration::ZERO);
+        assert!(job_store.get_past_job(1).is_none());
+        assert!(!job_store.has_past_jobs());
+        assert!(job_store.is_evicted_job(1));
+
+        job_store.add_active_job(TestJob { job_id: 3 });
+        assert_eq!(job_store.get_size().past_jobs, 0);
+        assert_eq!(job_store.get_size().evicted_jobs, 2);
+        assert!(job_store.is_evicted_job(2));
+        assert!(!job_store.is_evicted_job(3));
+    }
+
+    #[test]
+    fn test_job_store_size() {
+        let mut job_store = DefaultJobStore::new();
+        assert_eq!(job_store.get_size(), JobStoreSize::default());
+
+        job_store.add_active_job(TestJob { job_id: 1 });
+        job_store.add_active_job(TestJob { job_id: 2 });
+        job_store.add_future_job(1, TestJob { job_id: 3 });
+
+        let job_size = std::mem::size_of::<TestJob>();
+        assert_eq!(
+            job_store.get_size(),
+            JobStoreSize {
+                future_jobs: 1,
+                active_jobs: 1,
+                past_jobs: 1,
+                stale_jobs: 0,
+                evicted_jobs: 0,
+                bytes: 3 * job_size,
+            }
+        );
+
+        job_store.activate_future_job(1, 0);
+        let size = job_store.get_size();
+        assert_eq!(size.future_jobs, 0);
+        assert_eq!(size.past_jobs, 0);
+        assert_eq!(size.stale_jobs, 2);
+        assert_eq!(size.bytes, 3 * job_size);
+    }
+
+    #[test]
+    fn test_job_targets_follow_job_lifecycle() {
+        let mut job_store = DefaultJobStore::new();
+        job_store.set_max_past_jobs(Some(1));
+        assert_eq!(job_store.get_last_job_id(), None);
+        let target = Target::from_le_bytes([0x0f; 32]);
+
+        for job_id in 1..=3 {
+            job_store.set_job_target(job_id, target);
+            job_store.add_active_job(TestJob { job_id });
+        }
+        job_store.add_future_job(1, TestJob { job_id: 4 });
+        assert_eq!(job_store.get_last_job_id(), Some(4));
+
+        // the target of an evicted job is dropped
+        assert_eq!(job_store.get_job_target(1), None);
+        assert_eq!(job_store.get_job_target(2), Some(target));
+        assert_eq!(job_store.get_job_target(3), Some(target));
+
+        // once the future job is activated, only its target is kept
+        job_store.set_job_target(4, target);
+        assert!(job_store.activate_future_job(1, 0));
+        assert_eq!(job_store.get_job_target(2), None);
+        assert_eq!(job_store.get_job_target(3), None);
+        assert_eq!(job_store.get_job_target(4), Some(target));
+        assert_eq!(job_store.get_last_job_id(), Some(4));
+    }
 }
@@ -9,7 +9,8 @@
 //! - **Extended Jobs**: See [`extended`] submodule for SV2 extended job implementation.
 //! - **Standard Jobs**: See [`standard`] submodule for SV2 standard job implementation.
 //! - **Job Factories**: See [`factory`] for job creation logic and unique job ID assignment.
-//! - **Job Storage**: See [`job_store`] for job lifecycle management and storage abstractions.
+//! - **Job Storage**: See [`job_store`] for job lifecycle management and storage abstractions, and
+//!   [`file_job_store`] for a job store persisted to an append-only log.
 //! - **Job Origin Tracking**: Tracks job origin (template or custom job message).
 //! - **Job Trait**: Unified trait for all mining job types, supporting activation and job ID
 //!   retrieval.
@@ -21,9 +22,11 @@
 pub mod error;
 pub mod extended;
 pub mod factory;
+pub mod file_job_store;
 pub mod job_store;
 pub mod standard;
 
+use bitcoin::transaction::TxOut;
 use mining_sv2::SetCustomMiningJob;
 use template_distribution_sv2::NewTemplate;
 
@@ -43,4 +46,19 @@ pub trait Job: Send + Sync {
 
     /// Activates the job for a new chain tip or prev_hash header timestamp.
     fn activate(&mut self, prev_hash_header_timestamp: u32);
+
+    /// Returns an estimate of the memory taken by this job, in bytes, including heap allocations.
+    ///
+    /// The default implementation only accounts for the size of the type itself.
+    fn get_memory_footprint(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+}
+
+// Estimate of the memory taken by coinbase outputs, including their scripts.
+pub(crate) fn coinbase_outputs_footprint(outputs: &[TxOut]) -> usize {
+    outputs
+        .iter()
+        .map(|output| std::mem::size_of::<TxOut>() + output.script_pubkey.len())
+        .sum()
 }
@@ -21,10 +21,18 @@
 
 use crate::{
     outputs::deserialize_template_outputs,
-    server::jobs::{error::StandardJobError, Job},
+    server::jobs::{
+        coinbase_outputs_footprint,
+        error::{JobStoreError, StandardJobError},
+        file_job_store::{encode_sv2, write_field, FieldReader, PersistentJob},
+        Job,
+    },
+};
+use binary_sv2::{GetSize, Sv2Option, U256};
+use bitcoin::{
+    consensus::{deserialize, serialize},
+    transaction::TxOut,
 };
-use binary_sv2::{Sv2Option, U256};
-use bitcoin::transaction::TxOut;
 use mining_sv2::NewMiningJob;
 use template_distribution_sv2::NewTemplate;
 
@@ -51,6 +59,15 @@ impl Job for StandardJob<'_> {
     fn activate(&mut self, min_ntime: u32) {
         self.activate(min_ntime);
     }
+
+    /// Returns an estimate of the memory taken by this job, including its template.
+    fn get_memory_footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.template.get_size()
+            + self.extranonce_prefix.len()
+            + coinbase_outputs_footprint(&self.coinbase_outputs)
+            + self.job_message.get_size()
+    }
 }
 
 impl<'a> StandardJob<'a> {
@@ -116,3 +133,37 @@ impl<'a> StandardJob<'a> {
         self.job_message.min_ntime = Sv2Option::new(Some(min_ntime));
     }
 }
+
+impl PersistentJob for StandardJob<'static> {
+    fn to_bytes(&self) -> Result<Vec<u8>, JobStoreError> {
+        let mut bytes = vec![];
+        write_field(&mut bytes, &encode_sv2(self.template.clone())?)?;
+        write_field(&mut bytes, &self.extranonce_prefix)?;
+        write_field(&mut bytes, &serialize(&self.coinbase_outputs))?;
+        write_field(&mut bytes, &encode_sv2(self.job_message.clone())?)?;
+        Ok(bytes)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, JobStoreError> {
+        let mut reader = FieldReader::new(bytes);
+
+        let mut template = reader.read_field()?.to_vec();
+        let template = binary_sv2::from_bytes::<NewTemplate>(&mut template)
+            .map_err(|_| JobStoreError::FailedToDeserializeJob)?
+            .into_static();
+        let extranonce_prefix = reader.read_field()?.to_vec();
+        let coinbase_outputs =
+            deserialize(reader.read_field()?).map_err(|_| JobStoreError::FailedToDeserializeJob)?;
+        let mut job_message = reader.read_field()?.to_vec();
+        let job_message = binary_sv2::from_bytes::<NewMiningJob>(&mut job_message)
+            .map_err(|_| JobStoreError::FailedToDeserializeJob)?
+            .into_static();
+
+        Ok(Self {
+            template,
+            extranonce_prefix,
+            coinbase_outputs,
+            job_message,
+        })
+    }
+}
@@ -5,4 +5,6 @@ pub mod extended;
 pub mod group;
 pub mod jobs;
 pub mod share_accounting;
+pub mod share_reject_detail;
 pub mod standard;
+pub mod worker_accounting;
@@ -18,6 +18,7 @@
 //! Intended for use within mining server implementations that process SV2 share submissions and
 //! issue `SubmitShares.Success` messages. Not intended for use by mining clients.
 
+use super::jobs::error::JobStoreError;
 use bitcoin::hashes::sha256d::Hash;
 use std::collections::HashSet;
 
@@ -48,6 +49,8 @@ pub enum ShareValidationError {
     Invalid,
     /// The share is stale due to chain tip changes.
     Stale,
+    /// The share is for a past job evicted by the retention limits of the job store.
+    EvictedJob,
     /// The submitted job ID does not refer to any known job for this channel.
     InvalidJobId,
     /// The share does not meet the required target difficulty.
@@ -62,6 +65,8 @@ pub enum ShareValidationError {
     NoChainTip,
     /// The share extranonce size is different from the channel's rollable extranonce size.
     BadExtranonceSize,
+    /// The job store failed to look up the job of the share.
+    JobStoreError(JobStoreError),
 }
 
 /// The state of share validation in the context of some specific channel (either Extended or
@@ -0,0 +1,74 @@
+//! Share Reject Detail - Mining Server Abstraction.
+//!
+//! This module builds the diagnostics of the Share Reject Detail extension (`0x4003`), which
+//! servers append as TLV fields to `SubmitShares.Error` to tell clients why a share was rejected.
+//!
+//! [`ExtendedChannel::validate_share_with_reject_detail`](super::extended::ExtendedChannel::validate_share_with_reject_detail)
+//! and [`StandardChannel::validate_share_with_reject_detail`](super::standard::StandardChannel::validate_share_with_reject_detail)
+//! return the detail of a rejected share together with its [`ShareValidationError`], built from
+//! what the validation found out about the share: the state of its job, the target it had to
+//! meet and its hash.
+
+use super::share_accounting::ShareValidationError;
+use bitcoin::{hashes::sha256d::Hash, Target};
+use extensions_sv2::{JobState, RejectReason, ShareRejectDetail};
+
+impl From<&ShareValidationError> for RejectReason {
+    fn from(error: &ShareValidationError) -> Self {
+        match error {
+            ShareValidationError::Invalid | ShareValidationError::JobStoreError(_) => {
+                RejectReason::Invalid
+            }
+            ShareValidationError::Stale => RejectReason::Stale,
+            ShareValidationError::EvictedJob => RejectReason::EvictedJob,
+            ShareValidationError::InvalidJobId => RejectReason::InvalidJobId,
+            ShareValidationError::DoesNotMeetTarget => RejectReason::DoesNotMeetTarget,
+            ShareValidationError::VersionRollingNotAllowed => {
+                RejectReason::VersionRollingNotAllowed
+            }
+            ShareValidationError::DuplicateShare => RejectReason::DuplicateShare,
+            ShareValidationError::InvalidCoinbase => RejectReason::InvalidCoinbase,
+            ShareValidationError::NoChainTip => RejectReason::NoChainTip,
+            ShareValidationError::BadExtranonceSize => RejectReason::BadExtranonceSize,
+        }
+    }
+}
+
+// What the validation of a share found out about it.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ShareDetail {
+    job_state: JobState,
+    job_target: Option<Target>,
+    share_hash: Option<Hash>,
+}
+
+impl ShareDetail {
+    pub(crate) fn set_job_state(&mut self, job_state: JobState) {
+        self.job_state = job_state;
+    }
+
+    pub(crate) fn set_job_target(&mut self, job_target: Target) {
+        self.job_target = Some(job_target);
+    }
+
+    pub(crate) fn set_share_hash(&mut self, share_hash: Hash) {
+        self.share_hash = Some(share_hash);
+    }
+
+    // Builds the detail of the share rejected with `error`, the expected target being the
+    // channel target if the job of the share wasn't found.
+    pub(crate) fn to_share_reject_detail(
+        &self,
+        error: &ShareValidationError,
+        channel_target: Target,
+    ) -> ShareRejectDetail {
+        let expected_target = self.job_target.unwrap_or(channel_target);
+        let mut detail = ShareRejectDetail::new(error.into())
+            .with_job_state(self.job_state)
+            .with_expected_target(expected_target.to_le_bytes());
+        if let Some(share_hash) = self.share_hash {
+            detail = detail.with_share_hash(*share_hash.as_ref());
+        }
+        detail
+    }
+}
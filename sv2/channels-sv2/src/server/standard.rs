@@ -19,6 +19,10 @@
 //!   responses.
 //! - **Chain Tip Management**: Tracks the latest known chain tip (block height, previous hash,
 //!   timestamp, and target) for constructing headers and validating shares.
+//! - **Network Rules**: Applies the block target rules of the configured [`Network`] when
+//!   detecting found blocks.
+//! - **Signet Signing**: Optionally signs jobs for a signet challenge, so found blocks carry a valid
+//!   signet solution.
 //!
 //! ## Usage
 //!
@@ -38,10 +42,16 @@ use crate::{
     server::{
         error::StandardChannelError,
         jobs::{
-            extended::ExtendedJob, factory::JobFactory, job_store::JobStore, standard::StandardJob,
+            error::JobStoreError,
+            extended::ExtendedJob,
+            factory::JobFactory,
+            job_store::{JobStore, JobStoreAsync, JobStoreSize},
+            standard::StandardJob,
         },
         share_accounting::{ShareAccounting, ShareValidationError, ShareValidationResult},
+        share_reject_detail::ShareDetail,
     },
+    signet::{has_solution, SignetSigner},
     target::{bytes_to_hex, hash_rate_to_target, u256_to_block_hash},
     MAX_EXTRANONCE_PREFIX_LEN,
 };
@@ -54,10 +64,11 @@ use bitcoin::{
     consensus::Encodable,
     hashes::sha256d::Hash,
     transaction::{OutPoint, Transaction, TxIn, TxOut, Version as TxVersion},
-    CompactTarget, Sequence, Target,
+    CompactTarget, Network, Sequence, Target,
 };
+use extensions_sv2::{JobState, ShareRejectDetail};
 use mining_sv2::SubmitSharesStandard;
-use std::{collections::HashMap, convert::TryInto, marker::PhantomData};
+use std::{convert::TryInto, marker::PhantomData};
 use template_distribution_sv2::{NewTemplate, SetNewPrevHash};
 use tracing::debug;
 
@@ -69,37 +80,31 @@ use tracing::debug;
 /// - the channel's unique `extranonce_prefix`
 /// - the channel's requested max target (limit established by the client)
 /// - the channel's current target
-/// - the channel's mapping between `job_id` and target
 /// - the channel's nominal hashrate
-/// - the channel's [`JobStore`]
+/// - the channel's [`JobStore`] (or [`JobStoreAsync`])
 /// - the channel's share accounting state
 /// - the channel's expected share per minute
 /// - the channel's job factory
 /// - the channel's chain tip
+/// - the channel's [`Network`]
 #[derive(Debug)]
-pub struct StandardChannel<'a, J>
-where
-    J: JobStore<StandardJob<'a>>,
-{
+pub struct StandardChannel<'a, J> {
     pub channel_id: u32,
     user_identity: String,
     extranonce_prefix: Vec<u8>,
     requested_max_target: Target,
     target: Target,
-    job_id_to_target: HashMap<u32, Target>,
     nominal_hashrate: f32,
     share_accounting: ShareAccounting,
     expected_share_per_minute: f32,
     job_store: J,
     job_factory: JobFactory,
     chain_tip: Option<ChainTip>,
+    network: Network,
     phantom: PhantomData<&'a ()>,
 }
 
-impl<'a, J> StandardChannel<'a, J>
-where
-    J: JobStore<StandardJob<'a>>,
-{
+impl<'a, J> StandardChannel<'a, J> {
     /// Constructor of `StandardChannel` for a Sv2 Pool Server.
     /// Not meant for usage on a Sv2 Job Declaration Client.
     ///
@@ -121,7 +126,10 @@ where
         expected_share_per_minute: f32,
         job_store: J,
         pool_tag_string: String,
-    ) -> Result<Self, StandardChannelError> {
+    ) -> Result<Self, StandardChannelError>
+    where
+        J: JobStore<StandardJob<'a>>,
+    {
         Self::new(
             channel_id,
             user_identity,
@@ -136,6 +144,36 @@ where
         )
     }
 
+    /// Variant of [`StandardChannel::new_for_pool`] for channels backed by a [`JobStoreAsync`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_for_pool_async(
+        channel_id: u32,
+        user_identity: String,
+        extranonce_prefix: Vec<u8>,
+        requested_max_target: Target,
+        nominal_hashrate: f32,
+        share_batch_size: usize,
+        expected_share_per_minute: f32,
+        job_store: J,
+        pool_tag_string: String,
+    ) -> Result<Self, StandardChannelError>
+    where
+        J: JobStoreAsync<StandardJob<'a>>,
+    {
+        Self::new_async(
+            channel_id,
+            user_identity,
+            extranonce_prefix,
+            requested_max_target,
+            nominal_hashrate,
+            share_batch_size,
+            expected_share_per_minute,
+            job_store,
+            Some(pool_tag_string),
+            None,
+        )
+    }
+
     /// Constructor of `StandardChannel` for a Sv2 Job Declaration Client.
     /// Not meant for usage on a Sv2 Pool Server.
     ///
@@ -158,7 +196,10 @@ where
         job_store: J,
         pool_tag_string: Option<String>,
         miner_tag_string: String,
-    ) -> Result<Self, StandardChannelError> {
+    ) -> Result<Self, StandardChannelError>
+    where
+        J: JobStore<StandardJob<'a>>,
+    {
         Self::new(
             channel_id,
             user_identity,
@@ -173,7 +214,39 @@ where
         )
     }
 
-    // private constructor
+    /// Variant of [`StandardChannel::new_for_job_declaration_client`] for channels backed by a
+    /// [`JobStoreAsync`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_for_job_declaration_client_async(
+        channel_id: u32,
+        user_identity: String,
+        extranonce_prefix: Vec<u8>,
+        requested_max_target: Target,
+        nominal_hashrate: f32,
+        share_batch_size: usize,
+        expected_share_per_minute: f32,
+        job_store: J,
+        pool_tag_string: Option<String>,
+        miner_tag_string: String,
+    ) -> Result<Self, StandardChannelError>
+    where
+        J: JobStoreAsync<StandardJob<'a>>,
+    {
+        Self::new_async(
+            channel_id,
+            user_identity,
+            extranonce_prefix,
+            requested_max_target,
+            nominal_hashrate,
+            share_batch_size,
+            expected_share_per_minute,
+            job_store,
+            pool_tag_string,
+            Some(miner_tag_string),
+        )
+    }
+
+    // private constructor, for channels backed by a `JobStore`
     #[allow(clippy::too_many_arguments)]
     fn new(
         channel_id: u32,
@@ -186,6 +259,67 @@ where
         job_store: J,
         pool_tag_string: Option<String>,
         miner_tag_string: Option<String>,
+    ) -> Result<Self, StandardChannelError>
+    where
+        J: JobStore<StandardJob<'a>>,
+    {
+        Self::new_with_job_store(
+            channel_id,
+            user_identity,
+            extranonce_prefix,
+            requested_max_target,
+            nominal_hashrate,
+            share_batch_size,
+            expected_share_per_minute,
+            job_store,
+            pool_tag_string,
+            miner_tag_string,
+        )
+    }
+
+    // private constructor, for channels backed by a `JobStoreAsync`
+    #[allow(clippy::too_many_arguments)]
+    fn new_async(
+        channel_id: u32,
+        user_identity: String,
+        extranonce_prefix: Vec<u8>,
+        requested_max_target: Target,
+        nominal_hashrate: f32,
+        share_batch_size: usize,
+        expected_share_per_minute: f32,
+        job_store: J,
+        pool_tag_string: Option<String>,
+        miner_tag_string: Option<String>,
+    ) -> Result<Self, StandardChannelError>
+    where
+        J: JobStoreAsync<StandardJob<'a>>,
+    {
+        Self::new_with_job_store(
+            channel_id,
+            user_identity,
+            extranonce_prefix,
+            requested_max_target,
+            nominal_hashrate,
+            share_batch_size,
+            expected_share_per_minute,
+            job_store,
+            pool_tag_string,
+            miner_tag_string,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn new_with_job_store(
+        channel_id: u32,
+        user_identity: String,
+        extranonce_prefix: Vec<u8>,
+        requested_max_target: Target,
+        nominal_hashrate: f32,
+        share_batch_size: usize,
+        expected_share_per_minute: f32,
+        job_store: J,
+        pool_tag_string: Option<String>,
+        miner_tag_string: Option<String>,
     ) -> Result<Self, StandardChannelError> {
         let calculated_target =
             match hash_rate_to_target(nominal_hashrate.into(), expected_share_per_minute.into()) {
@@ -223,12 +357,12 @@ where
             extranonce_prefix,
             requested_max_target,
             target,
-            job_id_to_target: HashMap::new(),
             nominal_hashrate,
             share_accounting: ShareAccounting::new(share_batch_size),
             expected_share_per_minute,
             job_factory: JobFactory::new(true, pool_tag_string, miner_tag_string),
             chain_tip: None,
+            network: Network::Bitcoin,
             job_store,
             phantom: PhantomData,
         })
@@ -356,6 +490,295 @@ where
         Ok(())
     }
 
+    /// Returns the expected number of shares per minute for this channel.
+    pub fn get_shares_per_minute(&self) -> f32 {
+        self.expected_share_per_minute
+    }
+
+    /// Returns the current chain tip, if set.
+    pub fn get_chain_tip(&self) -> Option<&ChainTip> {
+        self.chain_tip.as_ref()
+    }
+
+    /// Returns the [`Network`] this channel is mining on.
+    pub fn get_network(&self) -> Network {
+        self.network
+    }
+
+    /// Sets the [`Network`] this channel is mining on.
+    ///
+    /// The network defines which target a share must meet to be considered a block (see
+    /// [`ChainTip::network_target`]). Defaults to [`Network::Bitcoin`].
+    pub fn set_network(&mut self, network: Network) {
+        self.network = network;
+    }
+
+    /// Sets the signer used to sign the jobs of this channel on a signet.
+    ///
+    /// Non-future jobs are signed when created. Future jobs are signed on activation, where the
+    /// activated job is replaced by a signed one with a new `job_id`, so
+    /// [`StandardChannel::get_active_job`] must be sent downstream after
+    /// [`StandardChannel::on_set_new_prev_hash`]. The activated job is kept as a past job, so shares
+    /// already in flight for it are still accepted, but they can't be reported as blocks.
+    ///
+    /// Since the signet solution commits to the block `version` and `ntime`, only shares that keep
+    /// the job `version` and `min_ntime` can be reported as blocks.
+    ///
+    /// Jobs received via [`StandardChannel::on_group_channel_job`] are not signed.
+    pub fn set_signet_signer(&mut self, signet_signer: SignetSigner) {
+        self.job_factory.set_signet_signer(signet_signer);
+    }
+
+    /// Sets the timestamp of the block at the current chain tip.
+    ///
+    /// `SetNewPrevHash` messages don't carry it, so it must be provided after every chain tip
+    /// update for the minimum difficulty rule of test networks to be applied to share validation.
+    pub fn set_chain_tip_parent_timestamp(
+        &mut self,
+        parent_timestamp: u32,
+    ) -> Result<(), StandardChannelError> {
+        match self.chain_tip.as_mut() {
+            Some(chain_tip) => {
+                chain_tip.set_parent_timestamp(parent_timestamp);
+                Ok(())
+            }
+            None => Err(StandardChannelError::ChainTipNotSet),
+        }
+    }
+
+    /// Only for testing purposes, not meant to be used in real apps.
+    #[cfg(test)]
+    fn set_chain_tip(&mut self, chain_tip: ChainTip) {
+        self.chain_tip = Some(chain_tip);
+    }
+
+    /// Returns a reference to the share accounting state for this channel.
+    pub fn get_share_accounting(&self) -> &ShareAccounting {
+        &self.share_accounting
+    }
+
+    // Makes sure the job factory doesn't hand out the job IDs of the job store, which may outlive
+    // this channel.
+    fn resume_job_ids(&mut self, last_job_id: Option<u32>) {
+        if let Some(last_job_id) = last_job_id {
+            self.job_factory.resume_job_ids_after(last_job_id);
+        }
+    }
+
+    // Creates a standard job from a template.
+    fn new_job_from_template(
+        &mut self,
+        template: NewTemplate<'a>,
+        coinbase_reward_outputs: Vec<TxOut>,
+    ) -> Result<StandardJob<'a>, StandardChannelError> {
+        let chain_tip = match template.future_template {
+            true => None,
+            // we can only create non-future jobs if we have a chain tip
+            false => Some(
+                self.chain_tip
+                    .clone()
+                    .ok_or(StandardChannelError::ChainTipNotSet)?,
+            ),
+        };
+
+        let new_job = self
+            .job_factory
+            .new_standard_job(
+                self.channel_id,
+                chain_tip,
+                self.extranonce_prefix.clone(),
+                template,
+                coinbase_reward_outputs,
+            )
+            .map_err(StandardChannelError::JobFactoryError)?;
+
+        Ok(new_job)
+    }
+
+    // Converts a group channel job into a standard job of this channel.
+    fn standard_job_from_group_channel_job(
+        &mut self,
+        extended_job: ExtendedJob<'a>,
+    ) -> Result<StandardJob<'a>, StandardChannelError> {
+        let standard_job = extended_job
+            .into_standard_job(self.channel_id, self.extranonce_prefix.clone())
+            .map_err(|_| StandardChannelError::FailedToConvertToStandardJob)?;
+
+        Ok(standard_job)
+    }
+
+    fn update_chain_tip(&mut self, set_new_prev_hash: SetNewPrevHash<'a>) {
+        // clear seen shares, as shares for past chain tip will be rejected as stale
+        self.share_accounting.flush_seen_shares();
+
+        // update the chain tip
+        self.chain_tip = Some(set_new_prev_hash.into());
+    }
+
+    // Recreates the active job with a signet solution for the current chain tip.
+    fn new_signet_signed_job(
+        &mut self,
+        active_job: StandardJob<'a>,
+    ) -> Result<StandardJob<'a>, StandardChannelError> {
+        let mut template = active_job.get_template().clone();
+        template.future_template = false;
+        let additional_coinbase_outputs_count =
+            active_job.get_coinbase_outputs().len() - template.coinbase_tx_outputs_count as usize;
+        let additional_coinbase_outputs =
+            active_job.get_coinbase_outputs()[..additional_coinbase_outputs_count].to_vec();
+
+        let signed_job = self
+            .job_factory
+            .new_standard_job(
+                self.channel_id,
+                self.chain_tip.clone(),
+                active_job.get_extranonce_prefix().clone(),
+                template,
+                additional_coinbase_outputs,
+            )
+            .map_err(StandardChannelError::JobFactoryError)?;
+
+        Ok(signed_job)
+    }
+
+    // Validates a share against the job it was submitted for, and the target the job store
+    // associates with it.
+    fn validate_share_for_job(
+        &mut self,
+        share: SubmitSharesStandard,
+        job: StandardJob<'a>,
+        job_target: Option<Target>,
+        detail: &mut ShareDetail,
+    ) -> Result<ShareValidationResult, ShareValidationError> {
+        let job_target = job_target.ok_or(ShareValidationError::JobStoreError(
+            JobStoreError::MissingJobTarget(share.job_id),
+        ))?;
+        detail.set_job_target(job_target);
+
+        let merkle_root: [u8; 32] = job
+            .get_merkle_root()
+            .inner_as_ref()
+            .try_into()
+            .expect("merkle root must be 32 bytes");
+
+        let chain_tip = self
+            .chain_tip
+            .as_ref()
+            .ok_or(ShareValidationError::NoChainTip)?;
+
+        let prev_hash = chain_tip.prev_hash();
+        // the header carries the nbits the miner was given in SetNewPrevHash, whatever the
+        // target of a block found on this network (see `ChainTip::network_target`)
+        let nbits = CompactTarget::from_consensus(chain_tip.nbits());
+
+        // create the header for validation
+        let header = Header {
+            version: Version::from_consensus(share.version as i32),
+            prev_blockhash: u256_to_block_hash(prev_hash.clone()),
+            merkle_root: (*Hash::from_bytes_ref(&merkle_root)).into(),
+            time: share.ntime,
+            bits: nbits,
+            nonce: share.nonce,
+        };
+
+        // convert the header hash to a target type for easy comparison
+        let share_hash = header.block_hash();
+        detail.set_share_hash(share_hash.to_raw_hash());
+        let share_raw_hash: [u8; 32] = *share_hash.to_raw_hash().as_ref();
+        let share_hash_target = Target::from_le_bytes(share_raw_hash);
+        let share_hash_as_diff = share_hash_target.difficulty_float();
+        let network_target = chain_tip.network_target(self.network, share.ntime);
+
+        // print hash_as_target and self.target as human readable hex
+        let share_hash_target_bytes = share_hash_target.to_be_bytes();
+        let job_target_bytes = job_target.to_be_bytes();
+
+        debug!(
+            "share validation \nshare:\t\t{}\njob target:\t{}\nnetwork target:\t{}",
+            bytes_to_hex(&share_hash_target_bytes),
+            bytes_to_hex(&job_target_bytes),
+            format!("{:x}", network_target)
+        );
+
+        // a signet solution commits to the job version and min_ntime, so shares rolling them, or
+        // shares for jobs activated before they could be signed, can't produce a valid block
+        let is_signet_solution_valid = self.job_factory.get_signet_signer().is_none()
+            || (has_solution(job.get_coinbase_outputs())
+                && share.version == job.get_job_message().version
+                && Some(share.ntime) == job.get_job_message().min_ntime.clone().into_inner());
+
+        // check if a block was found
+        if is_signet_solution_valid && network_target.is_met_by(share_hash) {
+            self.share_accounting.update_share_accounting(
+                job_target.difficulty_float(),
+                share.sequence_number,
+                share_hash.to_raw_hash(),
+            );
+
+            let op_pushbytes_pool_miner_tag = self
+                .job_factory
+                .op_pushbytes_pool_miner_tag()
+                .map_err(|_| ShareValidationError::InvalidCoinbase)?;
+
+            let mut script_sig = job.get_template().coinbase_prefix.to_vec();
+            script_sig.extend(op_pushbytes_pool_miner_tag);
+            script_sig.push(self.extranonce_prefix.len() as u8); // OP_PUSHBYTES_X (for the extranonce)
+            script_sig.extend(job.get_extranonce_prefix());
+
+            let tx_in = TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: script_sig.into(),
+                sequence: Sequence(job.get_template().coinbase_tx_input_sequence),
+                witness: Witness::from(vec![vec![0; 32]]),
+            };
+
+            let coinbase = Transaction {
+                version: TxVersion::non_standard(job.get_template().coinbase_tx_version as i32),
+                lock_time: LockTime::from_consensus(job.get_template().coinbase_tx_locktime),
+                input: vec![tx_in],
+                output: job.get_coinbase_outputs().to_vec(),
+            };
+            let mut serialized_coinbase = Vec::new();
+            coinbase
+                .consensus_encode(&mut serialized_coinbase)
+                .map_err(|_| ShareValidationError::InvalidCoinbase)?;
+
+            return Ok(ShareValidationResult::BlockFound(
+                share_hash.to_raw_hash(),
+                Some(job.get_template().template_id),
+                serialized_coinbase,
+            ));
+        }
+
+        // check if the share hash meets the job target
+        if share_hash_target <= job_target {
+            if self
+                .share_accounting
+                .is_share_seen(share_hash.to_raw_hash())
+            {
+                return Err(ShareValidationError::DuplicateShare);
+            }
+
+            self.share_accounting.update_share_accounting(
+                job_target.difficulty_float(),
+                share.sequence_number,
+                share_hash.to_raw_hash(),
+            );
+
+            // update the best diff
+            self.share_accounting.update_best_diff(share_hash_as_diff);
+
+            Ok(ShareValidationResult::Valid(share_hash.to_raw_hash()))
+        } else {
+            Err(ShareValidationError::DoesNotMeetTarget)
+        }
+    }
+}
+
+impl<'a, J> StandardChannel<'a, J>
+where
+    J: JobStore<StandardJob<'a>>,
+{
     /// Returns the currently active job, if any.
     pub fn get_active_job(&self) -> Option<StandardJob<'a>> {
         // cloning happens inside the job store
@@ -379,33 +802,18 @@ where
         self.job_store.get_past_job(job_id)
     }
 
+    /// Returns the number of jobs held by the job store in each state, and an estimate of their
+    /// memory footprint.
+    pub fn get_job_store_size(&self) -> JobStoreSize {
+        self.job_store.get_size()
+    }
+
     /// Returns an owned copy of a stale job from its job ID, if any.
     pub fn get_stale_job(&self, job_id: u32) -> Option<StandardJob<'a>> {
         // cloning happens inside the job store
         self.job_store.get_stale_job(job_id)
     }
 
-    /// Returns the expected number of shares per minute for this channel.
-    pub fn get_shares_per_minute(&self) -> f32 {
-        self.expected_share_per_minute
-    }
-
-    /// Returns the current chain tip, if set.
-    pub fn get_chain_tip(&self) -> Option<&ChainTip> {
-        self.chain_tip.as_ref()
-    }
-
-    /// Only for testing purposes, not meant to be used in real apps.
-    #[cfg(test)]
-    fn set_chain_tip(&mut self, chain_tip: ChainTip) {
-        self.chain_tip = Some(chain_tip);
-    }
-
-    /// Returns a reference to the share accounting state for this channel.
-    pub fn get_share_accounting(&self) -> &ShareAccounting {
-        &self.share_accounting
-    }
-
     /// Updates the channel state with a new job.
     ///
     /// If the template is a future template, the chain tip is not used.
@@ -422,46 +830,10 @@ where
         template: NewTemplate<'a>,
         coinbase_reward_outputs: Vec<TxOut>,
     ) -> Result<(), StandardChannelError> {
-        match template.future_template {
-            true => {
-                let new_job = self
-                    .job_factory
-                    .new_standard_job(
-                        self.channel_id,
-                        None,
-                        self.extranonce_prefix.clone(),
-                        template.clone(),
-                        coinbase_reward_outputs,
-                    )
-                    .map_err(StandardChannelError::JobFactoryError)?;
-                self.job_store.add_future_job(template.template_id, new_job);
-            }
-            false => {
-                match self.chain_tip.clone() {
-                    // we can only create non-future jobs if we have a chain tip
-                    None => return Err(StandardChannelError::ChainTipNotSet),
-                    Some(chain_tip) => {
-                        let new_job = self
-                            .job_factory
-                            .new_standard_job(
-                                self.channel_id,
-                                Some(chain_tip),
-                                self.extranonce_prefix.clone(),
-                                template.clone(),
-                                coinbase_reward_outputs,
-                            )
-                            .map_err(StandardChannelError::JobFactoryError)?;
-
-                        // associate the new active job with the current target
-                        self.job_id_to_target
-                            .insert(new_job.get_job_id(), self.target);
-
-                        // add the new active job to the job store
-                        self.job_store.add_active_job(new_job);
-                    }
-                }
-            }
-        }
+        let template_id = template.template_id;
+        self.resume_job_ids(self.job_store.get_last_job_id());
+        let new_job = self.new_job_from_template(template, coinbase_reward_outputs)?;
+        self.add_job(template_id, new_job);
 
         Ok(())
     }
@@ -475,230 +847,407 @@ where
         &mut self,
         extended_job: ExtendedJob<'a>,
     ) -> Result<(), StandardChannelError> {
-        let standard_job = extended_job
-            .into_standard_job(self.channel_id, self.extranonce_prefix.clone())
-            .map_err(|_| StandardChannelError::FailedToConvertToStandardJob)?;
+        let standard_job = self.standard_job_from_group_channel_job(extended_job)?;
+        let template_id = standard_job.get_template().template_id;
+        self.add_job(template_id, standard_job);
+
+        Ok(())
+    }
+
+    /// Updates the channel state with a new `SetNewPrevHash` message.
+    ///
+    /// If there are no future jobs, returns an error.
+    /// If there are future jobs, the active job is set to the job with the given `template_id`.
+    ///
+    /// All past jobs are cleared.
+    pub fn on_set_new_prev_hash(
+        &mut self,
+        set_new_prev_hash: SetNewPrevHash<'a>,
+    ) -> Result<(), StandardChannelError> {
+        let template_id = set_new_prev_hash.template_id;
+
+        if !self.job_store.has_future_jobs() {
+            return Err(StandardChannelError::TemplateIdNotFound);
+        }
+
+        let job_id = self
+            .job_store
+            .get_future_job_id_from_template_id(template_id)
+            .ok_or(StandardChannelError::TemplateIdNotFound)?;
+
+        // associate the job with the current target before it becomes the active job
+        self.job_store.set_job_target(job_id, self.target);
+
+        // try to activate the future job, and also mark past jobs as stale
+        if !self
+            .job_store
+            .activate_future_job(template_id, set_new_prev_hash.header_timestamp)
+        {
+            return Err(StandardChannelError::TemplateIdNotFound);
+        }
+        let activated_job = self.job_store.get_active_job();
+
+        self.update_chain_tip(set_new_prev_hash);
+
+        // the activated job was created before the chain tip was known, so it can't carry a
+        // signet solution yet
+        if let Some(activated_job) = activated_job {
+            if self.job_factory.get_signet_signer().is_some() {
+                // the unsigned job becomes a past job, so shares already in flight for it are
+                // still accepted
+                self.resume_job_ids(self.job_store.get_last_job_id());
+                let signed_job = self.new_signet_signed_job(activated_job)?;
+                self.add_job(template_id, signed_job);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates a submitted share and updates accounting state.
+    ///
+    /// Returns the result of share validation, including block found, valid share, duplicate, or
+    /// error if the share is stale or does not meet target.
+    pub fn validate_share(
+        &mut self,
+        share: SubmitSharesStandard,
+    ) -> Result<ShareValidationResult, ShareValidationError> {
+        self.validate_share_with_detail(share, &mut ShareDetail::default())
+    }
+
+    /// Validates a share like [`StandardChannel::validate_share`], returning the Share Reject Detail of
+    /// the share together with the error if it is rejected, to be appended to
+    /// `SubmitShares.Error` when the Share Reject Detail extension is negotiated.
+    pub fn validate_share_with_reject_detail(
+        &mut self,
+        share: SubmitSharesStandard,
+    ) -> Result<ShareValidationResult, (ShareValidationError, ShareRejectDetail)> {
+        let mut detail = ShareDetail::default();
+        self.validate_share_with_detail(share, &mut detail)
+            .map_err(|error| {
+                let reject_detail = detail.to_share_reject_detail(&error, self.target);
+                (error, reject_detail)
+            })
+    }
 
-        match standard_job.is_future() {
+    // Validates a share, recording in `detail` what the validation found out about it.
+    fn validate_share_with_detail(
+        &mut self,
+        share: SubmitSharesStandard,
+        detail: &mut ShareDetail,
+    ) -> Result<ShareValidationResult, ShareValidationError> {
+        let job_id = share.job_id;
+
+        if self.job_store.get_stale_job(job_id).is_some() {
+            detail.set_job_state(JobState::Stale);
+            return Err(ShareValidationError::Stale);
+        }
+
+        if self.job_store.is_evicted_job(job_id) {
+            detail.set_job_state(JobState::Past);
+            return Err(ShareValidationError::EvictedJob);
+        }
+
+        let job = match self.job_store.get_active_job() {
+            Some(job) if job.get_job_id() == job_id => {
+                detail.set_job_state(JobState::Active);
+                job
+            }
+            _ => {
+                let job = self
+                    .job_store
+                    .get_past_job(job_id)
+                    .ok_or(ShareValidationError::InvalidJobId)?;
+                detail.set_job_state(JobState::Past);
+                job
+            }
+        };
+        let job_target = self.job_store.get_job_target(job_id);
+
+        self.validate_share_for_job(share, job, job_target, detail)
+    }
+
+    // Adds a job to the job store, associating non-future jobs with the current target.
+    fn add_job(&mut self, template_id: u64, job: StandardJob<'a>) {
+        match job.is_future() {
             true => {
-                self.job_store
-                    .add_future_job(standard_job.get_template().template_id, standard_job);
+                self.job_store.add_future_job(template_id, job);
             }
             false => {
-                // associate the new active job with the current target
-                self.job_id_to_target
-                    .insert(standard_job.get_job_id(), self.target);
-
-                // add the new active job to the job store
-                self.job_store.add_active_job(standard_job);
+                self.job_store.set_job_target(job.get_job_id(), self.target);
+                self.job_store.add_active_job(job);
             }
         }
+    }
+}
+
+/// Variants of the methods that use the job store, for channels backed by a [`JobStoreAsync`].
+///
+/// Failures of the job store are reported as [`StandardChannelError::JobStoreError`] and
+/// [`ShareValidationError::JobStoreError`].
+impl<'a, J> StandardChannel<'a, J>
+where
+    J: JobStoreAsync<StandardJob<'a>>,
+{
+    /// Async variant of [`StandardChannel::get_active_job`].
+    pub async fn get_active_job_async(
+        &self,
+    ) -> Result<Option<StandardJob<'a>>, StandardChannelError> {
+        self.job_store
+            .get_active_job()
+            .await
+            .map_err(StandardChannelError::JobStoreError)
+    }
+
+    /// Async variant of [`StandardChannel::get_future_job_id_from_template_id`].
+    pub async fn get_future_job_id_from_template_id_async(
+        &self,
+        template_id: u64,
+    ) -> Result<Option<u32>, StandardChannelError> {
+        self.job_store
+            .get_future_job_id_from_template_id(template_id)
+            .await
+            .map_err(StandardChannelError::JobStoreError)
+    }
+
+    /// Async variant of [`StandardChannel::get_future_job`].
+    pub async fn get_future_job_async(
+        &self,
+        job_id: u32,
+    ) -> Result<Option<StandardJob<'a>>, StandardChannelError> {
+        self.job_store
+            .get_future_job(job_id)
+            .await
+            .map_err(StandardChannelError::JobStoreError)
+    }
+
+    /// Async variant of [`StandardChannel::get_past_job`].
+    pub async fn get_past_job_async(
+        &self,
+        job_id: u32,
+    ) -> Result<Option<StandardJob<'a>>, StandardChannelError> {
+        self.job_store
+            .get_past_job(job_id)
+            .await
+            .map_err(StandardChannelError::JobStoreError)
+    }
+
+    /// Async variant of [`StandardChannel::get_stale_job`].
+    pub async fn get_stale_job_async(
+        &self,
+        job_id: u32,
+    ) -> Result<Option<StandardJob<'a>>, StandardChannelError> {
+        self.job_store
+            .get_stale_job(job_id)
+            .await
+            .map_err(StandardChannelError::JobStoreError)
+    }
+
+    /// Async variant of [`StandardChannel::get_job_store_size`].
+    pub async fn get_job_store_size_async(&self) -> Result<JobStoreSize, StandardChannelError> {
+        self.job_store
+            .get_size()
+            .await
+            .map_err(StandardChannelError::JobStoreError)
+    }
+
+    /// Async variant of [`StandardChannel::on_new_template`].
+    pub async fn on_new_template_async(
+        &mut self,
+        template: NewTemplate<'a>,
+        coinbase_reward_outputs: Vec<TxOut>,
+    ) -> Result<(), StandardChannelError> {
+        let template_id = template.template_id;
+        self.resume_job_ids_async().await?;
+        let new_job = self.new_job_from_template(template, coinbase_reward_outputs)?;
+        self.add_job_async(template_id, new_job).await
+    }
 
-        Ok(())
+    /// Async variant of [`StandardChannel::on_group_channel_job`].
+    pub async fn on_group_channel_job_async(
+        &mut self,
+        extended_job: ExtendedJob<'a>,
+    ) -> Result<(), StandardChannelError> {
+        let standard_job = self.standard_job_from_group_channel_job(extended_job)?;
+        let template_id = standard_job.get_template().template_id;
+        self.add_job_async(template_id, standard_job).await
     }
 
-    /// Updates the channel state with a new `SetNewPrevHash` message.
-    ///
-    /// If there are no future jobs, returns an error.
-    /// If there are future jobs, the active job is set to the job with the given `template_id`.
-    ///
-    /// All past jobs are cleared.
-    pub fn on_set_new_prev_hash(
+    /// Async variant of [`StandardChannel::on_set_new_prev_hash`].
+    pub async fn on_set_new_prev_hash_async(
         &mut self,
         set_new_prev_hash: SetNewPrevHash<'a>,
     ) -> Result<(), StandardChannelError> {
-        // clear the job id to target mapping
-        self.job_id_to_target.clear();
+        let template_id = set_new_prev_hash.template_id;
 
-        match self.job_store.has_future_jobs() {
-            false => {
-                return Err(StandardChannelError::TemplateIdNotFound);
-            }
-            // try to activate the future job, and also mark past jobs as stale
-            true => {
-                if !self.job_store.activate_future_job(
-                    set_new_prev_hash.template_id,
-                    set_new_prev_hash.header_timestamp,
-                ) {
-                    return Err(StandardChannelError::TemplateIdNotFound);
-                }
+        if !self
+            .job_store
+            .has_future_jobs()
+            .await
+            .map_err(StandardChannelError::JobStoreError)?
+        {
+            return Err(StandardChannelError::TemplateIdNotFound);
+        }
 
-                // associate the new active job with the current target
-                let job_id = self
-                    .job_store
-                    .get_active_job()
-                    .expect("active job must exist")
-                    .get_job_id();
-                self.job_id_to_target.insert(job_id, self.target);
+        let job_id = self
+            .job_store
+            .get_future_job_id_from_template_id(template_id)
+            .await
+            .map_err(StandardChannelError::JobStoreError)?
+            .ok_or(StandardChannelError::TemplateIdNotFound)?;
+
+        // associate the job with the current target before it becomes the active job
+        self.job_store
+            .set_job_target(job_id, self.target)
+            .await
+            .map_err(StandardChannelError::JobStoreError)?;
+
+        // try to activate the future job, and also mark past jobs as stale
+        if !self
+            .job_store
+            .activate_future_job(template_id, set_new_prev_hash.header_timestamp)
+            .await
+            .map_err(StandardChannelError::JobStoreError)?
+        {
+            return Err(StandardChannelError::TemplateIdNotFound);
+        }
+        let activated_job = self
+            .job_store
+            .get_active_job()
+            .await
+            .map_err(StandardChannelError::JobStoreError)?;
+
+        self.update_chain_tip(set_new_prev_hash);
+
+        // the activated job was created before the chain tip was known, so it can't carry a
+        // signet solution yet
+        if let Some(activated_job) = activated_job {
+            if self.job_factory.get_signet_signer().is_some() {
+                self.resume_job_ids_async().await?;
+                let signed_job = self.new_signet_signed_job(activated_job)?;
+                self.add_job_async(template_id, signed_job).await?;
             }
         }
 
-        // clear seen shares, as shares for past chain tip will be rejected as stale
-        self.share_accounting.flush_seen_shares();
+        Ok(())
+    }
 
-        // update the chain tip
-        self.chain_tip = Some(set_new_prev_hash.into());
+    /// Async variant of [`StandardChannel::validate_share`].
+    pub async fn validate_share_async(
+        &mut self,
+        share: SubmitSharesStandard,
+    ) -> Result<ShareValidationResult, ShareValidationError> {
+        self.validate_share_with_detail_async(share, &mut ShareDetail::default())
+            .await
+    }
 
-        Ok(())
+    /// Async variant of [`StandardChannel::validate_share_with_reject_detail`].
+    pub async fn validate_share_with_reject_detail_async(
+        &mut self,
+        share: SubmitSharesStandard,
+    ) -> Result<ShareValidationResult, (ShareValidationError, ShareRejectDetail)> {
+        let mut detail = ShareDetail::default();
+        self.validate_share_with_detail_async(share, &mut detail)
+            .await
+            .map_err(|error| {
+                let reject_detail = detail.to_share_reject_detail(&error, self.target);
+                (error, reject_detail)
+            })
     }
 
-    /// Validates a submitted share and updates accounting state.
-    ///
-    /// Returns the result of share validation, including block found, valid share, duplicate, or
-    /// error if the share is stale or does not meet target.
-    pub fn validate_share(
+    async fn validate_share_with_detail_async(
         &mut self,
         share: SubmitSharesStandard,
+        detail: &mut ShareDetail,
     ) -> Result<ShareValidationResult, ShareValidationError> {
         let job_id = share.job_id;
 
-        // check if job_id is active job
-        let is_active_job = self
+        if self
             .job_store
-            .get_active_job()
-            .is_some_and(|job| job.get_job_id() == job_id);
-
-        // check if job_id is past job
-        let is_past_job = self.job_store.get_past_job(job_id).is_some();
-
-        // check if job_id is stale job
-        let is_stale_job = self.job_store.get_stale_job(job_id).is_some();
-
-        if is_stale_job {
+            .get_stale_job(job_id)
+            .await
+            .map_err(ShareValidationError::JobStoreError)?
+            .is_some()
+        {
+            detail.set_job_state(JobState::Stale);
             return Err(ShareValidationError::Stale);
         }
 
-        // if job_id is not active, past or stale, return error
-        if !is_active_job && !is_past_job && !is_stale_job {
-            return Err(ShareValidationError::InvalidJobId);
+        if self
+            .job_store
+            .is_evicted_job(job_id)
+            .await
+            .map_err(ShareValidationError::JobStoreError)?
+        {
+            detail.set_job_state(JobState::Past);
+            return Err(ShareValidationError::EvictedJob);
         }
 
-        let job = if is_active_job {
-            self.job_store
-                .get_active_job()
-                .expect("active job must exist")
-        } else if is_past_job {
-            self.job_store
-                .get_past_job(job_id)
-                .expect("past job must exist")
-        } else {
-            self.job_store
-                .get_stale_job(job_id)
-                .expect("stale job must exist")
+        let job = match self
+            .job_store
+            .get_active_job()
+            .await
+            .map_err(ShareValidationError::JobStoreError)?
+        {
+            Some(job) if job.get_job_id() == job_id => {
+                detail.set_job_state(JobState::Active);
+                job
+            }
+            _ => {
+                let job = self
+                    .job_store
+                    .get_past_job(job_id)
+                    .await
+                    .map_err(ShareValidationError::JobStoreError)?
+                    .ok_or(ShareValidationError::InvalidJobId)?;
+                detail.set_job_state(JobState::Past);
+                job
+            }
         };
-
         let job_target = self
-            .job_id_to_target
-            .get(&job_id)
-            .expect("job target must exist");
-
-        let merkle_root: [u8; 32] = job
-            .get_merkle_root()
-            .inner_as_ref()
-            .try_into()
-            .expect("merkle root must be 32 bytes");
-
-        let chain_tip = self
-            .chain_tip
-            .as_ref()
-            .ok_or(ShareValidationError::NoChainTip)?;
-
-        let prev_hash = chain_tip.prev_hash();
-        let nbits = CompactTarget::from_consensus(chain_tip.nbits());
-
-        // create the header for validation
-        let header = Header {
-            version: Version::from_consensus(share.version as i32),
-            prev_blockhash: u256_to_block_hash(prev_hash.clone()),
-            merkle_root: (*Hash::from_bytes_ref(&merkle_root)).into(),
-            time: share.ntime,
-            bits: nbits,
-            nonce: share.nonce,
-        };
-
-        // convert the header hash to a target type for easy comparison
-        let share_hash = header.block_hash();
-        let share_raw_hash: [u8; 32] = *share_hash.to_raw_hash().as_ref();
-        let share_hash_target = Target::from_le_bytes(share_raw_hash);
-        let share_hash_as_diff = share_hash_target.difficulty_float();
-        let network_target = Target::from_compact(nbits);
-
-        // print hash_as_target and self.target as human readable hex
-        let share_hash_target_bytes = share_hash_target.to_be_bytes();
-        let job_target_bytes = job_target.to_be_bytes();
-
-        debug!(
-            "share validation \nshare:\t\t{}\njob target:\t{}\nnetwork target:\t{}",
-            bytes_to_hex(&share_hash_target_bytes),
-            bytes_to_hex(&job_target_bytes),
-            format!("{:x}", network_target)
-        );
-
-        // check if a block was found
-        if network_target.is_met_by(share_hash) {
-            self.share_accounting.update_share_accounting(
-                job_target.difficulty_float(),
-                share.sequence_number,
-                share_hash.to_raw_hash(),
-            );
-
-            let op_pushbytes_pool_miner_tag = self
-                .job_factory
-                .op_pushbytes_pool_miner_tag()
-                .map_err(|_| ShareValidationError::InvalidCoinbase)?;
-
-            let mut script_sig = job.get_template().coinbase_prefix.to_vec();
-            script_sig.extend(op_pushbytes_pool_miner_tag);
-            script_sig.push(self.extranonce_prefix.len() as u8); // OP_PUSHBYTES_X (for the extranonce)
-            script_sig.extend(job.get_extranonce_prefix());
-
-            let tx_in = TxIn {
-                previous_output: OutPoint::null(),
-                script_sig: script_sig.into(),
-                sequence: Sequence(job.get_template().coinbase_tx_input_sequence),
-                witness: Witness::from(vec![vec![0; 32]]),
-            };
-
-            let coinbase = Transaction {
-                version: TxVersion::non_standard(job.get_template().coinbase_tx_version as i32),
-                lock_time: LockTime::from_consensus(job.get_template().coinbase_tx_locktime),
-                input: vec![tx_in],
-                output: job.get_coinbase_outputs().to_vec(),
-            };
-            let mut serialized_coinbase = Vec::new();
-            coinbase
-                .consensus_encode(&mut serialized_coinbase)
-                .map_err(|_| ShareValidationError::InvalidCoinbase)?;
+            .job_store
+            .get_job_target(job_id)
+            .await
+            .map_err(ShareValidationError::JobStoreError)?;
 
-            return Ok(ShareValidationResult::BlockFound(
-                share_hash.to_raw_hash(),
-                Some(job.get_template().template_id),
-                serialized_coinbase,
-            ));
-        }
+        self.validate_share_for_job(share, job, job_target, detail)
+    }
 
-        // check if the share hash meets the job target
-        if share_hash_target <= *job_target {
-            if self
-                .share_accounting
-                .is_share_seen(share_hash.to_raw_hash())
-            {
-                return Err(ShareValidationError::DuplicateShare);
+    async fn add_job_async(
+        &mut self,
+        template_id: u64,
+        job: StandardJob<'a>,
+    ) -> Result<(), StandardChannelError> {
+        match job.is_future() {
+            true => {
+                self.job_store
+                    .add_future_job(template_id, job)
+                    .await
+                    .map_err(StandardChannelError::JobStoreError)?;
+            }
+            false => {
+                // associate the new active job with the current target
+                self.job_store
+                    .set_job_target(job.get_job_id(), self.target)
+                    .await
+                    .map_err(StandardChannelError::JobStoreError)?;
+                self.job_store
+                    .add_active_job(job)
+                    .await
+                    .map_err(StandardChannelError::JobStoreError)?;
             }
-
-            self.share_accounting.update_share_accounting(
-                job_target.difficulty_float(),
-                share.sequence_number,
-                share_hash.to_raw_hash(),
-            );
-
-            // update the best diff
-            self.share_accounting.update_best_diff(share_hash_as_diff);
-
-            Ok(ShareValidationResult::Valid(share_hash.to_raw_hash()))
-        } else {
-            Err(ShareValidationError::DoesNotMeetTarget)
         }
+        Ok(())
+    }
+
+    async fn resume_job_ids_async(&mut self) -> Result<(), StandardChannelError> {
+        let last_job_id = self
+            .job_store
+            .get_last_job_id()
+            .await
+            .map_err(StandardChannelError::JobStoreError)?;
+        self.resume_job_ids(last_job_id);
+        Ok(())
     }
 }
 
@@ -715,9 +1264,14 @@ mod tests {
             share_accounting::{ShareValidationError, ShareValidationResult},
             standard::StandardChannel,
         },
+        signet::{SignetSigner, SIGNET_HEADER},
     };
     use binary_sv2::Sv2Option;
-    use bitcoin::{transaction::TxOut, Amount, ScriptBuf, Target};
+    use bitcoin::{
+        secp256k1::{Secp256k1, SecretKey},
+        transaction::TxOut,
+        Amount, CompressedPublicKey, ScriptBuf, Target,
+    };
     use mining_sv2::{NewMiningJob, SubmitSharesStandard};
     use std::convert::TryInto;
     use template_distribution_sv2::{NewTemplate, SetNewPrevHash as SetNewPrevHashTdp};
@@ -1421,4 +1975,128 @@ mod tests {
             .set_extranonce_prefix(new_extranonce_prefix_too_long)
             .is_err());
     }
+
+    #[test]
+    fn test_signet_future_job_activation_flow() {
+        let standard_channel_id = 1;
+        let extranonce_prefix = [
+            83, 116, 114, 97, 116, 117, 109, 32, 86, 50, 32, 83, 82, 73, 32, 80, 111, 111, 108, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+        ]
+        .to_vec();
+
+        let mut standard_channel = StandardChannel::new(
+            standard_channel_id,
+            "user_identity".to_string(),
+            extranonce_prefix,
+            Target::from_le_bytes([0xff; 32]),
+            10.0,
+            100,
+            1.0,
+            DefaultJobStore::<StandardJob>::new(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let secret_key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let public_key = secret_key.public_key(&Secp256k1::signing_only());
+        let challenge = ScriptBuf::new_p2wpkh(&CompressedPublicKey(public_key).wpubkey_hash());
+        standard_channel.set_signet_signer(SignetSigner::new(challenge, secret_key).unwrap());
+        // every share meets the channel target
+        standard_channel.set_target(Target::from_le_bytes([0xff; 32]));
+
+        let template = NewTemplate {
+            template_id: 1,
+            future_template: true,
+            version: 536870912,
+            coinbase_tx_version: 2,
+            coinbase_prefix: vec![2, 159, 0, 0].try_into().unwrap(),
+            coinbase_tx_input_sequence: 4294967294,
+            coinbase_tx_value_remaining: SATS_AVAILABLE_IN_TEMPLATE,
+            coinbase_tx_outputs_count: 1,
+            coinbase_tx_outputs: vec![
+                0, 0, 0, 0, 0, 0, 0, 0, 38, 106, 36, 170, 33, 169, 237, 226, 246, 28, 63, 113, 209,
+                222, 253, 63, 169, 153, 223, 163, 105, 83, 117, 92, 105, 6, 137, 121, 153, 98, 180,
+                139, 235, 216, 54, 151, 78, 140, 249,
+            ]
+            .try_into()
+            .unwrap(),
+            coinbase_tx_locktime: 158,
+            merkle_path: vec![].try_into().unwrap(),
+        };
+        let coinbase_reward_outputs = vec![TxOut {
+            value: Amount::from_sat(SATS_AVAILABLE_IN_TEMPLATE),
+            script_pubkey: ScriptBuf::new_p2wpkh(&CompressedPublicKey(public_key).wpubkey_hash()),
+        }];
+
+        standard_channel
+            .on_new_template(template.clone(), coinbase_reward_outputs.clone())
+            .unwrap();
+
+        // future jobs can't be signed, as the chain tip is unknown
+        let future_job = standard_channel.get_future_job(1).unwrap();
+        assert_eq!(future_job.get_coinbase_outputs(), &{
+            let mut outputs = coinbase_reward_outputs.clone();
+            outputs.extend(
+                crate::outputs::deserialize_template_outputs(
+                    template.coinbase_tx_outputs.to_vec(),
+                    1,
+                )
+                .unwrap(),
+            );
+            outputs
+        });
+
+        let ntime = 1747092633;
+        let set_new_prev_hash = SetNewPrevHashTdp {
+            template_id: template.template_id,
+            prev_hash: [
+                200, 53, 253, 129, 214, 31, 43, 84, 179, 58, 58, 76, 128, 213, 24, 53, 38, 144,
+                205, 88, 172, 20, 251, 22, 217, 141, 21, 221, 21, 0, 0, 0,
+            ]
+            .into(),
+            header_timestamp: ntime,
+            n_bits: 503543726,
+            target: [
+                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                174, 119, 3, 0, 0,
+            ]
+            .into(),
+        };
+        standard_channel
+            .on_set_new_prev_hash(set_new_prev_hash)
+            .unwrap();
+
+        // the activated job is replaced by a signed one, and kept as a past job
+        assert!(standard_channel.get_stale_job(1).is_none());
+        assert!(standard_channel.get_past_job(1).is_some());
+        let active_job = standard_channel.get_active_job().unwrap();
+        assert_eq!(active_job.get_job_id(), 2);
+        assert_eq!(
+            active_job.get_job_message().min_ntime,
+            Sv2Option::new(Some(ntime))
+        );
+        assert_ne!(active_job.get_merkle_root(), future_job.get_merkle_root());
+
+        let commitment = active_job.get_coinbase_outputs()[1].script_pubkey.clone();
+        let solution_push = commitment.instructions().last().unwrap().unwrap();
+        let solution = solution_push.push_bytes().unwrap().as_bytes();
+        assert_eq!(solution[..4], SIGNET_HEADER);
+        assert!(solution.len() > 4);
+
+        // shares already in flight for the activated job are still accepted
+        let share = SubmitSharesStandard {
+            channel_id: standard_channel_id,
+            sequence_number: 0,
+            job_id: 1,
+            nonce: 0,
+            ntime,
+            version: 536870912,
+        };
+        assert!(matches!(
+            standard_channel.validate_share(share),
+            Ok(ShareValidationResult::Valid(_))
+        ));
+    }
 }
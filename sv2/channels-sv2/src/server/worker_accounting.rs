@@ -0,0 +1,406 @@
+//! Worker Accounting - Mining Server Abstraction.
+//!
+//! This module provides per-worker share statistics for extended channels, based on the
+//! `UserIdentity` TLV of the Worker-Specific Hashrate Tracking extension (`0x0002`), which clients
+//! append to `SubmitSharesExtended` to tell which of the workers aggregated in the channel found
+//! the share.
+//!
+//! ## Responsibilities
+//!
+//! - **Worker Stats**: Accepted and rejected share counts, work sum, best difficulty and hashrate
+//!   estimate of each worker, averaged over the last `hashrate_window`.
+//! - **Bounded Cardinality**: At most `max_workers` workers are tracked per channel. Shares of
+//!   workers beyond that limit, and shares without a `UserIdentity`, are accounted as
+//!   unattributed.
+//! - **Idle Worker Expiry**: Workers that submitted no share for `idle_timeout` are dropped, making
+//!   room for new workers. Idle workers are looked for while recording shares, at most every
+//!   quarter of `idle_timeout`, and on demand with [`WorkerAccounting::expire_idle_workers`], e.g.
+//!   from a timer of the caller.
+//!
+//! ## Usage
+//!
+//! [`ExtendedChannel::validate_share_from_worker`](super::extended::ExtendedChannel::validate_share_from_worker)
+//! validates a share and records its result for the worker, and
+//! [`ExtendedChannel::validate_share_from_worker_with_reject_detail`](super::extended::ExtendedChannel::validate_share_from_worker_with_reject_detail)
+//! also returns the Share Reject Detail of rejected shares. [`WorkerAccounting`] can also be used
+//! on its own, with the result of [`ExtendedChannel::validate_share`](super::extended::ExtendedChannel::validate_share).
+
+use super::share_accounting::{ShareValidationError, ShareValidationResult};
+use bitcoin::{hashes::Hash as _, Target};
+use extensions_sv2::UserIdentity;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// Default maximum number of workers tracked per channel.
+pub const DEFAULT_MAX_WORKERS: usize = 1024;
+
+/// Default duration without shares after which a worker is dropped.
+pub const DEFAULT_WORKER_IDLE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Default time constant of the hashrate estimate of a worker.
+pub const DEFAULT_HASHRATE_WINDOW: Duration = Duration::from_secs(300);
+
+/// Share statistics of a worker, or of the unattributed shares of a channel.
+#[derive(Clone, Debug)]
+pub struct WorkerStats {
+    shares_accepted: u32,
+    shares_rejected: u32,
+    share_work_sum: f64,
+    best_diff: f64,
+    first_share_time: Instant,
+    last_share_time: Instant,
+    // Work of the accepted shares, decayed exponentially with `hashrate_window` as time constant
+    // since `last_share_time`.
+    decayed_work: f64,
+    hashrate_window: Duration,
+}
+
+impl WorkerStats {
+    fn new(now: Instant, hashrate_window: Duration) -> Self {
+        Self {
+            shares_accepted: 0,
+            shares_rejected: 0,
+            share_work_sum: 0.0,
+            best_diff: 0.0,
+            first_share_time: now,
+            last_share_time: now,
+            decayed_work: 0.0,
+            hashrate_window,
+        }
+    }
+
+    // Returns the factor by which work done at `since` is decayed at `now`.
+    fn decay(&self, since: Instant, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(since).as_secs_f64();
+        (-elapsed / self.hashrate_window.as_secs_f64()).exp()
+    }
+
+    fn record_share(
+        &mut self,
+        result: &Result<ShareValidationResult, ShareValidationError>,
+        share_work: f64,
+        now: Instant,
+    ) {
+        self.decayed_work *= self.decay(self.last_share_time, now);
+        self.last_share_time = now;
+        let share_hash = match result {
+            Ok(ShareValidationResult::Valid(share_hash))
+            | Ok(ShareValidationResult::BlockFound(share_hash, _, _)) => share_hash,
+            Err(_) => {
+                self.shares_rejected += 1;
+                return;
+            }
+        };
+        self.shares_accepted += 1;
+        self.share_work_sum += share_work;
+        self.decayed_work += share_work;
+        let share_diff = Target::from_le_bytes(share_hash.to_byte_array()).difficulty_float();
+        if share_diff > self.best_diff {
+            self.best_diff = share_diff;
+        }
+    }
+
+    /// Returns the number of accepted shares.
+    pub fn get_shares_accepted(&self) -> u32 {
+        self.shares_accepted
+    }
+
+    /// Returns the number of rejected shares.
+    pub fn get_shares_rejected(&self) -> u32 {
+        self.shares_rejected
+    }
+
+    /// Returns the sum of work contributed by accepted shares.
+    pub fn get_share_work_sum(&self) -> f64 {
+        self.share_work_sum
+    }
+
+    /// Returns the highest difficulty found among accepted shares.
+    pub fn get_best_diff(&self) -> f64 {
+        self.best_diff
+    }
+
+    /// Returns the time of the first share.
+    pub fn get_first_share_time(&self) -> Instant {
+        self.first_share_time
+    }
+
+    /// Returns the time of the last share.
+    pub fn get_last_share_time(&self) -> Instant {
+        self.last_share_time
+    }
+
+    /// Estimates the hashrate (H/s) from the work of the accepted shares.
+    ///
+    /// A share of difficulty 1 takes `2^32` hashes on average. The work is averaged with weights
+    /// decaying exponentially with the age of the shares, `hashrate_window` being the time
+    /// constant, so the estimate follows changes of the hashrate and decays to 0 when the worker
+    /// stops submitting shares. Until `hashrate_window` passed since the first share, the average
+    /// is corrected for the shorter history. Returns 0 until some time passed since the first
+    /// share.
+    pub fn get_hashrate_estimate(&self, now: Instant) -> f64 {
+        let history = 1.0 - self.decay(self.first_share_time, now);
+        if history <= 0.0 {
+            return 0.0;
+        }
+        let decayed_work = self.decayed_work * self.decay(self.last_share_time, now);
+        decayed_work * 2f64.powi(32) / (self.hashrate_window.as_secs_f64() * history)
+    }
+}
+
+/// Per-worker share statistics of a channel.
+///
+/// Workers are identified by the raw bytes of their `UserIdentity`.
+#[derive(Clone, Debug)]
+pub struct WorkerAccounting {
+    workers: HashMap<Vec<u8>, WorkerStats>,
+    unattributed: Option<WorkerStats>,
+    max_workers: usize,
+    idle_timeout: Duration,
+    hashrate_window: Duration,
+    // When idle workers are looked for next while recording shares.
+    next_expiry: Option<Instant>,
+}
+
+impl Default for WorkerAccounting {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_MAX_WORKERS,
+            DEFAULT_WORKER_IDLE_TIMEOUT,
+            DEFAULT_HASHRATE_WINDOW,
+        )
+    }
+}
+
+impl WorkerAccounting {
+    /// Constructs a new `WorkerAccounting` tracking at most `max_workers` workers, each dropped
+    /// after `idle_timeout` without shares, and estimating their hashrate over `hashrate_window`.
+    pub fn new(max_workers: usize, idle_timeout: Duration, hashrate_window: Duration) -> Self {
+        Self {
+            workers: HashMap::new(),
+            unattributed: None,
+            max_workers,
+            idle_timeout,
+            hashrate_window,
+            next_expiry: None,
+        }
+    }
+
+    /// Records the validation result of a share submitted by `user_identity`.
+    ///
+    /// `share_work` is the work of the share if accepted, i.e. the difficulty of the job target.
+    /// Shares without a `UserIdentity`, or from a new worker while `max_workers` workers are
+    /// active, are recorded as unattributed.
+    pub fn record_share(
+        &mut self,
+        user_identity: Option<&UserIdentity>,
+        result: &Result<ShareValidationResult, ShareValidationError>,
+        share_work: f64,
+        now: Instant,
+    ) {
+        if !matches!(self.next_expiry, Some(next_expiry) if now < next_expiry) {
+            self.expire_idle_workers(now);
+            self.next_expiry = Some(now + self.idle_timeout / 4);
+        }
+        let hashrate_window = self.hashrate_window;
+        let stats = match user_identity {
+            Some(user_identity) if self.track_worker(user_identity.as_bytes(), now) => self
+                .workers
+                .get_mut(user_identity.as_bytes())
+                .expect("the worker is tracked"),
+            _ => self
+                .unattributed
+                .get_or_insert_with(|| WorkerStats::new(now, hashrate_window)),
+        };
+        stats.record_share(result, share_work, now);
+    }
+
+    // Starts tracking a worker if there is room for it, returning whether it is tracked.
+    fn track_worker(&mut self, worker: &[u8], now: Instant) -> bool {
+        if self.workers.contains_key(worker) {
+            return true;
+        }
+        if self.workers.len() >= self.max_workers {
+            self.expire_idle_workers(now);
+        }
+        if self.workers.len() >= self.max_workers {
+            return false;
+        }
+        self.workers
+            .insert(worker.to_vec(), WorkerStats::new(now, self.hashrate_window));
+        true
+    }
+
+    /// Drops the workers that submitted no share for `idle_timeout`, returning how many were
+    /// dropped.
+    pub fn expire_idle_workers(&mut self, now: Instant) -> usize {
+        let len = self.workers.len();
+        let idle_timeout = self.idle_timeout;
+        self.workers
+            .retain(|_, stats| now.saturating_duration_since(stats.last_share_time) < idle_timeout);
+        len - self.workers.len()
+    }
+
+    /// Returns the stats of a worker, if tracked.
+    pub fn get_worker_stats(&self, user_identity: &UserIdentity) -> Option<&WorkerStats> {
+        self.workers.get(user_identity.as_bytes())
+    }
+
+    /// Returns the tracked workers, as the raw bytes of their `UserIdentity`, with their stats.
+    pub fn get_workers(&self) -> impl Iterator<Item = (&[u8], &WorkerStats)> {
+        self.workers
+            .iter()
+            .map(|(worker, stats)| (worker.as_slice(), stats))
+    }
+
+    /// Returns the number of tracked workers.
+    pub fn get_worker_count(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Returns the stats of the shares not attributed to a tracked worker, if any.
+    pub fn get_unattributed_stats(&self) -> Option<&WorkerStats> {
+        self.unattributed.as_ref()
+    }
+
+    /// Returns the maximum number of tracked workers.
+    pub fn get_max_workers(&self) -> usize {
+        self.max_workers
+    }
+
+    /// Returns the duration without shares after which a worker is dropped.
+    pub fn get_idle_timeout(&self) -> Duration {
+        self.idle_timeout
+    }
+
+    /// Returns the time constant of the hashrate estimates.
+    pub fn get_hashrate_window(&self) -> Duration {
+        self.hashrate_window
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::hashes::sha256d::Hash;
+
+    fn valid_share() -> Result<ShareValidationResult, ShareValidationError> {
+        // difficulty of a hash is computed from its little endian bytes
+        let mut hash = [0xff; 32];
+        hash[28..].copy_from_slice(&[0; 4]);
+        Ok(ShareValidationResult::Valid(Hash::from_byte_array(hash)))
+    }
+
+    #[test]
+    fn test_worker_stats() {
+        let now = Instant::now();
+        let mut accounting = WorkerAccounting::default();
+        let worker = UserIdentity::new("Worker_001").unwrap();
+
+        accounting.record_share(Some(&worker), &valid_share(), 2.0, now);
+        accounting.record_share(Some(&worker), &valid_share(), 2.0, now);
+        accounting.record_share(
+            Some(&worker),
+            &Err(ShareValidationError::DoesNotMeetTarget),
+            2.0,
+            now,
+        );
+        accounting.record_share(None, &valid_share(), 2.0, now);
+
+        let stats = accounting.get_worker_stats(&worker).unwrap();
+        assert_eq!(stats.get_shares_accepted(), 2);
+        assert_eq!(stats.get_shares_rejected(), 1);
+        assert_eq!(stats.get_share_work_sum(), 4.0);
+        assert!(stats.get_best_diff() > 0.9);
+        assert_eq!(stats.get_hashrate_estimate(now), 0.0);
+        // 4 units of work over the first 2 seconds
+        let hashrate = stats.get_hashrate_estimate(now + Duration::from_secs(2));
+        assert!((hashrate / 2f64.powi(33) - 1.0).abs() < 0.01);
+
+        let unattributed = accounting.get_unattributed_stats().unwrap();
+        assert_eq!(unattributed.get_shares_accepted(), 1);
+    }
+
+    #[test]
+    fn test_max_workers_and_expiry() {
+        let now = Instant::now();
+        let mut accounting =
+            WorkerAccounting::new(2, Duration::from_secs(60), DEFAULT_HASHRATE_WINDOW);
+        let workers: Vec<_> = (0..3)
+            .map(|i| UserIdentity::new(&format!("Worker_{i}")).unwrap())
+            .collect();
+
+        accounting.record_share(Some(&workers[0]), &valid_share(), 1.0, now);
+        let later = now + Duration::from_secs(30);
+        accounting.record_share(Some(&workers[1]), &valid_share(), 1.0, later);
+        // no room for a third worker
+        accounting.record_share(Some(&workers[2]), &valid_share(), 1.0, later);
+        assert_eq!(accounting.get_worker_count(), 2);
+        assert!(accounting.get_worker_stats(&workers[2]).is_none());
+        assert_eq!(
+            accounting
+                .get_unattributed_stats()
+                .unwrap()
+                .get_shares_accepted(),
+            1
+        );
+
+        // the first worker is idle, making room for the third one
+        let much_later = now + Duration::from_secs(60);
+        accounting.record_share(Some(&workers[2]), &valid_share(), 1.0, much_later);
+        assert!(accounting.get_worker_stats(&workers[0]).is_none());
+        assert_eq!(
+            accounting
+                .get_worker_stats(&workers[2])
+                .unwrap()
+                .get_shares_accepted(),
+            1
+        );
+        assert_eq!(accounting.expire_idle_workers(much_later), 0);
+    }
+
+    #[test]
+    fn test_hashrate_estimate_follows_recent_shares() {
+        let now = Instant::now();
+        let window = Duration::from_secs(60);
+        let mut accounting = WorkerAccounting::new(16, Duration::from_secs(3600), window);
+        let worker = UserIdentity::new("Worker_001").unwrap();
+
+        // 1 unit of work per second for 10 minutes, then 4 units per second for 10 minutes
+        for i in 0..1200 {
+            let share_work = if i < 600 { 1.0 } else { 4.0 };
+            let at = now + Duration::from_secs(i + 1);
+            accounting.record_share(Some(&worker), &valid_share(), share_work, at);
+        }
+        let end = now + Duration::from_secs(1200);
+        let stats = accounting.get_worker_stats(&worker).unwrap();
+        let hashrate = stats.get_hashrate_estimate(end) / 2f64.powi(32);
+        assert!((hashrate - 4.0).abs() < 0.2);
+
+        // the estimate decays once the worker stops submitting shares
+        let idle = stats.get_hashrate_estimate(end + window * 5) / 2f64.powi(32);
+        assert!(idle < 0.1);
+    }
+
+    #[test]
+    fn test_idle_workers_expire_on_share() {
+        let now = Instant::now();
+        let mut accounting =
+            WorkerAccounting::new(16, Duration::from_secs(60), DEFAULT_HASHRATE_WINDOW);
+        let workers: Vec<_> = (0..2)
+            .map(|i| UserIdentity::new(&format!("Worker_{i}")).unwrap())
+            .collect();
+
+        accounting.record_share(Some(&workers[0]), &valid_share(), 1.0, now);
+        accounting.record_share(Some(&workers[1]), &valid_share(), 1.0, now);
+        // the first worker goes idle while the second one keeps submitting shares
+        for i in 1..=8 {
+            let at = now + Duration::from_secs(10 * i);
+            accounting.record_share(Some(&workers[1]), &valid_share(), 1.0, at);
+        }
+        assert!(accounting.get_worker_stats(&workers[0]).is_none());
+        assert_eq!(accounting.get_worker_count(), 1);
+    }
+}
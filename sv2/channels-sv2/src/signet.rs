@@ -0,0 +1,708 @@
+//! Signet block signing, as specified in [BIP325](https://github.com/bitcoin/bips/blob/master/bip-0325.mediawiki).
+//!
+//! On signet, every block must carry a solution to the network challenge script. The solution is
+//! appended to the coinbase witness commitment output, as a push of [`SIGNET_HEADER`] followed by
+//! the serialized `scriptSig` and witness that satisfy the challenge.
+//!
+//! The signature commits to the block version, the previous block hash, the block time and a
+//! merkle root computed with the solution removed from the coinbase. Only the header nonce is left
+//! out of it. Since the solution lives in the coinbase, it also changes the merkle root of the
+//! block that is actually mined. This means a block must be signed **before** proof of work is
+//! searched for it, with version, time and coinbase fixed.
+//!
+//! [`SignetSigner`] supports challenges that can be satisfied by a single key:
+//! - pay to public key (`<pubkey> OP_CHECKSIG`)
+//! - bare 1-of-N multisig (`OP_1 <pubkey> ... OP_N OP_CHECKMULTISIG`)
+//! - pay to witness public key hash (`OP_0 <20 byte key hash>`)
+use bitcoin::{
+    absolute::LockTime,
+    block::Version as BlockVersion,
+    blockdata::{
+        opcodes::all::{OP_CHECKMULTISIG, OP_PUSHNUM_1, OP_RETURN},
+        script::{Builder, Instruction, PushBytesBuf},
+    },
+    consensus::Encodable,
+    ecdsa,
+    hashes::Hash,
+    secp256k1::{Message, PublicKey, Secp256k1, SecretKey},
+    sighash::{EcdsaSighashType, SighashCache},
+    transaction::{OutPoint, Transaction, TxIn, TxOut, Version},
+    Amount, Block, BlockHash, CompressedPublicKey, Script, ScriptBuf, Sequence, TxMerkleNode,
+    Witness,
+};
+
+/// Marker that precedes the signet solution inside the witness commitment output.
+pub const SIGNET_HEADER: [u8; 4] = [0xec, 0xc7, 0xda, 0xa2];
+
+/// Script prefix of a BIP141 witness commitment output (`OP_RETURN OP_PUSHBYTES_36 aa21a9ed`).
+const WITNESS_COMMITMENT_PREFIX: [u8; 6] = [0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SignetError {
+    /// The challenge is not a script that can be satisfied by a single key.
+    UnsupportedChallenge,
+    /// The public key of the signing key does not appear in the challenge.
+    KeyNotInChallenge,
+    /// The coinbase has no witness commitment output to carry the solution.
+    MissingWitnessCommitment,
+    /// The block has no transactions.
+    MissingCoinbase,
+    /// The witness commitment output has no reserved space for the solution.
+    SolutionNotReserved,
+    /// The solution is too large to be pushed into the witness commitment output.
+    SolutionTooLarge,
+    /// The signature hash could not be computed.
+    SighashError,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChallengeKind {
+    P2pk,
+    BareMultisig,
+    P2wpkh,
+}
+
+/// Signs signet blocks for a challenge script with a single key.
+#[derive(Debug, Clone)]
+pub struct SignetSigner {
+    challenge: ScriptBuf,
+    challenge_kind: ChallengeKind,
+    secret_key: SecretKey,
+    public_key: PublicKey,
+}
+
+impl SignetSigner {
+    /// Creates a new [`SignetSigner`] for the given challenge script.
+    ///
+    /// Returns an error if the challenge can't be satisfied by a single signature from
+    /// `secret_key`.
+    pub fn new(challenge: ScriptBuf, secret_key: SecretKey) -> Result<Self, SignetError> {
+        let secp = Secp256k1::signing_only();
+        let public_key = secret_key.public_key(&secp);
+
+        let challenge_kind = if challenge.is_p2wpkh() {
+            let expected = ScriptBuf::new_p2wpkh(&CompressedPublicKey(public_key).wpubkey_hash());
+            if challenge != expected {
+                return Err(SignetError::KeyNotInChallenge);
+            }
+            ChallengeKind::P2wpkh
+        } else if let Some(key) = challenge.p2pk_public_key() {
+            if key.inner != public_key {
+                return Err(SignetError::KeyNotInChallenge);
+            }
+            ChallengeKind::P2pk
+        } else if let Some(keys) = single_sig_multisig_keys(&challenge) {
+            if !keys.contains(&public_key) {
+                return Err(SignetError::KeyNotInChallenge);
+            }
+            ChallengeKind::BareMultisig
+        } else {
+            return Err(SignetError::UnsupportedChallenge);
+        };
+
+        Ok(Self {
+            challenge,
+            challenge_kind,
+            secret_key,
+            public_key,
+        })
+    }
+
+    /// Returns the challenge script.
+    pub fn challenge(&self) -> &Script {
+        &self.challenge
+    }
+
+    /// Computes the serialized signet solution for a block with the given header fields.
+    ///
+    /// `signet_merkle_root` is the merkle root of the block computed with the coinbase witness
+    /// commitment output carrying only [`SIGNET_HEADER`] (see [`reserve_solution`]).
+    pub fn solution(
+        &self,
+        version: BlockVersion,
+        prev_blockhash: BlockHash,
+        signet_merkle_root: TxMerkleNode,
+        time: u32,
+    ) -> Result<Vec<u8>, SignetError> {
+        let mut block_data = Vec::with_capacity(72);
+        block_data.extend_from_slice(&version.to_consensus().to_le_bytes());
+        block_data.extend_from_slice(prev_blockhash.as_byte_array());
+        block_data.extend_from_slice(signet_merkle_root.as_byte_array());
+        block_data.extend_from_slice(&time.to_le_bytes());
+
+        let to_spend = to_spend_tx(&block_data, &self.challenge);
+        let mut to_sign = to_sign_tx(&to_spend);
+
+        let sighash: [u8; 32] = match self.challenge_kind {
+            ChallengeKind::P2wpkh => SighashCache::new(&to_sign)
+                .p2wpkh_signature_hash(0, &self.challenge, Amount::ZERO, EcdsaSighashType::All)
+                .map_err(|_| SignetError::SighashError)?
+                .to_byte_array(),
+            ChallengeKind::P2pk | ChallengeKind::BareMultisig => SighashCache::new(&to_sign)
+                .legacy_signature_hash(0, &self.challenge, EcdsaSighashType::All.to_u32())
+                .map_err(|_| SignetError::SighashError)?
+                .to_byte_array(),
+        };
+
+        let secp = Secp256k1::signing_only();
+        let signature = ecdsa::Signature {
+            signature: secp.sign_ecdsa(&Message::from_digest(sighash), &self.secret_key),
+            sighash_type: EcdsaSighashType::All,
+        };
+        let signature = PushBytesBuf::try_from(signature.to_vec())
+            .expect("ecdsa signature is at most 73 bytes");
+
+        match self.challenge_kind {
+            ChallengeKind::P2pk => {
+                to_sign.input[0].script_sig = Builder::new().push_slice(signature).into_script();
+            }
+            ChallengeKind::BareMultisig => {
+                // OP_0 satisfies the extra stack element consumed by OP_CHECKMULTISIG
+                to_sign.input[0].script_sig = Builder::new()
+                    .push_int(0)
+                    .push_slice(signature)
+                    .into_script();
+            }
+            ChallengeKind::P2wpkh => {
+                let mut witness = Witness::new();
+                witness.push(signature.as_bytes());
+                witness.push(self.public_key.serialize());
+                to_sign.input[0].witness = witness;
+            }
+        }
+
+        let mut solution = Vec::new();
+        to_sign.input[0]
+            .script_sig
+            .consensus_encode(&mut solution)
+            .expect("writing to a vec can't fail");
+        to_sign.input[0]
+            .witness
+            .consensus_encode(&mut solution)
+            .expect("writing to a vec can't fail");
+
+        Ok(solution)
+    }
+
+    /// Signs a block, replacing any previous solution in its coinbase.
+    ///
+    /// The merkle root in the block header is updated to commit to the signed coinbase. Proof of
+    /// work must be searched for after signing, and only the header nonce may change afterwards.
+    pub fn sign_block(&self, block: &mut Block) -> Result<(), SignetError> {
+        let coinbase = block
+            .txdata
+            .first_mut()
+            .ok_or(SignetError::MissingCoinbase)?;
+        reserve_solution(&mut coinbase.output)?;
+
+        let signet_merkle_root = block
+            .compute_merkle_root()
+            .ok_or(SignetError::MissingCoinbase)?;
+        let solution = self.solution(
+            block.header.version,
+            block.header.prev_blockhash,
+            signet_merkle_root,
+            block.header.time,
+        )?;
+
+        insert_solution(&mut block.txdata[0].output, &solution)?;
+        block.header.merkle_root = block
+            .compute_merkle_root()
+            .ok_or(SignetError::MissingCoinbase)?;
+
+        Ok(())
+    }
+}
+
+/// Reserves space for the signet solution in the witness commitment output.
+///
+/// Any previous solution is removed, leaving a push of [`SIGNET_HEADER`] alone. This is the form
+/// of the coinbase the solution signature commits to.
+pub fn reserve_solution(coinbase_outputs: &mut [TxOut]) -> Result<(), SignetError> {
+    let index =
+        witness_commitment_index(coinbase_outputs).ok_or(SignetError::MissingWitnessCommitment)?;
+    let script = &coinbase_outputs[index].script_pubkey;
+
+    let mut builder = Builder::new();
+    let mut found_header = false;
+    for instruction in script.instructions() {
+        match instruction.map_err(|_| SignetError::MissingWitnessCommitment)? {
+            Instruction::PushBytes(data) => {
+                let data = data.as_bytes();
+                if !found_header && data.len() >= SIGNET_HEADER.len() && data[..4] == SIGNET_HEADER
+                {
+                    found_header = true;
+                    builder = builder.push_slice(SIGNET_HEADER);
+                } else {
+                    builder = builder.push_slice(
+                        PushBytesBuf::try_from(data.to_vec()).expect("data was already pushed"),
+                    );
+                }
+            }
+            Instruction::Op(opcode) => builder = builder.push_opcode(opcode),
+        }
+    }
+    if !found_header {
+        builder = builder.push_slice(SIGNET_HEADER);
+    }
+
+    coinbase_outputs[index].script_pubkey = builder.into_script();
+    Ok(())
+}
+
+/// Fills the space reserved by [`reserve_solution`] with a serialized signet solution.
+pub fn insert_solution(coinbase_outputs: &mut [TxOut], solution: &[u8]) -> Result<(), SignetError> {
+    let index =
+        witness_commitment_index(coinbase_outputs).ok_or(SignetError::MissingWitnessCommitment)?;
+    let script = &coinbase_outputs[index].script_pubkey;
+
+    let mut commitment = SIGNET_HEADER.to_vec();
+    commitment.extend_from_slice(solution);
+    let commitment =
+        PushBytesBuf::try_from(commitment).map_err(|_| SignetError::SolutionTooLarge)?;
+
+    let mut builder = Builder::new();
+    let mut found_header = false;
+    for instruction in script.instructions() {
+        match instruction.map_err(|_| SignetError::MissingWitnessCommitment)? {
+            Instruction::PushBytes(data) if !found_header && data.as_bytes() == SIGNET_HEADER => {
+                found_header = true;
+                builder = builder.push_slice(&commitment);
+            }
+            Instruction::PushBytes(data) => {
+                builder = builder.push_slice(
+                    PushBytesBuf::try_from(data.as_bytes().to_vec())
+                        .expect("data was already pushed"),
+                );
+            }
+            Instruction::Op(opcode) => builder = builder.push_opcode(opcode),
+        }
+    }
+    if !found_header {
+        return Err(SignetError::SolutionNotReserved);
+    }
+
+    coinbase_outputs[index].script_pubkey = builder.into_script();
+    Ok(())
+}
+
+/// Returns true if the witness commitment output carries a signet solution, as inserted by
+/// [`insert_solution`].
+pub fn has_solution(coinbase_outputs: &[TxOut]) -> bool {
+    witness_commitment_index(coinbase_outputs).is_some_and(|index| {
+        coinbase_outputs[index]
+            .script_pubkey
+            .instructions()
+            .any(|instruction| match instruction {
+                Ok(Instruction::PushBytes(data)) => {
+                    let data = data.as_bytes();
+                    data.len() > SIGNET_HEADER.len() && data[..4] == SIGNET_HEADER
+                }
+                _ => false,
+            })
+    })
+}
+
+// The witness commitment is the last output whose script starts with the BIP141 commitment
+// header.
+fn witness_commitment_index(outputs: &[TxOut]) -> Option<usize> {
+    outputs.iter().rposition(|output| {
+        let script = output.script_pubkey.as_bytes();
+        script.len() >= 38 && script[..6] == WITNESS_COMMITMENT_PREFIX
+    })
+}
+
+// Returns the keys of a `OP_1 <pubkey> ... OP_N OP_CHECKMULTISIG` script.
+fn single_sig_multisig_keys(script: &Script) -> Option<Vec<PublicKey>> {
+    let instructions = script.instructions().collect::<Result<Vec<_>, _>>().ok()?;
+    let (first, rest) = instructions.split_first()?;
+    let (last, rest) = rest.split_last()?;
+    let (key_count, keys) = rest.split_last()?;
+
+    if *first != Instruction::Op(OP_PUSHNUM_1) || *last != Instruction::Op(OP_CHECKMULTISIG) {
+        return None;
+    }
+    let key_count = key_count
+        .opcode()?
+        .to_u8()
+        .checked_sub(OP_PUSHNUM_1.to_u8() - 1)?;
+    if keys.is_empty() || keys.len() != key_count as usize {
+        return None;
+    }
+
+    keys.iter()
+        .map(|key| PublicKey::from_slice(key.push_bytes()?.as_bytes()).ok())
+        .collect()
+}
+
+fn to_spend_tx(block_data: &[u8], challenge: &Script) -> Transaction {
+    let block_data = PushBytesBuf::try_from(block_data.to_vec()).expect("block data is 72 bytes");
+    Transaction {
+        version: Version::non_standard(0),
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::null(),
+            script_sig: Builder::new()
+                .push_int(0)
+                .push_slice(block_data)
+                .into_script(),
+            sequence: Sequence::ZERO,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: challenge.to_owned(),
+        }],
+    }
+}
+
+fn to_sign_tx(to_spend: &Transaction) -> Transaction {
+    Transaction {
+        version: Version::non_standard(0),
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(to_spend.compute_txid(), 0),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ZERO,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: Builder::new().push_opcode(OP_RETURN).into_script(),
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::{
+        block::Header,
+        consensus::{encode::deserialize_hex, Decodable},
+        hashes::sha256d,
+        hex::FromHex,
+        secp256k1::ecdsa::Signature,
+        CompactTarget,
+    };
+
+    fn secret_key(byte: u8) -> SecretKey {
+        SecretKey::from_slice(&[byte; 32]).unwrap()
+    }
+
+    fn public_key(byte: u8) -> PublicKey {
+        secret_key(byte).public_key(&Secp256k1::signing_only())
+    }
+
+    fn unsigned_block() -> Block {
+        let mut commitment = WITNESS_COMMITMENT_PREFIX.to_vec();
+        commitment.extend_from_slice(&[7; 32]);
+
+        let coinbase = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::from(vec![1, 42]),
+                sequence: Sequence::MAX,
+                witness: Witness::from(vec![vec![0; 32]]),
+            }],
+            output: vec![
+                TxOut {
+                    value: Amount::from_sat(5_000_000_000),
+                    script_pubkey: ScriptBuf::new_p2wpkh(
+                        &CompressedPublicKey(public_key(9)).wpubkey_hash(),
+                    ),
+                },
+                TxOut {
+                    value: Amount::ZERO,
+                    script_pubkey: ScriptBuf::from(commitment),
+                },
+            ],
+        };
+        let other_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(
+                    bitcoin::Txid::from_raw_hash(sha256d::Hash::hash(b"prevout")),
+                    1,
+                ),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(1_000),
+                script_pubkey: ScriptBuf::new(),
+            }],
+        };
+
+        let mut block = Block {
+            header: Header {
+                version: BlockVersion::from_consensus(0x2000_0000),
+                prev_blockhash: BlockHash::from_byte_array([3; 32]),
+                merkle_root: TxMerkleNode::all_zeros(),
+                time: 1_747_092_633,
+                bits: CompactTarget::from_consensus(0x1e0377ae),
+                nonce: 0,
+            },
+            txdata: vec![coinbase, other_tx],
+        };
+        block.header.merkle_root = block.compute_merkle_root().unwrap();
+        block
+    }
+
+    // Returns the `scriptSig` and witness carried by the signet commitment of a block.
+    fn extract_solution(block: &Block) -> (ScriptBuf, Witness) {
+        let outputs = &block.txdata[0].output;
+        let script = &outputs[witness_commitment_index(outputs).unwrap()].script_pubkey;
+        let data = script
+            .instructions()
+            .filter_map(|instruction| match instruction.unwrap() {
+                Instruction::PushBytes(data) => Some(data.as_bytes()),
+                Instruction::Op(_) => None,
+            })
+            .find(|data| data.len() > 4 && data[..4] == SIGNET_HEADER)
+            .unwrap()
+            .to_vec();
+
+        let mut cursor = &data[4..];
+        let script_sig = ScriptBuf::consensus_decode(&mut cursor).unwrap();
+        let witness = Witness::consensus_decode(&mut cursor).unwrap();
+        assert!(cursor.is_empty());
+        (script_sig, witness)
+    }
+
+    // Recomputes the signature hash the solution of a block must commit to.
+    fn signet_sighash(block: &Block, challenge: &Script, segwit: bool) -> Message {
+        let mut stripped = block.clone();
+        reserve_solution(&mut stripped.txdata[0].output).unwrap();
+
+        let mut block_data = vec![];
+        block_data.extend_from_slice(&block.header.version.to_consensus().to_le_bytes());
+        block_data.extend_from_slice(block.header.prev_blockhash.as_byte_array());
+        block_data.extend_from_slice(stripped.compute_merkle_root().unwrap().as_byte_array());
+        block_data.extend_from_slice(&block.header.time.to_le_bytes());
+
+        let to_spend = to_spend_tx(&block_data, challenge);
+        let to_sign = to_sign_tx(&to_spend);
+        let mut cache = SighashCache::new(&to_sign);
+        let sighash = match segwit {
+            true => cache
+                .p2wpkh_signature_hash(0, challenge, Amount::ZERO, EcdsaSighashType::All)
+                .unwrap()
+                .to_byte_array(),
+            false => cache
+                .legacy_signature_hash(0, challenge, EcdsaSighashType::All.to_u32())
+                .unwrap()
+                .to_byte_array(),
+        };
+        Message::from_digest(sighash)
+    }
+
+    fn verify(message: &Message, signature: &[u8], public_key: &PublicKey) {
+        let (sighash_type, signature) = signature.split_last().unwrap();
+        assert_eq!(*sighash_type, EcdsaSighashType::All as u8);
+        Secp256k1::verification_only()
+            .verify_ecdsa(
+                message,
+                &Signature::from_der(signature).unwrap(),
+                public_key,
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_sign_block_p2wpkh_challenge() {
+        let challenge = ScriptBuf::new_p2wpkh(&CompressedPublicKey(public_key(1)).wpubkey_hash());
+        let signer = SignetSigner::new(challenge.clone(), secret_key(1)).unwrap();
+
+        let mut block = unsigned_block();
+        signer.sign_block(&mut block).unwrap();
+
+        // the header commits to the signed coinbase
+        assert_eq!(
+            block.header.merkle_root,
+            block.compute_merkle_root().unwrap()
+        );
+
+        let (script_sig, witness) = extract_solution(&block);
+        assert!(script_sig.is_empty());
+        assert_eq!(witness.len(), 2);
+        assert_eq!(witness.nth(1).unwrap(), public_key(1).serialize());
+
+        let message = signet_sighash(&block, &challenge, true);
+        verify(&message, witness.nth(0).unwrap(), &public_key(1));
+    }
+
+    #[test]
+    fn test_sign_block_multisig_challenge() {
+        let challenge = Builder::new()
+            .push_opcode(OP_PUSHNUM_1)
+            .push_slice(public_key(1).serialize())
+            .push_slice(public_key(2).serialize())
+            .push_int(2)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script();
+        let signer = SignetSigner::new(challenge.clone(), secret_key(2)).unwrap();
+
+        let mut block = unsigned_block();
+        signer.sign_block(&mut block).unwrap();
+
+        let (script_sig, witness) = extract_solution(&block);
+        assert!(witness.is_empty());
+        let instructions: Vec<_> = script_sig.instructions().map(|i| i.unwrap()).collect();
+        assert_eq!(instructions.len(), 2);
+        assert!(instructions[0].push_bytes().unwrap().is_empty());
+
+        let message = signet_sighash(&block, &challenge, false);
+        verify(
+            &message,
+            instructions[1].push_bytes().unwrap().as_bytes(),
+            &public_key(2),
+        );
+    }
+
+    // The block built by `unsigned_block`, and the solutions of its signed copies, as computed by
+    // an independent BIP325 implementation (deterministic RFC6979 signatures, low S).
+    const UNSIGNED_BLOCK: &str = "000000200303030303030303030303030303030303030303030303030303030303030303ddbd46d8ed389859cf2f0209d57b10809f6ad0bcf5920c0f138416e56bbbe14999842268ae77031e0000000002020000000001010000000000000000000000000000000000000000000000000000000000000000ffffffff02012affffffff0200f2052a01000000160014670caa79e51d78ed0c583b89ff39d9c49b7199e70000000000000000266a24aa21a9ed0707070707070707070707070707070707070707070707070707070707070707012000000000000000000000000000000000000000000000000000000000000000000000000002000000015f0132e561944b81e5f25136242c591c6890a2d963f6ac26d491a89160469def0100000000ffffffff01e8030000000000000000000000";
+    const P2WPKH_SOLUTION: &str = "0002483045022100f3951af940e9a9de7fcb0c59013b6a27fae6a0d7ef479b033572766f2df9eeb8022046d120b3607e4d2fa9d751859238cfd18aac7396df93abf9d029d87fccd17e250121031b84c5567b126440995d3ed5aaba0565d71e1834604819ff9c17f5e9d5dd078f";
+    const P2WPKH_MERKLE_ROOT: &str =
+        "406977629a24b563d31b752cba3b92ee8684b4d6071035d858ca7b4fdc670fd7";
+    const MULTISIG_SOLUTION: &str = "490047304402207a415f7916155eab3747699dfa9ca8bb78d1cb3210eebec1912259748223eed4022034cfca106405f6bb717668211160f162d6282ace27869d54dda8e5ed60768eb20100";
+    const MULTISIG_MERKLE_ROOT: &str =
+        "4a318ad7d3bc2bece4627a78b3d3ac4ddcf6fe75d506cc1fcf792b9c3bb0bcec";
+
+    #[test]
+    fn test_sign_block_matches_reference_vectors() {
+        let block: Block = deserialize_hex(UNSIGNED_BLOCK).unwrap();
+        assert_eq!(block, unsigned_block());
+
+        let p2wpkh = ScriptBuf::new_p2wpkh(&CompressedPublicKey(public_key(1)).wpubkey_hash());
+        let multisig = Builder::new()
+            .push_opcode(OP_PUSHNUM_1)
+            .push_slice(public_key(1).serialize())
+            .push_slice(public_key(2).serialize())
+            .push_int(2)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script();
+
+        for (challenge, secret_key, solution, merkle_root) in [
+            (p2wpkh, secret_key(1), P2WPKH_SOLUTION, P2WPKH_MERKLE_ROOT),
+            (
+                multisig,
+                secret_key(2),
+                MULTISIG_SOLUTION,
+                MULTISIG_MERKLE_ROOT,
+            ),
+        ] {
+            let signer = SignetSigner::new(challenge, secret_key).unwrap();
+            let mut signed = block.clone();
+            signer.sign_block(&mut signed).unwrap();
+
+            // the solution is pushed right after the witness commitment
+            let mut commitment = SIGNET_HEADER.to_vec();
+            commitment.extend(Vec::<u8>::from_hex(solution).unwrap());
+            let mut expected = block.txdata[0].output[1].script_pubkey.to_bytes();
+            expected.extend(
+                Builder::new()
+                    .push_slice(PushBytesBuf::try_from(commitment).unwrap())
+                    .into_script()
+                    .as_bytes(),
+            );
+            assert_eq!(
+                signed.txdata[0].output[1].script_pubkey.as_bytes(),
+                expected
+            );
+            assert_eq!(
+                signed.header.merkle_root.to_byte_array().to_vec(),
+                Vec::<u8>::from_hex(merkle_root).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_resigning_replaces_previous_solution() {
+        let challenge = Builder::new()
+            .push_slice(public_key(1).serialize())
+            .push_opcode(bitcoin::opcodes::all::OP_CHECKSIG)
+            .into_script();
+        let signer = SignetSigner::new(challenge.clone(), secret_key(1)).unwrap();
+
+        let mut block = unsigned_block();
+        signer.sign_block(&mut block).unwrap();
+        block.header.time += 1;
+        signer.sign_block(&mut block).unwrap();
+
+        // only one solution is carried, and it commits to the new header time
+        let commitment = &block.txdata[0].output[1].script_pubkey;
+        assert_eq!(commitment.instructions().count(), 3);
+        let (script_sig, _) = extract_solution(&block);
+        let signature = script_sig.instructions().next().unwrap().unwrap();
+        verify(
+            &signet_sighash(&block, &challenge, false),
+            signature.push_bytes().unwrap().as_bytes(),
+            &public_key(1),
+        );
+    }
+
+    #[test]
+    fn test_reserve_solution() {
+        let mut outputs = unsigned_block().txdata[0].output.clone();
+        let commitment = outputs[1].script_pubkey.clone();
+
+        reserve_solution(&mut outputs).unwrap();
+        let mut expected = commitment.to_bytes();
+        expected.push(4);
+        expected.extend_from_slice(&SIGNET_HEADER);
+        assert_eq!(outputs[1].script_pubkey.as_bytes(), expected);
+
+        // reserving again is a no-op
+        reserve_solution(&mut outputs).unwrap();
+        assert_eq!(outputs[1].script_pubkey.as_bytes(), expected);
+
+        // and it strips an inserted solution
+        assert!(!has_solution(&outputs));
+        insert_solution(&mut outputs, &[0, 0]).unwrap();
+        assert_ne!(outputs[1].script_pubkey.as_bytes(), expected);
+        assert!(has_solution(&outputs));
+        reserve_solution(&mut outputs).unwrap();
+        assert_eq!(outputs[1].script_pubkey.as_bytes(), expected);
+        assert!(!has_solution(&outputs));
+
+        assert_eq!(
+            insert_solution(&mut outputs[..1], &[0, 0]),
+            Err(SignetError::MissingWitnessCommitment)
+        );
+    }
+
+    #[test]
+    fn test_signer_rejects_unusable_challenges() {
+        let challenge = ScriptBuf::new_p2wpkh(&CompressedPublicKey(public_key(1)).wpubkey_hash());
+        assert_eq!(
+            SignetSigner::new(challenge, secret_key(2)).unwrap_err(),
+            SignetError::KeyNotInChallenge
+        );
+
+        // 2-of-2 multisig needs more than one key
+        let challenge = Builder::new()
+            .push_int(2)
+            .push_slice(public_key(1).serialize())
+            .push_slice(public_key(2).serialize())
+            .push_int(2)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script();
+        assert_eq!(
+            SignetSigner::new(challenge, secret_key(1)).unwrap_err(),
+            SignetError::UnsupportedChallenge
+        );
+
+        let challenge = Builder::new()
+            .push_opcode(bitcoin::opcodes::OP_TRUE)
+            .into_script();
+        assert_eq!(
+            SignetSigner::new(challenge, secret_key(1)).unwrap_err(),
+            SignetError::UnsupportedChallenge
+        );
+    }
+}
@@ -3,9 +3,13 @@
 extern crate alloc;
 use alloc::string::String;
 use binary_sv2::U256;
-use bitcoin::{hash_types::BlockHash, hashes::Hash, Target};
-use core::{cmp::max, fmt::Write, ops::Div};
-use primitive_types::U256 as U256Primitive;
+use bitcoin::{hash_types::BlockHash, hashes::Hash, CompactTarget, Target, Work};
+use core::{
+    cmp::{max, Ordering},
+    fmt::Write,
+    ops::Div,
+};
+use primitive_types::{U256 as U256Primitive, U512};
 
 /// Converts a `u256` to a [`BlockHash`] type.
 pub fn u256_to_block_hash(v: U256<'static>) -> BlockHash {
@@ -200,3 +204,350 @@ pub fn hash_rate_from_target(target: U256<'static>, share_per_min: f64) -> Resul
     // we multiply back by 100 so that it cancels with the same factor at the denominator
     Ok(result as f64)
 }
+
+// Target corresponding to a pool difficulty (pdiff) of 1, in big endian.
+const PDIFF_1_TARGET: [u8; 32] = [
+    0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+];
+
+/// Errors that can occur when building a [`Difficulty`] from a floating point value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DifficultyError {
+    /// The difficulty is `NaN` or infinite.
+    NotFinite,
+    /// The difficulty is zero or negative.
+    NotPositive,
+    /// The difficulty is so low that the corresponding target exceeds `2^256 - 1`.
+    TargetOverflow,
+    /// The difficulty is so high that the corresponding target rounds down to zero.
+    TargetUnderflow,
+}
+
+/// A mining difficulty, canonically represented by its [`Target`].
+///
+/// Pools, SV1 miners and Bitcoin Core express difficulty in different units. `Difficulty` keeps
+/// the exact 256-bit target and converts to and from every unit with well-defined rounding, so
+/// that independent components (e.g. `mining.set_difficulty`, dashboards and payout engines)
+/// always agree on the same value:
+///
+/// - [`Target`]: lossless in both directions.
+/// - compact `nbits`: [`Difficulty::from_compact`] is lossless, [`Difficulty::to_compact_lossy`]
+///   truncates the target to the 24-bit mantissa of the compact encoding, as Bitcoin Core does.
+/// - pool difficulty (pdiff, see [`Difficulty::pdiff_1`]) and bitcoin difficulty (bdiff, see
+///   [`Difficulty::bdiff_1`]): floats are converted to targets with `target = floor(diff_1_target /
+///   difficulty)`, so a target is never easier than the requested difficulty. Targets are
+///   converted to floats by rounding the exact quotient `diff_1_target / target` to the nearest
+///   `f64`. Any difficulty below `2^160` survives a float -> target -> float round trip unchanged.
+/// - expected work ([`Work`]): the expected number of hashes needed to find a hash below the
+///   target, `2^256 / (target + 1)`, as used by Bitcoin Core to compute chain work. Work values
+///   can be summed to compute the value of a set of shares.
+///
+/// Ordering follows difficulty: a `Difficulty` with a lower target compares as greater.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Difficulty(Target);
+
+impl Difficulty {
+    /// Pool difficulty (pdiff) 1.
+    ///
+    /// Its target is `0x00000000ffffffffffffffffffffffffffffffffffffffffffffffffffffffff`, the
+    /// convention used by most pools and SV1 firmware for share difficulty.
+    pub fn pdiff_1() -> Self {
+        Self(Target::from_be_bytes(PDIFF_1_TARGET))
+    }
+
+    /// Bitcoin difficulty (bdiff) 1.
+    ///
+    /// Its target is `0x00000000ffff0000000000000000000000000000000000000000000000000000`, encoded
+    /// by the compact `nbits` value `0x1d00ffff`. This is the convention used by Bitcoin Core and
+    /// by [`Target::difficulty_float`].
+    pub fn bdiff_1() -> Self {
+        Self(Target::MAX)
+    }
+
+    /// Creates a `Difficulty` from a [`Target`].
+    pub fn from_target(target: Target) -> Self {
+        Self(target)
+    }
+
+    /// Returns the [`Target`] of this difficulty.
+    pub fn to_target(self) -> Target {
+        self.0
+    }
+
+    /// Creates a `Difficulty` from a compact `nbits` value, as found in block headers.
+    pub fn from_compact(nbits: u32) -> Self {
+        Self(Target::from_compact(CompactTarget::from_consensus(nbits)))
+    }
+
+    /// Returns the compact `nbits` encoding of the target.
+    ///
+    /// The target is truncated to fit the 24-bit mantissa of the compact encoding, so the
+    /// resulting target is equal or lower (harder) than the original one.
+    pub fn to_compact_lossy(self) -> u32 {
+        self.0.to_compact_lossy().to_consensus()
+    }
+
+    /// Creates a `Difficulty` from a pool difficulty (pdiff) value.
+    pub fn from_pdiff(pdiff: f64) -> Result<Self, DifficultyError> {
+        difficulty_to_target(Self::pdiff_1().0, pdiff).map(Self)
+    }
+
+    /// Returns the pool difficulty (pdiff) of this target.
+    ///
+    /// Returns `f64::INFINITY` for a zero target.
+    pub fn to_pdiff(self) -> f64 {
+        target_to_difficulty(Self::pdiff_1().0, self.0)
+    }
+
+    /// Creates a `Difficulty` from a bitcoin difficulty (bdiff) value.
+    pub fn from_bdiff(bdiff: f64) -> Result<Self, DifficultyError> {
+        difficulty_to_target(Self::bdiff_1().0, bdiff).map(Self)
+    }
+
+    /// Returns the bitcoin difficulty (bdiff) of this target.
+    ///
+    /// Returns `f64::INFINITY` for a zero target.
+    pub fn to_bdiff(self) -> f64 {
+        target_to_difficulty(Self::bdiff_1().0, self.0)
+    }
+
+    /// Creates a `Difficulty` from the expected number of hashes per share.
+    ///
+    /// This is the inverse of [`Difficulty::to_work`], up to integer rounding.
+    pub fn from_work(work: Work) -> Self {
+        Self(work.to_target())
+    }
+
+    /// Returns the expected number of hashes needed to find a hash meeting this target.
+    ///
+    /// This is the value a share at this difficulty is worth in hashes.
+    pub fn to_work(self) -> Work {
+        self.0.to_work()
+    }
+
+    /// Returns the difficulty achieved by a block or share hash.
+    ///
+    /// This is the difficulty of the highest target the hash meets, and can be compared against
+    /// the difficulty of a job to tell how much better than required a share is.
+    pub fn from_hash(hash: BlockHash) -> Self {
+        Self(Target::from_le_bytes(hash.to_byte_array()))
+    }
+}
+
+impl From<Target> for Difficulty {
+    fn from(target: Target) -> Self {
+        Self(target)
+    }
+}
+
+impl From<Difficulty> for Target {
+    fn from(difficulty: Difficulty) -> Self {
+        difficulty.0
+    }
+}
+
+impl PartialOrd for Difficulty {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Difficulty {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // a lower target means a higher difficulty
+        other.0.cmp(&self.0)
+    }
+}
+
+fn target_to_u512(target: Target) -> U512 {
+    U512::from(U256Primitive::from_big_endian(&target.to_be_bytes()))
+}
+
+// Computes `floor(diff_1_target / difficulty)` exactly.
+fn difficulty_to_target(diff_1_target: Target, difficulty: f64) -> Result<Target, DifficultyError> {
+    if !difficulty.is_finite() {
+        return Err(DifficultyError::NotFinite);
+    }
+    if difficulty <= 0.0 {
+        return Err(DifficultyError::NotPositive);
+    }
+
+    // decompose the float into `mantissa * 2^exponent`, which is exact
+    let bits = difficulty.to_bits();
+    let biased_exponent = ((bits >> 52) & 0x7ff) as i32;
+    let fraction = bits & ((1 << 52) - 1);
+    let (mantissa, exponent) = match biased_exponent {
+        0 => (fraction, -1074),
+        _ => (fraction | (1 << 52), biased_exponent - 1075),
+    };
+
+    let diff_1_target = target_to_u512(diff_1_target);
+    let mantissa = U512::from(mantissa);
+    let target = if exponent >= 0 {
+        // the divisor is at least 2^256, larger than any diff 1 target
+        if exponent >= 256 {
+            return Err(DifficultyError::TargetUnderflow);
+        }
+        diff_1_target / (mantissa << exponent as usize)
+    } else {
+        // diff 1 targets are in [2^223, 2^224) and the mantissa is below 2^53: from this shift on,
+        // the quotient is way above 2^256 and the shifted numerator would not fit in 512 bits
+        let shift = exponent.unsigned_abs() as usize;
+        if shift >= 288 {
+            return Err(DifficultyError::TargetOverflow);
+        }
+        (diff_1_target << shift) / mantissa
+    };
+
+    if target.is_zero() {
+        return Err(DifficultyError::TargetUnderflow);
+    }
+    let target = U256Primitive::try_from(target).map_err(|_| DifficultyError::TargetOverflow)?;
+    Ok(Target::from_be_bytes(target.to_big_endian()))
+}
+
+// Computes `diff_1_target / target`, rounded to the nearest `f64`.
+fn target_to_difficulty(diff_1_target: Target, target: Target) -> f64 {
+    if target == Target::ZERO {
+        return f64::INFINITY;
+    }
+
+    // scale the numerator by 2^256 so that the integer quotient keeps all significant bits:
+    // diff 1 targets are at least 2^223, so the quotient is always at least 2^223
+    let (quotient, remainder) =
+        (target_to_u512(diff_1_target) << 256).div_mod(target_to_u512(target));
+
+    // keep the 64 most significant bits, folding every discarded bit (and the remainder) into
+    // the lowest one, so that the conversion to f64 rounds exactly as the full quotient would
+    let shift = quotient.bits() - 64;
+    let mut significand = (quotient >> shift).low_u64();
+    let discarded = quotient ^ ((quotient >> shift) << shift);
+    if !discarded.is_zero() || !remainder.is_zero() {
+        significand |= 1;
+    }
+
+    // scaling by a power of two is exact, and the result is always in the normal f64 range
+    let scale_exponent = shift as i64 - 256;
+    let scale = f64::from_bits(((scale_exponent + 1023) as u64) << 52);
+    significand as f64 * scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_difficulty_one() {
+        assert_eq!(Difficulty::pdiff_1().to_pdiff(), 1.0);
+        assert_eq!(Difficulty::bdiff_1().to_bdiff(), 1.0);
+        assert_eq!(Difficulty::bdiff_1().to_compact_lossy(), 0x1d00ffff);
+        assert_eq!(Difficulty::from_bdiff(1.0).unwrap(), Difficulty::bdiff_1());
+        assert_eq!(Difficulty::from_pdiff(1.0).unwrap(), Difficulty::pdiff_1());
+
+        // pdiff 1 is slightly easier than bdiff 1: 0xffff00...00 / 0xffff...ff
+        let bdiff_of_pdiff_1 = Difficulty::pdiff_1().to_bdiff();
+        assert_eq!(bdiff_of_pdiff_1, 65535.0 / 65536.0);
+        assert!(Difficulty::pdiff_1() < Difficulty::bdiff_1());
+    }
+
+    #[test]
+    fn test_float_round_trip() {
+        for difficulty in [0.5, 1.0, 1024.0, 65536.5, 123_456.789, 1e12, 1.5e18] {
+            let pdiff = Difficulty::from_pdiff(difficulty).unwrap();
+            assert_eq!(pdiff.to_pdiff(), difficulty);
+            let bdiff = Difficulty::from_bdiff(difficulty).unwrap();
+            assert_eq!(bdiff.to_bdiff(), difficulty);
+        }
+    }
+
+    #[test]
+    fn test_float_to_target_rounds_down() {
+        // 0xffffffff...ff / 3 is exact, 0xffffffff...ff / 7 is not
+        let pdiff_3 = Difficulty::from_pdiff(3.0).unwrap().to_target();
+        let mut expected = [0x55; 32];
+        expected[..4].copy_from_slice(&[0; 4]);
+        assert_eq!(pdiff_3, Target::from_be_bytes(expected));
+
+        let pdiff_7 = U256Primitive::from_big_endian(
+            &Difficulty::from_pdiff(7.0)
+                .unwrap()
+                .to_target()
+                .to_be_bytes(),
+        );
+        let pdiff_1 = U256Primitive::from_big_endian(&PDIFF_1_TARGET);
+        assert_eq!(pdiff_7, pdiff_1 / 7);
+    }
+
+    #[test]
+    fn test_network_difficulty() {
+        // network target: 000000000000d7c0000000000000000000000000000000000000000000000000
+        let nbits = 453040064;
+        let difficulty = Difficulty::from_compact(nbits);
+        assert_eq!(difficulty.to_compact_lossy(), nbits);
+        let target = Target::from_compact(CompactTarget::from_consensus(nbits));
+        assert_eq!(difficulty.to_target(), target);
+        let bdiff = difficulty.to_bdiff();
+        assert!((bdiff - target.difficulty_float()).abs() / bdiff < 1e-12);
+    }
+
+    #[test]
+    fn test_work() {
+        // (2^224 - 1) + 1 = 2^224, so pdiff 1 is worth exactly 2^32 hashes
+        let mut expected = [0; 32];
+        expected[27] = 1;
+        let work = Difficulty::pdiff_1().to_work();
+        assert_eq!(work, Work::from_be_bytes(expected));
+
+        let double = Difficulty::from_pdiff(2.0).unwrap().to_work();
+        assert_eq!(double, work + work);
+        assert_eq!(Difficulty::from_work(work).to_work(), work);
+    }
+
+    #[test]
+    fn test_share_hash_difficulty() {
+        // this hash (0x000000000faaaa...) meets pdiff 16 (0x000000000fffffff...) but not pdiff 17
+        // (0x000000000f0f0f0f...)
+        let mut hash = [0xaa; 32];
+        hash[27..].copy_from_slice(&[0x0f, 0, 0, 0, 0]);
+        let achieved = Difficulty::from_hash(BlockHash::from_byte_array(hash));
+        assert!(achieved >= Difficulty::from_pdiff(16.0).unwrap());
+        assert!(achieved < Difficulty::from_pdiff(17.0).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_difficulty() {
+        assert_eq!(
+            Difficulty::from_pdiff(f64::NAN),
+            Err(DifficultyError::NotFinite)
+        );
+        assert_eq!(
+            Difficulty::from_pdiff(f64::INFINITY),
+            Err(DifficultyError::NotFinite)
+        );
+        assert_eq!(
+            Difficulty::from_pdiff(0.0),
+            Err(DifficultyError::NotPositive)
+        );
+        assert_eq!(
+            Difficulty::from_pdiff(-1.0),
+            Err(DifficultyError::NotPositive)
+        );
+        assert_eq!(
+            Difficulty::from_pdiff(1e-20),
+            Err(DifficultyError::TargetOverflow)
+        );
+        assert_eq!(
+            Difficulty::from_pdiff(f64::MIN_POSITIVE),
+            Err(DifficultyError::TargetOverflow)
+        );
+        assert_eq!(
+            Difficulty::from_pdiff(1e80),
+            Err(DifficultyError::TargetUnderflow)
+        );
+        assert_eq!(
+            Difficulty::from_target(Target::ZERO).to_pdiff(),
+            f64::INFINITY
+        );
+    }
+}
@@ -7,7 +7,7 @@
 //! message types with fixed structures.
 
 use alloc::{fmt, vec::Vec};
-use binary_sv2::{self, Deserialize, Seq064K, Serialize};
+use binary_sv2::{self, Deserialize, Seq064K, Serialize, ValidateFields};
 
 /// Extension type for Extensions Negotiation
 pub const EXTENSION_TYPE: u16 = 0x0001;
@@ -30,7 +30,8 @@ pub const CHANNEL_BIT_REQUEST_EXTENSIONS_ERROR: bool = false;
 ///
 /// Clients MUST NOT use any features from extensions that are not confirmed as
 /// supported by the server.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RequestExtensions<'decoder> {
     /// Unique identifier for pairing request/response.
     ///
@@ -60,7 +61,8 @@ impl fmt::Display for RequestExtensions<'_> {
 /// This message is sent in response to a RequestExtensions message to indicate
 /// which of the requested extensions the server supports and will enable for
 /// this connection.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RequestExtensionsSuccess<'decoder> {
     /// Unique identifier for pairing request/response.
     ///
@@ -90,7 +92,8 @@ impl fmt::Display for RequestExtensionsSuccess<'_> {
 /// This message is sent in response to a RequestExtensions message when the server
 /// cannot support some or all of the requested extensions, or when the server requires
 /// extensions that were not requested by the client.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RequestExtensionsError<'decoder> {
     /// Unique identifier for pairing request/response.
     ///
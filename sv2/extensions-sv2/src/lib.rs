@@ -8,12 +8,16 @@
 //!   which optional extensions are supported during connection setup.
 //! - **Worker-Specific Hashrate Tracking** (extension_type=0x0002): Enables tracking per-worker hashrates
 //!   within extended channels via TLV fields.
+//! - **Share Reject Detail** (extension_type=0x4003): Explains share rejections with TLV fields
+//!   appended to `SubmitShares.Error`. Not part of the spec yet, so it uses an extension type of
+//!   the experimental range (0x4000-0x7FFF).
 //!
 //! ## Architecture
 //!
 //! The crate is organized into:
 //! - `extensions_negotiation`: Extension negotiation protocol
 //! - `worker_specific_hashrate_tracking`: Worker-Specific Hashrate Tracking extension
+//! - `share_reject_detail`: Share Reject Detail extension
 //!
 //! TLV encoding/decoding utilities are provided by the `parsers_sv2` crate.
 //!
@@ -31,6 +35,9 @@ pub mod extensions_negotiation;
 // Worker-Specific Hashrate Tracking (0x0002)
 pub mod worker_specific_hashrate_tracking;
 
+// Share Reject Detail (0x4003)
+pub mod share_reject_detail;
+
 // Re-export commonly used items from extensions_negotiation
 pub use extensions_negotiation::{
     RequestExtensions, RequestExtensionsError, RequestExtensionsSuccess,
@@ -45,3 +52,13 @@ pub use worker_specific_hashrate_tracking::{
     UserIdentity, EXTENSION_TYPE as EXTENSION_TYPE_WORKER_HASHRATE_TRACKING,
     FIELD_TYPE_USER_IDENTITY as TLV_FIELD_TYPE_USER_IDENTITY, MAX_USER_IDENTITY_LENGTH,
 };
+
+// Re-export commonly used items from share_reject_detail
+pub use share_reject_detail::{
+    JobState, RejectReason, ShareRejectDetail,
+    EXTENSION_TYPE as EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+    FIELD_TYPE_EXPECTED_TARGET as TLV_FIELD_TYPE_EXPECTED_TARGET,
+    FIELD_TYPE_JOB_STATE as TLV_FIELD_TYPE_JOB_STATE,
+    FIELD_TYPE_REJECT_REASON as TLV_FIELD_TYPE_REJECT_REASON,
+    FIELD_TYPE_SHARE_HASH as TLV_FIELD_TYPE_SHARE_HASH,
+};
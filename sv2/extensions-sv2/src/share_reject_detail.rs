@@ -0,0 +1,199 @@
+//! Share Reject Detail (extension_type=0x4003)
+//!
+//! This extension appends TLV-encoded diagnostics to `SubmitShares.Error` messages, telling miners
+//! why a share was rejected: a numeric reason, the target the share had to meet, the state of the
+//! job it was submitted for, and the hash computed by the server.
+//!
+//! The extension is not specified in the sv2-spec yet, so its extension type is taken from the
+//! experimental range (0x4000-0x7FFF), which is reserved for extensions under development. It will
+//! move to the extension type assigned by the spec once it is standardized.
+
+use alloc::fmt;
+
+/// Extension type for Share Reject Detail, in the experimental range.
+pub const EXTENSION_TYPE: u16 = 0x4003;
+
+/// TLV field type for the reject reason, a `U8`.
+pub const FIELD_TYPE_REJECT_REASON: u8 = 0x01;
+
+/// TLV field type for the target the share had to meet, a `U256`.
+pub const FIELD_TYPE_EXPECTED_TARGET: u8 = 0x02;
+
+/// TLV field type for the state of the job of the share, a `U8`.
+pub const FIELD_TYPE_JOB_STATE: u8 = 0x03;
+
+/// TLV field type for the hash of the share computed by the server, a `U256`.
+pub const FIELD_TYPE_SHARE_HASH: u8 = 0x04;
+
+/// Why a share was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    /// The share is invalid for unspecified reasons.
+    Invalid,
+    /// The share is for a job of a past chain tip.
+    Stale,
+    /// The share is for a past job no longer retained by the server.
+    EvictedJob,
+    /// The job ID does not refer to any known job of the channel.
+    InvalidJobId,
+    /// The share hash does not meet the target.
+    DoesNotMeetTarget,
+    /// The share rolls the version while version rolling is not allowed.
+    VersionRollingNotAllowed,
+    /// The share was already submitted.
+    DuplicateShare,
+    /// The coinbase transaction of the share is invalid.
+    InvalidCoinbase,
+    /// The server has no chain tip to validate the share against.
+    NoChainTip,
+    /// The extranonce size differs from the rollable extranonce size of the channel.
+    BadExtranonceSize,
+    /// A reason not known to this implementation.
+    Unknown(u8),
+}
+
+impl RejectReason {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0x01 => RejectReason::Invalid,
+            0x02 => RejectReason::Stale,
+            0x03 => RejectReason::EvictedJob,
+            0x04 => RejectReason::InvalidJobId,
+            0x05 => RejectReason::DoesNotMeetTarget,
+            0x06 => RejectReason::VersionRollingNotAllowed,
+            0x07 => RejectReason::DuplicateShare,
+            0x08 => RejectReason::InvalidCoinbase,
+            0x09 => RejectReason::NoChainTip,
+            0x0a => RejectReason::BadExtranonceSize,
+            value => RejectReason::Unknown(value),
+        }
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            RejectReason::Invalid => 0x01,
+            RejectReason::Stale => 0x02,
+            RejectReason::EvictedJob => 0x03,
+            RejectReason::InvalidJobId => 0x04,
+            RejectReason::DoesNotMeetTarget => 0x05,
+            RejectReason::VersionRollingNotAllowed => 0x06,
+            RejectReason::DuplicateShare => 0x07,
+            RejectReason::InvalidCoinbase => 0x08,
+            RejectReason::NoChainTip => 0x09,
+            RejectReason::BadExtranonceSize => 0x0a,
+            RejectReason::Unknown(value) => value,
+        }
+    }
+}
+
+/// State of the job a rejected share was submitted for, on the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JobState {
+    /// The job is unknown, or its state was not determined.
+    #[default]
+    Unknown,
+    /// The job is the active job of the channel.
+    Active,
+    /// The job is a past job of the current chain tip.
+    Past,
+    /// The job is for a past chain tip.
+    Stale,
+}
+
+impl JobState {
+    /// Returns the job state encoded as `value`, unknown values being [`JobState::Unknown`].
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0x01 => JobState::Active,
+            0x02 => JobState::Past,
+            0x03 => JobState::Stale,
+            _ => JobState::Unknown,
+        }
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            JobState::Unknown => 0x00,
+            JobState::Active => 0x01,
+            JobState::Past => 0x02,
+            JobState::Stale => 0x03,
+        }
+    }
+}
+
+/// Diagnostics of a rejected share, appended to `SubmitShares.Error` as TLV fields when the
+/// Share Reject Detail extension (0x4003) is negotiated.
+///
+/// Only the reason is required, the other fields being sent when known to the server. Targets
+/// and hashes are 32 bytes in little endian, as `U256` fields. The TLV fields are encoded and
+/// decoded by `parsers_sv2::share_reject_detail_to_tlvs` and
+/// `parsers_sv2::share_reject_detail_from_tlvs`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareRejectDetail {
+    pub reason: RejectReason,
+    pub expected_target: Option<[u8; 32]>,
+    pub job_state: Option<JobState>,
+    pub share_hash: Option<[u8; 32]>,
+}
+
+impl ShareRejectDetail {
+    pub fn new(reason: RejectReason) -> Self {
+        Self {
+            reason,
+            expected_target: None,
+            job_state: None,
+            share_hash: None,
+        }
+    }
+
+    pub fn with_expected_target(mut self, expected_target: [u8; 32]) -> Self {
+        self.expected_target = Some(expected_target);
+        self
+    }
+
+    pub fn with_job_state(mut self, job_state: JobState) -> Self {
+        self.job_state = Some(job_state);
+        self
+    }
+
+    pub fn with_share_hash(mut self, share_hash: [u8; 32]) -> Self {
+        self.share_hash = Some(share_hash);
+        self
+    }
+}
+
+impl fmt::Display for ShareRejectDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ShareRejectDetail(reason={:?}", self.reason)?;
+        if let Some(job_state) = self.job_state {
+            write!(f, ", job_state={:?}", job_state)?;
+        }
+        if let Some(expected_target) = self.expected_target {
+            write!(f, ", expected_target=")?;
+            // printed in big endian, as targets are usually shown
+            for byte in expected_target.iter().rev() {
+                write!(f, "{:02x}", byte)?;
+            }
+        }
+        if let Some(share_hash) = self.share_hash {
+            write!(f, ", share_hash=")?;
+            for byte in share_hash.iter().rev() {
+                write!(f, "{:02x}", byte)?;
+            }
+        }
+        write!(f, ")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reject_reason_round_trip() {
+        for value in 0..=u8::MAX {
+            assert_eq!(RejectReason::from_u8(value).to_u8(), value);
+        }
+        assert_eq!(RejectReason::from_u8(0x05), RejectReason::DoesNotMeetTarget);
+    }
+}
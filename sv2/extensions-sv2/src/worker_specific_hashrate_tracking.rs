@@ -8,6 +8,7 @@ use alloc::{
     string::{String, ToString},
     vec::Vec,
 };
+use binary_sv2::{FieldViolation, ValidateFields};
 
 /// Maximum length for user identity in bytes as per the spec.
 pub const MAX_USER_IDENTITY_LENGTH: usize = 32;
@@ -90,6 +91,18 @@ impl UserIdentity {
     }
 }
 
+impl ValidateFields for UserIdentity {
+    fn collect_violations(&self, violations: &mut Vec<FieldViolation>) {
+        if self.len() > MAX_USER_IDENTITY_LENGTH {
+            violations.push(FieldViolation::TooLong {
+                field: String::new(),
+                length: self.len(),
+                max_length: MAX_USER_IDENTITY_LENGTH,
+            });
+        }
+    }
+}
+
 impl fmt::Display for UserIdentity {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "UserIdentity({})", self.as_string_or_hex())
@@ -167,6 +180,26 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_validate_fields() {
+        let identity = UserIdentity::new("Worker_123").unwrap();
+        assert!(identity.validate_fields().is_ok());
+
+        let too_long = UserIdentity {
+            user_identity: vec![b'x'; MAX_USER_IDENTITY_LENGTH + 1],
+        };
+        let mut violations = Vec::new();
+        too_long.collect_field_violations("user_identity", &mut violations);
+        assert_eq!(
+            violations,
+            vec![FieldViolation::TooLong {
+                field: "user_identity".to_string(),
+                length: 33,
+                max_length: MAX_USER_IDENTITY_LENGTH,
+            }]
+        );
+    }
+
     #[test]
     fn test_display() {
         let identity = UserIdentity::new("TestWorker").unwrap();
@@ -67,10 +67,10 @@ impl Header {
         })
     }
 
-    // Get the payload length
+    /// Get the [`Header`] payload length, not including the MACs of encrypted frames.
     #[allow(clippy::len_without_is_empty)]
     #[inline]
-    pub(crate) fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         let inner: u32 = self.msg_length.into();
         inner as usize
     }
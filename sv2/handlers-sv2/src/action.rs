@@ -0,0 +1,454 @@
+use binary_sv2::{Error as BinarySv2Error, Str0255};
+use common_messages_sv2::{Reconnect, SetupConnectionError, SetupConnectionErrorCode};
+use job_declaration_sv2::{DeclareMiningJobError, DeclareMiningJobErrorCode};
+use mining_sv2::{
+    CloseChannel, OpenMiningChannelError, OpenMiningChannelErrorCode, SetCustomMiningJobError,
+    SetCustomMiningJobErrorCode, SubmitSharesError, SubmitSharesErrorCode, UpdateChannelError,
+    UpdateChannelErrorCode,
+};
+use parsers_sv2::{AnyMessage, CommonMessages, JobDeclaration, Mining, TemplateDistribution};
+use template_distribution_sv2::{RequestTransactionDataError, RequestTransactionDataErrorCode};
+
+/// An error message answering a request rejected by a handler.
+///
+/// `error_code` is the typed error code of the message, e.g. [`SubmitSharesErrorCode::StaleShare`]
+/// for `SubmitSharesError`, codes not defined by the spec being `Unknown`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorResponse {
+    /// Answered with `SetupConnection.Error`, after which the connection is closed.
+    SetupConnection {
+        flags: u32,
+        error_code: SetupConnectionErrorCode,
+    },
+    /// Answered with `OpenMiningChannel.Error`.
+    OpenMiningChannel {
+        request_id: u32,
+        error_code: OpenMiningChannelErrorCode,
+    },
+    /// Answered with `UpdateChannel.Error`.
+    UpdateChannel {
+        channel_id: u32,
+        error_code: UpdateChannelErrorCode,
+    },
+    /// Answered with `SubmitShares.Error`.
+    SubmitShares {
+        channel_id: u32,
+        sequence_number: u32,
+        error_code: SubmitSharesErrorCode,
+    },
+    /// Answered with `SetCustomMiningJob.Error`.
+    SetCustomMiningJob {
+        channel_id: u32,
+        request_id: u32,
+        error_code: SetCustomMiningJobErrorCode,
+    },
+    /// Answered with `DeclareMiningJob.Error`, also used when the transactions asked for with
+    /// `ProvideMissingTransactions` can't be provided or don't match the declared job.
+    DeclareMiningJob {
+        request_id: u32,
+        error_code: DeclareMiningJobErrorCode,
+        error_details: Vec<u8>,
+    },
+    /// Answered with `RequestTransactionData.Error`.
+    RequestTransactionData {
+        template_id: u64,
+        error_code: RequestTransactionDataErrorCode,
+    },
+}
+
+impl ErrorResponse {
+    pub fn invalid_channel_id_for_shares(channel_id: u32, sequence_number: u32) -> Self {
+        Self::SubmitShares {
+            channel_id,
+            sequence_number,
+            error_code: SubmitSharesErrorCode::InvalidChannelId,
+        }
+    }
+
+    pub fn stale_share(channel_id: u32, sequence_number: u32) -> Self {
+        Self::SubmitShares {
+            channel_id,
+            sequence_number,
+            error_code: SubmitSharesErrorCode::StaleShare,
+        }
+    }
+
+    pub fn difficulty_too_low(channel_id: u32, sequence_number: u32) -> Self {
+        Self::SubmitShares {
+            channel_id,
+            sequence_number,
+            error_code: SubmitSharesErrorCode::DifficultyTooLow,
+        }
+    }
+
+    pub fn invalid_job_id(channel_id: u32, sequence_number: u32) -> Self {
+        Self::SubmitShares {
+            channel_id,
+            sequence_number,
+            error_code: SubmitSharesErrorCode::InvalidJobId,
+        }
+    }
+
+    pub fn unknown_user(request_id: u32) -> Self {
+        Self::OpenMiningChannel {
+            request_id,
+            error_code: OpenMiningChannelErrorCode::UnknownUser,
+        }
+    }
+
+    pub fn open_channel_max_target_out_of_range(request_id: u32) -> Self {
+        Self::OpenMiningChannel {
+            request_id,
+            error_code: OpenMiningChannelErrorCode::MaxTargetOutOfRange,
+        }
+    }
+
+    pub fn update_channel_max_target_out_of_range(channel_id: u32) -> Self {
+        Self::UpdateChannel {
+            channel_id,
+            error_code: UpdateChannelErrorCode::MaxTargetOutOfRange,
+        }
+    }
+
+    pub fn update_channel_invalid_channel_id(channel_id: u32) -> Self {
+        Self::UpdateChannel {
+            channel_id,
+            error_code: UpdateChannelErrorCode::InvalidChannelId,
+        }
+    }
+
+    pub fn invalid_mining_job_token(request_id: u32) -> Self {
+        Self::DeclareMiningJob {
+            request_id,
+            error_code: DeclareMiningJobErrorCode::InvalidMiningJobToken,
+            error_details: Vec::new(),
+        }
+    }
+
+    /// Rejects a declared job whose missing transactions were not provided, or whose provided
+    /// transactions don't match its `wtxid_list`.
+    pub fn missing_transactions_not_provided(request_id: u32) -> Self {
+        Self::DeclareMiningJob {
+            request_id,
+            error_code: DeclareMiningJobErrorCode::InvalidJobParamValue("wtxid_list".to_string()),
+            error_details: Vec::new(),
+        }
+    }
+
+    pub fn template_id_not_found(template_id: u64) -> Self {
+        Self::RequestTransactionData {
+            template_id,
+            error_code: RequestTransactionDataErrorCode::TemplateIdNotFound,
+        }
+    }
+
+    pub fn stale_template_id(template_id: u64) -> Self {
+        Self::RequestTransactionData {
+            template_id,
+            error_code: RequestTransactionDataErrorCode::StaleTemplateId,
+        }
+    }
+
+    /// Returns `true` if the connection must be closed once the response is sent.
+    pub fn closes_connection(&self) -> bool {
+        matches!(self, ErrorResponse::SetupConnection { .. })
+    }
+
+    /// Builds the error message to send.
+    ///
+    /// Fails if an `Unknown` error code is longer than 255 bytes, or if `error_details` is longer
+    /// than 65535 bytes.
+    pub fn to_message(&self) -> Result<AnyMessage<'static>, BinarySv2Error> {
+        let message = match self {
+            ErrorResponse::SetupConnection { flags, error_code } => {
+                AnyMessage::Common(CommonMessages::SetupConnectionError(SetupConnectionError {
+                    flags: *flags,
+                    error_code: error_code.clone().try_into()?,
+                }))
+            }
+            ErrorResponse::OpenMiningChannel {
+                request_id,
+                error_code,
+            } => AnyMessage::Mining(Mining::OpenMiningChannelError(OpenMiningChannelError {
+                request_id: *request_id,
+                error_code: error_code.clone().try_into()?,
+            })),
+            ErrorResponse::UpdateChannel {
+                channel_id,
+                error_code,
+            } => AnyMessage::Mining(Mining::UpdateChannelError(UpdateChannelError {
+                channel_id: *channel_id,
+                error_code: error_code.clone().try_into()?,
+            })),
+            ErrorResponse::SubmitShares {
+                channel_id,
+                sequence_number,
+                error_code,
+            } => AnyMessage::Mining(Mining::SubmitSharesError(SubmitSharesError {
+                channel_id: *channel_id,
+                sequence_number: *sequence_number,
+                error_code: error_code.clone().try_into()?,
+            })),
+            ErrorResponse::SetCustomMiningJob {
+                channel_id,
+                request_id,
+                error_code,
+            } => AnyMessage::Mining(Mining::SetCustomMiningJobError(SetCustomMiningJobError {
+                channel_id: *channel_id,
+                request_id: *request_id,
+                error_code: error_code.clone().try_into()?,
+            })),
+            ErrorResponse::DeclareMiningJob {
+                request_id,
+                error_code,
+                error_details,
+            } => AnyMessage::JobDeclaration(JobDeclaration::DeclareMiningJobError(
+                DeclareMiningJobError {
+                    request_id: *request_id,
+                    error_code: error_code.clone().try_into()?,
+                    error_details: error_details.clone().try_into()?,
+                },
+            )),
+            ErrorResponse::RequestTransactionData {
+                template_id,
+                error_code,
+            } => AnyMessage::TemplateDistribution(
+                TemplateDistribution::RequestTransactionDataError(RequestTransactionDataError {
+                    template_id: *template_id,
+                    error_code: error_code.clone().try_into()?,
+                }),
+            ),
+        };
+        Ok(message)
+    }
+}
+
+/// What the owner of a connection does after a handler returned an error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandlerAction {
+    /// Answers the rejected request with an error message.
+    Reply(ErrorResponse),
+    /// Closes a channel with `CloseChannel`, keeping the connection open.
+    CloseChannel {
+        channel_id: u32,
+        reason_code: String,
+    },
+    /// Sends `Reconnect` and closes the connection.
+    Reconnect { new_host: String, new_port: u16 },
+    /// Drops the connection without sending anything.
+    Disconnect,
+}
+
+impl HandlerAction {
+    /// Returns `true` if the connection must be closed once the action is carried out.
+    pub fn closes_connection(&self) -> bool {
+        match self {
+            HandlerAction::Reply(response) => response.closes_connection(),
+            HandlerAction::CloseChannel { .. } => false,
+            HandlerAction::Reconnect { .. } | HandlerAction::Disconnect => true,
+        }
+    }
+
+    /// Builds the message to send to the peer, if any.
+    ///
+    /// Fails if a string field is longer than 255 bytes.
+    pub fn to_message(&self) -> Result<Option<AnyMessage<'static>>, BinarySv2Error> {
+        match self {
+            HandlerAction::Reply(response) => response.to_message().map(Some),
+            HandlerAction::CloseChannel {
+                channel_id,
+                reason_code,
+            } => Ok(Some(AnyMessage::Mining(Mining::CloseChannel(
+                CloseChannel {
+                    channel_id: *channel_id,
+                    reason_code: to_str0255(reason_code)?,
+                },
+            )))),
+            HandlerAction::Reconnect { new_host, new_port } => Ok(Some(AnyMessage::Common(
+                CommonMessages::Reconnect(Reconnect {
+                    new_host: to_str0255(new_host)?,
+                    new_port: *new_port,
+                }),
+            ))),
+            HandlerAction::Disconnect => Ok(None),
+        }
+    }
+}
+
+fn to_str0255(value: &str) -> Result<Str0255<'static>, BinarySv2Error> {
+    value.to_string().try_into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_response_to_message() {
+        let response = ErrorResponse::stale_share(1, 42);
+        assert!(!response.closes_connection());
+        match response.to_message().unwrap() {
+            AnyMessage::Mining(Mining::SubmitSharesError(error)) => {
+                assert_eq!(error.channel_id, 1);
+                assert_eq!(error.sequence_number, 42);
+                assert_eq!(error.get_error_code(), SubmitSharesErrorCode::StaleShare);
+            }
+            message => panic!("unexpected message {message:?}"),
+        }
+
+        let response = ErrorResponse::SetupConnection {
+            flags: 0b10,
+            error_code: SetupConnectionErrorCode::UnsupportedFeatureFlags,
+        };
+        assert!(response.closes_connection());
+        match response.to_message().unwrap() {
+            AnyMessage::Common(CommonMessages::SetupConnectionError(error)) => {
+                assert_eq!(error.flags, 0b10);
+                assert_eq!(
+                    error.error_code.as_utf8_or_hex(),
+                    "unsupported-feature-flags"
+                );
+            }
+            message => panic!("unexpected message {message:?}"),
+        }
+    }
+
+    #[test]
+    fn test_job_declaration_error_response_to_message() {
+        match ErrorResponse::invalid_mining_job_token(3)
+            .to_message()
+            .unwrap()
+        {
+            AnyMessage::JobDeclaration(JobDeclaration::DeclareMiningJobError(error)) => {
+                assert_eq!(error.request_id, 3);
+                assert_eq!(
+                    error.get_error_code(),
+                    DeclareMiningJobErrorCode::InvalidMiningJobToken
+                );
+                assert!(error.error_details.inner_as_ref().is_empty());
+            }
+            message => panic!("unexpected message {message:?}"),
+        }
+
+        match ErrorResponse::missing_transactions_not_provided(4)
+            .to_message()
+            .unwrap()
+        {
+            AnyMessage::JobDeclaration(JobDeclaration::DeclareMiningJobError(error)) => {
+                assert_eq!(error.request_id, 4);
+                assert_eq!(
+                    error.error_code.as_utf8_or_hex(),
+                    "invalid-job-param-value-wtxid_list"
+                );
+            }
+            message => panic!("unexpected message {message:?}"),
+        }
+
+        let response = ErrorResponse::DeclareMiningJob {
+            request_id: 5,
+            error_code: DeclareMiningJobErrorCode::InvalidMiningJobToken,
+            error_details: vec![0; 65536],
+        };
+        assert!(response.to_message().is_err());
+    }
+
+    #[test]
+    fn test_template_distribution_error_response_to_message() {
+        let response = ErrorResponse::stale_template_id(9);
+        assert!(!response.closes_connection());
+        match response.to_message().unwrap() {
+            AnyMessage::TemplateDistribution(
+                TemplateDistribution::RequestTransactionDataError(error),
+            ) => {
+                assert_eq!(error.template_id, 9);
+                assert_eq!(
+                    error.get_error_code(),
+                    RequestTransactionDataErrorCode::StaleTemplateId
+                );
+            }
+            message => panic!("unexpected message {message:?}"),
+        }
+
+        match ErrorResponse::template_id_not_found(10)
+            .to_message()
+            .unwrap()
+        {
+            AnyMessage::TemplateDistribution(
+                TemplateDistribution::RequestTransactionDataError(error),
+            ) => {
+                assert_eq!(error.error_code.as_utf8_or_hex(), "template-id-not-found");
+            }
+            message => panic!("unexpected message {message:?}"),
+        }
+    }
+
+    #[test]
+    fn test_error_response_error_code_too_long() {
+        let response = ErrorResponse::SetCustomMiningJob {
+            channel_id: 1,
+            request_id: 2,
+            error_code: SetCustomMiningJobErrorCode::Unknown("x".repeat(256)),
+        };
+        assert!(response.to_message().is_err());
+        assert!(HandlerAction::Reply(response).to_message().is_err());
+    }
+
+    #[test]
+    fn test_action_closes_connection() {
+        assert!(
+            !HandlerAction::Reply(ErrorResponse::update_channel_invalid_channel_id(1))
+                .closes_connection()
+        );
+        assert!(HandlerAction::Reply(ErrorResponse::SetupConnection {
+            flags: 0,
+            error_code: SetupConnectionErrorCode::UnsupportedProtocol,
+        })
+        .closes_connection());
+        assert!(!HandlerAction::CloseChannel {
+            channel_id: 1,
+            reason_code: "".to_string(),
+        }
+        .closes_connection());
+        assert!(HandlerAction::Reconnect {
+            new_host: "pool.example".to_string(),
+            new_port: 3333,
+        }
+        .closes_connection());
+        assert!(HandlerAction::Disconnect.closes_connection());
+    }
+
+    #[test]
+    fn test_action_to_message() {
+        assert!(HandlerAction::Disconnect.to_message().unwrap().is_none());
+
+        let action = HandlerAction::CloseChannel {
+            channel_id: 7,
+            reason_code: "shutdown".to_string(),
+        };
+        match action.to_message().unwrap() {
+            Some(AnyMessage::Mining(Mining::CloseChannel(close_channel))) => {
+                assert_eq!(close_channel.channel_id, 7);
+                assert_eq!(close_channel.reason_code.as_utf8_or_hex(), "shutdown");
+            }
+            message => panic!("unexpected message {message:?}"),
+        }
+
+        let action = HandlerAction::Reconnect {
+            new_host: "pool.example".to_string(),
+            new_port: 3333,
+        };
+        match action.to_message().unwrap() {
+            Some(AnyMessage::Common(CommonMessages::Reconnect(reconnect))) => {
+                assert_eq!(reconnect.new_host.as_utf8_or_hex(), "pool.example");
+                assert_eq!(reconnect.new_port, 3333);
+            }
+            message => panic!("unexpected message {message:?}"),
+        }
+
+        let action = HandlerAction::Reconnect {
+            new_host: "x".repeat(256),
+            new_port: 3333,
+        };
+        assert!(action.to_message().is_err());
+    }
+}
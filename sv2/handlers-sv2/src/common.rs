@@ -4,7 +4,10 @@ use common_messages_sv2::{
 use framing_sv2::header::Header;
 use parsers_sv2::{parse_message_frame_with_tlvs, AnyMessage, CommonMessages, Tlv};
 
-use crate::error::HandlerErrorType;
+use crate::{
+    error::HandlerErrorType,
+    shared::{impl_async_for_shared, SharedFrames, SharedMessages},
+};
 
 /// Synchronous handler trait for processing common messages received from servers.
 ///
@@ -248,6 +251,110 @@ pub trait HandleCommonMessagesFromServerAsync {
     ) -> Result<(), Self::Error>;
 }
 
+/// Asynchronous `&self` handler trait for common messages received from servers.
+///
+/// Unlike the `Async` variant, handler methods take `&self` and the context of the connection the
+/// message came from, so that a single handler can process messages from many connections in
+/// parallel. State shared between connections lives in the handler, behind whatever
+/// synchronization fits it (fine-grained locks, actors, ...), while `Self::Context` holds the
+/// state of one connection and is mutated without locking.
+///
+/// ## TLV Extension Support
+///
+/// The `tlv_data` parameter in message handlers contains validated TLV fields if the message has
+/// extension data appended. TLV fields are only passed if they match negotiated extensions
+/// returned by `get_negotiated_extensions_with_server()`.
+#[trait_variant::make(Send)]
+pub trait HandleCommonMessagesFromServerShared: Sync {
+    type Error: HandlerErrorType;
+
+    /// State of a single connection, e.g. the extensions negotiated with the peer.
+    type Context: Send;
+
+    /// Returns the list of negotiated extension_types with a server.
+    ///
+    /// Return an empty Vec if no extensions have been negotiated.
+    fn get_negotiated_extensions_with_server(
+        &self,
+        ctx: &Self::Context,
+    ) -> Result<Vec<u16>, Self::Error>;
+
+    /// Handles a raw Common protocol message frame from a server.
+    ///
+    /// This method parses the raw frame, extracts any TLV extension data, and delegates
+    /// to `handle_common_message_from_server` with the parsed message and TLV fields.
+    async fn handle_common_message_frame_from_server(
+        &self,
+        ctx: &mut Self::Context,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedFrames { handler: self, ctx }
+                .handle_common_message_frame_from_server(None, header, payload)
+                .await
+        }
+    }
+
+    /// Handles a parsed common message from a server.
+    ///
+    /// The `tlv_fields` parameter contains parsed TLV fields if the message has extension
+    /// data appended. It will be `Some(&[Tlv])` when valid TLV data is present, or `None`
+    /// if no TLV data exists or validation fails. Each `Tlv` struct provides direct access to
+    /// `extension_type`, `field_type`, `length`, and `value`.
+    async fn handle_common_message_from_server(
+        &self,
+        ctx: &mut Self::Context,
+        message: CommonMessages<'_>,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedMessages { handler: self, ctx }
+                .handle_common_message_from_server(None, message, tlv_fields)
+                .await
+        }
+    }
+
+    async fn handle_setup_connection_success(
+        &self,
+        ctx: &mut Self::Context,
+        msg: SetupConnectionSuccess,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_setup_connection_error(
+        &self,
+        ctx: &mut Self::Context,
+        msg: SetupConnectionError,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_channel_endpoint_changed(
+        &self,
+        ctx: &mut Self::Context,
+        msg: ChannelEndpointChanged,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_reconnect(
+        &self,
+        ctx: &mut Self::Context,
+        msg: Reconnect,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+}
+
+impl_async_for_shared! {
+    HandleCommonMessagesFromServerShared => HandleCommonMessagesFromServerAsync {
+        async fn handle_common_message_from_server(CommonMessages<'_>);
+        fn get_negotiated_extensions_with_server(&self) -> Result<Vec<u16>, Self::Error>;
+        async fn handle_setup_connection_success(SetupConnectionSuccess);
+        async fn handle_setup_connection_error(SetupConnectionError<'_>);
+        async fn handle_channel_endpoint_changed(ChannelEndpointChanged);
+        async fn handle_reconnect(Reconnect<'_>);
+    }
+}
+
 /// Synchronous handler trait for processing common messages received from clients.
 ///
 /// The client ID identifies which client a message originated from.
@@ -449,3 +556,83 @@ pub trait HandleCommonMessagesFromClientAsync {
         tlv_fields: Option<&[Tlv]>,
     ) -> Result<(), Self::Error>;
 }
+
+/// Asynchronous `&self` handler trait for common messages received from clients.
+///
+/// Unlike the `Async` variant, handler methods take `&self` and the context of the connection the
+/// message came from, so that a single handler can process messages from many connections in
+/// parallel. State shared between connections lives in the handler, behind whatever
+/// synchronization fits it (fine-grained locks, actors, ...), while `Self::Context` holds the
+/// state of one connection and is mutated without locking.
+///
+/// ## TLV Extension Support
+///
+/// The `tlv_data` parameter in message handlers contains validated TLV fields if the message has
+/// extension data appended. TLV fields are only passed if they match negotiated extensions
+/// returned by `get_negotiated_extensions_with_client()`.
+#[trait_variant::make(Send)]
+pub trait HandleCommonMessagesFromClientShared: Sync {
+    type Error: HandlerErrorType;
+
+    /// State of a single connection, e.g. the extensions negotiated with the peer.
+    type Context: Send;
+
+    /// Returns the list of negotiated extension_types with a client.
+    ///
+    /// Return an empty Vec if no extensions have been negotiated.
+    fn get_negotiated_extensions_with_client(
+        &self,
+        ctx: &Self::Context,
+    ) -> Result<Vec<u16>, Self::Error>;
+
+    /// Handles a raw Common protocol message frame from a client.
+    ///
+    /// This method parses the raw frame, extracts any TLV extension data, and delegates
+    /// to `handle_common_message_from_client` with the parsed message and TLV fields.
+    async fn handle_common_message_frame_from_client(
+        &self,
+        ctx: &mut Self::Context,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedFrames { handler: self, ctx }
+                .handle_common_message_frame_from_client(None, header, payload)
+                .await
+        }
+    }
+
+    /// Handles a parsed common message from a client.
+    ///
+    /// The `tlv_fields` parameter contains parsed TLV fields if the message has extension
+    /// data appended. It will be `Some(&[Tlv])` when valid TLV data is present, or `None`
+    /// if no TLV data exists or validation fails. Each `Tlv` struct provides direct access to
+    /// `extension_type`, `field_type`, `length`, and `value`.
+    async fn handle_common_message_from_client(
+        &self,
+        ctx: &mut Self::Context,
+        message: CommonMessages<'_>,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedMessages { handler: self, ctx }
+                .handle_common_message_from_client(None, message, tlv_fields)
+                .await
+        }
+    }
+
+    async fn handle_setup_connection(
+        &self,
+        ctx: &mut Self::Context,
+        msg: SetupConnection,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+}
+
+impl_async_for_shared! {
+    HandleCommonMessagesFromClientShared => HandleCommonMessagesFromClientAsync {
+        async fn handle_common_message_from_client(CommonMessages<'_>);
+        fn get_negotiated_extensions_with_client(&self) -> Result<Vec<u16>, Self::Error>;
+        async fn handle_setup_connection(SetupConnection<'_>);
+    }
+}
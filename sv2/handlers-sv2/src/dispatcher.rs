@@ -0,0 +1,1754 @@
+use std::{future::Future, pin::Pin};
+
+use common_messages_sv2::Protocol;
+use framing_sv2::header::Header;
+use parsers_sv2::{
+    AnyMessage, CommonMessageTypes, JobDeclarationTypes, MiningTypes, ParserError,
+    TemplateDistributionTypes,
+};
+
+use crate::{
+    common::{
+        HandleCommonMessagesFromClientAsync, HandleCommonMessagesFromClientSync,
+        HandleCommonMessagesFromServerAsync, HandleCommonMessagesFromServerSync,
+    },
+    error::HandlerErrorType,
+    extensions::{
+        HandleExtensionsFromClientAsync, HandleExtensionsFromClientSync,
+        HandleExtensionsFromServerAsync, HandleExtensionsFromServerSync,
+    },
+    job_declaration::{
+        HandleJobDeclarationMessagesFromClientAsync, HandleJobDeclarationMessagesFromClientSync,
+        HandleJobDeclarationMessagesFromServerAsync, HandleJobDeclarationMessagesFromServerSync,
+    },
+    mining::{
+        HandleMiningMessagesFromClientAsync, HandleMiningMessagesFromClientSync,
+        HandleMiningMessagesFromServerAsync, HandleMiningMessagesFromServerSync,
+    },
+    session::Session,
+    template_distribution::{
+        HandleTemplateDistributionMessagesFromClientAsync,
+        HandleTemplateDistributionMessagesFromClientSync,
+        HandleTemplateDistributionMessagesFromServerAsync,
+        HandleTemplateDistributionMessagesFromServerSync,
+    },
+};
+
+/// The handler a frame is routed to, as told by its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameRoute {
+    Common,
+    Mining,
+    JobDeclaration,
+    TemplateDistribution,
+    Extensions,
+}
+
+impl FrameRoute {
+    /// Returns the route of a frame from its extension type and message type.
+    ///
+    /// Frames with a non-zero extension type are routed to the extensions handler.
+    pub fn from_header(header: &Header) -> Result<Self, ParserError> {
+        if header.ext_type_without_channel_msg() != 0 {
+            return Ok(FrameRoute::Extensions);
+        }
+
+        let message_type = header.msg_type();
+        if CommonMessageTypes::try_from(message_type).is_ok() {
+            Ok(FrameRoute::Common)
+        } else if MiningTypes::try_from(message_type).is_ok() {
+            Ok(FrameRoute::Mining)
+        } else if JobDeclarationTypes::try_from(message_type).is_ok() {
+            Ok(FrameRoute::JobDeclaration)
+        } else if TemplateDistributionTypes::try_from(message_type).is_ok() {
+            Ok(FrameRoute::TemplateDistribution)
+        } else {
+            Err(ParserError::UnexpectedMessage(message_type))
+        }
+    }
+
+    /// Returns the subprotocol that must be negotiated in `SetupConnection` for frames of this
+    /// route to be accepted.
+    ///
+    /// Common messages and extension messages don't belong to any subprotocol.
+    pub fn protocol(&self) -> Option<Protocol> {
+        match self {
+            FrameRoute::Common | FrameRoute::Extensions => None,
+            FrameRoute::Mining => Some(Protocol::MiningProtocol),
+            FrameRoute::JobDeclaration => Some(Protocol::JobDeclarationProtocol),
+            FrameRoute::TemplateDistribution => Some(Protocol::TemplateDistributionProtocol),
+        }
+    }
+}
+
+/// Markers of the subprotocol handlers frames are routed to, naming the handler of a
+/// [`FrameHandlerFromServerAsync`] or [`FrameHandlerFromClientAsync`].
+pub mod route {
+    /// Handlers of Common messages.
+    #[derive(Debug, Clone, Copy)]
+    pub struct Common;
+    /// Handlers of Mining messages.
+    #[derive(Debug, Clone, Copy)]
+    pub struct Mining;
+    /// Handlers of Job Declaration messages.
+    #[derive(Debug, Clone, Copy)]
+    pub struct JobDeclaration;
+    /// Handlers of Template Distribution messages.
+    #[derive(Debug, Clone, Copy)]
+    pub struct TemplateDistribution;
+    /// Handlers of Extensions messages.
+    #[derive(Debug, Clone, Copy)]
+    pub struct Extensions;
+}
+
+/// Raw frame future returned by [`FrameHandlerFromServerAsync`] and [`FrameHandlerFromClientAsync`].
+pub type FrameFuture<'a, E> = Pin<Box<dyn Future<Output = Result<(), E>> + Send + 'a>>;
+
+/// Async handler of the subprotocol `R` that frames from servers can be routed to.
+///
+/// Async handler traits can't be used as trait objects, so [`DispatchFramesFromServerAsync`]
+/// returns its handlers as this trait instead. It is implemented for every `Send` async handler,
+/// e.g. `FrameHandlerFromServerAsync<route::Mining, E>` for every
+/// `HandleMiningMessagesFromServerAsync<Error = E>`.
+pub trait FrameHandlerFromServerAsync<R, E>: Send {
+    /// Forwards a raw frame to the `handle_*_message_frame_from_server` method of the handler.
+    fn handle_frame_from_server<'a>(
+        &'a mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &'a mut [u8],
+    ) -> FrameFuture<'a, E>;
+}
+
+/// Async handler of the subprotocol `R` that frames from clients can be routed to.
+///
+/// Async handler traits can't be used as trait objects, so [`DispatchFramesFromClientAsync`]
+/// returns its handlers as this trait instead. It is implemented for every `Send` async handler,
+/// e.g. `FrameHandlerFromClientAsync<route::Mining, E>` for every
+/// `HandleMiningMessagesFromClientAsync<Error = E>`.
+pub trait FrameHandlerFromClientAsync<R, E>: Send {
+    /// Forwards a raw frame to the `handle_*_message_frame_from_client` method of the handler.
+    fn handle_frame_from_client<'a>(
+        &'a mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &'a mut [u8],
+    ) -> FrameFuture<'a, E>;
+}
+
+impl<T> FrameHandlerFromServerAsync<route::Common, T::Error> for T
+where
+    T: HandleCommonMessagesFromServerAsync + Send,
+{
+    fn handle_frame_from_server<'a>(
+        &'a mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &'a mut [u8],
+    ) -> FrameFuture<'a, T::Error> {
+        Box::pin(self.handle_common_message_frame_from_server(server_id, header, payload))
+    }
+}
+
+impl<T> FrameHandlerFromServerAsync<route::Mining, T::Error> for T
+where
+    T: HandleMiningMessagesFromServerAsync + Send,
+{
+    fn handle_frame_from_server<'a>(
+        &'a mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &'a mut [u8],
+    ) -> FrameFuture<'a, T::Error> {
+        Box::pin(self.handle_mining_message_frame_from_server(server_id, header, payload))
+    }
+}
+
+impl<T> FrameHandlerFromServerAsync<route::JobDeclaration, T::Error> for T
+where
+    T: HandleJobDeclarationMessagesFromServerAsync + Send,
+{
+    fn handle_frame_from_server<'a>(
+        &'a mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &'a mut [u8],
+    ) -> FrameFuture<'a, T::Error> {
+        Box::pin(self.handle_job_declaration_message_frame_from_server(server_id, header, payload))
+    }
+}
+
+impl<T> FrameHandlerFromServerAsync<route::TemplateDistribution, T::Error> for T
+where
+    T: HandleTemplateDistributionMessagesFromServerAsync + Send,
+{
+    fn handle_frame_from_server<'a>(
+        &'a mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &'a mut [u8],
+    ) -> FrameFuture<'a, T::Error> {
+        Box::pin(
+            self.handle_template_distribution_message_frame_from_server(server_id, header, payload),
+        )
+    }
+}
+
+impl<T> FrameHandlerFromServerAsync<route::Extensions, T::Error> for T
+where
+    T: HandleExtensionsFromServerAsync + Send,
+{
+    fn handle_frame_from_server<'a>(
+        &'a mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &'a mut [u8],
+    ) -> FrameFuture<'a, T::Error> {
+        Box::pin(self.handle_extensions_message_frame_from_server(server_id, header, payload))
+    }
+}
+
+impl<T> FrameHandlerFromClientAsync<route::Common, T::Error> for T
+where
+    T: HandleCommonMessagesFromClientAsync + Send,
+{
+    fn handle_frame_from_client<'a>(
+        &'a mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &'a mut [u8],
+    ) -> FrameFuture<'a, T::Error> {
+        Box::pin(self.handle_common_message_frame_from_client(client_id, header, payload))
+    }
+}
+
+impl<T> FrameHandlerFromClientAsync<route::Mining, T::Error> for T
+where
+    T: HandleMiningMessagesFromClientAsync + Send,
+{
+    fn handle_frame_from_client<'a>(
+        &'a mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &'a mut [u8],
+    ) -> FrameFuture<'a, T::Error> {
+        Box::pin(self.handle_mining_message_frame_from_client(client_id, header, payload))
+    }
+}
+
+impl<T> FrameHandlerFromClientAsync<route::JobDeclaration, T::Error> for T
+where
+    T: HandleJobDeclarationMessagesFromClientAsync + Send,
+{
+    fn handle_frame_from_client<'a>(
+        &'a mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &'a mut [u8],
+    ) -> FrameFuture<'a, T::Error> {
+        Box::pin(self.handle_job_declaration_message_frame_from_client(client_id, header, payload))
+    }
+}
+
+impl<T> FrameHandlerFromClientAsync<route::TemplateDistribution, T::Error> for T
+where
+    T: HandleTemplateDistributionMessagesFromClientAsync + Send,
+{
+    fn handle_frame_from_client<'a>(
+        &'a mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &'a mut [u8],
+    ) -> FrameFuture<'a, T::Error> {
+        Box::pin(
+            self.handle_template_distribution_message_frame_from_client(client_id, header, payload),
+        )
+    }
+}
+
+impl<T> FrameHandlerFromClientAsync<route::Extensions, T::Error> for T
+where
+    T: HandleExtensionsFromClientAsync + Send,
+{
+    fn handle_frame_from_client<'a>(
+        &'a mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &'a mut [u8],
+    ) -> FrameFuture<'a, T::Error> {
+        Box::pin(self.handle_extensions_message_frame_from_client(client_id, header, payload))
+    }
+}
+
+/// Synchronous trait for routing any frame received from servers to the right handler.
+///
+/// `dispatch_frame_from_server` reads the extension type and message type of the frame header,
+/// checks the frame against the connection [`Session`] (if any) and that the subprotocol of the
+/// frame was negotiated in `SetupConnection`, and calls the matching
+/// `dispatch_*_frame_from_server` method.
+///
+/// Those methods forward the frame to the handler returned by the matching
+/// `get_*_handler_for_server` method, e.g. a `HandleMiningMessagesFromServerSync` for Mining
+/// frames, and reject it as unexpected when there is no handler.
+///
+/// `process_frame_from_server` wraps `dispatch_frame_from_server`, answering rejected frames as
+/// told by [`HandlerErrorType::get_action`].
+///
+/// The server ID identifies which server a message originated from.
+/// Whether this is relevant or not depends on which object is implementing the trait, and whether
+/// this contextual information is readily available or not. In cases where `server_id` is either
+/// irrelevant or can be inferred without the context, this should always be `None`.
+pub trait DispatchFramesFromServerSync {
+    type Error: HandlerErrorType;
+
+    /// Returns the subprotocol negotiated with a server in `SetupConnection`.
+    ///
+    /// Return `None` if the connection was not set up yet.
+    fn get_negotiated_protocol_with_server(
+        &self,
+        server_id: Option<usize>,
+    ) -> Result<Option<Protocol>, Self::Error>;
+
+    /// Returns the [`Session`] tracking the connection with a server, if any.
+    ///
+    /// When a session is returned, frames it doesn't allow at the current stage of the connection
+    /// are rejected with the `session_error` error before being routed.
+    fn get_session_with_server(
+        &self,
+        _server_id: Option<usize>,
+    ) -> Result<Option<&Session>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Returns the handler Common messages from a server are routed to, if any.
+    fn get_common_handler_for_server(
+        &mut self,
+        _server_id: Option<usize>,
+    ) -> Option<&mut dyn HandleCommonMessagesFromServerSync<Error = Self::Error>> {
+        None
+    }
+
+    /// Returns the handler Mining messages from a server are routed to, if any.
+    fn get_mining_handler_for_server(
+        &mut self,
+        _server_id: Option<usize>,
+    ) -> Option<&mut dyn HandleMiningMessagesFromServerSync<Error = Self::Error>> {
+        None
+    }
+
+    /// Returns the handler Job Declaration messages from a server are routed to, if any.
+    fn get_job_declaration_handler_for_server(
+        &mut self,
+        _server_id: Option<usize>,
+    ) -> Option<&mut dyn HandleJobDeclarationMessagesFromServerSync<Error = Self::Error>> {
+        None
+    }
+
+    /// Returns the handler Template Distribution messages from a server are routed to, if any.
+    fn get_template_distribution_handler_for_server(
+        &mut self,
+        _server_id: Option<usize>,
+    ) -> Option<&mut dyn HandleTemplateDistributionMessagesFromServerSync<Error = Self::Error>>
+    {
+        None
+    }
+
+    /// Returns the handler Extensions messages from a server are routed to, if any.
+    fn get_extensions_handler_for_server(
+        &mut self,
+        _server_id: Option<usize>,
+    ) -> Option<&mut dyn HandleExtensionsFromServerSync<Error = Self::Error>> {
+        None
+    }
+
+    /// Sends a message to a server, answering a frame rejected in `process_frame_from_server`.
+    fn send_message_to_server(
+        &mut self,
+        server_id: Option<usize>,
+        message: AnyMessage<'static>,
+    ) -> Result<(), Self::Error>;
+
+    /// Routes a raw frame from a server with `dispatch_frame_from_server`, and carries out the
+    /// [`crate::HandlerAction`] of the error if the frame is rejected.
+    ///
+    /// The message of the action (e.g. `SubmitShares.Error` or `CloseChannel`), if any, is sent
+    /// with `send_message_to_server`. Returns the error if the connection must be closed, and `Ok`
+    /// if it stays open.
+    fn process_frame_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let error = match self.dispatch_frame_from_server(server_id, header, payload) {
+            Ok(()) => return Ok(()),
+            Err(error) => error,
+        };
+        let action = error.get_action();
+        let message = action
+            .to_message()
+            .map_err(|e| Self::Error::parse_error(ParserError::BinaryError(e)))?;
+        if let Some(message) = message {
+            self.send_message_to_server(server_id, message)?;
+        }
+        match action.closes_connection() {
+            true => Err(error),
+            false => Ok(()),
+        }
+    }
+
+    /// Routes a raw frame from a server to the handler of its subprotocol.
+    ///
+    /// Returns the `protocol_not_negotiated` error if the frame belongs to a subprotocol other than
+    /// the one negotiated with the server.
+    fn dispatch_frame_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        if let Some(session) = self.get_session_with_server(server_id)? {
+            session.check_frame_from_server(&header).map_err(|error| {
+                Self::Error::session_error(
+                    error,
+                    header.ext_type_without_channel_msg(),
+                    header.msg_type(),
+                )
+            })?;
+        }
+
+        let route = FrameRoute::from_header(&header).map_err(Self::Error::parse_error)?;
+        if let Some(protocol) = route.protocol() {
+            if self.get_negotiated_protocol_with_server(server_id)? != Some(protocol) {
+                return Err(Self::Error::protocol_not_negotiated(
+                    protocol,
+                    header.ext_type_without_channel_msg(),
+                    header.msg_type(),
+                ));
+            }
+        }
+
+        match route {
+            FrameRoute::Common => {
+                self.dispatch_common_message_frame_from_server(server_id, header, payload)
+            }
+            FrameRoute::Mining => {
+                self.dispatch_mining_message_frame_from_server(server_id, header, payload)
+            }
+            FrameRoute::JobDeclaration => {
+                self.dispatch_job_declaration_message_frame_from_server(server_id, header, payload)
+            }
+            FrameRoute::TemplateDistribution => self
+                .dispatch_template_distribution_message_frame_from_server(
+                    server_id, header, payload,
+                ),
+            FrameRoute::Extensions => {
+                self.dispatch_extensions_message_frame_from_server(server_id, header, payload)
+            }
+        }
+    }
+
+    fn dispatch_common_message_frame_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        match self.get_common_handler_for_server(server_id) {
+            Some(handler) => {
+                handler.handle_common_message_frame_from_server(server_id, header, payload)
+            }
+            None => Err(Self::Error::unexpected_message(0, header.msg_type())),
+        }
+    }
+
+    fn dispatch_mining_message_frame_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        match self.get_mining_handler_for_server(server_id) {
+            Some(handler) => {
+                handler.handle_mining_message_frame_from_server(server_id, header, payload)
+            }
+            None => Err(Self::Error::unexpected_message(0, header.msg_type())),
+        }
+    }
+
+    fn dispatch_job_declaration_message_frame_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        match self.get_job_declaration_handler_for_server(server_id) {
+            Some(handler) => {
+                handler.handle_job_declaration_message_frame_from_server(server_id, header, payload)
+            }
+            None => Err(Self::Error::unexpected_message(0, header.msg_type())),
+        }
+    }
+
+    fn dispatch_template_distribution_message_frame_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        match self.get_template_distribution_handler_for_server(server_id) {
+            Some(handler) => handler
+                .handle_template_distribution_message_frame_from_server(server_id, header, payload),
+            None => Err(Self::Error::unexpected_message(0, header.msg_type())),
+        }
+    }
+
+    fn dispatch_extensions_message_frame_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        match self.get_extensions_handler_for_server(server_id) {
+            Some(handler) => {
+                handler.handle_extensions_message_frame_from_server(server_id, header, payload)
+            }
+            None => Err(Self::Error::unexpected_message(
+                header.ext_type_without_channel_msg(),
+                header.msg_type(),
+            )),
+        }
+    }
+}
+
+/// Asynchronous trait for routing any frame received from servers to the right handler.
+///
+/// `dispatch_frame_from_server` reads the extension type and message type of the frame header,
+/// checks the frame against the connection [`Session`] (if any) and that the subprotocol of the
+/// frame was negotiated in `SetupConnection`, and calls the matching
+/// `dispatch_*_frame_from_server` method.
+///
+/// Those methods forward the frame to the handler returned by the matching
+/// `get_*_handler_for_server` method, as a [`FrameHandlerFromServerAsync`] (which every
+/// `Send` async handler is, e.g. a `HandleMiningMessagesFromServerAsync` for Mining frames), and
+/// reject it as unexpected when there is no handler.
+///
+/// `process_frame_from_server` wraps `dispatch_frame_from_server`, answering rejected frames as
+/// told by [`HandlerErrorType::get_action`].
+///
+/// The server ID identifies which server a message originated from.
+/// Whether this is relevant or not depends on which object is implementing the trait, and whether
+/// this contextual information is readily available or not. In cases where `server_id` is either
+/// irrelevant or can be inferred without the context, this should always be `None`.
+#[trait_variant::make(Send)]
+pub trait DispatchFramesFromServerAsync {
+    type Error: HandlerErrorType;
+
+    /// Returns the subprotocol negotiated with a server in `SetupConnection`.
+    ///
+    /// Return `None` if the connection was not set up yet.
+    fn get_negotiated_protocol_with_server(
+        &self,
+        server_id: Option<usize>,
+    ) -> Result<Option<Protocol>, Self::Error>;
+
+    /// Returns the [`Session`] tracking the connection with a server, if any.
+    ///
+    /// When a session is returned, frames it doesn't allow at the current stage of the connection
+    /// are rejected with the `session_error` error before being routed.
+    fn get_session_with_server(
+        &self,
+        _server_id: Option<usize>,
+    ) -> Result<Option<&Session>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Returns the handler Common messages from a server are routed to, if any.
+    fn get_common_handler_for_server(
+        &mut self,
+        _server_id: Option<usize>,
+    ) -> Option<&mut dyn FrameHandlerFromServerAsync<route::Common, Self::Error>> {
+        None
+    }
+
+    /// Returns the handler Mining messages from a server are routed to, if any.
+    fn get_mining_handler_for_server(
+        &mut self,
+        _server_id: Option<usize>,
+    ) -> Option<&mut dyn FrameHandlerFromServerAsync<route::Mining, Self::Error>> {
+        None
+    }
+
+    /// Returns the handler Job Declaration messages from a server are routed to, if any.
+    fn get_job_declaration_handler_for_server(
+        &mut self,
+        _server_id: Option<usize>,
+    ) -> Option<&mut dyn FrameHandlerFromServerAsync<route::JobDeclaration, Self::Error>> {
+        None
+    }
+
+    /// Returns the handler Template Distribution messages from a server are routed to, if any.
+    fn get_template_distribution_handler_for_server(
+        &mut self,
+        _server_id: Option<usize>,
+    ) -> Option<&mut dyn FrameHandlerFromServerAsync<route::TemplateDistribution, Self::Error>>
+    {
+        None
+    }
+
+    /// Returns the handler Extensions messages from a server are routed to, if any.
+    fn get_extensions_handler_for_server(
+        &mut self,
+        _server_id: Option<usize>,
+    ) -> Option<&mut dyn FrameHandlerFromServerAsync<route::Extensions, Self::Error>> {
+        None
+    }
+
+    /// Sends a message to a server, answering a frame rejected in `process_frame_from_server`.
+    async fn send_message_to_server(
+        &mut self,
+        server_id: Option<usize>,
+        message: AnyMessage<'static>,
+    ) -> Result<(), Self::Error>;
+
+    /// Routes a raw frame from a server with `dispatch_frame_from_server`, and carries out the
+    /// [`crate::HandlerAction`] of the error if the frame is rejected.
+    ///
+    /// The message of the action (e.g. `SubmitShares.Error` or `CloseChannel`), if any, is sent
+    /// with `send_message_to_server`. Returns the error if the connection must be closed, and `Ok`
+    /// if it stays open.
+    async fn process_frame_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        Self::Error: Send,
+    {
+        async move {
+            let error = match self
+                .dispatch_frame_from_server(server_id, header, payload)
+                .await
+            {
+                Ok(()) => return Ok(()),
+                Err(error) => error,
+            };
+            let action = error.get_action();
+            let message = action
+                .to_message()
+                .map_err(|e| Self::Error::parse_error(ParserError::BinaryError(e)))?;
+            if let Some(message) = message {
+                self.send_message_to_server(server_id, message).await?;
+            }
+            match action.closes_connection() {
+                true => Err(error),
+                false => Ok(()),
+            }
+        }
+    }
+
+    /// Routes a raw frame from a server to the handler of its subprotocol.
+    ///
+    /// Returns the `protocol_not_negotiated` error if the frame belongs to a subprotocol other than
+    /// the one negotiated with the server.
+    async fn dispatch_frame_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            if let Some(session) = self.get_session_with_server(server_id)? {
+                session.check_frame_from_server(&header).map_err(|error| {
+                    Self::Error::session_error(
+                        error,
+                        header.ext_type_without_channel_msg(),
+                        header.msg_type(),
+                    )
+                })?;
+            }
+
+            let route = FrameRoute::from_header(&header).map_err(Self::Error::parse_error)?;
+            if let Some(protocol) = route.protocol() {
+                if self.get_negotiated_protocol_with_server(server_id)? != Some(protocol) {
+                    return Err(Self::Error::protocol_not_negotiated(
+                        protocol,
+                        header.ext_type_without_channel_msg(),
+                        header.msg_type(),
+                    ));
+                }
+            }
+
+            match route {
+                FrameRoute::Common => {
+                    self.dispatch_common_message_frame_from_server(server_id, header, payload)
+                        .await
+                }
+                FrameRoute::Mining => {
+                    self.dispatch_mining_message_frame_from_server(server_id, header, payload)
+                        .await
+                }
+                FrameRoute::JobDeclaration => {
+                    self.dispatch_job_declaration_message_frame_from_server(
+                        server_id, header, payload,
+                    )
+                    .await
+                }
+                FrameRoute::TemplateDistribution => {
+                    self.dispatch_template_distribution_message_frame_from_server(
+                        server_id, header, payload,
+                    )
+                    .await
+                }
+                FrameRoute::Extensions => {
+                    self.dispatch_extensions_message_frame_from_server(server_id, header, payload)
+                        .await
+                }
+            }
+        }
+    }
+
+    async fn dispatch_common_message_frame_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            match self.get_common_handler_for_server(server_id) {
+                Some(handler) => {
+                    handler
+                        .handle_frame_from_server(server_id, header, payload)
+                        .await
+                }
+                None => Err(Self::Error::unexpected_message(0, header.msg_type())),
+            }
+        }
+    }
+
+    async fn dispatch_mining_message_frame_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            match self.get_mining_handler_for_server(server_id) {
+                Some(handler) => {
+                    handler
+                        .handle_frame_from_server(server_id, header, payload)
+                        .await
+                }
+                None => Err(Self::Error::unexpected_message(0, header.msg_type())),
+            }
+        }
+    }
+
+    async fn dispatch_job_declaration_message_frame_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            match self.get_job_declaration_handler_for_server(server_id) {
+                Some(handler) => {
+                    handler
+                        .handle_frame_from_server(server_id, header, payload)
+                        .await
+                }
+                None => Err(Self::Error::unexpected_message(0, header.msg_type())),
+            }
+        }
+    }
+
+    async fn dispatch_template_distribution_message_frame_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            match self.get_template_distribution_handler_for_server(server_id) {
+                Some(handler) => {
+                    handler
+                        .handle_frame_from_server(server_id, header, payload)
+                        .await
+                }
+                None => Err(Self::Error::unexpected_message(0, header.msg_type())),
+            }
+        }
+    }
+
+    async fn dispatch_extensions_message_frame_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            match self.get_extensions_handler_for_server(server_id) {
+                Some(handler) => {
+                    handler
+                        .handle_frame_from_server(server_id, header, payload)
+                        .await
+                }
+                None => Err(Self::Error::unexpected_message(
+                    header.ext_type_without_channel_msg(),
+                    header.msg_type(),
+                )),
+            }
+        }
+    }
+}
+
+/// Synchronous trait for routing any frame received from clients to the right handler.
+///
+/// `dispatch_frame_from_client` reads the extension type and message type of the frame header,
+/// checks the frame against the connection [`Session`] (if any) and that the subprotocol of the
+/// frame was negotiated in `SetupConnection`, and calls the matching
+/// `dispatch_*_frame_from_client` method.
+///
+/// Those methods forward the frame to the handler returned by the matching
+/// `get_*_handler_for_client` method, e.g. a `HandleMiningMessagesFromClientSync` for Mining
+/// frames, and reject it as unexpected when there is no handler.
+///
+/// `process_frame_from_client` wraps `dispatch_frame_from_client`, answering rejected frames as
+/// told by [`HandlerErrorType::get_action`].
+///
+/// The client ID identifies which client a message originated from.
+/// Whether this is relevant or not depends on which object is implementing the trait, and whether
+/// this contextual information is readily available or not. In cases where `client_id` is either
+/// irrelevant or can be inferred without the context, this should always be `None`.
+pub trait DispatchFramesFromClientSync {
+    type Error: HandlerErrorType;
+
+    /// Returns the subprotocol negotiated with a client in `SetupConnection`.
+    ///
+    /// Return `None` if the connection was not set up yet.
+    fn get_negotiated_protocol_with_client(
+        &self,
+        client_id: Option<usize>,
+    ) -> Result<Option<Protocol>, Self::Error>;
+
+    /// Returns the [`Session`] tracking the connection with a client, if any.
+    ///
+    /// When a session is returned, frames it doesn't allow at the current stage of the connection
+    /// are rejected with the `session_error` error before being routed.
+    fn get_session_with_client(
+        &self,
+        _client_id: Option<usize>,
+    ) -> Result<Option<&Session>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Returns the handler Common messages from a client are routed to, if any.
+    fn get_common_handler_for_client(
+        &mut self,
+        _client_id: Option<usize>,
+    ) -> Option<&mut dyn HandleCommonMessagesFromClientSync<Error = Self::Error>> {
+        None
+    }
+
+    /// Returns the handler Mining messages from a client are routed to, if any.
+    fn get_mining_handler_for_client(
+        &mut self,
+        _client_id: Option<usize>,
+    ) -> Option<&mut dyn HandleMiningMessagesFromClientSync<Error = Self::Error>> {
+        None
+    }
+
+    /// Returns the handler Job Declaration messages from a client are routed to, if any.
+    fn get_job_declaration_handler_for_client(
+        &mut self,
+        _client_id: Option<usize>,
+    ) -> Option<&mut dyn HandleJobDeclarationMessagesFromClientSync<Error = Self::Error>> {
+        None
+    }
+
+    /// Returns the handler Template Distribution messages from a client are routed to, if any.
+    fn get_template_distribution_handler_for_client(
+        &mut self,
+        _client_id: Option<usize>,
+    ) -> Option<&mut dyn HandleTemplateDistributionMessagesFromClientSync<Error = Self::Error>>
+    {
+        None
+    }
+
+    /// Returns the handler Extensions messages from a client are routed to, if any.
+    fn get_extensions_handler_for_client(
+        &mut self,
+        _client_id: Option<usize>,
+    ) -> Option<&mut dyn HandleExtensionsFromClientSync<Error = Self::Error>> {
+        None
+    }
+
+    /// Sends a message to a client, answering a frame rejected in `process_frame_from_client`.
+    fn send_message_to_client(
+        &mut self,
+        client_id: Option<usize>,
+        message: AnyMessage<'static>,
+    ) -> Result<(), Self::Error>;
+
+    /// Routes a raw frame from a client with `dispatch_frame_from_client`, and carries out the
+    /// [`crate::HandlerAction`] of the error if the frame is rejected.
+    ///
+    /// The message of the action (e.g. `SubmitShares.Error` or `CloseChannel`), if any, is sent
+    /// with `send_message_to_client`. Returns the error if the connection must be closed, and `Ok`
+    /// if it stays open.
+    fn process_frame_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let error = match self.dispatch_frame_from_client(client_id, header, payload) {
+            Ok(()) => return Ok(()),
+            Err(error) => error,
+        };
+        let action = error.get_action();
+        let message = action
+            .to_message()
+            .map_err(|e| Self::Error::parse_error(ParserError::BinaryError(e)))?;
+        if let Some(message) = message {
+            self.send_message_to_client(client_id, message)?;
+        }
+        match action.closes_connection() {
+            true => Err(error),
+            false => Ok(()),
+        }
+    }
+
+    /// Routes a raw frame from a client to the handler of its subprotocol.
+    ///
+    /// Returns the `protocol_not_negotiated` error if the frame belongs to a subprotocol other than
+    /// the one negotiated with the client.
+    fn dispatch_frame_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        if let Some(session) = self.get_session_with_client(client_id)? {
+            session.check_frame_from_client(&header).map_err(|error| {
+                Self::Error::session_error(
+                    error,
+                    header.ext_type_without_channel_msg(),
+                    header.msg_type(),
+                )
+            })?;
+        }
+
+        let route = FrameRoute::from_header(&header).map_err(Self::Error::parse_error)?;
+        if let Some(protocol) = route.protocol() {
+            if self.get_negotiated_protocol_with_client(client_id)? != Some(protocol) {
+                return Err(Self::Error::protocol_not_negotiated(
+                    protocol,
+                    header.ext_type_without_channel_msg(),
+                    header.msg_type(),
+                ));
+            }
+        }
+
+        match route {
+            FrameRoute::Common => {
+                self.dispatch_common_message_frame_from_client(client_id, header, payload)
+            }
+            FrameRoute::Mining => {
+                self.dispatch_mining_message_frame_from_client(client_id, header, payload)
+            }
+            FrameRoute::JobDeclaration => {
+                self.dispatch_job_declaration_message_frame_from_client(client_id, header, payload)
+            }
+            FrameRoute::TemplateDistribution => self
+                .dispatch_template_distribution_message_frame_from_client(
+                    client_id, header, payload,
+                ),
+            FrameRoute::Extensions => {
+                self.dispatch_extensions_message_frame_from_client(client_id, header, payload)
+            }
+        }
+    }
+
+    fn dispatch_common_message_frame_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        match self.get_common_handler_for_client(client_id) {
+            Some(handler) => {
+                handler.handle_common_message_frame_from_client(client_id, header, payload)
+            }
+            None => Err(Self::Error::unexpected_message(0, header.msg_type())),
+        }
+    }
+
+    fn dispatch_mining_message_frame_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        match self.get_mining_handler_for_client(client_id) {
+            Some(handler) => {
+                handler.handle_mining_message_frame_from_client(client_id, header, payload)
+            }
+            None => Err(Self::Error::unexpected_message(0, header.msg_type())),
+        }
+    }
+
+    fn dispatch_job_declaration_message_frame_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        match self.get_job_declaration_handler_for_client(client_id) {
+            Some(handler) => {
+                handler.handle_job_declaration_message_frame_from_client(client_id, header, payload)
+            }
+            None => Err(Self::Error::unexpected_message(0, header.msg_type())),
+        }
+    }
+
+    fn dispatch_template_distribution_message_frame_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        match self.get_template_distribution_handler_for_client(client_id) {
+            Some(handler) => handler
+                .handle_template_distribution_message_frame_from_client(client_id, header, payload),
+            None => Err(Self::Error::unexpected_message(0, header.msg_type())),
+        }
+    }
+
+    fn dispatch_extensions_message_frame_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        match self.get_extensions_handler_for_client(client_id) {
+            Some(handler) => {
+                handler.handle_extensions_message_frame_from_client(client_id, header, payload)
+            }
+            None => Err(Self::Error::unexpected_message(
+                header.ext_type_without_channel_msg(),
+                header.msg_type(),
+            )),
+        }
+    }
+}
+
+/// Asynchronous trait for routing any frame received from clients to the right handler.
+///
+/// `dispatch_frame_from_client` reads the extension type and message type of the frame header,
+/// checks the frame against the connection [`Session`] (if any) and that the subprotocol of the
+/// frame was negotiated in `SetupConnection`, and calls the matching
+/// `dispatch_*_frame_from_client` method.
+///
+/// Those methods forward the frame to the handler returned by the matching
+/// `get_*_handler_for_client` method, as a [`FrameHandlerFromClientAsync`] (which every
+/// `Send` async handler is, e.g. a `HandleMiningMessagesFromClientAsync` for Mining frames), and
+/// reject it as unexpected when there is no handler.
+///
+/// `process_frame_from_client` wraps `dispatch_frame_from_client`, answering rejected frames as
+/// told by [`HandlerErrorType::get_action`].
+///
+/// The client ID identifies which client a message originated from.
+/// Whether this is relevant or not depends on which object is implementing the trait, and whether
+/// this contextual information is readily available or not. In cases where `client_id` is either
+/// irrelevant or can be inferred without the context, this should always be `None`.
+#[trait_variant::make(Send)]
+pub trait DispatchFramesFromClientAsync {
+    type Error: HandlerErrorType;
+
+    /// Returns the subprotocol negotiated with a client in `SetupConnection`.
+    ///
+    /// Return `None` if the connection was not set up yet.
+    fn get_negotiated_protocol_with_client(
+        &self,
+        client_id: Option<usize>,
+    ) -> Result<Option<Protocol>, Self::Error>;
+
+    /// Returns the [`Session`] tracking the connection with a client, if any.
+    ///
+    /// When a session is returned, frames it doesn't allow at the current stage of the connection
+    /// are rejected with the `session_error` error before being routed.
+    fn get_session_with_client(
+        &self,
+        _client_id: Option<usize>,
+    ) -> Result<Option<&Session>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Returns the handler Common messages from a client are routed to, if any.
+    fn get_common_handler_for_client(
+        &mut self,
+        _client_id: Option<usize>,
+    ) -> Option<&mut dyn FrameHandlerFromClientAsync<route::Common, Self::Error>> {
+        None
+    }
+
+    /// Returns the handler Mining messages from a client are routed to, if any.
+    fn get_mining_handler_for_client(
+        &mut self,
+        _client_id: Option<usize>,
+    ) -> Option<&mut dyn FrameHandlerFromClientAsync<route::Mining, Self::Error>> {
+        None
+    }
+
+    /// Returns the handler Job Declaration messages from a client are routed to, if any.
+    fn get_job_declaration_handler_for_client(
+        &mut self,
+        _client_id: Option<usize>,
+    ) -> Option<&mut dyn FrameHandlerFromClientAsync<route::JobDeclaration, Self::Error>> {
+        None
+    }
+
+    /// Returns the handler Template Distribution messages from a client are routed to, if any.
+    fn get_template_distribution_handler_for_client(
+        &mut self,
+        _client_id: Option<usize>,
+    ) -> Option<&mut dyn FrameHandlerFromClientAsync<route::TemplateDistribution, Self::Error>>
+    {
+        None
+    }
+
+    /// Returns the handler Extensions messages from a client are routed to, if any.
+    fn get_extensions_handler_for_client(
+        &mut self,
+        _client_id: Option<usize>,
+    ) -> Option<&mut dyn FrameHandlerFromClientAsync<route::Extensions, Self::Error>> {
+        None
+    }
+
+    /// Sends a message to a client, answering a frame rejected in `process_frame_from_client`.
+    async fn send_message_to_client(
+        &mut self,
+        client_id: Option<usize>,
+        message: AnyMessage<'static>,
+    ) -> Result<(), Self::Error>;
+
+    /// Routes a raw frame from a client with `dispatch_frame_from_client`, and carries out the
+    /// [`crate::HandlerAction`] of the error if the frame is rejected.
+    ///
+    /// The message of the action (e.g. `SubmitShares.Error` or `CloseChannel`), if any, is sent
+    /// with `send_message_to_client`. Returns the error if the connection must be closed, and `Ok`
+    /// if it stays open.
+    async fn process_frame_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        Self::Error: Send,
+    {
+        async move {
+            let error = match self
+                .dispatch_frame_from_client(client_id, header, payload)
+                .await
+            {
+                Ok(()) => return Ok(()),
+                Err(error) => error,
+            };
+            let action = error.get_action();
+            let message = action
+                .to_message()
+                .map_err(|e| Self::Error::parse_error(ParserError::BinaryError(e)))?;
+            if let Some(message) = message {
+                self.send_message_to_client(client_id, message).await?;
+            }
+            match action.closes_connection() {
+                true => Err(error),
+                false => Ok(()),
+            }
+        }
+    }
+
+    /// Routes a raw frame from a client to the handler of its subprotocol.
+    ///
+    /// Returns the `protocol_not_negotiated` error if the frame belongs to a subprotocol other than
+    /// the one negotiated with the client.
+    async fn dispatch_frame_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            if let Some(session) = self.get_session_with_client(client_id)? {
+                session.check_frame_from_client(&header).map_err(|error| {
+                    Self::Error::session_error(
+                        error,
+                        header.ext_type_without_channel_msg(),
+                        header.msg_type(),
+                    )
+                })?;
+            }
+
+            let route = FrameRoute::from_header(&header).map_err(Self::Error::parse_error)?;
+            if let Some(protocol) = route.protocol() {
+                if self.get_negotiated_protocol_with_client(client_id)? != Some(protocol) {
+                    return Err(Self::Error::protocol_not_negotiated(
+                        protocol,
+                        header.ext_type_without_channel_msg(),
+                        header.msg_type(),
+                    ));
+                }
+            }
+
+            match route {
+                FrameRoute::Common => {
+                    self.dispatch_common_message_frame_from_client(client_id, header, payload)
+                        .await
+                }
+                FrameRoute::Mining => {
+                    self.dispatch_mining_message_frame_from_client(client_id, header, payload)
+                        .await
+                }
+                FrameRoute::JobDeclaration => {
+                    self.dispatch_job_declaration_message_frame_from_client(
+                        client_id, header, payload,
+                    )
+                    .await
+                }
+                FrameRoute::TemplateDistribution => {
+                    self.dispatch_template_distribution_message_frame_from_client(
+                        client_id, header, payload,
+                    )
+                    .await
+                }
+                FrameRoute::Extensions => {
+                    self.dispatch_extensions_message_frame_from_client(client_id, header, payload)
+                        .await
+                }
+            }
+        }
+    }
+
+    async fn dispatch_common_message_frame_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            match self.get_common_handler_for_client(client_id) {
+                Some(handler) => {
+                    handler
+                        .handle_frame_from_client(client_id, header, payload)
+                        .await
+                }
+                None => Err(Self::Error::unexpected_message(0, header.msg_type())),
+            }
+        }
+    }
+
+    async fn dispatch_mining_message_frame_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            match self.get_mining_handler_for_client(client_id) {
+                Some(handler) => {
+                    handler
+                        .handle_frame_from_client(client_id, header, payload)
+                        .await
+                }
+                None => Err(Self::Error::unexpected_message(0, header.msg_type())),
+            }
+        }
+    }
+
+    async fn dispatch_job_declaration_message_frame_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            match self.get_job_declaration_handler_for_client(client_id) {
+                Some(handler) => {
+                    handler
+                        .handle_frame_from_client(client_id, header, payload)
+                        .await
+                }
+                None => Err(Self::Error::unexpected_message(0, header.msg_type())),
+            }
+        }
+    }
+
+    async fn dispatch_template_distribution_message_frame_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            match self.get_template_distribution_handler_for_client(client_id) {
+                Some(handler) => {
+                    handler
+                        .handle_frame_from_client(client_id, header, payload)
+                        .await
+                }
+                None => Err(Self::Error::unexpected_message(0, header.msg_type())),
+            }
+        }
+    }
+
+    async fn dispatch_extensions_message_frame_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            match self.get_extensions_handler_for_client(client_id) {
+                Some(handler) => {
+                    handler
+                        .handle_frame_from_client(client_id, header, payload)
+                        .await
+                }
+                None => Err(Self::Error::unexpected_message(
+                    header.ext_type_without_channel_msg(),
+                    header.msg_type(),
+                )),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        action::ErrorResponse,
+        error::HandlerError,
+        testing::{block_on, encode_frame, MockHandler, RecordingHandler},
+    };
+    use crate::{
+        negotiation::{ProtocolCapabilities, SetupConnectionNegotiator},
+        session::SessionError,
+    };
+    use common_messages_sv2::{
+        SetupConnection, SetupConnectionSuccess, MESSAGE_TYPE_SETUP_CONNECTION_SUCCESS,
+    };
+    use extensions_sv2::RequestExtensions;
+    use mining_sv2::{
+        OpenExtendedMiningChannel, SubmitSharesErrorCode, SubmitSharesStandard,
+        SubmitSharesSuccess, MESSAGE_TYPE_OPEN_EXTENDED_MINING_CHANNEL,
+        MESSAGE_TYPE_SUBMIT_SHARES_STANDARD, MESSAGE_TYPE_SUBMIT_SHARES_SUCCESS,
+    };
+    use parsers_sv2::{
+        AnyMessage, CommonMessages, Extensions, ExtensionsNegotiation, Mining, TemplateDistribution,
+    };
+    use template_distribution_sv2::{
+        CoinbaseOutputConstraints, MESSAGE_TYPE_COINBASE_OUTPUT_CONSTRAINTS,
+    };
+
+    // A pool serving Mining clients, without Template Distribution or Extensions handlers.
+    struct Pool {
+        negotiated_protocol: Option<Protocol>,
+        session: Option<Session>,
+        common: RecordingHandler,
+        mining: MockHandler,
+        sent: Vec<(Option<usize>, AnyMessage<'static>)>,
+    }
+
+    impl Pool {
+        fn new(negotiated_protocol: Option<Protocol>) -> Self {
+            Self {
+                negotiated_protocol,
+                session: None,
+                common: RecordingHandler::new(),
+                mining: MockHandler::new(),
+                sent: Vec::new(),
+            }
+        }
+    }
+
+    impl DispatchFramesFromClientSync for Pool {
+        type Error = HandlerError;
+
+        fn get_negotiated_protocol_with_client(
+            &self,
+            _client_id: Option<usize>,
+        ) -> Result<Option<Protocol>, Self::Error> {
+            Ok(self.negotiated_protocol)
+        }
+
+        fn get_session_with_client(
+            &self,
+            _client_id: Option<usize>,
+        ) -> Result<Option<&Session>, Self::Error> {
+            Ok(self.session.as_ref())
+        }
+
+        fn send_message_to_client(
+            &mut self,
+            client_id: Option<usize>,
+            message: AnyMessage<'static>,
+        ) -> Result<(), Self::Error> {
+            self.sent.push((client_id, message));
+            Ok(())
+        }
+
+        fn get_common_handler_for_client(
+            &mut self,
+            _client_id: Option<usize>,
+        ) -> Option<&mut dyn HandleCommonMessagesFromClientSync<Error = Self::Error>> {
+            Some(&mut self.common)
+        }
+
+        fn get_mining_handler_for_client(
+            &mut self,
+            _client_id: Option<usize>,
+        ) -> Option<&mut dyn HandleMiningMessagesFromClientSync<Error = Self::Error>> {
+            Some(&mut self.mining)
+        }
+    }
+
+    // A mining client, with async Common and Mining handlers.
+    struct Miner {
+        common: RecordingHandler,
+        mining: RecordingHandler,
+        sent: Vec<AnyMessage<'static>>,
+    }
+
+    impl DispatchFramesFromServerAsync for Miner {
+        type Error = HandlerError;
+
+        fn get_negotiated_protocol_with_server(
+            &self,
+            _server_id: Option<usize>,
+        ) -> Result<Option<Protocol>, Self::Error> {
+            Ok(Some(Protocol::MiningProtocol))
+        }
+
+        async fn send_message_to_server(
+            &mut self,
+            _server_id: Option<usize>,
+            message: AnyMessage<'static>,
+        ) -> Result<(), Self::Error> {
+            self.sent.push(message);
+            Ok(())
+        }
+
+        fn get_common_handler_for_server(
+            &mut self,
+            _server_id: Option<usize>,
+        ) -> Option<&mut dyn FrameHandlerFromServerAsync<route::Common, Self::Error>> {
+            Some(&mut self.common)
+        }
+
+        fn get_mining_handler_for_server(
+            &mut self,
+            _server_id: Option<usize>,
+        ) -> Option<&mut dyn FrameHandlerFromServerAsync<route::Mining, Self::Error>> {
+            Some(&mut self.mining)
+        }
+    }
+
+    fn submit_shares_standard() -> AnyMessage<'static> {
+        AnyMessage::Mining(Mining::SubmitSharesStandard(SubmitSharesStandard {
+            channel_id: 1,
+            sequence_number: 2,
+            job_id: 3,
+            nonce: 4,
+            ntime: 5,
+            version: 6,
+        }))
+    }
+
+    fn coinbase_output_constraints() -> AnyMessage<'static> {
+        AnyMessage::TemplateDistribution(TemplateDistribution::CoinbaseOutputConstraints(
+            CoinbaseOutputConstraints {
+                coinbase_output_max_additional_size: 100,
+                coinbase_output_max_additional_sigops: 10,
+            },
+        ))
+    }
+
+    #[test]
+    fn test_frame_route_from_header() {
+        let (header, _) = encode_frame(submit_shares_standard());
+        let route = FrameRoute::from_header(&header).unwrap();
+        assert_eq!(route, FrameRoute::Mining);
+        assert_eq!(route.protocol(), Some(Protocol::MiningProtocol));
+
+        let (header, _) = encode_frame(coinbase_output_constraints());
+        assert_eq!(
+            FrameRoute::from_header(&header).unwrap(),
+            FrameRoute::TemplateDistribution
+        );
+
+        let request_extensions = RequestExtensions {
+            request_id: 1,
+            requested_extensions: vec![0x0002].try_into().unwrap(),
+        };
+        let (header, _) = encode_frame(AnyMessage::Extensions(Extensions::ExtensionsNegotiation(
+            ExtensionsNegotiation::RequestExtensions(request_extensions),
+        )));
+        let route = FrameRoute::from_header(&header).unwrap();
+        assert_eq!(route, FrameRoute::Extensions);
+        assert_eq!(route.protocol(), None);
+
+        let unknown = Header::from_bytes(&[0, 0, 0xff, 0, 0, 0]).unwrap();
+        assert!(matches!(
+            FrameRoute::from_header(&unknown),
+            Err(ParserError::UnexpectedMessage(0xff))
+        ));
+    }
+
+    #[test]
+    fn test_frames_are_routed_to_their_handler() {
+        let mut pool = Pool::new(Some(Protocol::MiningProtocol));
+
+        let (header, mut payload) = encode_frame(submit_shares_standard());
+        pool.dispatch_frame_from_client(Some(7), header, &mut payload)
+            .unwrap();
+
+        assert!(pool.common.get_messages().is_empty());
+        let messages = pool.mining.get_messages();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].peer_id, Some(7));
+        assert_eq!(
+            messages[0].get_message_type(),
+            (0, MESSAGE_TYPE_SUBMIT_SHARES_STANDARD)
+        );
+        match &messages[0].message {
+            AnyMessage::Mining(Mining::SubmitSharesStandard(share)) => {
+                assert_eq!(share.sequence_number, 2);
+                assert_eq!(share.nonce, 4);
+            }
+            message => panic!("unexpected message: {:?}", message),
+        }
+    }
+
+    #[test]
+    fn test_frame_without_handler_is_unexpected() {
+        let mut pool = Pool::new(Some(Protocol::TemplateDistributionProtocol));
+
+        let (header, mut payload) = encode_frame(coinbase_output_constraints());
+        let error = pool
+            .dispatch_frame_from_client(None, header, &mut payload)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::UnexpectedMessage {
+                extension_type: 0,
+                message_type: MESSAGE_TYPE_COINBASE_OUTPUT_CONSTRAINTS,
+            }
+        ));
+
+        let request_extensions = RequestExtensions {
+            request_id: 1,
+            requested_extensions: vec![0x0002].try_into().unwrap(),
+        };
+        let (header, mut payload) =
+            encode_frame(AnyMessage::Extensions(Extensions::ExtensionsNegotiation(
+                ExtensionsNegotiation::RequestExtensions(request_extensions),
+            )));
+        let error = pool
+            .dispatch_frame_from_client(None, header, &mut payload)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::UnexpectedMessage {
+                extension_type: 0x0001,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_frame_of_other_protocol_is_rejected() {
+        let mut pool = Pool::new(Some(Protocol::MiningProtocol));
+
+        let (header, mut payload) = encode_frame(coinbase_output_constraints());
+        let error = pool
+            .dispatch_frame_from_client(None, header, &mut payload)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::ProtocolNotNegotiated {
+                protocol: Protocol::TemplateDistributionProtocol,
+                ..
+            }
+        ));
+
+        // before SetupConnection, only common messages get through
+        let mut pool = Pool::new(None);
+        let (header, mut payload) = encode_frame(submit_shares_standard());
+        let error = pool
+            .dispatch_frame_from_client(None, header, &mut payload)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::ProtocolNotNegotiated {
+                protocol: Protocol::MiningProtocol,
+                ..
+            }
+        ));
+        assert!(pool.mining.get_messages().is_empty());
+    }
+
+    #[test]
+    fn test_frame_not_allowed_by_setup_connection_flags_is_rejected() {
+        // the client set REQUIRES_STANDARD_JOBS
+        let mut capabilities = ProtocolCapabilities::new(Protocol::MiningProtocol, 2, 2);
+        capabilities.optional_flags = 0b1;
+        let mut session = Session::new();
+        session.on_handshake_completed().unwrap();
+        session
+            .on_setup_connection(
+                &SetupConnection {
+                    protocol: Protocol::MiningProtocol,
+                    min_version: 2,
+                    max_version: 2,
+                    flags: 0b1,
+                    endpoint_host: "0.0.0.0".to_string().try_into().unwrap(),
+                    endpoint_port: 3333,
+                    vendor: "vendor".to_string().try_into().unwrap(),
+                    hardware_version: "".to_string().try_into().unwrap(),
+                    firmware: "".to_string().try_into().unwrap(),
+                    device_id: "".to_string().try_into().unwrap(),
+                },
+                &SetupConnectionNegotiator::new().with_protocol(capabilities),
+            )
+            .unwrap();
+        let mut pool = Pool::new(Some(Protocol::MiningProtocol));
+        pool.session = Some(session);
+
+        let open_extended_mining_channel = AnyMessage::Mining(Mining::OpenExtendedMiningChannel(
+            OpenExtendedMiningChannel {
+                request_id: 1,
+                user_identity: "user".to_string().try_into().unwrap(),
+                nominal_hash_rate: 1.0,
+                max_target: [0xff; 32].into(),
+                min_extranonce_size: 8,
+            },
+        ));
+        let (header, mut payload) = encode_frame(open_extended_mining_channel);
+        let error = pool
+            .dispatch_frame_from_client(None, header, &mut payload)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::Session {
+                error: SessionError::StandardJobsRequired,
+                message_type: MESSAGE_TYPE_OPEN_EXTENDED_MINING_CHANNEL,
+                ..
+            }
+        ));
+        assert!(pool.mining.get_messages().is_empty());
+
+        let (header, mut payload) = encode_frame(submit_shares_standard());
+        pool.dispatch_frame_from_client(None, header, &mut payload)
+            .unwrap();
+        assert_eq!(pool.mining.get_messages().len(), 1);
+    }
+
+    #[test]
+    fn test_rejected_frame_is_answered_with_its_action() {
+        let mut pool = Pool::new(Some(Protocol::MiningProtocol));
+        pool.mining.push_response(
+            0,
+            MESSAGE_TYPE_SUBMIT_SHARES_STANDARD,
+            Err(HandlerError::reply(ErrorResponse::stale_share(1, 2))),
+        );
+        pool.mining.push_response(
+            0,
+            MESSAGE_TYPE_SUBMIT_SHARES_STANDARD,
+            Err(HandlerError::reconnect("pool.example", 3333)),
+        );
+
+        // the error message is sent, and the connection stays open
+        let (header, mut payload) = encode_frame(submit_shares_standard());
+        pool.process_frame_from_client(Some(7), header, &mut payload)
+            .unwrap();
+        match pool.sent.as_slice() {
+            [(Some(7), AnyMessage::Mining(Mining::SubmitSharesError(error)))] => {
+                assert_eq!(error.channel_id, 1);
+                assert_eq!(error.sequence_number, 2);
+                assert_eq!(error.get_error_code(), SubmitSharesErrorCode::StaleShare);
+            }
+            sent => panic!("unexpected messages: {:?}", sent),
+        }
+
+        // Reconnect is sent, and the connection must be closed
+        let (header, mut payload) = encode_frame(submit_shares_standard());
+        let error = pool
+            .process_frame_from_client(Some(7), header, &mut payload)
+            .unwrap_err();
+        assert!(error.get_action().closes_connection());
+        assert!(matches!(
+            pool.sent.last(),
+            Some((Some(7), AnyMessage::Common(CommonMessages::Reconnect(_))))
+        ));
+
+        // fatal errors are returned without sending anything
+        let (header, mut payload) = encode_frame(coinbase_output_constraints());
+        assert!(pool
+            .process_frame_from_client(Some(7), header, &mut payload)
+            .is_err());
+        assert_eq!(pool.sent.len(), 2);
+
+        // accepted frames are routed as with `dispatch_frame_from_client`
+        let (header, mut payload) = encode_frame(submit_shares_standard());
+        pool.process_frame_from_client(Some(7), header, &mut payload)
+            .unwrap();
+        assert_eq!(pool.mining.get_messages().len(), 3);
+        assert_eq!(pool.sent.len(), 2);
+    }
+
+    #[test]
+    fn test_async_frames_are_routed_to_their_handler() {
+        let mut miner = Miner {
+            common: RecordingHandler::new(),
+            mining: RecordingHandler::new(),
+            sent: Vec::new(),
+        };
+
+        let setup_connection_success = AnyMessage::Common(CommonMessages::SetupConnectionSuccess(
+            SetupConnectionSuccess {
+                used_version: 2,
+                flags: 0,
+            },
+        ));
+        let (header, mut payload) = encode_frame(setup_connection_success);
+        block_on(miner.dispatch_frame_from_server(None, header, &mut payload)).unwrap();
+
+        let submit_shares_success =
+            AnyMessage::Mining(Mining::SubmitSharesSuccess(SubmitSharesSuccess {
+                channel_id: 1,
+                last_sequence_number: 2,
+                new_submits_accepted_count: 1,
+                new_shares_sum: 100,
+            }));
+        let (header, mut payload) = encode_frame(submit_shares_success);
+        block_on(miner.dispatch_frame_from_server(None, header, &mut payload)).unwrap();
+
+        assert_eq!(
+            miner.common.get_message_types(),
+            vec![(0, MESSAGE_TYPE_SETUP_CONNECTION_SUCCESS)]
+        );
+        assert_eq!(
+            miner.mining.get_message_types(),
+            vec![(0, MESSAGE_TYPE_SUBMIT_SHARES_SUCCESS)]
+        );
+
+        // frames of a subprotocol that was not negotiated are fatal
+        let (header, mut payload) = encode_frame(coinbase_output_constraints());
+        assert!(block_on(miner.process_frame_from_server(None, header, &mut payload)).is_err());
+        assert!(miner.sent.is_empty());
+    }
+}
@@ -1,5 +1,276 @@
+use common_messages_sv2::Protocol;
 use parsers_sv2::ParserError;
+
+use crate::{
+    action::{ErrorResponse, HandlerAction},
+    negotiation::NegotiationError,
+    session::SessionError,
+    tlv_fields::TlvFieldError,
+};
+
+/// Error returned by the handler traits.
+///
+/// Besides the errors raised by the default methods, implementors tell through `get_action` how
+/// the connection owner reacts to the error: answering with an error message, closing a channel,
+/// redirecting the peer or dropping the connection. [`HandlerError`] is a ready-made
+/// implementation, which applications can use directly or wrap in their own error type.
 pub trait HandlerErrorType {
     fn unexpected_message(extension_type: u16, message_type: u8) -> Self;
     fn parse_error(error: ParserError) -> Self;
+
+    /// Error for a frame of a subprotocol other than the one negotiated in `SetupConnection`.
+    ///
+    /// Falls back to `unexpected_message` by default.
+    fn protocol_not_negotiated(_protocol: Protocol, extension_type: u16, message_type: u8) -> Self
+    where
+        Self: Sized,
+    {
+        Self::unexpected_message(extension_type, message_type)
+    }
+
+    /// Error for a message rejected by a [`crate::Session`], being out of order or for a
+    /// subprotocol or extension that was not negotiated.
+    ///
+    /// Falls back to `unexpected_message` by default.
+    fn session_error(_error: SessionError, extension_type: u16, message_type: u8) -> Self
+    where
+        Self: Sized,
+    {
+        Self::unexpected_message(extension_type, message_type)
+    }
+
+    /// Error for a message carrying a TLV field that violates its extension's rules.
+    ///
+    /// Falls back to `unexpected_message` by default.
+    fn invalid_tlv_field(_error: TlvFieldError, extension_type: u16, message_type: u8) -> Self
+    where
+        Self: Sized,
+    {
+        Self::unexpected_message(extension_type, message_type)
+    }
+
+    /// Error for a message from a server that is not known to a [`crate::MultiUpstreamHandler`].
+    ///
+    /// Falls back to `unexpected_message` by default.
+    fn unknown_server(_server_id: Option<usize>, extension_type: u16, message_type: u8) -> Self
+    where
+        Self: Sized,
+    {
+        Self::unexpected_message(extension_type, message_type)
+    }
+
+    /// Returns what to do with the connection because of this error.
+    ///
+    /// Errors are fatal by default.
+    fn get_action(&self) -> HandlerAction {
+        HandlerAction::Disconnect
+    }
+}
+
+/// Standard [`HandlerErrorType`], carrying the [`HandlerAction`] of rejected requests.
+#[derive(Debug)]
+pub enum HandlerError {
+    UnexpectedMessage {
+        extension_type: u16,
+        message_type: u8,
+    },
+    Parse(ParserError),
+    ProtocolNotNegotiated {
+        protocol: Protocol,
+        extension_type: u16,
+        message_type: u8,
+    },
+    Session {
+        error: SessionError,
+        extension_type: u16,
+        message_type: u8,
+    },
+    InvalidTlvField {
+        error: TlvFieldError,
+        extension_type: u16,
+        message_type: u8,
+    },
+    UnknownServer {
+        server_id: Option<usize>,
+        extension_type: u16,
+        message_type: u8,
+    },
+    /// A request was rejected by the handler, which tells how to react.
+    Rejected(HandlerAction),
+}
+
+impl HandlerError {
+    /// Rejects a request with an error message.
+    pub fn reply(response: ErrorResponse) -> Self {
+        HandlerError::Rejected(HandlerAction::Reply(response))
+    }
+
+    /// Rejects a request by closing its channel.
+    pub fn close_channel(channel_id: u32, reason_code: impl Into<String>) -> Self {
+        HandlerError::Rejected(HandlerAction::CloseChannel {
+            channel_id,
+            reason_code: reason_code.into(),
+        })
+    }
+
+    /// Rejects a request by redirecting the peer to another endpoint.
+    pub fn reconnect(new_host: impl Into<String>, new_port: u16) -> Self {
+        HandlerError::Rejected(HandlerAction::Reconnect {
+            new_host: new_host.into(),
+            new_port,
+        })
+    }
+
+    /// Rejects a request by dropping the connection.
+    pub fn fatal() -> Self {
+        HandlerError::Rejected(HandlerAction::Disconnect)
+    }
+}
+
+impl HandlerErrorType for HandlerError {
+    fn unexpected_message(extension_type: u16, message_type: u8) -> Self {
+        HandlerError::UnexpectedMessage {
+            extension_type,
+            message_type,
+        }
+    }
+
+    fn parse_error(error: ParserError) -> Self {
+        HandlerError::Parse(error)
+    }
+
+    fn protocol_not_negotiated(protocol: Protocol, extension_type: u16, message_type: u8) -> Self {
+        HandlerError::ProtocolNotNegotiated {
+            protocol,
+            extension_type,
+            message_type,
+        }
+    }
+
+    fn session_error(error: SessionError, extension_type: u16, message_type: u8) -> Self {
+        HandlerError::Session {
+            error,
+            extension_type,
+            message_type,
+        }
+    }
+
+    fn invalid_tlv_field(error: TlvFieldError, extension_type: u16, message_type: u8) -> Self {
+        HandlerError::InvalidTlvField {
+            error,
+            extension_type,
+            message_type,
+        }
+    }
+
+    fn unknown_server(server_id: Option<usize>, extension_type: u16, message_type: u8) -> Self {
+        HandlerError::UnknownServer {
+            server_id,
+            extension_type,
+            message_type,
+        }
+    }
+
+    /// A `SetupConnection` rejected by the session is answered with `SetupConnection.Error`, other
+    /// errors not raised by the handler itself are fatal.
+    fn get_action(&self) -> HandlerAction {
+        match self {
+            HandlerError::Session { error, .. } => match error.setup_connection_error_code() {
+                Some(error_code) => HandlerAction::Reply(ErrorResponse::SetupConnection {
+                    flags: match error {
+                        SessionError::Negotiation(NegotiationError::UnsupportedFeatureFlags(
+                            flags,
+                        )) => *flags,
+                        _ => 0,
+                    },
+                    error_code,
+                }),
+                None => HandlerAction::Disconnect,
+            },
+            HandlerError::Rejected(action) => action.clone(),
+            _ => HandlerAction::Disconnect,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_messages_sv2::SetupConnectionErrorCode;
+
+    #[test]
+    fn test_session_error_action() {
+        let error = HandlerError::session_error(
+            SessionError::Negotiation(NegotiationError::UnsupportedFeatureFlags(0b10)),
+            0,
+            0,
+        );
+        assert_eq!(
+            error.get_action(),
+            HandlerAction::Reply(ErrorResponse::SetupConnection {
+                flags: 0b10,
+                error_code: SetupConnectionErrorCode::UnsupportedFeatureFlags,
+            })
+        );
+
+        let error = HandlerError::session_error(
+            SessionError::Negotiation(NegotiationError::UnsupportedProtocol(
+                Protocol::JobDeclarationProtocol,
+            )),
+            0,
+            0,
+        );
+        assert_eq!(
+            error.get_action(),
+            HandlerAction::Reply(ErrorResponse::SetupConnection {
+                flags: 0,
+                error_code: SetupConnectionErrorCode::UnsupportedProtocol,
+            })
+        );
+
+        // other session errors have no error message to answer with
+        let error = HandlerError::session_error(SessionError::SetupConnectionRequired, 0, 0x1a);
+        assert_eq!(error.get_action(), HandlerAction::Disconnect);
+        let error = HandlerError::session_error(SessionError::UnsupportedFeatureFlags(0b10), 0, 0);
+        assert_eq!(error.get_action(), HandlerAction::Disconnect);
+    }
+
+    #[test]
+    fn test_rejected_action() {
+        assert_eq!(
+            HandlerError::reply(ErrorResponse::invalid_job_id(1, 2)).get_action(),
+            HandlerAction::Reply(ErrorResponse::invalid_job_id(1, 2))
+        );
+        assert_eq!(
+            HandlerError::close_channel(1, "shutdown").get_action(),
+            HandlerAction::CloseChannel {
+                channel_id: 1,
+                reason_code: "shutdown".to_string(),
+            }
+        );
+        assert_eq!(
+            HandlerError::reconnect("pool.example", 3333).get_action(),
+            HandlerAction::Reconnect {
+                new_host: "pool.example".to_string(),
+                new_port: 3333,
+            }
+        );
+        assert_eq!(
+            HandlerError::fatal().get_action(),
+            HandlerAction::Disconnect
+        );
+    }
+
+    #[test]
+    fn test_other_errors_are_fatal() {
+        let errors = [
+            HandlerError::unexpected_message(0, 0x1a),
+            HandlerError::parse_error(ParserError::UnexpectedMessage(0xff)),
+            HandlerError::protocol_not_negotiated(Protocol::MiningProtocol, 0, 0x1a),
+            HandlerError::unknown_server(Some(1), 0, 0x1a),
+        ];
+        for error in errors {
+            assert_eq!(error.get_action(), HandlerAction::Disconnect);
+        }
+    }
 }
@@ -0,0 +1,495 @@
+use binary_sv2::Seq064K;
+use extensions_sv2::{RequestExtensions, RequestExtensionsError, RequestExtensionsSuccess};
+
+/// Errors of a `RequestExtensions` negotiation, on either side of the connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtensionsNegotiationError {
+    /// The client didn't request extensions the server requires.
+    MissingRequiredExtensions {
+        request_id: u16,
+        unsupported_extensions: Vec<u16>,
+        required_extensions: Vec<u16>,
+    },
+    /// More extensions than fit in a `Seq064K`.
+    TooManyExtensions(usize),
+    /// A new request was made while the request with this id is still unanswered.
+    RequestPending(u16),
+    /// The server answered while no request was pending.
+    NoPendingRequest,
+    /// The server answered with the id of another request.
+    RequestIdMismatch { expected: u16, received: u16 },
+    /// The server answered with extensions the client didn't request.
+    UnrequestedExtensions(Vec<u16>),
+}
+
+impl ExtensionsNegotiationError {
+    /// Returns the `RequestExtensions.Error` to answer with, for errors raised by
+    /// [`ExtensionsNegotiator::negotiate`].
+    pub fn to_request_extensions_error(&self) -> Option<RequestExtensionsError<'static>> {
+        match self {
+            ExtensionsNegotiationError::MissingRequiredExtensions {
+                request_id,
+                unsupported_extensions,
+                required_extensions,
+            } => Some(RequestExtensionsError {
+                request_id: *request_id,
+                unsupported_extensions: Seq064K::new(unsupported_extensions.clone()).ok()?,
+                required_extensions: Seq064K::new(required_extensions.clone()).ok()?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Answers `RequestExtensions` messages according to the extensions a server supports.
+///
+/// Requested extensions the server doesn't support are left out of
+/// `RequestExtensions.Success`. If the client didn't request all the extensions the server
+/// requires, [`ExtensionsNegotiationError::to_request_extensions_error`] gives the
+/// `RequestExtensions.Error` to answer with, and the client is expected to request them again.
+#[derive(Debug, Clone, Default)]
+pub struct ExtensionsNegotiator {
+    supported_extensions: Vec<u16>,
+    required_extensions: Vec<u16>,
+}
+
+impl ExtensionsNegotiator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a supported extension.
+    pub fn with_supported_extension(mut self, extension_type: u16) -> Self {
+        if !self.supported_extensions.contains(&extension_type) {
+            self.supported_extensions.push(extension_type);
+        }
+        self
+    }
+
+    /// Adds an extension clients must request, which is also supported.
+    pub fn with_required_extension(mut self, extension_type: u16) -> Self {
+        if !self.required_extensions.contains(&extension_type) {
+            self.required_extensions.push(extension_type);
+        }
+        self.with_supported_extension(extension_type)
+    }
+
+    pub fn get_supported_extensions(&self) -> &[u16] {
+        &self.supported_extensions
+    }
+
+    pub fn get_required_extensions(&self) -> &[u16] {
+        &self.required_extensions
+    }
+
+    /// Negotiates the extensions of a `RequestExtensions` received from a client, returning the
+    /// `RequestExtensions.Success` to answer with and the negotiated extensions, to be returned
+    /// by `get_negotiated_extensions_with_client`.
+    pub fn negotiate(
+        &self,
+        request_extensions: &RequestExtensions,
+    ) -> Result<(RequestExtensionsSuccess<'static>, Vec<u16>), ExtensionsNegotiationError> {
+        let request_id = request_extensions.request_id;
+        let mut requested_extensions = request_extensions.requested_extensions.clone().into_inner();
+        let mut seen = Vec::with_capacity(requested_extensions.len());
+        requested_extensions.retain(|extension_type| {
+            let first = !seen.contains(extension_type);
+            seen.push(*extension_type);
+            first
+        });
+
+        let missing_extensions: Vec<u16> = self
+            .required_extensions
+            .iter()
+            .filter(|extension_type| !requested_extensions.contains(extension_type))
+            .copied()
+            .collect();
+        let (supported_extensions, unsupported_extensions): (Vec<u16>, Vec<u16>) =
+            requested_extensions
+                .into_iter()
+                .partition(|extension_type| self.supported_extensions.contains(extension_type));
+        if !missing_extensions.is_empty() {
+            return Err(ExtensionsNegotiationError::MissingRequiredExtensions {
+                request_id,
+                unsupported_extensions,
+                required_extensions: missing_extensions,
+            });
+        }
+
+        let request_extensions_success = RequestExtensionsSuccess {
+            request_id,
+            supported_extensions: Seq064K::new(supported_extensions.clone())
+                .expect("the supported extensions are a subset of the requested ones"),
+        };
+        Ok((request_extensions_success, supported_extensions))
+    }
+}
+
+/// State of the extensions negotiation of a client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtensionsNegotiationState {
+    /// No extensions were requested yet.
+    NotRequested,
+    /// Waiting for the answer to `RequestExtensions`.
+    Pending {
+        request_id: u16,
+        requested_extensions: Vec<u16>,
+    },
+    /// The server answered with `RequestExtensions.Success`.
+    Negotiated(Vec<u16>),
+    /// The server answered with `RequestExtensions.Error`.
+    Rejected {
+        unsupported_extensions: Vec<u16>,
+        required_extensions: Vec<u16>,
+    },
+}
+
+/// Tracks the `RequestExtensions` sent by a client and checks the answers of the server.
+///
+/// TLV fields of an extension may only be sent once the server accepted it, as returned by
+/// [`ExtensionsNegotiationClient::is_negotiated`]. After a `RequestExtensions.Error`, the
+/// extensions required by the server can be requested with a new request.
+#[derive(Debug, Clone)]
+pub struct ExtensionsNegotiationClient {
+    state: ExtensionsNegotiationState,
+    next_request_id: u16,
+}
+
+impl Default for ExtensionsNegotiationClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExtensionsNegotiationClient {
+    pub fn new() -> Self {
+        Self {
+            state: ExtensionsNegotiationState::NotRequested,
+            next_request_id: 0,
+        }
+    }
+
+    pub fn get_state(&self) -> &ExtensionsNegotiationState {
+        &self.state
+    }
+
+    /// Builds the `RequestExtensions` to send, with a new request id.
+    pub fn request_extensions(
+        &mut self,
+        requested_extensions: Vec<u16>,
+    ) -> Result<RequestExtensions<'static>, ExtensionsNegotiationError> {
+        if let ExtensionsNegotiationState::Pending { request_id, .. } = self.state {
+            return Err(ExtensionsNegotiationError::RequestPending(request_id));
+        }
+        let len = requested_extensions.len();
+        let request_id = self.next_request_id;
+        let request_extensions = RequestExtensions {
+            request_id,
+            requested_extensions: Seq064K::new(requested_extensions.clone())
+                .map_err(|_| ExtensionsNegotiationError::TooManyExtensions(len))?,
+        };
+        self.next_request_id = self.next_request_id.wrapping_add(1);
+        self.state = ExtensionsNegotiationState::Pending {
+            request_id,
+            requested_extensions,
+        };
+        Ok(request_extensions)
+    }
+
+    // Checks that a response answers the pending request, and only lists requested extensions.
+    fn check_response(
+        &self,
+        received_request_id: u16,
+        extensions: &[u16],
+    ) -> Result<(), ExtensionsNegotiationError> {
+        let ExtensionsNegotiationState::Pending {
+            request_id,
+            requested_extensions,
+        } = &self.state
+        else {
+            return Err(ExtensionsNegotiationError::NoPendingRequest);
+        };
+        if *request_id != received_request_id {
+            return Err(ExtensionsNegotiationError::RequestIdMismatch {
+                expected: *request_id,
+                received: received_request_id,
+            });
+        }
+        let unrequested_extensions: Vec<u16> = extensions
+            .iter()
+            .filter(|extension_type| !requested_extensions.contains(extension_type))
+            .copied()
+            .collect();
+        if !unrequested_extensions.is_empty() {
+            return Err(ExtensionsNegotiationError::UnrequestedExtensions(
+                unrequested_extensions,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks a `RequestExtensions.Success` received in answer to the pending request, returning
+    /// the negotiated extensions.
+    pub fn on_request_extensions_success(
+        &mut self,
+        request_extensions_success: &RequestExtensionsSuccess,
+    ) -> Result<&[u16], ExtensionsNegotiationError> {
+        let supported_extensions = request_extensions_success
+            .supported_extensions
+            .clone()
+            .into_inner();
+        self.check_response(request_extensions_success.request_id, &supported_extensions)?;
+        self.state = ExtensionsNegotiationState::Negotiated(supported_extensions);
+        Ok(self.get_negotiated_extensions())
+    }
+
+    /// Checks a `RequestExtensions.Error` received in answer to the pending request.
+    ///
+    /// No extension is negotiated until a new request succeeds.
+    pub fn on_request_extensions_error(
+        &mut self,
+        request_extensions_error: &RequestExtensionsError,
+    ) -> Result<(), ExtensionsNegotiationError> {
+        let unsupported_extensions = request_extensions_error
+            .unsupported_extensions
+            .clone()
+            .into_inner();
+        self.check_response(request_extensions_error.request_id, &unsupported_extensions)?;
+        self.state = ExtensionsNegotiationState::Rejected {
+            unsupported_extensions,
+            required_extensions: request_extensions_error
+                .required_extensions
+                .clone()
+                .into_inner(),
+        };
+        Ok(())
+    }
+
+    /// Returns the extensions accepted by the server, to be returned by
+    /// `get_negotiated_extensions_with_server`. Empty until the negotiation succeeds.
+    pub fn get_negotiated_extensions(&self) -> &[u16] {
+        match &self.state {
+            ExtensionsNegotiationState::Negotiated(extensions) => extensions,
+            _ => &[],
+        }
+    }
+
+    /// Returns the extensions the server reported as required in `RequestExtensions.Error`.
+    pub fn get_required_extensions(&self) -> &[u16] {
+        match &self.state {
+            ExtensionsNegotiationState::Rejected {
+                required_extensions,
+                ..
+            } => required_extensions,
+            _ => &[],
+        }
+    }
+
+    /// Returns whether TLV fields of `extension_type` may be sent.
+    pub fn is_negotiated(&self, extension_type: u16) -> bool {
+        self.get_negotiated_extensions().contains(&extension_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request_extensions(request_id: u16, extensions: Vec<u16>) -> RequestExtensions<'static> {
+        RequestExtensions {
+            request_id,
+            requested_extensions: Seq064K::new(extensions).unwrap(),
+        }
+    }
+
+    fn request_extensions_success(
+        request_id: u16,
+        extensions: Vec<u16>,
+    ) -> RequestExtensionsSuccess<'static> {
+        RequestExtensionsSuccess {
+            request_id,
+            supported_extensions: Seq064K::new(extensions).unwrap(),
+        }
+    }
+
+    fn request_extensions_error(
+        request_id: u16,
+        unsupported_extensions: Vec<u16>,
+        required_extensions: Vec<u16>,
+    ) -> RequestExtensionsError<'static> {
+        RequestExtensionsError {
+            request_id,
+            unsupported_extensions: Seq064K::new(unsupported_extensions).unwrap(),
+            required_extensions: Seq064K::new(required_extensions).unwrap(),
+        }
+    }
+
+    // A server supporting the extensions 0x0002 and 0x0003, requiring 0x0003.
+    fn negotiator() -> ExtensionsNegotiator {
+        ExtensionsNegotiator::new()
+            .with_supported_extension(0x0002)
+            .with_required_extension(0x0003)
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let (success, negotiated_extensions) = negotiator()
+            .negotiate(&request_extensions(1, vec![0x0003, 0x0004, 0x0002, 0x0003]))
+            .unwrap();
+        // unsupported and duplicate extensions are left out
+        assert_eq!(negotiated_extensions, [0x0003, 0x0002]);
+        assert_eq!(success.request_id, 1);
+        assert_eq!(
+            success.supported_extensions.into_inner(),
+            negotiated_extensions
+        );
+    }
+
+    #[test]
+    fn test_missing_required_extension() {
+        let error = negotiator()
+            .negotiate(&request_extensions(1, vec![0x0002, 0x0004]))
+            .unwrap_err();
+        assert_eq!(
+            error,
+            ExtensionsNegotiationError::MissingRequiredExtensions {
+                request_id: 1,
+                unsupported_extensions: vec![0x0004],
+                required_extensions: vec![0x0003],
+            }
+        );
+        let request_extensions_error = error.to_request_extensions_error().unwrap();
+        assert_eq!(request_extensions_error.request_id, 1);
+        assert_eq!(
+            request_extensions_error.unsupported_extensions.into_inner(),
+            [0x0004]
+        );
+        assert_eq!(
+            request_extensions_error.required_extensions.into_inner(),
+            [0x0003]
+        );
+
+        // client side errors are not answered with `RequestExtensions.Error`
+        assert!(ExtensionsNegotiationError::NoPendingRequest
+            .to_request_extensions_error()
+            .is_none());
+    }
+
+    #[test]
+    fn test_client_negotiation() {
+        let mut client = ExtensionsNegotiationClient::new();
+        let request = client.request_extensions(vec![0x0002, 0x0003]).unwrap();
+        assert!(!client.is_negotiated(0x0002));
+
+        let (success, _) = negotiator().negotiate(&request).unwrap();
+        assert_eq!(
+            client.on_request_extensions_success(&success).unwrap(),
+            [0x0002, 0x0003]
+        );
+        assert!(client.is_negotiated(0x0003));
+        assert_eq!(
+            client.get_state(),
+            &ExtensionsNegotiationState::Negotiated(vec![0x0002, 0x0003])
+        );
+    }
+
+    #[test]
+    fn test_client_request_pending() {
+        let mut client = ExtensionsNegotiationClient::new();
+        client.request_extensions(vec![0x0002]).unwrap();
+        assert_eq!(
+            client.request_extensions(vec![0x0003]),
+            Err(ExtensionsNegotiationError::RequestPending(0))
+        );
+    }
+
+    #[test]
+    fn test_client_no_pending_request() {
+        let mut client = ExtensionsNegotiationClient::new();
+        assert_eq!(
+            client.on_request_extensions_success(&request_extensions_success(0, vec![])),
+            Err(ExtensionsNegotiationError::NoPendingRequest)
+        );
+        assert_eq!(
+            client.on_request_extensions_error(&request_extensions_error(0, vec![], vec![])),
+            Err(ExtensionsNegotiationError::NoPendingRequest)
+        );
+    }
+
+    #[test]
+    fn test_client_request_id_mismatch() {
+        let mut client = ExtensionsNegotiationClient::new();
+        let request = client.request_extensions(vec![0x0002]).unwrap();
+        let received = request.request_id.wrapping_add(1);
+        assert_eq!(
+            client
+                .on_request_extensions_success(&request_extensions_success(received, vec![0x0002])),
+            Err(ExtensionsNegotiationError::RequestIdMismatch {
+                expected: request.request_id,
+                received,
+            })
+        );
+        assert_eq!(
+            client.on_request_extensions_error(&request_extensions_error(
+                received,
+                vec![],
+                vec![0x0003]
+            )),
+            Err(ExtensionsNegotiationError::RequestIdMismatch {
+                expected: request.request_id,
+                received,
+            })
+        );
+        // the request is still pending
+        assert!(matches!(
+            client.get_state(),
+            ExtensionsNegotiationState::Pending { .. }
+        ));
+        assert!(!client.is_negotiated(0x0002));
+    }
+
+    #[test]
+    fn test_client_unrequested_extensions() {
+        let mut client = ExtensionsNegotiationClient::new();
+        let request = client.request_extensions(vec![0x0002]).unwrap();
+        assert_eq!(
+            client.on_request_extensions_success(&request_extensions_success(
+                request.request_id,
+                vec![0x0002, 0x0003]
+            )),
+            Err(ExtensionsNegotiationError::UnrequestedExtensions(vec![
+                0x0003
+            ]))
+        );
+        assert!(!client.is_negotiated(0x0002));
+    }
+
+    #[test]
+    fn test_client_required_extension_rejection() {
+        let mut client = ExtensionsNegotiationClient::new();
+        let request = client.request_extensions(vec![0x0002, 0x0004]).unwrap();
+        let error = negotiator().negotiate(&request).unwrap_err();
+        client
+            .on_request_extensions_error(&error.to_request_extensions_error().unwrap())
+            .unwrap();
+        assert_eq!(
+            client.get_state(),
+            &ExtensionsNegotiationState::Rejected {
+                unsupported_extensions: vec![0x0004],
+                required_extensions: vec![0x0003],
+            }
+        );
+        assert!(client.get_negotiated_extensions().is_empty());
+        assert_eq!(client.get_required_extensions(), [0x0003]);
+
+        // the required extensions are requested again, with a new request id
+        let mut requested_extensions = vec![0x0002];
+        requested_extensions.extend_from_slice(client.get_required_extensions());
+        let retry = client.request_extensions(requested_extensions).unwrap();
+        assert_ne!(retry.request_id, request.request_id);
+        let (success, _) = negotiator().negotiate(&retry).unwrap();
+        client.on_request_extensions_success(&success).unwrap();
+        assert!(client.is_negotiated(0x0003));
+        assert!(client.get_required_extensions().is_empty());
+    }
+}
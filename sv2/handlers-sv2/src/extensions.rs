@@ -1,8 +1,14 @@
 use extensions_sv2::{RequestExtensions, RequestExtensionsError, RequestExtensionsSuccess};
 use framing_sv2::header::Header;
-use parsers_sv2::{parse_message_frame_with_tlvs, AnyMessage, Extensions, Tlv};
+use parsers_sv2::{
+    parse_message_frame_with_registry, parse_message_frame_with_tlvs, AnyMessage, CustomMessage,
+    ExtensionRegistry, Extensions, Tlv,
+};
 
-use crate::error::HandlerErrorType;
+use crate::{
+    error::HandlerErrorType,
+    shared::{impl_async_for_shared, SharedFrames, SharedMessages},
+};
 
 /// Synchronous handler trait for processing extension messages received from servers.
 ///
@@ -27,6 +33,14 @@ pub trait HandleExtensionsFromServerSync {
         server_id: Option<usize>,
     ) -> Result<Vec<u16>, Self::Error>;
 
+    /// Returns the registry parsing the messages of third-party extensions, if any.
+    ///
+    /// Frames of the registered extensions are parsed with it, and their messages passed to
+    /// `handle_custom_message_from_server`.
+    fn get_extension_registry(&self, _server_id: Option<usize>) -> Option<&ExtensionRegistry> {
+        None
+    }
+
     /// Handles a raw Extensions protocol message frame from a server.
     ///
     /// This method parses the raw frame, extracts any TLV extension data, and delegates
@@ -38,15 +52,20 @@ pub trait HandleExtensionsFromServerSync {
         payload: &mut [u8],
     ) -> Result<(), Self::Error> {
         let negotiated_extensions = self.get_negotiated_extensions_with_server(server_id)?;
-        if negotiated_extensions.is_empty() {
+        let registry = self.get_extension_registry(server_id);
+        if negotiated_extensions.is_empty() && registry.is_none() {
             let parsed: Extensions<'_> = (header.ext_type(), header.msg_type(), payload)
                 .try_into()
                 .map_err(Self::Error::parse_error)?;
             return self.handle_extensions_message_from_server(server_id, parsed, None);
         }
-        let (parsed, tlv_fields) =
-            parse_message_frame_with_tlvs(header, payload, &negotiated_extensions)
-                .map_err(Self::Error::parse_error)?;
+        let (parsed, tlv_fields) = match registry {
+            Some(registry) => {
+                parse_message_frame_with_registry(header, payload, &negotiated_extensions, registry)
+            }
+            None => parse_message_frame_with_tlvs(header, payload, &negotiated_extensions),
+        }
+        .map_err(Self::Error::parse_error)?;
         match parsed {
             AnyMessage::Extensions(parsed) => {
                 self.handle_extensions_message_from_server(server_id, parsed, tlv_fields.as_deref())
@@ -86,6 +105,9 @@ pub trait HandleExtensionsFromServerSync {
                     ))
                 }
             },
+            Extensions::Custom(msg) => {
+                self.handle_custom_message_from_server(server_id, msg, tlv_fields)
+            }
         }
     }
 
@@ -102,6 +124,21 @@ pub trait HandleExtensionsFromServerSync {
         msg: RequestExtensionsError,
         tlv_fields: Option<&[Tlv]>,
     ) -> Result<(), Self::Error>;
+
+    /// Handles a message of an extension of the registry returned by `get_extension_registry`.
+    ///
+    /// Rejected as unexpected by default.
+    fn handle_custom_message_from_server(
+        &mut self,
+        _server_id: Option<usize>,
+        msg: CustomMessage,
+        _tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error> {
+        Err(Self::Error::unexpected_message(
+            msg.get_message().extension_type(),
+            msg.get_message().message_type(),
+        ))
+    }
 }
 
 /// Asynchronous handler trait for processing extension messages received from servers.
@@ -128,6 +165,14 @@ pub trait HandleExtensionsFromServerAsync {
         server_id: Option<usize>,
     ) -> Result<Vec<u16>, Self::Error>;
 
+    /// Returns the registry parsing the messages of third-party extensions, if any.
+    ///
+    /// Frames of the registered extensions are parsed with it, and their messages passed to
+    /// `handle_custom_message_from_server`.
+    fn get_extension_registry(&self, _server_id: Option<usize>) -> Option<&ExtensionRegistry> {
+        None
+    }
+
     /// Handles a raw Extensions protocol message frame from a server.
     ///
     /// This method parses the raw frame, extracts any TLV extension data, and delegates
@@ -140,7 +185,8 @@ pub trait HandleExtensionsFromServerAsync {
     ) -> Result<(), Self::Error> {
         async move {
             let negotiated_extensions = self.get_negotiated_extensions_with_server(server_id)?;
-            if negotiated_extensions.is_empty() {
+            let registry = self.get_extension_registry(server_id);
+            if negotiated_extensions.is_empty() && registry.is_none() {
                 let parsed: Extensions<'_> = (header.ext_type(), header.msg_type(), payload)
                     .try_into()
                     .map_err(Self::Error::parse_error)?;
@@ -148,9 +194,16 @@ pub trait HandleExtensionsFromServerAsync {
                     .handle_extensions_message_from_server(server_id, parsed, None)
                     .await;
             }
-            let (parsed, tlv_fields) =
-                parse_message_frame_with_tlvs(header, payload, &negotiated_extensions)
-                    .map_err(Self::Error::parse_error)?;
+            let (parsed, tlv_fields) = match registry {
+                Some(registry) => parse_message_frame_with_registry(
+                    header,
+                    payload,
+                    &negotiated_extensions,
+                    registry,
+                ),
+                None => parse_message_frame_with_tlvs(header, payload, &negotiated_extensions),
+            }
+            .map_err(Self::Error::parse_error)?;
             match parsed {
                 AnyMessage::Extensions(parsed) => {
                     self.handle_extensions_message_from_server(
@@ -199,6 +252,10 @@ pub trait HandleExtensionsFromServerAsync {
                         ))
                     }
                 },
+                Extensions::Custom(msg) => {
+                    self.handle_custom_message_from_server(server_id, msg, tlv_fields)
+                        .await
+                }
             }
         }
     }
@@ -216,6 +273,137 @@ pub trait HandleExtensionsFromServerAsync {
         msg: RequestExtensionsError,
         tlv_fields: Option<&[Tlv]>,
     ) -> Result<(), Self::Error>;
+
+    /// Handles a message of an extension of the registry returned by `get_extension_registry`.
+    ///
+    /// Rejected as unexpected by default.
+    async fn handle_custom_message_from_server(
+        &mut self,
+        _server_id: Option<usize>,
+        msg: CustomMessage,
+        _tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error> {
+        async move {
+            Err(Self::Error::unexpected_message(
+                msg.get_message().extension_type(),
+                msg.get_message().message_type(),
+            ))
+        }
+    }
+}
+
+/// Asynchronous `&self` handler trait for extension messages received from servers.
+///
+/// Unlike the `Async` variant, handler methods take `&self` and the context of the connection the
+/// message came from, so that a single handler can process messages from many connections in
+/// parallel. State shared between connections lives in the handler, while `Self::Context` holds
+/// the state of one connection and is mutated without locking.
+///
+/// ## TLV Extension Support
+///
+/// The `tlv_data` parameter in message handlers contains validated TLV fields if the message has
+/// extension data appended. TLV fields are only passed if they match negotiated extensions
+/// returned by `get_negotiated_extensions_with_server()`.
+#[trait_variant::make(Send)]
+pub trait HandleExtensionsFromServerShared: Sync {
+    type Error: HandlerErrorType;
+
+    /// State of a single connection, e.g. the extensions negotiated with the peer.
+    type Context: Send;
+
+    /// Returns the list of negotiated extension_types with a server.
+    ///
+    /// Return an empty Vec if no extensions have been negotiated.
+    fn get_negotiated_extensions_with_server(
+        &self,
+        ctx: &Self::Context,
+    ) -> Result<Vec<u16>, Self::Error>;
+
+    /// Returns the registry parsing the messages of third-party extensions, if any.
+    ///
+    /// Frames of the registered extensions are parsed with it, and their messages passed to
+    /// `handle_custom_message_from_server`.
+    fn get_extension_registry(&self, _ctx: &Self::Context) -> Option<&ExtensionRegistry> {
+        None
+    }
+
+    /// Handles a raw Extensions protocol message frame from a server.
+    ///
+    /// This method parses the raw frame, extracts any TLV extension data, and delegates
+    /// to `handle_extensions_message_from_server` with the parsed message and TLV fields.
+    async fn handle_extensions_message_frame_from_server(
+        &self,
+        ctx: &mut Self::Context,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedFrames { handler: self, ctx }
+                .handle_extensions_message_frame_from_server(None, header, payload)
+                .await
+        }
+    }
+
+    /// Handles a parsed extensions message from a server.
+    ///
+    /// The `tlv_fields` parameter contains parsed TLV fields if the message has extension
+    /// data appended. It will be `Some(&[Tlv])` when valid TLV data is present, or `None`
+    /// if no TLV data exists or validation fails. Each `Tlv` struct provides direct access to
+    /// `extension_type`, `field_type`, `length`, and `value`.
+    async fn handle_extensions_message_from_server(
+        &self,
+        ctx: &mut Self::Context,
+        message: Extensions<'_>,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedMessages { handler: self, ctx }
+                .handle_extensions_message_from_server(None, message, tlv_fields)
+                .await
+        }
+    }
+
+    async fn handle_request_extensions_success(
+        &self,
+        ctx: &mut Self::Context,
+        msg: RequestExtensionsSuccess,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_request_extensions_error(
+        &self,
+        ctx: &mut Self::Context,
+        msg: RequestExtensionsError,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    /// Handles a message of an extension of the registry returned by `get_extension_registry`.
+    ///
+    /// Rejected as unexpected by default.
+    async fn handle_custom_message_from_server(
+        &self,
+        _ctx: &mut Self::Context,
+        msg: CustomMessage,
+        _tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error> {
+        async move {
+            Err(Self::Error::unexpected_message(
+                msg.get_message().extension_type(),
+                msg.get_message().message_type(),
+            ))
+        }
+    }
+}
+
+impl_async_for_shared! {
+    HandleExtensionsFromServerShared => HandleExtensionsFromServerAsync {
+        async fn handle_extensions_message_from_server(Extensions<'_>);
+        fn get_negotiated_extensions_with_server(&self) -> Result<Vec<u16>, Self::Error>;
+        fn get_extension_registry(&self) -> Option<&ExtensionRegistry>;
+        async fn handle_request_extensions_success(RequestExtensionsSuccess<'_>);
+        async fn handle_request_extensions_error(RequestExtensionsError<'_>);
+        async fn handle_custom_message_from_server(CustomMessage);
+    }
 }
 
 /// Synchronous handler trait for processing extension messages received from clients.
@@ -241,6 +429,14 @@ pub trait HandleExtensionsFromClientSync {
         client_id: Option<usize>,
     ) -> Result<Vec<u16>, Self::Error>;
 
+    /// Returns the registry parsing the messages of third-party extensions, if any.
+    ///
+    /// Frames of the registered extensions are parsed with it, and their messages passed to
+    /// `handle_custom_message_from_client`.
+    fn get_extension_registry(&self, _client_id: Option<usize>) -> Option<&ExtensionRegistry> {
+        None
+    }
+
     /// Handles a raw Extensions protocol message frame from a client.
     ///
     /// This method parses the raw frame, extracts any TLV extension data, and delegates
@@ -252,15 +448,20 @@ pub trait HandleExtensionsFromClientSync {
         payload: &mut [u8],
     ) -> Result<(), Self::Error> {
         let negotiated_extensions = self.get_negotiated_extensions_with_client(client_id)?;
-        if negotiated_extensions.is_empty() {
+        let registry = self.get_extension_registry(client_id);
+        if negotiated_extensions.is_empty() && registry.is_none() {
             let parsed: Extensions<'_> = (header.ext_type(), header.msg_type(), payload)
                 .try_into()
                 .map_err(Self::Error::parse_error)?;
             return self.handle_extensions_message_from_client(client_id, parsed, None);
         }
-        let (parsed, tlv_fields) =
-            parse_message_frame_with_tlvs(header, payload, &negotiated_extensions)
-                .map_err(Self::Error::parse_error)?;
+        let (parsed, tlv_fields) = match registry {
+            Some(registry) => {
+                parse_message_frame_with_registry(header, payload, &negotiated_extensions, registry)
+            }
+            None => parse_message_frame_with_tlvs(header, payload, &negotiated_extensions),
+        }
+        .map_err(Self::Error::parse_error)?;
         match parsed {
             AnyMessage::Extensions(parsed) => {
                 self.handle_extensions_message_from_client(client_id, parsed, tlv_fields.as_deref())
@@ -303,6 +504,9 @@ pub trait HandleExtensionsFromClientSync {
                     ))
                 }
             },
+            Extensions::Custom(msg) => {
+                self.handle_custom_message_from_client(client_id, msg, tlv_fields)
+            }
         }
     }
 
@@ -312,6 +516,21 @@ pub trait HandleExtensionsFromClientSync {
         msg: RequestExtensions,
         tlv_fields: Option<&[Tlv]>,
     ) -> Result<(), Self::Error>;
+
+    /// Handles a message of an extension of the registry returned by `get_extension_registry`.
+    ///
+    /// Rejected as unexpected by default.
+    fn handle_custom_message_from_client(
+        &mut self,
+        _client_id: Option<usize>,
+        msg: CustomMessage,
+        _tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error> {
+        Err(Self::Error::unexpected_message(
+            msg.get_message().extension_type(),
+            msg.get_message().message_type(),
+        ))
+    }
 }
 
 /// Asynchronous handler trait for processing extension messages received from clients.
@@ -338,6 +557,14 @@ pub trait HandleExtensionsFromClientAsync {
         client_id: Option<usize>,
     ) -> Result<Vec<u16>, Self::Error>;
 
+    /// Returns the registry parsing the messages of third-party extensions, if any.
+    ///
+    /// Frames of the registered extensions are parsed with it, and their messages passed to
+    /// `handle_custom_message_from_client`.
+    fn get_extension_registry(&self, _client_id: Option<usize>) -> Option<&ExtensionRegistry> {
+        None
+    }
+
     /// Handles a raw Extensions protocol message frame from a client.
     ///
     /// This method parses the raw frame, extracts any TLV extension data, and delegates
@@ -350,7 +577,8 @@ pub trait HandleExtensionsFromClientAsync {
     ) -> Result<(), Self::Error> {
         async move {
             let negotiated_extensions = self.get_negotiated_extensions_with_client(client_id)?;
-            if negotiated_extensions.is_empty() {
+            let registry = self.get_extension_registry(client_id);
+            if negotiated_extensions.is_empty() && registry.is_none() {
                 let parsed: Extensions<'_> = (header.ext_type(), header.msg_type(), payload)
                     .try_into()
                     .map_err(Self::Error::parse_error)?;
@@ -358,9 +586,16 @@ pub trait HandleExtensionsFromClientAsync {
                     .handle_extensions_message_from_client(client_id, parsed, None)
                     .await;
             }
-            let (parsed, tlv_fields) =
-                parse_message_frame_with_tlvs(header, payload, &negotiated_extensions)
-                    .map_err(Self::Error::parse_error)?;
+            let (parsed, tlv_fields) = match registry {
+                Some(registry) => parse_message_frame_with_registry(
+                    header,
+                    payload,
+                    &negotiated_extensions,
+                    registry,
+                ),
+                None => parse_message_frame_with_tlvs(header, payload, &negotiated_extensions),
+            }
+            .map_err(Self::Error::parse_error)?;
             match parsed {
                 AnyMessage::Extensions(parsed) => {
                     self.handle_extensions_message_from_client(
@@ -411,6 +646,10 @@ pub trait HandleExtensionsFromClientAsync {
                         ))
                     }
                 },
+                Extensions::Custom(msg) => {
+                    self.handle_custom_message_from_client(client_id, msg, tlv_fields)
+                        .await
+                }
             }
         }
     }
@@ -421,4 +660,280 @@ pub trait HandleExtensionsFromClientAsync {
         msg: RequestExtensions,
         tlv_fields: Option<&[Tlv]>,
     ) -> Result<(), Self::Error>;
+
+    /// Handles a message of an extension of the registry returned by `get_extension_registry`.
+    ///
+    /// Rejected as unexpected by default.
+    async fn handle_custom_message_from_client(
+        &mut self,
+        _client_id: Option<usize>,
+        msg: CustomMessage,
+        _tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error> {
+        async move {
+            Err(Self::Error::unexpected_message(
+                msg.get_message().extension_type(),
+                msg.get_message().message_type(),
+            ))
+        }
+    }
+}
+
+/// Asynchronous `&self` handler trait for extension messages received from clients.
+///
+/// Unlike the `Async` variant, handler methods take `&self` and the context of the connection the
+/// message came from, so that a single handler can process messages from many connections in
+/// parallel. State shared between connections lives in the handler, while `Self::Context` holds
+/// the state of one connection and is mutated without locking.
+///
+/// ## TLV Extension Support
+///
+/// The `tlv_data` parameter in message handlers contains validated TLV fields if the message has
+/// extension data appended. TLV fields are only passed if they match negotiated extensions
+/// returned by `get_negotiated_extensions_with_client()`.
+#[trait_variant::make(Send)]
+pub trait HandleExtensionsFromClientShared: Sync {
+    type Error: HandlerErrorType;
+
+    /// State of a single connection, e.g. the extensions negotiated with the peer.
+    type Context: Send;
+
+    /// Returns the list of negotiated extension_types with a client.
+    ///
+    /// Return an empty Vec if no extensions have been negotiated.
+    fn get_negotiated_extensions_with_client(
+        &self,
+        ctx: &Self::Context,
+    ) -> Result<Vec<u16>, Self::Error>;
+
+    /// Returns the registry parsing the messages of third-party extensions, if any.
+    ///
+    /// Frames of the registered extensions are parsed with it, and their messages passed to
+    /// `handle_custom_message_from_client`.
+    fn get_extension_registry(&self, _ctx: &Self::Context) -> Option<&ExtensionRegistry> {
+        None
+    }
+
+    /// Handles a raw Extensions protocol message frame from a client.
+    ///
+    /// This method parses the raw frame, extracts any TLV extension data, and delegates
+    /// to `handle_extensions_message_from_client` with the parsed message and TLV fields.
+    async fn handle_extensions_message_frame_from_client(
+        &self,
+        ctx: &mut Self::Context,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedFrames { handler: self, ctx }
+                .handle_extensions_message_frame_from_client(None, header, payload)
+                .await
+        }
+    }
+
+    /// Handles a parsed extensions message from a client.
+    ///
+    /// The `tlv_fields` parameter contains parsed TLV fields if the message has extension
+    /// data appended. It will be `Some(&[Tlv])` when valid TLV data is present, or `None`
+    /// if no TLV data exists or validation fails. Each `Tlv` struct provides direct access to
+    /// `extension_type`, `field_type`, `length`, and `value`.
+    async fn handle_extensions_message_from_client(
+        &self,
+        ctx: &mut Self::Context,
+        message: Extensions<'_>,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedMessages { handler: self, ctx }
+                .handle_extensions_message_from_client(None, message, tlv_fields)
+                .await
+        }
+    }
+
+    async fn handle_request_extensions(
+        &self,
+        ctx: &mut Self::Context,
+        msg: RequestExtensions,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    /// Handles a message of an extension of the registry returned by `get_extension_registry`.
+    ///
+    /// Rejected as unexpected by default.
+    async fn handle_custom_message_from_client(
+        &self,
+        _ctx: &mut Self::Context,
+        msg: CustomMessage,
+        _tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error> {
+        async move {
+            Err(Self::Error::unexpected_message(
+                msg.get_message().extension_type(),
+                msg.get_message().message_type(),
+            ))
+        }
+    }
+}
+
+impl_async_for_shared! {
+    HandleExtensionsFromClientShared => HandleExtensionsFromClientAsync {
+        async fn handle_extensions_message_from_client(Extensions<'_>);
+        fn get_negotiated_extensions_with_client(&self) -> Result<Vec<u16>, Self::Error>;
+        fn get_extension_registry(&self) -> Option<&ExtensionRegistry>;
+        async fn handle_request_extensions(RequestExtensions<'_>);
+        async fn handle_custom_message_from_client(CustomMessage);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::HandlerError;
+    use core::{any::Any, fmt};
+    use parsers_sv2::{ExtensionMessage, ParserError};
+
+    const EXTENSION_TYPE_TELEMETRY: u16 = 0x4000;
+
+    #[derive(Debug)]
+    struct DeviceTelemetry {
+        temperature: u16,
+    }
+
+    impl fmt::Display for DeviceTelemetry {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "DeviceTelemetry(temperature={})", self.temperature)
+        }
+    }
+
+    impl ExtensionMessage for DeviceTelemetry {
+        fn extension_type(&self) -> u16 {
+            EXTENSION_TYPE_TELEMETRY
+        }
+        fn message_type(&self) -> u8 {
+            0x00
+        }
+        fn channel_bit(&self) -> bool {
+            false
+        }
+        fn to_bytes(&self) -> Vec<u8> {
+            self.temperature.to_le_bytes().to_vec()
+        }
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    fn decode_device_telemetry(payload: &[u8]) -> Result<CustomMessage, ParserError> {
+        let temperature = payload.get(..2).ok_or(ParserError::BadPayloadSize)?;
+        Ok(CustomMessage::new(DeviceTelemetry {
+            temperature: u16::from_le_bytes([temperature[0], temperature[1]]),
+        }))
+    }
+
+    // A server receiving the telemetry of its clients, if it knows the extension.
+    struct Server {
+        registry: Option<ExtensionRegistry>,
+        temperatures: Vec<u16>,
+    }
+
+    impl Server {
+        fn new(registry: Option<ExtensionRegistry>) -> Self {
+            Self {
+                registry,
+                temperatures: Vec::new(),
+            }
+        }
+    }
+
+    impl HandleExtensionsFromClientSync for Server {
+        type Error = HandlerError;
+
+        fn get_negotiated_extensions_with_client(
+            &self,
+            _client_id: Option<usize>,
+        ) -> Result<Vec<u16>, Self::Error> {
+            Ok(vec![EXTENSION_TYPE_TELEMETRY])
+        }
+
+        fn get_extension_registry(&self, _client_id: Option<usize>) -> Option<&ExtensionRegistry> {
+            self.registry.as_ref()
+        }
+
+        fn handle_request_extensions(
+            &mut self,
+            _client_id: Option<usize>,
+            _msg: RequestExtensions,
+            _tlv_fields: Option<&[Tlv]>,
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn handle_custom_message_from_client(
+            &mut self,
+            _client_id: Option<usize>,
+            msg: CustomMessage,
+            _tlv_fields: Option<&[Tlv]>,
+        ) -> Result<(), Self::Error> {
+            let telemetry: &DeviceTelemetry = msg.downcast_ref().unwrap();
+            self.temperatures.push(telemetry.temperature);
+            Ok(())
+        }
+    }
+
+    fn telemetry_frame() -> (Header, Vec<u8>) {
+        let payload = vec![0x2a, 0x00];
+        let header = Header::from_bytes(&[0x00, 0x40, 0x00, payload.len() as u8, 0, 0]).unwrap();
+        (header, payload)
+    }
+
+    #[test]
+    fn test_custom_message_is_parsed_with_the_registry() {
+        let mut registry = ExtensionRegistry::new();
+        registry
+            .register_extension(EXTENSION_TYPE_TELEMETRY, "Telemetry")
+            .unwrap();
+        registry
+            .register_message(
+                EXTENSION_TYPE_TELEMETRY,
+                0x00,
+                "DeviceTelemetry",
+                decode_device_telemetry,
+            )
+            .unwrap();
+        let mut server = Server::new(Some(registry));
+
+        let (header, mut payload) = telemetry_frame();
+        server
+            .handle_extensions_message_frame_from_client(None, header, &mut payload)
+            .unwrap();
+        assert_eq!(server.temperatures, [42]);
+    }
+
+    #[test]
+    fn test_custom_message_without_registry() {
+        let mut server = Server::new(None);
+
+        let (header, mut payload) = telemetry_frame();
+        let error = server
+            .handle_extensions_message_frame_from_client(None, header, &mut payload)
+            .unwrap_err();
+        assert!(matches!(error, HandlerError::Parse(_)));
+        assert!(server.temperatures.is_empty());
+
+        // without an override, custom messages are unexpected
+        let error = HandleExtensionsFromServerSync::handle_extensions_message_from_server(
+            &mut crate::testing::RecordingHandler::new(),
+            None,
+            Extensions::Custom(CustomMessage::new(DeviceTelemetry { temperature: 42 })),
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::UnexpectedMessage {
+                extension_type: EXTENSION_TYPE_TELEMETRY,
+                message_type: 0x00,
+            }
+        ));
+    }
 }
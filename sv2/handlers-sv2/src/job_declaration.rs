@@ -7,7 +7,10 @@ use job_declaration_sv2::{
 };
 use parsers_sv2::{parse_message_frame_with_tlvs, AnyMessage, JobDeclaration, Tlv};
 
-use crate::error::HandlerErrorType;
+use crate::{
+    error::HandlerErrorType,
+    shared::{impl_async_for_shared, SharedFrames, SharedMessages},
+};
 
 /// Synchronous handler trait for processing job declaration messages received from servers.
 ///
@@ -285,6 +288,110 @@ pub trait HandleJobDeclarationMessagesFromServerAsync {
     ) -> Result<(), Self::Error>;
 }
 
+/// Asynchronous `&self` handler trait for job declaration messages received from servers.
+///
+/// Unlike the `Async` variant, handler methods take `&self` and the context of the connection the
+/// message came from, so that a single handler can process messages from many connections in
+/// parallel. State shared between connections lives in the handler, behind whatever
+/// synchronization fits it (fine-grained locks, actors, ...), while `Self::Context` holds the
+/// state of one connection and is mutated without locking.
+///
+/// ## TLV Extension Support
+///
+/// The `tlv_data` parameter in message handlers contains validated TLV fields if the message has
+/// extension data appended. TLV fields are only passed if they match negotiated extensions
+/// returned by `get_negotiated_extensions_with_server()`.
+#[trait_variant::make(Send)]
+pub trait HandleJobDeclarationMessagesFromServerShared: Sync {
+    type Error: HandlerErrorType;
+
+    /// State of a single connection, e.g. the extensions negotiated with the peer.
+    type Context: Send;
+
+    /// Returns the list of negotiated extension_types with a server.
+    ///
+    /// Return an empty Vec if no extensions have been negotiated.
+    fn get_negotiated_extensions_with_server(
+        &self,
+        ctx: &Self::Context,
+    ) -> Result<Vec<u16>, Self::Error>;
+
+    /// Handles a raw Job Declaration protocol message frame from a server.
+    ///
+    /// This method parses the raw frame, extracts any TLV extension data, and delegates
+    /// to `handle_job_declaration_message_from_server` with the parsed message and TLV fields.
+    async fn handle_job_declaration_message_frame_from_server(
+        &self,
+        ctx: &mut Self::Context,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedFrames { handler: self, ctx }
+                .handle_job_declaration_message_frame_from_server(None, header, payload)
+                .await
+        }
+    }
+
+    /// Handles a parsed job declaration message from a server.
+    ///
+    /// The `tlv_fields` parameter contains parsed TLV fields if the message has extension
+    /// data appended. It will be `Some(&[Tlv])` when valid TLV data is present, or `None`
+    /// if no TLV data exists or validation fails. Each `Tlv` struct provides direct access to
+    /// `extension_type`, `field_type`, `length`, and `value`.
+    async fn handle_job_declaration_message_from_server(
+        &self,
+        ctx: &mut Self::Context,
+        message: JobDeclaration<'_>,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedMessages { handler: self, ctx }
+                .handle_job_declaration_message_from_server(None, message, tlv_fields)
+                .await
+        }
+    }
+
+    async fn handle_allocate_mining_job_token_success(
+        &self,
+        ctx: &mut Self::Context,
+        msg: AllocateMiningJobTokenSuccess,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_declare_mining_job_success(
+        &self,
+        ctx: &mut Self::Context,
+        msg: DeclareMiningJobSuccess,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_declare_mining_job_error(
+        &self,
+        ctx: &mut Self::Context,
+        msg: DeclareMiningJobError,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_provide_missing_transactions(
+        &self,
+        ctx: &mut Self::Context,
+        msg: ProvideMissingTransactions,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+}
+
+impl_async_for_shared! {
+    HandleJobDeclarationMessagesFromServerShared => HandleJobDeclarationMessagesFromServerAsync {
+        async fn handle_job_declaration_message_from_server(JobDeclaration<'_>);
+        fn get_negotiated_extensions_with_server(&self) -> Result<Vec<u16>, Self::Error>;
+        async fn handle_allocate_mining_job_token_success(AllocateMiningJobTokenSuccess<'_>);
+        async fn handle_declare_mining_job_success(DeclareMiningJobSuccess<'_>);
+        async fn handle_declare_mining_job_error(DeclareMiningJobError<'_>);
+        async fn handle_provide_missing_transactions(ProvideMissingTransactions<'_>);
+    }
+}
+
 /// Synchronous handler trait for processing job declaration messages received from clients.
 ///
 /// The client ID identifies which client a message originated from.
@@ -557,3 +664,107 @@ pub trait HandleJobDeclarationMessagesFromClientAsync {
         tlv_fields: Option<&[Tlv]>,
     ) -> Result<(), Self::Error>;
 }
+
+/// Asynchronous `&self` handler trait for job declaration messages received from clients.
+///
+/// Unlike the `Async` variant, handler methods take `&self` and the context of the connection the
+/// message came from, so that a single handler can process messages from many connections in
+/// parallel. State shared between connections lives in the handler, behind whatever
+/// synchronization fits it (fine-grained locks, actors, ...), while `Self::Context` holds the
+/// state of one connection and is mutated without locking.
+///
+/// ## TLV Extension Support
+///
+/// The `tlv_data` parameter in message handlers contains validated TLV fields if the message has
+/// extension data appended. TLV fields are only passed if they match negotiated extensions
+/// returned by `get_negotiated_extensions_with_client()`.
+#[trait_variant::make(Send)]
+pub trait HandleJobDeclarationMessagesFromClientShared: Sync {
+    type Error: HandlerErrorType;
+
+    /// State of a single connection, e.g. the extensions negotiated with the peer.
+    type Context: Send;
+
+    /// Returns the list of negotiated extension_types with a client.
+    ///
+    /// Return an empty Vec if no extensions have been negotiated.
+    fn get_negotiated_extensions_with_client(
+        &self,
+        ctx: &Self::Context,
+    ) -> Result<Vec<u16>, Self::Error>;
+
+    /// Handles a raw Job Declaration protocol message frame from a client.
+    ///
+    /// This method parses the raw frame, extracts any TLV extension data, and delegates
+    /// to `handle_job_declaration_message_from_client` with the parsed message and TLV fields.
+    async fn handle_job_declaration_message_frame_from_client(
+        &self,
+        ctx: &mut Self::Context,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedFrames { handler: self, ctx }
+                .handle_job_declaration_message_frame_from_client(None, header, payload)
+                .await
+        }
+    }
+
+    /// Handles a parsed job declaration message from a client.
+    ///
+    /// The `tlv_fields` parameter contains parsed TLV fields if the message has extension
+    /// data appended. It will be `Some(&[Tlv])` when valid TLV data is present, or `None`
+    /// if no TLV data exists or validation fails. Each `Tlv` struct provides direct access to
+    /// `extension_type`, `field_type`, `length`, and `value`.
+    async fn handle_job_declaration_message_from_client(
+        &self,
+        ctx: &mut Self::Context,
+        message: JobDeclaration<'_>,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedMessages { handler: self, ctx }
+                .handle_job_declaration_message_from_client(None, message, tlv_fields)
+                .await
+        }
+    }
+
+    async fn handle_allocate_mining_job_token(
+        &self,
+        ctx: &mut Self::Context,
+        msg: AllocateMiningJobToken,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_declare_mining_job(
+        &self,
+        ctx: &mut Self::Context,
+        msg: DeclareMiningJob,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_provide_missing_transactions_success(
+        &self,
+        ctx: &mut Self::Context,
+        msg: ProvideMissingTransactionsSuccess,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_push_solution(
+        &self,
+        ctx: &mut Self::Context,
+        msg: PushSolution,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+}
+
+impl_async_for_shared! {
+    HandleJobDeclarationMessagesFromClientShared => HandleJobDeclarationMessagesFromClientAsync {
+        async fn handle_job_declaration_message_from_client(JobDeclaration<'_>);
+        fn get_negotiated_extensions_with_client(&self) -> Result<Vec<u16>, Self::Error>;
+        async fn handle_allocate_mining_job_token(AllocateMiningJobToken<'_>);
+        async fn handle_declare_mining_job(DeclareMiningJob<'_>);
+        async fn handle_provide_missing_transactions_success(ProvideMissingTransactionsSuccess<'_>);
+        async fn handle_push_solution(PushSolution<'_>);
+    }
+}
@@ -1,35 +1,89 @@
+mod action;
 mod common;
+mod dispatcher;
 mod error;
+mod extension_negotiation;
 mod extensions;
 mod job_declaration;
+mod middleware;
 mod mining;
+mod multi_upstream;
+mod negotiation;
+mod outbound;
+mod session;
+mod shared;
 mod template_distribution;
+#[cfg(any(test, feature = "test_utils"))]
+pub mod testing;
+mod tlv_fields;
 
-pub use error::HandlerErrorType;
+pub use action::{ErrorResponse, HandlerAction};
+pub use error::{HandlerError, HandlerErrorType};
+
+pub use negotiation::{
+    NegotiatedConnection, NegotiationError, ProtocolCapabilities, SetupConnectionNegotiator,
+};
+
+pub use extension_negotiation::{
+    ExtensionsNegotiationClient, ExtensionsNegotiationError, ExtensionsNegotiationState,
+    ExtensionsNegotiator,
+};
+
+pub use session::{Session, SessionError, SessionState};
+
+pub use dispatcher::{
+    route, DispatchFramesFromClientAsync, DispatchFramesFromClientSync,
+    DispatchFramesFromServerAsync, DispatchFramesFromServerSync, FrameFuture,
+    FrameHandlerFromClientAsync, FrameHandlerFromServerAsync, FrameRoute,
+};
+
+pub use middleware::{
+    HandlerMiddlewareAsync, HandlerMiddlewareSync, InterceptMessagesFromClientAsync,
+    InterceptMessagesFromClientSync, InterceptMessagesFromServerAsync,
+    InterceptMessagesFromServerSync, MiddlewareChain,
+};
+
+pub use outbound::{
+    ChannelParameters, ChannelType, OutboundError, ValidateJobDeclarationMessagesToClient,
+    ValidateJobDeclarationMessagesToServer, ValidateMiningMessagesToClient,
+    ValidateMiningMessagesToServer, ValidateTemplateDistributionMessagesToClient,
+    ValidateTemplateDistributionMessagesToServer,
+};
 
 pub use common::{
-    HandleCommonMessagesFromClientAsync, HandleCommonMessagesFromClientSync,
-    HandleCommonMessagesFromServerAsync, HandleCommonMessagesFromServerSync,
+    HandleCommonMessagesFromClientAsync, HandleCommonMessagesFromClientShared,
+    HandleCommonMessagesFromClientSync, HandleCommonMessagesFromServerAsync,
+    HandleCommonMessagesFromServerShared, HandleCommonMessagesFromServerSync,
 };
 
 pub use mining::{
-    HandleMiningMessagesFromClientAsync, HandleMiningMessagesFromClientSync,
-    HandleMiningMessagesFromServerAsync, HandleMiningMessagesFromServerSync, SupportedChannelTypes,
+    HandleMiningMessagesFromClientAsync, HandleMiningMessagesFromClientShared,
+    HandleMiningMessagesFromClientSync, HandleMiningMessagesFromServerAsync,
+    HandleMiningMessagesFromServerShared, HandleMiningMessagesFromServerSync,
+    SupportedChannelTypes,
 };
 
+pub use multi_upstream::MultiUpstreamHandler;
+
+pub use tlv_fields::{HandleTlvFieldsAsync, HandleTlvFieldsSync, TlvFieldError, TlvFieldsLayer};
+
 pub use template_distribution::{
     HandleTemplateDistributionMessagesFromClientAsync,
+    HandleTemplateDistributionMessagesFromClientShared,
     HandleTemplateDistributionMessagesFromClientSync,
     HandleTemplateDistributionMessagesFromServerAsync,
+    HandleTemplateDistributionMessagesFromServerShared,
     HandleTemplateDistributionMessagesFromServerSync,
 };
 
 pub use job_declaration::{
-    HandleJobDeclarationMessagesFromClientAsync, HandleJobDeclarationMessagesFromClientSync,
-    HandleJobDeclarationMessagesFromServerAsync, HandleJobDeclarationMessagesFromServerSync,
+    HandleJobDeclarationMessagesFromClientAsync, HandleJobDeclarationMessagesFromClientShared,
+    HandleJobDeclarationMessagesFromClientSync, HandleJobDeclarationMessagesFromServerAsync,
+    HandleJobDeclarationMessagesFromServerShared, HandleJobDeclarationMessagesFromServerSync,
 };
 
 pub use extensions::{
-    HandleExtensionsFromClientAsync, HandleExtensionsFromClientSync,
-    HandleExtensionsFromServerAsync, HandleExtensionsFromServerSync,
+    HandleExtensionsFromClientAsync, HandleExtensionsFromClientShared,
+    HandleExtensionsFromClientSync, HandleExtensionsFromServerAsync,
+    HandleExtensionsFromServerShared, HandleExtensionsFromServerSync,
 };
@@ -0,0 +1,754 @@
+use framing_sv2::header::Header;
+use parsers_sv2::{parse_message_frame_with_tlvs, AnyMessage, Tlv};
+
+use crate::error::HandlerErrorType;
+
+/// Synchronous layer run around the handling of every inbound message.
+///
+/// A middleware sees the frame header, the parsed message and its TLV fields before they reach
+/// the handler. It passes the message on by returning `Ok(())`, possibly after modifying the
+/// message or the TLV fields in place, or rejects it by returning an error, in which case the
+/// handler is not called.
+///
+/// `on_message_handled` is called with the result of the handler once it returns, which is
+/// where latency metrics or audit records are completed.
+///
+/// The peer ID identifies which peer a message originated from, as for the handler traits.
+///
+/// Layers are composed with [`MiddlewareChain`].
+pub trait HandlerMiddlewareSync {
+    type Error: HandlerErrorType;
+
+    /// Called before a message is handled.
+    fn on_message(
+        &mut self,
+        _peer_id: Option<usize>,
+        _header: &Header,
+        _message: &mut AnyMessage<'_>,
+        _tlv_fields: &mut Option<Vec<Tlv>>,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called after a message passed by `on_message` is handled (or rejected by an inner layer).
+    fn on_message_handled(
+        &mut self,
+        _peer_id: Option<usize>,
+        _header: &Header,
+        _result: &Result<(), Self::Error>,
+    ) {
+    }
+}
+
+/// Asynchronous layer run around the handling of every inbound message.
+///
+/// A middleware sees the frame header, the parsed message and its TLV fields before they reach
+/// the handler. It passes the message on by returning `Ok(())`, possibly after modifying the
+/// message or the TLV fields in place, or rejects it by returning an error, in which case the
+/// handler is not called.
+///
+/// `on_message_handled` is called with the result of the handler once it returns, which is
+/// where latency metrics or audit records are completed. It is synchronous so that the result
+/// doesn't have to be held across an await point.
+///
+/// The peer ID identifies which peer a message originated from, as for the handler traits.
+///
+/// Layers are composed with [`MiddlewareChain`].
+#[trait_variant::make(Send)]
+pub trait HandlerMiddlewareAsync {
+    type Error: HandlerErrorType;
+
+    /// Called before a message is handled.
+    async fn on_message(
+        &mut self,
+        _peer_id: Option<usize>,
+        _header: &Header,
+        _message: &mut AnyMessage<'_>,
+        _tlv_fields: &mut Option<Vec<Tlv>>,
+    ) -> Result<(), Self::Error> {
+        async move { Ok(()) }
+    }
+
+    /// Called after a message passed by `on_message` is handled (or rejected by an inner layer).
+    fn on_message_handled(
+        &mut self,
+        _peer_id: Option<usize>,
+        _header: &Header,
+        _result: &Result<(), Self::Error>,
+    ) {
+    }
+}
+
+/// Two middleware layers run one around the other.
+///
+/// `on_message` runs the outer layer first, and the inner one only if the outer one passed the
+/// message. `on_message_handled` runs in the opposite order, so that the outer layer also sees
+/// the messages rejected by the inner one. Longer chains are built by nesting chains.
+#[derive(Debug, Clone)]
+pub struct MiddlewareChain<Outer, Inner> {
+    outer: Outer,
+    inner: Inner,
+}
+
+impl<Outer, Inner> MiddlewareChain<Outer, Inner> {
+    pub fn new(outer: Outer, inner: Inner) -> Self {
+        Self { outer, inner }
+    }
+
+    pub fn get_outer(&mut self) -> &mut Outer {
+        &mut self.outer
+    }
+
+    pub fn get_inner(&mut self) -> &mut Inner {
+        &mut self.inner
+    }
+
+    /// Wraps the chain into another layer, run after the layers of this chain.
+    pub fn with<Next>(self, next: Next) -> MiddlewareChain<Self, Next> {
+        MiddlewareChain::new(self, next)
+    }
+}
+
+impl<E, Outer, Inner> HandlerMiddlewareSync for MiddlewareChain<Outer, Inner>
+where
+    E: HandlerErrorType,
+    Outer: HandlerMiddlewareSync<Error = E>,
+    Inner: HandlerMiddlewareSync<Error = E>,
+{
+    type Error = E;
+
+    fn on_message(
+        &mut self,
+        peer_id: Option<usize>,
+        header: &Header,
+        message: &mut AnyMessage<'_>,
+        tlv_fields: &mut Option<Vec<Tlv>>,
+    ) -> Result<(), E> {
+        self.outer
+            .on_message(peer_id, header, message, tlv_fields)?;
+        let result = self.inner.on_message(peer_id, header, message, tlv_fields);
+        if result.is_err() {
+            self.outer.on_message_handled(peer_id, header, &result);
+        }
+        result
+    }
+
+    fn on_message_handled(
+        &mut self,
+        peer_id: Option<usize>,
+        header: &Header,
+        result: &Result<(), E>,
+    ) {
+        self.inner.on_message_handled(peer_id, header, result);
+        self.outer.on_message_handled(peer_id, header, result);
+    }
+}
+
+impl<E, Outer, Inner> HandlerMiddlewareAsync for MiddlewareChain<Outer, Inner>
+where
+    E: HandlerErrorType,
+    Outer: HandlerMiddlewareAsync<Error = E> + Send,
+    Inner: HandlerMiddlewareAsync<Error = E> + Send,
+{
+    type Error = E;
+
+    async fn on_message(
+        &mut self,
+        peer_id: Option<usize>,
+        header: &Header,
+        message: &mut AnyMessage<'_>,
+        tlv_fields: &mut Option<Vec<Tlv>>,
+    ) -> Result<(), E> {
+        self.outer
+            .on_message(peer_id, header, message, tlv_fields)
+            .await?;
+        let result = self
+            .inner
+            .on_message(peer_id, header, message, tlv_fields)
+            .await;
+        if result.is_err() {
+            self.outer.on_message_handled(peer_id, header, &result);
+        }
+        result
+    }
+
+    fn on_message_handled(
+        &mut self,
+        peer_id: Option<usize>,
+        header: &Header,
+        result: &Result<(), E>,
+    ) {
+        self.inner.on_message_handled(peer_id, header, result);
+        self.outer.on_message_handled(peer_id, header, result);
+    }
+}
+
+/// Synchronous trait for running the messages received from servers through a middleware.
+///
+/// `handle_message_frame_from_server` parses a raw frame with its TLV fields, runs it through the
+/// middleware returned by `get_middleware`, and passes the resulting message to
+/// `handle_message_from_server`. Implementors forward it to the `handle_*_message_from_server`
+/// method of the matching handler trait, e.g. `HandleMiningMessagesFromServerSync`.
+///
+/// The server ID identifies which server a message originated from.
+/// Whether this is relevant or not depends on which object is implementing the trait, and whether
+/// this contextual information is readily available or not. In cases where `server_id` is either
+/// irrelevant or can be inferred without the context, this should always be `None`.
+pub trait InterceptMessagesFromServerSync {
+    type Error: HandlerErrorType;
+    type Middleware: HandlerMiddlewareSync<Error = Self::Error>;
+
+    /// Returns the middleware messages from servers go through.
+    fn get_middleware(&mut self) -> &mut Self::Middleware;
+
+    /// Returns the list of negotiated extension_types with a server.
+    ///
+    /// Return an empty Vec if no extensions have been negotiated.
+    fn get_negotiated_extensions_with_server(
+        &self,
+        server_id: Option<usize>,
+    ) -> Result<Vec<u16>, Self::Error>;
+
+    /// Handles a raw message frame from a server, running it through the middleware.
+    fn handle_message_frame_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let negotiated_extensions = self.get_negotiated_extensions_with_server(server_id)?;
+        let (mut message, mut tlv_fields) =
+            parse_message_frame_with_tlvs(header, payload, &negotiated_extensions)
+                .map_err(Self::Error::parse_error)?;
+        self.get_middleware()
+            .on_message(server_id, &header, &mut message, &mut tlv_fields)?;
+        let result = self.handle_message_from_server(server_id, message, tlv_fields.as_deref());
+        self.get_middleware()
+            .on_message_handled(server_id, &header, &result);
+        result
+    }
+
+    /// Handles a message from a server passed by the middleware.
+    fn handle_message_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        message: AnyMessage<'_>,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+}
+
+/// Asynchronous trait for running the messages received from servers through a middleware.
+///
+/// `handle_message_frame_from_server` parses a raw frame with its TLV fields, runs it through the
+/// middleware returned by `get_middleware`, and passes the resulting message to
+/// `handle_message_from_server`. Implementors forward it to the `handle_*_message_from_server`
+/// method of the matching handler trait, e.g. `HandleMiningMessagesFromServerAsync`.
+///
+/// The server ID identifies which server a message originated from.
+/// Whether this is relevant or not depends on which object is implementing the trait, and whether
+/// this contextual information is readily available or not. In cases where `server_id` is either
+/// irrelevant or can be inferred without the context, this should always be `None`.
+#[trait_variant::make(Send)]
+pub trait InterceptMessagesFromServerAsync {
+    type Error: HandlerErrorType;
+    type Middleware: HandlerMiddlewareAsync<Error = Self::Error> + Send;
+
+    /// Returns the middleware messages from servers go through.
+    fn get_middleware(&mut self) -> &mut Self::Middleware;
+
+    /// Returns the list of negotiated extension_types with a server.
+    ///
+    /// Return an empty Vec if no extensions have been negotiated.
+    fn get_negotiated_extensions_with_server(
+        &self,
+        server_id: Option<usize>,
+    ) -> Result<Vec<u16>, Self::Error>;
+
+    /// Handles a raw message frame from a server, running it through the middleware.
+    async fn handle_message_frame_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            let negotiated_extensions = self.get_negotiated_extensions_with_server(server_id)?;
+            let (mut message, mut tlv_fields) =
+                parse_message_frame_with_tlvs(header, payload, &negotiated_extensions)
+                    .map_err(Self::Error::parse_error)?;
+            self.get_middleware()
+                .on_message(server_id, &header, &mut message, &mut tlv_fields)
+                .await?;
+            let result = self
+                .handle_message_from_server(server_id, message, tlv_fields.as_deref())
+                .await;
+            self.get_middleware()
+                .on_message_handled(server_id, &header, &result);
+            result
+        }
+    }
+
+    /// Handles a message from a server passed by the middleware.
+    async fn handle_message_from_server(
+        &mut self,
+        server_id: Option<usize>,
+        message: AnyMessage<'_>,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+}
+
+/// Synchronous trait for running the messages received from clients through a middleware.
+///
+/// `handle_message_frame_from_client` parses a raw frame with its TLV fields, runs it through the
+/// middleware returned by `get_middleware`, and passes the resulting message to
+/// `handle_message_from_client`. Implementors forward it to the `handle_*_message_from_client`
+/// method of the matching handler trait, e.g. `HandleMiningMessagesFromClientSync`.
+///
+/// The client ID identifies which client a message originated from.
+/// Whether this is relevant or not depends on which object is implementing the trait, and whether
+/// this contextual information is readily available or not. In cases where `client_id` is either
+/// irrelevant or can be inferred without the context, this should always be `None`.
+pub trait InterceptMessagesFromClientSync {
+    type Error: HandlerErrorType;
+    type Middleware: HandlerMiddlewareSync<Error = Self::Error>;
+
+    /// Returns the middleware messages from clients go through.
+    fn get_middleware(&mut self) -> &mut Self::Middleware;
+
+    /// Returns the list of negotiated extension_types with a client.
+    ///
+    /// Return an empty Vec if no extensions have been negotiated.
+    fn get_negotiated_extensions_with_client(
+        &self,
+        client_id: Option<usize>,
+    ) -> Result<Vec<u16>, Self::Error>;
+
+    /// Handles a raw message frame from a client, running it through the middleware.
+    fn handle_message_frame_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let negotiated_extensions = self.get_negotiated_extensions_with_client(client_id)?;
+        let (mut message, mut tlv_fields) =
+            parse_message_frame_with_tlvs(header, payload, &negotiated_extensions)
+                .map_err(Self::Error::parse_error)?;
+        self.get_middleware()
+            .on_message(client_id, &header, &mut message, &mut tlv_fields)?;
+        let result = self.handle_message_from_client(client_id, message, tlv_fields.as_deref());
+        self.get_middleware()
+            .on_message_handled(client_id, &header, &result);
+        result
+    }
+
+    /// Handles a message from a client passed by the middleware.
+    fn handle_message_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        message: AnyMessage<'_>,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+}
+
+/// Asynchronous trait for running the messages received from clients through a middleware.
+///
+/// `handle_message_frame_from_client` parses a raw frame with its TLV fields, runs it through the
+/// middleware returned by `get_middleware`, and passes the resulting message to
+/// `handle_message_from_client`. Implementors forward it to the `handle_*_message_from_client`
+/// method of the matching handler trait, e.g. `HandleMiningMessagesFromClientAsync`.
+///
+/// The client ID identifies which client a message originated from.
+/// Whether this is relevant or not depends on which object is implementing the trait, and whether
+/// this contextual information is readily available or not. In cases where `client_id` is either
+/// irrelevant or can be inferred without the context, this should always be `None`.
+#[trait_variant::make(Send)]
+pub trait InterceptMessagesFromClientAsync {
+    type Error: HandlerErrorType;
+    type Middleware: HandlerMiddlewareAsync<Error = Self::Error> + Send;
+
+    /// Returns the middleware messages from clients go through.
+    fn get_middleware(&mut self) -> &mut Self::Middleware;
+
+    /// Returns the list of negotiated extension_types with a client.
+    ///
+    /// Return an empty Vec if no extensions have been negotiated.
+    fn get_negotiated_extensions_with_client(
+        &self,
+        client_id: Option<usize>,
+    ) -> Result<Vec<u16>, Self::Error>;
+
+    /// Handles a raw message frame from a client, running it through the middleware.
+    async fn handle_message_frame_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            let negotiated_extensions = self.get_negotiated_extensions_with_client(client_id)?;
+            let (mut message, mut tlv_fields) =
+                parse_message_frame_with_tlvs(header, payload, &negotiated_extensions)
+                    .map_err(Self::Error::parse_error)?;
+            self.get_middleware()
+                .on_message(client_id, &header, &mut message, &mut tlv_fields)
+                .await?;
+            let result = self
+                .handle_message_from_client(client_id, message, tlv_fields.as_deref())
+                .await;
+            self.get_middleware()
+                .on_message_handled(client_id, &header, &result);
+            result
+        }
+    }
+
+    /// Handles a message from a client passed by the middleware.
+    async fn handle_message_from_client(
+        &mut self,
+        client_id: Option<usize>,
+        message: AnyMessage<'_>,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::HandlerError,
+        testing::{block_on, encode_frame},
+    };
+    use common_messages_sv2::ChannelEndpointChanged;
+    use parsers_sv2::CommonMessages;
+    use std::sync::{Arc, Mutex};
+
+    type Events = Arc<Mutex<Vec<String>>>;
+
+    // A layer logging what it sees, rejecting every message if `reject` is set.
+    struct Layer {
+        name: &'static str,
+        reject: bool,
+        events: Events,
+    }
+
+    impl Layer {
+        fn new(name: &'static str, reject: bool, events: &Events) -> Self {
+            Self {
+                name,
+                reject,
+                events: events.clone(),
+            }
+        }
+
+        fn on_message(&mut self) -> Result<(), HandlerError> {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("{} on_message", self.name));
+            match self.reject {
+                true => Err(HandlerError::fatal()),
+                false => Ok(()),
+            }
+        }
+
+        fn on_message_handled(&mut self, result: &Result<(), HandlerError>) {
+            let result = match result {
+                Ok(()) => "ok",
+                Err(_) => "err",
+            };
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("{} handled {}", self.name, result));
+        }
+    }
+
+    impl HandlerMiddlewareSync for Layer {
+        type Error = HandlerError;
+
+        fn on_message(
+            &mut self,
+            _peer_id: Option<usize>,
+            _header: &Header,
+            _message: &mut AnyMessage<'_>,
+            _tlv_fields: &mut Option<Vec<Tlv>>,
+        ) -> Result<(), HandlerError> {
+            Layer::on_message(self)
+        }
+
+        fn on_message_handled(
+            &mut self,
+            _peer_id: Option<usize>,
+            _header: &Header,
+            result: &Result<(), HandlerError>,
+        ) {
+            Layer::on_message_handled(self, result)
+        }
+    }
+
+    impl HandlerMiddlewareAsync for Layer {
+        type Error = HandlerError;
+
+        async fn on_message(
+            &mut self,
+            _peer_id: Option<usize>,
+            _header: &Header,
+            _message: &mut AnyMessage<'_>,
+            _tlv_fields: &mut Option<Vec<Tlv>>,
+        ) -> Result<(), HandlerError> {
+            Layer::on_message(self)
+        }
+
+        fn on_message_handled(
+            &mut self,
+            _peer_id: Option<usize>,
+            _header: &Header,
+            result: &Result<(), HandlerError>,
+        ) {
+            Layer::on_message_handled(self, result)
+        }
+    }
+
+    // A server whose handler logs the messages it handles, failing if `reject` is set.
+    struct Server<M> {
+        middleware: M,
+        reject: bool,
+        events: Events,
+    }
+
+    impl<M> Server<M> {
+        fn handle_message(&mut self) -> Result<(), HandlerError> {
+            self.events.lock().unwrap().push("handler".to_string());
+            match self.reject {
+                true => Err(HandlerError::fatal()),
+                false => Ok(()),
+            }
+        }
+    }
+
+    impl<M: HandlerMiddlewareSync<Error = HandlerError>> InterceptMessagesFromClientSync for Server<M> {
+        type Error = HandlerError;
+        type Middleware = M;
+
+        fn get_middleware(&mut self) -> &mut M {
+            &mut self.middleware
+        }
+
+        fn get_negotiated_extensions_with_client(
+            &self,
+            _client_id: Option<usize>,
+        ) -> Result<Vec<u16>, HandlerError> {
+            Ok(vec![])
+        }
+
+        fn handle_message_from_client(
+            &mut self,
+            _client_id: Option<usize>,
+            _message: AnyMessage<'_>,
+            _tlv_fields: Option<&[Tlv]>,
+        ) -> Result<(), HandlerError> {
+            self.handle_message()
+        }
+    }
+
+    impl<M: HandlerMiddlewareAsync<Error = HandlerError> + Send> InterceptMessagesFromClientAsync
+        for Server<M>
+    {
+        type Error = HandlerError;
+        type Middleware = M;
+
+        fn get_middleware(&mut self) -> &mut M {
+            &mut self.middleware
+        }
+
+        fn get_negotiated_extensions_with_client(
+            &self,
+            _client_id: Option<usize>,
+        ) -> Result<Vec<u16>, HandlerError> {
+            Ok(vec![])
+        }
+
+        async fn handle_message_from_client(
+            &mut self,
+            _client_id: Option<usize>,
+            _message: AnyMessage<'_>,
+            _tlv_fields: Option<&[Tlv]>,
+        ) -> Result<(), HandlerError> {
+            self.handle_message()
+        }
+    }
+
+    fn new_server(
+        outer_rejects: bool,
+        inner_rejects: bool,
+        handler_rejects: bool,
+    ) -> Server<MiddlewareChain<Layer, Layer>> {
+        let events = Events::default();
+        Server {
+            middleware: MiddlewareChain::new(
+                Layer::new("outer", outer_rejects, &events),
+                Layer::new("inner", inner_rejects, &events),
+            ),
+            reject: handler_rejects,
+            events,
+        }
+    }
+
+    fn frame() -> (Header, Vec<u8>) {
+        encode_frame(AnyMessage::Common(CommonMessages::ChannelEndpointChanged(
+            ChannelEndpointChanged { channel_id: 1 },
+        )))
+    }
+
+    fn get_events<M>(server: &Server<M>) -> Vec<String> {
+        server.events.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn test_chain_order() {
+        let events = Events::default();
+        let mut server = Server {
+            middleware: MiddlewareChain::new(
+                Layer::new("first", false, &events),
+                Layer::new("second", false, &events),
+            )
+            .with(Layer::new("third", false, &events)),
+            reject: false,
+            events,
+        };
+        let (header, mut payload) = frame();
+        InterceptMessagesFromClientSync::handle_message_frame_from_client(
+            &mut server,
+            None,
+            header,
+            &mut payload,
+        )
+        .unwrap();
+        assert_eq!(
+            get_events(&server),
+            [
+                "first on_message",
+                "second on_message",
+                "third on_message",
+                "handler",
+                "third handled ok",
+                "second handled ok",
+                "first handled ok",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_outer_layer_rejects() {
+        let mut server = new_server(true, false, false);
+        let (header, mut payload) = frame();
+        let error = InterceptMessagesFromClientSync::handle_message_frame_from_client(
+            &mut server,
+            None,
+            header,
+            &mut payload,
+        )
+        .unwrap_err();
+        assert!(matches!(error, HandlerError::Rejected(_)));
+        // neither the inner layer nor the handler see the message
+        assert_eq!(get_events(&server), ["outer on_message"]);
+    }
+
+    #[test]
+    fn test_inner_layer_rejects() {
+        let mut server = new_server(false, true, false);
+        let (header, mut payload) = frame();
+        InterceptMessagesFromClientSync::handle_message_frame_from_client(
+            &mut server,
+            None,
+            header,
+            &mut payload,
+        )
+        .unwrap_err();
+        // the outer layer sees the rejection, the handler is not called
+        assert_eq!(
+            get_events(&server),
+            ["outer on_message", "inner on_message", "outer handled err"]
+        );
+    }
+
+    #[test]
+    fn test_handler_error() {
+        let mut server = new_server(false, false, true);
+        let (header, mut payload) = frame();
+        InterceptMessagesFromClientSync::handle_message_frame_from_client(
+            &mut server,
+            None,
+            header,
+            &mut payload,
+        )
+        .unwrap_err();
+        assert_eq!(
+            get_events(&server),
+            [
+                "outer on_message",
+                "inner on_message",
+                "handler",
+                "inner handled err",
+                "outer handled err",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_frame() {
+        let mut server = new_server(false, false, false);
+        let (header, mut payload) = frame();
+        payload.truncate(1);
+        let error = InterceptMessagesFromClientSync::handle_message_frame_from_client(
+            &mut server,
+            None,
+            header,
+            &mut payload,
+        )
+        .unwrap_err();
+        assert!(matches!(error, HandlerError::Parse(_)));
+        // frames that can't be parsed don't reach the middleware
+        assert!(get_events(&server).is_empty());
+    }
+
+    #[test]
+    fn test_async_chain() {
+        let mut server = new_server(false, true, false);
+        let (header, mut payload) = frame();
+        block_on(
+            InterceptMessagesFromClientAsync::handle_message_frame_from_client(
+                &mut server,
+                None,
+                header,
+                &mut payload,
+            ),
+        )
+        .unwrap_err();
+        assert_eq!(
+            get_events(&server),
+            ["outer on_message", "inner on_message", "outer handled err"]
+        );
+
+        let mut server = new_server(false, false, true);
+        block_on(
+            InterceptMessagesFromClientAsync::handle_message_frame_from_client(
+                &mut server,
+                None,
+                header,
+                &mut payload,
+            ),
+        )
+        .unwrap_err();
+        assert_eq!(
+            get_events(&server),
+            [
+                "outer on_message",
+                "inner on_message",
+                "handler",
+                "inner handled err",
+                "outer handled err",
+            ]
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use crate::error::HandlerErrorType;
+use crate::{
+    error::HandlerErrorType,
+    shared::{impl_async_for_shared, SharedFrames, SharedMessages},
+};
 use binary_sv2::Str0255;
 use framing_sv2::header::Header;
 use mining_sv2::{
@@ -13,7 +16,7 @@ use parsers_sv2::{parse_message_frame_with_tlvs, AnyMessage, Mining, Tlv};
 
 use mining_sv2::*;
 
-#[derive(PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum SupportedChannelTypes {
     Standard,
     Extended,
@@ -635,6 +638,201 @@ pub trait HandleMiningMessagesFromServerAsync {
     ) -> Result<(), Self::Error>;
 }
 
+/// Asynchronous `&self` handler trait for mining messages received from servers.
+///
+/// Unlike the `Async` variant, handler methods take `&self` and the context of the connection the
+/// message came from, so that a single handler can process messages from many connections in
+/// parallel. State shared between connections lives in the handler, behind whatever
+/// synchronization fits it (fine-grained locks, actors, ...), while `Self::Context` holds the
+/// state of one connection and is mutated without locking.
+///
+/// Handler methods receive an optional `tlv_data` parameter containing validated TLV fields
+/// when extension data is appended to messages. It will be `Some` only if validation succeeds
+/// against negotiated extensions.
+#[trait_variant::make(Send)]
+pub trait HandleMiningMessagesFromServerShared: Sync {
+    type Error: HandlerErrorType;
+
+    /// State of a single connection, e.g. the extensions negotiated with the peer.
+    type Context: Send;
+
+    fn get_channel_type_for_server(&self, ctx: &Self::Context) -> SupportedChannelTypes;
+    fn is_work_selection_enabled_for_server(&self, ctx: &Self::Context) -> bool;
+
+    /// Returns the list of negotiated extension_types with a server.
+    ///
+    /// Return an empty Vec if no extensions have been negotiated.
+    fn get_negotiated_extensions_with_server(
+        &self,
+        ctx: &Self::Context,
+    ) -> Result<Vec<u16>, Self::Error>;
+
+    /// Handles a raw Mining protocol message frame from a server.
+    ///
+    /// This method parses the raw frame, extracts any TLV extension data, and delegates
+    /// to `handle_mining_message_from_server` with the parsed message and TLV fields.
+    async fn handle_mining_message_frame_from_server(
+        &self,
+        ctx: &mut Self::Context,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedFrames { handler: self, ctx }
+                .handle_mining_message_frame_from_server(None, header, payload)
+                .await
+        }
+    }
+
+    /// Handles a parsed Mining protocol message from a server.
+    ///
+    /// The `tlv_fields` parameter contains parsed TLV fields if the message has extension
+    /// data appended. It will be `Some(&[Tlv])` when valid TLV data is present, or `None`
+    /// if no TLV data exists or validation fails. Each `Tlv` struct provides direct access to
+    /// `extension_type`, `field_type`, `length`, and `value`.
+    async fn handle_mining_message_from_server(
+        &self,
+        ctx: &mut Self::Context,
+        message: Mining,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedMessages { handler: self, ctx }
+                .handle_mining_message_from_server(None, message, tlv_fields)
+                .await
+        }
+    }
+
+    async fn handle_open_standard_mining_channel_success(
+        &self,
+        ctx: &mut Self::Context,
+        msg: OpenStandardMiningChannelSuccess,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_open_extended_mining_channel_success(
+        &self,
+        ctx: &mut Self::Context,
+        msg: OpenExtendedMiningChannelSuccess,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_open_mining_channel_error(
+        &self,
+        ctx: &mut Self::Context,
+        msg: OpenMiningChannelError,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_update_channel_error(
+        &self,
+        ctx: &mut Self::Context,
+        msg: UpdateChannelError,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_close_channel(
+        &self,
+        ctx: &mut Self::Context,
+        msg: CloseChannel,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_set_extranonce_prefix(
+        &self,
+        ctx: &mut Self::Context,
+        msg: SetExtranoncePrefix,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_submit_shares_success(
+        &self,
+        ctx: &mut Self::Context,
+        msg: SubmitSharesSuccess,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_submit_shares_error(
+        &self,
+        ctx: &mut Self::Context,
+        msg: SubmitSharesError,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_new_mining_job(
+        &self,
+        ctx: &mut Self::Context,
+        msg: NewMiningJob,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_new_extended_mining_job(
+        &self,
+        ctx: &mut Self::Context,
+        msg: NewExtendedMiningJob,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_set_new_prev_hash(
+        &self,
+        ctx: &mut Self::Context,
+        msg: SetNewPrevHash,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_set_custom_mining_job_success(
+        &self,
+        ctx: &mut Self::Context,
+        msg: SetCustomMiningJobSuccess,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_set_custom_mining_job_error(
+        &self,
+        ctx: &mut Self::Context,
+        msg: SetCustomMiningJobError,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_set_target(
+        &self,
+        ctx: &mut Self::Context,
+        msg: SetTarget,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_set_group_channel(
+        &self,
+        ctx: &mut Self::Context,
+        msg: SetGroupChannel,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+}
+
+impl_async_for_shared! {
+    HandleMiningMessagesFromServerShared => HandleMiningMessagesFromServerAsync {
+        async fn handle_mining_message_from_server(Mining<'_>);
+        fn get_channel_type_for_server(&self) -> SupportedChannelTypes;
+        fn is_work_selection_enabled_for_server(&self) -> bool;
+        fn get_negotiated_extensions_with_server(&self) -> Result<Vec<u16>, Self::Error>;
+        async fn handle_open_standard_mining_channel_success(OpenStandardMiningChannelSuccess<'_>);
+        async fn handle_open_extended_mining_channel_success(OpenExtendedMiningChannelSuccess<'_>);
+        async fn handle_open_mining_channel_error(OpenMiningChannelError<'_>);
+        async fn handle_update_channel_error(UpdateChannelError<'_>);
+        async fn handle_close_channel(CloseChannel<'_>);
+        async fn handle_set_extranonce_prefix(SetExtranoncePrefix<'_>);
+        async fn handle_submit_shares_success(SubmitSharesSuccess);
+        async fn handle_submit_shares_error(SubmitSharesError<'_>);
+        async fn handle_new_mining_job(NewMiningJob<'_>);
+        async fn handle_new_extended_mining_job(NewExtendedMiningJob<'_>);
+        async fn handle_set_new_prev_hash(SetNewPrevHash<'_>);
+        async fn handle_set_custom_mining_job_success(SetCustomMiningJobSuccess);
+        async fn handle_set_custom_mining_job_error(SetCustomMiningJobError<'_>);
+        async fn handle_set_target(SetTarget<'_>);
+        async fn handle_set_group_channel(SetGroupChannel<'_>);
+    }
+}
+
 /// Synchronous handler trait for processing mining messages received from clients.
 ///
 /// The client ID identifies which client a message originated from.
@@ -1123,3 +1321,140 @@ pub trait HandleMiningMessagesFromClientAsync {
         tlv_fields: Option<&[Tlv]>,
     ) -> Result<(), Self::Error>;
 }
+
+/// Asynchronous `&self` handler trait for mining messages received from clients.
+///
+/// Unlike the `Async` variant, handler methods take `&self` and the context of the connection the
+/// message came from, so that a single handler can process messages from many connections in
+/// parallel. State shared between connections lives in the handler, behind whatever
+/// synchronization fits it (fine-grained locks, actors, ...), while `Self::Context` holds the
+/// state of one connection and is mutated without locking.
+///
+/// Handler methods receive an optional `tlv_data` parameter containing validated TLV fields
+/// when extension data is appended to messages. It will be `Some` only if validation succeeds
+/// against negotiated extensions.
+#[trait_variant::make(Send)]
+pub trait HandleMiningMessagesFromClientShared: Sync {
+    type Error: HandlerErrorType;
+
+    /// State of a single connection, e.g. the extensions negotiated with the peer.
+    type Context: Send;
+
+    fn get_channel_type_for_client(&self, ctx: &Self::Context) -> SupportedChannelTypes;
+    fn is_work_selection_enabled_for_client(&self, ctx: &Self::Context) -> bool;
+    fn is_client_authorized(
+        &self,
+        ctx: &Self::Context,
+        user_identity: &Str0255,
+    ) -> Result<bool, Self::Error>;
+
+    /// Returns the list of negotiated extension types for a client.
+    ///
+    /// Return an empty Vec if no extensions have been negotiated.
+    fn get_negotiated_extensions_with_client(
+        &self,
+        ctx: &Self::Context,
+    ) -> Result<Vec<u16>, Self::Error>;
+
+    /// Handles a raw Mining protocol message frame from a client.
+    ///
+    /// This method parses the raw frame, extracts any TLV extension data, and delegates
+    /// to `handle_mining_message_from_client` with the parsed message and TLV fields.
+    async fn handle_mining_message_frame_from_client(
+        &self,
+        ctx: &mut Self::Context,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedFrames { handler: self, ctx }
+                .handle_mining_message_frame_from_client(None, header, payload)
+                .await
+        }
+    }
+
+    /// Handles a parsed Mining protocol message from a client.
+    ///
+    /// The `tlv_fields` parameter contains parsed TLV fields if the message has extension
+    /// data appended. It will be `Some(&[Tlv])` when valid TLV data is present, or `None`
+    /// if no TLV data exists or validation fails. Each `Tlv` struct provides direct access to
+    /// `extension_type`, `field_type`, `length`, and `value`.
+    async fn handle_mining_message_from_client(
+        &self,
+        ctx: &mut Self::Context,
+        message: Mining,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedMessages { handler: self, ctx }
+                .handle_mining_message_from_client(None, message, tlv_fields)
+                .await
+        }
+    }
+
+    async fn handle_close_channel(
+        &self,
+        ctx: &mut Self::Context,
+        msg: CloseChannel,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_open_standard_mining_channel(
+        &self,
+        ctx: &mut Self::Context,
+        msg: OpenStandardMiningChannel,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_open_extended_mining_channel(
+        &self,
+        ctx: &mut Self::Context,
+        msg: OpenExtendedMiningChannel,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_update_channel(
+        &self,
+        ctx: &mut Self::Context,
+        msg: UpdateChannel,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_submit_shares_standard(
+        &self,
+        ctx: &mut Self::Context,
+        msg: SubmitSharesStandard,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_submit_shares_extended(
+        &self,
+        ctx: &mut Self::Context,
+        msg: SubmitSharesExtended,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_set_custom_mining_job(
+        &self,
+        ctx: &mut Self::Context,
+        msg: SetCustomMiningJob,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+}
+
+impl_async_for_shared! {
+    HandleMiningMessagesFromClientShared => HandleMiningMessagesFromClientAsync {
+        async fn handle_mining_message_from_client(Mining<'_>);
+        fn get_channel_type_for_client(&self) -> SupportedChannelTypes;
+        fn is_work_selection_enabled_for_client(&self) -> bool;
+        fn is_client_authorized(&self, user_identity: &Str0255) -> Result<bool, Self::Error>;
+        fn get_negotiated_extensions_with_client(&self) -> Result<Vec<u16>, Self::Error>;
+        async fn handle_close_channel(CloseChannel<'_>);
+        async fn handle_open_standard_mining_channel(OpenStandardMiningChannel<'_>);
+        async fn handle_open_extended_mining_channel(OpenExtendedMiningChannel<'_>);
+        async fn handle_update_channel(UpdateChannel<'_>);
+        async fn handle_submit_shares_standard(SubmitSharesStandard);
+        async fn handle_submit_shares_extended(SubmitSharesExtended<'_>);
+        async fn handle_set_custom_mining_job(SetCustomMiningJob<'_>);
+    }
+}
@@ -0,0 +1,416 @@
+use std::collections::HashMap;
+
+use mining_sv2::*;
+use parsers_sv2::Tlv;
+
+use crate::{
+    error::HandlerErrorType,
+    mining::{
+        HandleMiningMessagesFromServerAsync, HandleMiningMessagesFromServerSync,
+        SupportedChannelTypes,
+    },
+};
+
+struct Upstream<H> {
+    handler: H,
+    channel_type: SupportedChannelTypes,
+    work_selection_enabled: bool,
+    negotiated_extensions: Vec<u16>,
+}
+
+/// Mining handler for a proxy connected to several upstream servers at once.
+///
+/// Each upstream is registered under its `server_id`, with its own handler and the parameters
+/// negotiated with it: channel types, work selection and extensions. The handler traits are
+/// implemented from that registry, and every message is routed to the handler of the upstream it
+/// came from.
+///
+/// Messages from a `server_id` that is not registered (or `None`) are rejected with the
+/// `unknown_server` error.
+pub struct MultiUpstreamHandler<H> {
+    upstreams: HashMap<usize, Upstream<H>>,
+}
+
+impl<H> MultiUpstreamHandler<H> {
+    pub fn new() -> Self {
+        Self {
+            upstreams: HashMap::new(),
+        }
+    }
+
+    /// Registers an upstream, returning the handler it replaces if `server_id` was already
+    /// registered.
+    ///
+    /// No extension is negotiated with a new upstream until `set_negotiated_extensions` is called.
+    pub fn add_upstream(
+        &mut self,
+        server_id: usize,
+        handler: H,
+        channel_type: SupportedChannelTypes,
+        work_selection_enabled: bool,
+    ) -> Option<H> {
+        self.upstreams
+            .insert(
+                server_id,
+                Upstream {
+                    handler,
+                    channel_type,
+                    work_selection_enabled,
+                    negotiated_extensions: Vec::new(),
+                },
+            )
+            .map(|upstream| upstream.handler)
+    }
+
+    /// Unregisters an upstream, returning its handler.
+    pub fn remove_upstream(&mut self, server_id: usize) -> Option<H> {
+        self.upstreams
+            .remove(&server_id)
+            .map(|upstream| upstream.handler)
+    }
+
+    /// Records the extensions negotiated with an upstream.
+    ///
+    /// Returns `false` if the upstream is not registered.
+    pub fn set_negotiated_extensions(
+        &mut self,
+        server_id: usize,
+        negotiated_extensions: Vec<u16>,
+    ) -> bool {
+        match self.upstreams.get_mut(&server_id) {
+            Some(upstream) => {
+                upstream.negotiated_extensions = negotiated_extensions;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the IDs of the registered upstreams.
+    pub fn get_server_ids(&self) -> Vec<usize> {
+        self.upstreams.keys().copied().collect()
+    }
+
+    pub fn get_handler(&self, server_id: usize) -> Option<&H> {
+        self.upstreams
+            .get(&server_id)
+            .map(|upstream| &upstream.handler)
+    }
+
+    pub fn get_handler_mut(&mut self, server_id: usize) -> Option<&mut H> {
+        self.upstreams
+            .get_mut(&server_id)
+            .map(|upstream| &mut upstream.handler)
+    }
+
+    fn get_upstream(&self, server_id: Option<usize>) -> Option<&Upstream<H>> {
+        server_id.and_then(|server_id| self.upstreams.get(&server_id))
+    }
+
+    fn get_upstream_handler_mut(&mut self, server_id: Option<usize>) -> Option<&mut H> {
+        server_id.and_then(|server_id| self.get_handler_mut(server_id))
+    }
+}
+
+impl<H> Default for MultiUpstreamHandler<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Implements the `handle_*` methods by forwarding the message to the handler of its upstream.
+macro_rules! route_to_upstream {
+    (sync, $($method:ident($msg:ty, $message_type:expr);)*) => {
+        $(
+            fn $method(
+                &mut self,
+                server_id: Option<usize>,
+                msg: $msg,
+                tlv_fields: Option<&[Tlv]>,
+            ) -> Result<(), Self::Error> {
+                match self.get_upstream_handler_mut(server_id) {
+                    Some(handler) => handler.$method(server_id, msg, tlv_fields),
+                    None => Err(Self::Error::unknown_server(server_id, 0, $message_type)),
+                }
+            }
+        )*
+    };
+    (async, $($method:ident($msg:ty, $message_type:expr);)*) => {
+        $(
+            async fn $method(
+                &mut self,
+                server_id: Option<usize>,
+                msg: $msg,
+                tlv_fields: Option<&[Tlv]>,
+            ) -> Result<(), Self::Error> {
+                match self.get_upstream_handler_mut(server_id) {
+                    Some(handler) => handler.$method(server_id, msg, tlv_fields).await,
+                    None => Err(Self::Error::unknown_server(server_id, 0, $message_type)),
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_multi_upstream_handler {
+    ($kind:tt, $handler_trait:ident $(+ $bound:ident)*) => {
+        impl<H> $handler_trait for MultiUpstreamHandler<H>
+        where
+            H: $handler_trait $(+ $bound)*,
+        {
+            type Error = H::Error;
+
+            /// Returns the channel types of the upstream, or `GroupAndExtended` for unknown
+            /// upstreams, whose messages are then rejected when routed.
+            fn get_channel_type_for_server(
+                &self,
+                server_id: Option<usize>,
+            ) -> SupportedChannelTypes {
+                self.get_upstream(server_id)
+                    .map_or(SupportedChannelTypes::GroupAndExtended, |upstream| {
+                        upstream.channel_type
+                    })
+            }
+
+            fn is_work_selection_enabled_for_server(&self, server_id: Option<usize>) -> bool {
+                self.get_upstream(server_id)
+                    .is_some_and(|upstream| upstream.work_selection_enabled)
+            }
+
+            fn get_negotiated_extensions_with_server(
+                &self,
+                server_id: Option<usize>,
+            ) -> Result<Vec<u16>, Self::Error> {
+                Ok(self
+                    .get_upstream(server_id)
+                    .map(|upstream| upstream.negotiated_extensions.clone())
+                    .unwrap_or_default())
+            }
+
+            route_to_upstream!($kind,
+                handle_open_standard_mining_channel_success(OpenStandardMiningChannelSuccess<'_>, MESSAGE_TYPE_OPEN_STANDARD_MINING_CHANNEL_SUCCESS);
+                handle_open_extended_mining_channel_success(OpenExtendedMiningChannelSuccess<'_>, MESSAGE_TYPE_OPEN_EXTENDED_MINING_CHANNEL_SUCCESS);
+                handle_open_mining_channel_error(OpenMiningChannelError<'_>, MESSAGE_TYPE_OPEN_MINING_CHANNEL_ERROR);
+                handle_update_channel_error(UpdateChannelError<'_>, MESSAGE_TYPE_UPDATE_CHANNEL_ERROR);
+                handle_close_channel(CloseChannel<'_>, MESSAGE_TYPE_CLOSE_CHANNEL);
+                handle_set_extranonce_prefix(SetExtranoncePrefix<'_>, MESSAGE_TYPE_SET_EXTRANONCE_PREFIX);
+                handle_submit_shares_success(SubmitSharesSuccess, MESSAGE_TYPE_SUBMIT_SHARES_SUCCESS);
+                handle_submit_shares_error(SubmitSharesError<'_>, MESSAGE_TYPE_SUBMIT_SHARES_ERROR);
+                handle_new_mining_job(NewMiningJob<'_>, MESSAGE_TYPE_NEW_MINING_JOB);
+                handle_new_extended_mining_job(NewExtendedMiningJob<'_>, MESSAGE_TYPE_NEW_EXTENDED_MINING_JOB);
+                handle_set_new_prev_hash(SetNewPrevHash<'_>, MESSAGE_TYPE_MINING_SET_NEW_PREV_HASH);
+                handle_set_custom_mining_job_success(SetCustomMiningJobSuccess, MESSAGE_TYPE_SET_CUSTOM_MINING_JOB_SUCCESS);
+                handle_set_custom_mining_job_error(SetCustomMiningJobError<'_>, MESSAGE_TYPE_SET_CUSTOM_MINING_JOB_ERROR);
+                handle_set_target(SetTarget<'_>, MESSAGE_TYPE_SET_TARGET);
+                handle_set_group_channel(SetGroupChannel<'_>, MESSAGE_TYPE_SET_GROUP_CHANNEL);
+            );
+        }
+    };
+}
+
+impl_multi_upstream_handler!(sync, HandleMiningMessagesFromServerSync);
+impl_multi_upstream_handler!(async, HandleMiningMessagesFromServerAsync + Send);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::HandlerError,
+        testing::{block_on, encode_frame, RecordingHandler},
+    };
+    use parsers_sv2::{AnyMessage, Mining};
+
+    fn submit_shares_success() -> AnyMessage<'static> {
+        AnyMessage::Mining(Mining::SubmitSharesSuccess(SubmitSharesSuccess {
+            channel_id: 1,
+            last_sequence_number: 1,
+            new_submits_accepted_count: 1,
+            new_shares_sum: 1,
+        }))
+    }
+
+    fn set_custom_mining_job_success() -> AnyMessage<'static> {
+        AnyMessage::Mining(Mining::SetCustomMiningJobSuccess(
+            SetCustomMiningJobSuccess {
+                channel_id: 1,
+                request_id: 1,
+                job_id: 1,
+            },
+        ))
+    }
+
+    // Two upstreams, the first one with work selection enabled.
+    fn proxy() -> MultiUpstreamHandler<RecordingHandler> {
+        let mut proxy = MultiUpstreamHandler::new();
+        proxy.add_upstream(
+            1,
+            RecordingHandler::new(),
+            SupportedChannelTypes::Extended,
+            true,
+        );
+        proxy.add_upstream(
+            2,
+            RecordingHandler::new(),
+            SupportedChannelTypes::Extended,
+            false,
+        );
+        proxy
+    }
+
+    fn handle(
+        proxy: &mut MultiUpstreamHandler<RecordingHandler>,
+        server_id: Option<usize>,
+        message: AnyMessage<'_>,
+    ) -> Result<(), HandlerError> {
+        let (header, mut payload) = encode_frame(message);
+        HandleMiningMessagesFromServerSync::handle_mining_message_frame_from_server(
+            proxy,
+            server_id,
+            header,
+            &mut payload,
+        )
+    }
+
+    #[test]
+    fn test_routes_to_upstream() {
+        let mut proxy = proxy();
+        handle(&mut proxy, Some(2), submit_shares_success()).unwrap();
+
+        assert!(proxy.get_handler(1).unwrap().get_messages().is_empty());
+        let messages = proxy.get_handler(2).unwrap().get_messages();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].peer_id, Some(2));
+        assert_eq!(
+            messages[0].get_message_type(),
+            (0, MESSAGE_TYPE_SUBMIT_SHARES_SUCCESS)
+        );
+    }
+
+    #[test]
+    fn test_unknown_upstream() {
+        let mut proxy = proxy();
+        for server_id in [Some(3), None] {
+            let error = handle(&mut proxy, server_id, submit_shares_success()).unwrap_err();
+            assert!(matches!(
+                error,
+                HandlerError::UnknownServer {
+                    server_id: id,
+                    extension_type: 0,
+                    message_type: MESSAGE_TYPE_SUBMIT_SHARES_SUCCESS,
+                } if id == server_id
+            ));
+        }
+        assert!(proxy.get_handler(1).unwrap().get_messages().is_empty());
+        assert!(proxy.get_handler(2).unwrap().get_messages().is_empty());
+    }
+
+    #[test]
+    fn test_removed_upstream() {
+        let mut proxy = proxy();
+        let handler = proxy.remove_upstream(2).unwrap();
+        assert!(handler.get_messages().is_empty());
+        assert!(proxy.remove_upstream(2).is_none());
+        assert_eq!(proxy.get_server_ids(), [1]);
+        assert!(!proxy.set_negotiated_extensions(2, vec![0x0002]));
+
+        let error = handle(&mut proxy, Some(2), submit_shares_success()).unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::UnknownServer {
+                server_id: Some(2),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_replaced_upstream() {
+        let mut proxy = proxy();
+        handle(&mut proxy, Some(1), submit_shares_success()).unwrap();
+        let replaced = proxy
+            .add_upstream(
+                1,
+                RecordingHandler::new(),
+                SupportedChannelTypes::Extended,
+                false,
+            )
+            .unwrap();
+        assert_eq!(replaced.get_messages().len(), 1);
+        assert!(proxy.get_handler(1).unwrap().get_messages().is_empty());
+        // the parameters of the replaced upstream are dropped too
+        assert!(
+            !HandleMiningMessagesFromServerSync::is_work_selection_enabled_for_server(
+                &proxy,
+                Some(1)
+            )
+        );
+    }
+
+    #[test]
+    fn test_parameters_per_upstream() {
+        let mut proxy = proxy();
+        assert!(proxy.set_negotiated_extensions(1, vec![0x0002]));
+        assert_eq!(
+            HandleMiningMessagesFromServerSync::get_negotiated_extensions_with_server(
+                &proxy,
+                Some(1)
+            )
+            .unwrap(),
+            [0x0002]
+        );
+        assert!(
+            HandleMiningMessagesFromServerSync::get_negotiated_extensions_with_server(
+                &proxy,
+                Some(2)
+            )
+            .unwrap()
+            .is_empty()
+        );
+
+        // work selection is enabled with the first upstream only
+        handle(&mut proxy, Some(1), set_custom_mining_job_success()).unwrap();
+        let error = handle(&mut proxy, Some(2), set_custom_mining_job_success()).unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::UnexpectedMessage {
+                message_type: MESSAGE_TYPE_SET_CUSTOM_MINING_JOB_SUCCESS,
+                ..
+            }
+        ));
+        assert_eq!(proxy.get_handler(1).unwrap().get_messages().len(), 1);
+        assert!(proxy.get_handler(2).unwrap().get_messages().is_empty());
+    }
+
+    #[test]
+    fn test_async_unknown_upstream() {
+        let mut proxy = proxy();
+        let (header, mut payload) = encode_frame(submit_shares_success());
+        let error = block_on(
+            HandleMiningMessagesFromServerAsync::handle_mining_message_frame_from_server(
+                &mut proxy,
+                Some(3),
+                header,
+                &mut payload,
+            ),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::UnknownServer {
+                server_id: Some(3),
+                ..
+            }
+        ));
+
+        block_on(
+            HandleMiningMessagesFromServerAsync::handle_mining_message_frame_from_server(
+                &mut proxy,
+                Some(1),
+                header,
+                &mut payload,
+            ),
+        )
+        .unwrap();
+        assert_eq!(proxy.get_handler(1).unwrap().get_messages().len(), 1);
+    }
+}
@@ -0,0 +1,444 @@
+use common_messages_sv2::{
+    Protocol, SetupConnection, SetupConnectionError, SetupConnectionErrorCode,
+    SetupConnectionSuccess,
+};
+
+use crate::action::ErrorResponse;
+
+/// What a server supports for one subprotocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolCapabilities {
+    pub protocol: Protocol,
+    pub min_version: u16,
+    pub max_version: u16,
+    /// Flags clients must set in `SetupConnection`.
+    pub required_flags: u32,
+    /// Flags clients may set in `SetupConnection`, on top of the required ones.
+    pub optional_flags: u32,
+    /// Flags sent back in `SetupConnection.Success`, e.g. `REQUIRES_FIXED_VERSION` for the Mining
+    /// Protocol.
+    pub server_flags: u32,
+}
+
+impl ProtocolCapabilities {
+    pub fn new(protocol: Protocol, min_version: u16, max_version: u16) -> Self {
+        Self {
+            protocol,
+            min_version,
+            max_version,
+            required_flags: 0,
+            optional_flags: 0,
+            server_flags: 0,
+        }
+    }
+}
+
+/// Errors of a `SetupConnection` negotiation, on either side of the connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NegotiationError {
+    /// The server doesn't support the requested subprotocol.
+    UnsupportedProtocol(Protocol),
+    /// The server supports none of the versions between `min_version` and `max_version`.
+    ProtocolVersionMismatch { min_version: u16, max_version: u16 },
+    /// The client set flags the server doesn't support, or missed flags the server requires.
+    UnsupportedFeatureFlags(u32),
+    /// The server selected a version outside of the range sent in `SetupConnection`.
+    UsedVersionOutOfRange(u16),
+    /// The server answered with flags the client doesn't support.
+    UnsupportedServerFlags(u32),
+    /// The server answered with an error code not defined by the spec.
+    UnknownErrorCode(String),
+}
+
+impl NegotiationError {
+    /// Builds the error from a `SetupConnection.Error` received in answer to `setup_connection`.
+    pub fn from_setup_connection_error(
+        setup_connection: &SetupConnection,
+        setup_connection_error: &SetupConnectionError,
+    ) -> Self {
+        match setup_connection_error.get_error_code() {
+            SetupConnectionErrorCode::UnsupportedProtocol => {
+                NegotiationError::UnsupportedProtocol(setup_connection.protocol)
+            }
+            SetupConnectionErrorCode::ProtocolVersionMismatch => {
+                NegotiationError::ProtocolVersionMismatch {
+                    min_version: setup_connection.min_version,
+                    max_version: setup_connection.max_version,
+                }
+            }
+            SetupConnectionErrorCode::UnsupportedFeatureFlags => {
+                NegotiationError::UnsupportedFeatureFlags(setup_connection_error.flags)
+            }
+            SetupConnectionErrorCode::Unknown(error_code) => {
+                NegotiationError::UnknownErrorCode(error_code)
+            }
+        }
+    }
+
+    /// Returns the `SetupConnection.Error` error code to answer with, for errors raised by
+    /// [`SetupConnectionNegotiator::negotiate`].
+    pub fn setup_connection_error_code(&self) -> Option<SetupConnectionErrorCode> {
+        match self {
+            NegotiationError::UnsupportedProtocol(_) => {
+                Some(SetupConnectionErrorCode::UnsupportedProtocol)
+            }
+            NegotiationError::ProtocolVersionMismatch { .. } => {
+                Some(SetupConnectionErrorCode::ProtocolVersionMismatch)
+            }
+            NegotiationError::UnsupportedFeatureFlags(_) => {
+                Some(SetupConnectionErrorCode::UnsupportedFeatureFlags)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the `SetupConnection.Error` to answer with, for errors raised by
+    /// [`SetupConnectionNegotiator::negotiate`].
+    ///
+    /// Its `flags` are the offending flags for `unsupported-feature-flags`, and 0 otherwise.
+    pub fn to_error_response(&self) -> Option<ErrorResponse> {
+        let flags = match self {
+            NegotiationError::UnsupportedFeatureFlags(flags) => *flags,
+            _ => 0,
+        };
+        self.setup_connection_error_code()
+            .map(|error_code| ErrorResponse::SetupConnection { flags, error_code })
+    }
+}
+
+/// The parameters a connection was set up with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NegotiatedConnection {
+    protocol: Protocol,
+    version: u16,
+    flags: u32,
+    server_flags: u32,
+}
+
+impl NegotiatedConnection {
+    /// Checks a `SetupConnection.Success` received in answer to `setup_connection`.
+    ///
+    /// `supported_server_flags` are the flags of `SetupConnection.Success` this client can work
+    /// with. Any other flag set by the server fails the negotiation.
+    pub fn from_setup_connection_success(
+        setup_connection: &SetupConnection,
+        setup_connection_success: &SetupConnectionSuccess,
+        supported_server_flags: u32,
+    ) -> Result<Self, NegotiationError> {
+        let version = setup_connection_success.used_version;
+        if version < setup_connection.min_version || version > setup_connection.max_version {
+            return Err(NegotiationError::UsedVersionOutOfRange(version));
+        }
+
+        let unsupported_flags = setup_connection_success.flags & !supported_server_flags;
+        if unsupported_flags != 0 {
+            return Err(NegotiationError::UnsupportedServerFlags(unsupported_flags));
+        }
+
+        Ok(Self {
+            protocol: setup_connection.protocol,
+            version,
+            flags: setup_connection.flags,
+            server_flags: setup_connection_success.flags,
+        })
+    }
+
+    pub fn get_protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    /// Returns the version used for the rest of the connection.
+    pub fn get_version(&self) -> u16 {
+        self.version
+    }
+
+    /// Returns the flags sent by the client in `SetupConnection`.
+    pub fn get_flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// Returns the flags sent by the server in `SetupConnection.Success`.
+    pub fn get_server_flags(&self) -> u32 {
+        self.server_flags
+    }
+}
+
+/// Answers `SetupConnection` messages according to the capabilities of a server.
+///
+/// The negotiation picks the highest version supported by both endpoints, and checks the client
+/// flags against the required and optional flags of the subprotocol. On error,
+/// [`NegotiationError::to_error_response`] gives the `SetupConnection.Error` to answer with.
+///
+/// Connections tracked by a [`crate::Session`] are negotiated with
+/// [`crate::Session::on_setup_connection`], which also moves the session to the set up stage.
+#[derive(Debug, Clone, Default)]
+pub struct SetupConnectionNegotiator {
+    capabilities: Vec<ProtocolCapabilities>,
+}
+
+impl SetupConnectionNegotiator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a supported subprotocol, replacing its previous capabilities if any.
+    pub fn with_protocol(mut self, capabilities: ProtocolCapabilities) -> Self {
+        self.capabilities
+            .retain(|c| c.protocol != capabilities.protocol);
+        self.capabilities.push(capabilities);
+        self
+    }
+
+    /// Returns the capabilities of a subprotocol, if supported.
+    pub fn get_capabilities(&self, protocol: Protocol) -> Option<&ProtocolCapabilities> {
+        self.capabilities.iter().find(|c| c.protocol == protocol)
+    }
+
+    /// Negotiates a connection with a `SetupConnection` received from a client, returning the
+    /// `SetupConnection.Success` to answer with.
+    pub fn negotiate(
+        &self,
+        setup_connection: &SetupConnection,
+    ) -> Result<(SetupConnectionSuccess, NegotiatedConnection), NegotiationError> {
+        let protocol = setup_connection.protocol;
+        let capabilities = self
+            .get_capabilities(protocol)
+            .ok_or(NegotiationError::UnsupportedProtocol(protocol))?;
+
+        let version = setup_connection
+            .get_version(capabilities.min_version, capabilities.max_version)
+            .ok_or(NegotiationError::ProtocolVersionMismatch {
+                min_version: setup_connection.min_version,
+                max_version: setup_connection.max_version,
+            })?;
+
+        let flags = setup_connection.flags;
+        let unsupported_flags =
+            flags & !(capabilities.required_flags | capabilities.optional_flags);
+        let missing_flags = capabilities.required_flags & !flags;
+        if unsupported_flags | missing_flags != 0 {
+            return Err(NegotiationError::UnsupportedFeatureFlags(
+                unsupported_flags | missing_flags,
+            ));
+        }
+
+        let setup_connection_success = SetupConnectionSuccess {
+            used_version: version,
+            flags: capabilities.server_flags,
+        };
+        let negotiated_connection = NegotiatedConnection {
+            protocol,
+            version,
+            flags,
+            server_flags: capabilities.server_flags,
+        };
+        Ok((setup_connection_success, negotiated_connection))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Work selection, as a client sets it in `SetupConnection` flags.
+    const WORK_SELECTION: u32 = 0b10;
+    // Version rolling, as a client sets it in `SetupConnection` flags.
+    const VERSION_ROLLING: u32 = 0b100;
+
+    fn setup_connection(
+        protocol: Protocol,
+        min_version: u16,
+        max_version: u16,
+        flags: u32,
+    ) -> SetupConnection<'static> {
+        SetupConnection {
+            protocol,
+            min_version,
+            max_version,
+            flags,
+            endpoint_host: "0.0.0.0".to_string().try_into().unwrap(),
+            endpoint_port: 3333,
+            vendor: "vendor".to_string().try_into().unwrap(),
+            hardware_version: "".to_string().try_into().unwrap(),
+            firmware: "".to_string().try_into().unwrap(),
+            device_id: "".to_string().try_into().unwrap(),
+        }
+    }
+
+    // A pool supporting versions 2 to 3 of the Mining Protocol, requiring version rolling and
+    // allowing work selection.
+    fn negotiator() -> SetupConnectionNegotiator {
+        let mut capabilities = ProtocolCapabilities::new(Protocol::MiningProtocol, 2, 3);
+        capabilities.required_flags = VERSION_ROLLING;
+        capabilities.optional_flags = WORK_SELECTION;
+        capabilities.server_flags = 0b1;
+        SetupConnectionNegotiator::new().with_protocol(capabilities)
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let setup_connection = setup_connection(
+            Protocol::MiningProtocol,
+            2,
+            4,
+            VERSION_ROLLING | WORK_SELECTION,
+        );
+        let (setup_connection_success, negotiated_connection) =
+            negotiator().negotiate(&setup_connection).unwrap();
+        // the highest version supported by both endpoints
+        assert_eq!(setup_connection_success.used_version, 3);
+        assert_eq!(setup_connection_success.flags, 0b1);
+        assert_eq!(negotiated_connection.get_version(), 3);
+        assert_eq!(
+            negotiated_connection.get_flags(),
+            VERSION_ROLLING | WORK_SELECTION
+        );
+        assert_eq!(negotiated_connection.get_server_flags(), 0b1);
+    }
+
+    #[test]
+    fn test_unsupported_protocol() {
+        let setup_connection = setup_connection(Protocol::JobDeclarationProtocol, 2, 2, 0);
+        let error = negotiator().negotiate(&setup_connection).unwrap_err();
+        assert_eq!(
+            error,
+            NegotiationError::UnsupportedProtocol(Protocol::JobDeclarationProtocol)
+        );
+        assert_eq!(
+            error.to_error_response(),
+            Some(ErrorResponse::SetupConnection {
+                flags: 0,
+                error_code: SetupConnectionErrorCode::UnsupportedProtocol,
+            })
+        );
+    }
+
+    #[test]
+    fn test_protocol_version_mismatch() {
+        let setup_connection = setup_connection(Protocol::MiningProtocol, 4, 5, VERSION_ROLLING);
+        let error = negotiator().negotiate(&setup_connection).unwrap_err();
+        assert_eq!(
+            error,
+            NegotiationError::ProtocolVersionMismatch {
+                min_version: 4,
+                max_version: 5,
+            }
+        );
+        assert_eq!(
+            error.setup_connection_error_code(),
+            Some(SetupConnectionErrorCode::ProtocolVersionMismatch)
+        );
+    }
+
+    #[test]
+    fn test_unsupported_feature_flags() {
+        // version rolling is required
+        let missing_flag = setup_connection(Protocol::MiningProtocol, 2, 2, WORK_SELECTION);
+        let error = negotiator().negotiate(&missing_flag).unwrap_err();
+        assert_eq!(
+            error,
+            NegotiationError::UnsupportedFeatureFlags(VERSION_ROLLING)
+        );
+        assert_eq!(
+            error.to_error_response(),
+            Some(ErrorResponse::SetupConnection {
+                flags: VERSION_ROLLING,
+                error_code: SetupConnectionErrorCode::UnsupportedFeatureFlags,
+            })
+        );
+
+        // flags neither required nor optional are not supported
+        let with_unknown_flag =
+            setup_connection(Protocol::MiningProtocol, 2, 2, VERSION_ROLLING | 0b1);
+        assert_eq!(
+            negotiator().negotiate(&with_unknown_flag),
+            Err(NegotiationError::UnsupportedFeatureFlags(0b1))
+        );
+    }
+
+    #[test]
+    fn test_with_protocol_replaces_capabilities() {
+        let negotiator =
+            negotiator().with_protocol(ProtocolCapabilities::new(Protocol::MiningProtocol, 2, 2));
+        let capabilities = negotiator
+            .get_capabilities(Protocol::MiningProtocol)
+            .unwrap();
+        assert_eq!(capabilities.required_flags, 0);
+        assert!(negotiator
+            .get_capabilities(Protocol::TemplateDistributionProtocol)
+            .is_none());
+    }
+
+    #[test]
+    fn test_setup_connection_success_checks() {
+        let setup_connection = setup_connection(Protocol::MiningProtocol, 2, 3, VERSION_ROLLING);
+        let success = |used_version, flags| SetupConnectionSuccess {
+            used_version,
+            flags,
+        };
+
+        assert_eq!(
+            NegotiatedConnection::from_setup_connection_success(
+                &setup_connection,
+                &success(4, 0),
+                0
+            ),
+            Err(NegotiationError::UsedVersionOutOfRange(4))
+        );
+        assert_eq!(
+            NegotiatedConnection::from_setup_connection_success(
+                &setup_connection,
+                &success(2, 0b11),
+                0b1
+            ),
+            Err(NegotiationError::UnsupportedServerFlags(0b10))
+        );
+
+        let negotiated_connection = NegotiatedConnection::from_setup_connection_success(
+            &setup_connection,
+            &success(2, 0b1),
+            0b1,
+        )
+        .unwrap();
+        assert_eq!(negotiated_connection.get_version(), 2);
+        // client side errors are not answered with `SetupConnection.Error`
+        assert_eq!(
+            NegotiationError::UnsupportedServerFlags(0b10).to_error_response(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_from_setup_connection_error() {
+        let setup_connection = setup_connection(Protocol::MiningProtocol, 2, 3, 0);
+        let error = |flags, error_code: &str| SetupConnectionError {
+            flags,
+            error_code: error_code.to_string().try_into().unwrap(),
+        };
+
+        assert_eq!(
+            NegotiationError::from_setup_connection_error(
+                &setup_connection,
+                &error(0, "protocol-version-mismatch")
+            ),
+            NegotiationError::ProtocolVersionMismatch {
+                min_version: 2,
+                max_version: 3,
+            }
+        );
+        assert_eq!(
+            NegotiationError::from_setup_connection_error(
+                &setup_connection,
+                &error(VERSION_ROLLING, "unsupported-feature-flags")
+            ),
+            NegotiationError::UnsupportedFeatureFlags(VERSION_ROLLING)
+        );
+        assert_eq!(
+            NegotiationError::from_setup_connection_error(
+                &setup_connection,
+                &error(0, "overloaded")
+            ),
+            NegotiationError::UnknownErrorCode("overloaded".to_string())
+        );
+    }
+}
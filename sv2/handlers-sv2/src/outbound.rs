@@ -0,0 +1,942 @@
+use common_messages_sv2::{has_work_selection, Protocol};
+use parsers_sv2::{IsSv2Message, JobDeclaration, Mining, TemplateDistribution};
+
+use crate::session::{Session, SessionError, SessionState};
+
+/// The type of a mining channel, as opened on a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelType {
+    Standard,
+    Extended,
+    Group,
+}
+
+/// The parameters of an open mining channel that outbound messages are checked against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelParameters {
+    pub channel_type: ChannelType,
+    /// The `max_target` requested when the channel was opened (or updated with `UpdateChannel`),
+    /// as little-endian bytes. `None` for group channels.
+    pub max_target: Option<[u8; 32]>,
+}
+
+/// Errors of outbound messages that would violate the spec if sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutboundError {
+    /// The connection is not set up for the subprotocol of the message.
+    Session(SessionError),
+    /// The message is never sent in this direction, e.g. `NewMiningJob` from a client.
+    WrongDirection { message_type: u8 },
+    /// The message refers to a channel that is not open.
+    UnknownChannel(u32),
+    /// The message doesn't apply to the type of the channel it refers to, e.g. `NewMiningJob`
+    /// for an extended channel.
+    WrongChannelType {
+        channel_id: u32,
+        channel_type: ChannelType,
+        message_type: u8,
+    },
+    /// `SetTarget` sets a target above the `max_target` of the channel.
+    TargetAboveMaxTarget(u32),
+    /// A custom job message was sent, but work selection was not negotiated in
+    /// `SetupConnection`.
+    WorkSelectionNotNegotiated,
+    /// `DeclareMiningJob` uses a token that was not allocated with
+    /// `AllocateMiningJobToken.Success`.
+    MiningJobTokenNotAllocated,
+    /// The message answers a `DeclareMiningJob` request that is not pending.
+    UnknownRequest(u32),
+    /// The message refers to a template that was not sent with `NewTemplate`.
+    UnknownTemplate(u64),
+}
+
+/// Checker for mining messages sent to clients.
+///
+/// `validate_mining_message_to_client` checks that the connection is set up for the Mining
+/// Protocol, that the message is one a server sends, and that it matches the parameters of the
+/// channel it refers to. It is meant to be called right before sending, e.g. in tests or behind a
+/// debug assertion.
+///
+/// The client ID identifies which client a message is sent to, as for the handler traits.
+pub trait ValidateMiningMessagesToClient {
+    /// Returns the [`Session`] tracking the connection with a client.
+    fn get_session_with_client(&self, client_id: Option<usize>) -> Option<&Session>;
+
+    /// Returns the parameters of a channel open with a client, if any.
+    fn get_channel_with_client(
+        &self,
+        client_id: Option<usize>,
+        channel_id: u32,
+    ) -> Option<ChannelParameters>;
+
+    fn validate_mining_message_to_client(
+        &self,
+        client_id: Option<usize>,
+        message: &Mining<'_>,
+    ) -> Result<(), OutboundError> {
+        let session = self.get_session_with_client(client_id);
+        check_session(session, Protocol::MiningProtocol)?;
+
+        let message_type = message.message_type();
+        let channel = |channel_id| {
+            self.get_channel_with_client(client_id, channel_id)
+                .ok_or(OutboundError::UnknownChannel(channel_id))
+        };
+        let channel_of_type = |channel_id, allowed: &[ChannelType]| {
+            let channel = channel(channel_id)?;
+            check_channel_type(channel_id, &channel, allowed, message_type)?;
+            Ok(channel)
+        };
+
+        match message {
+            Mining::OpenStandardMiningChannelSuccess(m) => {
+                channel_of_type(m.channel_id, &[ChannelType::Standard]).map(|_| ())
+            }
+            Mining::OpenExtendedMiningChannelSuccess(m) => {
+                channel_of_type(m.channel_id, &[ChannelType::Extended]).map(|_| ())
+            }
+            Mining::NewMiningJob(m) => {
+                channel_of_type(m.channel_id, &[ChannelType::Standard]).map(|_| ())
+            }
+            Mining::NewExtendedMiningJob(m) => {
+                channel_of_type(m.channel_id, &[ChannelType::Extended, ChannelType::Group])
+                    .map(|_| ())
+            }
+            Mining::SetGroupChannel(m) => {
+                channel_of_type(m.group_channel_id, &[ChannelType::Group]).map(|_| ())
+            }
+            // a group channel target applies to every channel of the group, and is checked
+            // against their own max_target by the caller
+            Mining::SetTarget(m) => {
+                let channel = channel_of_type(
+                    m.channel_id,
+                    &[
+                        ChannelType::Standard,
+                        ChannelType::Extended,
+                        ChannelType::Group,
+                    ],
+                )?;
+                match channel.max_target {
+                    Some(max_target) if is_above(m.maximum_target.inner_as_ref(), &max_target) => {
+                        Err(OutboundError::TargetAboveMaxTarget(m.channel_id))
+                    }
+                    _ => Ok(()),
+                }
+            }
+            Mining::SetNewPrevHash(m) => channel(m.channel_id).map(|_| ()),
+            Mining::SetExtranoncePrefix(m) => channel(m.channel_id).map(|_| ()),
+            Mining::SubmitSharesSuccess(m) => channel(m.channel_id).map(|_| ()),
+            Mining::CloseChannel(m) => channel(m.channel_id).map(|_| ()),
+            Mining::SetCustomMiningJobSuccess(_) | Mining::SetCustomMiningJobError(_) => {
+                check_work_selection(session)
+            }
+            // error messages may answer requests for unknown channels
+            Mining::OpenMiningChannelError(_)
+            | Mining::UpdateChannelError(_)
+            | Mining::SubmitSharesError(_) => Ok(()),
+            Mining::OpenStandardMiningChannel(_)
+            | Mining::OpenExtendedMiningChannel(_)
+            | Mining::UpdateChannel(_)
+            | Mining::SubmitSharesStandard(_)
+            | Mining::SubmitSharesExtended(_)
+            | Mining::SetCustomMiningJob(_) => Err(OutboundError::WrongDirection { message_type }),
+        }
+    }
+}
+
+/// Checker for mining messages sent to servers.
+///
+/// `validate_mining_message_to_server` checks that the connection is set up for the Mining
+/// Protocol, that the message is one a client sends, and that it matches the parameters of the
+/// channel it refers to. It is meant to be called right before sending, e.g. in tests or behind a
+/// debug assertion.
+///
+/// The server ID identifies which server a message is sent to, as for the handler traits.
+pub trait ValidateMiningMessagesToServer {
+    /// Returns the [`Session`] tracking the connection with a server.
+    fn get_session_with_server(&self, server_id: Option<usize>) -> Option<&Session>;
+
+    /// Returns the parameters of a channel open with a server, if any.
+    fn get_channel_with_server(
+        &self,
+        server_id: Option<usize>,
+        channel_id: u32,
+    ) -> Option<ChannelParameters>;
+
+    fn validate_mining_message_to_server(
+        &self,
+        server_id: Option<usize>,
+        message: &Mining<'_>,
+    ) -> Result<(), OutboundError> {
+        let session = self.get_session_with_server(server_id);
+        check_session(session, Protocol::MiningProtocol)?;
+
+        let message_type = message.message_type();
+        let channel_of_type = |channel_id, allowed: &[ChannelType]| {
+            let channel = self
+                .get_channel_with_server(server_id, channel_id)
+                .ok_or(OutboundError::UnknownChannel(channel_id))?;
+            check_channel_type(channel_id, &channel, allowed, message_type)
+        };
+        let any_channel = &[
+            ChannelType::Standard,
+            ChannelType::Extended,
+            ChannelType::Group,
+        ];
+
+        match message {
+            Mining::OpenStandardMiningChannel(_) | Mining::OpenExtendedMiningChannel(_) => Ok(()),
+            Mining::UpdateChannel(m) => channel_of_type(m.channel_id, any_channel),
+            Mining::CloseChannel(m) => channel_of_type(m.channel_id, any_channel),
+            Mining::SubmitSharesStandard(m) => {
+                channel_of_type(m.channel_id, &[ChannelType::Standard])
+            }
+            Mining::SubmitSharesExtended(m) => {
+                channel_of_type(m.channel_id, &[ChannelType::Extended])
+            }
+            Mining::SetCustomMiningJob(m) => {
+                check_work_selection(session)?;
+                channel_of_type(m.channel_id, &[ChannelType::Extended])
+            }
+            Mining::OpenStandardMiningChannelSuccess(_)
+            | Mining::OpenExtendedMiningChannelSuccess(_)
+            | Mining::OpenMiningChannelError(_)
+            | Mining::UpdateChannelError(_)
+            | Mining::NewMiningJob(_)
+            | Mining::NewExtendedMiningJob(_)
+            | Mining::SetGroupChannel(_)
+            | Mining::SetTarget(_)
+            | Mining::SetNewPrevHash(_)
+            | Mining::SetExtranoncePrefix(_)
+            | Mining::SubmitSharesSuccess(_)
+            | Mining::SubmitSharesError(_)
+            | Mining::SetCustomMiningJobSuccess(_)
+            | Mining::SetCustomMiningJobError(_) => {
+                Err(OutboundError::WrongDirection { message_type })
+            }
+        }
+    }
+}
+
+/// Checker for Job Declaration messages sent to clients.
+///
+/// The client ID identifies which client a message is sent to, as for the handler traits.
+pub trait ValidateJobDeclarationMessagesToClient {
+    /// Returns the [`Session`] tracking the connection with a client.
+    fn get_session_with_client(&self, client_id: Option<usize>) -> Option<&Session>;
+
+    /// Returns true if a `DeclareMiningJob` with this request ID was received from a client, and
+    /// not answered with `DeclareMiningJob.Success` or `DeclareMiningJob.Error` yet.
+    fn is_declare_mining_job_pending(&self, client_id: Option<usize>, request_id: u32) -> bool;
+
+    /// Checks that the connection is set up for the Job Declaration Protocol, that the message is
+    /// one a server sends, and that responses to `DeclareMiningJob` answer a pending request.
+    fn validate_job_declaration_message_to_client(
+        &self,
+        client_id: Option<usize>,
+        message: &JobDeclaration<'_>,
+    ) -> Result<(), OutboundError> {
+        check_session(
+            self.get_session_with_client(client_id),
+            Protocol::JobDeclarationProtocol,
+        )?;
+        let pending_request = |request_id| {
+            if self.is_declare_mining_job_pending(client_id, request_id) {
+                Ok(())
+            } else {
+                Err(OutboundError::UnknownRequest(request_id))
+            }
+        };
+        match message {
+            JobDeclaration::AllocateMiningJobTokenSuccess(_) => Ok(()),
+            JobDeclaration::DeclareMiningJobSuccess(m) => pending_request(m.request_id),
+            JobDeclaration::DeclareMiningJobError(m) => pending_request(m.request_id),
+            JobDeclaration::ProvideMissingTransactions(m) => pending_request(m.request_id),
+            _ => Err(OutboundError::WrongDirection {
+                message_type: message.message_type(),
+            }),
+        }
+    }
+}
+
+/// Checker for Job Declaration messages sent to servers.
+///
+/// The server ID identifies which server a message is sent to, as for the handler traits.
+pub trait ValidateJobDeclarationMessagesToServer {
+    /// Returns the [`Session`] tracking the connection with a server.
+    fn get_session_with_server(&self, server_id: Option<usize>) -> Option<&Session>;
+
+    /// Returns true if the token was allocated by a server with `AllocateMiningJobToken.Success`.
+    fn is_mining_job_token_allocated(
+        &self,
+        server_id: Option<usize>,
+        mining_job_token: &[u8],
+    ) -> bool;
+
+    /// Returns true if a `DeclareMiningJob` with this request ID was sent to a server, and not
+    /// answered with `DeclareMiningJob.Success` or `DeclareMiningJob.Error` yet.
+    fn is_declare_mining_job_pending(&self, server_id: Option<usize>, request_id: u32) -> bool;
+
+    /// Checks that the connection is set up for the Job Declaration Protocol, that the message is
+    /// one a client sends, that `DeclareMiningJob` uses an allocated token and that
+    /// `ProvideMissingTransactions.Success` answers a pending request.
+    fn validate_job_declaration_message_to_server(
+        &self,
+        server_id: Option<usize>,
+        message: &JobDeclaration<'_>,
+    ) -> Result<(), OutboundError> {
+        check_session(
+            self.get_session_with_server(server_id),
+            Protocol::JobDeclarationProtocol,
+        )?;
+        match message {
+            JobDeclaration::AllocateMiningJobToken(_) | JobDeclaration::PushSolution(_) => Ok(()),
+            JobDeclaration::DeclareMiningJob(m) => {
+                if self.is_mining_job_token_allocated(server_id, m.mining_job_token.inner_as_ref())
+                {
+                    Ok(())
+                } else {
+                    Err(OutboundError::MiningJobTokenNotAllocated)
+                }
+            }
+            JobDeclaration::ProvideMissingTransactionsSuccess(m) => {
+                if self.is_declare_mining_job_pending(server_id, m.request_id) {
+                    Ok(())
+                } else {
+                    Err(OutboundError::UnknownRequest(m.request_id))
+                }
+            }
+            _ => Err(OutboundError::WrongDirection {
+                message_type: message.message_type(),
+            }),
+        }
+    }
+}
+
+/// Checker for Template Distribution messages sent to clients.
+///
+/// The client ID identifies which client a message is sent to, as for the handler traits.
+pub trait ValidateTemplateDistributionMessagesToClient {
+    /// Returns the [`Session`] tracking the connection with a client.
+    fn get_session_with_client(&self, client_id: Option<usize>) -> Option<&Session>;
+
+    /// Returns true if a template with this ID was sent to a client with `NewTemplate`.
+    fn is_template_sent(&self, client_id: Option<usize>, template_id: u64) -> bool;
+
+    /// Checks that the connection is set up for the Template Distribution Protocol, that the
+    /// message is one a Template Provider sends, and that `SetNewPrevHash` and
+    /// `RequestTransactionData.Success` refer to a template sent before.
+    fn validate_template_distribution_message_to_client(
+        &self,
+        client_id: Option<usize>,
+        message: &TemplateDistribution<'_>,
+    ) -> Result<(), OutboundError> {
+        check_session(
+            self.get_session_with_client(client_id),
+            Protocol::TemplateDistributionProtocol,
+        )?;
+        let sent_template = |template_id| {
+            if self.is_template_sent(client_id, template_id) {
+                Ok(())
+            } else {
+                Err(OutboundError::UnknownTemplate(template_id))
+            }
+        };
+        match message {
+            TemplateDistribution::SetNewPrevHash(m) => sent_template(m.template_id),
+            TemplateDistribution::RequestTransactionDataSuccess(m) => sent_template(m.template_id),
+            // errors may answer requests for unknown templates
+            TemplateDistribution::NewTemplate(_)
+            | TemplateDistribution::RequestTransactionDataError(_) => Ok(()),
+            _ => Err(OutboundError::WrongDirection {
+                message_type: message.message_type(),
+            }),
+        }
+    }
+}
+
+/// Checker for Template Distribution messages sent to servers.
+///
+/// The server ID identifies which server a message is sent to, as for the handler traits.
+pub trait ValidateTemplateDistributionMessagesToServer {
+    /// Returns the [`Session`] tracking the connection with a server.
+    fn get_session_with_server(&self, server_id: Option<usize>) -> Option<&Session>;
+
+    /// Returns true if a template with this ID was received from a server with `NewTemplate`.
+    fn is_template_received(&self, server_id: Option<usize>, template_id: u64) -> bool;
+
+    /// Checks that the connection is set up for the Template Distribution Protocol, that the
+    /// message is one a Template Provider client sends, and that `RequestTransactionData` and
+    /// `SubmitSolution` refer to a template received before.
+    fn validate_template_distribution_message_to_server(
+        &self,
+        server_id: Option<usize>,
+        message: &TemplateDistribution<'_>,
+    ) -> Result<(), OutboundError> {
+        check_session(
+            self.get_session_with_server(server_id),
+            Protocol::TemplateDistributionProtocol,
+        )?;
+        let received_template = |template_id| {
+            if self.is_template_received(server_id, template_id) {
+                Ok(())
+            } else {
+                Err(OutboundError::UnknownTemplate(template_id))
+            }
+        };
+        match message {
+            TemplateDistribution::CoinbaseOutputConstraints(_) => Ok(()),
+            TemplateDistribution::RequestTransactionData(m) => received_template(m.template_id),
+            TemplateDistribution::SubmitSolution(m) => received_template(m.template_id),
+            _ => Err(OutboundError::WrongDirection {
+                message_type: message.message_type(),
+            }),
+        }
+    }
+}
+
+fn check_session(session: Option<&Session>, protocol: Protocol) -> Result<(), OutboundError> {
+    let session = session.ok_or(OutboundError::Session(SessionError::HandshakeNotCompleted))?;
+    match session.get_state() {
+        SessionState::Handshake => Err(OutboundError::Session(SessionError::HandshakeNotCompleted)),
+        SessionState::SetUp {
+            protocol: negotiated,
+            ..
+        } if negotiated != protocol => Err(OutboundError::Session(
+            SessionError::ProtocolNotNegotiated {
+                negotiated,
+                received: protocol,
+            },
+        )),
+        SessionState::SetUp { .. } => Ok(()),
+        _ => Err(OutboundError::Session(
+            SessionError::SetupConnectionRequired,
+        )),
+    }
+}
+
+fn check_work_selection(session: Option<&Session>) -> Result<(), OutboundError> {
+    match session.and_then(Session::get_flags) {
+        Some(flags) if has_work_selection(flags) => Ok(()),
+        _ => Err(OutboundError::WorkSelectionNotNegotiated),
+    }
+}
+
+fn check_channel_type(
+    channel_id: u32,
+    channel: &ChannelParameters,
+    allowed: &[ChannelType],
+    message_type: u8,
+) -> Result<(), OutboundError> {
+    if allowed.contains(&channel.channel_type) {
+        Ok(())
+    } else {
+        Err(OutboundError::WrongChannelType {
+            channel_id,
+            channel_type: channel.channel_type,
+            message_type,
+        })
+    }
+}
+
+// Compares two little-endian 256-bit integers.
+fn is_above(target: &[u8], max_target: &[u8; 32]) -> bool {
+    target.iter().rev().cmp(max_target.iter().rev()) == core::cmp::Ordering::Greater
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::negotiation::{ProtocolCapabilities, SetupConnectionNegotiator};
+    use binary_sv2::U256;
+    use common_messages_sv2::SetupConnection;
+    use job_declaration_sv2::{
+        AllocateMiningJobToken, DeclareMiningJob, DeclareMiningJobSuccess,
+        ProvideMissingTransactionsSuccess,
+    };
+    use mining_sv2::{
+        CloseChannel, SetCustomMiningJobSuccess, SetTarget, SubmitSharesStandard,
+        SubmitSharesSuccess, UpdateChannel,
+    };
+    use std::collections::HashMap;
+    use template_distribution_sv2::{
+        CoinbaseOutputConstraints, RequestTransactionData, RequestTransactionDataError,
+        SetNewPrevHash, SubmitSolution,
+    };
+
+    // Work selection, as a client sets it in `SetupConnection` flags.
+    const WORK_SELECTION: u32 = 0b10;
+
+    #[derive(Default)]
+    struct Peer {
+        session: Option<Session>,
+        channels: HashMap<u32, ChannelParameters>,
+        mining_job_tokens: Vec<Vec<u8>>,
+        pending_requests: Vec<u32>,
+        templates: Vec<u64>,
+    }
+
+    impl Peer {
+        fn set_up(protocol: Protocol, flags: u32) -> Self {
+            let mut session = Session::new();
+            session.on_handshake_completed().unwrap();
+            let setup_connection = SetupConnection {
+                protocol,
+                min_version: 2,
+                max_version: 2,
+                flags,
+                endpoint_host: "0.0.0.0".to_string().try_into().unwrap(),
+                endpoint_port: 3333,
+                vendor: "vendor".to_string().try_into().unwrap(),
+                hardware_version: "".to_string().try_into().unwrap(),
+                firmware: "".to_string().try_into().unwrap(),
+                device_id: "".to_string().try_into().unwrap(),
+            };
+            let mut capabilities = ProtocolCapabilities::new(protocol, 2, 2);
+            capabilities.optional_flags = flags;
+            let negotiator = SetupConnectionNegotiator::new().with_protocol(capabilities);
+            session
+                .on_setup_connection(&setup_connection, &negotiator)
+                .unwrap();
+            Self {
+                session: Some(session),
+                ..Default::default()
+            }
+        }
+
+        fn with_channel(
+            mut self,
+            channel_id: u32,
+            channel_type: ChannelType,
+            max_target: Option<[u8; 32]>,
+        ) -> Self {
+            self.channels.insert(
+                channel_id,
+                ChannelParameters {
+                    channel_type,
+                    max_target,
+                },
+            );
+            self
+        }
+    }
+
+    impl ValidateMiningMessagesToClient for Peer {
+        fn get_session_with_client(&self, _client_id: Option<usize>) -> Option<&Session> {
+            self.session.as_ref()
+        }
+
+        fn get_channel_with_client(
+            &self,
+            _client_id: Option<usize>,
+            channel_id: u32,
+        ) -> Option<ChannelParameters> {
+            self.channels.get(&channel_id).cloned()
+        }
+    }
+
+    impl ValidateMiningMessagesToServer for Peer {
+        fn get_session_with_server(&self, _server_id: Option<usize>) -> Option<&Session> {
+            self.session.as_ref()
+        }
+
+        fn get_channel_with_server(
+            &self,
+            _server_id: Option<usize>,
+            channel_id: u32,
+        ) -> Option<ChannelParameters> {
+            self.channels.get(&channel_id).cloned()
+        }
+    }
+
+    impl ValidateJobDeclarationMessagesToClient for Peer {
+        fn get_session_with_client(&self, _client_id: Option<usize>) -> Option<&Session> {
+            self.session.as_ref()
+        }
+
+        fn is_declare_mining_job_pending(
+            &self,
+            _client_id: Option<usize>,
+            request_id: u32,
+        ) -> bool {
+            self.pending_requests.contains(&request_id)
+        }
+    }
+
+    impl ValidateJobDeclarationMessagesToServer for Peer {
+        fn get_session_with_server(&self, _server_id: Option<usize>) -> Option<&Session> {
+            self.session.as_ref()
+        }
+
+        fn is_mining_job_token_allocated(
+            &self,
+            _server_id: Option<usize>,
+            mining_job_token: &[u8],
+        ) -> bool {
+            self.mining_job_tokens
+                .iter()
+                .any(|token| token == mining_job_token)
+        }
+
+        fn is_declare_mining_job_pending(
+            &self,
+            _server_id: Option<usize>,
+            request_id: u32,
+        ) -> bool {
+            self.pending_requests.contains(&request_id)
+        }
+    }
+
+    impl ValidateTemplateDistributionMessagesToClient for Peer {
+        fn get_session_with_client(&self, _client_id: Option<usize>) -> Option<&Session> {
+            self.session.as_ref()
+        }
+
+        fn is_template_sent(&self, _client_id: Option<usize>, template_id: u64) -> bool {
+            self.templates.contains(&template_id)
+        }
+    }
+
+    impl ValidateTemplateDistributionMessagesToServer for Peer {
+        fn get_session_with_server(&self, _server_id: Option<usize>) -> Option<&Session> {
+            self.session.as_ref()
+        }
+
+        fn is_template_received(&self, _server_id: Option<usize>, template_id: u64) -> bool {
+            self.templates.contains(&template_id)
+        }
+    }
+
+    fn set_target(channel_id: u32, maximum_target: [u8; 32]) -> Mining<'static> {
+        Mining::SetTarget(SetTarget {
+            channel_id,
+            maximum_target: U256::from(maximum_target),
+        })
+    }
+
+    fn submit_shares_success(channel_id: u32) -> Mining<'static> {
+        Mining::SubmitSharesSuccess(SubmitSharesSuccess {
+            channel_id,
+            last_sequence_number: 1,
+            new_submits_accepted_count: 1,
+            new_shares_sum: 1,
+        })
+    }
+
+    fn update_channel(channel_id: u32) -> Mining<'static> {
+        Mining::UpdateChannel(UpdateChannel {
+            channel_id,
+            nominal_hash_rate: 1.0,
+            maximum_target: U256::from([0xff; 32]),
+        })
+    }
+
+    #[test]
+    fn test_session_not_set_up() {
+        let peer = Peer::default();
+        assert_eq!(
+            peer.validate_mining_message_to_client(None, &submit_shares_success(1)),
+            Err(OutboundError::Session(SessionError::HandshakeNotCompleted))
+        );
+
+        let mut session = Session::new();
+        session.on_handshake_completed().unwrap();
+        let peer = Peer {
+            session: Some(session),
+            ..Default::default()
+        };
+        assert_eq!(
+            peer.validate_mining_message_to_server(None, &update_channel(1)),
+            Err(OutboundError::Session(
+                SessionError::SetupConnectionRequired
+            ))
+        );
+    }
+
+    #[test]
+    fn test_protocol_not_negotiated() {
+        let peer = Peer::set_up(Protocol::MiningProtocol, 0);
+        let message =
+            TemplateDistribution::RequestTransactionData(RequestTransactionData { template_id: 1 });
+        assert_eq!(
+            peer.validate_template_distribution_message_to_client(None, &message),
+            Err(OutboundError::Session(
+                SessionError::ProtocolNotNegotiated {
+                    negotiated: Protocol::MiningProtocol,
+                    received: Protocol::TemplateDistributionProtocol,
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_wrong_direction() {
+        let peer = Peer::set_up(Protocol::MiningProtocol, 0).with_channel(
+            1,
+            ChannelType::Standard,
+            Some([0xff; 32]),
+        );
+        assert_eq!(
+            peer.validate_mining_message_to_client(None, &update_channel(1)),
+            Err(OutboundError::WrongDirection {
+                message_type: mining_sv2::MESSAGE_TYPE_UPDATE_CHANNEL
+            })
+        );
+        assert_eq!(
+            peer.validate_mining_message_to_server(None, &submit_shares_success(1)),
+            Err(OutboundError::WrongDirection {
+                message_type: mining_sv2::MESSAGE_TYPE_SUBMIT_SHARES_SUCCESS
+            })
+        );
+
+        let peer = Peer::set_up(Protocol::JobDeclarationProtocol, 0);
+        let message = JobDeclaration::AllocateMiningJobToken(AllocateMiningJobToken {
+            user_identifier: "user".to_string().try_into().unwrap(),
+            request_id: 1,
+        });
+        assert_eq!(
+            peer.validate_job_declaration_message_to_server(None, &message),
+            Ok(())
+        );
+
+        let peer = Peer::set_up(Protocol::TemplateDistributionProtocol, 0);
+        let message = TemplateDistribution::CoinbaseOutputConstraints(CoinbaseOutputConstraints {
+            coinbase_output_max_additional_size: 100,
+            coinbase_output_max_additional_sigops: 1,
+        });
+        assert_eq!(
+            peer.validate_template_distribution_message_to_client(None, &message),
+            Err(OutboundError::WrongDirection {
+                message_type: template_distribution_sv2::MESSAGE_TYPE_COINBASE_OUTPUT_CONSTRAINTS
+            })
+        );
+    }
+
+    #[test]
+    fn test_unknown_channel() {
+        let peer = Peer::set_up(Protocol::MiningProtocol, 0);
+        assert_eq!(
+            peer.validate_mining_message_to_client(None, &submit_shares_success(1)),
+            Err(OutboundError::UnknownChannel(1))
+        );
+        assert_eq!(
+            peer.validate_mining_message_to_server(None, &update_channel(1)),
+            Err(OutboundError::UnknownChannel(1))
+        );
+        // error messages may answer requests for unknown channels
+        let message = Mining::SubmitSharesError(mining_sv2::SubmitSharesError {
+            channel_id: 1,
+            sequence_number: 1,
+            error_code: "invalid-channel-id".to_string().try_into().unwrap(),
+        });
+        assert_eq!(
+            peer.validate_mining_message_to_client(None, &message),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_wrong_channel_type() {
+        let peer =
+            Peer::set_up(Protocol::MiningProtocol, 0).with_channel(1, ChannelType::Group, None);
+        let message = Mining::SubmitSharesStandard(SubmitSharesStandard {
+            channel_id: 1,
+            sequence_number: 1,
+            job_id: 1,
+            nonce: 1,
+            ntime: 1,
+            version: 1,
+        });
+        assert_eq!(
+            peer.validate_mining_message_to_server(None, &message),
+            Err(OutboundError::WrongChannelType {
+                channel_id: 1,
+                channel_type: ChannelType::Group,
+                message_type: mining_sv2::MESSAGE_TYPE_SUBMIT_SHARES_STANDARD,
+            })
+        );
+        // the target of a group channel applies to every channel of the group
+        assert_eq!(
+            peer.validate_mining_message_to_client(None, &set_target(1, [0xff; 32])),
+            Ok(())
+        );
+        let message = Mining::CloseChannel(CloseChannel {
+            channel_id: 1,
+            reason_code: "".to_string().try_into().unwrap(),
+        });
+        assert_eq!(
+            peer.validate_mining_message_to_server(None, &message),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_target_above_max_target() {
+        let mut max_target = [0; 32];
+        max_target[31] = 0x01;
+        let peer = Peer::set_up(Protocol::MiningProtocol, 0).with_channel(
+            1,
+            ChannelType::Extended,
+            Some(max_target),
+        );
+        assert_eq!(
+            peer.validate_mining_message_to_client(None, &set_target(1, max_target)),
+            Ok(())
+        );
+
+        // above in the most significant byte, which is the last one
+        let mut target = [0; 32];
+        target[31] = 0x02;
+        assert_eq!(
+            peer.validate_mining_message_to_client(None, &set_target(1, target)),
+            Err(OutboundError::TargetAboveMaxTarget(1))
+        );
+
+        // below, despite the greater least significant bytes
+        let mut target = [0xff; 32];
+        target[31] = 0x00;
+        assert_eq!(
+            peer.validate_mining_message_to_client(None, &set_target(1, target)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_work_selection_not_negotiated() {
+        let message = Mining::SetCustomMiningJobSuccess(SetCustomMiningJobSuccess {
+            channel_id: 1,
+            request_id: 1,
+            job_id: 1,
+        });
+        let peer = Peer::set_up(Protocol::MiningProtocol, 0).with_channel(
+            1,
+            ChannelType::Extended,
+            Some([0xff; 32]),
+        );
+        assert_eq!(
+            peer.validate_mining_message_to_client(None, &message),
+            Err(OutboundError::WorkSelectionNotNegotiated)
+        );
+
+        let peer = Peer::set_up(Protocol::MiningProtocol, WORK_SELECTION).with_channel(
+            1,
+            ChannelType::Extended,
+            Some([0xff; 32]),
+        );
+        assert_eq!(
+            peer.validate_mining_message_to_client(None, &message),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_declare_mining_job_before_token_allocated() {
+        let mut peer = Peer::set_up(Protocol::JobDeclarationProtocol, 0);
+        let message = JobDeclaration::DeclareMiningJob(DeclareMiningJob {
+            request_id: 1,
+            mining_job_token: vec![0x01, 0x02].try_into().unwrap(),
+            version: 0x20000000,
+            coinbase_tx_prefix: vec![].try_into().unwrap(),
+            coinbase_tx_suffix: vec![].try_into().unwrap(),
+            wtxid_list: vec![].into(),
+            excess_data: vec![].try_into().unwrap(),
+        });
+        assert_eq!(
+            peer.validate_job_declaration_message_to_server(None, &message),
+            Err(OutboundError::MiningJobTokenNotAllocated)
+        );
+
+        // allocated with AllocateMiningJobToken.Success
+        peer.mining_job_tokens.push(vec![0x01, 0x02]);
+        assert_eq!(
+            peer.validate_job_declaration_message_to_server(None, &message),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_job_declaration_responses_answer_pending_requests() {
+        let mut peer = Peer::set_up(Protocol::JobDeclarationProtocol, 0);
+        let provide_missing_transactions_success =
+            JobDeclaration::ProvideMissingTransactionsSuccess(ProvideMissingTransactionsSuccess {
+                request_id: 1,
+                transaction_list: vec![].into(),
+            });
+        let declare_mining_job_success =
+            JobDeclaration::DeclareMiningJobSuccess(DeclareMiningJobSuccess {
+                request_id: 1,
+                new_mining_job_token: vec![0x01].try_into().unwrap(),
+            });
+        assert_eq!(
+            peer.validate_job_declaration_message_to_server(
+                None,
+                &provide_missing_transactions_success
+            ),
+            Err(OutboundError::UnknownRequest(1))
+        );
+        assert_eq!(
+            peer.validate_job_declaration_message_to_client(None, &declare_mining_job_success),
+            Err(OutboundError::UnknownRequest(1))
+        );
+
+        peer.pending_requests.push(1);
+        assert_eq!(
+            peer.validate_job_declaration_message_to_server(
+                None,
+                &provide_missing_transactions_success
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            peer.validate_job_declaration_message_to_client(None, &declare_mining_job_success),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_unknown_template() {
+        let mut peer = Peer::set_up(Protocol::TemplateDistributionProtocol, 0);
+        let set_new_prev_hash = TemplateDistribution::SetNewPrevHash(SetNewPrevHash {
+            template_id: 1,
+            prev_hash: U256::from([0; 32]),
+            header_timestamp: 1,
+            n_bits: 1,
+            target: U256::from([0xff; 32]),
+        });
+        let submit_solution = TemplateDistribution::SubmitSolution(SubmitSolution {
+            template_id: 1,
+            version: 1,
+            header_timestamp: 1,
+            header_nonce: 1,
+            coinbase_tx: vec![].try_into().unwrap(),
+        });
+        assert_eq!(
+            peer.validate_template_distribution_message_to_client(None, &set_new_prev_hash),
+            Err(OutboundError::UnknownTemplate(1))
+        );
+        assert_eq!(
+            peer.validate_template_distribution_message_to_server(None, &submit_solution),
+            Err(OutboundError::UnknownTemplate(1))
+        );
+        // errors may answer requests for unknown templates
+        let message =
+            TemplateDistribution::RequestTransactionDataError(RequestTransactionDataError {
+                template_id: 1,
+                error_code: "template-id-not-found".to_string().try_into().unwrap(),
+            });
+        assert_eq!(
+            peer.validate_template_distribution_message_to_client(None, &message),
+            Ok(())
+        );
+
+        peer.templates.push(1);
+        assert_eq!(
+            peer.validate_template_distribution_message_to_client(None, &set_new_prev_hash),
+            Ok(())
+        );
+        assert_eq!(
+            peer.validate_template_distribution_message_to_server(None, &submit_solution),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,660 @@
+use common_messages_sv2::{
+    has_requires_std_job, has_work_selection, Protocol, SetupConnection, SetupConnectionErrorCode,
+    SetupConnectionSuccess, MESSAGE_TYPE_SETUP_CONNECTION, MESSAGE_TYPE_SETUP_CONNECTION_ERROR,
+    MESSAGE_TYPE_SETUP_CONNECTION_SUCCESS,
+};
+use extensions_sv2::EXTENSION_TYPE_EXTENSIONS_NEGOTIATION;
+use framing_sv2::header::Header;
+use mining_sv2::{MESSAGE_TYPE_OPEN_EXTENDED_MINING_CHANNEL, MESSAGE_TYPE_SET_CUSTOM_MINING_JOB};
+
+use crate::{
+    dispatcher::FrameRoute,
+    negotiation::{NegotiatedConnection, NegotiationError, SetupConnectionNegotiator},
+};
+
+/// The stage of a Sv2 connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionState {
+    /// The noise handshake is not completed yet.
+    Handshake,
+    /// The handshake is completed, and `SetupConnection` was not sent yet.
+    AwaitingSetupConnection,
+    /// `SetupConnection` was sent to the server, which did not answer yet.
+    ///
+    /// Only reached on the client side of a connection.
+    AwaitingSetupConnectionSuccess { protocol: Protocol, flags: u32 },
+    /// The connection is set up for `protocol`, with the `flags` sent by the client in
+    /// `SetupConnection`.
+    SetUp { protocol: Protocol, flags: u32 },
+}
+
+/// Errors of messages received out of order, or for a subprotocol other than the negotiated one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionError {
+    /// A message was received before the noise handshake was completed.
+    HandshakeNotCompleted,
+    /// The noise handshake was reported as completed twice.
+    HandshakeAlreadyCompleted,
+    /// A message other than `SetupConnection` (or its response) was received before the
+    /// connection was set up.
+    SetupConnectionRequired,
+    /// A `SetupConnection` (or its response) was received on a connection already set up, or
+    /// before `SetupConnection` was sent.
+    UnexpectedSetupConnection,
+    /// The client asked for a subprotocol, version or flags this server doesn't support,
+    /// according to [`SetupConnectionNegotiator::negotiate`].
+    Negotiation(NegotiationError),
+    /// The flags of `SetupConnection.Success` are not compatible with the flags sent in
+    /// `SetupConnection`, according to [`SetupConnection::check_flags`].
+    UnsupportedFeatureFlags(u32),
+    /// A message of a subprotocol other than the one negotiated was received.
+    ProtocolNotNegotiated {
+        negotiated: Protocol,
+        received: Protocol,
+    },
+    /// An extension message was received for an extension that was not negotiated.
+    ExtensionNotNegotiated(u16),
+    /// `OpenExtendedMiningChannel` was received from a client that set `REQUIRES_STANDARD_JOBS`
+    /// in `SetupConnection`.
+    StandardJobsRequired,
+    /// `SetCustomMiningJob` was received from a client that didn't set `REQUIRES_WORK_SELECTION`
+    /// in `SetupConnection`.
+    WorkSelectionNotNegotiated,
+    /// The message type is not known.
+    UnknownMessage,
+}
+
+impl SessionError {
+    /// Returns the `SetupConnection.Error` error code to answer with, for errors raised by
+    /// [`Session::on_setup_connection`].
+    pub fn setup_connection_error_code(&self) -> Option<SetupConnectionErrorCode> {
+        match self {
+            SessionError::Negotiation(error) => error.setup_connection_error_code(),
+            _ => None,
+        }
+    }
+}
+
+/// Tracks the stage of a Sv2 connection, to reject messages before they reach the handlers.
+///
+/// The session goes through the handshake, `SetupConnection` and extensions negotiation. The
+/// owner of the session reports each step with the `on_*` methods, and checks every received
+/// frame with [`Session::check_frame_from_client`] or [`Session::check_frame_from_server`]
+/// (which the `DispatchFrames*` traits do via `get_session_with_client` /
+/// `get_session_with_server`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    state: SessionState,
+    negotiated_extensions: Vec<u16>,
+}
+
+impl Session {
+    /// Creates a session for a connection whose handshake is not completed yet.
+    pub fn new() -> Self {
+        Self {
+            state: SessionState::Handshake,
+            negotiated_extensions: Vec::new(),
+        }
+    }
+
+    /// Returns the stage of the connection.
+    pub fn get_state(&self) -> SessionState {
+        self.state
+    }
+
+    /// Returns the negotiated subprotocol, if the connection is set up.
+    pub fn get_protocol(&self) -> Option<Protocol> {
+        match self.state {
+            SessionState::SetUp { protocol, .. } => Some(protocol),
+            _ => None,
+        }
+    }
+
+    /// Returns the flags sent by the client in `SetupConnection`, if the connection is set up.
+    pub fn get_flags(&self) -> Option<u32> {
+        match self.state {
+            SessionState::SetUp { flags, .. } => Some(flags),
+            _ => None,
+        }
+    }
+
+    /// Returns the negotiated extension types.
+    pub fn get_negotiated_extensions(&self) -> &[u16] {
+        &self.negotiated_extensions
+    }
+
+    /// Moves the session past the noise handshake.
+    pub fn on_handshake_completed(&mut self) -> Result<(), SessionError> {
+        match self.state {
+            SessionState::Handshake => {
+                self.state = SessionState::AwaitingSetupConnection;
+                Ok(())
+            }
+            _ => Err(SessionError::HandshakeAlreadyCompleted),
+        }
+    }
+
+    /// Sets the connection up with a `SetupConnection` received from a client, negotiated with
+    /// [`SetupConnectionNegotiator::negotiate`].
+    ///
+    /// Returns the `SetupConnection.Success` to answer with. On error, the session is left
+    /// untouched and [`SessionError::setup_connection_error_code`] gives the error code to answer
+    /// with.
+    pub fn on_setup_connection(
+        &mut self,
+        setup_connection: &SetupConnection,
+        negotiator: &SetupConnectionNegotiator,
+    ) -> Result<(SetupConnectionSuccess, NegotiatedConnection), SessionError> {
+        match self.state {
+            SessionState::Handshake => return Err(SessionError::HandshakeNotCompleted),
+            SessionState::AwaitingSetupConnection => {}
+            _ => return Err(SessionError::UnexpectedSetupConnection),
+        }
+
+        let (setup_connection_success, negotiated_connection) = negotiator
+            .negotiate(setup_connection)
+            .map_err(SessionError::Negotiation)?;
+
+        self.state = SessionState::SetUp {
+            protocol: negotiated_connection.get_protocol(),
+            flags: negotiated_connection.get_flags(),
+        };
+        Ok((setup_connection_success, negotiated_connection))
+    }
+
+    /// Records a `SetupConnection` sent to a server.
+    pub fn on_setup_connection_sent(
+        &mut self,
+        setup_connection: &SetupConnection,
+    ) -> Result<(), SessionError> {
+        match self.state {
+            SessionState::Handshake => Err(SessionError::HandshakeNotCompleted),
+            SessionState::AwaitingSetupConnection => {
+                self.state = SessionState::AwaitingSetupConnectionSuccess {
+                    protocol: setup_connection.protocol,
+                    flags: setup_connection.flags,
+                };
+                Ok(())
+            }
+            _ => Err(SessionError::UnexpectedSetupConnection),
+        }
+    }
+
+    /// Sets the connection up with a `SetupConnection.Success` received from a server.
+    ///
+    /// The flags required by the server are checked against the flags sent in `SetupConnection`
+    /// with [`SetupConnection::check_flags`].
+    pub fn on_setup_connection_success(
+        &mut self,
+        setup_connection_success: &SetupConnectionSuccess,
+    ) -> Result<(), SessionError> {
+        let (protocol, flags) = match self.state {
+            SessionState::Handshake => return Err(SessionError::HandshakeNotCompleted),
+            SessionState::AwaitingSetupConnectionSuccess { protocol, flags } => (protocol, flags),
+            _ => return Err(SessionError::UnexpectedSetupConnection),
+        };
+
+        if !flags_are_compatible(protocol, setup_connection_success.flags, flags) {
+            return Err(SessionError::UnsupportedFeatureFlags(
+                setup_connection_success.flags,
+            ));
+        }
+
+        self.state = SessionState::SetUp { protocol, flags };
+        Ok(())
+    }
+
+    /// Records the extensions negotiated with `RequestExtensions`.
+    pub fn on_extensions_negotiated(&mut self, extensions: &[u16]) -> Result<(), SessionError> {
+        if !matches!(self.state, SessionState::SetUp { .. }) {
+            return Err(SessionError::SetupConnectionRequired);
+        }
+
+        for extension in extensions {
+            if !self.negotiated_extensions.contains(extension) {
+                self.negotiated_extensions.push(*extension);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that a frame received from a client is allowed at this stage of the connection.
+    pub fn check_frame_from_client(&self, header: &Header) -> Result<(), SessionError> {
+        self.check_frame(header, true)
+    }
+
+    /// Checks that a frame received from a server is allowed at this stage of the connection.
+    pub fn check_frame_from_server(&self, header: &Header) -> Result<(), SessionError> {
+        self.check_frame(header, false)
+    }
+
+    fn check_frame(&self, header: &Header, from_client: bool) -> Result<(), SessionError> {
+        let route = FrameRoute::from_header(header).map_err(|_| SessionError::UnknownMessage)?;
+        let setup_message_types: &[u8] = match from_client {
+            true => &[MESSAGE_TYPE_SETUP_CONNECTION],
+            false => &[
+                MESSAGE_TYPE_SETUP_CONNECTION_SUCCESS,
+                MESSAGE_TYPE_SETUP_CONNECTION_ERROR,
+            ],
+        };
+        let is_setup_message =
+            route == FrameRoute::Common && setup_message_types.contains(&header.msg_type());
+
+        let (negotiated, flags) = match (self.state, from_client) {
+            (SessionState::Handshake, _) => return Err(SessionError::HandshakeNotCompleted),
+            (SessionState::SetUp { protocol, flags }, _) => (protocol, flags),
+            // the setup message is expected
+            (SessionState::AwaitingSetupConnection, true)
+            | (SessionState::AwaitingSetupConnectionSuccess { .. }, false)
+                if is_setup_message =>
+            {
+                return Ok(())
+            }
+            _ if is_setup_message => return Err(SessionError::UnexpectedSetupConnection),
+            _ => return Err(SessionError::SetupConnectionRequired),
+        };
+
+        if is_setup_message {
+            return Err(SessionError::UnexpectedSetupConnection);
+        }
+
+        match route {
+            FrameRoute::Common => Ok(()),
+            FrameRoute::Extensions => {
+                let extension_type = header.ext_type_without_channel_msg();
+                if extension_type == EXTENSION_TYPE_EXTENSIONS_NEGOTIATION
+                    || self.negotiated_extensions.contains(&extension_type)
+                {
+                    Ok(())
+                } else {
+                    Err(SessionError::ExtensionNotNegotiated(extension_type))
+                }
+            }
+            _ => match route.protocol() {
+                Some(received) if received != negotiated => {
+                    Err(SessionError::ProtocolNotNegotiated {
+                        negotiated,
+                        received,
+                    })
+                }
+                Some(Protocol::MiningProtocol) if from_client => {
+                    check_mining_flags(header.msg_type(), flags)
+                }
+                _ => Ok(()),
+            },
+        }
+    }
+}
+
+// Checks a Mining Protocol message from a client against the flags it sent in `SetupConnection`.
+fn check_mining_flags(message_type: u8, flags: u32) -> Result<(), SessionError> {
+    match message_type {
+        MESSAGE_TYPE_OPEN_EXTENDED_MINING_CHANNEL if has_requires_std_job(flags) => {
+            Err(SessionError::StandardJobsRequired)
+        }
+        MESSAGE_TYPE_SET_CUSTOM_MINING_JOB if !has_work_selection(flags) => {
+            Err(SessionError::WorkSelectionNotNegotiated)
+        }
+        _ => Ok(()),
+    }
+}
+
+// The Template Distribution Protocol defines no flags, so there is nothing to check.
+fn flags_are_compatible(protocol: Protocol, required_flags: u32, flags: u32) -> bool {
+    protocol == Protocol::TemplateDistributionProtocol
+        || SetupConnection::check_flags(protocol, required_flags, flags)
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::negotiation::ProtocolCapabilities;
+    use extensions_sv2::{EXTENSION_TYPE_SHARE_REJECT_DETAIL, MESSAGE_TYPE_REQUEST_EXTENSIONS};
+    use job_declaration_sv2::MESSAGE_TYPE_DECLARE_MINING_JOB;
+    use mining_sv2::{
+        MESSAGE_TYPE_OPEN_STANDARD_MINING_CHANNEL, MESSAGE_TYPE_SUBMIT_SHARES_STANDARD,
+    };
+
+    fn header(extension_type: u16, msg_type: u8) -> Header {
+        let [extension_type_0, extension_type_1] = extension_type.to_le_bytes();
+        Header::from_bytes(&[extension_type_0, extension_type_1, msg_type, 0, 0, 0]).unwrap()
+    }
+
+    fn setup_connection(protocol: Protocol, flags: u32) -> SetupConnection<'static> {
+        SetupConnection {
+            protocol,
+            min_version: 2,
+            max_version: 2,
+            flags,
+            endpoint_host: "0.0.0.0".to_string().try_into().unwrap(),
+            endpoint_port: 3333,
+            vendor: "vendor".to_string().try_into().unwrap(),
+            hardware_version: "".to_string().try_into().unwrap(),
+            firmware: "".to_string().try_into().unwrap(),
+            device_id: "".to_string().try_into().unwrap(),
+        }
+    }
+
+    // A server supporting version 2 of `protocol`, requiring `required_flags`.
+    fn negotiator(protocol: Protocol, required_flags: u32) -> SetupConnectionNegotiator {
+        let mut capabilities = ProtocolCapabilities::new(protocol, 2, 2);
+        capabilities.required_flags = required_flags;
+        SetupConnectionNegotiator::new().with_protocol(capabilities)
+    }
+
+    fn set_up_session(protocol: Protocol) -> Session {
+        set_up_session_with_flags(protocol, 0)
+    }
+
+    fn set_up_session_with_flags(protocol: Protocol, flags: u32) -> Session {
+        let mut session = Session::new();
+        session.on_handshake_completed().unwrap();
+        let mut capabilities = ProtocolCapabilities::new(protocol, 2, 2);
+        capabilities.optional_flags = flags;
+        session
+            .on_setup_connection(
+                &setup_connection(protocol, flags),
+                &SetupConnectionNegotiator::new().with_protocol(capabilities),
+            )
+            .unwrap();
+        session
+    }
+
+    #[test]
+    fn test_handshake_completed_twice() {
+        let mut session = Session::new();
+        session.on_handshake_completed().unwrap();
+        assert_eq!(
+            session.on_handshake_completed(),
+            Err(SessionError::HandshakeAlreadyCompleted)
+        );
+        assert_eq!(session.get_state(), SessionState::AwaitingSetupConnection);
+    }
+
+    #[test]
+    fn test_setup_connection_before_handshake() {
+        let mut session = Session::new();
+        let setup_connection = setup_connection(Protocol::MiningProtocol, 0);
+        assert_eq!(
+            session
+                .on_setup_connection(&setup_connection, &negotiator(Protocol::MiningProtocol, 0)),
+            Err(SessionError::HandshakeNotCompleted)
+        );
+        assert_eq!(
+            session.on_setup_connection_sent(&setup_connection),
+            Err(SessionError::HandshakeNotCompleted)
+        );
+        assert_eq!(
+            session.check_frame_from_client(&header(0, MESSAGE_TYPE_SETUP_CONNECTION)),
+            Err(SessionError::HandshakeNotCompleted)
+        );
+        assert_eq!(session.get_state(), SessionState::Handshake);
+    }
+
+    #[test]
+    fn test_setup_connection_unsupported_protocol() {
+        let mut session = Session::new();
+        session.on_handshake_completed().unwrap();
+        let error = session
+            .on_setup_connection(
+                &setup_connection(Protocol::JobDeclarationProtocol, 0),
+                &negotiator(Protocol::MiningProtocol, 0),
+            )
+            .unwrap_err();
+        assert_eq!(
+            error,
+            SessionError::Negotiation(NegotiationError::UnsupportedProtocol(
+                Protocol::JobDeclarationProtocol
+            ))
+        );
+        assert_eq!(
+            error.setup_connection_error_code(),
+            Some(SetupConnectionErrorCode::UnsupportedProtocol)
+        );
+        // the session is left untouched
+        assert_eq!(session.get_state(), SessionState::AwaitingSetupConnection);
+    }
+
+    #[test]
+    fn test_setup_connection_unsupported_feature_flags() {
+        let mut session = Session::new();
+        session.on_handshake_completed().unwrap();
+        // the server requires the flag 0b10, which the client doesn't set
+        let error = session
+            .on_setup_connection(
+                &setup_connection(Protocol::MiningProtocol, 0),
+                &negotiator(Protocol::MiningProtocol, 0b10),
+            )
+            .unwrap_err();
+        assert_eq!(
+            error,
+            SessionError::Negotiation(NegotiationError::UnsupportedFeatureFlags(0b10))
+        );
+        assert_eq!(
+            error.setup_connection_error_code(),
+            Some(SetupConnectionErrorCode::UnsupportedFeatureFlags)
+        );
+        assert_eq!(session.get_state(), SessionState::AwaitingSetupConnection);
+
+        let (setup_connection_success, _) = session
+            .on_setup_connection(
+                &setup_connection(Protocol::MiningProtocol, 0b10),
+                &negotiator(Protocol::MiningProtocol, 0b10),
+            )
+            .unwrap();
+        assert_eq!(setup_connection_success.used_version, 2);
+        assert_eq!(session.get_protocol(), Some(Protocol::MiningProtocol));
+        assert_eq!(session.get_flags(), Some(0b10));
+    }
+
+    #[test]
+    fn test_setup_connection_twice() {
+        let mut session = set_up_session(Protocol::MiningProtocol);
+        let error = session
+            .on_setup_connection(
+                &setup_connection(Protocol::MiningProtocol, 0),
+                &negotiator(Protocol::MiningProtocol, 0),
+            )
+            .unwrap_err();
+        assert_eq!(error, SessionError::UnexpectedSetupConnection);
+        assert_eq!(error.setup_connection_error_code(), None);
+        assert_eq!(
+            session.check_frame_from_client(&header(0, MESSAGE_TYPE_SETUP_CONNECTION)),
+            Err(SessionError::UnexpectedSetupConnection)
+        );
+    }
+
+    #[test]
+    fn test_setup_connection_success_not_expected() {
+        let mut session = Session::new();
+        let success = SetupConnectionSuccess {
+            used_version: 2,
+            flags: 0,
+        };
+        assert_eq!(
+            session.on_setup_connection_success(&success),
+            Err(SessionError::HandshakeNotCompleted)
+        );
+
+        session.on_handshake_completed().unwrap();
+        // SetupConnection was not sent yet
+        assert_eq!(
+            session.on_setup_connection_success(&success),
+            Err(SessionError::UnexpectedSetupConnection)
+        );
+        assert_eq!(
+            session.check_frame_from_server(&header(0, MESSAGE_TYPE_SETUP_CONNECTION_SUCCESS)),
+            Err(SessionError::UnexpectedSetupConnection)
+        );
+    }
+
+    #[test]
+    fn test_setup_connection_success_unsupported_feature_flags() {
+        let mut session = Session::new();
+        session.on_handshake_completed().unwrap();
+        session
+            .on_setup_connection_sent(&setup_connection(Protocol::MiningProtocol, 0))
+            .unwrap();
+        assert_eq!(
+            session.on_setup_connection_sent(&setup_connection(Protocol::MiningProtocol, 0)),
+            Err(SessionError::UnexpectedSetupConnection)
+        );
+
+        let success = SetupConnectionSuccess {
+            used_version: 2,
+            flags: 0b10,
+        };
+        assert_eq!(
+            session.on_setup_connection_success(&success),
+            Err(SessionError::UnsupportedFeatureFlags(0b10))
+        );
+        assert_eq!(
+            session.get_state(),
+            SessionState::AwaitingSetupConnectionSuccess {
+                protocol: Protocol::MiningProtocol,
+                flags: 0,
+            }
+        );
+
+        session
+            .on_setup_connection_success(&SetupConnectionSuccess {
+                used_version: 2,
+                flags: 0,
+            })
+            .unwrap();
+        assert_eq!(session.get_protocol(), Some(Protocol::MiningProtocol));
+        assert_eq!(
+            session.check_frame_from_server(&header(0, MESSAGE_TYPE_SETUP_CONNECTION_ERROR)),
+            Err(SessionError::UnexpectedSetupConnection)
+        );
+    }
+
+    #[test]
+    fn test_frames_before_setup_connection() {
+        let mut session = Session::new();
+        session.on_handshake_completed().unwrap();
+        assert_eq!(
+            session.check_frame_from_client(&header(0, MESSAGE_TYPE_SUBMIT_SHARES_STANDARD)),
+            Err(SessionError::SetupConnectionRequired)
+        );
+        // the responses to SetupConnection are not expected from clients
+        assert_eq!(
+            session.check_frame_from_client(&header(0, MESSAGE_TYPE_SETUP_CONNECTION_SUCCESS)),
+            Err(SessionError::SetupConnectionRequired)
+        );
+        // nor SetupConnection from servers
+        assert_eq!(
+            session.check_frame_from_server(&header(0, MESSAGE_TYPE_SETUP_CONNECTION)),
+            Err(SessionError::SetupConnectionRequired)
+        );
+        assert_eq!(
+            session.check_frame_from_client(&header(0, MESSAGE_TYPE_SETUP_CONNECTION)),
+            Ok(())
+        );
+        assert_eq!(
+            session.on_extensions_negotiated(&[EXTENSION_TYPE_SHARE_REJECT_DETAIL]),
+            Err(SessionError::SetupConnectionRequired)
+        );
+        assert!(session.get_negotiated_extensions().is_empty());
+    }
+
+    #[test]
+    fn test_frame_of_other_protocol() {
+        let session = set_up_session(Protocol::MiningProtocol);
+        assert_eq!(
+            session.check_frame_from_client(&header(0, MESSAGE_TYPE_SUBMIT_SHARES_STANDARD)),
+            Ok(())
+        );
+        assert_eq!(
+            session.check_frame_from_client(&header(0, MESSAGE_TYPE_DECLARE_MINING_JOB)),
+            Err(SessionError::ProtocolNotNegotiated {
+                negotiated: Protocol::MiningProtocol,
+                received: Protocol::JobDeclarationProtocol,
+            })
+        );
+        assert_eq!(
+            session.check_frame_from_client(&header(0, 0xff)),
+            Err(SessionError::UnknownMessage)
+        );
+    }
+
+    #[test]
+    fn test_extension_frames() {
+        let mut session = set_up_session(Protocol::MiningProtocol);
+        // extensions negotiation is always allowed once the connection is set up
+        assert_eq!(
+            session.check_frame_from_client(&header(
+                EXTENSION_TYPE_EXTENSIONS_NEGOTIATION,
+                MESSAGE_TYPE_REQUEST_EXTENSIONS
+            )),
+            Ok(())
+        );
+        assert_eq!(
+            session.check_frame_from_server(&header(EXTENSION_TYPE_SHARE_REJECT_DETAIL, 0x00)),
+            Err(SessionError::ExtensionNotNegotiated(
+                EXTENSION_TYPE_SHARE_REJECT_DETAIL
+            ))
+        );
+
+        session
+            .on_extensions_negotiated(&[
+                EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+                EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+            ])
+            .unwrap();
+        assert_eq!(
+            session.get_negotiated_extensions(),
+            &[EXTENSION_TYPE_SHARE_REJECT_DETAIL]
+        );
+        // the channel message bit is not part of the extension type
+        assert_eq!(
+            session.check_frame_from_server(&header(
+                EXTENSION_TYPE_SHARE_REJECT_DETAIL | 0x8000,
+                0x00
+            )),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_open_extended_channel_with_standard_jobs_required() {
+        // REQUIRES_STANDARD_JOBS
+        let session = set_up_session_with_flags(Protocol::MiningProtocol, 0b1);
+        assert_eq!(
+            session.check_frame_from_client(&header(0, MESSAGE_TYPE_OPEN_EXTENDED_MINING_CHANNEL)),
+            Err(SessionError::StandardJobsRequired)
+        );
+        assert_eq!(
+            session.check_frame_from_client(&header(0, MESSAGE_TYPE_OPEN_STANDARD_MINING_CHANNEL)),
+            Ok(())
+        );
+
+        let session = set_up_session(Protocol::MiningProtocol);
+        assert_eq!(
+            session.check_frame_from_client(&header(0, MESSAGE_TYPE_OPEN_EXTENDED_MINING_CHANNEL)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_set_custom_mining_job_without_work_selection() {
+        let session = set_up_session(Protocol::MiningProtocol);
+        assert_eq!(
+            session.check_frame_from_client(&header(0, MESSAGE_TYPE_SET_CUSTOM_MINING_JOB)),
+            Err(SessionError::WorkSelectionNotNegotiated)
+        );
+
+        // REQUIRES_WORK_SELECTION
+        let session = set_up_session_with_flags(Protocol::MiningProtocol, 0b10);
+        assert_eq!(
+            session.check_frame_from_client(&header(0, MESSAGE_TYPE_SET_CUSTOM_MINING_JOB)),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! Adapters running the `Async` handler traits' frame parsing and message dispatch on behalf of
+//! the `Shared` handler traits.
+//!
+//! A `Shared` handler borrowed together with the context of one connection behaves like an
+//! `Async` handler for that connection, so the `Shared` traits reuse the `Async` default methods
+//! instead of duplicating them:
+//! - [`SharedFrames`] parses a frame and hands the parsed message back to the handler's
+//!   `handle_*_message_from_*`, so that overriding it on the `Shared` trait is still honored.
+//! - [`SharedMessages`] dispatches a parsed message to the handler method for its type.
+
+/// A `Shared` handler and a connection context, parsing frames with the `Async` default.
+pub(crate) struct SharedFrames<'a, H: ?Sized, C> {
+    pub(crate) handler: &'a H,
+    pub(crate) ctx: &'a mut C,
+}
+
+/// A `Shared` handler and a connection context, dispatching messages with the `Async` default.
+pub(crate) struct SharedMessages<'a, H: ?Sized, C> {
+    pub(crate) handler: &'a H,
+    pub(crate) ctx: &'a mut C,
+}
+
+/// Implements an `Async` handler trait for [`SharedFrames`] and [`SharedMessages`] over any
+/// handler implementing the matching `Shared` trait, forwarding every method that is not a
+/// default of the `Async` trait to the handler, with the connection context in place of the peer
+/// id.
+macro_rules! impl_async_for_shared {
+    (
+        $shared:ident => $async:ident {
+            async fn $message:ident($message_ty:ty);
+            $(fn $getter:ident(&self $(, $arg:ident: $arg_ty:ty)*) -> $ret:ty;)*
+            $(async fn $leaf:ident($leaf_ty:ty);)*
+        }
+    ) => {
+        impl<H: $shared + ?Sized> $async for $crate::shared::SharedFrames<'_, H, H::Context> {
+            type Error = H::Error;
+
+            $(
+                fn $getter(&self, _peer_id: Option<usize> $(, $arg: $arg_ty)*) -> $ret {
+                    self.handler.$getter(self.ctx $(, $arg)*)
+                }
+            )*
+
+            async fn $message(
+                &mut self,
+                _peer_id: Option<usize>,
+                message: $message_ty,
+                tlv_fields: Option<&[::parsers_sv2::Tlv]>,
+            ) -> Result<(), Self::Error> {
+                self.handler.$message(self.ctx, message, tlv_fields).await
+            }
+
+            $(
+                async fn $leaf(
+                    &mut self,
+                    _peer_id: Option<usize>,
+                    msg: $leaf_ty,
+                    tlv_fields: Option<&[::parsers_sv2::Tlv]>,
+                ) -> Result<(), Self::Error> {
+                    self.handler.$leaf(self.ctx, msg, tlv_fields).await
+                }
+            )*
+        }
+
+        impl<H: $shared + ?Sized> $async for $crate::shared::SharedMessages<'_, H, H::Context> {
+            type Error = H::Error;
+
+            $(
+                fn $getter(&self, _peer_id: Option<usize> $(, $arg: $arg_ty)*) -> $ret {
+                    self.handler.$getter(self.ctx $(, $arg)*)
+                }
+            )*
+
+            $(
+                async fn $leaf(
+                    &mut self,
+                    _peer_id: Option<usize>,
+                    msg: $leaf_ty,
+                    tlv_fields: Option<&[::parsers_sv2::Tlv]>,
+                ) -> Result<(), Self::Error> {
+                    self.handler.$leaf(self.ctx, msg, tlv_fields).await
+                }
+            )*
+        }
+    };
+}
+
+pub(crate) use impl_async_for_shared;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use extensions_sv2::RequestExtensions;
+    use mining_sv2::{
+        CloseChannel, OpenExtendedMiningChannel, OpenStandardMiningChannel, SetCustomMiningJob,
+        SubmitSharesExtended, SubmitSharesStandard, SubmitSharesSuccess, UpdateChannel,
+        MESSAGE_TYPE_SUBMIT_SHARES_SUCCESS,
+    };
+    use parsers_sv2::{AnyMessage, Extensions, ExtensionsNegotiation, Mining, Tlv};
+
+    use crate::{
+        error::HandlerError,
+        testing::{block_on, encode_frame},
+        HandleExtensionsFromClientShared, HandleMiningMessagesFromClientShared,
+        SupportedChannelTypes,
+    };
+
+    // State of one miner connection.
+    #[derive(Default)]
+    struct Connection {
+        negotiated_extensions: Vec<u16>,
+        shares: Vec<u32>,
+    }
+
+    // A pool validating shares from all of its connections, collecting them in one place.
+    #[derive(Default)]
+    struct Pool {
+        shares: Mutex<Vec<u32>>,
+    }
+
+    impl HandleMiningMessagesFromClientShared for Pool {
+        type Error = HandlerError;
+        type Context = Connection;
+
+        fn get_channel_type_for_client(&self, _ctx: &Connection) -> SupportedChannelTypes {
+            SupportedChannelTypes::Standard
+        }
+
+        fn is_work_selection_enabled_for_client(&self, _ctx: &Connection) -> bool {
+            false
+        }
+
+        fn is_client_authorized(
+            &self,
+            _ctx: &Connection,
+            _user_identity: &binary_sv2::Str0255,
+        ) -> Result<bool, Self::Error> {
+            Ok(true)
+        }
+
+        fn get_negotiated_extensions_with_client(
+            &self,
+            ctx: &Connection,
+        ) -> Result<Vec<u16>, Self::Error> {
+            Ok(ctx.negotiated_extensions.clone())
+        }
+
+        async fn handle_close_channel(
+            &self,
+            _ctx: &mut Connection,
+            _msg: CloseChannel<'_>,
+            _tlv_fields: Option<&[Tlv]>,
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn handle_open_standard_mining_channel(
+            &self,
+            _ctx: &mut Connection,
+            _msg: OpenStandardMiningChannel<'_>,
+            _tlv_fields: Option<&[Tlv]>,
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn handle_open_extended_mining_channel(
+            &self,
+            _ctx: &mut Connection,
+            _msg: OpenExtendedMiningChannel<'_>,
+            _tlv_fields: Option<&[Tlv]>,
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn handle_update_channel(
+            &self,
+            _ctx: &mut Connection,
+            _msg: UpdateChannel<'_>,
+            _tlv_fields: Option<&[Tlv]>,
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn handle_submit_shares_standard(
+            &self,
+            ctx: &mut Connection,
+            msg: SubmitSharesStandard,
+            _tlv_fields: Option<&[Tlv]>,
+        ) -> Result<(), Self::Error> {
+            ctx.shares.push(msg.sequence_number);
+            self.shares.lock().unwrap().push(msg.sequence_number);
+            Ok(())
+        }
+
+        async fn handle_submit_shares_extended(
+            &self,
+            _ctx: &mut Connection,
+            _msg: SubmitSharesExtended<'_>,
+            _tlv_fields: Option<&[Tlv]>,
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn handle_set_custom_mining_job(
+            &self,
+            _ctx: &mut Connection,
+            _msg: SetCustomMiningJob<'_>,
+            _tlv_fields: Option<&[Tlv]>,
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    impl HandleExtensionsFromClientShared for Pool {
+        type Error = HandlerError;
+        type Context = Connection;
+
+        fn get_negotiated_extensions_with_client(
+            &self,
+            ctx: &Connection,
+        ) -> Result<Vec<u16>, Self::Error> {
+            Ok(ctx.negotiated_extensions.clone())
+        }
+
+        async fn handle_request_extensions(
+            &self,
+            ctx: &mut Connection,
+            msg: RequestExtensions<'_>,
+            _tlv_fields: Option<&[Tlv]>,
+        ) -> Result<(), Self::Error> {
+            ctx.negotiated_extensions = msg.requested_extensions.into_inner();
+            Ok(())
+        }
+    }
+
+    fn submit_shares_standard(sequence_number: u32) -> AnyMessage<'static> {
+        AnyMessage::Mining(Mining::SubmitSharesStandard(SubmitSharesStandard {
+            channel_id: 1,
+            sequence_number,
+            job_id: 1,
+            nonce: 0,
+            ntime: 0,
+            version: 0,
+        }))
+    }
+
+    #[test]
+    fn test_shared_handler_with_two_contexts() {
+        let pool = Pool::default();
+        let mut first = Connection::default();
+        let mut second = Connection::default();
+
+        for sequence_number in 1..=3 {
+            let ctx = if sequence_number == 2 {
+                &mut second
+            } else {
+                &mut first
+            };
+            let (header, mut payload) = encode_frame(submit_shares_standard(sequence_number));
+            block_on(pool.handle_mining_message_frame_from_client(ctx, header, &mut payload))
+                .unwrap();
+        }
+
+        assert_eq!(first.shares, vec![1, 3]);
+        assert_eq!(second.shares, vec![2]);
+        assert_eq!(*pool.shares.lock().unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_shared_extensions_handler_with_two_contexts() {
+        let pool = Pool::default();
+        let mut first = Connection::default();
+        let mut second = Connection::default();
+
+        let request_extensions = RequestExtensions {
+            request_id: 1,
+            requested_extensions: vec![0x0002].try_into().unwrap(),
+        };
+        let (header, mut payload) =
+            encode_frame(AnyMessage::Extensions(Extensions::ExtensionsNegotiation(
+                ExtensionsNegotiation::RequestExtensions(request_extensions),
+            )));
+        block_on(pool.handle_extensions_message_frame_from_client(
+            &mut first,
+            header,
+            &mut payload,
+        ))
+        .unwrap();
+
+        assert_eq!(first.negotiated_extensions, vec![0x0002]);
+        assert!(second.negotiated_extensions.is_empty());
+
+        // Frames are parsed against the extensions negotiated on their own connection.
+        for (ctx, sequence_number) in [(&mut first, 1), (&mut second, 2)] {
+            let (header, mut payload) = encode_frame(submit_shares_standard(sequence_number));
+            block_on(pool.handle_mining_message_frame_from_client(ctx, header, &mut payload))
+                .unwrap();
+        }
+        assert_eq!(first.shares, vec![1]);
+        assert_eq!(second.shares, vec![2]);
+    }
+
+    #[test]
+    fn test_shared_handler_rejects_unexpected_message() {
+        let pool = Pool::default();
+        let mut ctx = Connection::default();
+
+        let message = Mining::SubmitSharesSuccess(SubmitSharesSuccess {
+            channel_id: 1,
+            last_sequence_number: 1,
+            new_submits_accepted_count: 1,
+            new_shares_sum: 1,
+        });
+        let error =
+            block_on(pool.handle_mining_message_from_client(&mut ctx, message, None)).unwrap_err();
+
+        assert!(matches!(
+            error,
+            HandlerError::UnexpectedMessage {
+                extension_type: 0,
+                message_type: MESSAGE_TYPE_SUBMIT_SHARES_SUCCESS,
+            }
+        ));
+        assert!(ctx.shares.is_empty());
+    }
+}
@@ -6,7 +6,10 @@ use template_distribution_sv2::{
     RequestTransactionDataSuccess, SetNewPrevHash, SubmitSolution,
 };
 
-use crate::error::HandlerErrorType;
+use crate::{
+    error::HandlerErrorType,
+    shared::{impl_async_for_shared, SharedFrames, SharedMessages},
+};
 
 /// Synchronous handler trait for processing template distribution messages received from servers.
 ///
@@ -268,6 +271,109 @@ pub trait HandleTemplateDistributionMessagesFromServerAsync {
     ) -> Result<(), Self::Error>;
 }
 
+/// Asynchronous `&self` handler trait for template distribution messages received from servers.
+///
+/// Unlike the `Async` variant, handler methods take `&self` and the context of the connection the
+/// message came from, so that a single handler can process messages from many connections in
+/// parallel. State shared between connections lives in the handler, behind whatever
+/// synchronization fits it (fine-grained locks, actors, ...), while `Self::Context` holds the
+/// state of one connection and is mutated without locking.
+///
+/// ## TLV Extension Support
+///
+/// The `tlv_data` parameter in message handlers contains validated TLV fields if the message has
+/// extension data appended. TLV fields are only passed if they match negotiated extensions
+/// returned by `get_negotiated_extensions_with_server()`.
+#[trait_variant::make(Send)]
+pub trait HandleTemplateDistributionMessagesFromServerShared: Sync {
+    type Error: HandlerErrorType;
+
+    /// State of a single connection, e.g. the extensions negotiated with the peer.
+    type Context: Send;
+
+    /// Returns the list of negotiated extension_types with a server.
+    ///
+    /// Return an empty Vec if no extensions have been negotiated.
+    fn get_negotiated_extensions_with_server(
+        &self,
+        ctx: &Self::Context,
+    ) -> Result<Vec<u16>, Self::Error>;
+
+    /// Handles a raw Template Distribution protocol message frame from a server.
+    ///
+    /// This method parses the raw frame, extracts any TLV extension data, and delegates
+    /// to `handle_template_distribution_message_from_server` with the parsed message and TLV fields.
+    async fn handle_template_distribution_message_frame_from_server(
+        &self,
+        ctx: &mut Self::Context,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedFrames { handler: self, ctx }
+                .handle_template_distribution_message_frame_from_server(None, header, payload)
+                .await
+        }
+    }
+
+    /// Handles a parsed template distribution message from a server.
+    ///
+    /// The `tlv_fields` parameter contains parsed TLV fields if the message has extension
+    /// data appended. It will be `Some(&[Tlv])` when valid TLV data is present, or `None`
+    /// if no TLV data exists or validation fails. Each `Tlv` struct provides direct access to
+    /// `extension_type`, `field_type`, `length`, and `value`.
+    async fn handle_template_distribution_message_from_server(
+        &self,
+        ctx: &mut Self::Context,
+        message: TemplateDistribution<'_>,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedMessages { handler: self, ctx }
+                .handle_template_distribution_message_from_server(None, message, tlv_fields)
+                .await
+        }
+    }
+    async fn handle_new_template(
+        &self,
+        ctx: &mut Self::Context,
+        msg: NewTemplate,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_set_new_prev_hash(
+        &self,
+        ctx: &mut Self::Context,
+        msg: SetNewPrevHash,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_request_tx_data_success(
+        &self,
+        ctx: &mut Self::Context,
+        msg: RequestTransactionDataSuccess,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_request_tx_data_error(
+        &self,
+        ctx: &mut Self::Context,
+        msg: RequestTransactionDataError,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+}
+
+impl_async_for_shared! {
+    HandleTemplateDistributionMessagesFromServerShared => HandleTemplateDistributionMessagesFromServerAsync {
+        async fn handle_template_distribution_message_from_server(TemplateDistribution<'_>);
+        fn get_negotiated_extensions_with_server(&self) -> Result<Vec<u16>, Self::Error>;
+        async fn handle_new_template(NewTemplate<'_>);
+        async fn handle_set_new_prev_hash(SetNewPrevHash<'_>);
+        async fn handle_request_tx_data_success(RequestTransactionDataSuccess<'_>);
+        async fn handle_request_tx_data_error(RequestTransactionDataError<'_>);
+    }
+}
+
 /// Synchronous handler trait for processing template distribution messages received from clients.
 ///
 /// The client ID identifies which client a message originated from.
@@ -516,3 +622,98 @@ pub trait HandleTemplateDistributionMessagesFromClientAsync {
         tlv_fields: Option<&[Tlv]>,
     ) -> Result<(), Self::Error>;
 }
+
+/// Asynchronous `&self` handler trait for template distribution messages received from clients.
+///
+/// Unlike the `Async` variant, handler methods take `&self` and the context of the connection the
+/// message came from, so that a single handler can process messages from many connections in
+/// parallel. State shared between connections lives in the handler, behind whatever
+/// synchronization fits it (fine-grained locks, actors, ...), while `Self::Context` holds the
+/// state of one connection and is mutated without locking.
+///
+/// ## TLV Extension Support
+///
+/// The `tlv_data` parameter in message handlers contains validated TLV fields if the message has
+/// extension data appended. TLV fields are only passed if they match negotiated extensions
+/// returned by `get_negotiated_extensions_with_client()`.
+#[trait_variant::make(Send)]
+pub trait HandleTemplateDistributionMessagesFromClientShared: Sync {
+    type Error: HandlerErrorType;
+
+    /// State of a single connection, e.g. the extensions negotiated with the peer.
+    type Context: Send;
+
+    /// Returns the list of negotiated extension_types with a client.
+    ///
+    /// Return an empty Vec if no extensions have been negotiated.
+    fn get_negotiated_extensions_with_client(
+        &self,
+        ctx: &Self::Context,
+    ) -> Result<Vec<u16>, Self::Error>;
+
+    /// Handles a raw Template Distribution protocol message frame from a client.
+    ///
+    /// This method parses the raw frame, extracts any TLV extension data, and delegates
+    /// to `handle_template_distribution_message_from_client` with the parsed message and TLV fields.
+    async fn handle_template_distribution_message_frame_from_client(
+        &self,
+        ctx: &mut Self::Context,
+        header: Header,
+        payload: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedFrames { handler: self, ctx }
+                .handle_template_distribution_message_frame_from_client(None, header, payload)
+                .await
+        }
+    }
+
+    /// Handles a parsed template distribution message from a client.
+    ///
+    /// The `tlv_fields` parameter contains parsed TLV fields if the message has extension
+    /// data appended. It will be `Some(&[Tlv])` when valid TLV data is present, or `None`
+    /// if no TLV data exists or validation fails. Each `Tlv` struct provides direct access to
+    /// `extension_type`, `field_type`, `length`, and `value`.
+    async fn handle_template_distribution_message_from_client(
+        &self,
+        ctx: &mut Self::Context,
+        message: TemplateDistribution<'_>,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error> {
+        async move {
+            SharedMessages { handler: self, ctx }
+                .handle_template_distribution_message_from_client(None, message, tlv_fields)
+                .await
+        }
+    }
+
+    async fn handle_coinbase_output_constraints(
+        &self,
+        ctx: &mut Self::Context,
+        msg: CoinbaseOutputConstraints,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+
+    async fn handle_request_tx_data(
+        &self,
+        ctx: &mut Self::Context,
+        msg: RequestTransactionData,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+    async fn handle_submit_solution(
+        &self,
+        ctx: &mut Self::Context,
+        msg: SubmitSolution,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), Self::Error>;
+}
+
+impl_async_for_shared! {
+    HandleTemplateDistributionMessagesFromClientShared => HandleTemplateDistributionMessagesFromClientAsync {
+        async fn handle_template_distribution_message_from_client(TemplateDistribution<'_>);
+        fn get_negotiated_extensions_with_client(&self) -> Result<Vec<u16>, Self::Error>;
+        async fn handle_coinbase_output_constraints(CoinbaseOutputConstraints);
+        async fn handle_request_tx_data(RequestTransactionData);
+        async fn handle_submit_solution(SubmitSolution<'_>);
+    }
+}
@@ -0,0 +1,790 @@
+//! Ready-made handlers for testing code that sends messages to handlers.
+//!
+//! [`RecordingHandler`] stores every message it receives, with its TLV fields, in order.
+//! [`MockHandler`] does the same, and answers each message type with the responses it was
+//! scripted with. Both implement the sync and async handler traits of every subprotocol, on the
+//! server and the client side. Wrapped into a [`SharedHandler`], they implement the `Shared`
+//! handler traits too, with the peer id as the connection context.
+//!
+//! [`encode_frame`] builds the raw frames that the `handle_*_frame_*` and `dispatch_*` methods
+//! take.
+//!
+//! Available with the `test_utils` feature.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Mutex, MutexGuard},
+};
+
+use binary_sv2::Str0255;
+use common_messages_sv2::{
+    ChannelEndpointChanged, Reconnect, SetupConnection, SetupConnectionError,
+    SetupConnectionSuccess,
+};
+use extensions_sv2::{RequestExtensions, RequestExtensionsError, RequestExtensionsSuccess};
+use framing_sv2::header::Header;
+use job_declaration_sv2::{
+    AllocateMiningJobToken, AllocateMiningJobTokenSuccess, DeclareMiningJob, DeclareMiningJobError,
+    DeclareMiningJobSuccess, ProvideMissingTransactions, ProvideMissingTransactionsSuccess,
+    PushSolution,
+};
+use mining_sv2::{
+    CloseChannel, NewExtendedMiningJob, NewMiningJob, OpenExtendedMiningChannel,
+    OpenExtendedMiningChannelSuccess, OpenMiningChannelError, OpenStandardMiningChannel,
+    OpenStandardMiningChannelSuccess, SetCustomMiningJob, SetCustomMiningJobError,
+    SetCustomMiningJobSuccess, SetExtranoncePrefix, SetGroupChannel, SetTarget, SubmitSharesError,
+    SubmitSharesExtended, SubmitSharesStandard, SubmitSharesSuccess, UpdateChannel,
+    UpdateChannelError,
+};
+use parsers_sv2::{
+    AnyMessage, CommonMessages, Extensions, ExtensionsNegotiation, IsSv2Message, JobDeclaration,
+    Mining, TemplateDistribution, Tlv,
+};
+use template_distribution_sv2::{
+    CoinbaseOutputConstraints, NewTemplate, RequestTransactionData, RequestTransactionDataError,
+    RequestTransactionDataSuccess, SubmitSolution,
+};
+
+use crate::{
+    common::*, error::HandlerError, extensions::*, job_declaration::*, mining::*,
+    template_distribution::*,
+};
+
+/// A message received by a test handler.
+#[derive(Debug, Clone)]
+pub struct RecordedMessage {
+    /// The `server_id` or `client_id` the handler was called with, or the context of a
+    /// [`SharedHandler`].
+    pub peer_id: Option<usize>,
+    pub message: AnyMessage<'static>,
+    pub tlv_fields: Option<Vec<Tlv>>,
+}
+
+impl RecordedMessage {
+    /// Returns the extension type and message type of the message.
+    pub fn get_message_type(&self) -> (u16, u8) {
+        (self.message.extension_type(), self.message.message_type())
+    }
+}
+
+/// Handler recording every message it receives, and accepting all of them.
+///
+/// What the handler answers to the `get_*` methods of the handler traits is set with the `set_*`
+/// methods. By default no extension is negotiated, work selection is disabled, every client is
+/// authorized and both group and extended channels are supported.
+#[derive(Debug, Clone)]
+pub struct RecordingHandler {
+    messages: Vec<RecordedMessage>,
+    negotiated_extensions: Vec<u16>,
+    channel_type: SupportedChannelTypes,
+    work_selection_enabled: bool,
+    clients_authorized: bool,
+}
+
+impl RecordingHandler {
+    pub fn new() -> Self {
+        Self {
+            messages: Vec::new(),
+            negotiated_extensions: Vec::new(),
+            channel_type: SupportedChannelTypes::GroupAndExtended,
+            work_selection_enabled: false,
+            clients_authorized: true,
+        }
+    }
+
+    pub fn set_negotiated_extensions(&mut self, negotiated_extensions: Vec<u16>) {
+        self.negotiated_extensions = negotiated_extensions;
+    }
+
+    pub fn set_channel_type(&mut self, channel_type: SupportedChannelTypes) {
+        self.channel_type = channel_type;
+    }
+
+    pub fn set_work_selection_enabled(&mut self, work_selection_enabled: bool) {
+        self.work_selection_enabled = work_selection_enabled;
+    }
+
+    pub fn set_clients_authorized(&mut self, clients_authorized: bool) {
+        self.clients_authorized = clients_authorized;
+    }
+
+    /// Returns the messages received so far, in order.
+    pub fn get_messages(&self) -> &[RecordedMessage] {
+        &self.messages
+    }
+
+    /// Returns the extension type and message type of the messages received so far, in order.
+    pub fn get_message_types(&self) -> Vec<(u16, u8)> {
+        self.messages
+            .iter()
+            .map(RecordedMessage::get_message_type)
+            .collect()
+    }
+
+    /// Returns the messages received so far, and forgets them.
+    pub fn take_messages(&mut self) -> Vec<RecordedMessage> {
+        std::mem::take(&mut self.messages)
+    }
+
+    fn push(
+        &mut self,
+        peer_id: Option<usize>,
+        message: AnyMessage<'_>,
+        tlv_fields: Option<&[Tlv]>,
+    ) {
+        self.messages.push(RecordedMessage {
+            peer_id,
+            message: message.into_static(),
+            tlv_fields: tlv_fields.map(<[Tlv]>::to_vec),
+        });
+    }
+}
+
+impl Default for RecordingHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Handler recording every message it receives, and answering with scripted responses.
+///
+/// Responses are queued per message type with `push_response`, and consumed in order. Messages
+/// without a queued response are accepted.
+#[derive(Debug, Default)]
+pub struct MockHandler {
+    recorder: RecordingHandler,
+    responses: HashMap<(u16, u8), VecDeque<Result<(), HandlerError>>>,
+}
+
+impl MockHandler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the recorder of the received messages, also used to set what the handler answers
+    /// to the `get_*` methods of the handler traits.
+    pub fn get_recorder(&mut self) -> &mut RecordingHandler {
+        &mut self.recorder
+    }
+
+    /// Returns the messages received so far, in order.
+    pub fn get_messages(&self) -> &[RecordedMessage] {
+        self.recorder.get_messages()
+    }
+
+    /// Queues the response to the next message of this type without a response yet.
+    pub fn push_response(
+        &mut self,
+        extension_type: u16,
+        message_type: u8,
+        response: Result<(), HandlerError>,
+    ) {
+        self.responses
+            .entry((extension_type, message_type))
+            .or_default()
+            .push_back(response);
+    }
+
+    /// Returns the number of queued responses not consumed yet.
+    pub fn get_pending_responses(&self) -> usize {
+        self.responses.values().map(VecDeque::len).sum()
+    }
+}
+
+/// Test handler behind a mutex, implementing the `Shared` handler traits.
+///
+/// The context of a connection is its peer id, recorded with each message like the `server_id`
+/// or `client_id` of the `Sync` and `Async` traits.
+#[derive(Debug, Default)]
+pub struct SharedHandler<H> {
+    handler: Mutex<H>,
+}
+
+impl<H> SharedHandler<H> {
+    pub fn new(handler: H) -> Self {
+        Self {
+            handler: Mutex::new(handler),
+        }
+    }
+
+    /// Locks the wrapped handler, e.g. to read the messages it received or script its responses.
+    pub fn lock(&self) -> MutexGuard<'_, H> {
+        self.handler
+            .lock()
+            .expect("test handler mutex must not be poisoned")
+    }
+
+    pub fn into_inner(self) -> H {
+        self.handler
+            .into_inner()
+            .expect("test handler mutex must not be poisoned")
+    }
+}
+
+// Shared by the test handlers, so that the handler traits are implemented once for all of them.
+trait TestHandler {
+    fn with_recording_handler<T>(&self, f: impl FnOnce(&RecordingHandler) -> T) -> T;
+
+    fn on_message(
+        &mut self,
+        peer_id: Option<usize>,
+        message: AnyMessage<'_>,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), HandlerError>;
+}
+
+impl TestHandler for RecordingHandler {
+    fn with_recording_handler<T>(&self, f: impl FnOnce(&RecordingHandler) -> T) -> T {
+        f(self)
+    }
+
+    fn on_message(
+        &mut self,
+        peer_id: Option<usize>,
+        message: AnyMessage<'_>,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), HandlerError> {
+        self.push(peer_id, message, tlv_fields);
+        Ok(())
+    }
+}
+
+impl TestHandler for MockHandler {
+    fn with_recording_handler<T>(&self, f: impl FnOnce(&RecordingHandler) -> T) -> T {
+        f(&self.recorder)
+    }
+
+    fn on_message(
+        &mut self,
+        peer_id: Option<usize>,
+        message: AnyMessage<'_>,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), HandlerError> {
+        let message_type = (message.extension_type(), message.message_type());
+        self.recorder.push(peer_id, message, tlv_fields);
+        self.responses
+            .get_mut(&message_type)
+            .and_then(VecDeque::pop_front)
+            .unwrap_or(Ok(()))
+    }
+}
+
+impl<H: TestHandler> TestHandler for SharedHandler<H> {
+    fn with_recording_handler<T>(&self, f: impl FnOnce(&RecordingHandler) -> T) -> T {
+        self.lock().with_recording_handler(f)
+    }
+
+    fn on_message(
+        &mut self,
+        peer_id: Option<usize>,
+        message: AnyMessage<'_>,
+        tlv_fields: Option<&[Tlv]>,
+    ) -> Result<(), HandlerError> {
+        self.lock().on_message(peer_id, message, tlv_fields)
+    }
+}
+
+fn common(message: CommonMessages<'_>) -> AnyMessage<'_> {
+    AnyMessage::Common(message)
+}
+
+fn mining(message: Mining<'_>) -> AnyMessage<'_> {
+    AnyMessage::Mining(message)
+}
+
+fn job_declaration(message: JobDeclaration<'_>) -> AnyMessage<'_> {
+    AnyMessage::JobDeclaration(message)
+}
+
+fn template_distribution(message: TemplateDistribution<'_>) -> AnyMessage<'_> {
+    AnyMessage::TemplateDistribution(message)
+}
+
+fn extensions_negotiation(message: ExtensionsNegotiation<'_>) -> AnyMessage<'_> {
+    AnyMessage::Extensions(Extensions::ExtensionsNegotiation(message))
+}
+
+// Implements the `handle_*` methods by passing the message to `TestHandler::on_message`, wrapped
+// into an `AnyMessage` by `$wrap($variant(msg))`. The `Shared` traits pass the context as the
+// peer id.
+macro_rules! record_messages {
+    (sync, $($method:ident($msg:ty) => $wrap:ident($variant:path);)*) => {
+        $(
+            fn $method(
+                &mut self,
+                peer_id: Option<usize>,
+                msg: $msg,
+                tlv_fields: Option<&[Tlv]>,
+            ) -> Result<(), HandlerError> {
+                self.on_message(peer_id, $wrap($variant(msg)), tlv_fields)
+            }
+        )*
+    };
+    (async, $($method:ident($msg:ty) => $wrap:ident($variant:path);)*) => {
+        $(
+            async fn $method(
+                &mut self,
+                peer_id: Option<usize>,
+                msg: $msg,
+                tlv_fields: Option<&[Tlv]>,
+            ) -> Result<(), HandlerError> {
+                self.on_message(peer_id, $wrap($variant(msg)), tlv_fields)
+            }
+        )*
+    };
+    (shared, $($method:ident($msg:ty) => $wrap:ident($variant:path);)*) => {
+        $(
+            async fn $method(
+                &self,
+                peer_id: &mut Option<usize>,
+                msg: $msg,
+                tlv_fields: Option<&[Tlv]>,
+            ) -> Result<(), HandlerError> {
+                self.lock().on_message(*peer_id, $wrap($variant(msg)), tlv_fields)
+            }
+        )*
+    };
+}
+
+macro_rules! impl_test_handler {
+    ($handler:ty) => {
+        impl_test_handler!(@common $handler, sync, Option<usize>, HandleCommonMessagesFromServerSync, HandleCommonMessagesFromClientSync);
+        impl_test_handler!(@common $handler, async, Option<usize>, HandleCommonMessagesFromServerAsync, HandleCommonMessagesFromClientAsync);
+        impl_test_handler!(@mining $handler, sync, Option<usize>, HandleMiningMessagesFromServerSync, HandleMiningMessagesFromClientSync);
+        impl_test_handler!(@mining $handler, async, Option<usize>, HandleMiningMessagesFromServerAsync, HandleMiningMessagesFromClientAsync);
+        impl_test_handler!(@job_declaration $handler, sync, Option<usize>, HandleJobDeclarationMessagesFromServerSync, HandleJobDeclarationMessagesFromClientSync);
+        impl_test_handler!(@job_declaration $handler, async, Option<usize>, HandleJobDeclarationMessagesFromServerAsync, HandleJobDeclarationMessagesFromClientAsync);
+        impl_test_handler!(@template_distribution $handler, sync, Option<usize>, HandleTemplateDistributionMessagesFromServerSync, HandleTemplateDistributionMessagesFromClientSync);
+        impl_test_handler!(@template_distribution $handler, async, Option<usize>, HandleTemplateDistributionMessagesFromServerAsync, HandleTemplateDistributionMessagesFromClientAsync);
+        impl_test_handler!(@extensions $handler, sync, Option<usize>, HandleExtensionsFromServerSync, HandleExtensionsFromClientSync);
+        impl_test_handler!(@extensions $handler, async, Option<usize>, HandleExtensionsFromServerAsync, HandleExtensionsFromClientAsync);
+    };
+    (@shared $handler:ty) => {
+        impl_test_handler!(@common $handler, shared, &Option<usize>, HandleCommonMessagesFromServerShared, HandleCommonMessagesFromClientShared);
+        impl_test_handler!(@mining $handler, shared, &Option<usize>, HandleMiningMessagesFromServerShared, HandleMiningMessagesFromClientShared);
+        impl_test_handler!(@job_declaration $handler, shared, &Option<usize>, HandleJobDeclarationMessagesFromServerShared, HandleJobDeclarationMessagesFromClientShared);
+        impl_test_handler!(@template_distribution $handler, shared, &Option<usize>, HandleTemplateDistributionMessagesFromServerShared, HandleTemplateDistributionMessagesFromClientShared);
+        impl_test_handler!(@extensions $handler, shared, &Option<usize>, HandleExtensionsFromServerShared, HandleExtensionsFromClientShared);
+    };
+    (@context shared) => {
+        type Context = Option<usize>;
+    };
+    (@context $kind:tt) => {};
+    (@extensions_getters $peer:ty) => {
+        fn get_negotiated_extensions_with_server(
+            &self,
+            _server_id: $peer,
+        ) -> Result<Vec<u16>, HandlerError> {
+            Ok(self.with_recording_handler(|handler| handler.negotiated_extensions.clone()))
+        }
+    };
+    (@extensions_getters_client $peer:ty) => {
+        fn get_negotiated_extensions_with_client(
+            &self,
+            _client_id: $peer,
+        ) -> Result<Vec<u16>, HandlerError> {
+            Ok(self.with_recording_handler(|handler| handler.negotiated_extensions.clone()))
+        }
+    };
+    (@common $handler:ty, $kind:tt, $peer:ty, $from_server:ident, $from_client:ident) => {
+        impl $from_server for $handler {
+            type Error = HandlerError;
+            impl_test_handler!(@context $kind);
+            impl_test_handler!(@extensions_getters $peer);
+            record_messages!($kind,
+                handle_setup_connection_success(SetupConnectionSuccess) => common(CommonMessages::SetupConnectionSuccess);
+                handle_setup_connection_error(SetupConnectionError<'_>) => common(CommonMessages::SetupConnectionError);
+                handle_channel_endpoint_changed(ChannelEndpointChanged) => common(CommonMessages::ChannelEndpointChanged);
+                handle_reconnect(Reconnect<'_>) => common(CommonMessages::Reconnect);
+            );
+        }
+
+        impl $from_client for $handler {
+            type Error = HandlerError;
+            impl_test_handler!(@context $kind);
+            impl_test_handler!(@extensions_getters_client $peer);
+            record_messages!($kind,
+                handle_setup_connection(SetupConnection<'_>) => common(CommonMessages::SetupConnection);
+            );
+        }
+    };
+    (@mining $handler:ty, $kind:tt, $peer:ty, $from_server:ident, $from_client:ident) => {
+        impl $from_server for $handler {
+            type Error = HandlerError;
+
+            fn get_channel_type_for_server(&self, _server_id: $peer) -> SupportedChannelTypes {
+                self.with_recording_handler(|handler| handler.channel_type)
+            }
+
+            fn is_work_selection_enabled_for_server(&self, _server_id: $peer) -> bool {
+                self.with_recording_handler(|handler| handler.work_selection_enabled)
+            }
+
+            impl_test_handler!(@context $kind);
+            impl_test_handler!(@extensions_getters $peer);
+            record_messages!($kind,
+                handle_open_standard_mining_channel_success(OpenStandardMiningChannelSuccess<'_>) => mining(Mining::OpenStandardMiningChannelSuccess);
+                handle_open_extended_mining_channel_success(OpenExtendedMiningChannelSuccess<'_>) => mining(Mining::OpenExtendedMiningChannelSuccess);
+                handle_open_mining_channel_error(OpenMiningChannelError<'_>) => mining(Mining::OpenMiningChannelError);
+                handle_update_channel_error(UpdateChannelError<'_>) => mining(Mining::UpdateChannelError);
+                handle_close_channel(CloseChannel<'_>) => mining(Mining::CloseChannel);
+                handle_set_extranonce_prefix(SetExtranoncePrefix<'_>) => mining(Mining::SetExtranoncePrefix);
+                handle_submit_shares_success(SubmitSharesSuccess) => mining(Mining::SubmitSharesSuccess);
+                handle_submit_shares_error(SubmitSharesError<'_>) => mining(Mining::SubmitSharesError);
+                handle_new_mining_job(NewMiningJob<'_>) => mining(Mining::NewMiningJob);
+                handle_new_extended_mining_job(NewExtendedMiningJob<'_>) => mining(Mining::NewExtendedMiningJob);
+                handle_set_new_prev_hash(mining_sv2::SetNewPrevHash<'_>) => mining(Mining::SetNewPrevHash);
+                handle_set_custom_mining_job_success(SetCustomMiningJobSuccess) => mining(Mining::SetCustomMiningJobSuccess);
+                handle_set_custom_mining_job_error(SetCustomMiningJobError<'_>) => mining(Mining::SetCustomMiningJobError);
+                handle_set_target(SetTarget<'_>) => mining(Mining::SetTarget);
+                handle_set_group_channel(SetGroupChannel<'_>) => mining(Mining::SetGroupChannel);
+            );
+        }
+
+        impl $from_client for $handler {
+            type Error = HandlerError;
+
+            fn get_channel_type_for_client(&self, _client_id: $peer) -> SupportedChannelTypes {
+                self.with_recording_handler(|handler| handler.channel_type)
+            }
+
+            fn is_work_selection_enabled_for_client(&self, _client_id: $peer) -> bool {
+                self.with_recording_handler(|handler| handler.work_selection_enabled)
+            }
+
+            fn is_client_authorized(
+                &self,
+                _client_id: $peer,
+                _user_identity: &Str0255,
+            ) -> Result<bool, HandlerError> {
+                Ok(self.with_recording_handler(|handler| handler.clients_authorized))
+            }
+
+            impl_test_handler!(@context $kind);
+            impl_test_handler!(@extensions_getters_client $peer);
+            record_messages!($kind,
+                handle_close_channel(CloseChannel<'_>) => mining(Mining::CloseChannel);
+                handle_open_standard_mining_channel(OpenStandardMiningChannel<'_>) => mining(Mining::OpenStandardMiningChannel);
+                handle_open_extended_mining_channel(OpenExtendedMiningChannel<'_>) => mining(Mining::OpenExtendedMiningChannel);
+                handle_update_channel(UpdateChannel<'_>) => mining(Mining::UpdateChannel);
+                handle_submit_shares_standard(SubmitSharesStandard) => mining(Mining::SubmitSharesStandard);
+                handle_submit_shares_extended(SubmitSharesExtended<'_>) => mining(Mining::SubmitSharesExtended);
+                handle_set_custom_mining_job(SetCustomMiningJob<'_>) => mining(Mining::SetCustomMiningJob);
+            );
+        }
+    };
+    (@job_declaration $handler:ty, $kind:tt, $peer:ty, $from_server:ident, $from_client:ident) => {
+        impl $from_server for $handler {
+            type Error = HandlerError;
+            impl_test_handler!(@context $kind);
+            impl_test_handler!(@extensions_getters $peer);
+            record_messages!($kind,
+                handle_allocate_mining_job_token_success(AllocateMiningJobTokenSuccess<'_>) => job_declaration(JobDeclaration::AllocateMiningJobTokenSuccess);
+                handle_declare_mining_job_success(DeclareMiningJobSuccess<'_>) => job_declaration(JobDeclaration::DeclareMiningJobSuccess);
+                handle_declare_mining_job_error(DeclareMiningJobError<'_>) => job_declaration(JobDeclaration::DeclareMiningJobError);
+                handle_provide_missing_transactions(ProvideMissingTransactions<'_>) => job_declaration(JobDeclaration::ProvideMissingTransactions);
+            );
+        }
+
+        impl $from_client for $handler {
+            type Error = HandlerError;
+            impl_test_handler!(@context $kind);
+            impl_test_handler!(@extensions_getters_client $peer);
+            record_messages!($kind,
+                handle_allocate_mining_job_token(AllocateMiningJobToken<'_>) => job_declaration(JobDeclaration::AllocateMiningJobToken);
+                handle_declare_mining_job(DeclareMiningJob<'_>) => job_declaration(JobDeclaration::DeclareMiningJob);
+                handle_provide_missing_transactions_success(ProvideMissingTransactionsSuccess<'_>) => job_declaration(JobDeclaration::ProvideMissingTransactionsSuccess);
+                handle_push_solution(PushSolution<'_>) => job_declaration(JobDeclaration::PushSolution);
+            );
+        }
+    };
+    (@template_distribution $handler:ty, $kind:tt, $peer:ty, $from_server:ident, $from_client:ident) => {
+        impl $from_server for $handler {
+            type Error = HandlerError;
+            impl_test_handler!(@context $kind);
+            impl_test_handler!(@extensions_getters $peer);
+            record_messages!($kind,
+                handle_new_template(NewTemplate<'_>) => template_distribution(TemplateDistribution::NewTemplate);
+                handle_set_new_prev_hash(template_distribution_sv2::SetNewPrevHash<'_>) => template_distribution(TemplateDistribution::SetNewPrevHash);
+                handle_request_tx_data_success(RequestTransactionDataSuccess<'_>) => template_distribution(TemplateDistribution::RequestTransactionDataSuccess);
+                handle_request_tx_data_error(RequestTransactionDataError<'_>) => template_distribution(TemplateDistribution::RequestTransactionDataError);
+            );
+        }
+
+        impl $from_client for $handler {
+            type Error = HandlerError;
+            impl_test_handler!(@context $kind);
+            impl_test_handler!(@extensions_getters_client $peer);
+            record_messages!($kind,
+                handle_coinbase_output_constraints(CoinbaseOutputConstraints) => template_distribution(TemplateDistribution::CoinbaseOutputConstraints);
+                handle_request_tx_data(RequestTransactionData) => template_distribution(TemplateDistribution::RequestTransactionData);
+                handle_submit_solution(SubmitSolution<'_>) => template_distribution(TemplateDistribution::SubmitSolution);
+            );
+        }
+    };
+    (@extensions $handler:ty, $kind:tt, $peer:ty, $from_server:ident, $from_client:ident) => {
+        impl $from_server for $handler {
+            type Error = HandlerError;
+            impl_test_handler!(@context $kind);
+            impl_test_handler!(@extensions_getters $peer);
+            record_messages!($kind,
+                handle_request_extensions_success(RequestExtensionsSuccess<'_>) => extensions_negotiation(ExtensionsNegotiation::RequestExtensionsSuccess);
+                handle_request_extensions_error(RequestExtensionsError<'_>) => extensions_negotiation(ExtensionsNegotiation::RequestExtensionsError);
+            );
+        }
+
+        impl $from_client for $handler {
+            type Error = HandlerError;
+            impl_test_handler!(@context $kind);
+            impl_test_handler!(@extensions_getters_client $peer);
+            record_messages!($kind,
+                handle_request_extensions(RequestExtensions<'_>) => extensions_negotiation(ExtensionsNegotiation::RequestExtensions);
+            );
+        }
+    };
+}
+
+impl_test_handler!(RecordingHandler);
+impl_test_handler!(MockHandler);
+impl_test_handler!(@shared SharedHandler<RecordingHandler>);
+impl_test_handler!(@shared SharedHandler<MockHandler>);
+
+/// Encodes a message into the header and payload of the raw frame it is received as.
+pub fn encode_frame(message: AnyMessage<'_>) -> (Header, Vec<u8>) {
+    let mut extension_type = message.extension_type();
+    if message.channel_bit() {
+        extension_type |= 0x8000;
+    }
+    let message_type = message.message_type();
+    let payload = binary_sv2::to_bytes(message).expect("message must be encodable");
+
+    let extension_type = extension_type.to_le_bytes();
+    let length = (payload.len() as u32).to_le_bytes();
+    let header = Header::from_bytes(&[
+        extension_type[0],
+        extension_type[1],
+        message_type,
+        length[0],
+        length[1],
+        length[2],
+    ])
+    .expect("header must be valid");
+    (header, payload)
+}
+
+// Drives a future to completion, for tests of the async traits.
+#[cfg(test)]
+pub(crate) fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::{
+        sync::Arc,
+        task::{Context, Poll, Wake, Waker},
+    };
+
+    struct NoopWaker;
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut context = Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mining_sv2::{MESSAGE_TYPE_CLOSE_CHANNEL, MESSAGE_TYPE_SUBMIT_SHARES_STANDARD};
+
+    fn submit_shares_standard(sequence_number: u32) -> SubmitSharesStandard {
+        SubmitSharesStandard {
+            channel_id: 1,
+            sequence_number,
+            job_id: 1,
+            nonce: 0,
+            ntime: 0,
+            version: 0,
+        }
+    }
+
+    fn close_channel() -> CloseChannel<'static> {
+        CloseChannel {
+            channel_id: 1,
+            reason_code: "done".to_string().try_into().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_encode_frame() {
+        let (header, mut payload) = encode_frame(mining(Mining::SubmitSharesStandard(
+            submit_shares_standard(7),
+        )));
+        assert_eq!(header.msg_type(), MESSAGE_TYPE_SUBMIT_SHARES_STANDARD);
+        assert!(header.channel_msg());
+        assert_eq!(header.len(), payload.len());
+
+        let mut handler = RecordingHandler::new();
+        HandleMiningMessagesFromClientSync::handle_mining_message_frame_from_client(
+            &mut handler,
+            None,
+            header,
+            &mut payload,
+        )
+        .unwrap();
+        match &handler.get_messages()[0].message {
+            AnyMessage::Mining(Mining::SubmitSharesStandard(msg)) => {
+                assert_eq!(msg.sequence_number, 7)
+            }
+            message => panic!("unexpected message {message:?}"),
+        }
+    }
+
+    #[test]
+    fn test_recording_handler_records_messages_in_order() {
+        let mut handler = RecordingHandler::new();
+        HandleMiningMessagesFromClientSync::handle_submit_shares_standard(
+            &mut handler,
+            Some(1),
+            submit_shares_standard(1),
+            None,
+        )
+        .unwrap();
+        HandleMiningMessagesFromClientSync::handle_close_channel(
+            &mut handler,
+            Some(2),
+            close_channel(),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            handler.get_message_types(),
+            vec![
+                (0, MESSAGE_TYPE_SUBMIT_SHARES_STANDARD),
+                (0, MESSAGE_TYPE_CLOSE_CHANNEL)
+            ]
+        );
+        let messages = handler.take_messages();
+        assert_eq!(messages[0].peer_id, Some(1));
+        assert_eq!(messages[1].peer_id, Some(2));
+        assert!(handler.get_messages().is_empty());
+    }
+
+    #[test]
+    fn test_recording_handler_answers_getters_as_set() {
+        let mut handler = RecordingHandler::new();
+        assert_eq!(
+            HandleMiningMessagesFromClientSync::get_channel_type_for_client(&handler, None),
+            SupportedChannelTypes::GroupAndExtended
+        );
+        assert!(HandleMiningMessagesFromClientSync::is_client_authorized(
+            &handler,
+            None,
+            &"user".to_string().try_into().unwrap()
+        )
+        .unwrap());
+
+        handler.set_channel_type(SupportedChannelTypes::Standard);
+        handler.set_work_selection_enabled(true);
+        handler.set_clients_authorized(false);
+        handler.set_negotiated_extensions(vec![0x0002]);
+
+        assert_eq!(
+            HandleMiningMessagesFromClientSync::get_channel_type_for_client(&handler, None),
+            SupportedChannelTypes::Standard
+        );
+        assert!(
+            HandleMiningMessagesFromClientSync::is_work_selection_enabled_for_client(
+                &handler, None
+            )
+        );
+        assert!(!HandleMiningMessagesFromClientSync::is_client_authorized(
+            &handler,
+            None,
+            &"user".to_string().try_into().unwrap()
+        )
+        .unwrap());
+        assert_eq!(
+            HandleCommonMessagesFromServerSync::get_negotiated_extensions_with_server(
+                &handler, None
+            )
+            .unwrap(),
+            vec![0x0002]
+        );
+    }
+
+    #[test]
+    fn test_mock_handler_answers_with_scripted_responses() {
+        let mut handler = MockHandler::new();
+        handler.push_response(
+            0,
+            MESSAGE_TYPE_SUBMIT_SHARES_STANDARD,
+            Err(HandlerError::fatal()),
+        );
+        assert_eq!(handler.get_pending_responses(), 1);
+
+        let first = block_on(
+            HandleMiningMessagesFromClientAsync::handle_submit_shares_standard(
+                &mut handler,
+                None,
+                submit_shares_standard(1),
+                None,
+            ),
+        );
+        let second = block_on(
+            HandleMiningMessagesFromClientAsync::handle_submit_shares_standard(
+                &mut handler,
+                None,
+                submit_shares_standard(2),
+                None,
+            ),
+        );
+
+        assert!(matches!(
+            first,
+            Err(HandlerError::Rejected(crate::HandlerAction::Disconnect))
+        ));
+        assert!(second.is_ok());
+        assert_eq!(handler.get_pending_responses(), 0);
+        assert_eq!(handler.get_messages().len(), 2);
+    }
+
+    #[test]
+    fn test_shared_handler_records_the_context_as_peer_id() {
+        let handler = SharedHandler::new(MockHandler::new());
+        handler
+            .lock()
+            .push_response(0, MESSAGE_TYPE_CLOSE_CHANNEL, Err(HandlerError::fatal()));
+        let mut first = Some(1);
+        let mut second = Some(2);
+
+        block_on(
+            HandleMiningMessagesFromClientShared::handle_submit_shares_standard(
+                &handler,
+                &mut first,
+                submit_shares_standard(1),
+                None,
+            ),
+        )
+        .unwrap();
+        let closed = block_on(HandleMiningMessagesFromClientShared::handle_close_channel(
+            &handler,
+            &mut second,
+            close_channel(),
+            None,
+        ));
+
+        assert!(closed.is_err());
+        let handler = handler.into_inner();
+        let peer_ids: Vec<_> = handler
+            .get_messages()
+            .iter()
+            .map(|message| message.peer_id)
+            .collect();
+        assert_eq!(peer_ids, vec![Some(1), Some(2)]);
+    }
+}
@@ -0,0 +1,836 @@
+use core::slice;
+
+use extensions_sv2::{
+    ShareRejectDetail, UserIdentity, EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+    EXTENSION_TYPE_WORKER_HASHRATE_TRACKING, TLV_FIELD_TYPE_EXPECTED_TARGET,
+    TLV_FIELD_TYPE_JOB_STATE, TLV_FIELD_TYPE_REJECT_REASON, TLV_FIELD_TYPE_SHARE_HASH,
+    TLV_FIELD_TYPE_USER_IDENTITY,
+};
+use framing_sv2::header::Header;
+use parsers_sv2::{
+    share_reject_detail_from_tlvs, AnyMessage, CustomTlvField, ExtensionRegistry, IsSv2Message,
+    Mining, ParserError, Tlv, TlvField,
+};
+
+use crate::{
+    error::HandlerErrorType,
+    middleware::{HandlerMiddlewareAsync, HandlerMiddlewareSync},
+};
+
+/// Errors of TLV fields that don't follow the rules of their extension.
+#[derive(Debug)]
+pub enum TlvFieldError {
+    /// The field is not defined for the message it was appended to, e.g. `UserIdentity` on a
+    /// message other than `SubmitSharesExtended`.
+    UnexpectedMessage { extension_type: u16, field_type: u8 },
+    /// The extension doesn't define this field type.
+    UnknownField { extension_type: u16, field_type: u8 },
+    /// The field was appended more than once to the same message.
+    DuplicateField { extension_type: u16, field_type: u8 },
+    /// A field required by the extension, once any of its fields is appended, is missing.
+    MissingField { extension_type: u16, field_type: u8 },
+    /// The value of the field could not be decoded.
+    InvalidValue(ParserError),
+}
+
+// A TLV field checked against the rules of its extension, with its decoded value.
+enum DecodedTlvField<'a> {
+    UserIdentity {
+        channel_id: u32,
+        user_identity: UserIdentity,
+    },
+    // One of the fields of the Share Reject Detail of a `SubmitSharesError` for `channel_id`, the
+    // detail being decoded from all of them.
+    ShareRejectDetail {
+        channel_id: u32,
+    },
+    Custom(CustomTlvField),
+    Unknown(&'a Tlv),
+}
+
+// Checks and decodes the fields of one of the extensions known to this crate.
+type TlvFieldDecoder =
+    for<'a> fn(&AnyMessage<'_>, &'a Tlv) -> Result<DecodedTlvField<'a>, TlvFieldError>;
+
+// Decoders of the extensions known to this crate, by extension type.
+const TLV_FIELD_DECODERS: &[(u16, TlvFieldDecoder)] = &[
+    (
+        EXTENSION_TYPE_WORKER_HASHRATE_TRACKING,
+        decode_worker_hashrate_tracking_field,
+    ),
+    (
+        EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+        decode_share_reject_detail_field,
+    ),
+];
+
+fn decode_worker_hashrate_tracking_field<'a>(
+    message: &AnyMessage<'_>,
+    tlv: &'a Tlv,
+) -> Result<DecodedTlvField<'a>, TlvFieldError> {
+    let (extension_type, field_type) = (tlv.r#type.extension_type, tlv.r#type.field_type);
+    match field_type {
+        TLV_FIELD_TYPE_USER_IDENTITY => {
+            let channel_id = match message {
+                AnyMessage::Mining(Mining::SubmitSharesExtended(m)) => m.channel_id,
+                _ => {
+                    return Err(TlvFieldError::UnexpectedMessage {
+                        extension_type,
+                        field_type,
+                    })
+                }
+            };
+            let user_identity = UserIdentity::from_tlv(tlv).map_err(TlvFieldError::InvalidValue)?;
+            Ok(DecodedTlvField::UserIdentity {
+                channel_id,
+                user_identity,
+            })
+        }
+        _ => Err(TlvFieldError::UnknownField {
+            extension_type,
+            field_type,
+        }),
+    }
+}
+
+fn decode_share_reject_detail_field<'a>(
+    message: &AnyMessage<'_>,
+    tlv: &'a Tlv,
+) -> Result<DecodedTlvField<'a>, TlvFieldError> {
+    let (extension_type, field_type) = (tlv.r#type.extension_type, tlv.r#type.field_type);
+    if !matches!(
+        field_type,
+        TLV_FIELD_TYPE_REJECT_REASON
+            | TLV_FIELD_TYPE_EXPECTED_TARGET
+            | TLV_FIELD_TYPE_JOB_STATE
+            | TLV_FIELD_TYPE_SHARE_HASH
+    ) {
+        return Err(TlvFieldError::UnknownField {
+            extension_type,
+            field_type,
+        });
+    }
+    let channel_id = match message {
+        AnyMessage::Mining(Mining::SubmitSharesError(m)) => m.channel_id,
+        _ => {
+            return Err(TlvFieldError::UnexpectedMessage {
+                extension_type,
+                field_type,
+            })
+        }
+    };
+    share_reject_detail_from_tlvs(slice::from_ref(tlv)).map_err(TlvFieldError::InvalidValue)?;
+    Ok(DecodedTlvField::ShareRejectDetail { channel_id })
+}
+
+// Decodes the Share Reject Detail of a `SubmitSharesError` from its fields, checked one by one by
+// `decode_share_reject_detail_field`.
+fn decode_share_reject_detail(tlv_fields: &[Tlv]) -> Result<ShareRejectDetail, TlvFieldError> {
+    share_reject_detail_from_tlvs(tlv_fields)
+        .map_err(TlvFieldError::InvalidValue)?
+        .ok_or(TlvFieldError::MissingField {
+            extension_type: EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+            field_type: TLV_FIELD_TYPE_REJECT_REASON,
+        })
+}
+
+// Checks and decodes the `i`-th field appended to a message, with the decoder of its extension if
+// it is known to this crate, or else of its type in `registry`.
+fn decode_tlv_field<'a>(
+    message: &AnyMessage<'_>,
+    tlv_fields: &'a [Tlv],
+    i: usize,
+    registry: Option<&ExtensionRegistry>,
+) -> Result<DecodedTlvField<'a>, TlvFieldError> {
+    let tlv = &tlv_fields[i];
+    if tlv_fields[..i]
+        .iter()
+        .any(|other| other.r#type == tlv.r#type)
+    {
+        return Err(TlvFieldError::DuplicateField {
+            extension_type: tlv.r#type.extension_type,
+            field_type: tlv.r#type.field_type,
+        });
+    }
+
+    if let Some((_, decode)) = TLV_FIELD_DECODERS
+        .iter()
+        .find(|(extension_type, _)| *extension_type == tlv.r#type.extension_type)
+    {
+        return decode(message, tlv);
+    }
+    match registry.and_then(|registry| registry.decode_tlv_field(tlv)) {
+        Some(field) => field
+            .map(DecodedTlvField::Custom)
+            .map_err(TlvFieldError::InvalidValue),
+        None => Ok(DecodedTlvField::Unknown(tlv)),
+    }
+}
+
+/// Synchronous trait for receiving the TLV fields of negotiated extensions as typed values.
+///
+/// `handle_tlv_fields` checks every field appended to a message against the rules of its
+/// extension, decodes it and calls the matching `on_*` hook. Implementors override the hooks of
+/// the extensions they support, the others ignore the fields by default. The fields of the Share
+/// Reject Detail extension are decoded together, `on_share_reject_detail` being called once for
+/// the message after the hooks of the other fields.
+///
+/// Fields of the extensions registered in the [`ExtensionRegistry`] returned by
+/// `get_extension_registry` are decoded with it and passed to `on_custom_tlv_field`, the fields
+/// of other extensions unknown to this crate to `on_unknown_tlv_field`.
+///
+/// [`TlvFieldsLayer`] runs `handle_tlv_fields` as a middleware, so that the hooks fire for every
+/// message going through the `InterceptMessages*` traits.
+///
+/// The peer ID identifies which peer a message originated from, as for the handler traits.
+pub trait HandleTlvFieldsSync {
+    type Error: HandlerErrorType;
+
+    /// Returns the registry decoding the TLV fields of third-party extensions, if any.
+    fn get_extension_registry(&self) -> Option<&ExtensionRegistry> {
+        None
+    }
+
+    /// Checks and decodes the TLV fields appended to a message, and calls the matching hooks.
+    ///
+    /// Returns the `invalid_tlv_field` error for fields violating their extension's rules.
+    fn handle_tlv_fields(
+        &mut self,
+        peer_id: Option<usize>,
+        message: &AnyMessage<'_>,
+        tlv_fields: &[Tlv],
+    ) -> Result<(), Self::Error> {
+        let (extension_type, message_type) = (message.extension_type(), message.message_type());
+        let mut share_reject_detail_channel_id = None;
+        for i in 0..tlv_fields.len() {
+            let field = decode_tlv_field(message, tlv_fields, i, self.get_extension_registry())
+                .map_err(|e| Self::Error::invalid_tlv_field(e, extension_type, message_type))?;
+            match field {
+                DecodedTlvField::UserIdentity {
+                    channel_id,
+                    user_identity,
+                } => self.on_user_identity(peer_id, channel_id, user_identity)?,
+                DecodedTlvField::ShareRejectDetail { channel_id } => {
+                    share_reject_detail_channel_id = Some(channel_id)
+                }
+                DecodedTlvField::Custom(field) => {
+                    self.on_custom_tlv_field(peer_id, message, field)?
+                }
+                DecodedTlvField::Unknown(tlv) => {
+                    self.on_unknown_tlv_field(peer_id, message, tlv)?
+                }
+            }
+        }
+        if let Some(channel_id) = share_reject_detail_channel_id {
+            let detail = decode_share_reject_detail(tlv_fields)
+                .map_err(|e| Self::Error::invalid_tlv_field(e, extension_type, message_type))?;
+            self.on_share_reject_detail(peer_id, channel_id, detail)?;
+        }
+        Ok(())
+    }
+
+    /// Called with the `UserIdentity` of the Worker-Specific Hashrate Tracking extension, appended
+    /// to a `SubmitSharesExtended` for `channel_id`.
+    fn on_user_identity(
+        &mut self,
+        _peer_id: Option<usize>,
+        _channel_id: u32,
+        _user_identity: UserIdentity,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called with the Share Reject Detail appended to a `SubmitSharesError` for `channel_id`.
+    fn on_share_reject_detail(
+        &mut self,
+        _peer_id: Option<usize>,
+        _channel_id: u32,
+        _detail: ShareRejectDetail,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called with the fields of the extensions of the registry, decoded as their registered
+    /// type.
+    fn on_custom_tlv_field(
+        &mut self,
+        _peer_id: Option<usize>,
+        _message: &AnyMessage<'_>,
+        _field: CustomTlvField,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called with the fields of extensions unknown to this crate and to the registry.
+    fn on_unknown_tlv_field(
+        &mut self,
+        _peer_id: Option<usize>,
+        _message: &AnyMessage<'_>,
+        _tlv: &Tlv,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Asynchronous trait for receiving the TLV fields of negotiated extensions as typed values.
+///
+/// `handle_tlv_fields` checks every field appended to a message against the rules of its
+/// extension, decodes it and calls the matching `on_*` hook. Implementors override the hooks of
+/// the extensions they support, the others ignore the fields by default. The fields of the Share
+/// Reject Detail extension are decoded together, `on_share_reject_detail` being called once for
+/// the message after the hooks of the other fields.
+///
+/// Fields of the extensions registered in the [`ExtensionRegistry`] returned by
+/// `get_extension_registry` are decoded with it and passed to `on_custom_tlv_field`, the fields
+/// of other extensions unknown to this crate to `on_unknown_tlv_field`.
+///
+/// [`TlvFieldsLayer`] runs `handle_tlv_fields` as a middleware, so that the hooks fire for every
+/// message going through the `InterceptMessages*` traits.
+///
+/// The peer ID identifies which peer a message originated from, as for the handler traits.
+#[trait_variant::make(Send)]
+pub trait HandleTlvFieldsAsync {
+    type Error: HandlerErrorType;
+
+    /// Returns the registry decoding the TLV fields of third-party extensions, if any.
+    fn get_extension_registry(&self) -> Option<&ExtensionRegistry> {
+        None
+    }
+
+    /// Checks and decodes the TLV fields appended to a message, and calls the matching hooks.
+    ///
+    /// Returns the `invalid_tlv_field` error for fields violating their extension's rules.
+    async fn handle_tlv_fields(
+        &mut self,
+        peer_id: Option<usize>,
+        message: &AnyMessage<'_>,
+        tlv_fields: &[Tlv],
+    ) -> Result<(), Self::Error> {
+        async move {
+            let (extension_type, message_type) = (message.extension_type(), message.message_type());
+            let mut share_reject_detail_channel_id = None;
+            for i in 0..tlv_fields.len() {
+                let field = decode_tlv_field(message, tlv_fields, i, self.get_extension_registry())
+                    .map_err(|e| Self::Error::invalid_tlv_field(e, extension_type, message_type))?;
+                match field {
+                    DecodedTlvField::UserIdentity {
+                        channel_id,
+                        user_identity,
+                    } => {
+                        self.on_user_identity(peer_id, channel_id, user_identity)
+                            .await?
+                    }
+                    DecodedTlvField::ShareRejectDetail { channel_id } => {
+                        share_reject_detail_channel_id = Some(channel_id)
+                    }
+                    DecodedTlvField::Custom(field) => {
+                        self.on_custom_tlv_field(peer_id, message, field).await?
+                    }
+                    DecodedTlvField::Unknown(tlv) => {
+                        self.on_unknown_tlv_field(peer_id, message, tlv).await?
+                    }
+                }
+            }
+            if let Some(channel_id) = share_reject_detail_channel_id {
+                let detail = decode_share_reject_detail(tlv_fields)
+                    .map_err(|e| Self::Error::invalid_tlv_field(e, extension_type, message_type))?;
+                self.on_share_reject_detail(peer_id, channel_id, detail)
+                    .await?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Called with the `UserIdentity` of the Worker-Specific Hashrate Tracking extension, appended
+    /// to a `SubmitSharesExtended` for `channel_id`.
+    async fn on_user_identity(
+        &mut self,
+        _peer_id: Option<usize>,
+        _channel_id: u32,
+        _user_identity: UserIdentity,
+    ) -> Result<(), Self::Error> {
+        async move { Ok(()) }
+    }
+
+    /// Called with the Share Reject Detail appended to a `SubmitSharesError` for `channel_id`.
+    async fn on_share_reject_detail(
+        &mut self,
+        _peer_id: Option<usize>,
+        _channel_id: u32,
+        _detail: ShareRejectDetail,
+    ) -> Result<(), Self::Error> {
+        async move { Ok(()) }
+    }
+
+    /// Called with the fields of the extensions of the registry, decoded as their registered
+    /// type.
+    async fn on_custom_tlv_field(
+        &mut self,
+        _peer_id: Option<usize>,
+        _message: &AnyMessage<'_>,
+        _field: CustomTlvField,
+    ) -> Result<(), Self::Error> {
+        async move { Ok(()) }
+    }
+
+    /// Called with the fields of extensions unknown to this crate and to the registry.
+    async fn on_unknown_tlv_field(
+        &mut self,
+        _peer_id: Option<usize>,
+        _message: &AnyMessage<'_>,
+        _tlv: &Tlv,
+    ) -> Result<(), Self::Error> {
+        async move { Ok(()) }
+    }
+}
+
+/// Middleware running the TLV fields of every message through a `HandleTlvFields*`
+/// implementation, rejecting the message if a field violates its extension's rules.
+#[derive(Debug, Clone)]
+pub struct TlvFieldsLayer<H> {
+    handler: H,
+}
+
+impl<H> TlvFieldsLayer<H> {
+    pub fn new(handler: H) -> Self {
+        Self { handler }
+    }
+
+    pub fn get_handler(&mut self) -> &mut H {
+        &mut self.handler
+    }
+}
+
+impl<H: HandleTlvFieldsSync> HandlerMiddlewareSync for TlvFieldsLayer<H> {
+    type Error = H::Error;
+
+    fn on_message(
+        &mut self,
+        peer_id: Option<usize>,
+        _header: &Header,
+        message: &mut AnyMessage<'_>,
+        tlv_fields: &mut Option<Vec<Tlv>>,
+    ) -> Result<(), H::Error> {
+        match tlv_fields {
+            Some(tlv_fields) => self.handler.handle_tlv_fields(peer_id, message, tlv_fields),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<H: HandleTlvFieldsAsync + Send> HandlerMiddlewareAsync for TlvFieldsLayer<H> {
+    type Error = H::Error;
+
+    async fn on_message(
+        &mut self,
+        peer_id: Option<usize>,
+        _header: &Header,
+        message: &mut AnyMessage<'_>,
+        tlv_fields: &mut Option<Vec<Tlv>>,
+    ) -> Result<(), H::Error> {
+        match tlv_fields {
+            Some(tlv_fields) => {
+                self.handler
+                    .handle_tlv_fields(peer_id, message, tlv_fields)
+                    .await
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::HandlerError, testing::block_on};
+    use extensions_sv2::{JobState, RejectReason};
+    use mining_sv2::{SubmitSharesError, SubmitSharesExtended, SubmitSharesStandard};
+    use parsers_sv2::share_reject_detail_to_tlvs;
+
+    // Records the user identities and the fields of other extensions it receives.
+    #[derive(Default)]
+    struct Recorder {
+        registry: Option<ExtensionRegistry>,
+        user_identities: Vec<(u32, String)>,
+        fan_speeds: Vec<u8>,
+        share_reject_details: Vec<(u32, ShareRejectDetail)>,
+        unknown_fields: Vec<Tlv>,
+    }
+
+    // A field of a third-party extension, decoded by the registry.
+    struct FanSpeed(u8);
+
+    impl TlvField for FanSpeed {
+        const EXTENSION_TYPE: u16 = 0x4000;
+        const FIELD_TYPE: u8 = 0x01;
+
+        fn from_bytes(bytes: &[u8]) -> Result<Tlv, ParserError> {
+            Tlv::decode(bytes).map_err(Into::into)
+        }
+        fn to_bytes(&self) -> Result<Vec<u8>, ParserError> {
+            self.to_tlv()?.encode().map_err(Into::into)
+        }
+        fn from_tlv(tlv: &Tlv) -> Result<Self, ParserError> {
+            tlv.value
+                .first()
+                .map(|speed| FanSpeed(*speed))
+                .ok_or(ParserError::BadPayloadSize)
+        }
+        fn to_tlv(&self) -> Result<Tlv, ParserError> {
+            Ok(Tlv::new(
+                Self::EXTENSION_TYPE,
+                Self::FIELD_TYPE,
+                vec![self.0],
+            ))
+        }
+    }
+
+    impl HandleTlvFieldsSync for Recorder {
+        type Error = HandlerError;
+
+        fn get_extension_registry(&self) -> Option<&ExtensionRegistry> {
+            self.registry.as_ref()
+        }
+
+        fn on_custom_tlv_field(
+            &mut self,
+            _peer_id: Option<usize>,
+            _message: &AnyMessage<'_>,
+            field: CustomTlvField,
+        ) -> Result<(), HandlerError> {
+            self.fan_speeds
+                .push(field.downcast_ref::<FanSpeed>().unwrap().0);
+            Ok(())
+        }
+
+        fn on_user_identity(
+            &mut self,
+            _peer_id: Option<usize>,
+            channel_id: u32,
+            user_identity: UserIdentity,
+        ) -> Result<(), HandlerError> {
+            self.user_identities
+                .push((channel_id, user_identity.as_string_or_hex()));
+            Ok(())
+        }
+
+        fn on_share_reject_detail(
+            &mut self,
+            _peer_id: Option<usize>,
+            channel_id: u32,
+            detail: ShareRejectDetail,
+        ) -> Result<(), HandlerError> {
+            self.share_reject_details.push((channel_id, detail));
+            Ok(())
+        }
+
+        fn on_unknown_tlv_field(
+            &mut self,
+            _peer_id: Option<usize>,
+            _message: &AnyMessage<'_>,
+            tlv: &Tlv,
+        ) -> Result<(), HandlerError> {
+            self.unknown_fields.push(tlv.clone());
+            Ok(())
+        }
+    }
+
+    impl HandleTlvFieldsAsync for Recorder {
+        type Error = HandlerError;
+
+        async fn on_user_identity(
+            &mut self,
+            _peer_id: Option<usize>,
+            channel_id: u32,
+            user_identity: UserIdentity,
+        ) -> Result<(), HandlerError> {
+            self.user_identities
+                .push((channel_id, user_identity.as_string_or_hex()));
+            Ok(())
+        }
+    }
+
+    fn submit_shares_extended() -> AnyMessage<'static> {
+        AnyMessage::Mining(Mining::SubmitSharesExtended(SubmitSharesExtended {
+            channel_id: 7,
+            sequence_number: 1,
+            job_id: 1,
+            nonce: 0,
+            ntime: 0,
+            version: 0,
+            extranonce: vec![1, 0, 0, 0].try_into().unwrap(),
+        }))
+    }
+
+    fn user_identity(user_identity: &str) -> Tlv {
+        Tlv::new(
+            EXTENSION_TYPE_WORKER_HASHRATE_TRACKING,
+            TLV_FIELD_TYPE_USER_IDENTITY,
+            user_identity.as_bytes().to_vec(),
+        )
+    }
+
+    fn handle(
+        recorder: &mut Recorder,
+        message: &AnyMessage<'_>,
+        tlv_fields: &[Tlv],
+    ) -> Result<(), HandlerError> {
+        HandleTlvFieldsSync::handle_tlv_fields(recorder, None, message, tlv_fields)
+    }
+
+    #[test]
+    fn test_user_identity() {
+        let mut recorder = Recorder::default();
+        let unknown_field = Tlv::new(0x4000, 0x01, vec![1, 2]);
+        handle(
+            &mut recorder,
+            &submit_shares_extended(),
+            &[user_identity("Worker_001"), unknown_field.clone()],
+        )
+        .unwrap();
+        assert_eq!(recorder.user_identities, [(7, "Worker_001".to_string())]);
+        assert_eq!(recorder.unknown_fields, [unknown_field]);
+    }
+
+    #[test]
+    fn test_registered_field() {
+        let mut registry = ExtensionRegistry::new();
+        registry.register_extension(0x4000, "Telemetry").unwrap();
+        registry.register_tlv_field::<FanSpeed>("FanSpeed").unwrap();
+        let mut recorder = Recorder {
+            registry: Some(registry),
+            ..Default::default()
+        };
+
+        // fields of registered types are decoded, the others of the extension are unknown
+        let unregistered_field = Tlv::new(0x4000, 0x02, vec![1]);
+        handle(
+            &mut recorder,
+            &submit_shares_extended(),
+            &[Tlv::new(0x4000, 0x01, vec![80]), unregistered_field.clone()],
+        )
+        .unwrap();
+        assert_eq!(recorder.fan_speeds, [80]);
+        assert_eq!(recorder.unknown_fields, [unregistered_field]);
+
+        let error = handle(
+            &mut recorder,
+            &submit_shares_extended(),
+            &[Tlv::new(0x4000, 0x01, vec![])],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::InvalidTlvField {
+                error: TlvFieldError::InvalidValue(ParserError::BadPayloadSize),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_share_reject_detail() {
+        let mut recorder = Recorder::default();
+        let message = AnyMessage::Mining(Mining::SubmitSharesError(SubmitSharesError {
+            channel_id: 7,
+            sequence_number: 1,
+            error_code: "stale-share".to_string().try_into().unwrap(),
+        }));
+        let detail = ShareRejectDetail::new(RejectReason::Stale)
+            .with_expected_target([0xff; 32])
+            .with_job_state(JobState::Stale);
+        let tlv_fields = share_reject_detail_to_tlvs(&detail).unwrap();
+
+        // the fields are decoded together, the hook being called once
+        handle(&mut recorder, &message, &tlv_fields).unwrap();
+        assert_eq!(recorder.share_reject_details, [(7, detail)]);
+
+        // the reason is required
+        let error = handle(&mut recorder, &message, &tlv_fields[1..]).unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::InvalidTlvField {
+                error: TlvFieldError::MissingField {
+                    extension_type: EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+                    field_type: TLV_FIELD_TYPE_REJECT_REASON,
+                },
+                ..
+            }
+        ));
+
+        // the detail is only defined for `SubmitSharesError`
+        let error = handle(&mut recorder, &submit_shares_extended(), &tlv_fields).unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::InvalidTlvField {
+                error: TlvFieldError::UnexpectedMessage {
+                    extension_type: EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+                    field_type: TLV_FIELD_TYPE_REJECT_REASON,
+                },
+                ..
+            }
+        ));
+        assert_eq!(recorder.share_reject_details.len(), 1);
+    }
+
+    #[test]
+    fn test_duplicate_field() {
+        let mut recorder = Recorder::default();
+        let error = handle(
+            &mut recorder,
+            &submit_shares_extended(),
+            &[user_identity("Worker_001"), user_identity("Worker_002")],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::InvalidTlvField {
+                error: TlvFieldError::DuplicateField {
+                    extension_type: EXTENSION_TYPE_WORKER_HASHRATE_TRACKING,
+                    field_type: TLV_FIELD_TYPE_USER_IDENTITY,
+                },
+                extension_type: 0,
+                message_type: mining_sv2::MESSAGE_TYPE_SUBMIT_SHARES_EXTENDED,
+            }
+        ));
+
+        // duplicates of unknown extensions are rejected too
+        let unknown_field = Tlv::new(0x4000, 0x01, vec![1, 2]);
+        let error = handle(
+            &mut recorder,
+            &submit_shares_extended(),
+            &[unknown_field.clone(), unknown_field],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::InvalidTlvField {
+                error: TlvFieldError::DuplicateField {
+                    extension_type: 0x4000,
+                    field_type: 0x01,
+                },
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_field_on_wrong_message() {
+        let mut recorder = Recorder::default();
+        let message = AnyMessage::Mining(Mining::SubmitSharesStandard(SubmitSharesStandard {
+            channel_id: 7,
+            sequence_number: 1,
+            job_id: 1,
+            nonce: 0,
+            ntime: 0,
+            version: 0,
+        }));
+        let error = handle(&mut recorder, &message, &[user_identity("Worker_001")]).unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::InvalidTlvField {
+                error: TlvFieldError::UnexpectedMessage {
+                    extension_type: EXTENSION_TYPE_WORKER_HASHRATE_TRACKING,
+                    field_type: TLV_FIELD_TYPE_USER_IDENTITY,
+                },
+                extension_type: 0,
+                message_type: mining_sv2::MESSAGE_TYPE_SUBMIT_SHARES_STANDARD,
+            }
+        ));
+        assert!(recorder.user_identities.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_field_type() {
+        let mut recorder = Recorder::default();
+        let tlv = Tlv::new(EXTENSION_TYPE_WORKER_HASHRATE_TRACKING, 0x02, vec![1]);
+        let error = handle(&mut recorder, &submit_shares_extended(), &[tlv]).unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::InvalidTlvField {
+                error: TlvFieldError::UnknownField {
+                    extension_type: EXTENSION_TYPE_WORKER_HASHRATE_TRACKING,
+                    field_type: 0x02,
+                },
+                ..
+            }
+        ));
+        assert!(recorder.unknown_fields.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_value() {
+        let mut recorder = Recorder::default();
+        // user identities are at most 32 bytes long
+        let error = handle(
+            &mut recorder,
+            &submit_shares_extended(),
+            &[user_identity(&"x".repeat(33))],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::InvalidTlvField {
+                error: TlvFieldError::InvalidValue(_),
+                ..
+            }
+        ));
+        assert!(recorder.user_identities.is_empty());
+    }
+
+    #[test]
+    fn test_layer() {
+        let header = Header::from_bytes(&[
+            0,
+            0,
+            mining_sv2::MESSAGE_TYPE_SUBMIT_SHARES_EXTENDED,
+            0,
+            0,
+            0,
+        ])
+        .unwrap();
+        let mut layer = TlvFieldsLayer::new(Recorder::default());
+
+        // messages without TLV fields pass through
+        HandlerMiddlewareSync::on_message(
+            &mut layer,
+            None,
+            &header,
+            &mut submit_shares_extended(),
+            &mut None,
+        )
+        .unwrap();
+
+        let mut tlv_fields = Some(vec![user_identity("Worker_001")]);
+        HandlerMiddlewareSync::on_message(
+            &mut layer,
+            None,
+            &header,
+            &mut submit_shares_extended(),
+            &mut tlv_fields,
+        )
+        .unwrap();
+        assert_eq!(layer.get_handler().user_identities.len(), 1);
+
+        let mut tlv_fields = Some(vec![
+            user_identity("Worker_001"),
+            user_identity("Worker_002"),
+        ]);
+        let error = block_on(HandlerMiddlewareAsync::on_message(
+            &mut layer,
+            None,
+            &header,
+            &mut submit_shares_extended(),
+            &mut tlv_fields,
+        ))
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            HandlerError::InvalidTlvField {
+                error: TlvFieldError::DuplicateField { .. },
+                ..
+            }
+        ));
+        assert_eq!(layer.get_handler().user_identities.len(), 2);
+    }
+}
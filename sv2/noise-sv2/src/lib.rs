@@ -37,9 +37,11 @@
 #[macro_use]
 extern crate alloc;
 
+use aed_cipher::AeadCipher;
 use aes_gcm::aead::Buffer;
 pub use aes_gcm::aead::Error as AeadError;
-use cipher_state::GenericCipher;
+use chacha20poly1305::ChaCha20Poly1305;
+use cipher_state::{Cipher, GenericCipher};
 mod aed_cipher;
 mod cipher_state;
 mod error;
@@ -121,6 +123,23 @@ impl core::fmt::Debug for NoiseCodec {
 }
 
 impl NoiseCodec {
+    /// Builds a [`NoiseCodec`] from the ChaCha20-Poly1305 transport keys of an established
+    /// session, with both nonces at 0.
+    ///
+    /// Each key is the one derived by the handshake for a direction of the connection: the
+    /// [`Initiator`] encrypts with the initiator-to-responder key and decrypts with the
+    /// responder-to-initiator key, and the [`Responder`] the other way around. The resulting codec
+    /// handles the transport frames of the session from its first frame, e.g. to analyze a
+    /// captured connection offline.
+    pub fn from_transport_keys(encryption_key: [u8; 32], decryption_key: [u8; 32]) -> Self {
+        let encryptor = Cipher::from_cipher(ChaCha20Poly1305::from_key(encryption_key));
+        let decryptor = Cipher::from_cipher(ChaCha20Poly1305::from_key(decryption_key));
+        Self {
+            encryptor: GenericCipher::ChaCha20Poly1305(encryptor),
+            decryptor: GenericCipher::ChaCha20Poly1305(decryptor),
+        }
+    }
+
     /// Encrypts a message (`msg`) in place using the stored cipher.
     pub fn encrypt<T: Buffer>(&mut self, msg: &mut T) -> Result<(), aes_gcm::Error> {
         self.encryptor.encrypt(msg)
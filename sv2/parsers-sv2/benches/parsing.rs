@@ -0,0 +1,128 @@
+//! Benchmarks of the owned and borrowed message parsing paths.
+//!
+//! `parse_message_frame_with_tlvs` copies the payload and converts the message with
+//! `into_static`, while `parse_message_frame_borrowed` borrows both the message fields and the TLV
+//! fields from the frame. The allocations of one parse of each frame are printed before the timing
+//! benchmarks.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    convert::TryInto,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use binary_sv2::{Seq0255, U256};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use framing_sv2::header::Header;
+use mining_sv2::SubmitSharesExtended;
+use parsers_sv2::{
+    parse_message_frame_borrowed, parse_message_frame_with_tlvs, AnyMessage, Mining,
+    TemplateDistribution, Tlv, TlvList,
+};
+use template_distribution_sv2::NewTemplate;
+
+// Counts the allocations of the benchmarked code.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const NEGOTIATED_EXTENSIONS: &[u16] = &[0x0002];
+
+fn submit_shares_extended_frame() -> Vec<u8> {
+    let message = Mining::SubmitSharesExtended(SubmitSharesExtended {
+        channel_id: 1,
+        sequence_number: 2,
+        job_id: 3,
+        nonce: 4,
+        ntime: 5,
+        version: 6,
+        extranonce: vec![7; 32].try_into().unwrap(),
+    });
+    let user_identity = Tlv::new(0x0002, 0x01, b"Worker_001".to_vec());
+    TlvList::from_slice(&[user_identity])
+        .unwrap()
+        .build_frame_bytes_with_tlvs(message)
+        .unwrap()
+}
+
+fn new_template_frame() -> Vec<u8> {
+    let merkle_path: Vec<U256> = (0..12).map(|i| [i; 32].into()).collect();
+    let message = TemplateDistribution::NewTemplate(NewTemplate {
+        template_id: 1,
+        future_template: false,
+        version: 0x2000_0000,
+        coinbase_tx_version: 2,
+        coinbase_prefix: vec![3; 64].try_into().unwrap(),
+        coinbase_tx_input_sequence: u32::MAX,
+        coinbase_tx_value_remaining: 312_500_000,
+        coinbase_tx_outputs_count: 4,
+        coinbase_tx_outputs: vec![5; 4 * 43].try_into().unwrap(),
+        coinbase_tx_locktime: 0,
+        merkle_path: Seq0255::new(merkle_path).unwrap(),
+    });
+    TlvList::from_slice(&[])
+        .unwrap()
+        .build_frame_bytes_with_tlvs(AnyMessage::TemplateDistribution(message))
+        .unwrap()
+}
+
+fn parse_owned(frame: &mut [u8]) -> usize {
+    let header = Header::from_bytes(&frame[..Header::SIZE]).unwrap();
+    let (message, tlvs) =
+        parse_message_frame_with_tlvs(header, &mut frame[Header::SIZE..], NEGOTIATED_EXTENSIONS)
+            .unwrap();
+    black_box(&message);
+    tlvs.map_or(0, |tlvs| tlvs.len())
+}
+
+fn parse_borrowed(frame: &mut [u8]) -> usize {
+    let header = Header::from_bytes(&frame[..Header::SIZE]).unwrap();
+    let (message, tlvs) =
+        parse_message_frame_borrowed(header, &mut frame[Header::SIZE..], NEGOTIATED_EXTENSIONS)
+            .unwrap();
+    black_box(&message);
+    tlvs.filter_map(Result::ok).count()
+}
+
+fn allocations(parse: fn(&mut [u8]) -> usize, frame: &mut [u8]) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(parse(frame));
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn bench_parsing(c: &mut Criterion) {
+    for (name, mut frame) in [
+        ("SubmitSharesExtended", submit_shares_extended_frame()),
+        ("NewTemplate", new_template_frame()),
+    ] {
+        println!(
+            "{name}: {} allocations owned, {} allocations borrowed",
+            allocations(parse_owned, &mut frame),
+            allocations(parse_borrowed, &mut frame),
+        );
+
+        let mut group = c.benchmark_group(format!("parse::{name}"));
+        group.bench_function("owned", |b| b.iter(|| parse_owned(black_box(&mut frame))));
+        group.bench_function("borrowed", |b| {
+            b.iter(|| parse_borrowed(black_box(&mut frame)))
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_parsing);
+criterion_main!(benches);
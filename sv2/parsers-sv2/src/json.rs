@@ -0,0 +1,84 @@
+use crate::AnyMessage;
+use alloc::string::String;
+
+impl AnyMessage<'_> {
+    /// Serializes the message to its JSON representation.
+    ///
+    /// Fails if a `STR0_255` field is not valid UTF-8.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Deserializes a message from its JSON representation.
+    pub fn from_json(json: &str) -> Result<AnyMessage<'static>, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{AnyMessage, CommonMessages, Mining};
+    use alloc::{string::ToString, vec};
+    use common_messages_sv2::{Protocol, SetupConnection};
+    use core::convert::TryInto;
+    use mining_sv2::SubmitSharesExtended;
+
+    #[test]
+    fn submit_shares_extended_json() {
+        let message = AnyMessage::Mining(Mining::SubmitSharesExtended(SubmitSharesExtended {
+            channel_id: 1,
+            sequence_number: 2,
+            job_id: 3,
+            nonce: 4,
+            ntime: 5,
+            version: 6,
+            extranonce: vec![0x01, 0x02, 0xff].try_into().unwrap(),
+        }));
+        let json = message.to_json().unwrap();
+        assert_eq!(
+            json,
+            r#"{"subprotocol":"Mining","message":{"type":"SubmitSharesExtended","channel_id":1,"sequence_number":2,"job_id":3,"nonce":4,"ntime":5,"version":6,"extranonce":"0102ff"}}"#
+        );
+
+        match AnyMessage::from_json(&json).unwrap() {
+            AnyMessage::Mining(Mining::SubmitSharesExtended(m)) => {
+                assert_eq!(m.sequence_number, 2);
+                assert_eq!(m.extranonce.inner_as_ref(), &[0x01, 0x02, 0xff]);
+            }
+            m => panic!("unexpected message {m}"),
+        }
+    }
+
+    #[test]
+    fn setup_connection_json() {
+        let setup_connection = SetupConnection {
+            protocol: Protocol::MiningProtocol,
+            min_version: 2,
+            max_version: 2,
+            flags: 0,
+            endpoint_host: "0.0.0.0".to_string().try_into().unwrap(),
+            endpoint_port: 3333,
+            vendor: "vendor".to_string().try_into().unwrap(),
+            hardware_version: "".to_string().try_into().unwrap(),
+            firmware: "".to_string().try_into().unwrap(),
+            device_id: "".to_string().try_into().unwrap(),
+        };
+        let message = AnyMessage::Common(CommonMessages::SetupConnection(setup_connection.clone()));
+        let json = message.to_json().unwrap();
+        assert!(json.contains(r#""type":"SetupConnection","protocol":"MiningProtocol""#));
+        assert!(json.contains(r#""vendor":"vendor""#));
+
+        match AnyMessage::from_json(&json).unwrap() {
+            AnyMessage::Common(CommonMessages::SetupConnection(m)) => {
+                assert_eq!(m, setup_connection)
+            }
+            m => panic!("unexpected message {m}"),
+        }
+    }
+
+    #[test]
+    fn byte_array_length_is_checked() {
+        let json = r#"{"subprotocol":"Mining","message":{"type":"SetTarget","channel_id":1,"maximum_target":"ff"}}"#;
+        assert!(AnyMessage::from_json(json).is_err());
+    }
+}
@@ -21,21 +21,53 @@
 //! - **Job Declaration**: Manages custom mining job declarations, transactions, and solutions.
 //! - **Mining Protocol**: Manages standard mining communication (e.g., job dispatch, shares
 //!   submission).
+//!
+//! ## Custom Extensions
+//! Extensions other than the ones of `extensions_sv2` are registered at runtime in an
+//! [`ExtensionRegistry`], with the decoders of their messages and TLV fields, and parsed as
+//! [`Extensions::Custom`].
+//!
+//! ## JSON Representation
+//! With the `serde` feature, every message implements `serde::Serialize` and `serde::Deserialize`,
+//! and [`AnyMessage::to_json`] / [`AnyMessage::from_json`] convert any message to and from JSON:
+//!
+//! ```text
+//! {"subprotocol":"Mining","message":{"type":"SetTarget","channel_id":1,"maximum_target":"ffff..."}}
+//! ```
+//!
+//! - `subprotocol` is the [`AnyMessage`] variant and `type` the message name. Extensions messages
+//!   are nested under an `extension` tag, e.g. `{"extension":"ExtensionsNegotiation","message":
+//!   {"type":"RequestExtensions",...}}`.
+//! - Fields keep their names. Integers are numbers, byte arrays (`U256`, `B0_255`, `B0_64K`, ...)
+//!   are lowercase hex strings, `STR0_255` fields are UTF-8 strings, sequences are arrays and
+//!   absent `OPTION` fields are `null`.
 
 pub mod error;
+#[cfg(feature = "serde")]
+mod json;
+mod registry;
 mod tlv;
 mod tlv_extensions;
 
-pub use tlv::{Tlv, TlvError, TlvField, TlvList, TLV_HEADER_SIZE};
-pub use tlv_extensions::{ExtensionError, UserIdentityError};
+pub use derive_codec_sv2::TlvField;
+pub use registry::{
+    parse_message_frame_with_registry, CustomMessage, CustomTlvField, ExtensionMessage,
+    ExtensionRegistry, MessageDecoder, RegistryError,
+};
+pub use tlv::{Tlv, TlvError, TlvField, TlvList, TlvRef, TlvRefIter, TLV_HEADER_SIZE};
+pub use tlv_extensions::{
+    share_reject_detail_from_tlvs, share_reject_detail_to_tlvs, ExtensionError, UserIdentityError,
+};
 
 extern crate alloc;
+// Lets `#[derive(TlvField)]`, which refers to `parsers_sv2`, be used in this crate.
+extern crate self as parsers_sv2;
 use alloc::vec::Vec;
 use binary_sv2::{
     self,
     decodable::{DecodableField, FieldMarker},
     encodable::EncodableField,
-    from_bytes, Deserialize, GetSize,
+    from_bytes_with_tail, Deserialize, GetSize,
 };
 use common_messages_sv2::*;
 use core::{
@@ -136,6 +168,11 @@ pub fn message_type_to_name(msg_type: u8) -> &'static str {
 /// A parser of messages that are common to all Sv2 subprotocols, to be used for parsing raw
 /// messages
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type")
+)]
 pub enum CommonMessages<'a> {
     /// Notifies about changes in channel endpoint configuration.
     ChannelEndpointChanged(ChannelEndpointChanged),
@@ -163,6 +200,11 @@ impl fmt::Display for CommonMessages<'_> {
 
 /// A parser of messages of Template Distribution subprotocol, to be used for parsing raw messages
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type")
+)]
 pub enum TemplateDistribution<'a> {
     CoinbaseOutputConstraints(CoinbaseOutputConstraints),
     NewTemplate(NewTemplate<'a>),
@@ -197,6 +239,11 @@ impl fmt::Display for TemplateDistribution<'_> {
 
 /// A parser of messages of Job Declaration subprotocol, to be used for parsing raw messages
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type")
+)]
 pub enum JobDeclaration<'a> {
     AllocateMiningJobToken(AllocateMiningJobToken<'a>),
     AllocateMiningJobTokenSuccess(AllocateMiningJobTokenSuccess<'a>),
@@ -255,6 +302,11 @@ impl fmt::Display for JobDeclaration<'_> {
 ///     unified interface for handling mining-related communication. This reduces complexity and
 ///     ensures consistency across roles.
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type")
+)]
 pub enum Mining<'a> {
     CloseChannel(CloseChannel<'a>),
     NewExtendedMiningJob(NewExtendedMiningJob<'a>),
@@ -425,6 +477,11 @@ impl JobDeclaration<'_> {
 /// These messages allow endpoints to negotiate which optional extensions are supported
 /// during connection setup.
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type")
+)]
 pub enum ExtensionsNegotiation<'a> {
     RequestExtensions(RequestExtensions<'a>),
     RequestExtensionsSuccess(RequestExtensionsSuccess<'a>),
@@ -468,18 +525,30 @@ impl ExtensionsNegotiation<'_> {
 ///
 /// ## Supported Extension Types
 /// - **Extensions Negotiation (0x0001)**: Basic extension negotiation protocol
+/// - **Custom**: Messages of the extensions registered in an [`ExtensionRegistry`]
 ///
 /// Future extension types will be added as new variants (e.g., Worker Hashrate Tracking).
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "extension", content = "message")
+)]
 pub enum Extensions<'a> {
     /// Extensions Negotiation messages (extension_type=0x0001)
     ExtensionsNegotiation(ExtensionsNegotiation<'a>),
+    /// Messages of the extensions registered in an [`ExtensionRegistry`], only returned by
+    /// [`ExtensionRegistry::parse_message`] and [`parse_message_frame_with_registry`]. They have
+    /// no JSON representation.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(CustomMessage),
 }
 
 impl fmt::Display for Extensions<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Extensions::ExtensionsNegotiation(m) => write!(f, "{m}"),
+            Extensions::Custom(m) => write!(f, "{m}"),
         }
     }
 }
@@ -491,6 +560,7 @@ impl Extensions<'_> {
             Extensions::ExtensionsNegotiation(m) => {
                 Extensions::ExtensionsNegotiation(m.into_static())
             }
+            Extensions::Custom(m) => Extensions::Custom(m),
         }
     }
 }
@@ -511,6 +581,9 @@ impl AnyMessage<'_> {
 }
 
 /// Single entry point for parsing any SV2 protocol message frame with TLV support.
+///
+/// Messages of third-party extensions are rejected, use [`parse_message_frame_with_registry`] to
+/// parse the ones registered in an [`ExtensionRegistry`].
 pub fn parse_message_frame_with_tlvs(
     header: Header,
     payload: &mut [u8],
@@ -523,6 +596,26 @@ pub fn parse_message_frame_with_tlvs(
     Ok((message.into_static(), tlvs))
 }
 
+/// Borrowed version of [`parse_message_frame_with_tlvs`], which doesn't copy the payload.
+///
+/// The message borrows its variable-length fields (`B064K`, `Seq064K`, ...) from `payload`
+/// instead of being converted with `into_static`, and the TLV fields of the negotiated extensions
+/// are decoded lazily from the bytes following the message, as they are iterated.
+///
+/// Decoding still allocates, e.g. for the list of fields of the message, so this saves some of the
+/// allocations of the owned path rather than all of them: `cargo bench --bench parsing` counts 18
+/// instead of 22 for a `SubmitSharesExtended` with TLV fields, and 43 instead of 59 for a
+/// `NewTemplate`.
+pub fn parse_message_frame_borrowed<'a, 'e>(
+    header: Header,
+    payload: &'a mut [u8],
+    negotiated_extensions: &'e [u16],
+) -> Result<(AnyMessage<'a>, TlvRefIter<'a, 'e>), ParserError> {
+    let (message, tail) = AnyMessage::parse_with_tail(header, payload)?;
+    let tlvs = TlvRefIter::new(tail).with_extensions(negotiated_extensions);
+    Ok((message, tlvs))
+}
+
 /// Internal helper to extract TLV fields from the remaining payload bytes.
 fn extract_tlv_fields(
     raw_payload: &[u8],
@@ -713,6 +806,7 @@ impl IsSv2Message for Extensions<'_> {
                     MESSAGE_TYPE_REQUEST_EXTENSIONS_ERROR
                 }
             },
+            Self::Custom(m) => m.get_message().message_type(),
         }
     }
 
@@ -727,12 +821,14 @@ impl IsSv2Message for Extensions<'_> {
                     CHANNEL_BIT_REQUEST_EXTENSIONS_ERROR
                 }
             },
+            Self::Custom(m) => m.get_message().channel_bit(),
         }
     }
 
     fn extension_type(&self) -> u16 {
         match self {
             Self::ExtensionsNegotiation(_) => EXTENSION_TYPE_EXTENSIONS_NEGOTIATION,
+            Self::Custom(m) => m.get_message().extension_type(),
         }
     }
 }
@@ -878,6 +974,7 @@ impl<'decoder> From<Extensions<'decoder>> for EncodableField<'decoder> {
                 ExtensionsNegotiation::RequestExtensionsSuccess(a) => a.into(),
                 ExtensionsNegotiation::RequestExtensionsError(a) => a.into(),
             },
+            Extensions::Custom(m) => m.into(),
         }
     }
 }
@@ -890,6 +987,7 @@ impl GetSize for Extensions<'_> {
                 ExtensionsNegotiation::RequestExtensionsSuccess(a) => a.get_size(),
                 ExtensionsNegotiation::RequestExtensionsError(a) => a.get_size(),
             },
+            Extensions::Custom(m) => m.get_size(),
         }
     }
 }
@@ -999,27 +1097,37 @@ impl<'a> TryFrom<(u8, &'a mut [u8])> for CommonMessages<'a> {
     type Error = ParserError;
 
     fn try_from(v: (u8, &'a mut [u8])) -> Result<Self, Self::Error> {
-        let msg_type: CommonMessageTypes = v.0.try_into()?;
+        Self::parse_with_tail(v.0, v.1).map(|(message, _)| message)
+    }
+}
+
+impl<'a> CommonMessages<'a> {
+    // Parses the message at the start of the payload, and returns the bytes following it.
+    fn parse_with_tail(
+        msg_type: u8,
+        payload: &'a mut [u8],
+    ) -> Result<(Self, &'a mut [u8]), ParserError> {
+        let msg_type: CommonMessageTypes = msg_type.try_into()?;
         match msg_type {
             CommonMessageTypes::SetupConnection => {
-                let message: SetupConnection<'a> = from_bytes(v.1)?;
-                Ok(CommonMessages::SetupConnection(message))
+                let (message, tail): (SetupConnection<'a>, _) = from_bytes_with_tail(payload)?;
+                Ok((CommonMessages::SetupConnection(message), tail))
             }
             CommonMessageTypes::SetupConnectionSuccess => {
-                let message: SetupConnectionSuccess = from_bytes(v.1)?;
-                Ok(CommonMessages::SetupConnectionSuccess(message))
+                let (message, tail): (SetupConnectionSuccess, _) = from_bytes_with_tail(payload)?;
+                Ok((CommonMessages::SetupConnectionSuccess(message), tail))
             }
             CommonMessageTypes::SetupConnectionError => {
-                let message: SetupConnectionError<'a> = from_bytes(v.1)?;
-                Ok(CommonMessages::SetupConnectionError(message))
+                let (message, tail): (SetupConnectionError<'a>, _) = from_bytes_with_tail(payload)?;
+                Ok((CommonMessages::SetupConnectionError(message), tail))
             }
             CommonMessageTypes::ChannelEndpointChanged => {
-                let message: ChannelEndpointChanged = from_bytes(v.1)?;
-                Ok(CommonMessages::ChannelEndpointChanged(message))
+                let (message, tail): (ChannelEndpointChanged, _) = from_bytes_with_tail(payload)?;
+                Ok((CommonMessages::ChannelEndpointChanged(message), tail))
             }
             CommonMessageTypes::Reconnect => {
-                let message: Reconnect = from_bytes(v.1)?;
-                Ok(CommonMessages::Reconnect(message))
+                let (message, tail): (Reconnect, _) = from_bytes_with_tail(payload)?;
+                Ok((CommonMessages::Reconnect(message), tail))
             }
         }
     }
@@ -1068,35 +1176,57 @@ impl<'a> TryFrom<(u8, &'a mut [u8])> for TemplateDistribution<'a> {
     type Error = ParserError;
 
     fn try_from(v: (u8, &'a mut [u8])) -> Result<Self, Self::Error> {
-        let msg_type: TemplateDistributionTypes = v.0.try_into()?;
+        Self::parse_with_tail(v.0, v.1).map(|(message, _)| message)
+    }
+}
+
+impl<'a> TemplateDistribution<'a> {
+    // Parses the message at the start of the payload, and returns the bytes following it.
+    fn parse_with_tail(
+        msg_type: u8,
+        payload: &'a mut [u8],
+    ) -> Result<(Self, &'a mut [u8]), ParserError> {
+        let msg_type: TemplateDistributionTypes = msg_type.try_into()?;
         match msg_type {
             TemplateDistributionTypes::CoinbaseOutputConstraints => {
-                let message: CoinbaseOutputConstraints = from_bytes(v.1)?;
-                Ok(TemplateDistribution::CoinbaseOutputConstraints(message))
+                let (message, tail): (CoinbaseOutputConstraints, _) =
+                    from_bytes_with_tail(payload)?;
+                Ok((
+                    TemplateDistribution::CoinbaseOutputConstraints(message),
+                    tail,
+                ))
             }
             TemplateDistributionTypes::NewTemplate => {
-                let message: NewTemplate<'a> = from_bytes(v.1)?;
-                Ok(TemplateDistribution::NewTemplate(message))
+                let (message, tail): (NewTemplate<'a>, _) = from_bytes_with_tail(payload)?;
+                Ok((TemplateDistribution::NewTemplate(message), tail))
             }
             TemplateDistributionTypes::SetNewPrevHash => {
-                let message: SetNewPrevHash<'a> = from_bytes(v.1)?;
-                Ok(TemplateDistribution::SetNewPrevHash(message))
+                let (message, tail): (SetNewPrevHash<'a>, _) = from_bytes_with_tail(payload)?;
+                Ok((TemplateDistribution::SetNewPrevHash(message), tail))
             }
             TemplateDistributionTypes::RequestTransactionData => {
-                let message: RequestTransactionData = from_bytes(v.1)?;
-                Ok(TemplateDistribution::RequestTransactionData(message))
+                let (message, tail): (RequestTransactionData, _) = from_bytes_with_tail(payload)?;
+                Ok((TemplateDistribution::RequestTransactionData(message), tail))
             }
             TemplateDistributionTypes::RequestTransactionDataSuccess => {
-                let message: RequestTransactionDataSuccess = from_bytes(v.1)?;
-                Ok(TemplateDistribution::RequestTransactionDataSuccess(message))
+                let (message, tail): (RequestTransactionDataSuccess, _) =
+                    from_bytes_with_tail(payload)?;
+                Ok((
+                    TemplateDistribution::RequestTransactionDataSuccess(message),
+                    tail,
+                ))
             }
             TemplateDistributionTypes::RequestTransactionDataError => {
-                let message: RequestTransactionDataError = from_bytes(v.1)?;
-                Ok(TemplateDistribution::RequestTransactionDataError(message))
+                let (message, tail): (RequestTransactionDataError, _) =
+                    from_bytes_with_tail(payload)?;
+                Ok((
+                    TemplateDistribution::RequestTransactionDataError(message),
+                    tail,
+                ))
             }
             TemplateDistributionTypes::SubmitSolution => {
-                let message: SubmitSolution = from_bytes(v.1)?;
-                Ok(TemplateDistribution::SubmitSolution(message))
+                let (message, tail): (SubmitSolution, _) = from_bytes_with_tail(payload)?;
+                Ok((TemplateDistribution::SubmitSolution(message), tail))
             }
         }
     }
@@ -1149,39 +1279,55 @@ impl<'a> TryFrom<(u8, &'a mut [u8])> for JobDeclaration<'a> {
     type Error = ParserError;
 
     fn try_from(v: (u8, &'a mut [u8])) -> Result<Self, Self::Error> {
-        let msg_type: JobDeclarationTypes = v.0.try_into()?;
+        Self::parse_with_tail(v.0, v.1).map(|(message, _)| message)
+    }
+}
+
+impl<'a> JobDeclaration<'a> {
+    // Parses the message at the start of the payload, and returns the bytes following it.
+    fn parse_with_tail(
+        msg_type: u8,
+        payload: &'a mut [u8],
+    ) -> Result<(Self, &'a mut [u8]), ParserError> {
+        let msg_type: JobDeclarationTypes = msg_type.try_into()?;
         match msg_type {
             JobDeclarationTypes::AllocateMiningJobToken => {
-                let message: AllocateMiningJobToken = from_bytes(v.1)?;
-                Ok(JobDeclaration::AllocateMiningJobToken(message))
+                let (message, tail): (AllocateMiningJobToken, _) = from_bytes_with_tail(payload)?;
+                Ok((JobDeclaration::AllocateMiningJobToken(message), tail))
             }
             JobDeclarationTypes::AllocateMiningJobTokenSuccess => {
-                let message: AllocateMiningJobTokenSuccess = from_bytes(v.1)?;
-                Ok(JobDeclaration::AllocateMiningJobTokenSuccess(message))
+                let (message, tail): (AllocateMiningJobTokenSuccess, _) =
+                    from_bytes_with_tail(payload)?;
+                Ok((JobDeclaration::AllocateMiningJobTokenSuccess(message), tail))
             }
             JobDeclarationTypes::DeclareMiningJob => {
-                let message: DeclareMiningJob = from_bytes(v.1)?;
-                Ok(JobDeclaration::DeclareMiningJob(message))
+                let (message, tail): (DeclareMiningJob, _) = from_bytes_with_tail(payload)?;
+                Ok((JobDeclaration::DeclareMiningJob(message), tail))
             }
             JobDeclarationTypes::DeclareMiningJobSuccess => {
-                let message: DeclareMiningJobSuccess = from_bytes(v.1)?;
-                Ok(JobDeclaration::DeclareMiningJobSuccess(message))
+                let (message, tail): (DeclareMiningJobSuccess, _) = from_bytes_with_tail(payload)?;
+                Ok((JobDeclaration::DeclareMiningJobSuccess(message), tail))
             }
             JobDeclarationTypes::DeclareMiningJobError => {
-                let message: DeclareMiningJobError = from_bytes(v.1)?;
-                Ok(JobDeclaration::DeclareMiningJobError(message))
+                let (message, tail): (DeclareMiningJobError, _) = from_bytes_with_tail(payload)?;
+                Ok((JobDeclaration::DeclareMiningJobError(message), tail))
             }
             JobDeclarationTypes::ProvideMissingTransactions => {
-                let message: ProvideMissingTransactions = from_bytes(v.1)?;
-                Ok(JobDeclaration::ProvideMissingTransactions(message))
+                let (message, tail): (ProvideMissingTransactions, _) =
+                    from_bytes_with_tail(payload)?;
+                Ok((JobDeclaration::ProvideMissingTransactions(message), tail))
             }
             JobDeclarationTypes::ProvideMissingTransactionsSuccess => {
-                let message: ProvideMissingTransactionsSuccess = from_bytes(v.1)?;
-                Ok(JobDeclaration::ProvideMissingTransactionsSuccess(message))
+                let (message, tail): (ProvideMissingTransactionsSuccess, _) =
+                    from_bytes_with_tail(payload)?;
+                Ok((
+                    JobDeclaration::ProvideMissingTransactionsSuccess(message),
+                    tail,
+                ))
             }
             JobDeclarationTypes::PushSolution => {
-                let message: PushSolution = from_bytes(v.1)?;
-                Ok(JobDeclaration::PushSolution(message))
+                let (message, tail): (PushSolution, _) = from_bytes_with_tail(payload)?;
+                Ok((JobDeclaration::PushSolution(message), tail))
             }
         }
     }
@@ -1257,91 +1403,106 @@ impl<'a> TryFrom<(u8, &'a mut [u8])> for Mining<'a> {
     type Error = ParserError;
 
     fn try_from(v: (u8, &'a mut [u8])) -> Result<Self, Self::Error> {
-        let msg_type: MiningTypes = v.0.try_into()?;
+        Self::parse_with_tail(v.0, v.1).map(|(message, _)| message)
+    }
+}
+
+impl<'a> Mining<'a> {
+    // Parses the message at the start of the payload, and returns the bytes following it.
+    fn parse_with_tail(
+        msg_type: u8,
+        payload: &'a mut [u8],
+    ) -> Result<(Self, &'a mut [u8]), ParserError> {
+        let msg_type: MiningTypes = msg_type.try_into()?;
         match msg_type {
             MiningTypes::CloseChannel => {
-                let message: CloseChannel = from_bytes(v.1)?;
-                Ok(Mining::CloseChannel(message))
+                let (message, tail): (CloseChannel, _) = from_bytes_with_tail(payload)?;
+                Ok((Mining::CloseChannel(message), tail))
             }
             MiningTypes::NewExtendedMiningJob => {
-                let message: NewExtendedMiningJob = from_bytes(v.1)?;
-                Ok(Mining::NewExtendedMiningJob(message))
+                let (message, tail): (NewExtendedMiningJob, _) = from_bytes_with_tail(payload)?;
+                Ok((Mining::NewExtendedMiningJob(message), tail))
             }
             MiningTypes::NewMiningJob => {
-                let message: NewMiningJob = from_bytes(v.1)?;
-                Ok(Mining::NewMiningJob(message))
+                let (message, tail): (NewMiningJob, _) = from_bytes_with_tail(payload)?;
+                Ok((Mining::NewMiningJob(message), tail))
             }
             MiningTypes::OpenExtendedMiningChannel => {
-                let message: OpenExtendedMiningChannel = from_bytes(v.1)?;
-                Ok(Mining::OpenExtendedMiningChannel(message))
+                let (message, tail): (OpenExtendedMiningChannel, _) =
+                    from_bytes_with_tail(payload)?;
+                Ok((Mining::OpenExtendedMiningChannel(message), tail))
             }
             MiningTypes::OpenExtendedMiningChannelSuccess => {
-                let message: OpenExtendedMiningChannelSuccess = from_bytes(v.1)?;
-                Ok(Mining::OpenExtendedMiningChannelSuccess(message))
+                let (message, tail): (OpenExtendedMiningChannelSuccess, _) =
+                    from_bytes_with_tail(payload)?;
+                Ok((Mining::OpenExtendedMiningChannelSuccess(message), tail))
             }
             MiningTypes::OpenMiningChannelError => {
-                let message: OpenMiningChannelError = from_bytes(v.1)?;
-                Ok(Mining::OpenMiningChannelError(message))
+                let (message, tail): (OpenMiningChannelError, _) = from_bytes_with_tail(payload)?;
+                Ok((Mining::OpenMiningChannelError(message), tail))
             }
             MiningTypes::OpenStandardMiningChannel => {
-                let message: OpenStandardMiningChannel = from_bytes(v.1)?;
-                Ok(Mining::OpenStandardMiningChannel(message))
+                let (message, tail): (OpenStandardMiningChannel, _) =
+                    from_bytes_with_tail(payload)?;
+                Ok((Mining::OpenStandardMiningChannel(message), tail))
             }
             MiningTypes::OpenStandardMiningChannelSuccess => {
-                let message: OpenStandardMiningChannelSuccess = from_bytes(v.1)?;
-                Ok(Mining::OpenStandardMiningChannelSuccess(message))
+                let (message, tail): (OpenStandardMiningChannelSuccess, _) =
+                    from_bytes_with_tail(payload)?;
+                Ok((Mining::OpenStandardMiningChannelSuccess(message), tail))
             }
             MiningTypes::SetCustomMiningJob => {
-                let message: SetCustomMiningJob = from_bytes(v.1)?;
-                Ok(Mining::SetCustomMiningJob(message))
+                let (message, tail): (SetCustomMiningJob, _) = from_bytes_with_tail(payload)?;
+                Ok((Mining::SetCustomMiningJob(message), tail))
             }
             MiningTypes::SetCustomMiningJobError => {
-                let message: SetCustomMiningJobError = from_bytes(v.1)?;
-                Ok(Mining::SetCustomMiningJobError(message))
+                let (message, tail): (SetCustomMiningJobError, _) = from_bytes_with_tail(payload)?;
+                Ok((Mining::SetCustomMiningJobError(message), tail))
             }
             MiningTypes::SetCustomMiningJobSuccess => {
-                let message: SetCustomMiningJobSuccess = from_bytes(v.1)?;
-                Ok(Mining::SetCustomMiningJobSuccess(message))
+                let (message, tail): (SetCustomMiningJobSuccess, _) =
+                    from_bytes_with_tail(payload)?;
+                Ok((Mining::SetCustomMiningJobSuccess(message), tail))
             }
             MiningTypes::SetExtranoncePrefix => {
-                let message: SetExtranoncePrefix = from_bytes(v.1)?;
-                Ok(Mining::SetExtranoncePrefix(message))
+                let (message, tail): (SetExtranoncePrefix, _) = from_bytes_with_tail(payload)?;
+                Ok((Mining::SetExtranoncePrefix(message), tail))
             }
             MiningTypes::SetGroupChannel => {
-                let message: SetGroupChannel = from_bytes(v.1)?;
-                Ok(Mining::SetGroupChannel(message))
+                let (message, tail): (SetGroupChannel, _) = from_bytes_with_tail(payload)?;
+                Ok((Mining::SetGroupChannel(message), tail))
             }
             MiningTypes::SetNewPrevHash => {
-                let message: MiningSetNewPrevHash = from_bytes(v.1)?;
-                Ok(Mining::SetNewPrevHash(message))
+                let (message, tail): (MiningSetNewPrevHash, _) = from_bytes_with_tail(payload)?;
+                Ok((Mining::SetNewPrevHash(message), tail))
             }
             MiningTypes::SetTarget => {
-                let message: SetTarget = from_bytes(v.1)?;
-                Ok(Mining::SetTarget(message))
+                let (message, tail): (SetTarget, _) = from_bytes_with_tail(payload)?;
+                Ok((Mining::SetTarget(message), tail))
             }
             MiningTypes::SubmitSharesError => {
-                let message: SubmitSharesError = from_bytes(v.1)?;
-                Ok(Mining::SubmitSharesError(message))
+                let (message, tail): (SubmitSharesError, _) = from_bytes_with_tail(payload)?;
+                Ok((Mining::SubmitSharesError(message), tail))
             }
             MiningTypes::SubmitSharesExtended => {
-                let message: SubmitSharesExtended = from_bytes(v.1)?;
-                Ok(Mining::SubmitSharesExtended(message))
+                let (message, tail): (SubmitSharesExtended, _) = from_bytes_with_tail(payload)?;
+                Ok((Mining::SubmitSharesExtended(message), tail))
             }
             MiningTypes::SubmitSharesStandard => {
-                let message: SubmitSharesStandard = from_bytes(v.1)?;
-                Ok(Mining::SubmitSharesStandard(message))
+                let (message, tail): (SubmitSharesStandard, _) = from_bytes_with_tail(payload)?;
+                Ok((Mining::SubmitSharesStandard(message), tail))
             }
             MiningTypes::SubmitSharesSuccess => {
-                let message: SubmitSharesSuccess = from_bytes(v.1)?;
-                Ok(Mining::SubmitSharesSuccess(message))
+                let (message, tail): (SubmitSharesSuccess, _) = from_bytes_with_tail(payload)?;
+                Ok((Mining::SubmitSharesSuccess(message), tail))
             }
             MiningTypes::UpdateChannel => {
-                let message: UpdateChannel = from_bytes(v.1)?;
-                Ok(Mining::UpdateChannel(message))
+                let (message, tail): (UpdateChannel, _) = from_bytes_with_tail(payload)?;
+                Ok((Mining::UpdateChannel(message), tail))
             }
             MiningTypes::UpdateChannelError => {
-                let message: UpdateChannelError = from_bytes(v.1)?;
-                Ok(Mining::UpdateChannelError(message))
+                let (message, tail): (UpdateChannelError, _) = from_bytes_with_tail(payload)?;
+                Ok((Mining::UpdateChannelError(message), tail))
             }
         }
     }
@@ -1382,32 +1543,53 @@ impl<'a> TryFrom<(u16, u8, &'a mut [u8])> for Extensions<'a> {
     type Error = ParserError;
 
     fn try_from(v: (u16, u8, &'a mut [u8])) -> Result<Self, Self::Error> {
+        Self::parse_with_tail(v.0, v.1, v.2).map(|(message, _)| message)
+    }
+}
+
+impl<'a> Extensions<'a> {
+    // Parses the message at the start of the payload, and returns the bytes following it.
+    fn parse_with_tail(
+        extension_type: u16,
+        msg_type: u8,
+        payload: &'a mut [u8],
+    ) -> Result<(Self, &'a mut [u8]), ParserError> {
         // Remove the channel_msg bit (bit 15) from extension_type to ensure correct matching
         const CHANNEL_MSG_MASK: u16 = 0b1000_0000_0000_0000;
-        let extension_type = v.0 & !CHANNEL_MSG_MASK;
-        let msg_type = v.1;
-        let payload = v.2;
+        let extension_type = extension_type & !CHANNEL_MSG_MASK;
 
         match extension_type {
             EXTENSION_TYPE_EXTENSIONS_NEGOTIATION => {
                 let msg_enum: ExtensionsNegotiationTypes = msg_type.try_into()?;
                 match msg_enum {
                     ExtensionsNegotiationTypes::RequestExtensions => {
-                        let message: RequestExtensions = from_bytes(payload)?;
-                        Ok(Extensions::ExtensionsNegotiation(
-                            ExtensionsNegotiation::RequestExtensions(message),
+                        let (message, tail): (RequestExtensions, _) =
+                            from_bytes_with_tail(payload)?;
+                        Ok((
+                            Extensions::ExtensionsNegotiation(
+                                ExtensionsNegotiation::RequestExtensions(message),
+                            ),
+                            tail,
                         ))
                     }
                     ExtensionsNegotiationTypes::RequestExtensionsSuccess => {
-                        let message: RequestExtensionsSuccess = from_bytes(payload)?;
-                        Ok(Extensions::ExtensionsNegotiation(
-                            ExtensionsNegotiation::RequestExtensionsSuccess(message),
+                        let (message, tail): (RequestExtensionsSuccess, _) =
+                            from_bytes_with_tail(payload)?;
+                        Ok((
+                            Extensions::ExtensionsNegotiation(
+                                ExtensionsNegotiation::RequestExtensionsSuccess(message),
+                            ),
+                            tail,
                         ))
                     }
                     ExtensionsNegotiationTypes::RequestExtensionsError => {
-                        let message: RequestExtensionsError = from_bytes(payload)?;
-                        Ok(Extensions::ExtensionsNegotiation(
-                            ExtensionsNegotiation::RequestExtensionsError(message),
+                        let (message, tail): (RequestExtensionsError, _) =
+                            from_bytes_with_tail(payload)?;
+                        Ok((
+                            Extensions::ExtensionsNegotiation(
+                                ExtensionsNegotiation::RequestExtensionsError(message),
+                            ),
+                            tail,
                         ))
                     }
                 }
@@ -1419,6 +1601,11 @@ impl<'a> TryFrom<(u16, u8, &'a mut [u8])> for Extensions<'a> {
 
 /// A parser of messages that a Mining Device could send
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "subprotocol", content = "message")
+)]
 pub enum MiningDeviceMessages<'a> {
     Common(CommonMessages<'a>),
     Mining(Mining<'a>),
@@ -1460,6 +1647,11 @@ impl<'a> TryFrom<(u8, &'a mut [u8])> for MiningDeviceMessages<'a> {
 
 /// A parser of all possible SV2 messages
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "subprotocol", content = "message")
+)]
 pub enum AnyMessage<'a> {
     Common(CommonMessages<'a>),
     Mining(Mining<'a>),
@@ -1582,16 +1774,24 @@ impl<'a> TryFrom<(Header, &'a mut [u8])> for AnyMessage<'a> {
     type Error = ParserError;
 
     fn try_from(v: (Header, &'a mut [u8])) -> Result<Self, Self::Error> {
-        let header = v.0;
-        let payload = v.1;
+        Self::parse_with_tail(v.0, v.1).map(|(message, _)| message)
+    }
+}
+
+impl<'a> AnyMessage<'a> {
+    // Parses the message at the start of the payload, and returns the bytes following it, i.e.
+    // the TLV fields.
+    fn parse_with_tail(
+        header: Header,
+        payload: &'a mut [u8],
+    ) -> Result<(Self, &'a mut [u8]), ParserError> {
         let extension_type = header.ext_type_without_channel_msg();
         let message_type = header.msg_type();
 
         // Try to parse as Extensions message first (if extension_type != 0)
         if extension_type != 0 {
-            return (extension_type, message_type, payload)
-                .try_into()
-                .map(Self::Extensions);
+            return Extensions::parse_with_tail(extension_type, message_type, payload)
+                .map(|(message, tail)| (Self::Extensions(message), tail));
         }
 
         // Fall back to standard protocol message parsing (extension_type == 0)
@@ -1608,17 +1808,21 @@ impl<'a> TryFrom<(Header, &'a mut [u8])> for AnyMessage<'a> {
             is_template_distribution,
         ) {
             (Ok(_), Err(_), Err(_), Err(_)) => {
-                Ok(Self::Common((message_type, payload).try_into()?))
+                let (message, tail) = CommonMessages::parse_with_tail(message_type, payload)?;
+                Ok((Self::Common(message), tail))
             }
             (Err(_), Ok(_), Err(_), Err(_)) => {
-                Ok(Self::Mining((message_type, payload).try_into()?))
+                let (message, tail) = Mining::parse_with_tail(message_type, payload)?;
+                Ok((Self::Mining(message), tail))
             }
             (Err(_), Err(_), Ok(_), Err(_)) => {
-                Ok(Self::JobDeclaration((message_type, payload).try_into()?))
+                let (message, tail) = JobDeclaration::parse_with_tail(message_type, payload)?;
+                Ok((Self::JobDeclaration(message), tail))
+            }
+            (Err(_), Err(_), Err(_), Ok(_)) => {
+                let (message, tail) = TemplateDistribution::parse_with_tail(message_type, payload)?;
+                Ok((Self::TemplateDistribution(message), tail))
             }
-            (Err(_), Err(_), Err(_), Ok(_)) => Ok(Self::TemplateDistribution(
-                (message_type, payload).try_into()?,
-            )),
             (Err(e), Err(_), Err(_), Err(_)) => Err(e),
             // This is an impossible state is safe to panic here
             _ => panic!(),
@@ -1748,14 +1952,19 @@ impl<'a> TryFrom<AnyMessage<'a>> for MiningDeviceMessages<'a> {
 
 #[cfg(test)]
 mod test {
-    use crate::{AnyMessage, Extensions, ExtensionsNegotiation, Mining};
+    use crate::{
+        parse_message_frame_borrowed, parse_message_frame_with_tlvs, AnyMessage, Extensions,
+        ExtensionsNegotiation, Mining, Tlv, TlvList,
+    };
+    use alloc::string::ToString;
     use alloc::vec;
     use alloc::vec::Vec;
     use binary_sv2::{Seq064K, Sv2Option, U256};
     use codec_sv2::StandardSv2Frame;
     use core::convert::{TryFrom, TryInto};
     use extensions_sv2::{RequestExtensions, EXTENSION_TYPE_EXTENSIONS_NEGOTIATION};
-    use mining_sv2::NewMiningJob;
+    use framing_sv2::header::Header;
+    use mining_sv2::{NewMiningJob, SubmitSharesExtended};
 
     pub type Message = AnyMessage<'static>;
     pub type StdFrame = StandardSv2Frame<Message>;
@@ -1807,6 +2016,75 @@ mod test {
         message_serialization_check(mining_message, CORRECTLY_SERIALIZED_MSG);
     }
 
+    #[test]
+    fn borrowed_parsing_matches_owned_parsing() {
+        let submit_shares = SubmitSharesExtended {
+            channel_id: 1,
+            sequence_number: 2,
+            job_id: 3,
+            nonce: 4,
+            ntime: 5,
+            version: 6,
+            extranonce: vec![7; 16].try_into().unwrap(),
+        };
+        let user_identity = Tlv::new(0x0002, 0x01, b"Worker_001".to_vec());
+        let unknown = Tlv::new(0x0003, 0x01, b"data".to_vec());
+        let mut frame = TlvList::from_slice(&[user_identity.clone(), unknown])
+            .unwrap()
+            .build_frame_bytes_with_tlvs(Mining::SubmitSharesExtended(submit_shares))
+            .unwrap();
+        let header = Header::from_bytes(&frame[..Header::SIZE]).unwrap();
+
+        let mut payload = frame[Header::SIZE..].to_vec();
+        let (owned, owned_tlvs) =
+            parse_message_frame_with_tlvs(header, &mut payload, &[0x0002]).unwrap();
+
+        let (borrowed, tlvs) =
+            parse_message_frame_borrowed(header, &mut frame[Header::SIZE..], &[0x0002]).unwrap();
+        let tlvs: Vec<Tlv> = tlvs.map(|tlv| tlv.unwrap().to_tlv()).collect();
+        assert_eq!(tlvs, vec![user_identity]);
+        assert_eq!(Some(tlvs), owned_tlvs);
+        assert_eq!(borrowed.to_string(), owned.to_string());
+    }
+
+    #[test]
+    fn borrowed_message_outlives_negotiated_extensions() {
+        let submit_shares = SubmitSharesExtended {
+            channel_id: 1,
+            sequence_number: 2,
+            job_id: 3,
+            nonce: 4,
+            ntime: 5,
+            version: 6,
+            extranonce: vec![7; 16].try_into().unwrap(),
+        };
+        let user_identity = Tlv::new(0x0002, 0x01, b"Worker_001".to_vec());
+        let mut frame = TlvList::from_slice(&[user_identity])
+            .unwrap()
+            .build_frame_bytes_with_tlvs(Mining::SubmitSharesExtended(submit_shares))
+            .unwrap();
+        let header = Header::from_bytes(&frame[..Header::SIZE]).unwrap();
+
+        let message = {
+            let negotiated_extensions = vec![0x0002];
+            let (message, tlvs) = parse_message_frame_borrowed(
+                header,
+                &mut frame[Header::SIZE..],
+                &negotiated_extensions,
+            )
+            .unwrap();
+            assert_eq!(tlvs.count(), 1);
+            message
+        };
+        assert!(matches!(
+            message,
+            AnyMessage::Mining(Mining::SubmitSharesExtended(SubmitSharesExtended {
+                channel_id: 1,
+                ..
+            }))
+        ));
+    }
+
     fn message_serialization_check(message: AnyMessage<'static>, expected_result: &[u8]) {
         let frame = StdFrame::try_from(message).unwrap();
         let encoded_frame_length = frame.encoded_length();
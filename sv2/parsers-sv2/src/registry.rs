@@ -0,0 +1,559 @@
+//! Runtime registry of third-party extensions.
+//!
+//! [`Extensions`] only knows the extensions defined in `extensions_sv2`. Applications prototyping
+//! their own extensions register them in an [`ExtensionRegistry`] at startup, with the decoders
+//! of their message types and their TLV field types. [`ExtensionRegistry::parse_message`] and
+//! [`parse_message_frame_with_registry`] then return the messages of registered extensions as
+//! [`Extensions::Custom`], from which the application recovers its own types with
+//! [`CustomMessage::downcast_ref`].
+//!
+//! Built-in extensions can't be registered, so a registry never changes how they are parsed.
+
+use crate::{extract_tlv_fields, AnyMessage, Extensions, ParserError, Tlv, TlvField};
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
+use binary_sv2::{
+    encodable::{EncodableField, EncodablePrimitive},
+    GetSize,
+};
+use core::{any::Any, convert::TryInto, fmt};
+use extensions_sv2::{
+    EXTENSION_TYPE_EXTENSIONS_NEGOTIATION, EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+    EXTENSION_TYPE_WORKER_HASHRATE_TRACKING,
+};
+use framing_sv2::header::Header;
+
+const CHANNEL_MSG_MASK: u16 = 0b1000_0000_0000_0000;
+
+/// A message of an extension registered in an [`ExtensionRegistry`].
+pub trait ExtensionMessage: fmt::Debug + fmt::Display + Send + Sync + 'static {
+    fn extension_type(&self) -> u16;
+
+    fn message_type(&self) -> u8;
+
+    fn channel_bit(&self) -> bool;
+
+    /// Encodes the message payload, without the frame header and the TLV fields.
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// Returns the size of the encoded payload.
+    fn get_size(&self) -> usize {
+        self.to_bytes().len()
+    }
+
+    /// Returns the message as [`Any`], for [`CustomMessage::downcast_ref`]. Implementations
+    /// return `self`.
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// A message of an extension registered in an [`ExtensionRegistry`], carried by
+/// [`Extensions::Custom`].
+#[derive(Clone, Debug)]
+pub struct CustomMessage(Arc<dyn ExtensionMessage>);
+
+impl CustomMessage {
+    pub fn new<M: ExtensionMessage>(message: M) -> Self {
+        Self(Arc::new(message))
+    }
+
+    /// Returns the message as its concrete type, or `None` if it is of another type.
+    pub fn downcast_ref<M: ExtensionMessage>(&self) -> Option<&M> {
+        self.0.as_any().downcast_ref()
+    }
+
+    pub fn get_message(&self) -> &dyn ExtensionMessage {
+        self.0.as_ref()
+    }
+}
+
+impl fmt::Display for CustomMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl GetSize for CustomMessage {
+    fn get_size(&self) -> usize {
+        self.0.get_size()
+    }
+}
+
+impl<'a> From<CustomMessage> for EncodableField<'a> {
+    fn from(m: CustomMessage) -> Self {
+        EncodableField::Struct(
+            m.0.to_bytes()
+                .into_iter()
+                .map(|byte| EncodableField::Primitive(EncodablePrimitive::OwnedU8(byte)))
+                .collect(),
+        )
+    }
+}
+
+impl From<CustomMessage> for Extensions<'_> {
+    fn from(m: CustomMessage) -> Self {
+        Extensions::Custom(m)
+    }
+}
+
+/// A TLV field of a type registered in an [`ExtensionRegistry`], with its decoded value.
+#[derive(Clone)]
+pub struct CustomTlvField {
+    tlv: Tlv,
+    value: Arc<dyn Any + Send + Sync>,
+}
+
+impl CustomTlvField {
+    pub fn get_tlv(&self) -> &Tlv {
+        &self.tlv
+    }
+
+    /// Returns the decoded value, or `None` if `T` is not the registered type of the field.
+    pub fn downcast_ref<T: TlvField + 'static>(&self) -> Option<&T> {
+        self.value.downcast_ref()
+    }
+}
+
+impl fmt::Debug for CustomTlvField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomTlvField")
+            .field("tlv", &self.tlv)
+            .finish()
+    }
+}
+
+/// Decodes the payload of a registered message type.
+///
+/// The payload can be followed by TLV fields, so decoders should ignore trailing bytes.
+pub type MessageDecoder = fn(&[u8]) -> Result<CustomMessage, ParserError>;
+
+type TlvFieldDecoder = fn(&Tlv) -> Result<Arc<dyn Any + Send + Sync>, ParserError>;
+
+fn decode_tlv_field<T: TlvField + Send + Sync + 'static>(
+    tlv: &Tlv,
+) -> Result<Arc<dyn Any + Send + Sync>, ParserError> {
+    Ok(Arc::new(T::from_tlv(tlv)?))
+}
+
+/// Errors registering extensions, message types or TLV field types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    /// The extension type is 0 (the base protocol) or one of the extensions of `extensions_sv2`.
+    BuiltInExtension(u16),
+    /// The extension type has the `channel_msg` bit set.
+    InvalidExtensionType(u16),
+    /// The extension was already registered.
+    DuplicateExtension(u16),
+    /// The extension of the message or TLV field type was not registered first.
+    UnknownExtension(u16),
+    /// The message type was already registered for this extension.
+    DuplicateMessageType {
+        extension_type: u16,
+        message_type: u8,
+    },
+    /// The TLV field type was already registered for this extension.
+    DuplicateTlvField { extension_type: u16, field_type: u8 },
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::BuiltInExtension(t) => write!(f, "Extension 0x{t:04x} is built in"),
+            RegistryError::InvalidExtensionType(t) => {
+                write!(f, "Extension type 0x{t:04x} has the channel_msg bit set")
+            }
+            RegistryError::DuplicateExtension(t) => {
+                write!(f, "Extension 0x{t:04x} is already registered")
+            }
+            RegistryError::UnknownExtension(t) => write!(f, "Extension 0x{t:04x} is not registered"),
+            RegistryError::DuplicateMessageType {
+                extension_type,
+                message_type,
+            } => write!(
+                f,
+                "Message type 0x{message_type:02x} of extension 0x{extension_type:04x} is already registered"
+            ),
+            RegistryError::DuplicateTlvField {
+                extension_type,
+                field_type,
+            } => write!(
+                f,
+                "TLV field type 0x{field_type:02x} of extension 0x{extension_type:04x} is already registered"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RegisteredExtension {
+    name: &'static str,
+    messages: BTreeMap<u8, (&'static str, MessageDecoder)>,
+    tlv_fields: BTreeMap<u8, (&'static str, TlvFieldDecoder)>,
+}
+
+/// Extensions registered at runtime, with their message and TLV field types.
+///
+/// ```ignore
+/// let mut registry = ExtensionRegistry::new();
+/// registry.register_extension(0x4000, "Vendor Telemetry")?;
+/// registry.register_message(0x4000, 0x00, "DeviceTelemetry", decode_device_telemetry)?;
+/// registry.register_tlv_field::<DeviceTemperature>("DeviceTemperature")?;
+///
+/// let (message, tlv_fields) =
+///     parse_message_frame_with_registry(header, payload, &negotiated_extensions, &registry)?;
+/// if let AnyMessage::Extensions(Extensions::Custom(m)) = &message {
+///     let telemetry: &DeviceTelemetry = m.downcast_ref().unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ExtensionRegistry {
+    extensions: BTreeMap<u16, RegisteredExtension>,
+}
+
+impl ExtensionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers an extension, before its message and TLV field types.
+    pub fn register_extension(
+        &mut self,
+        extension_type: u16,
+        name: &'static str,
+    ) -> Result<(), RegistryError> {
+        if extension_type & CHANNEL_MSG_MASK != 0 {
+            return Err(RegistryError::InvalidExtensionType(extension_type));
+        }
+        if matches!(
+            extension_type,
+            0 | EXTENSION_TYPE_EXTENSIONS_NEGOTIATION
+                | EXTENSION_TYPE_WORKER_HASHRATE_TRACKING
+                | EXTENSION_TYPE_SHARE_REJECT_DETAIL
+        ) {
+            return Err(RegistryError::BuiltInExtension(extension_type));
+        }
+        if self.extensions.contains_key(&extension_type) {
+            return Err(RegistryError::DuplicateExtension(extension_type));
+        }
+        self.extensions.insert(
+            extension_type,
+            RegisteredExtension {
+                name,
+                messages: BTreeMap::new(),
+                tlv_fields: BTreeMap::new(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Registers a message type of a registered extension, with the decoder of its payload.
+    pub fn register_message(
+        &mut self,
+        extension_type: u16,
+        message_type: u8,
+        name: &'static str,
+        decoder: MessageDecoder,
+    ) -> Result<(), RegistryError> {
+        let extension = self
+            .extensions
+            .get_mut(&extension_type)
+            .ok_or(RegistryError::UnknownExtension(extension_type))?;
+        if extension.messages.contains_key(&message_type) {
+            return Err(RegistryError::DuplicateMessageType {
+                extension_type,
+                message_type,
+            });
+        }
+        extension.messages.insert(message_type, (name, decoder));
+        Ok(())
+    }
+
+    /// Registers the TLV field type `T` of a registered extension, decoded with
+    /// [`TlvField::from_tlv`].
+    pub fn register_tlv_field<T: TlvField + Send + Sync + 'static>(
+        &mut self,
+        name: &'static str,
+    ) -> Result<(), RegistryError> {
+        let (extension_type, field_type) = (T::EXTENSION_TYPE, T::FIELD_TYPE);
+        let extension = self
+            .extensions
+            .get_mut(&extension_type)
+            .ok_or(RegistryError::UnknownExtension(extension_type))?;
+        if extension.tlv_fields.contains_key(&field_type) {
+            return Err(RegistryError::DuplicateTlvField {
+                extension_type,
+                field_type,
+            });
+        }
+        extension
+            .tlv_fields
+            .insert(field_type, (name, decode_tlv_field::<T>));
+        Ok(())
+    }
+
+    /// Returns `true` if the extension is registered. The `channel_msg` bit is ignored.
+    pub fn is_registered(&self, extension_type: u16) -> bool {
+        self.extensions
+            .contains_key(&(extension_type & !CHANNEL_MSG_MASK))
+    }
+
+    /// Returns the types of the registered extensions.
+    pub fn get_extension_types(&self) -> Vec<u16> {
+        self.extensions.keys().copied().collect()
+    }
+
+    pub fn get_extension_name(&self, extension_type: u16) -> Option<&'static str> {
+        self.extensions
+            .get(&(extension_type & !CHANNEL_MSG_MASK))
+            .map(|extension| extension.name)
+    }
+
+    pub fn get_message_name(&self, extension_type: u16, message_type: u8) -> Option<&'static str> {
+        self.extensions
+            .get(&(extension_type & !CHANNEL_MSG_MASK))
+            .and_then(|extension| extension.messages.get(&message_type))
+            .map(|(name, _)| *name)
+    }
+
+    pub fn get_tlv_field_name(&self, extension_type: u16, field_type: u8) -> Option<&'static str> {
+        self.extensions
+            .get(&(extension_type & !CHANNEL_MSG_MASK))
+            .and_then(|extension| extension.tlv_fields.get(&field_type))
+            .map(|(name, _)| *name)
+    }
+
+    /// Parses a message, with the registered decoders for the messages of registered extensions
+    /// and as [`AnyMessage::try_from`] for the others.
+    ///
+    /// Returns [`ParserError::UnexpectedMessage`] for message types not registered for their
+    /// extension.
+    pub fn parse_message<'a>(
+        &self,
+        header: Header,
+        payload: &'a mut [u8],
+    ) -> Result<AnyMessage<'a>, ParserError> {
+        let extension_type = header.ext_type_without_channel_msg();
+        match self.extensions.get(&extension_type) {
+            Some(extension) => {
+                let (_, decoder) = extension
+                    .messages
+                    .get(&header.msg_type())
+                    .ok_or(ParserError::UnexpectedMessage(header.msg_type()))?;
+                Ok(AnyMessage::Extensions(Extensions::Custom(decoder(
+                    payload,
+                )?)))
+            }
+            None => (header, payload).try_into(),
+        }
+    }
+
+    /// Decodes a TLV field with the decoder of its registered type.
+    ///
+    /// Returns `None` if the field type is not registered.
+    pub fn decode_tlv_field(&self, tlv: &Tlv) -> Option<Result<CustomTlvField, ParserError>> {
+        let (_, decoder) = self
+            .extensions
+            .get(&tlv.r#type.extension_type)?
+            .tlv_fields
+            .get(&tlv.r#type.field_type)?;
+        Some(decoder(tlv).map(|value| CustomTlvField {
+            tlv: tlv.clone(),
+            value,
+        }))
+    }
+}
+
+/// Like [`parse_message_frame_with_tlvs`](crate::parse_message_frame_with_tlvs), with the
+/// messages of the extensions of `registry` parsed as [`Extensions::Custom`].
+///
+/// TLV fields are kept for the extensions in `negotiated_extensions`, registered or not. The
+/// fields of registered types are decoded with [`ExtensionRegistry::decode_tlv_field`].
+pub fn parse_message_frame_with_registry(
+    header: Header,
+    payload: &mut [u8],
+    negotiated_extensions: &[u16],
+    registry: &ExtensionRegistry,
+) -> Result<(AnyMessage<'static>, Option<Vec<Tlv>>), ParserError> {
+    let raw_payload = payload.to_vec();
+    let message = registry.parse_message(header, payload)?;
+    let tlv_fields = extract_tlv_fields(&raw_payload, message.get_size(), negotiated_extensions);
+    Ok((message.into_static(), tlv_fields))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{IsSv2Message, Mining};
+    use alloc::{format, vec};
+
+    const EXTENSION_TYPE_TELEMETRY: u16 = 0x4000;
+
+    #[derive(Debug, PartialEq)]
+    struct DeviceTelemetry {
+        temperature: u16,
+    }
+
+    impl fmt::Display for DeviceTelemetry {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "DeviceTelemetry(temperature={})", self.temperature)
+        }
+    }
+
+    impl ExtensionMessage for DeviceTelemetry {
+        fn extension_type(&self) -> u16 {
+            EXTENSION_TYPE_TELEMETRY
+        }
+        fn message_type(&self) -> u8 {
+            0x00
+        }
+        fn channel_bit(&self) -> bool {
+            false
+        }
+        fn to_bytes(&self) -> Vec<u8> {
+            self.temperature.to_le_bytes().to_vec()
+        }
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    fn decode_device_telemetry(payload: &[u8]) -> Result<CustomMessage, ParserError> {
+        let temperature = payload.get(..2).ok_or(ParserError::BadPayloadSize)?;
+        Ok(CustomMessage::new(DeviceTelemetry {
+            temperature: u16::from_le_bytes([temperature[0], temperature[1]]),
+        }))
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct FanSpeed(u8);
+
+    impl TlvField for FanSpeed {
+        const EXTENSION_TYPE: u16 = EXTENSION_TYPE_TELEMETRY;
+        const FIELD_TYPE: u8 = 0x01;
+
+        fn from_bytes(bytes: &[u8]) -> Result<Tlv, ParserError> {
+            Tlv::decode(bytes).map_err(Into::into)
+        }
+        fn to_bytes(&self) -> Result<Vec<u8>, ParserError> {
+            self.to_tlv()?.encode().map_err(Into::into)
+        }
+        fn from_tlv(tlv: &Tlv) -> Result<Self, ParserError> {
+            tlv.value
+                .first()
+                .map(|speed| FanSpeed(*speed))
+                .ok_or(ParserError::BadPayloadSize)
+        }
+        fn to_tlv(&self) -> Result<Tlv, ParserError> {
+            Ok(Tlv::new(
+                Self::EXTENSION_TYPE,
+                Self::FIELD_TYPE,
+                vec![self.0],
+            ))
+        }
+    }
+
+    fn registry() -> ExtensionRegistry {
+        let mut registry = ExtensionRegistry::new();
+        registry
+            .register_extension(EXTENSION_TYPE_TELEMETRY, "Telemetry")
+            .unwrap();
+        registry
+            .register_message(
+                EXTENSION_TYPE_TELEMETRY,
+                0x00,
+                "DeviceTelemetry",
+                decode_device_telemetry,
+            )
+            .unwrap();
+        registry.register_tlv_field::<FanSpeed>("FanSpeed").unwrap();
+        registry
+    }
+
+    #[test]
+    fn registration_errors() {
+        let mut registry = registry();
+        assert_eq!(
+            registry.register_extension(EXTENSION_TYPE_EXTENSIONS_NEGOTIATION, "Negotiation"),
+            Err(RegistryError::BuiltInExtension(
+                EXTENSION_TYPE_EXTENSIONS_NEGOTIATION
+            ))
+        );
+        assert_eq!(
+            registry.register_extension(0xc000, "Channel"),
+            Err(RegistryError::InvalidExtensionType(0xc000))
+        );
+        assert_eq!(
+            registry.register_extension(EXTENSION_TYPE_TELEMETRY, "Telemetry"),
+            Err(RegistryError::DuplicateExtension(EXTENSION_TYPE_TELEMETRY))
+        );
+        assert_eq!(
+            registry.register_message(0x4001, 0x00, "Unknown", decode_device_telemetry),
+            Err(RegistryError::UnknownExtension(0x4001))
+        );
+        assert_eq!(
+            registry.register_tlv_field::<FanSpeed>("FanSpeed"),
+            Err(RegistryError::DuplicateTlvField {
+                extension_type: EXTENSION_TYPE_TELEMETRY,
+                field_type: 0x01
+            })
+        );
+        assert_eq!(
+            registry.get_message_name(EXTENSION_TYPE_TELEMETRY | CHANNEL_MSG_MASK, 0x00),
+            Some("DeviceTelemetry")
+        );
+        assert_eq!(
+            registry.get_tlv_field_name(EXTENSION_TYPE_TELEMETRY | CHANNEL_MSG_MASK, 0x01),
+            Some("FanSpeed")
+        );
+    }
+
+    #[test]
+    fn parse_custom_message_with_tlv_fields() {
+        let registry = registry();
+        let fan_speed = FanSpeed(80).to_bytes().unwrap();
+        let mut payload = vec![0x2a, 0x00];
+        payload.extend_from_slice(&fan_speed);
+        let header = Header::from_bytes(&[0x00, 0x40, 0x00, payload.len() as u8, 0, 0]).unwrap();
+
+        let (message, tlv_fields) = parse_message_frame_with_registry(
+            header,
+            &mut payload,
+            &[EXTENSION_TYPE_TELEMETRY],
+            &registry,
+        )
+        .unwrap();
+        assert_eq!(message.extension_type(), EXTENSION_TYPE_TELEMETRY);
+        assert_eq!(message.get_size(), 2);
+        assert_eq!(
+            format!("{message}"),
+            "ExtensionsMessage: DeviceTelemetry(temperature=42)"
+        );
+        let AnyMessage::Extensions(Extensions::Custom(custom)) = &message else {
+            panic!("unexpected message {message}");
+        };
+        assert_eq!(
+            custom.downcast_ref::<DeviceTelemetry>(),
+            Some(&DeviceTelemetry { temperature: 42 })
+        );
+
+        let tlv_fields = tlv_fields.unwrap();
+        assert_eq!(tlv_fields.len(), 1);
+        let field = registry.decode_tlv_field(&tlv_fields[0]).unwrap().unwrap();
+        assert_eq!(field.downcast_ref::<FanSpeed>(), Some(&FanSpeed(80)));
+
+        // Unregistered message types of registered extensions are rejected.
+        let header = Header::from_bytes(&[0x00, 0x40, 0x01, 0, 0, 0]).unwrap();
+        assert!(matches!(
+            registry.parse_message(header, &mut []),
+            Err(ParserError::UnexpectedMessage(0x01))
+        ));
+
+        // Other messages are parsed as without a registry.
+        let mut payload = vec![1, 0, 0, 0, 4];
+        payload.extend_from_slice(b"test");
+        let header = Header::from_bytes(&[0x00, 0x80, 0x18, 9, 0, 0]).unwrap();
+        let message = registry.parse_message(header, &mut payload).unwrap();
+        assert!(matches!(
+            message,
+            AnyMessage::Mining(Mining::CloseChannel(_))
+        ));
+    }
+}
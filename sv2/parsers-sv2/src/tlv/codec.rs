@@ -73,32 +73,7 @@ impl Tlv {
     /// Expects the buffer to start with a complete TLV (Type-Length-Value).
     /// Returns an error if the buffer is too short or decoding fails.
     pub fn decode(data: &[u8]) -> Result<Self, TlvError> {
-        if data.len() < TLV_HEADER_SIZE {
-            return Err(TlvError::BufferTooShort(data.len(), TLV_HEADER_SIZE));
-        }
-
-        // Decode Type (3 bytes)
-        let extension_type = u16::from_le_bytes([data[0], data[1]]);
-        let field_type = data[2];
-        let r#type = Type::new(extension_type, field_type);
-
-        // Decode Length (2 bytes at offset 3)
-        let length = u16::from_le_bytes([data[3], data[4]]);
-
-        // Check if we have enough data for the value
-        let total_size = TLV_HEADER_SIZE + length as usize;
-        if data.len() < total_size {
-            return Err(TlvError::BufferTooShort(data.len(), total_size));
-        }
-
-        // Extract value
-        let value = data[TLV_HEADER_SIZE..total_size].to_vec();
-
-        Ok(Self {
-            r#type,
-            length,
-            value,
-        })
+        TlvRef::decode(data).map(|tlv| tlv.to_tlv())
     }
 
     /// Encodes this TLV into bytes.
@@ -135,6 +110,63 @@ impl Tlv {
     }
 }
 
+/// A TLV field borrowed from the buffer it was decoded from.
+///
+/// Decoding a [`Tlv`] copies its value, a `TlvRef` only points to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TlvRef<'a> {
+    /// TLV type (extension_type + field_type)
+    pub r#type: Type,
+    /// Raw value bytes
+    pub value: &'a [u8],
+}
+
+impl<'a> TlvRef<'a> {
+    /// Decodes a TLV from a byte buffer, without copying its value.
+    ///
+    /// Expects the buffer to start with a complete TLV (Type-Length-Value).
+    /// Returns an error if the buffer is too short.
+    pub fn decode(data: &'a [u8]) -> Result<Self, TlvError> {
+        if data.len() < TLV_HEADER_SIZE {
+            return Err(TlvError::BufferTooShort(data.len(), TLV_HEADER_SIZE));
+        }
+
+        // Decode Type (3 bytes)
+        let extension_type = u16::from_le_bytes([data[0], data[1]]);
+        let field_type = data[2];
+        let r#type = Type::new(extension_type, field_type);
+
+        // Decode Length (2 bytes at offset 3)
+        let length = u16::from_le_bytes([data[3], data[4]]);
+
+        // Check if we have enough data for the value
+        let total_size = TLV_HEADER_SIZE + length as usize;
+        if data.len() < total_size {
+            return Err(TlvError::BufferTooShort(data.len(), total_size));
+        }
+
+        Ok(Self {
+            r#type,
+            value: &data[TLV_HEADER_SIZE..total_size],
+        })
+    }
+
+    /// Returns the total encoded size of this TLV in bytes.
+    #[inline]
+    pub fn encoded_size(&self) -> usize {
+        TLV_HEADER_SIZE + self.value.len()
+    }
+
+    /// Copies this TLV into an owned [`Tlv`].
+    pub fn to_tlv(&self) -> Tlv {
+        Tlv::new(
+            self.r#type.extension_type,
+            self.r#type.field_type,
+            self.value.to_vec(),
+        )
+    }
+}
+
 impl fmt::Display for Tlv {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -225,6 +257,19 @@ mod tests {
         assert!(matches!(result, Err(TlvError::BufferTooShort(10, 15))));
     }
 
+    #[test]
+    fn test_tlv_ref_decode() {
+        let tlv = Tlv::new(0x0002, 0x01, b"Worker_001".to_vec());
+        let mut encoded = tlv.encode().unwrap();
+        encoded.extend_from_slice(b"next");
+
+        let decoded = TlvRef::decode(&encoded).unwrap();
+        assert_eq!(decoded.r#type, tlv.r#type);
+        assert_eq!(decoded.value, b"Worker_001");
+        assert_eq!(decoded.encoded_size(), 15);
+        assert_eq!(decoded.to_tlv(), tlv);
+    }
+
     #[test]
     fn test_tlv_is_valid() {
         let valid_tlv = Tlv::new(0x0002, 0x01, b"test".to_vec());
@@ -18,6 +18,16 @@ pub enum TlvError {
     /// Contains the underlying binary_sv2::Error.
     DecodingError(binary_sv2::Error),
 
+    /// TLV value is longer than the maximum length of its field type.
+    ///
+    /// Contains (actual_length, maximum_length).
+    ValueTooLong(usize, usize),
+
+    /// TLV extension_type and field_type do not match the expected field type.
+    ///
+    /// Contains the (extension_type, field_type) found.
+    UnexpectedType(u16, u8),
+
     /// Failed to construct a StandardSv2Frame from bytes.
     ///
     /// Contains the error code returned by `Sv2Frame::from_bytes`.
@@ -34,6 +44,16 @@ impl fmt::Display for TlvError {
             ),
             TlvError::EncodingError(err) => write!(f, "Failed to encode TLV data: {:?}", err),
             TlvError::DecodingError(err) => write!(f, "Failed to decode TLV data: {:?}", err),
+            TlvError::ValueTooLong(actual, maximum) => write!(
+                f,
+                "TLV value too long: {} bytes, maximum {} bytes",
+                actual, maximum
+            ),
+            TlvError::UnexpectedType(extension_type, field_type) => write!(
+                f,
+                "Unexpected TLV type: extension_type 0x{:04x}, field_type 0x{:02x}",
+                extension_type, field_type
+            ),
             TlvError::FrameConstructionFailed(code) => {
                 write!(
                     f,
@@ -1,6 +1,6 @@
 //! TLV iterator for parsing byte streams.
 
-use super::{Tlv, TlvError, TLV_HEADER_SIZE};
+use super::{Tlv, TlvError, TlvRef, TLV_HEADER_SIZE};
 
 /// Iterator over TLV fields in a byte buffer.
 ///
@@ -42,6 +42,65 @@ impl<'a> Iterator for TlvIter<'a> {
     }
 }
 
+/// Iterator over borrowed TLV fields in a byte buffer.
+///
+/// Same as [`TlvIter`], without copying the TLV values. Stops iteration on the first decode
+/// error or when no more complete TLV headers can be read.
+///
+/// The negotiated extension types of [`TlvRefIter::with_extensions`] may be borrowed for a
+/// shorter lifetime than the data.
+#[derive(Debug, Clone)]
+pub struct TlvRefIter<'a, 'e> {
+    data: &'a [u8],
+    offset: usize,
+    negotiated: Option<&'e [u16]>,
+}
+
+impl<'a, 'e> TlvRefIter<'a, 'e> {
+    /// Creates a new borrowed TLV iterator over the provided data.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            offset: 0,
+            negotiated: None,
+        }
+    }
+
+    /// Only yields the TLVs of the negotiated extension types.
+    pub fn with_extensions(mut self, negotiated: &'e [u16]) -> Self {
+        self.negotiated = Some(negotiated);
+        self
+    }
+}
+
+impl<'a> Iterator for TlvRefIter<'a, '_> {
+    type Item = Result<TlvRef<'a>, TlvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Check if we have enough data for a TLV header
+        while self.offset + TLV_HEADER_SIZE <= self.data.len() {
+            match TlvRef::decode(&self.data[self.offset..]) {
+                Ok(tlv) => {
+                    self.offset += tlv.encoded_size();
+                    if self
+                        .negotiated
+                        .is_some_and(|negotiated| !negotiated.contains(&tlv.r#type.extension_type))
+                    {
+                        continue;
+                    }
+                    return Some(Ok(tlv));
+                }
+                Err(e) => {
+                    // Stop iteration on error by advancing to end
+                    self.offset = self.data.len();
+                    return Some(Err(e));
+                }
+            }
+        }
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,6 +124,25 @@ mod tests {
         assert!(iter.next().is_none());
     }
 
+    #[test]
+    fn test_tlv_ref_iter_for_extensions() {
+        let tlv1 = Tlv::new(0x0002, 0x01, b"worker1".to_vec());
+        let tlv2 = Tlv::new(0x0003, 0x01, b"data".to_vec());
+        let tlv3 = Tlv::new(0x0002, 0x02, b"more".to_vec());
+
+        let mut buffer = Vec::new();
+        for tlv in [&tlv1, &tlv2, &tlv3] {
+            buffer.extend_from_slice(&tlv.encode().unwrap());
+        }
+
+        let tlvs: Vec<Tlv> = TlvRefIter::new(&buffer)
+            .with_extensions(&[0x0002])
+            .map(|tlv| tlv.unwrap().to_tlv())
+            .collect();
+        assert_eq!(tlvs, [tlv1, tlv3]);
+        assert_eq!(TlvRefIter::new(&buffer).count(), 3);
+    }
+
     #[test]
     fn test_tlv_iter_empty() {
         let buffer: Vec<u8> = Vec::new();
@@ -3,7 +3,7 @@
 //! This module provides generic functions for working with TLV fields in Stratum V2 extensions.
 //! TLV fields are used to extend base protocol messages with optional extension-specific data.
 
-pub use crate::tlv::codec::Tlv;
+pub use crate::tlv::codec::{Tlv, TlvRef};
 use alloc::vec::Vec;
 
 extern crate alloc;
@@ -14,12 +14,23 @@ mod iter;
 mod list;
 
 pub use error::TlvError;
+pub use iter::TlvRefIter;
 pub use list::TlvList;
 
 /// TLV header size in bytes (3 bytes Type + 2 bytes Length)
 pub const TLV_HEADER_SIZE: usize = 5;
 
 /// Trait for types that can be encoded/decoded as TLV fields.
+///
+/// Fields whose value has the `binary_sv2` encoding implement it with `#[derive(TlvField)]`:
+///
+/// ```ignore
+/// #[derive(Clone, Deserialize, Serialize, TlvField)]
+/// #[tlv(extension_type = 0x4000, field_type = 0x01, max_length = 64)]
+/// struct DeviceName<'decoder> {
+///     name: Str0255<'decoder>,
+/// }
+/// ```
 pub trait TlvField: Sized {
     /// The extension type this field belongs to.
     const EXTENSION_TYPE: u16;
@@ -48,3 +59,77 @@ pub trait TlvField: Sized {
     /// This creates a TLV with the correct extension_type and field_type for this field.
     fn to_tlv(&self) -> Result<Tlv, crate::ParserError>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParserError, TlvField};
+    use alloc::{string::ToString, vec};
+    use binary_sv2::{self, Deserialize, Serialize, Str0255};
+    use core::convert::TryInto;
+
+    const MAX_DEVICE_NAME_LENGTH: usize = 9;
+
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize, TlvField)]
+    #[tlv(extension_type = 0x4000, field_type = 0x01, max_length = MAX_DEVICE_NAME_LENGTH)]
+    struct DeviceName<'decoder> {
+        name: Str0255<'decoder>,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize, TlvField)]
+    #[tlv(extension_type = 0x4000, field_type = 0x02)]
+    struct Temperature {
+        celsius: u16,
+        fan_speed: u8,
+    }
+
+    fn device_name(name: &str) -> DeviceName<'static> {
+        DeviceName {
+            name: name.to_string().try_into().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_derived_tlv_field_round_trip() {
+        let temperature = Temperature {
+            celsius: 75,
+            fan_speed: 80,
+        };
+        // `Serialize` and `Deserialize` also have `to_bytes` and `from_bytes` methods
+        let bytes = TlvField::to_bytes(&temperature).unwrap();
+        assert_eq!(bytes, vec![0x00, 0x40, 0x02, 3, 0, 75, 0, 80]);
+        let tlv = <Temperature as TlvField>::from_bytes(&bytes).unwrap();
+        assert_eq!(Temperature::from_tlv(&tlv).unwrap(), temperature);
+
+        let name = device_name("antminer");
+        let tlv = name.to_tlv().unwrap();
+        assert_eq!(tlv.value, b"\x08antminer".to_vec());
+        assert_eq!(DeviceName::from_tlv(&tlv).unwrap(), name);
+    }
+
+    #[test]
+    fn test_derived_tlv_field_limits() {
+        assert!(matches!(
+            device_name("antminer1").to_tlv(),
+            Err(ParserError::TlvError(TlvError::ValueTooLong(10, 9)))
+        ));
+        let tlv = Tlv::new(0x4000, 0x01, b"\x09antminer1".to_vec());
+        assert!(matches!(
+            DeviceName::from_tlv(&tlv),
+            Err(ParserError::TlvError(TlvError::ValueTooLong(10, 9)))
+        ));
+
+        let tlv = Tlv::new(0x4000, 0x01, vec![75, 0, 80]);
+        assert!(matches!(
+            Temperature::from_tlv(&tlv),
+            Err(ParserError::TlvError(TlvError::UnexpectedType(
+                0x4000, 0x01
+            )))
+        ));
+        let tlv = Tlv::new(0x4000, 0x02, vec![75]);
+        assert!(matches!(
+            Temperature::from_tlv(&tlv),
+            Err(ParserError::TlvError(TlvError::DecodingError(_)))
+        ));
+    }
+}
@@ -4,8 +4,10 @@
 //! defined in the `extensions_sv2` crate, along with extension-specific error types.
 
 mod error;
+mod share_reject_detail;
 
 pub use error::{ExtensionError, UserIdentityError};
+pub use share_reject_detail::{share_reject_detail_from_tlvs, share_reject_detail_to_tlvs};
 
 use super::{Tlv, TlvField};
 use crate::ParserError;
@@ -0,0 +1,186 @@
+//! TLV fields of the Share Reject Detail extension (0x4003).
+//!
+//! Each field of a [`ShareRejectDetail`] is a TLV field of its own, derived with
+//! `#[derive(TlvField)]` on a private struct holding its value.
+
+use crate::{ParserError, Tlv, TlvField};
+use binary_sv2::{self, Deserialize, Serialize, U256};
+use extensions_sv2::{
+    JobState, RejectReason, ShareRejectDetail, EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+    TLV_FIELD_TYPE_EXPECTED_TARGET, TLV_FIELD_TYPE_JOB_STATE, TLV_FIELD_TYPE_REJECT_REASON,
+    TLV_FIELD_TYPE_SHARE_HASH,
+};
+
+extern crate alloc;
+use alloc::vec::Vec;
+
+#[derive(Clone, Deserialize, Serialize, TlvField)]
+#[tlv(
+    extension_type = EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+    field_type = TLV_FIELD_TYPE_REJECT_REASON,
+    max_length = 1
+)]
+struct RejectReasonField {
+    reason: u8,
+}
+
+#[derive(Clone, Deserialize, Serialize, TlvField)]
+#[tlv(
+    extension_type = EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+    field_type = TLV_FIELD_TYPE_EXPECTED_TARGET,
+    max_length = 32
+)]
+struct ExpectedTargetField<'decoder> {
+    target: U256<'decoder>,
+}
+
+#[derive(Clone, Deserialize, Serialize, TlvField)]
+#[tlv(
+    extension_type = EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+    field_type = TLV_FIELD_TYPE_JOB_STATE,
+    max_length = 1
+)]
+struct JobStateField {
+    state: u8,
+}
+
+#[derive(Clone, Deserialize, Serialize, TlvField)]
+#[tlv(
+    extension_type = EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+    field_type = TLV_FIELD_TYPE_SHARE_HASH,
+    max_length = 32
+)]
+struct ShareHashField<'decoder> {
+    hash: U256<'decoder>,
+}
+
+fn u256_to_bytes(value: &U256<'_>) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    bytes.copy_from_slice(value.inner_as_ref());
+    bytes
+}
+
+/// Encodes a [`ShareRejectDetail`] as the TLV fields of the Share Reject Detail extension, to be
+/// appended to `SubmitShares.Error`, the reason first.
+pub fn share_reject_detail_to_tlvs(detail: &ShareRejectDetail) -> Result<Vec<Tlv>, ParserError> {
+    let mut tlvs = Vec::with_capacity(4);
+    let reason = RejectReasonField {
+        reason: detail.reason.to_u8(),
+    };
+    tlvs.push(reason.to_tlv()?);
+    if let Some(expected_target) = detail.expected_target {
+        let target = ExpectedTargetField {
+            target: expected_target.into(),
+        };
+        tlvs.push(target.to_tlv()?);
+    }
+    if let Some(job_state) = detail.job_state {
+        let state = JobStateField {
+            state: job_state.to_u8(),
+        };
+        tlvs.push(state.to_tlv()?);
+    }
+    if let Some(share_hash) = detail.share_hash {
+        let hash = ShareHashField {
+            hash: share_hash.into(),
+        };
+        tlvs.push(hash.to_tlv()?);
+    }
+    Ok(tlvs)
+}
+
+/// Decodes a [`ShareRejectDetail`] from the TLV fields of a `SubmitShares.Error`.
+///
+/// TLVs of other extensions, and unknown fields of this one, are ignored. Returns `Ok(None)` if
+/// the server sent no reason.
+pub fn share_reject_detail_from_tlvs(
+    tlvs: &[Tlv],
+) -> Result<Option<ShareRejectDetail>, ParserError> {
+    let mut reason = None;
+    let mut detail = ShareRejectDetail::new(RejectReason::Invalid);
+    for tlv in tlvs
+        .iter()
+        .filter(|tlv| tlv.r#type.extension_type == EXTENSION_TYPE_SHARE_REJECT_DETAIL)
+    {
+        match tlv.r#type.field_type {
+            TLV_FIELD_TYPE_REJECT_REASON => {
+                let field = RejectReasonField::from_tlv(tlv)?;
+                reason = Some(RejectReason::from_u8(field.reason));
+            }
+            TLV_FIELD_TYPE_EXPECTED_TARGET => {
+                let field = ExpectedTargetField::from_tlv(tlv)?;
+                detail.expected_target = Some(u256_to_bytes(&field.target));
+            }
+            TLV_FIELD_TYPE_JOB_STATE => {
+                let field = JobStateField::from_tlv(tlv)?;
+                detail.job_state = Some(JobState::from_u8(field.state));
+            }
+            TLV_FIELD_TYPE_SHARE_HASH => {
+                let field = ShareHashField::from_tlv(tlv)?;
+                detail.share_hash = Some(u256_to_bytes(&field.hash));
+            }
+            _ => {}
+        }
+    }
+    Ok(reason.map(|reason| ShareRejectDetail { reason, ..detail }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TlvError;
+    use alloc::vec;
+
+    #[test]
+    fn test_share_reject_detail_tlvs_round_trip() {
+        let detail = ShareRejectDetail::new(RejectReason::Stale)
+            .with_expected_target([0xff; 32])
+            .with_job_state(JobState::Stale)
+            .with_share_hash([1; 32]);
+        let mut tlvs = share_reject_detail_to_tlvs(&detail).unwrap();
+        assert_eq!(tlvs.len(), 4);
+        assert_eq!(
+            tlvs[0],
+            Tlv::new(
+                EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+                TLV_FIELD_TYPE_REJECT_REASON,
+                vec![0x02]
+            )
+        );
+        assert_eq!(tlvs[1].value, vec![0xff; 32]);
+        tlvs.push(Tlv::new(0x0002, 0x01, b"Worker_001".to_vec()));
+
+        assert_eq!(share_reject_detail_from_tlvs(&tlvs).unwrap(), Some(detail));
+        assert_eq!(share_reject_detail_from_tlvs(&tlvs[1..]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_share_reject_detail_from_invalid_tlvs() {
+        let tlvs = [
+            Tlv::new(
+                EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+                TLV_FIELD_TYPE_REJECT_REASON,
+                vec![0x05],
+            ),
+            Tlv::new(
+                EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+                TLV_FIELD_TYPE_EXPECTED_TARGET,
+                vec![0xff; 4],
+            ),
+        ];
+        assert!(matches!(
+            share_reject_detail_from_tlvs(&tlvs),
+            Err(ParserError::TlvError(TlvError::DecodingError(_)))
+        ));
+
+        let tlvs = [Tlv::new(
+            EXTENSION_TYPE_SHARE_REJECT_DETAIL,
+            TLV_FIELD_TYPE_JOB_STATE,
+            vec![0x01, 0x02],
+        )];
+        assert!(matches!(
+            share_reject_detail_from_tlvs(&tlvs),
+            Err(ParserError::TlvError(TlvError::ValueTooLong(2, 1)))
+        ));
+    }
+}
@@ -1,5 +1,5 @@
 use alloc::{fmt, vec::Vec};
-use binary_sv2::{self, Deserialize, Serialize};
+use binary_sv2::{self, Deserialize, Serialize, ValidateFields};
 use core::convert::TryInto;
 
 /// Message used by an upstream role for announcing a mining channel endpoint change.
@@ -11,7 +11,8 @@ use core::convert::TryInto;
 /// When a downstream receives such a message, any extension state (including version and extension
 /// support) must be reset and renegotiated.
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChannelEndpointChanged {
     /// Unique identifier of the channel that has changed its endpoint.
     pub channel_id: u32,
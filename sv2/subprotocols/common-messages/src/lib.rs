@@ -29,7 +29,7 @@ pub use channel_endpoint_changed::ChannelEndpointChanged;
 pub use reconnect::Reconnect;
 pub use setup_connection::{
     has_requires_std_job, has_version_rolling, has_work_selection, Protocol, SetupConnection,
-    SetupConnectionError, SetupConnectionSuccess,
+    SetupConnectionError, SetupConnectionErrorCode, SetupConnectionSuccess,
 };
 
 // Discriminants for Stratum V2 (sub)protocols
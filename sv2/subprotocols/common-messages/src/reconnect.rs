@@ -1,5 +1,5 @@
 use alloc::{fmt, vec::Vec};
-use binary_sv2::{self, Deserialize, Serialize, Str0255};
+use binary_sv2::{self, Deserialize, Serialize, Str0255, ValidateFields};
 use core::convert::TryInto;
 
 /// Message used by upstream to redirect downstream connection(s) to a new host.
@@ -12,9 +12,11 @@ use core::convert::TryInto;
 /// and thus cannot be used to reconnect to a different pool. This ensures that an attacker will
 /// not be able to redirect hashrate to an arbitrary server in case the pool server get compromised
 /// and instructed to send reconnects to a new location.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Reconnect<'decoder> {
     /// When empty, downstream node should attempt to reconnect to current pool host.
+    #[cfg_attr(feature = "serde", serde(with = "binary_sv2::serde_str"))]
     pub new_host: Str0255<'decoder>,
     /// When 0, downstream node should attempt to reconnect to current pool host.
     pub new_port: u16,
@@ -2,11 +2,15 @@ use crate::{
     SV2_JOB_DECLARATION_PROTOCOL_DISCRIMINANT, SV2_MINING_PROTOCOL_DISCRIMINANT,
     SV2_TEMPLATE_DISTRIBUTION_PROTOCOL_DISCRIMINANT,
 };
-use alloc::{fmt, vec::Vec};
+use alloc::{
+    fmt,
+    string::{String, ToString},
+    vec::Vec,
+};
 use binary_sv2::{
     self,
     decodable::{DecodableField, FieldMarker},
-    Deserialize, GetSize, Serialize, Str0255,
+    Deserialize, FieldViolation, GetSize, Serialize, Str0255, ValidateFields,
 };
 use core::convert::{TryFrom, TryInto};
 
@@ -23,7 +27,8 @@ use core::convert::{TryFrom, TryInto};
 ///
 /// A valid response to this message from the upstream role can either be [`SetupConnectionSuccess`]
 /// or [`SetupConnectionError`] message.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetupConnection<'decoder> {
     /// Protocol to be used for the connection.
     pub protocol: Protocol,
@@ -40,16 +45,21 @@ pub struct SetupConnection<'decoder> {
     /// Each [`SetupConnection::protocol`] value has it's own flags.
     pub flags: u32,
     /// ASCII representation of the connection hostname or IP address.
+    #[cfg_attr(feature = "serde", serde(with = "binary_sv2::serde_str"))]
     pub endpoint_host: Str0255<'decoder>,
     /// Connection port value.
     pub endpoint_port: u16,
     /// Device vendor name.
+    #[cfg_attr(feature = "serde", serde(with = "binary_sv2::serde_str"))]
     pub vendor: Str0255<'decoder>,
     /// Device hardware version.
+    #[cfg_attr(feature = "serde", serde(with = "binary_sv2::serde_str"))]
     pub hardware_version: Str0255<'decoder>,
     /// Device firmware version.
+    #[cfg_attr(feature = "serde", serde(with = "binary_sv2::serde_str"))]
     pub firmware: Str0255<'decoder>,
     /// Device identifier.
+    #[cfg_attr(feature = "serde", serde(with = "binary_sv2::serde_str"))]
     pub device_id: Str0255<'decoder>,
 }
 
@@ -200,7 +210,8 @@ pub fn has_work_selection(flags: u32) -> bool {
 /// Message used by an upstream role to accept a connection setup request from a downstream role.
 ///
 /// This message is sent in response to a [`SetupConnection`] message.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq, Eq, Copy)]
 
 pub struct SetupConnectionSuccess {
     /// Selected version based on the [`SetupConnection::min_version`] and
@@ -239,7 +250,8 @@ impl fmt::Display for SetupConnectionSuccess {
 /// The upstream must provide the full set of flags which it does not support in each
 /// [`SetupConnectionError`] message and must consistently support the same set of flags across all
 /// servers on the same hostname and port number.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetupConnectionError<'decoder> {
     /// Unsupported feature flags.
     ///
@@ -252,6 +264,7 @@ pub struct SetupConnectionError<'decoder> {
     /// - unsupported-feature-flags
     /// - unsupported-protocol
     /// - protocol-version-mismatch
+    #[cfg_attr(feature = "serde", serde(with = "binary_sv2::serde_str"))]
     pub error_code: Str0255<'decoder>,
 }
 
@@ -266,8 +279,72 @@ impl fmt::Display for SetupConnectionError<'_> {
     }
 }
 
+impl SetupConnectionError<'_> {
+    /// Returns the error code of the message, typed.
+    pub fn get_error_code(&self) -> SetupConnectionErrorCode {
+        SetupConnectionErrorCode::from(&self.error_code)
+    }
+}
+
+/// Error codes of [`SetupConnectionError`] defined by the spec.
+///
+/// Codes not defined by the spec are kept as [`SetupConnectionErrorCode::Unknown`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SetupConnectionErrorCode {
+    /// `unsupported-feature-flags`
+    UnsupportedFeatureFlags,
+    /// `unsupported-protocol`
+    UnsupportedProtocol,
+    /// `protocol-version-mismatch`
+    ProtocolVersionMismatch,
+    /// A code not defined by the spec.
+    Unknown(String),
+}
+
+impl fmt::Display for SetupConnectionErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetupConnectionErrorCode::UnsupportedFeatureFlags => {
+                write!(f, "unsupported-feature-flags")
+            }
+            SetupConnectionErrorCode::UnsupportedProtocol => write!(f, "unsupported-protocol"),
+            SetupConnectionErrorCode::ProtocolVersionMismatch => {
+                write!(f, "protocol-version-mismatch")
+            }
+            SetupConnectionErrorCode::Unknown(error_code) => write!(f, "{error_code}"),
+        }
+    }
+}
+
+impl From<&str> for SetupConnectionErrorCode {
+    fn from(error_code: &str) -> Self {
+        match error_code {
+            "unsupported-feature-flags" => SetupConnectionErrorCode::UnsupportedFeatureFlags,
+            "unsupported-protocol" => SetupConnectionErrorCode::UnsupportedProtocol,
+            "protocol-version-mismatch" => SetupConnectionErrorCode::ProtocolVersionMismatch,
+            _ => SetupConnectionErrorCode::Unknown(error_code.to_string()),
+        }
+    }
+}
+
+impl From<&Str0255<'_>> for SetupConnectionErrorCode {
+    fn from(error_code: &Str0255<'_>) -> Self {
+        SetupConnectionErrorCode::from(error_code.as_utf8_or_hex().as_str())
+    }
+}
+
+impl TryFrom<SetupConnectionErrorCode> for Str0255<'static> {
+    type Error = binary_sv2::Error;
+
+    /// Fails if the code is longer than 255 bytes.
+    fn try_from(error_code: SetupConnectionErrorCode) -> Result<Self, Self::Error> {
+        error_code.to_string().try_into()
+    }
+}
+
 /// This enum has a list of the different Stratum V2 subprotocols.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 #[allow(clippy::enum_variant_names)]
 pub enum Protocol {
@@ -324,6 +401,10 @@ impl GetSize for Protocol {
     }
 }
 
+impl ValidateFields for Protocol {
+    fn collect_violations(&self, _violations: &mut Vec<FieldViolation>) {}
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -391,6 +472,46 @@ mod test {
         assert!(!has_work_selection(flags));
     }
 
+    #[test]
+    fn test_validate_fields() {
+        let mut setup_connection = create_setup_connection();
+        assert!(setup_connection.validate_fields().is_ok());
+
+        setup_connection.vendor = Str0255::Owned(vec![b'x'; 256]);
+        assert_eq!(
+            setup_connection.validate_fields(),
+            Err(vec![FieldViolation::TooLong {
+                field: "vendor".to_string(),
+                length: 256,
+                max_length: 255,
+            }])
+        );
+    }
+
+    #[test]
+    fn test_setup_connection_error_code() {
+        let error = SetupConnectionError {
+            flags: 0,
+            error_code: SetupConnectionErrorCode::ProtocolVersionMismatch
+                .try_into()
+                .unwrap(),
+        };
+        assert_eq!(
+            error.get_error_code(),
+            SetupConnectionErrorCode::ProtocolVersionMismatch
+        );
+
+        let error_code: Str0255 = "protocol-version-mismatch "
+            .to_string()
+            .into_bytes()
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            SetupConnectionErrorCode::from(&error_code),
+            SetupConnectionErrorCode::Unknown("protocol-version-mismatch ".to_string())
+        );
+    }
+
     fn create_setup_connection() -> SetupConnection<'static> {
         SetupConnection {
             protocol: Protocol::MiningProtocol,
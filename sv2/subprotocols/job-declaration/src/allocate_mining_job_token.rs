@@ -1,14 +1,16 @@
 use alloc::{fmt, vec::Vec};
-use binary_sv2::{self, Deserialize, Serialize, Str0255, B0255, B064K};
+use binary_sv2::{self, Deserialize, Serialize, Str0255, ValidateFields, B0255, B064K};
 use core::convert::TryInto;
 
 /// Message used by JDC to request an identifier for a future mining job from JDS.
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct AllocateMiningJobToken<'decoder> {
     /// Unconstrained sequence of bytes. Whatever is needed by the JDS to
     /// identify/authenticate the client. Additional restrictions can be imposed by the
     /// JDS. It is highly recommended that UTF-8 encoding is used.
+    #[cfg_attr(feature = "serde", serde(with = "binary_sv2::serde_str"))]
     pub user_identifier: Str0255<'decoder>,
     /// A unique identifier for pairing the response/request.
     pub request_id: u32,
@@ -26,7 +28,8 @@ impl fmt::Display for AllocateMiningJobToken<'_> {
 }
 
 /// Message used by JDS to accept [`AllocateMiningJobToken`] message.
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct AllocateMiningJobTokenSuccess<'decoder> {
     /// A unique identifier for pairing the response/request.
@@ -1,5 +1,11 @@
-use alloc::{fmt, vec::Vec};
-use binary_sv2::{self, Deserialize, Seq064K, Serialize, Str0255, B0255, B064K, U256};
+use alloc::{
+    fmt,
+    string::{String, ToString},
+    vec::Vec,
+};
+use binary_sv2::{
+    self, Deserialize, Seq064K, Serialize, Str0255, ValidateFields, B0255, B064K, U256,
+};
 use core::convert::TryInto;
 
 /// Message used by JDC to proposes a selected set of transactions to JDS they wish to
@@ -8,7 +14,8 @@ use core::convert::TryInto;
 /// Used only under [`Full Template`] mode.
 ///
 /// [`Full Template`]: https://github.com/stratum-mining/sv2-spec/blob/main/06-Job-Declaration-Protocol.md#632-full-template-mode
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct DeclareMiningJob<'decoder> {
     /// A unique identifier for this request.
@@ -56,7 +63,8 @@ impl fmt::Display for DeclareMiningJob<'_> {
 /// before making this commitment.
 ///
 /// [`Full Template`]: https://github.com/stratum-mining/sv2-spec/blob/main/06-Job-Declaration-Protocol.md#632-full-template-mode
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct DeclareMiningJobSuccess<'decoder> {
     /// A unique identifier for this request.
@@ -85,7 +93,8 @@ impl fmt::Display for DeclareMiningJobSuccess<'_> {
 ///
 /// Downstream should consider this as a trigger to fallback into some other Pool/JDS or solo
 /// mining.
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct DeclareMiningJobError<'decoder> {
     /// The unique identifier of the request.
@@ -96,6 +105,7 @@ pub struct DeclareMiningJobError<'decoder> {
     ///
     /// - invalid-mining-job-token
     /// - invalid-job-param-value-{DeclareMiningJob::field}
+    #[cfg_attr(feature = "serde", serde(with = "binary_sv2::serde_str"))]
     pub error_code: Str0255<'decoder>,
     /// Optional details about the error.
     pub error_details: B064K<'decoder>,
@@ -112,3 +122,106 @@ impl fmt::Display for DeclareMiningJobError<'_> {
         )
     }
 }
+
+impl DeclareMiningJobError<'_> {
+    /// Returns the error code of the message, typed.
+    pub fn get_error_code(&self) -> DeclareMiningJobErrorCode {
+        DeclareMiningJobErrorCode::from(&self.error_code)
+    }
+}
+
+/// Error codes of [`DeclareMiningJobError`] defined by the spec.
+///
+/// Codes not defined by the spec are kept as [`DeclareMiningJobErrorCode::Unknown`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeclareMiningJobErrorCode {
+    /// `invalid-mining-job-token`
+    InvalidMiningJobToken,
+    /// `invalid-job-param-value-{field_name}`, with the name of the offending field.
+    InvalidJobParamValue(String),
+    /// A code not defined by the spec.
+    Unknown(String),
+}
+
+impl fmt::Display for DeclareMiningJobErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeclareMiningJobErrorCode::InvalidMiningJobToken => {
+                write!(f, "invalid-mining-job-token")
+            }
+            DeclareMiningJobErrorCode::InvalidJobParamValue(field) => {
+                write!(f, "invalid-job-param-value-{field}")
+            }
+            DeclareMiningJobErrorCode::Unknown(error_code) => write!(f, "{error_code}"),
+        }
+    }
+}
+
+impl From<&str> for DeclareMiningJobErrorCode {
+    fn from(error_code: &str) -> Self {
+        match error_code {
+            "invalid-mining-job-token" => DeclareMiningJobErrorCode::InvalidMiningJobToken,
+            _ => match error_code.strip_prefix("invalid-job-param-value-") {
+                Some(field) => DeclareMiningJobErrorCode::InvalidJobParamValue(field.to_string()),
+                None => DeclareMiningJobErrorCode::Unknown(error_code.to_string()),
+            },
+        }
+    }
+}
+
+impl From<&Str0255<'_>> for DeclareMiningJobErrorCode {
+    fn from(error_code: &Str0255<'_>) -> Self {
+        DeclareMiningJobErrorCode::from(error_code.as_utf8_or_hex().as_str())
+    }
+}
+
+impl TryFrom<DeclareMiningJobErrorCode> for Str0255<'static> {
+    type Error = binary_sv2::Error;
+
+    /// Fails if the code is longer than 255 bytes.
+    fn try_from(error_code: DeclareMiningJobErrorCode) -> Result<Self, Self::Error> {
+        error_code.to_string().try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_declare_mining_job_error_code_round_trip() {
+        for error_code in [
+            DeclareMiningJobErrorCode::InvalidMiningJobToken,
+            DeclareMiningJobErrorCode::InvalidJobParamValue("coinbase_tx_prefix".to_string()),
+            DeclareMiningJobErrorCode::Unknown("unknown-job".to_string()),
+        ] {
+            let error = DeclareMiningJobError {
+                request_id: 1,
+                error_code: error_code.clone().try_into().unwrap(),
+                error_details: Vec::new().try_into().unwrap(),
+            };
+            assert_eq!(error.get_error_code(), error_code);
+            assert_eq!(
+                DeclareMiningJobErrorCode::from(error_code.to_string().as_str()),
+                error_code
+            );
+        }
+    }
+
+    #[test]
+    fn test_declare_mining_job_error_code_invalid_job_param_value() {
+        assert_eq!(
+            DeclareMiningJobErrorCode::from("invalid-job-param-value-version"),
+            DeclareMiningJobErrorCode::InvalidJobParamValue("version".to_string())
+        );
+        // the field name is whatever follows the prefix, even if empty
+        assert_eq!(
+            DeclareMiningJobErrorCode::from("invalid-job-param-value-"),
+            DeclareMiningJobErrorCode::InvalidJobParamValue(String::new())
+        );
+        assert_eq!(
+            DeclareMiningJobErrorCode::from("invalid-job-param-value"),
+            DeclareMiningJobErrorCode::Unknown("invalid-job-param-value".to_string())
+        );
+    }
+}
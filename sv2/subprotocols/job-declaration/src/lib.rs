@@ -20,7 +20,9 @@ mod provide_missing_transactions;
 mod push_solution;
 
 pub use allocate_mining_job_token::{AllocateMiningJobToken, AllocateMiningJobTokenSuccess};
-pub use declare_mining_job::{DeclareMiningJob, DeclareMiningJobError, DeclareMiningJobSuccess};
+pub use declare_mining_job::{
+    DeclareMiningJob, DeclareMiningJobError, DeclareMiningJobErrorCode, DeclareMiningJobSuccess,
+};
 pub use provide_missing_transactions::{
     ProvideMissingTransactions, ProvideMissingTransactionsSuccess,
 };
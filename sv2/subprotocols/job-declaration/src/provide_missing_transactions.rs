@@ -1,5 +1,5 @@
 use alloc::{fmt, vec::Vec};
-use binary_sv2::{self, Deserialize, Seq064K, Serialize, B016M};
+use binary_sv2::{self, Deserialize, Seq064K, Serialize, ValidateFields, B016M};
 use core::convert::TryInto;
 
 /// Message used by the JDS to ask for transactions that it did not recognize from
@@ -15,7 +15,8 @@ use core::convert::TryInto;
 /// Used only under [`Full Template`] mode.
 ///
 /// [`Full Template`]: https://github.com/stratum-mining/sv2-spec/blob/main/06-Job-Declaration-Protocol.md#632-full-template-mode
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct ProvideMissingTransactions<'decoder> {
     /// Unique Identifier.
@@ -40,7 +41,8 @@ impl fmt::Display for ProvideMissingTransactions<'_> {
 
 /// Message used by JDC to accept [`ProvideMissingTransactions`] message and provide the full
 /// list of transactions in the order they were requested by [`ProvideMissingTransactions`].
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct ProvideMissingTransactionsSuccess<'decoder> {
     /// Unique Identifier.
@@ -1,5 +1,5 @@
 use alloc::vec::Vec;
-use binary_sv2::{self, Deserialize, Serialize, B032, U256};
+use binary_sv2::{self, Deserialize, Serialize, ValidateFields, B032, U256};
 use core::{convert::TryInto, fmt};
 
 /// Message used by JDC to push a solution to JDS as soon as it finds a new valid block.
@@ -12,7 +12,8 @@ use core::{convert::TryInto, fmt};
 /// Used only under [`Full Template`] mode.
 ///
 /// [`Full Template`]: https://github.com/stratum-mining/sv2-spec/blob/main/06-Job-Declaration-Protocol.md#632-full-template-mode
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct PushSolution<'decoder> {
     /// Full extranonce that forms a valid submission.
@@ -1,5 +1,5 @@
 use alloc::{fmt, vec::Vec};
-use binary_sv2::{self, Deserialize, Serialize, Str0255};
+use binary_sv2::{self, Deserialize, Serialize, Str0255, ValidateFields};
 use core::convert::TryInto;
 
 /// Message used by a downstream to close a mining channel.
@@ -8,11 +8,13 @@ use core::convert::TryInto;
 /// it for each open channel separately.
 ///
 /// Upon receiving this message, upstream **must** stop sending messages for the channel.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CloseChannel<'decoder> {
     /// Channel id of the channel to be closed.
     pub channel_id: u32,
     /// Reason for closing the channel.
+    #[cfg_attr(feature = "serde", serde(with = "binary_sv2::serde_str"))]
     pub reason_code: Str0255<'decoder>,
 }
 
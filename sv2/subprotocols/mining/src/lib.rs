@@ -46,19 +46,21 @@ use core::ops::Range;
 pub use new_mining_job::{NewExtendedMiningJob, NewMiningJob};
 pub use open_channel::{
     OpenExtendedMiningChannel, OpenExtendedMiningChannelSuccess, OpenMiningChannelError,
-    OpenStandardMiningChannel, OpenStandardMiningChannelSuccess,
+    OpenMiningChannelErrorCode, OpenStandardMiningChannel, OpenStandardMiningChannelSuccess,
 };
 pub use set_custom_mining_job::{
-    SetCustomMiningJob, SetCustomMiningJobError, SetCustomMiningJobSuccess,
+    SetCustomMiningJob, SetCustomMiningJobError, SetCustomMiningJobErrorCode,
+    SetCustomMiningJobSuccess,
 };
 pub use set_extranonce_prefix::SetExtranoncePrefix;
 pub use set_group_channel::SetGroupChannel;
 pub use set_new_prev_hash::SetNewPrevHash;
 pub use set_target::SetTarget;
 pub use submit_shares::{
-    SubmitSharesError, SubmitSharesExtended, SubmitSharesStandard, SubmitSharesSuccess,
+    SubmitSharesError, SubmitSharesErrorCode, SubmitSharesExtended, SubmitSharesStandard,
+    SubmitSharesSuccess,
 };
-pub use update_channel::{UpdateChannel, UpdateChannelError};
+pub use update_channel::{UpdateChannel, UpdateChannelError, UpdateChannelErrorCode};
 
 // Mining Protocol message types.
 pub const MESSAGE_TYPE_OPEN_STANDARD_MINING_CHANNEL: u8 = 0x10;
@@ -1,5 +1,5 @@
 use alloc::vec::Vec;
-use binary_sv2::{self, Deserialize, Seq0255, Serialize, Sv2Option, B064K, U256};
+use binary_sv2::{self, Deserialize, Seq0255, Serialize, Sv2Option, ValidateFields, B064K, U256};
 use core::{convert::TryInto, fmt};
 
 /// Message used by an upstream to provide an updated mining job to downstream.
@@ -9,7 +9,8 @@ use core::{convert::TryInto, fmt};
 /// Note that Standard Jobs distrbuted through this message are restricted to a fixed Merkle Root,
 /// and the only rollable bits are `version`, `nonce`, and `nTime` fields of the block header.
 #[allow(clippy::derive_partial_eq_without_eq)]
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NewMiningJob<'decoder> {
     /// Channel identifier for the channel that this job is valid for.
     ///
@@ -74,7 +75,8 @@ impl NewMiningJob<'_> {
 /// An Extended Job allows rolling Merkle Roots, giving extensive control over the search space so
 /// that they can implement various advanced use cases such as: translation between Stratum V1 and
 /// V2 protocols, difficulty aggregation and search space splitting.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NewExtendedMiningJob<'decoder> {
     /// Identifier of the Extended Mining Channel that this job is valid for.
     ///
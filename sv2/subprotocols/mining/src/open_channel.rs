@@ -1,12 +1,16 @@
-use alloc::{string::ToString, vec::Vec};
-use binary_sv2::{self, Deserialize, Serialize, Str0255, U32AsRef, B032, U256};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use binary_sv2::{self, Deserialize, Serialize, Str0255, U32AsRef, ValidateFields, B032, U256};
 use core::{convert::TryInto, fmt};
 /// Message used by a downstream to request opening a Standard Channel.
 ///
 /// Upon receiving `SetupConnectionSuccess` message, the downstream should open channel(s) on the
 /// connection within a reasonable period, otherwise the upstream should close the connection for
 /// inactivity.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OpenStandardMiningChannel<'decoder> {
     /// Specified by downstream role.
     ///
@@ -21,6 +25,7 @@ pub struct OpenStandardMiningChannel<'decoder> {
     ///
     /// Additional restrictions can be imposed by the upstream role (e.g. a pool). It is highly
     /// recommended to use UTF-8 encoding.
+    #[cfg_attr(feature = "serde", serde(with = "binary_sv2::serde_str"))]
     pub user_identity: Str0255<'decoder>,
     /// Expected hash rate of the device (or cumulative hashrate on the channel if multiple devices
     /// are connected downstream) in h/s.
@@ -66,7 +71,8 @@ impl OpenStandardMiningChannel<'_> {
 
 /// Message used by upstream to accept [`OpenStandardMiningChannel`] request from downstream.
 #[allow(clippy::derive_partial_eq_without_eq)]
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OpenStandardMiningChannelSuccess<'decoder> {
     /// Used for matching requests/responses.
     ///
@@ -123,7 +129,8 @@ impl OpenStandardMiningChannelSuccess<'_> {
 /// The main difference is the extranonce size is not fixed for a Extended Channel and can be set
 /// by the upstream role based on the [`OpenExtendedMiningChannel::min_extranonce_size`] requested
 /// by the downstream.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OpenExtendedMiningChannel<'decoder> {
     /// Specified by downstream role.
     ///
@@ -138,6 +145,7 @@ pub struct OpenExtendedMiningChannel<'decoder> {
     ///
     /// Additional restrictions can be imposed by the upstream role (e.g. a pool). It is highly
     /// recommended to use UTF-8 encoding.
+    #[cfg_attr(feature = "serde", serde(with = "binary_sv2::serde_str"))]
     pub user_identity: Str0255<'decoder>,
     /// Expected hash rate of the device (or cumulative hashrate on the channel if multiple devices
     /// are connected downstream) in h/s.
@@ -176,7 +184,8 @@ impl OpenExtendedMiningChannel<'_> {
 }
 
 /// Message used by upstream to accept [`OpenExtendedMiningChannel` request from downstream.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OpenExtendedMiningChannelSuccess<'decoder> {
     /// Used for matching requests/responses.
     ///
@@ -214,7 +223,8 @@ impl fmt::Display for OpenExtendedMiningChannelSuccess<'_> {
 
 /// Message used by upstream to reject [`OpenExtendedMiningChannel`] or
 /// [`OpenStandardMiningchannel`] request from downstream.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OpenMiningChannelError<'decoder> {
     /// Used for matching requests/responses.
     ///
@@ -227,6 +237,8 @@ pub struct OpenMiningChannelError<'decoder> {
     ///
     /// - ‘unknown-user’
     /// - ‘max-target-out-of-range’
+    /// - ‘unsupported-min-extranonce-size’
+    #[cfg_attr(feature = "serde", serde(with = "binary_sv2::serde_str"))]
     pub error_code: Str0255<'decoder>,
 }
 
@@ -242,6 +254,11 @@ impl fmt::Display for OpenMiningChannelError<'_> {
 }
 
 impl OpenMiningChannelError<'_> {
+    /// Returns the error code of the message, typed.
+    pub fn get_error_code(&self) -> OpenMiningChannelErrorCode {
+        OpenMiningChannelErrorCode::from(&self.error_code)
+    }
+
     pub fn new_max_target_out_of_range(request_id: u32) -> Self {
         Self {
             request_id,
@@ -265,6 +282,62 @@ impl OpenMiningChannelError<'_> {
     }
 }
 
+/// Error codes of [`OpenMiningChannelError`] defined by the spec.
+///
+/// Codes not defined by the spec are kept as [`OpenMiningChannelErrorCode::Unknown`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpenMiningChannelErrorCode {
+    /// `unknown-user`
+    UnknownUser,
+    /// `max-target-out-of-range`
+    MaxTargetOutOfRange,
+    /// `unsupported-min-extranonce-size`
+    UnsupportedMinExtranonceSize,
+    /// A code not defined by the spec.
+    Unknown(String),
+}
+
+impl fmt::Display for OpenMiningChannelErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpenMiningChannelErrorCode::UnknownUser => write!(f, "unknown-user"),
+            OpenMiningChannelErrorCode::MaxTargetOutOfRange => write!(f, "max-target-out-of-range"),
+            OpenMiningChannelErrorCode::UnsupportedMinExtranonceSize => {
+                write!(f, "unsupported-min-extranonce-size")
+            }
+            OpenMiningChannelErrorCode::Unknown(error_code) => write!(f, "{error_code}"),
+        }
+    }
+}
+
+impl From<&str> for OpenMiningChannelErrorCode {
+    fn from(error_code: &str) -> Self {
+        match error_code {
+            "unknown-user" => OpenMiningChannelErrorCode::UnknownUser,
+            "max-target-out-of-range" => OpenMiningChannelErrorCode::MaxTargetOutOfRange,
+            "unsupported-min-extranonce-size" => {
+                OpenMiningChannelErrorCode::UnsupportedMinExtranonceSize
+            }
+            _ => OpenMiningChannelErrorCode::Unknown(error_code.to_string()),
+        }
+    }
+}
+
+impl From<&Str0255<'_>> for OpenMiningChannelErrorCode {
+    fn from(error_code: &Str0255<'_>) -> Self {
+        OpenMiningChannelErrorCode::from(error_code.as_utf8_or_hex().as_str())
+    }
+}
+
+impl TryFrom<OpenMiningChannelErrorCode> for Str0255<'static> {
+    type Error = binary_sv2::Error;
+
+    /// Fails if the code is longer than 255 bytes.
+    fn try_from(error_code: OpenMiningChannelErrorCode) -> Result<Self, Self::Error> {
+        error_code.to_string().try_into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -345,6 +418,31 @@ mod tests {
         request_id == test_request_id_1
     }
 
+    // *** OPEN MINING CHANNEL ERROR ***
+    #[quickcheck_macros::quickcheck]
+    fn test_open_mining_channel_error_code_round_trip(error_code: String) -> bool {
+        OpenMiningChannelErrorCode::from(error_code.as_str()).to_string() == error_code
+    }
+
+    #[test]
+    fn test_open_mining_channel_error_code() {
+        let error = OpenMiningChannelError {
+            request_id: 1,
+            error_code: OpenMiningChannelErrorCode::MaxTargetOutOfRange
+                .try_into()
+                .unwrap(),
+        };
+        assert_eq!(error.error_code.as_utf8_or_hex(), "max-target-out-of-range");
+        assert_eq!(
+            error.get_error_code(),
+            OpenMiningChannelErrorCode::MaxTargetOutOfRange
+        );
+        assert_eq!(
+            OpenMiningChannelErrorCode::from("unknown-pool"),
+            OpenMiningChannelErrorCode::Unknown("unknown-pool".to_string())
+        );
+    }
+
     // *** HELPERS ***
     mod helpers {
         use super::*;
@@ -1,5 +1,11 @@
-use alloc::{fmt, vec::Vec};
-use binary_sv2::{self, Deserialize, Seq0255, Serialize, Str0255, B0255, B064K, U256};
+use alloc::{
+    fmt,
+    string::{String, ToString},
+    vec::Vec,
+};
+use binary_sv2::{
+    self, Deserialize, Seq0255, Serialize, Str0255, ValidateFields, B0255, B064K, U256,
+};
 use core::convert::TryInto;
 
 /// Message used by downstream role to set a custom job to an upstream (Pool).
@@ -12,7 +18,8 @@ use core::convert::TryInto;
 ///
 /// Previously exchanged `SetupConnection::flags` must contain `REQUIRES_WORK_SELECTION` flag i.e.,
 /// work selection feature was successfully negotiated.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetCustomMiningJob<'decoder> {
     /// Extended mining channel identifier.
     pub channel_id: u32,
@@ -76,7 +83,8 @@ impl fmt::Display for SetCustomMiningJob<'_> {
 ///
 /// Upon receiving this message, downstream can start submitting shares for this job immediately (by
 /// using the [`SetCustomMiningJobSuccess::job_id`] provided within this response).
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetCustomMiningJobSuccess {
     /// Extended mining channel identifier.
     pub channel_id: u32,
@@ -97,7 +105,8 @@ impl fmt::Display for SetCustomMiningJobSuccess {
 }
 
 /// Message used by upstream to reject [`SetCustomMiningJob`] request.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetCustomMiningJobError<'decoder> {
     /// Extended mining channel identifier.
     pub channel_id: u32,
@@ -109,6 +118,7 @@ pub struct SetCustomMiningJobError<'decoder> {
     /// - invalid-channel-id
     /// - invalid-mining-job-token
     /// - invalid-job-param-value-{field_name}
+    #[cfg_attr(feature = "serde", serde(with = "binary_sv2::serde_str"))]
     pub error_code: Str0255<'decoder>,
 }
 
@@ -123,3 +133,116 @@ impl fmt::Display for SetCustomMiningJobError<'_> {
         )
     }
 }
+
+impl SetCustomMiningJobError<'_> {
+    /// Returns the error code of the message, typed.
+    pub fn get_error_code(&self) -> SetCustomMiningJobErrorCode {
+        SetCustomMiningJobErrorCode::from(&self.error_code)
+    }
+}
+
+/// Error codes of [`SetCustomMiningJobError`] defined by the spec.
+///
+/// Codes not defined by the spec are kept as [`SetCustomMiningJobErrorCode::Unknown`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SetCustomMiningJobErrorCode {
+    /// `invalid-channel-id`
+    InvalidChannelId,
+    /// `invalid-mining-job-token`
+    InvalidMiningJobToken,
+    /// `invalid-job-param-value-{field_name}`, with the name of the offending field.
+    InvalidJobParamValue(String),
+    /// A code not defined by the spec.
+    Unknown(String),
+}
+
+impl fmt::Display for SetCustomMiningJobErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetCustomMiningJobErrorCode::InvalidChannelId => write!(f, "invalid-channel-id"),
+            SetCustomMiningJobErrorCode::InvalidMiningJobToken => {
+                write!(f, "invalid-mining-job-token")
+            }
+            SetCustomMiningJobErrorCode::InvalidJobParamValue(field) => {
+                write!(f, "invalid-job-param-value-{field}")
+            }
+            SetCustomMiningJobErrorCode::Unknown(error_code) => write!(f, "{error_code}"),
+        }
+    }
+}
+
+impl From<&str> for SetCustomMiningJobErrorCode {
+    fn from(error_code: &str) -> Self {
+        match error_code {
+            "invalid-channel-id" => SetCustomMiningJobErrorCode::InvalidChannelId,
+            "invalid-mining-job-token" => SetCustomMiningJobErrorCode::InvalidMiningJobToken,
+            _ => match error_code.strip_prefix("invalid-job-param-value-") {
+                Some(field) => SetCustomMiningJobErrorCode::InvalidJobParamValue(field.to_string()),
+                None => SetCustomMiningJobErrorCode::Unknown(error_code.to_string()),
+            },
+        }
+    }
+}
+
+impl From<&Str0255<'_>> for SetCustomMiningJobErrorCode {
+    fn from(error_code: &Str0255<'_>) -> Self {
+        SetCustomMiningJobErrorCode::from(error_code.as_utf8_or_hex().as_str())
+    }
+}
+
+impl TryFrom<SetCustomMiningJobErrorCode> for Str0255<'static> {
+    type Error = binary_sv2::Error;
+
+    /// Fails if the code is longer than 255 bytes.
+    fn try_from(error_code: SetCustomMiningJobErrorCode) -> Result<Self, Self::Error> {
+        error_code.to_string().try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[quickcheck_macros::quickcheck]
+    fn test_set_custom_mining_job_error_code_round_trip(error_code: String) -> bool {
+        SetCustomMiningJobErrorCode::from(error_code.as_str()).to_string() == error_code
+    }
+
+    #[test]
+    fn test_set_custom_mining_job_error_code() {
+        for error_code in [
+            SetCustomMiningJobErrorCode::InvalidChannelId,
+            SetCustomMiningJobErrorCode::InvalidMiningJobToken,
+            SetCustomMiningJobErrorCode::InvalidJobParamValue("coinbase_tx_version".to_string()),
+            SetCustomMiningJobErrorCode::Unknown("unknown-job".to_string()),
+        ] {
+            let error = SetCustomMiningJobError {
+                channel_id: 1,
+                request_id: 1,
+                error_code: error_code.clone().try_into().unwrap(),
+            };
+            assert_eq!(error.get_error_code(), error_code);
+        }
+    }
+
+    #[test]
+    fn test_set_custom_mining_job_error_code_invalid_job_param_value() {
+        assert_eq!(
+            SetCustomMiningJobErrorCode::from("invalid-job-param-value-merkle_path"),
+            SetCustomMiningJobErrorCode::InvalidJobParamValue("merkle_path".to_string())
+        );
+        // the field name is whatever follows the prefix, even if empty
+        assert_eq!(
+            SetCustomMiningJobErrorCode::from("invalid-job-param-value-"),
+            SetCustomMiningJobErrorCode::InvalidJobParamValue(String::new())
+        );
+        assert_eq!(
+            SetCustomMiningJobErrorCode::from("invalid-job-param-value"),
+            SetCustomMiningJobErrorCode::Unknown("invalid-job-param-value".to_string())
+        );
+        assert_eq!(
+            SetCustomMiningJobErrorCode::InvalidJobParamValue("version".to_string()).to_string(),
+            "invalid-job-param-value-version"
+        );
+    }
+}
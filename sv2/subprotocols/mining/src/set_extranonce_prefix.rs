@@ -1,6 +1,6 @@
 use alloc::{fmt, vec::Vec};
 
-use binary_sv2::{self, Deserialize, Serialize, B032};
+use binary_sv2::{self, Deserialize, Serialize, ValidateFields, B032};
 
 use core::convert::TryInto;
 
@@ -11,7 +11,8 @@ use core::convert::TryInto;
 ///
 /// Note that this message is applicable only for opened Standard or Extended Channels, not Group
 /// Channels.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetExtranoncePrefix<'decoder> {
     /// Extended or Standard Channel identifier.
     pub channel_id: u32,
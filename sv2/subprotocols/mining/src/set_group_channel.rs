@@ -1,5 +1,5 @@
 use alloc::{fmt, vec::Vec};
-use binary_sv2::{self, Deserialize, Seq064K, Serialize};
+use binary_sv2::{self, Deserialize, Seq064K, Serialize, ValidateFields};
 use core::convert::TryInto;
 
 /// Message used by upstream to associate a set of Standard Channel(s) to a Group Channel.
@@ -17,7 +17,8 @@ use core::convert::TryInto;
 ///
 /// This message can be sent only to connections that didnt set `REQUIRES_STANDARD_JOBS` flag in
 /// `SetupConnection` message.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetGroupChannel<'decoder> {
     /// Identifier of the group where the standard channel belongs.
     pub group_channel_id: u32,
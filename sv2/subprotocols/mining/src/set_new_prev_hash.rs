@@ -1,5 +1,5 @@
 use alloc::vec::Vec;
-use binary_sv2::{self, Deserialize, Serialize, U256};
+use binary_sv2::{self, Deserialize, Serialize, ValidateFields, U256};
 use core::{convert::TryInto, fmt};
 
 /// Message used by upstream to share or distribute the latest block hash.
@@ -10,7 +10,8 @@ use core::{convert::TryInto, fmt};
 ///
 /// When a downstream receives this message, only the job referenced by [`SetNewPrevHash::job_id`]
 /// is valid. Remaining jobs have to be dropped.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetNewPrevHash<'decoder> {
     /// Group channel or channel that this prevhash is valid for.
     pub channel_id: u32,
@@ -1,5 +1,5 @@
 use alloc::{fmt, vec::Vec};
-use binary_sv2::{self, Deserialize, Serialize, U256};
+use binary_sv2::{self, Deserialize, Serialize, ValidateFields, U256};
 use core::convert::TryInto;
 
 /// Message used by upstream to control the downstream submission rate by adjusting the difficulty
@@ -14,7 +14,8 @@ use core::convert::TryInto;
 ///
 /// When this message is sent to a group channel, the maximum target is applicable to all channels
 /// in the group.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetTarget<'decoder> {
     /// Channel identifier.
     pub channel_id: u32,
@@ -1,9 +1,14 @@
-use alloc::{fmt, vec::Vec};
-use binary_sv2::{self, Deserialize, Serialize, Str0255, B032};
+use alloc::{
+    fmt,
+    string::{String, ToString},
+    vec::Vec,
+};
+use binary_sv2::{self, Deserialize, Serialize, Str0255, ValidateFields, B032};
 use core::convert::TryInto;
 
 /// Message used by downstream to send result of its hashing work to an upstream.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SubmitSharesStandard {
     /// Channel identification.
     pub channel_id: u32,
@@ -42,7 +47,8 @@ impl fmt::Display for SubmitSharesStandard {
 /// [`SubmitSharesExtended::extranonce`].
 ///
 /// Only relevant for Extended Channels.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SubmitSharesExtended<'decoder> {
     /// Channel identification.
     pub channel_id: u32,
@@ -90,7 +96,8 @@ impl fmt::Display for SubmitSharesExtended<'_> {
 /// The upstream doesn’t have to double check that the sequence numbers sent by a downstream are
 /// actually increasing. It can use the last one received when sending a response. It is the
 /// downstream’s responsibility to keep the sequence numbers correct/useful.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SubmitSharesSuccess {
     /// Channel identifier.
     pub channel_id: u32,
@@ -117,7 +124,8 @@ impl fmt::Display for SubmitSharesSuccess {
 /// In case the upstream is not able to immediately validate the submission, the error is sent as
 /// soon as the result is known. This delayed validation can occur when a miner gets faster
 /// updates about a new `prevhash` than the upstream does.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SubmitSharesError<'decoder> {
     /// Channel identification.
     pub channel_id: u32,
@@ -131,6 +139,7 @@ pub struct SubmitSharesError<'decoder> {
     /// - stale-share
     /// - difficulty-too-low
     /// - invalid-job-id
+    #[cfg_attr(feature = "serde", serde(with = "binary_sv2::serde_str"))]
     pub error_code: Str0255<'decoder>,
 }
 
@@ -147,6 +156,11 @@ impl fmt::Display for SubmitSharesError<'_> {
 }
 
 impl SubmitSharesError<'_> {
+    /// Returns the error code of the message, typed.
+    pub fn get_error_code(&self) -> SubmitSharesErrorCode {
+        SubmitSharesErrorCode::from(&self.error_code)
+    }
+
     pub fn invalid_channel_error_code() -> &'static str {
         "invalid-channel-id"
     }
@@ -159,4 +173,127 @@ impl SubmitSharesError<'_> {
     pub fn invalid_job_id_error_code() -> &'static str {
         "invalid-job-id"
     }
+
+    /// Typed variant of [`SubmitSharesError::invalid_channel_error_code`].
+    pub fn invalid_channel_error_code_typed() -> SubmitSharesErrorCode {
+        SubmitSharesErrorCode::InvalidChannelId
+    }
+    /// Typed variant of [`SubmitSharesError::stale_share_error_code`].
+    pub fn stale_share_error_code_typed() -> SubmitSharesErrorCode {
+        SubmitSharesErrorCode::StaleShare
+    }
+    /// Typed variant of [`SubmitSharesError::difficulty_too_low_error_code`].
+    pub fn difficulty_too_low_error_code_typed() -> SubmitSharesErrorCode {
+        SubmitSharesErrorCode::DifficultyTooLow
+    }
+    /// Typed variant of [`SubmitSharesError::invalid_job_id_error_code`].
+    pub fn invalid_job_id_error_code_typed() -> SubmitSharesErrorCode {
+        SubmitSharesErrorCode::InvalidJobId
+    }
+}
+
+/// Error codes of [`SubmitSharesError`] defined by the spec.
+///
+/// Codes not defined by the spec are kept as [`SubmitSharesErrorCode::Unknown`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubmitSharesErrorCode {
+    /// `invalid-channel-id`
+    InvalidChannelId,
+    /// `stale-share`
+    StaleShare,
+    /// `difficulty-too-low`
+    DifficultyTooLow,
+    /// `invalid-job-id`
+    InvalidJobId,
+    /// A code not defined by the spec.
+    Unknown(String),
+}
+
+impl fmt::Display for SubmitSharesErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubmitSharesErrorCode::InvalidChannelId => write!(f, "invalid-channel-id"),
+            SubmitSharesErrorCode::StaleShare => write!(f, "stale-share"),
+            SubmitSharesErrorCode::DifficultyTooLow => write!(f, "difficulty-too-low"),
+            SubmitSharesErrorCode::InvalidJobId => write!(f, "invalid-job-id"),
+            SubmitSharesErrorCode::Unknown(error_code) => write!(f, "{error_code}"),
+        }
+    }
+}
+
+impl From<&str> for SubmitSharesErrorCode {
+    fn from(error_code: &str) -> Self {
+        match error_code {
+            "invalid-channel-id" => SubmitSharesErrorCode::InvalidChannelId,
+            "stale-share" => SubmitSharesErrorCode::StaleShare,
+            "difficulty-too-low" => SubmitSharesErrorCode::DifficultyTooLow,
+            "invalid-job-id" => SubmitSharesErrorCode::InvalidJobId,
+            _ => SubmitSharesErrorCode::Unknown(error_code.to_string()),
+        }
+    }
+}
+
+impl From<&Str0255<'_>> for SubmitSharesErrorCode {
+    fn from(error_code: &Str0255<'_>) -> Self {
+        SubmitSharesErrorCode::from(error_code.as_utf8_or_hex().as_str())
+    }
+}
+
+impl TryFrom<SubmitSharesErrorCode> for Str0255<'static> {
+    type Error = binary_sv2::Error;
+
+    /// Fails if the code is longer than 255 bytes.
+    fn try_from(error_code: SubmitSharesErrorCode) -> Result<Self, Self::Error> {
+        error_code.to_string().try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[quickcheck_macros::quickcheck]
+    fn test_submit_shares_error_code_round_trip(error_code: String) -> bool {
+        SubmitSharesErrorCode::from(error_code.as_str()).to_string() == error_code
+    }
+
+    #[test]
+    fn test_submit_shares_error_code() {
+        for error_code in [
+            SubmitSharesError::invalid_channel_error_code_typed(),
+            SubmitSharesError::stale_share_error_code_typed(),
+            SubmitSharesError::difficulty_too_low_error_code_typed(),
+            SubmitSharesError::invalid_job_id_error_code_typed(),
+            SubmitSharesErrorCode::Unknown("duplicate-share".to_string()),
+        ] {
+            let error = SubmitSharesError {
+                channel_id: 1,
+                sequence_number: 1,
+                error_code: error_code.clone().try_into().unwrap(),
+            };
+            assert_eq!(error.get_error_code(), error_code);
+        }
+        // the typed codes are encoded as the codes of the spec
+        for (error_code, typed) in [
+            (
+                SubmitSharesError::invalid_channel_error_code(),
+                SubmitSharesError::invalid_channel_error_code_typed(),
+            ),
+            (
+                SubmitSharesError::stale_share_error_code(),
+                SubmitSharesError::stale_share_error_code_typed(),
+            ),
+            (
+                SubmitSharesError::difficulty_too_low_error_code(),
+                SubmitSharesError::difficulty_too_low_error_code_typed(),
+            ),
+            (
+                SubmitSharesError::invalid_job_id_error_code(),
+                SubmitSharesError::invalid_job_id_error_code_typed(),
+            ),
+        ] {
+            assert_eq!(typed.to_string(), error_code);
+            assert_eq!(SubmitSharesErrorCode::from(error_code), typed);
+        }
+    }
 }
@@ -1,5 +1,9 @@
-use alloc::{fmt, vec::Vec};
-use binary_sv2::{self, Deserialize, Serialize, Str0255, U256};
+use alloc::{
+    fmt,
+    string::{String, ToString},
+    vec::Vec,
+};
+use binary_sv2::{self, Deserialize, Serialize, Str0255, ValidateFields, U256};
 use core::convert::TryInto;
 
 /// Message used by downstream to notify an upstream about changes on a specified channel.
@@ -8,7 +12,8 @@ use core::convert::TryInto;
 /// message when downstream channels change.
 ///
 /// Only relevant for Extended Channels.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UpdateChannel<'decoder> {
     /// Channel identification.
     pub channel_id: u32,
@@ -45,7 +50,8 @@ impl fmt::Display for UpdateChannel<'_> {
 }
 
 /// Message used by upstream to notify downstream about an error in the [`UpdateChannel`] message.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UpdateChannelError<'decoder> {
     /// Channel identification.
     pub channel_id: u32,
@@ -54,6 +60,7 @@ pub struct UpdateChannelError<'decoder> {
     /// Possible error codes:
     /// - max-target-out-of-range
     /// - invalid-channel-id
+    #[cfg_attr(feature = "serde", serde(with = "binary_sv2::serde_str"))]
     pub error_code: Str0255<'decoder>,
 }
 
@@ -67,3 +74,87 @@ impl fmt::Display for UpdateChannelError<'_> {
         )
     }
 }
+
+impl UpdateChannelError<'_> {
+    /// Returns the error code of the message, typed.
+    pub fn get_error_code(&self) -> UpdateChannelErrorCode {
+        UpdateChannelErrorCode::from(&self.error_code)
+    }
+}
+
+/// Error codes of [`UpdateChannelError`] defined by the spec.
+///
+/// Codes not defined by the spec are kept as [`UpdateChannelErrorCode::Unknown`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateChannelErrorCode {
+    /// `max-target-out-of-range`
+    MaxTargetOutOfRange,
+    /// `invalid-channel-id`
+    InvalidChannelId,
+    /// A code not defined by the spec.
+    Unknown(String),
+}
+
+impl fmt::Display for UpdateChannelErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpdateChannelErrorCode::MaxTargetOutOfRange => write!(f, "max-target-out-of-range"),
+            UpdateChannelErrorCode::InvalidChannelId => write!(f, "invalid-channel-id"),
+            UpdateChannelErrorCode::Unknown(error_code) => write!(f, "{error_code}"),
+        }
+    }
+}
+
+impl From<&str> for UpdateChannelErrorCode {
+    fn from(error_code: &str) -> Self {
+        match error_code {
+            "max-target-out-of-range" => UpdateChannelErrorCode::MaxTargetOutOfRange,
+            "invalid-channel-id" => UpdateChannelErrorCode::InvalidChannelId,
+            _ => UpdateChannelErrorCode::Unknown(error_code.to_string()),
+        }
+    }
+}
+
+impl From<&Str0255<'_>> for UpdateChannelErrorCode {
+    fn from(error_code: &Str0255<'_>) -> Self {
+        UpdateChannelErrorCode::from(error_code.as_utf8_or_hex().as_str())
+    }
+}
+
+impl TryFrom<UpdateChannelErrorCode> for Str0255<'static> {
+    type Error = binary_sv2::Error;
+
+    /// Fails if the code is longer than 255 bytes.
+    fn try_from(error_code: UpdateChannelErrorCode) -> Result<Self, Self::Error> {
+        error_code.to_string().try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[quickcheck_macros::quickcheck]
+    fn test_update_channel_error_code_round_trip(error_code: String) -> bool {
+        UpdateChannelErrorCode::from(error_code.as_str()).to_string() == error_code
+    }
+
+    #[test]
+    fn test_update_channel_error_code() {
+        for error_code in [
+            UpdateChannelErrorCode::MaxTargetOutOfRange,
+            UpdateChannelErrorCode::InvalidChannelId,
+            UpdateChannelErrorCode::Unknown("unknown-channel".to_string()),
+        ] {
+            let error = UpdateChannelError {
+                channel_id: 1,
+                error_code: error_code.clone().try_into().unwrap(),
+            };
+            assert_eq!(error.get_error_code(), error_code);
+        }
+        assert_eq!(
+            UpdateChannelErrorCode::from("max-target-out-of-range"),
+            UpdateChannelErrorCode::MaxTargetOutOfRange
+        );
+    }
+}
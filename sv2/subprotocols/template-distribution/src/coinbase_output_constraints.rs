@@ -1,5 +1,5 @@
 use alloc::{fmt, vec::Vec};
-use binary_sv2::{self, Deserialize, Serialize};
+use binary_sv2::{self, Deserialize, Serialize, ValidateFields};
 use core::convert::TryInto;
 
 /// Message used by a downstream to indicate the size of the additional bytes they will need in
@@ -22,7 +22,8 @@ use core::convert::TryInto;
 /// transaction when complying with the size limits.
 ///
 /// [`NewTemplate`]: crate::NewTemplate
-#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Serialize, Deserialize, ValidateFields, Copy, Clone, Debug, PartialEq, Eq)]
 
 pub struct CoinbaseOutputConstraints {
     /// Additional serialized bytes needed in coinbase transaction outputs.
@@ -33,7 +33,8 @@ mod submit_solution;
 pub use coinbase_output_constraints::CoinbaseOutputConstraints;
 pub use new_template::NewTemplate;
 pub use request_transaction_data::{
-    RequestTransactionData, RequestTransactionDataError, RequestTransactionDataSuccess,
+    RequestTransactionData, RequestTransactionDataError, RequestTransactionDataErrorCode,
+    RequestTransactionDataSuccess,
 };
 pub use set_new_prev_hash::SetNewPrevHash;
 pub use submit_solution::SubmitSolution;
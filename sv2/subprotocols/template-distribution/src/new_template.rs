@@ -1,10 +1,11 @@
 use alloc::{fmt, vec::Vec};
-use binary_sv2::{self, Deserialize, Seq0255, Serialize, B0255, B064K, U256};
+use binary_sv2::{self, Deserialize, Seq0255, Serialize, ValidateFields, B0255, B064K, U256};
 use core::convert::TryInto;
 
 /// Message used by an upstream(Template Provider) to provide a new template for downstream to mine
 /// on.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NewTemplate<'decoder> {
     /// Upstream’s identification of the template.
     ///
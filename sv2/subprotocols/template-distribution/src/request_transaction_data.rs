@@ -1,5 +1,9 @@
-use alloc::{fmt, vec::Vec};
-use binary_sv2::{self, Deserialize, Seq064K, Serialize, Str0255, B016M, B064K};
+use alloc::{
+    fmt,
+    string::{String, ToString},
+    vec::Vec,
+};
+use binary_sv2::{self, Deserialize, Seq064K, Serialize, Str0255, ValidateFields, B016M, B064K};
 use core::convert::TryInto;
 
 /// Message used by a downstream to request data about all transactions in a block template.
@@ -7,7 +11,8 @@ use core::convert::TryInto;
 /// Data includes the full transaction data and any additional data required to block validation.
 ///
 /// Note that the coinbase transaction is excluded from this data.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq, Eq, Copy)]
 
 pub struct RequestTransactionData {
     /// Identifier of the template that the downstream node is requesting transaction data for.
@@ -59,7 +64,8 @@ impl fmt::Display for RequestTransactionData {
 /// changed in a non-compatible way at the time of fork activation, given sufficient time from
 /// code-release to activation and there being in protocol(Template Declaration) signaling of
 /// support for the new fork (e.g. for soft-forks activated using [BIP 9]).
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RequestTransactionDataSuccess<'decoder> {
     /// The template_id corresponding to a NewTemplate/RequestTransactionData message.
     pub template_id: u64,
@@ -81,7 +87,8 @@ impl fmt::Display for RequestTransactionDataSuccess<'_> {
 
 /// Message used by an upstream(Template Provider) to respond with an error to a
 /// [`RequestTransactionData`] message.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RequestTransactionDataError<'decoder> {
     /// Identifier of the template that the downstream node is requesting transaction data for.
     pub template_id: u64,
@@ -89,6 +96,8 @@ pub struct RequestTransactionDataError<'decoder> {
     ///
     /// Possible error codes:
     /// - template-id-not-found
+    /// - stale-template-id
+    #[cfg_attr(feature = "serde", serde(with = "binary_sv2::serde_str"))]
     pub error_code: Str0255<'decoder>,
 }
 
@@ -102,3 +111,84 @@ impl fmt::Display for RequestTransactionDataError<'_> {
         )
     }
 }
+
+impl RequestTransactionDataError<'_> {
+    /// Returns the error code of the message, typed.
+    pub fn get_error_code(&self) -> RequestTransactionDataErrorCode {
+        RequestTransactionDataErrorCode::from(&self.error_code)
+    }
+}
+
+/// Error codes of [`RequestTransactionDataError`] defined by the spec.
+///
+/// Codes not defined by the spec are kept as [`RequestTransactionDataErrorCode::Unknown`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestTransactionDataErrorCode {
+    /// `template-id-not-found`
+    TemplateIdNotFound,
+    /// `stale-template-id`
+    StaleTemplateId,
+    /// A code not defined by the spec.
+    Unknown(String),
+}
+
+impl fmt::Display for RequestTransactionDataErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestTransactionDataErrorCode::TemplateIdNotFound => {
+                write!(f, "template-id-not-found")
+            }
+            RequestTransactionDataErrorCode::StaleTemplateId => write!(f, "stale-template-id"),
+            RequestTransactionDataErrorCode::Unknown(error_code) => write!(f, "{error_code}"),
+        }
+    }
+}
+
+impl From<&str> for RequestTransactionDataErrorCode {
+    fn from(error_code: &str) -> Self {
+        match error_code {
+            "template-id-not-found" => RequestTransactionDataErrorCode::TemplateIdNotFound,
+            "stale-template-id" => RequestTransactionDataErrorCode::StaleTemplateId,
+            _ => RequestTransactionDataErrorCode::Unknown(error_code.to_string()),
+        }
+    }
+}
+
+impl From<&Str0255<'_>> for RequestTransactionDataErrorCode {
+    fn from(error_code: &Str0255<'_>) -> Self {
+        RequestTransactionDataErrorCode::from(error_code.as_utf8_or_hex().as_str())
+    }
+}
+
+impl TryFrom<RequestTransactionDataErrorCode> for Str0255<'static> {
+    type Error = binary_sv2::Error;
+
+    /// Fails if the code is longer than 255 bytes.
+    fn try_from(error_code: RequestTransactionDataErrorCode) -> Result<Self, Self::Error> {
+        error_code.to_string().try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_transaction_data_error_code_round_trip() {
+        for error_code in [
+            RequestTransactionDataErrorCode::TemplateIdNotFound,
+            RequestTransactionDataErrorCode::StaleTemplateId,
+            RequestTransactionDataErrorCode::Unknown("unknown-template".to_string()),
+        ] {
+            let error = RequestTransactionDataError {
+                template_id: 1,
+                error_code: error_code.clone().try_into().unwrap(),
+            };
+            assert_eq!(error.get_error_code(), error_code);
+            assert_eq!(
+                RequestTransactionDataErrorCode::from(error_code.to_string().as_str()),
+                error_code
+            );
+        }
+    }
+}
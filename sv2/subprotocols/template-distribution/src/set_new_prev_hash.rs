@@ -1,5 +1,5 @@
 use alloc::vec::Vec;
-use binary_sv2::{self, Deserialize, Serialize, U256};
+use binary_sv2::{self, Deserialize, Serialize, ValidateFields, U256};
 use core::{convert::TryInto, fmt};
 
 /// Message used by an upstream(Template Provider) to indicate the latest block header hash
@@ -10,7 +10,8 @@ use core::{convert::TryInto, fmt};
 /// If a [`crate::NewTemplate`] message has previously been sent with the
 /// [`crate::NewTemplate::future_template`] flag set, the [`SetNewPrevHash::template_id`] field
 /// **should** be set to the [`crate::NewTemplate::template_id`].
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetNewPrevHash<'decoder> {
     /// Identifier of the template to mine on.
     ///
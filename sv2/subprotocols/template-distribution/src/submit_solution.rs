@@ -1,5 +1,5 @@
 use alloc::{fmt, vec::Vec};
-use binary_sv2::{self, Deserialize, Serialize, B064K};
+use binary_sv2::{self, Deserialize, Serialize, ValidateFields, B064K};
 use core::convert::TryInto;
 
 /// Message used by a downstream to submit a successful solution to a previously provided template.
@@ -10,7 +10,8 @@ use core::convert::TryInto;
 ///
 /// Upon receiving this message, upstream(Template Provider) **must** immediately construct the
 /// corresponding full block and attempt to propagate it to the Bitcoin network.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, ValidateFields, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SubmitSolution<'decoder> {
     /// Identifies the template to which this solution corresponds.
     ///